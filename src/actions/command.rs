@@ -136,6 +136,18 @@ pub enum Command {
     /// history management
     History(HistoryCommand),
 
+    // ==================== Remote Commands ====================
+    /// pairing and known hosts for remote control (CLI only)
+    Remote(RemoteCommand),
+
+    // ==================== Profile Commands ====================
+    /// switch between named config profiles
+    Profile(ProfileCommand),
+
+    // ==================== Shortcuts Commands ====================
+    /// share shortcuts with other tools
+    Shortcuts(ShortcutsCommand),
+
     // ==================== Install Commands ====================
     /// install cwm to system PATH
     Install {
@@ -218,28 +230,85 @@ pub enum DaemonCommand {
     },
     /// uninstall daemon from login items
     Uninstall,
+    /// show daemon metrics
+    Stats {
+        /// return Prometheus text instead of JSON
+        prometheus: bool,
+    },
 }
 
 /// config subcommands
 #[derive(Debug, Clone)]
 pub enum ConfigCommand {
     /// show current configuration
-    Show,
+    Show {
+        /// merge in included files
+        resolved: bool,
+        /// merge in included files and annotate where each entry came from
+        origin: bool,
+    },
     /// show configuration file path
     Path,
+    /// read a configuration value
+    Get {
+        /// configuration path (e.g. settings.animate, shortcuts[0].keys)
+        key: String,
+    },
     /// set a configuration value
     Set {
-        /// configuration key
+        /// configuration path (e.g. settings.animate, app_rules[app=Slack].delay_ms)
         key: String,
         /// value to set
         value: String,
     },
+    /// remove a configuration value, restoring its default
+    Unset {
+        /// configuration path
+        key: String,
+    },
+    /// append a shortcut
+    AddShortcut {
+        keys: String,
+        action: String,
+        app: Option<String>,
+        launch: Option<bool>,
+        /// condition as JSON5
+        when: Option<String>,
+    },
+    /// append an app rule
+    AddRule {
+        app: String,
+        action: String,
+        delay_ms: Option<u64>,
+        /// condition as JSON5
+        when: Option<String>,
+    },
     /// reset configuration to defaults
     Reset,
     /// show the default configuration with examples
     Default,
     /// verify configuration file for errors
     Verify,
+    /// report conflicts, shadowed and unused entries at or above `severity`
+    Lint {
+        severity: crate::config::lint::Severity,
+    },
+    /// upgrade the config file to the current format version
+    Migrate { dry_run: bool },
+    /// merge shortcuts exported from another window manager into the config
+    Import {
+        from: crate::config::import::Source,
+        /// exported config file of the other window manager
+        file: String,
+        dry_run: bool,
+    },
+    /// render the config file in another format, replacing it when `write` is set
+    Convert {
+        to: crate::config::format::Format,
+        write: bool,
+    },
+    /// print the config JSON schema
+    Schema,
 }
 
 /// spotlight subcommands
@@ -298,6 +367,64 @@ pub enum HistoryCommand {
     Clear,
 }
 
+/// profile subcommands
+#[derive(Debug, Clone)]
+pub enum ProfileCommand {
+    /// activate a profile
+    Use {
+        /// profile name
+        name: String,
+    },
+    /// go back to the base config
+    Clear,
+    /// list profiles
+    List,
+}
+
+/// shortcuts subcommands
+#[derive(Debug, Clone)]
+pub enum ShortcutsCommand {
+    /// render the shortcuts for another hotkey tool or as a cheat sheet
+    Export {
+        /// output format
+        format: crate::config::export::Target,
+    },
+    /// hotkeys already used by macOS, common apps or other shortcuts
+    Conflicts,
+}
+
+/// remote subcommands
+#[derive(Debug, Clone)]
+pub enum RemoteCommand {
+    /// create a one-time pairing code on this machine (asks the local daemon)
+    Pair,
+    /// pair with a remote host and remember it under `name`
+    Add {
+        /// local name for the host (used with --host)
+        name: String,
+        /// hostname or IP address
+        address: String,
+        /// TCP port of the remote listener
+        port: u16,
+        /// one-time code printed by `cwm remote pair` on the host
+        code: String,
+        /// expected certificate fingerprint (as printed by `cwm remote pair`)
+        fingerprint: String,
+    },
+    /// list known hosts and paired clients
+    List,
+    /// forget a known host
+    Remove {
+        /// host name given to `remote add`
+        name: String,
+    },
+    /// revoke a paired client so it can no longer control this machine
+    Revoke {
+        /// client name or fingerprint (at least 8 characters)
+        client: String,
+    },
+}
+
 /// record subcommands
 /// note: these variants exist for IPC rejection and is_interactive checks
 /// the actual implementation is in CLI handlers
@@ -370,6 +497,9 @@ impl Command {
             Command::Redo => "redo",
             Command::History(HistoryCommand::List) => "history_list",
             Command::History(HistoryCommand::Clear) => "history_clear",
+            Command::Remote(_) => "remote",
+            Command::Profile(_) => "profile",
+            Command::Shortcuts(_) => "shortcuts",
             Command::Install { .. } => "install",
             Command::Uninstall { .. } => "uninstall",
             Command::Update { .. } => "update",
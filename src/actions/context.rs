@@ -4,6 +4,8 @@ use std::path::{Path, PathBuf};
 
 use crate::config::Config;
 
+use super::control::RequestControl;
+
 /// context passed to action handlers
 pub struct ExecutionContext<'a> {
     /// configuration
@@ -14,6 +16,10 @@ pub struct ExecutionContext<'a> {
     pub is_cli: bool,
     /// config file path override (for --config flag)
     pub config_path: Option<PathBuf>,
+    /// cancellation, deadline and progress for the current IPC request
+    pub control: Option<&'a RequestControl>,
+    /// paired remote host to run the command on (`--host`)
+    pub remote_host: Option<String>,
 }
 
 impl<'a> ExecutionContext<'a> {
@@ -24,6 +30,8 @@ impl<'a> ExecutionContext<'a> {
             verbose,
             is_cli: false,
             config_path: None,
+            control: None,
+            remote_host: None,
         }
     }
 
@@ -34,6 +42,8 @@ impl<'a> ExecutionContext<'a> {
             verbose,
             is_cli,
             config_path: None,
+            control: None,
+            remote_host: None,
         }
     }
 
@@ -48,9 +58,23 @@ impl<'a> ExecutionContext<'a> {
             verbose,
             is_cli: true,
             config_path: config_path.map(|p| p.to_path_buf()),
+            control: None,
+            remote_host: None,
         }
     }
 
+    /// attach request control (used by the daemon for IPC requests)
+    pub fn with_control(mut self, control: &'a RequestControl) -> Self {
+        self.control = Some(control);
+        self
+    }
+
+    /// run commands on a paired remote host instead of this machine
+    pub fn with_remote_host(mut self, host: Option<String>) -> Self {
+        self.remote_host = host;
+        self
+    }
+
     /// get config path override as Option<&Path>
     pub fn config_path_override(&self) -> Option<&Path> {
         self.config_path.as_deref()
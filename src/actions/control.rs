@@ -0,0 +1,279 @@
+//! cancellation, deadlines and progress reporting for long-running actions
+//!
+//! the daemon attaches a `RequestControl` to the execution context of each IPC
+//! request. handlers that block (waiting for an app to terminate, retrying until
+//! a window appears) poll it between steps and report progress through it
+
+use serde::Serialize;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+
+use crate::cli::exit_codes;
+
+use super::error::ActionError;
+
+/// how often interruptible sleeps wake up to check for cancellation
+const POLL_INTERVAL: Duration = Duration::from_millis(25);
+
+/// reason a long-running action stopped early
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Interrupted {
+    /// client sent `$/cancelRequest` for this request
+    Cancelled,
+    /// the request's deadline passed
+    DeadlineExceeded,
+}
+
+impl Interrupted {
+    pub fn code(&self) -> i32 {
+        match self {
+            Self::Cancelled => exit_codes::CANCELLED,
+            Self::DeadlineExceeded => exit_codes::TIMEOUT,
+        }
+    }
+
+    pub fn message(&self) -> &'static str {
+        match self {
+            Self::Cancelled => "request cancelled",
+            Self::DeadlineExceeded => "request deadline exceeded",
+        }
+    }
+}
+
+impl std::fmt::Display for Interrupted {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.message())
+    }
+}
+
+impl std::error::Error for Interrupted {}
+
+impl From<Interrupted> for ActionError {
+    fn from(reason: Interrupted) -> Self {
+        ActionError::new(reason.code(), reason.message())
+    }
+}
+
+/// a progress update for a long-running action
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Progress {
+    pub message: String,
+    /// current step (1-based), if the operation is counted
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub current: Option<u32>,
+    /// total number of steps, if known
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub total: Option<u32>,
+}
+
+impl Progress {
+    pub fn message(message: impl Into<String>) -> Self {
+        Self {
+            message: message.into(),
+            current: None,
+            total: None,
+        }
+    }
+
+    pub fn step(message: impl Into<String>, current: u32, total: u32) -> Self {
+        Self {
+            message: message.into(),
+            current: Some(current),
+            total: Some(total),
+        }
+    }
+}
+
+type ProgressSink = Box<dyn Fn(&Progress) + Send + Sync>;
+
+/// cancellation flag, optional deadline and progress sink for one request
+#[derive(Default)]
+pub struct RequestControl {
+    cancelled: AtomicBool,
+    deadline: Option<Instant>,
+    progress: Option<ProgressSink>,
+}
+
+impl RequestControl {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// stop the request once `timeout` has elapsed from now
+    pub fn with_deadline(mut self, timeout: Duration) -> Self {
+        self.deadline = Some(Instant::now() + timeout);
+        self
+    }
+
+    /// send progress updates to `sink`
+    pub fn with_progress(mut self, sink: impl Fn(&Progress) + Send + Sync + 'static) -> Self {
+        self.progress = Some(Box::new(sink));
+        self
+    }
+
+    /// mark the request as cancelled
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::SeqCst);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::SeqCst)
+    }
+
+    /// time left before the deadline, if one is set
+    pub fn remaining(&self) -> Option<Duration> {
+        self.deadline
+            .map(|d| d.saturating_duration_since(Instant::now()))
+    }
+
+    /// return an error if the request was cancelled or its deadline passed
+    pub fn check(&self) -> Result<(), Interrupted> {
+        if self.is_cancelled() {
+            return Err(Interrupted::Cancelled);
+        }
+        if self.remaining() == Some(Duration::ZERO) {
+            return Err(Interrupted::DeadlineExceeded);
+        }
+        Ok(())
+    }
+
+    pub fn is_interrupted(&self) -> bool {
+        self.check().is_err()
+    }
+
+    /// report progress to the client (no-op without a sink)
+    pub fn report(&self, progress: Progress) {
+        if let Some(sink) = &self.progress {
+            sink(&progress);
+        }
+    }
+
+    /// sleep for `duration`, waking early if the request is interrupted
+    pub fn sleep(&self, duration: Duration) -> Result<(), Interrupted> {
+        let end = Instant::now() + duration;
+
+        loop {
+            self.check()?;
+
+            let now = Instant::now();
+            if now >= end {
+                return Ok(());
+            }
+
+            std::thread::sleep(POLL_INTERVAL.min(end - now));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+
+    #[test]
+    fn test_new_control_not_interrupted() {
+        let control = RequestControl::new();
+        assert!(control.check().is_ok());
+        assert!(control.remaining().is_none());
+    }
+
+    #[test]
+    fn test_cancel() {
+        let control = RequestControl::new();
+        control.cancel();
+        assert!(control.is_cancelled());
+        assert_eq!(control.check(), Err(Interrupted::Cancelled));
+    }
+
+    #[test]
+    fn test_deadline_exceeded() {
+        let control = RequestControl::new().with_deadline(Duration::ZERO);
+        assert_eq!(control.check(), Err(Interrupted::DeadlineExceeded));
+    }
+
+    #[test]
+    fn test_deadline_not_yet_exceeded() {
+        let control = RequestControl::new().with_deadline(Duration::from_secs(60));
+        assert!(control.check().is_ok());
+        assert!(control.remaining().unwrap() > Duration::from_secs(50));
+    }
+
+    #[test]
+    fn test_cancel_wins_over_deadline() {
+        let control = RequestControl::new().with_deadline(Duration::ZERO);
+        control.cancel();
+        assert_eq!(control.check(), Err(Interrupted::Cancelled));
+    }
+
+    #[test]
+    fn test_sleep_completes() {
+        let control = RequestControl::new();
+        assert!(control.sleep(Duration::from_millis(10)).is_ok());
+    }
+
+    #[test]
+    fn test_sleep_stops_at_deadline() {
+        let control = RequestControl::new().with_deadline(Duration::from_millis(30));
+        let start = Instant::now();
+        assert_eq!(
+            control.sleep(Duration::from_secs(5)),
+            Err(Interrupted::DeadlineExceeded)
+        );
+        assert!(start.elapsed() < Duration::from_secs(1));
+    }
+
+    #[test]
+    fn test_sleep_stops_on_cancel() {
+        let control = Arc::new(RequestControl::new());
+        let canceller = Arc::clone(&control);
+        let handle = std::thread::spawn(move || {
+            std::thread::sleep(Duration::from_millis(30));
+            canceller.cancel();
+        });
+
+        let start = Instant::now();
+        assert_eq!(
+            control.sleep(Duration::from_secs(5)),
+            Err(Interrupted::Cancelled)
+        );
+        assert!(start.elapsed() < Duration::from_secs(1));
+        handle.join().unwrap();
+    }
+
+    #[test]
+    fn test_report_progress() {
+        let received = Arc::new(Mutex::new(Vec::new()));
+        let sink = Arc::clone(&received);
+        let control =
+            RequestControl::new().with_progress(move |p| sink.lock().unwrap().push(p.clone()));
+
+        control.report(Progress::step("retrying", 1, 3));
+        control.report(Progress::message("waiting"));
+
+        let received = received.lock().unwrap();
+        assert_eq!(received.len(), 2);
+        assert_eq!(received[0].current, Some(1));
+        assert_eq!(received[0].total, Some(3));
+        assert_eq!(received[1].message, "waiting");
+    }
+
+    #[test]
+    fn test_report_without_sink_is_noop() {
+        RequestControl::new().report(Progress::message("ignored"));
+    }
+
+    #[test]
+    fn test_progress_serialization_skips_missing_counts() {
+        let json = serde_json::to_value(Progress::message("waiting")).unwrap();
+        assert_eq!(json, serde_json::json!({"message": "waiting"}));
+    }
+
+    #[test]
+    fn test_interrupted_to_action_error() {
+        let err: ActionError = Interrupted::Cancelled.into();
+        assert_eq!(err.code, exit_codes::CANCELLED);
+
+        let err: ActionError = Interrupted::DeadlineExceeded.into();
+        assert_eq!(err.code, exit_codes::TIMEOUT);
+    }
+}
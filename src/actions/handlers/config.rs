@@ -5,12 +5,35 @@ use std::path::Path;
 use crate::actions::context::ExecutionContext;
 use crate::actions::error::ActionError;
 use crate::actions::result::ActionResult;
+use crate::config::format::Format;
+use crate::config::import::{self, Source};
+use crate::config::lint::Severity;
 use crate::config::{self, Config};
 
 /// execute config show action
-pub fn execute_show(ctx: &ExecutionContext) -> Result<ActionResult, ActionError> {
-    // serialize the current config to JSON
-    let config_json = serde_json::to_value(ctx.config.clone()).map_err(|e| {
+/// without flags only the main config file is shown; `resolved` merges in
+/// included files and `origin` also annotates where each entry came from
+pub fn execute_show(
+    resolved: bool,
+    origin: bool,
+    config_override: Option<&Path>,
+    ctx: &ExecutionContext,
+) -> Result<ActionResult, ActionError> {
+    if resolved || origin {
+        let resolved =
+            config::load_resolved_with_override(config_override).map_err(ActionError::from)?;
+        let config_json = if origin {
+            config::annotate_origins(&resolved.value, &resolved.origins)
+        } else {
+            resolved.value
+        };
+        return Ok(ActionResult::simple("config_show", config_json));
+    }
+
+    // serialize the main config file to JSON
+    let main =
+        config::load_main_with_override(config_override).unwrap_or_else(|_| ctx.config.clone());
+    let config_json = serde_json::to_value(main).map_err(|e| {
         ActionError::new(
             crate::cli::exit_codes::ERROR,
             format!("failed to serialize config: {}", e),
@@ -46,25 +69,75 @@ pub fn execute_verify(config_override: Option<&Path>) -> Result<ActionResult, Ac
         )
     })?;
 
-    let errors = config::verify(&path).map_err(|e| {
+    let verification = config::verify(&path).map_err(|e| {
         ActionError::new(
             crate::cli::exit_codes::ERROR,
             format!("failed to verify config: {}", e),
         )
     })?;
 
-    let valid = errors.is_empty();
+    let valid = verification.errors.is_empty();
 
     Ok(ActionResult::simple(
         "config_verify",
         serde_json::json!({
             "valid": valid,
-            "errors": errors,
+            "errors": verification.errors,
+            "warnings": verification.warnings,
+            "path": path.to_string_lossy(),
+        }),
+    ))
+}
+
+/// execute config lint action
+/// displays are only checked when they can be listed
+pub fn execute_lint(
+    severity: Severity,
+    config_override: Option<&Path>,
+) -> Result<ActionResult, ActionError> {
+    let path = config::get_config_path_with_override(config_override).map_err(|e| {
+        ActionError::new(
+            crate::cli::exit_codes::ERROR,
+            format!("failed to get config path: {}", e),
+        )
+    })?;
+
+    let displays = crate::display::get_displays().ok();
+    let findings: Vec<_> = config::lint(&path, displays.as_deref())
+        .map_err(|e| {
+            ActionError::new(
+                crate::cli::exit_codes::ERROR,
+                format!("failed to lint config: {}", e),
+            )
+        })?
+        .into_iter()
+        .filter(|f| f.severity >= severity)
+        .collect();
+
+    let count = |s: Severity| findings.iter().filter(|f| f.severity == s).count();
+
+    Ok(ActionResult::simple(
+        "config_lint",
+        serde_json::json!({
             "path": path.to_string_lossy(),
+            "counts": {
+                "error": count(Severity::Error),
+                "warning": count(Severity::Warning),
+                "info": count(Severity::Info),
+            },
+            "findings": findings,
         }),
     ))
 }
 
+/// execute config schema action (the JSON schema generated from the config types)
+pub fn execute_schema() -> Result<ActionResult, ActionError> {
+    Ok(ActionResult::simple(
+        "config_schema",
+        config::generate_schema(),
+    ))
+}
+
 /// execute config default action (show default config with examples)
 pub fn execute_default(_ctx: &ExecutionContext) -> Result<ActionResult, ActionError> {
     let default_config = config::default_with_examples();
@@ -91,7 +164,7 @@ pub fn execute_set(
         ));
     }
 
-    let mut cfg = config::load_with_override(config_override).map_err(ActionError::from)?;
+    let mut cfg = config::load_main_with_override(config_override).map_err(ActionError::from)?;
     config::set_value(&mut cfg, key, value).map_err(ActionError::from)?;
     config::save_with_override(&cfg, config_override).map_err(ActionError::from)?;
 
@@ -104,6 +177,125 @@ pub fn execute_set(
     ))
 }
 
+/// config get - reads a value from the main config file
+pub fn execute_get(
+    key: &str,
+    config_override: Option<&Path>,
+    ctx: &ExecutionContext,
+) -> Result<ActionResult, ActionError> {
+    let main =
+        config::load_main_with_override(config_override).unwrap_or_else(|_| ctx.config.clone());
+    let value = config::get_value(&main, key).map_err(ActionError::from)?;
+
+    Ok(ActionResult::simple(
+        "config_get",
+        serde_json::json!({
+            "key": key,
+            "value": value,
+        }),
+    ))
+}
+
+/// config unset - requires CLI (modifies files)
+pub fn execute_unset(
+    key: &str,
+    config_override: Option<&Path>,
+    ctx: &ExecutionContext,
+) -> Result<ActionResult, ActionError> {
+    if !ctx.is_cli {
+        return Err(ActionError::not_supported(
+            "config unset is only available via CLI",
+        ));
+    }
+
+    let mut cfg = config::load_main_with_override(config_override).map_err(ActionError::from)?;
+    config::unset_value(&mut cfg, key).map_err(ActionError::from)?;
+    config::save_with_override(&cfg, config_override).map_err(ActionError::from)?;
+
+    Ok(ActionResult::simple(
+        "config_unset",
+        serde_json::json!({
+            "key": key,
+        }),
+    ))
+}
+
+/// config add shortcut - requires CLI (modifies files)
+pub fn execute_add_shortcut(
+    keys: &str,
+    action: &str,
+    app: Option<&str>,
+    launch: Option<bool>,
+    when: Option<&str>,
+    config_override: Option<&Path>,
+    ctx: &ExecutionContext,
+) -> Result<ActionResult, ActionError> {
+    let mut entry = serde_json::json!({ "keys": keys, "action": action });
+    if let Some(app) = app {
+        entry["app"] = app.into();
+    }
+    if let Some(launch) = launch {
+        entry["launch"] = launch.into();
+    }
+
+    execute_add("shortcuts", entry, when, config_override, ctx)
+}
+
+/// config add rule - requires CLI (modifies files)
+pub fn execute_add_rule(
+    app: &str,
+    action: &str,
+    delay_ms: Option<u64>,
+    when: Option<&str>,
+    config_override: Option<&Path>,
+    ctx: &ExecutionContext,
+) -> Result<ActionResult, ActionError> {
+    let mut entry = serde_json::json!({ "app": app, "action": action });
+    if let Some(delay_ms) = delay_ms {
+        entry["delay_ms"] = delay_ms.into();
+    }
+
+    execute_add("app_rules", entry, when, config_override, ctx)
+}
+
+/// appends `entry` to the `list` array, validating it like `config set list[+]`
+fn execute_add(
+    list: &str,
+    mut entry: serde_json::Value,
+    when: Option<&str>,
+    config_override: Option<&Path>,
+    ctx: &ExecutionContext,
+) -> Result<ActionResult, ActionError> {
+    if !ctx.is_cli {
+        return Err(ActionError::not_supported(
+            "config add is only available via CLI",
+        ));
+    }
+
+    if let Some(when) = when {
+        entry["when"] = json5::from_str(when)
+            .map_err(|e| ActionError::invalid_args(format!("invalid --when condition: {}", e)))?;
+    }
+
+    let mut cfg = config::load_main_with_override(config_override).map_err(ActionError::from)?;
+    config::set_json_value(&mut cfg, &format!("{}[+]", list), entry.clone())
+        .map_err(ActionError::from)?;
+    config::save_with_override(&cfg, config_override).map_err(ActionError::from)?;
+
+    let index = config::get_value(&cfg, list)
+        .ok()
+        .and_then(|v| v.as_array().map(|a| a.len().saturating_sub(1)));
+
+    Ok(ActionResult::simple(
+        "config_add",
+        serde_json::json!({
+            "list": list,
+            "index": index,
+            "entry": entry,
+        }),
+    ))
+}
+
 /// config reset - requires CLI (modifies files)
 pub fn execute_reset(
     config_override: Option<&Path>,
@@ -123,3 +315,119 @@ pub fn execute_reset(
         serde_json::json!({"status": "reset"}),
     ))
 }
+
+/// config import - merges shortcuts exported from another window manager into
+/// the main config file; writing requires CLI, a dry run only reports them
+pub fn execute_import(
+    from: Source,
+    file: &str,
+    dry_run: bool,
+    config_override: Option<&Path>,
+    ctx: &ExecutionContext,
+) -> Result<ActionResult, ActionError> {
+    if !dry_run && !ctx.is_cli {
+        return Err(ActionError::not_supported(
+            "config import is only available via CLI, use dry_run to preview",
+        ));
+    }
+
+    let file = shellexpand::tilde(file).to_string();
+    let text = std::fs::read_to_string(&file)
+        .map_err(|e| ActionError::general(format!("failed to read {}: {}", file, e)))?;
+
+    let path = config::get_config_path_with_override(config_override).map_err(ActionError::from)?;
+    let mut cfg = config::load_main_with_override(config_override).map_err(ActionError::from)?;
+    let imported =
+        import::parse(from, &text, config::keyboard_layout(&cfg)).map_err(ActionError::from)?;
+    let (added, conflicts) = import::merge(&mut cfg, imported.shortcuts);
+    if !dry_run && !added.is_empty() {
+        config::save_with_override(&cfg, config_override).map_err(ActionError::from)?;
+    }
+
+    let mut skipped = imported.skipped;
+    skipped.extend(conflicts);
+
+    Ok(ActionResult::simple(
+        "config_import",
+        serde_json::json!({
+            "from": from.as_str(),
+            "file": file,
+            "path": path.to_string_lossy(),
+            "added": added,
+            "skipped": skipped,
+            "dry_run": dry_run,
+        }),
+    ))
+}
+
+/// config convert - renders the config file in another format; writing the
+/// new file (and moving the old one aside) requires CLI
+pub fn execute_convert(
+    to: Format,
+    write: bool,
+    config_override: Option<&Path>,
+    ctx: &ExecutionContext,
+) -> Result<ActionResult, ActionError> {
+    if write && !ctx.is_cli {
+        return Err(ActionError::not_supported(
+            "config convert --write is only available via CLI",
+        ));
+    }
+
+    let path = config::get_config_path_with_override(config_override).map_err(ActionError::from)?;
+    let conversion = config::convert(&path, to).map_err(ActionError::from)?;
+
+    let backup = if write {
+        Some(conversion.apply().map_err(ActionError::from)?)
+    } else {
+        None
+    };
+
+    Ok(ActionResult::simple(
+        "config_convert",
+        serde_json::json!({
+            "from": conversion.from.to_string_lossy(),
+            "to": conversion.to.to_string_lossy(),
+            "format": to.as_str(),
+            "content": conversion.content,
+            "backup": backup.map(|b| b.to_string_lossy().to_string()),
+            "written": write,
+        }),
+    ))
+}
+
+/// config migrate - upgrades the config file to the current format version;
+/// writing requires CLI, a dry run only reports the diff
+pub fn execute_migrate(
+    dry_run: bool,
+    config_override: Option<&Path>,
+    ctx: &ExecutionContext,
+) -> Result<ActionResult, ActionError> {
+    if !dry_run && !ctx.is_cli {
+        return Err(ActionError::not_supported(
+            "config migrate is only available via CLI, use dry_run to preview",
+        ));
+    }
+
+    let path = config::get_config_path_with_override(config_override).map_err(ActionError::from)?;
+    let plan = config::migrate::plan(&path).map_err(ActionError::from)?;
+
+    let backup = if plan.migrated.is_needed() && !dry_run {
+        Some(plan.apply().map_err(ActionError::from)?)
+    } else {
+        None
+    };
+
+    Ok(ActionResult::simple(
+        "config_migrate",
+        serde_json::json!({
+            "path": path.to_string_lossy(),
+            "from": plan.migrated.from,
+            "to": plan.migrated.to,
+            "changes": plan.migrated.changes,
+            "diff": config::migrate::diff_lines(&plan.original, &plan.content),
+            "backup": backup.map(|b| b.to_string_lossy().to_string()),
+            "dry_run": dry_run,
+        }),
+    ))
+}
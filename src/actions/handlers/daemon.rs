@@ -1,10 +1,14 @@
 //! daemon action handlers
 
+use std::collections::HashMap;
+
 use crate::actions::context::ExecutionContext;
 use crate::actions::error::ActionError;
 use crate::actions::result::ActionResult;
 use crate::daemon::ipc;
 
+use super::history::daemon_error;
+
 /// execute daemon status action
 pub fn execute_status(_ctx: &ExecutionContext) -> Result<ActionResult, ActionError> {
     let running = ipc::is_daemon_running();
@@ -31,6 +35,38 @@ pub fn execute_status(_ctx: &ExecutionContext) -> Result<ActionResult, ActionErr
     ))
 }
 
+/// execute daemon stats action - fetches metrics from the running daemon
+pub fn execute_stats(
+    prometheus: bool,
+    _ctx: &ExecutionContext,
+) -> Result<ActionResult, ActionError> {
+    if !ipc::is_daemon_running() {
+        return Err(ActionError::general(
+            "Daemon not running. Start with 'cwm daemon start'",
+        ));
+    }
+
+    let mut params = HashMap::new();
+    if prometheus {
+        params.insert("format".to_string(), "prometheus".to_string());
+    }
+
+    let response_str = ipc::send_jsonrpc("stats", params, Some("1"))
+        .map_err(|e| ActionError::general(format!("IPC error: {}", e)))?;
+
+    let response: serde_json::Value = serde_json::from_str(&response_str)
+        .map_err(|e| ActionError::general(format!("Invalid JSON response: {}", e)))?;
+
+    if let Some(error) = response.get("error") {
+        return Err(daemon_error(error));
+    }
+
+    match response.get("result") {
+        Some(result) => Ok(ActionResult::simple("daemon_stats", result.clone())),
+        None => Err(ActionError::general("Invalid response from daemon")),
+    }
+}
+
 /// daemon start - requires CLI (spawns background process)
 pub fn execute_start(
     log: Option<String>,
@@ -8,8 +8,30 @@ use std::collections::HashMap;
 use crate::actions::error::ActionError;
 use crate::actions::result::ActionResult;
 use crate::cli::exit_codes;
+use crate::cli::output::from_jsonrpc_code;
 use crate::daemon::ipc::{is_daemon_running, send_jsonrpc};
 
+/// convert a daemon JSON-RPC error object into an ActionError
+///
+/// access-denied errors keep their dedicated exit code so scripts can tell
+/// them apart from action failures
+pub(crate) fn daemon_error(error: &serde_json::Value) -> ActionError {
+    let message = error
+        .get("message")
+        .and_then(|m| m.as_str())
+        .unwrap_or("Unknown error");
+
+    let code = error
+        .get("code")
+        .and_then(|c| c.as_i64())
+        .map(|c| from_jsonrpc_code(c as i32));
+
+    match code {
+        Some(exit_codes::ACCESS_DENIED) => ActionError::new(exit_codes::ACCESS_DENIED, message),
+        _ => ActionError::general(message),
+    }
+}
+
 /// execute undo action via daemon IPC
 #[allow(unused_variables)]
 pub fn execute_undo(
@@ -32,11 +54,7 @@ pub fn execute_undo(
 
     // check for error in response
     if let Some(error) = response.get("error") {
-        let message = error
-            .get("message")
-            .and_then(|m| m.as_str())
-            .unwrap_or("Unknown error");
-        return Err(ActionError::general(message));
+        return Err(daemon_error(error));
     }
 
     if let Some(result) = response.get("result") {
@@ -66,11 +84,7 @@ pub fn execute_redo(
         .map_err(|e| ActionError::general(format!("Invalid JSON response: {}", e)))?;
 
     if let Some(error) = response.get("error") {
-        let message = error
-            .get("message")
-            .and_then(|m| m.as_str())
-            .unwrap_or("Unknown error");
-        return Err(ActionError::general(message));
+        return Err(daemon_error(error));
     }
 
     if let Some(result) = response.get("result") {
@@ -100,11 +114,7 @@ pub fn execute_list(
         .map_err(|e| ActionError::general(format!("Invalid JSON response: {}", e)))?;
 
     if let Some(error) = response.get("error") {
-        let message = error
-            .get("message")
-            .and_then(|m| m.as_str())
-            .unwrap_or("Unknown error");
-        return Err(ActionError::general(message));
+        return Err(daemon_error(error));
     }
 
     if let Some(result) = response.get("result") {
@@ -134,11 +144,7 @@ pub fn execute_clear(
         .map_err(|e| ActionError::general(format!("Invalid JSON response: {}", e)))?;
 
     if let Some(error) = response.get("error") {
-        let message = error
-            .get("message")
-            .and_then(|m| m.as_str())
-            .unwrap_or("Unknown error");
-        return Err(ActionError::general(message));
+        return Err(daemon_error(error));
     }
 
     if let Some(result) = response.get("result") {
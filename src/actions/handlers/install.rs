@@ -354,7 +354,8 @@ pub fn execute_update(
             perform_update(release, force).map_err(ActionError::from)?;
 
             // update last check time in config
-            let mut cfg = ctx.config.clone();
+            let mut cfg = config::load_main_with_override(ctx.config_path_override())
+                .unwrap_or_else(|_| ctx.config.clone());
             cfg.settings.update.last_check = Some(chrono::Utc::now());
             if let Err(e) = config::save_with_override(&cfg, ctx.config_path_override()) {
                 eprintln!("warning: failed to save config: {}", e);
@@ -371,7 +372,8 @@ pub fn execute_update(
         }
         None => {
             // update last check time
-            let mut cfg = ctx.config.clone();
+            let mut cfg = config::load_main_with_override(ctx.config_path_override())
+                .unwrap_or_else(|_| ctx.config.clone());
             cfg.settings.update.last_check = Some(chrono::Utc::now());
             if let Err(e) = config::save_with_override(&cfg, ctx.config_path_override()) {
                 eprintln!("warning: failed to save config: {}", e);
@@ -1,6 +1,7 @@
 //! kill action handler
 
 use crate::actions::context::ExecutionContext;
+use crate::actions::control::Progress;
 use crate::actions::error::ActionError;
 use crate::actions::result::{ActionResult, AppData, MatchData};
 use crate::window::manager;
@@ -38,12 +39,31 @@ pub fn execute(
 
     // optionally wait for termination
     let terminated = if wait {
-        manager::wait_for_termination(found_app.pid, DEFAULT_WAIT_TIMEOUT_MS, ctx.verbose)
+        if let Some(control) = ctx.control {
+            control.report(Progress::message(format!(
+                "waiting for {} to terminate",
+                found_app.name
+            )));
+        }
+        let should_abort = || ctx.control.is_some_and(|c| c.is_interrupted());
+        manager::wait_for_termination(
+            found_app.pid,
+            DEFAULT_WAIT_TIMEOUT_MS,
+            ctx.verbose,
+            &should_abort,
+        )
     } else {
         // we don't know if it actually terminated yet
         true
     };
 
+    // cancelled or past the request deadline while waiting
+    if let Some(control) = ctx.control {
+        if !terminated {
+            control.check()?;
+        }
+    }
+
     if wait && !terminated {
         return Err(ActionError::general(format!(
             "timeout waiting for {} to terminate",
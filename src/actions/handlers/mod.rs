@@ -12,7 +12,10 @@ pub mod kill;
 pub mod list;
 pub mod maximize;
 pub mod move_window;
+pub mod profile;
 pub mod record;
+pub mod remote;
 pub mod resize;
+pub mod shortcuts;
 pub mod spotlight;
 pub mod system;
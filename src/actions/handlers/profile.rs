@@ -0,0 +1,93 @@
+//! profile action handlers
+
+use std::collections::HashMap;
+
+use crate::actions::context::ExecutionContext;
+use crate::actions::error::ActionError;
+use crate::actions::result::ActionResult;
+use crate::cli::exit_codes;
+use crate::config;
+use crate::daemon::ipc;
+
+use super::history::daemon_error;
+
+/// switch to a profile (None = base config)
+///
+/// the running daemon switches immediately; otherwise the profile is
+/// persisted and picked up on the next start
+pub fn execute_use(
+    name: Option<&str>,
+    ctx: &ExecutionContext,
+) -> Result<ActionResult, ActionError> {
+    if let Some(name) = name {
+        if !ctx.config.profiles.contains_key(name) {
+            return Err(ActionError::with_suggestions(
+                exit_codes::INVALID_ARGS,
+                format!("unknown profile '{}'", name),
+                config::profile::names(ctx.config),
+            ));
+        }
+        config::profile::apply(ctx.config, name)
+            .map_err(|e| ActionError::new(exit_codes::CONFIG_ERROR, format!("{:#}", e)))?;
+    }
+
+    let daemon_running = ipc::is_daemon_running();
+    if daemon_running {
+        let mut params = HashMap::new();
+        match name {
+            Some(name) => {
+                params.insert("command".to_string(), "use".to_string());
+                params.insert("name".to_string(), name.to_string());
+            }
+            None => {
+                params.insert("command".to_string(), "clear".to_string());
+            }
+        }
+
+        let response_str = ipc::send_jsonrpc("profile", params, Some("1"))
+            .map_err(|e| ActionError::general(format!("IPC error: {}", e)))?;
+        let response: serde_json::Value = serde_json::from_str(&response_str)
+            .map_err(|e| ActionError::general(format!("Invalid JSON response: {}", e)))?;
+
+        if let Some(error) = response.get("error") {
+            return Err(daemon_error(error));
+        }
+    } else {
+        config::profile::save_active(name).map_err(ActionError::from)?;
+    }
+
+    Ok(ActionResult::simple(
+        "profile_use",
+        serde_json::json!({
+            "profile": name,
+            "daemon": daemon_running,
+        }),
+    ))
+}
+
+/// list configured profiles and which one is active
+pub fn execute_list(ctx: &ExecutionContext) -> Result<ActionResult, ActionError> {
+    let active = config::profile::load_active();
+
+    let profiles: Vec<serde_json::Value> = config::profile::names(ctx.config)
+        .into_iter()
+        .map(|name| {
+            let profile = &ctx.config.profiles[&name];
+            serde_json::json!({
+                "active": active.as_deref() == Some(name.as_str()),
+                "shortcuts": profile.shortcuts.len(),
+                "app_rules": profile.app_rules.len(),
+                "settings": profile.settings.keys().collect::<Vec<_>>(),
+                "name": name,
+            })
+        })
+        .collect();
+
+    Ok(ActionResult::simple(
+        "profile_list",
+        serde_json::json!({
+            "active": active,
+            "profiles": profiles,
+        }),
+    ))
+}
@@ -0,0 +1,254 @@
+//! remote control action handlers
+//!
+//! `forward` runs a command on a paired host (`cwm --host`); the other handlers
+//! manage pairing, known hosts and paired clients
+
+use std::collections::HashMap;
+
+use serde_json::Value;
+
+use crate::actions::command::Command;
+use crate::actions::context::ExecutionContext;
+use crate::actions::error::ActionError;
+use crate::actions::result::ActionResult;
+use crate::cli::exit_codes;
+use crate::cli::output::from_jsonrpc_code;
+use crate::daemon::ipc::{self, is_daemon_running, send_jsonrpc};
+use crate::remote::client::{self, KnownHost, KnownHosts};
+use crate::remote::pairing::{self, PairedClients};
+
+/// convert a JSON-RPC error object from a daemon into an ActionError,
+/// keeping its exit code and suggestions
+fn rpc_error(error: &Value) -> ActionError {
+    let message = error
+        .get("message")
+        .and_then(|m| m.as_str())
+        .unwrap_or("Unknown error");
+
+    let code = error
+        .get("code")
+        .and_then(|c| c.as_i64())
+        .map(|c| from_jsonrpc_code(c as i32))
+        .unwrap_or(exit_codes::ERROR);
+
+    let suggestions = error
+        .get("data")
+        .and_then(|d| d.get("suggestions"))
+        .and_then(|s| s.as_array())
+        .map(|s| {
+            s.iter()
+                .filter_map(|v| v.as_str().map(str::to_string))
+                .collect()
+        })
+        .unwrap_or_default();
+
+    ActionError::with_suggestions(code, message, suggestions)
+}
+
+/// execute a command on a paired remote host
+pub fn forward(
+    host: &str,
+    cmd: &Command,
+    _ctx: &ExecutionContext,
+) -> Result<ActionResult, ActionError> {
+    let (method, params) = cmd.to_jsonrpc().ok_or_else(|| {
+        ActionError::not_supported(format!(
+            "{} can't be run on a remote host",
+            cmd.method_name()
+        ))
+    })?;
+
+    let hosts = KnownHosts::load(&client::hosts_path()?)?;
+    let known = hosts.get(host).ok_or_else(|| {
+        ActionError::with_suggestions(
+            exit_codes::INVALID_ARGS,
+            format!(
+                "unknown host '{}', pair with it first: cwm remote add {} <address> --code <code> --fingerprint <fingerprint>",
+                host, host
+            ),
+            hosts.names(),
+        )
+    })?;
+    let client_key = client::load_or_create_client_key()?;
+
+    let mut request = serde_json::json!({
+        "jsonrpc": "2.0",
+        "method": method,
+        "params": params,
+        "id": "1",
+    });
+    if let Some(token) = ipc::client_token() {
+        request["token"] = serde_json::json!(token);
+    }
+
+    let response = client::request(known, &client_key, request)
+        .map_err(|e| ActionError::general(format!("{}: {}", known.name, e)))?;
+
+    if let Some(error) = response.get("error") {
+        return Err(rpc_error(error));
+    }
+
+    // action results carry their own `action` field; daemon-internal results
+    // (undo, history) are wrapped the same way the local handlers wrap them
+    match response.get("result") {
+        Some(Value::Object(fields)) if fields.contains_key("action") => {
+            Ok(ActionResult::remote(method, fields.clone()))
+        }
+        Some(result) => Ok(ActionResult::simple(method, result.clone())),
+        None => Err(ActionError::general("Invalid response from remote host")),
+    }
+}
+
+/// ask the local daemon for a one-time pairing code
+pub fn execute_pair(_ctx: &ExecutionContext) -> Result<ActionResult, ActionError> {
+    if !is_daemon_running() {
+        return Err(ActionError::general(
+            "Daemon not running. Start with 'cwm daemon start'",
+        ));
+    }
+
+    let response_str = send_jsonrpc("remote_pair", HashMap::new(), Some("1"))
+        .map_err(|e| ActionError::general(format!("IPC error: {}", e)))?;
+
+    let response: Value = serde_json::from_str(&response_str)
+        .map_err(|e| ActionError::general(format!("Invalid JSON response: {}", e)))?;
+
+    if let Some(error) = response.get("error") {
+        return Err(rpc_error(error));
+    }
+
+    match response.get("result") {
+        Some(result) => Ok(ActionResult::simple("remote_pair", result.clone())),
+        None => Err(ActionError::general("Invalid response from daemon")),
+    }
+}
+
+/// pair with a remote host and remember it under `name`
+pub fn execute_add(
+    name: &str,
+    address: &str,
+    port: u16,
+    code: &str,
+    expected_fingerprint: &str,
+    _ctx: &ExecutionContext,
+) -> Result<ActionResult, ActionError> {
+    if name.is_empty() || name.contains(char::is_whitespace) {
+        return Err(ActionError::invalid_args(
+            "host name must be non-empty and contain no spaces",
+        ));
+    }
+
+    let client_key = client::load_or_create_client_key()?;
+    let fingerprint = client::pair(
+        address,
+        port,
+        code,
+        &client::local_hostname(),
+        &client_key,
+        expected_fingerprint,
+    )
+    .map_err(|e| {
+        ActionError::new(
+            exit_codes::ACCESS_DENIED,
+            format!("pairing with {} failed: {}", address, e),
+        )
+    })?;
+
+    let path = client::hosts_path()?;
+    let mut hosts = KnownHosts::load(&path)?;
+    let host = KnownHost {
+        name: name.to_string(),
+        address: address.to_string(),
+        port,
+        fingerprint,
+        paired_at: chrono::Utc::now(),
+    };
+    hosts.add(host.clone());
+    hosts.save(&path)?;
+
+    Ok(ActionResult::simple(
+        "remote_add",
+        serde_json::to_value(&host).unwrap_or_default(),
+    ))
+}
+
+/// list known hosts (machines this one controls) and paired clients
+/// (machines that control this one)
+pub fn execute_list(_ctx: &ExecutionContext) -> Result<ActionResult, ActionError> {
+    let hosts = KnownHosts::load(&client::hosts_path()?)?;
+    let clients = PairedClients::load(&pairing::clients_path()?)?;
+
+    Ok(ActionResult::simple(
+        "remote_list",
+        serde_json::json!({
+            "hosts": hosts.hosts,
+            "clients": clients.clients,
+        }),
+    ))
+}
+
+/// forget a known host
+pub fn execute_remove(name: &str, _ctx: &ExecutionContext) -> Result<ActionResult, ActionError> {
+    let path = client::hosts_path()?;
+    let mut hosts = KnownHosts::load(&path)?;
+
+    let removed = hosts.remove(name).ok_or_else(|| {
+        ActionError::with_suggestions(
+            exit_codes::INVALID_ARGS,
+            format!("unknown host '{}'", name),
+            hosts.names(),
+        )
+    })?;
+    hosts.save(&path)?;
+
+    Ok(ActionResult::simple(
+        "remote_remove",
+        serde_json::json!({ "removed": removed }),
+    ))
+}
+
+/// revoke a paired client by name or fingerprint
+pub fn execute_revoke(client: &str, _ctx: &ExecutionContext) -> Result<ActionResult, ActionError> {
+    let path = pairing::clients_path()?;
+    let mut clients = PairedClients::load(&path)?;
+
+    let revoked = clients.revoke(client);
+    if revoked.is_empty() {
+        return Err(ActionError::with_suggestions(
+            exit_codes::INVALID_ARGS,
+            format!("no paired client matches '{}'", client),
+            clients.clients.iter().map(|c| c.name.clone()).collect(),
+        ));
+    }
+    clients.save(&path)?;
+
+    Ok(ActionResult::simple(
+        "remote_revoke",
+        serde_json::json!({ "revoked": revoked }),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rpc_error_keeps_code_and_suggestions() {
+        let error = serde_json::json!({
+            "code": -32002,
+            "message": "App not found",
+            "data": { "suggestions": ["Safari", "Slack"] }
+        });
+        let err = rpc_error(&error);
+        assert_eq!(err.code, exit_codes::APP_NOT_FOUND);
+        assert_eq!(err.message, "App not found");
+        assert_eq!(err.suggestions, vec!["Safari", "Slack"]);
+    }
+
+    #[test]
+    fn test_rpc_error_without_code() {
+        let err = rpc_error(&serde_json::json!({ "message": "boom" }));
+        assert_eq!(err.code, exit_codes::ERROR);
+        assert!(err.suggestions.is_empty());
+    }
+}
@@ -0,0 +1,36 @@
+//! shortcuts action handlers
+
+use crate::actions::context::ExecutionContext;
+use crate::actions::error::ActionError;
+use crate::actions::result::ActionResult;
+use crate::config::conflicts;
+use crate::config::export::{self, Target};
+
+/// shortcuts export - renders the configured shortcuts for another hotkey
+/// tool or as a cheat sheet, listing those the target cannot express
+pub fn execute_export(format: Target, ctx: &ExecutionContext) -> Result<ActionResult, ActionError> {
+    let exported = export::export(ctx.config, format);
+
+    Ok(ActionResult::simple(
+        "shortcuts_export",
+        serde_json::json!({
+            "format": format.as_str(),
+            "content": exported.content,
+            "skipped": exported.skipped,
+        }),
+    ))
+}
+
+/// shortcuts conflicts - the configured shortcuts whose hotkeys macOS, common
+/// apps or earlier shortcuts already use
+pub fn execute_conflicts(ctx: &ExecutionContext) -> Result<ActionResult, ActionError> {
+    let conflicts = conflicts::conflicts(ctx.config);
+
+    Ok(ActionResult::simple(
+        "shortcuts_conflicts",
+        serde_json::json!({
+            "count": conflicts.len(),
+            "conflicts": conflicts,
+        }),
+    ))
+}
@@ -6,6 +6,7 @@
 
 mod command;
 mod context;
+mod control;
 mod error;
 pub mod handlers;
 mod parse;
@@ -13,9 +14,10 @@ mod result;
 
 pub use command::{
     Command, ConfigCommand, DaemonCommand, EventsCommand, GetTarget, HistoryCommand, ListResource,
-    RecordCommand, SpotlightCommand,
+    ProfileCommand, RecordCommand, RemoteCommand, ShortcutsCommand, SpotlightCommand,
 };
 pub use context::ExecutionContext;
+pub use control::{Interrupted, Progress, RequestControl};
 pub use error::ActionError;
 pub use parse::JsonRpcRequest;
 pub use result::ActionResult;
@@ -32,6 +34,11 @@ pub fn execute(cmd: Command, ctx: &ExecutionContext) -> Result<ActionResult, Act
         )));
     }
 
+    // forward to a paired remote host (`cwm --host`)
+    if let Some(host) = &ctx.remote_host {
+        return handlers::remote::forward(host, &cmd, ctx);
+    }
+
     match cmd {
         // window commands
         Command::Focus { app, launch } => handlers::focus::execute(app, launch, ctx),
@@ -90,19 +97,71 @@ pub fn execute(cmd: Command, ctx: &ExecutionContext) -> Result<ActionResult, Act
             DaemonCommand::Stop => handlers::daemon::execute_stop(ctx),
             DaemonCommand::Install { bin, log } => handlers::daemon::execute_install(bin, log, ctx),
             DaemonCommand::Uninstall => handlers::daemon::execute_uninstall(ctx),
+            DaemonCommand::Stats { prometheus } => handlers::daemon::execute_stats(prometheus, ctx),
         },
 
         // config commands
         Command::Config(config_cmd) => {
             let config_override = ctx.config_path_override();
             match config_cmd {
-                ConfigCommand::Show => handlers::config::execute_show(ctx),
+                ConfigCommand::Show { resolved, origin } => {
+                    handlers::config::execute_show(resolved, origin, config_override, ctx)
+                }
                 ConfigCommand::Path => handlers::config::execute_path(config_override),
                 ConfigCommand::Verify => handlers::config::execute_verify(config_override),
+                ConfigCommand::Lint { severity } => {
+                    handlers::config::execute_lint(severity, config_override)
+                }
+                ConfigCommand::Migrate { dry_run } => {
+                    handlers::config::execute_migrate(dry_run, config_override, ctx)
+                }
+                ConfigCommand::Import {
+                    from,
+                    ref file,
+                    dry_run,
+                } => handlers::config::execute_import(from, file, dry_run, config_override, ctx),
+                ConfigCommand::Convert { to, write } => {
+                    handlers::config::execute_convert(to, write, config_override, ctx)
+                }
+                ConfigCommand::Schema => handlers::config::execute_schema(),
                 ConfigCommand::Default => handlers::config::execute_default(ctx),
+                ConfigCommand::Get { ref key } => {
+                    handlers::config::execute_get(key, config_override, ctx)
+                }
                 ConfigCommand::Set { ref key, ref value } => {
                     handlers::config::execute_set(key, value, config_override, ctx)
                 }
+                ConfigCommand::Unset { ref key } => {
+                    handlers::config::execute_unset(key, config_override, ctx)
+                }
+                ConfigCommand::AddShortcut {
+                    ref keys,
+                    ref action,
+                    ref app,
+                    launch,
+                    ref when,
+                } => handlers::config::execute_add_shortcut(
+                    keys,
+                    action,
+                    app.as_deref(),
+                    launch,
+                    when.as_deref(),
+                    config_override,
+                    ctx,
+                ),
+                ConfigCommand::AddRule {
+                    ref app,
+                    ref action,
+                    delay_ms,
+                    ref when,
+                } => handlers::config::execute_add_rule(
+                    app,
+                    action,
+                    delay_ms,
+                    when.as_deref(),
+                    config_override,
+                    ctx,
+                ),
                 ConfigCommand::Reset => handlers::config::execute_reset(config_override, ctx),
             }
         }
@@ -125,6 +184,41 @@ pub fn execute(cmd: Command, ctx: &ExecutionContext) -> Result<ActionResult, Act
             HistoryCommand::Clear => handlers::history::execute_clear(ctx),
         },
 
+        // remote commands
+        Command::Remote(remote_cmd) => match remote_cmd {
+            RemoteCommand::Pair => handlers::remote::execute_pair(ctx),
+            RemoteCommand::Add {
+                ref name,
+                ref address,
+                port,
+                ref code,
+                ref fingerprint,
+            } => handlers::remote::execute_add(
+                name,
+                address,
+                port,
+                code,
+                fingerprint,
+                ctx,
+            ),
+            RemoteCommand::List => handlers::remote::execute_list(ctx),
+            RemoteCommand::Remove { ref name } => handlers::remote::execute_remove(name, ctx),
+            RemoteCommand::Revoke { ref client } => handlers::remote::execute_revoke(client, ctx),
+        },
+
+        // profile commands
+        Command::Profile(profile_cmd) => match profile_cmd {
+            ProfileCommand::Use { ref name } => handlers::profile::execute_use(Some(name), ctx),
+            ProfileCommand::Clear => handlers::profile::execute_use(None, ctx),
+            ProfileCommand::List => handlers::profile::execute_list(ctx),
+        },
+
+        // shortcuts commands
+        Command::Shortcuts(shortcuts_cmd) => match shortcuts_cmd {
+            ShortcutsCommand::Export { format } => handlers::shortcuts::execute_export(format, ctx),
+            ShortcutsCommand::Conflicts => handlers::shortcuts::execute_conflicts(ctx),
+        },
+
         // spotlight commands
         Command::Spotlight(spotlight_cmd) => match spotlight_cmd {
             SpotlightCommand::List => handlers::spotlight::execute_list(ctx),
@@ -10,6 +10,7 @@ use serde::Deserialize;
 
 use crate::actions::command::*;
 use crate::actions::error::ActionError;
+use crate::config::lint::Severity;
 use crate::display::DisplayTarget;
 use crate::window::manager::{MoveTarget, ResizeTarget};
 
@@ -191,17 +192,74 @@ impl JsonRpcRequest {
             "config" => {
                 let cmd = params.get_string("command")?;
                 match cmd.as_str() {
-                    "show" => Ok(Command::Config(ConfigCommand::Show)),
+                    "show" => Ok(Command::Config(ConfigCommand::Show {
+                        resolved: params.get_bool_or("resolved", false),
+                        origin: params.get_bool_or("origin", false),
+                    })),
                     "path" => Ok(Command::Config(ConfigCommand::Path)),
+                    "get" => Ok(Command::Config(ConfigCommand::Get {
+                        key: params.get_string("key")?,
+                    })),
                     "set" => Ok(Command::Config(ConfigCommand::Set {
                         key: params.get_string("key")?,
                         value: params.get_string("value")?,
                     })),
+                    "unset" => Ok(Command::Config(ConfigCommand::Unset {
+                        key: params.get_string("key")?,
+                    })),
+                    "add" => {
+                        let kind = params.get_string("kind")?;
+                        match kind.as_str() {
+                            "shortcut" => Ok(Command::Config(ConfigCommand::AddShortcut {
+                                keys: params.get_string("keys")?,
+                                action: params.get_string("action")?,
+                                app: params.get_optional_string("app")?,
+                                launch: params.get_optional_bool("launch")?,
+                                when: params.get_optional_string("when")?,
+                            })),
+                            "rule" => Ok(Command::Config(ConfigCommand::AddRule {
+                                app: params.get_string("app")?,
+                                action: params.get_string("action")?,
+                                delay_ms: params.get_optional_u64("delay_ms")?,
+                                when: params.get_optional_string("when")?,
+                            })),
+                            _ => Err(ActionError::invalid_args(format!(
+                                "unknown config add kind '{}', expected: shortcut, rule",
+                                kind
+                            ))),
+                        }
+                    }
                     "reset" => Ok(Command::Config(ConfigCommand::Reset)),
                     "default" => Ok(Command::Config(ConfigCommand::Default)),
                     "verify" => Ok(Command::Config(ConfigCommand::Verify)),
+                    "lint" => {
+                        let severity = match params.get_optional_string("severity")? {
+                            Some(s) => s.parse().map_err(ActionError::invalid_args)?,
+                            None => Severity::Info,
+                        };
+                        Ok(Command::Config(ConfigCommand::Lint { severity }))
+                    }
+                    "migrate" => Ok(Command::Config(ConfigCommand::Migrate {
+                        dry_run: params.get_bool_or("dry_run", false),
+                    })),
+                    "import" => Ok(Command::Config(ConfigCommand::Import {
+                        from: params
+                            .get_string("from")?
+                            .parse()
+                            .map_err(ActionError::invalid_args)?,
+                        file: params.get_string("file")?,
+                        dry_run: params.get_bool_or("dry_run", false),
+                    })),
+                    "convert" => Ok(Command::Config(ConfigCommand::Convert {
+                        to: params
+                            .get_string("to")?
+                            .parse()
+                            .map_err(ActionError::invalid_args)?,
+                        write: params.get_bool_or("write", false),
+                    })),
+                    "schema" => Ok(Command::Config(ConfigCommand::Schema)),
                     _ => Err(ActionError::invalid_args(format!(
-                        "unknown config command '{}', expected: show, path, set, reset, default, verify",
+                        "unknown config command '{}', expected: show, path, get, set, unset, add, reset, default, verify, lint, migrate, import, convert, schema",
                         cmd
                     ))),
                 }
@@ -286,6 +344,41 @@ impl JsonRpcRequest {
             "record_layout" => Err(ActionError::not_supported(
                 "record_layout is a CLI-only command",
             )),
+            "remote" => Err(ActionError::not_supported("remote is a CLI-only command")),
+
+            // ==================== Profile Commands ====================
+            "profile" => {
+                let cmd = params.get_string("command")?;
+                match cmd.as_str() {
+                    "use" => Ok(Command::Profile(ProfileCommand::Use {
+                        name: params.get_string("name")?,
+                    })),
+                    "clear" => Ok(Command::Profile(ProfileCommand::Clear)),
+                    "list" => Ok(Command::Profile(ProfileCommand::List)),
+                    _ => Err(ActionError::invalid_args(format!(
+                        "unknown profile command '{}', expected: use, clear, list",
+                        cmd
+                    ))),
+                }
+            }
+
+            // ==================== Shortcuts Commands ====================
+            "shortcuts" => {
+                let cmd = params.get_string("command")?;
+                match cmd.as_str() {
+                    "export" => Ok(Command::Shortcuts(ShortcutsCommand::Export {
+                        format: params
+                            .get_string("format")?
+                            .parse()
+                            .map_err(ActionError::invalid_args)?,
+                    })),
+                    "conflicts" => Ok(Command::Shortcuts(ShortcutsCommand::Conflicts)),
+                    _ => Err(ActionError::invalid_args(format!(
+                        "unknown shortcuts command '{}', expected: export, conflicts",
+                        cmd
+                    ))),
+                }
+            }
 
             // ==================== Unknown ====================
             _ => Err(ActionError::invalid_args(format!(
@@ -296,6 +389,67 @@ impl JsonRpcRequest {
     }
 }
 
+impl Command {
+    /// convert a command back into a JSON-RPC method and params
+    ///
+    /// this is the inverse of `JsonRpcRequest::to_command`, used to forward
+    /// commands to a remote daemon. returns None for commands that only make
+    /// sense on the local machine
+    pub fn to_jsonrpc(&self) -> Option<(&'static str, serde_json::Value)> {
+        use serde_json::json;
+
+        let params = match self {
+            Command::Focus { app, launch } | Command::Maximize { app, launch } => {
+                json!({ "app": app, "launch": launch })
+            }
+            Command::Resize {
+                app,
+                to,
+                overflow,
+                launch,
+            } => json!({
+                "app": app,
+                "to": to.to_string(),
+                "overflow": overflow,
+                "launch": launch,
+            }),
+            Command::Move {
+                app,
+                to,
+                display,
+                launch,
+            } => json!({
+                "app": app,
+                "to": to.as_ref().map(|t| t.to_string()),
+                "display": display.as_ref().map(|d| d.to_string()),
+                "launch": launch,
+            }),
+            Command::Kill { app, force, wait } => {
+                json!({ "app": app, "force": force, "wait": wait })
+            }
+            Command::Close { app } => json!({ "app": app }),
+            Command::List { resource, detailed } => {
+                json!({ "resource": resource.to_string(), "detailed": detailed })
+            }
+            Command::Get {
+                target: GetTarget::Focused,
+            } => json!({ "target": "focused" }),
+            Command::Get {
+                target: GetTarget::Window { app },
+            } => json!({ "target": "window", "app": app }),
+            Command::Ping
+            | Command::Status
+            | Command::Version
+            | Command::Undo
+            | Command::Redo
+            | Command::History(_) => json!({}),
+            _ => return None,
+        };
+
+        Some((self.method_name(), params))
+    }
+}
+
 /// helper for extracting typed values from JSON params
 struct Params<'a> {
     value: &'a serde_json::Value,
@@ -384,6 +538,17 @@ impl<'a> Params<'a> {
             None => Ok(None),
         }
     }
+
+    /// get optional non-negative integer parameter
+    fn get_optional_u64(&self, key: &str) -> Result<Option<u64>, ActionError> {
+        match self.value.get(key) {
+            Some(v) if v.is_null() => Ok(None),
+            Some(v) => v.as_u64().map(Some).ok_or_else(|| {
+                ActionError::invalid_args(format!("{} must be a non-negative integer", key))
+            }),
+            None => Ok(None),
+        }
+    }
 }
 
 #[cfg(test)]
@@ -802,7 +967,29 @@ mod tests {
         let req =
             JsonRpcRequest::parse(r#"{"method":"config","params":{"command":"show"}}"#).unwrap();
         let cmd = req.to_command().unwrap();
-        assert!(matches!(cmd, Command::Config(ConfigCommand::Show)));
+        assert!(matches!(
+            cmd,
+            Command::Config(ConfigCommand::Show {
+                resolved: false,
+                origin: false
+            })
+        ));
+    }
+
+    #[test]
+    fn test_parse_profile_use() {
+        let req = JsonRpcRequest::parse(
+            r#"{"method":"profile","params":{"command":"use","name":"presenting"}}"#,
+        )
+        .unwrap();
+        match req.to_command().unwrap() {
+            Command::Profile(ProfileCommand::Use { name }) => assert_eq!(name, "presenting"),
+            other => panic!("unexpected command: {:?}", other),
+        }
+
+        let req =
+            JsonRpcRequest::parse(r#"{"method":"profile","params":{"command":"nope"}}"#).unwrap();
+        assert!(req.to_command().is_err());
     }
 
     #[test]
@@ -822,6 +1009,169 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_parse_config_get_unset() {
+        let req = JsonRpcRequest::parse(
+            r#"{"method":"config","params":{"command":"get","key":"shortcuts[0].keys"}}"#,
+        )
+        .unwrap();
+        match req.to_command().unwrap() {
+            Command::Config(ConfigCommand::Get { key }) => assert_eq!(key, "shortcuts[0].keys"),
+            other => panic!("unexpected command: {:?}", other),
+        }
+
+        let req = JsonRpcRequest::parse(
+            r#"{"method":"config","params":{"command":"unset","key":"settings.delay_ms"}}"#,
+        )
+        .unwrap();
+        assert!(matches!(
+            req.to_command().unwrap(),
+            Command::Config(ConfigCommand::Unset { .. })
+        ));
+    }
+
+    #[test]
+    fn test_parse_config_migrate() {
+        let req = JsonRpcRequest::parse(
+            r#"{"method":"config","params":{"command":"migrate","dry_run":true}}"#,
+        )
+        .unwrap();
+        assert!(matches!(
+            req.to_command().unwrap(),
+            Command::Config(ConfigCommand::Migrate { dry_run: true })
+        ));
+    }
+
+    #[test]
+    fn test_parse_config_import() {
+        let req = JsonRpcRequest::parse(
+            r#"{"method":"config","params":{"command":"import","from":"skhd","file":"~/.skhdrc","dry_run":true}}"#,
+        )
+        .unwrap();
+        match req.to_command().unwrap() {
+            Command::Config(ConfigCommand::Import {
+                from,
+                file,
+                dry_run,
+            }) => {
+                assert_eq!(from, crate::config::import::Source::Skhd);
+                assert_eq!(file, "~/.skhdrc");
+                assert!(dry_run);
+            }
+            other => panic!("unexpected command: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_parse_shortcuts_export() {
+        let req = JsonRpcRequest::parse(
+            r#"{"method":"shortcuts","params":{"command":"export","format":"markdown"}}"#,
+        )
+        .unwrap();
+        assert!(matches!(
+            req.to_command().unwrap(),
+            Command::Shortcuts(ShortcutsCommand::Export {
+                format: crate::config::export::Target::Markdown
+            })
+        ));
+
+        let req = JsonRpcRequest::parse(
+            r#"{"method":"shortcuts","params":{"command":"export","format":"pdf"}}"#,
+        )
+        .unwrap();
+        assert!(req.to_command().is_err());
+
+        let req =
+            JsonRpcRequest::parse(r#"{"method":"shortcuts","params":{"command":"conflicts"}}"#)
+                .unwrap();
+        assert!(matches!(
+            req.to_command().unwrap(),
+            Command::Shortcuts(ShortcutsCommand::Conflicts)
+        ));
+    }
+
+    #[test]
+    fn test_parse_config_convert() {
+        let req = JsonRpcRequest::parse(
+            r#"{"method":"config","params":{"command":"convert","to":"yaml"}}"#,
+        )
+        .unwrap();
+        assert!(matches!(
+            req.to_command().unwrap(),
+            Command::Config(ConfigCommand::Convert {
+                to: crate::config::format::Format::Yaml,
+                write: false
+            })
+        ));
+
+        let req = JsonRpcRequest::parse(
+            r#"{"method":"config","params":{"command":"convert","to":"ini"}}"#,
+        )
+        .unwrap();
+        assert!(req.to_command().is_err());
+    }
+
+    #[test]
+    fn test_parse_config_lint() {
+        let req = JsonRpcRequest::parse(
+            r#"{"method":"config","params":{"command":"lint","severity":"error"}}"#,
+        )
+        .unwrap();
+        assert!(matches!(
+            req.to_command().unwrap(),
+            Command::Config(ConfigCommand::Lint {
+                severity: Severity::Error
+            })
+        ));
+
+        let req =
+            JsonRpcRequest::parse(r#"{"method":"config","params":{"command":"lint"}}"#).unwrap();
+        assert!(matches!(
+            req.to_command().unwrap(),
+            Command::Config(ConfigCommand::Lint {
+                severity: Severity::Info
+            })
+        ));
+    }
+
+    #[test]
+    fn test_parse_config_schema() {
+        let req =
+            JsonRpcRequest::parse(r#"{"method":"config","params":{"command":"schema"}}"#).unwrap();
+        assert!(matches!(
+            req.to_command().unwrap(),
+            Command::Config(ConfigCommand::Schema)
+        ));
+    }
+
+    #[test]
+    fn test_parse_config_add() {
+        let req = JsonRpcRequest::parse(
+            r#"{"method":"config","params":{"command":"add","kind":"rule","app":"Slack","action":"maximize","delay_ms":200}}"#,
+        )
+        .unwrap();
+        match req.to_command().unwrap() {
+            Command::Config(ConfigCommand::AddRule {
+                app,
+                action,
+                delay_ms,
+                when,
+            }) => {
+                assert_eq!(app, "Slack");
+                assert_eq!(action, "maximize");
+                assert_eq!(delay_ms, Some(200));
+                assert!(when.is_none());
+            }
+            other => panic!("unexpected command: {:?}", other),
+        }
+
+        let req = JsonRpcRequest::parse(
+            r#"{"method":"config","params":{"command":"add","kind":"shortcut","keys":"ctrl+alt+m"}}"#,
+        )
+        .unwrap();
+        assert!(req.to_command().is_err());
+    }
+
     #[test]
     fn test_parse_spotlight_install() {
         let req = JsonRpcRequest::parse(
@@ -976,4 +1326,90 @@ mod tests {
         let cmd = req.to_command().unwrap();
         assert!(matches!(cmd, Command::History(HistoryCommand::Clear)));
     }
+
+    fn roundtrip(cmd: Command) -> Command {
+        let (method, params) = cmd.to_jsonrpc().expect("command should be remotable");
+        let json = serde_json::json!({ "method": method, "params": params }).to_string();
+        JsonRpcRequest::parse(&json).unwrap().to_command().unwrap()
+    }
+
+    #[test]
+    fn test_to_jsonrpc_roundtrip() {
+        let commands = vec![
+            Command::Focus {
+                app: vec!["Safari".to_string(), "Chrome".to_string()],
+                launch: Some(true),
+            },
+            Command::Maximize {
+                app: vec![],
+                launch: None,
+            },
+            Command::Resize {
+                app: vec!["Terminal".to_string()],
+                to: ResizeTarget::parse("1920x1080px").unwrap(),
+                overflow: true,
+                launch: Some(false),
+            },
+            Command::Move {
+                app: vec![],
+                to: Some(MoveTarget::parse("+100,-50").unwrap()),
+                display: Some(DisplayTarget::parse("external").unwrap()),
+                launch: None,
+            },
+            Command::Move {
+                app: vec!["Safari".to_string()],
+                to: Some(MoveTarget::parse("25%,75%").unwrap()),
+                display: None,
+                launch: None,
+            },
+            Command::Kill {
+                app: vec!["Safari".to_string()],
+                force: true,
+                wait: true,
+            },
+            Command::Close {
+                app: vec!["Finder".to_string()],
+            },
+            Command::List {
+                resource: ListResource::Displays,
+                detailed: true,
+            },
+            Command::Get {
+                target: GetTarget::Focused,
+            },
+            Command::Get {
+                target: GetTarget::Window {
+                    app: vec!["Safari".to_string()],
+                },
+            },
+            Command::Ping,
+            Command::Version,
+            Command::Undo,
+            Command::History(HistoryCommand::Clear),
+        ];
+
+        for cmd in commands {
+            let expected = format!("{:?}", cmd);
+            assert_eq!(format!("{:?}", roundtrip(cmd)), expected);
+        }
+    }
+
+    #[test]
+    fn test_to_jsonrpc_local_only_commands() {
+        assert!(Command::Config(ConfigCommand::Show {
+            resolved: false,
+            origin: false
+        })
+        .to_jsonrpc()
+        .is_none());
+        assert!(Command::Daemon(DaemonCommand::Stop).to_jsonrpc().is_none());
+        assert!(Command::Remote(RemoteCommand::List).to_jsonrpc().is_none());
+    }
+
+    #[test]
+    fn test_parse_remote_rejected() {
+        let req = JsonRpcRequest::parse(r#"{"method":"remote"}"#).unwrap();
+        let err = req.to_command().unwrap_err();
+        assert!(err.message.contains("CLI-only"));
+    }
 }
@@ -1,6 +1,7 @@
 //! action result types - unified response format for all interfaces
 
 use serde::Serialize;
+use std::borrow::Cow;
 
 use crate::window::matching::{AppInfo, MatchResult, MatchType};
 
@@ -8,7 +9,7 @@ use crate::window::matching::{AppInfo, MatchResult, MatchType};
 #[derive(Debug, Clone, Serialize)]
 pub struct ActionResult {
     /// action that was performed
-    pub action: &'static str,
+    pub action: Cow<'static, str>,
     /// result data (varies by action type)
     #[serde(flatten)]
     pub data: ActionData,
@@ -90,6 +91,9 @@ pub enum ActionData {
 
     /// simple result with arbitrary JSON data (for system commands, etc.)
     Simple { result: serde_json::Value },
+
+    /// result returned by a remote daemon, passed through as-is
+    Remote(serde_json::Map<String, serde_json::Value>),
 }
 
 /// basic app information
@@ -182,21 +186,21 @@ pub struct WindowData {
 impl ActionResult {
     pub fn focus(app: AppData, match_info: MatchData) -> Self {
         Self {
-            action: "focus",
+            action: Cow::Borrowed("focus"),
             data: ActionData::Focus { app, match_info },
         }
     }
 
     pub fn maximize(app: AppData, match_info: Option<MatchData>) -> Self {
         Self {
-            action: "maximize",
+            action: Cow::Borrowed("maximize"),
             data: ActionData::Maximize { app, match_info },
         }
     }
 
     pub fn resize(app: AppData, size: SizeData, match_info: Option<MatchData>) -> Self {
         Self {
-            action: "resize",
+            action: Cow::Borrowed("resize"),
             data: ActionData::Resize {
                 app,
                 size,
@@ -212,7 +216,7 @@ impl ActionResult {
         match_info: Option<MatchData>,
     ) -> Self {
         Self {
-            action: "move",
+            action: Cow::Borrowed("move"),
             data: ActionData::Move {
                 app,
                 position,
@@ -224,14 +228,14 @@ impl ActionResult {
 
     pub fn list(action: &'static str, items: Vec<serde_json::Value>) -> Self {
         Self {
-            action,
+            action: Cow::Borrowed(action),
             data: ActionData::List { items },
         }
     }
 
     pub fn get(app: AppData, window: WindowData, display: DisplayData) -> Self {
         Self {
-            action: "get",
+            action: Cow::Borrowed("get"),
             data: ActionData::Get {
                 app,
                 window,
@@ -242,7 +246,7 @@ impl ActionResult {
 
     pub fn launched(app: String) -> Self {
         Self {
-            action: "focus",
+            action: Cow::Borrowed("focus"),
             data: ActionData::Launched {
                 message: format!("{} launched, run command again once ready", app),
                 app,
@@ -252,7 +256,7 @@ impl ActionResult {
 
     pub fn kill(app: AppData, match_info: MatchData, force: bool, terminated: bool) -> Self {
         Self {
-            action: "kill",
+            action: Cow::Borrowed("kill"),
             data: ActionData::Kill {
                 app,
                 match_info,
@@ -264,7 +268,7 @@ impl ActionResult {
 
     pub fn close(app: AppData, match_info: MatchData, windows_closed: usize) -> Self {
         Self {
-            action: "close",
+            action: Cow::Borrowed("close"),
             data: ActionData::Close {
                 app,
                 match_info,
@@ -276,21 +280,40 @@ impl ActionResult {
     /// create a simple result with arbitrary JSON data
     pub fn simple(action: &'static str, result: serde_json::Value) -> Self {
         Self {
-            action,
+            action: Cow::Borrowed(action),
             data: ActionData::Simple { result },
         }
     }
 
+    /// wrap a result received from a remote daemon (`cwm --host`)
+    ///
+    /// the remote `action` field becomes the result's action so output
+    /// formatting matches a local run
+    pub fn remote(
+        fallback_action: &'static str,
+        mut fields: serde_json::Map<String, serde_json::Value>,
+    ) -> Self {
+        let action = match fields.remove("action") {
+            Some(serde_json::Value::String(action)) => Cow::Owned(action),
+            _ => Cow::Borrowed(fallback_action),
+        };
+
+        Self {
+            action,
+            data: ActionData::Remote(fields),
+        }
+    }
+
     pub fn undo(app: AppData, restored: WindowData) -> Self {
         Self {
-            action: "undo",
+            action: Cow::Borrowed("undo"),
             data: ActionData::Undo { app, restored },
         }
     }
 
     pub fn redo(app: AppData, restored: WindowData) -> Self {
         Self {
-            action: "redo",
+            action: Cow::Borrowed("redo"),
             data: ActionData::Redo { app, restored },
         }
     }
@@ -4,8 +4,12 @@ use clap::{Parser, Subcommand, ValueEnum};
 use std::path::PathBuf;
 
 use crate::actions::{self, Command, ExecutionContext};
+use crate::config::export::Target as ExportTarget;
+use crate::config::format::Format;
+use crate::config::import::Source as ImportSource;
+use crate::config::lint::Severity;
 use crate::config::{self, Shortcut};
-use crate::daemon::hotkeys;
+use crate::daemon::{hotkeys, keyboard};
 use crate::display;
 
 use super::convert::resolve_launch_flags;
@@ -32,6 +36,10 @@ pub struct Cli {
     #[arg(short, long, global = true)]
     pub quiet: bool,
 
+    /// Run the command on a paired remote host (see 'cwm remote')
+    #[arg(long, global = true, value_name = "HOST")]
+    pub host: Option<String>,
+
     #[command(subcommand)]
     pub command: Commands,
 }
@@ -289,6 +297,24 @@ pub enum Commands {
         #[command(subcommand)]
         command: HistoryCommands,
     },
+
+    /// Control other machines running cwm, or let them control this one
+    Remote {
+        #[command(subcommand)]
+        command: RemoteCommands,
+    },
+
+    /// Switch between named config profiles
+    Profile {
+        #[command(subcommand)]
+        command: ProfileCommands,
+    },
+
+    /// Share shortcuts with other hotkey tools, print a cheat sheet or check for conflicts
+    Shortcuts {
+        #[command(subcommand)]
+        command: ShortcutsCommands,
+    },
 }
 
 #[derive(Subcommand)]
@@ -319,6 +345,12 @@ pub enum DaemonCommands {
     },
     /// Uninstall daemon from login items
     Uninstall,
+    /// Show daemon metrics (requests, action latency, hotkeys, events)
+    Stats {
+        /// Print metrics in the Prometheus text format
+        #[arg(long)]
+        prometheus: bool,
+    },
     /// Run the daemon in the foreground (used internally)
     #[command(hide = true)]
     RunForeground {
@@ -331,22 +363,128 @@ pub enum DaemonCommands {
 #[derive(Subcommand)]
 pub enum ConfigCommands {
     /// Show current configuration
-    Show,
+    Show {
+        /// Show the configuration merged with all included files
+        #[arg(long)]
+        resolved: bool,
+
+        /// Like --resolved, annotating which file each entry came from
+        #[arg(long)]
+        origin: bool,
+    },
     /// Show configuration file path
     Path,
+    /// Print a configuration value
+    Get {
+        /// Configuration path (e.g., "settings.animate", "shortcuts[0].keys")
+        key: String,
+    },
     /// Set a configuration value
     Set {
-        /// Configuration key (e.g., "behavior.launch_if_not_running")
+        /// Configuration path (e.g., "settings.animate", "app_rules[app=Slack].delay_ms",
+        /// "display_aliases.office[+]")
         key: String,
-        /// Value to set
+        /// Value to set (JSON5 for objects and arrays)
         value: String,
     },
+    /// Remove a configuration value, restoring its default
+    Unset {
+        /// Configuration path (e.g., "settings.delay_ms", "shortcuts[2]")
+        key: String,
+    },
+    /// Append a shortcut or app rule
+    Add {
+        #[command(subcommand)]
+        command: ConfigAddCommands,
+    },
     /// Reset configuration to defaults
     Reset,
     /// Show the default configuration with example shortcuts and rules
     Default,
     /// Verify configuration file for errors
     Verify,
+    /// Find conflicting hotkeys, shadowed rules and unused definitions
+    Lint {
+        /// Only report findings at or above this severity (info, warning, error)
+        #[arg(long, default_value = "info")]
+        severity: Severity,
+    },
+    /// Upgrade the configuration file to the current format version
+    Migrate {
+        /// Show the changes as a diff without writing the file
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Import shortcuts from Rectangle, Spectacle, skhd or Hammerspoon
+    Import {
+        /// Window manager the file was exported from (rectangle, spectacle, skhd, hammerspoon)
+        #[arg(long)]
+        from: ImportSource,
+        /// Exported config file (e.g. RectangleConfig.json, ~/.skhdrc, ~/.hammerspoon/init.lua)
+        file: String,
+        /// Show what would be imported without writing the config
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Print the configuration file in another format (json, yaml, toml)
+    Convert {
+        /// Target format: json, yaml or toml
+        #[arg(long)]
+        to: Format,
+        /// Write config.<format> and move the current file to a .bak backup
+        #[arg(long)]
+        write: bool,
+    },
+    /// Print the JSON schema for the configuration file
+    Schema,
+}
+
+#[derive(Subcommand)]
+pub enum ConfigAddCommands {
+    /// Append a global shortcut
+    Shortcut {
+        /// Hotkey combination (e.g., "ctrl+alt+s")
+        #[arg(long)]
+        keys: String,
+
+        /// Action to run (e.g., "focus", "maximize", "move:next")
+        #[arg(long)]
+        action: String,
+
+        /// Target application
+        #[arg(long)]
+        app: Option<String>,
+
+        /// Launch the app if not running
+        #[arg(long, conflicts_with = "no_launch")]
+        launch: bool,
+
+        /// Never launch the app, even if settings.launch is set
+        #[arg(long, conflicts_with = "launch")]
+        no_launch: bool,
+
+        /// Condition as JSON5 (e.g., "{time: '9AM-5PM'}")
+        #[arg(long)]
+        when: Option<String>,
+    },
+    /// Append an app rule
+    Rule {
+        /// Application name
+        #[arg(long)]
+        app: String,
+
+        /// Action to run when the app launches
+        #[arg(long)]
+        action: String,
+
+        /// Delay in milliseconds before running the action
+        #[arg(long)]
+        delay_ms: Option<u64>,
+
+        /// Condition as JSON5
+        #[arg(long)]
+        when: Option<String>,
+    },
 }
 
 #[derive(Subcommand)]
@@ -417,6 +555,69 @@ pub enum HistoryCommands {
     Clear,
 }
 
+#[derive(Subcommand)]
+pub enum ProfileCommands {
+    /// Activate a profile (applies immediately if the daemon is running)
+    Use {
+        /// Profile name from the 'profiles' config section
+        name: String,
+    },
+    /// Deactivate the current profile and go back to the base config
+    Clear,
+    /// List profiles and show which one is active
+    List,
+}
+
+#[derive(Subcommand)]
+pub enum ShortcutsCommands {
+    /// Print the shortcuts as skhd or Karabiner-Elements bindings, or as a cheat sheet
+    Export {
+        /// Output format: skhd, karabiner, markdown or html
+        #[arg(long)]
+        format: ExportTarget,
+    },
+    /// List shortcuts whose hotkeys macOS, common apps or other shortcuts already use
+    Conflicts,
+}
+
+#[derive(Subcommand)]
+pub enum RemoteCommands {
+    /// Create a one-time pairing code for another machine (requires the daemon with remote enabled)
+    Pair,
+    /// Pair with a remote host using the code from 'cwm remote pair'
+    Add {
+        /// Name to use with --host
+        name: String,
+
+        /// Hostname or IP address of the remote machine
+        address: String,
+
+        /// One-time pairing code shown by 'cwm remote pair'
+        #[arg(long)]
+        code: String,
+
+        /// Port of the remote listener
+        #[arg(long, default_value_t = config::DEFAULT_REMOTE_PORT)]
+        port: u16,
+
+        /// Certificate fingerprint shown by 'cwm remote pair'
+        #[arg(long)]
+        fingerprint: String,
+    },
+    /// List known hosts and paired clients
+    List,
+    /// Forget a known host
+    Remove {
+        /// Host name given to 'cwm remote add'
+        name: String,
+    },
+    /// Revoke a paired client's access to this machine
+    Revoke {
+        /// Client name or fingerprint (at least 8 characters)
+        client: String,
+    },
+}
+
 #[derive(Subcommand)]
 pub enum GetCommands {
     /// Get info about the currently focused window
@@ -604,9 +805,35 @@ fn handle_action_error(err: crate::actions::ActionError, output_mode: &OutputMod
     std::process::exit(err.code);
 }
 
+/// whether a command can run on a remote host with --host
+fn supports_remote_host(command: &Commands) -> bool {
+    matches!(
+        command,
+        Commands::Focus { .. }
+            | Commands::Maximize { .. }
+            | Commands::Move { .. }
+            | Commands::Resize { .. }
+            | Commands::Kill { .. }
+            | Commands::Close { .. }
+            | Commands::List { .. }
+            | Commands::Get { .. }
+            | Commands::Version
+            | Commands::Undo
+            | Commands::Redo
+            | Commands::History { .. }
+    )
+}
+
 pub fn execute(cli: Cli) -> Result<()> {
     let config_path = cli.config.as_deref();
     let output_mode = OutputMode::from_flags(cli.json, cli.no_json, cli.quiet, false, false);
+    let host = cli.host.clone();
+
+    if host.is_some() && !supports_remote_host(&cli.command) {
+        return Err(anyhow!(
+            "--host is only supported for window, list, get, version and history commands"
+        ));
+    }
 
     match cli.command {
         Commands::Focus {
@@ -622,7 +849,8 @@ pub fn execute(cli: Cli) -> Result<()> {
                 app: apps,
                 launch: resolve_launch_flags(launch, no_launch),
             };
-            let ctx = ExecutionContext::new_with_verbose(&config, true, verbose);
+            let ctx = ExecutionContext::new_with_verbose(&config, true, verbose)
+                .with_remote_host(host.clone());
 
             match actions::execute(cmd, &ctx) {
                 Ok(result) => {
@@ -649,7 +877,8 @@ pub fn execute(cli: Cli) -> Result<()> {
                 app: app.map(|a| vec![a]).unwrap_or_default(),
                 launch: resolve_launch_flags(launch, no_launch),
             };
-            let ctx = ExecutionContext::new_with_verbose(&config, true, verbose);
+            let ctx = ExecutionContext::new_with_verbose(&config, true, verbose)
+                .with_remote_host(host.clone());
 
             match actions::execute(cmd, &ctx) {
                 Ok(result) => {
@@ -693,7 +922,8 @@ pub fn execute(cli: Cli) -> Result<()> {
                 display: display_target,
                 launch: resolve_launch_flags(launch, no_launch),
             };
-            let ctx = ExecutionContext::new_with_verbose(&config, true, verbose);
+            let ctx = ExecutionContext::new_with_verbose(&config, true, verbose)
+                .with_remote_host(host.clone());
 
             match actions::execute(cmd, &ctx) {
                 Ok(result) => {
@@ -726,7 +956,8 @@ pub fn execute(cli: Cli) -> Result<()> {
                 overflow,
                 launch: resolve_launch_flags(launch, no_launch),
             };
-            let ctx = ExecutionContext::new_with_verbose(&config, true, verbose);
+            let ctx = ExecutionContext::new_with_verbose(&config, true, verbose)
+                .with_remote_host(host.clone());
 
             match actions::execute(cmd, &ctx) {
                 Ok(result) => {
@@ -753,7 +984,8 @@ pub fn execute(cli: Cli) -> Result<()> {
                 force,
                 wait,
             };
-            let ctx = ExecutionContext::new_with_verbose(&config, true, verbose);
+            let ctx = ExecutionContext::new_with_verbose(&config, true, verbose)
+                .with_remote_host(host.clone());
 
             match actions::execute(cmd, &ctx) {
                 Ok(result) => {
@@ -771,7 +1003,8 @@ pub fn execute(cli: Cli) -> Result<()> {
             let apps = resolve_app_names(&apps)?;
 
             let cmd = Command::Close { app: apps };
-            let ctx = ExecutionContext::new_with_verbose(&config, true, verbose);
+            let ctx = ExecutionContext::new_with_verbose(&config, true, verbose)
+                .with_remote_host(host.clone());
 
             match actions::execute(cmd, &ctx) {
                 Ok(result) => {
@@ -792,9 +1025,19 @@ pub fn execute(cli: Cli) -> Result<()> {
                 no_launch,
                 yes,
             } => {
+                // keys are named after the configured keyboard layout
+                if let Ok(config) = config::load_main_with_override(config_path) {
+                    keyboard::select(config.settings.keyboard_layout.as_deref());
+                }
+
                 // record the hotkey
-                let keys = hotkeys::record_hotkey()?;
+                let hotkey = hotkeys::record_hotkey()?;
+                let keys = hotkey.to_string();
                 println!("\nDetected: {}", keys);
+                let physical = hotkey.physical().to_string();
+                if physical != keys {
+                    println!("Physical: {}", physical);
+                }
 
                 // if no action specified, just print the keys and exit
                 if action.is_none() {
@@ -830,6 +1073,7 @@ pub fn execute(cli: Cli) -> Result<()> {
                     app: app.clone(),
                     launch: None,
                     when: None,
+                    ..Default::default()
                 };
 
                 if launch {
@@ -844,36 +1088,48 @@ pub fn execute(cli: Cli) -> Result<()> {
                 println!("\nShortcut to add:\n{}", json);
 
                 // load config and check for duplicates
-                let mut config = config::load_with_override(config_path)?;
+                let mut config = config::load_main_with_override(config_path)?;
                 let existing = config
                     .shortcuts
                     .iter()
                     .position(|s| s.keys.to_lowercase() == keys.to_lowercase());
 
-                if let Some(idx) = existing {
+                // hotkeys macOS, common apps or other shortcuts already use
+                let conflicts = config::conflicts::check(&config, &shortcut, existing);
+                for conflict in &conflicts {
+                    println!("\nWarning: {}", conflict);
+                }
+
+                let prompt = if let Some(idx) = existing {
                     let existing_shortcut = &config.shortcuts[idx];
                     println!(
                         "\nWarning: '{}' is already bound to '{}'",
                         keys, existing_shortcut.action
                     );
+                    Some("Overwrite? [y/N]: ")
+                } else if !conflicts.is_empty() {
+                    Some("Save anyway? [y/N]: ")
+                } else {
+                    None
+                };
 
-                    if !yes {
-                        print!("Overwrite? [y/N]: ");
-                        use std::io::{self, Write};
-                        io::stdout().flush()?;
+                if let (Some(prompt), false) = (prompt, yes) {
+                    print!("{}", prompt);
+                    use std::io::{self, Write};
+                    io::stdout().flush()?;
 
-                        let mut input = String::new();
-                        io::stdin().read_line(&mut input)?;
+                    let mut input = String::new();
+                    io::stdin().read_line(&mut input)?;
 
-                        if !input.trim().eq_ignore_ascii_case("y") {
-                            println!("Cancelled.");
-                            return Ok(());
-                        }
+                    if !input.trim().eq_ignore_ascii_case("y") {
+                        println!("Cancelled.");
+                        return Ok(());
                     }
+                }
 
-                    config.shortcuts[idx] = shortcut;
-                } else {
-                    config.shortcuts.push(shortcut);
+                match existing {
+                    Some(idx) => config.shortcuts[idx] = shortcut,
+                    None => config.shortcuts.push(shortcut),
                 }
 
                 // save config
@@ -909,8 +1165,11 @@ pub fn execute(cli: Cli) -> Result<()> {
                     } else if !output_mode.is_quiet() {
                         let value = serde_json::to_value(&result).unwrap_or_default();
                         if let Some(res) = value.get("result") {
-                            if let Some(status) = res.get("status").and_then(|v| v.as_str()) {
-                                match result.action {
+                            if result.action == "daemon_stats" {
+                                print_daemon_stats(res);
+                            } else if let Some(status) = res.get("status").and_then(|v| v.as_str())
+                            {
+                                match result.action.as_ref() {
                                     "daemon_status" => {
                                         let running = res
                                             .get("running")
@@ -962,17 +1221,30 @@ pub fn execute(cli: Cli) -> Result<()> {
         }
 
         Commands::Config { command } => {
-            let config = config::load_with_override(config_path)?;
+            // config commands report outdated files instead of rewriting them
+            let config = config::load_in_memory_with_override(config_path)?;
             let cmd = command.to_command();
             let ctx = ExecutionContext::cli_with_config_path(&config, false, config_path);
 
             match actions::execute(cmd, &ctx) {
                 Ok(result) => {
-                    if output_mode.is_json() {
+                    if result.action == "config_schema" {
+                        // already JSON: printed as-is in every mode so it can be redirected
+                        // to a file (`cwm config schema > config.schema.json`)
+                        let value = serde_json::to_value(&result).unwrap_or_default();
+                        if !output_mode.is_quiet() {
+                            if let Some(schema) = value.get("result") {
+                                println!(
+                                    "{}",
+                                    serde_json::to_string_pretty(schema).unwrap_or_default()
+                                );
+                            }
+                        }
+                    } else if output_mode.is_json() {
                         output::print_json(&result);
                     } else if !output_mode.is_quiet() {
                         let value = serde_json::to_value(&result).unwrap_or_default();
-                        match result.action {
+                        match result.action.as_ref() {
                             "config_show" | "config_default" => {
                                 // pretty print the config JSON
                                 if let Some(res) = value.get("result") {
@@ -996,9 +1268,194 @@ pub fn execute(cli: Cli) -> Result<()> {
                                     println!("Set {} = {}", key, val);
                                 }
                             }
+                            "config_get" => {
+                                if let Some(res) = value.get("result").and_then(|r| r.get("value"))
+                                {
+                                    match res {
+                                        serde_json::Value::String(s) => println!("{}", s),
+                                        other => println!(
+                                            "{}",
+                                            serde_json::to_string_pretty(other).unwrap_or_default()
+                                        ),
+                                    }
+                                }
+                            }
+                            "config_unset" => {
+                                if let Some(res) = value.get("result") {
+                                    let key = res.get("key").and_then(|v| v.as_str()).unwrap_or("");
+                                    println!("Unset {}", key);
+                                }
+                            }
+                            "config_add" => {
+                                if let Some(res) = value.get("result") {
+                                    let list =
+                                        res.get("list").and_then(|v| v.as_str()).unwrap_or("");
+                                    let index = res.get("index").and_then(|v| v.as_u64());
+                                    match index {
+                                        Some(i) => println!("Added {}[{}]", list, i),
+                                        None => println!("Added to {}", list),
+                                    }
+                                }
+                            }
                             "config_reset" => {
                                 println!("Configuration reset to defaults");
                             }
+                            "config_migrate" => {
+                                if let Some(res) = value.get("result") {
+                                    let from =
+                                        res.get("from").and_then(|v| v.as_u64()).unwrap_or(0);
+                                    let to = res.get("to").and_then(|v| v.as_u64()).unwrap_or(0);
+                                    let path =
+                                        res.get("path").and_then(|v| v.as_str()).unwrap_or("");
+                                    let dry_run = res
+                                        .get("dry_run")
+                                        .and_then(|v| v.as_bool())
+                                        .unwrap_or(false);
+
+                                    if from == to {
+                                        println!("Configuration is already at version {}", to);
+                                    } else if dry_run {
+                                        println!(
+                                            "Would migrate {} from version {} to {}:",
+                                            path, from, to
+                                        );
+                                        println!();
+                                        if let Some(diff) = res.get("diff").and_then(|v| v.as_str())
+                                        {
+                                            print!("{}", diff);
+                                        }
+                                    } else {
+                                        let backup = res
+                                            .get("backup")
+                                            .and_then(|v| v.as_str())
+                                            .unwrap_or("");
+                                        println!(
+                                            "Migrated {} from version {} to {} (backup: {})",
+                                            path, from, to, backup
+                                        );
+                                    }
+                                }
+                            }
+                            "config_import" => {
+                                if let Some(res) = value.get("result") {
+                                    let field = |v: &serde_json::Value, key: &str| {
+                                        v.get(key)
+                                            .and_then(|v| v.as_str())
+                                            .unwrap_or("")
+                                            .to_string()
+                                    };
+                                    let list = |key: &str| {
+                                        res.get(key)
+                                            .and_then(|v| v.as_array())
+                                            .cloned()
+                                            .unwrap_or_default()
+                                    };
+                                    let added = list("added");
+                                    let skipped = list("skipped");
+                                    let dry_run = res
+                                        .get("dry_run")
+                                        .and_then(|v| v.as_bool())
+                                        .unwrap_or(false);
+
+                                    println!(
+                                        "{} {} shortcut(s) from {} into {}",
+                                        if dry_run { "Would import" } else { "Imported" },
+                                        added.len(),
+                                        field(res, "file"),
+                                        field(res, "path")
+                                    );
+                                    for shortcut in &added {
+                                        // imported shortcuts run an action or a cycle
+                                        let shortcut: Shortcut =
+                                            serde_json::from_value(shortcut.clone())
+                                                .unwrap_or_default();
+                                        println!(
+                                            "  {} -> {}",
+                                            shortcut.keys,
+                                            config::conflicts::describe_shortcut(&shortcut)
+                                        );
+                                    }
+                                    if !skipped.is_empty() {
+                                        println!();
+                                        println!("Skipped {}:", skipped.len());
+                                        for entry in &skipped {
+                                            println!(
+                                                "  {}: {}",
+                                                field(entry, "entry"),
+                                                field(entry, "reason")
+                                            );
+                                        }
+                                    }
+                                }
+                            }
+                            "config_convert" => {
+                                if let Some(res) = value.get("result") {
+                                    let field = |key: &str| {
+                                        res.get(key).and_then(|v| v.as_str()).unwrap_or("")
+                                    };
+                                    if res.get("written").and_then(|v| v.as_bool()) == Some(true) {
+                                        println!(
+                                            "Converted {} to {} (backup: {})",
+                                            field("from"),
+                                            field("to"),
+                                            field("backup")
+                                        );
+                                    } else {
+                                        // printed as-is so it can be redirected to a file
+                                        print!("{}", field("content"));
+                                    }
+                                }
+                            }
+                            "config_lint" => {
+                                if let Some(res) = value.get("result") {
+                                    let path =
+                                        res.get("path").and_then(|v| v.as_str()).unwrap_or("");
+                                    let findings = res
+                                        .get("findings")
+                                        .and_then(|v| v.as_array())
+                                        .cloned()
+                                        .unwrap_or_default();
+
+                                    if findings.is_empty() {
+                                        println!("✓ No problems found: {}", path);
+                                        return Ok(());
+                                    }
+
+                                    let field = |f: &serde_json::Value, key: &str| {
+                                        f.get(key)
+                                            .and_then(|v| v.as_str())
+                                            .unwrap_or("")
+                                            .to_string()
+                                    };
+                                    for finding in &findings {
+                                        println!(
+                                            "{}[{}] {}: {}",
+                                            field(finding, "severity"),
+                                            field(finding, "code"),
+                                            field(finding, "path"),
+                                            field(finding, "message")
+                                        );
+                                    }
+
+                                    let count = |key: &str| {
+                                        res.get("counts")
+                                            .and_then(|c| c.get(key))
+                                            .and_then(|v| v.as_u64())
+                                            .unwrap_or(0)
+                                    };
+                                    println!();
+                                    println!(
+                                        "{} error(s), {} warning(s), {} info: {}",
+                                        count("error"),
+                                        count("warning"),
+                                        count("info"),
+                                        path
+                                    );
+                                    if count("error") > 0 {
+                                        return Err(anyhow!("configuration lint failed"));
+                                    }
+                                }
+                            }
                             "config_verify" => {
                                 if let Some(res) = value.get("result") {
                                     let valid =
@@ -1006,6 +1463,18 @@ pub fn execute(cli: Cli) -> Result<()> {
                                     let path =
                                         res.get("path").and_then(|v| v.as_str()).unwrap_or("");
 
+                                    let warnings: Vec<&str> = res
+                                        .get("warnings")
+                                        .and_then(|v| v.as_array())
+                                        .map(|arr| arr.iter().filter_map(|w| w.as_str()).collect())
+                                        .unwrap_or_default();
+                                    for warning in &warnings {
+                                        eprintln!("warning: {}", warning);
+                                    }
+                                    if !warnings.is_empty() {
+                                        eprintln!();
+                                    }
+
                                     if valid {
                                         println!("✓ Configuration is valid: {}", path);
                                     } else {
@@ -1092,7 +1561,8 @@ pub fn execute(cli: Cli) -> Result<()> {
                 resource: action_resource,
                 detailed,
             };
-            let ctx = ExecutionContext::new_with_verbose(&config, true, false);
+            let ctx = ExecutionContext::new_with_verbose(&config, true, false)
+                .with_remote_host(host.clone());
 
             let result = actions::execute(cmd, &ctx).map_err(|e| anyhow!("{}", e.message))?;
 
@@ -1263,7 +1733,8 @@ pub fn execute(cli: Cli) -> Result<()> {
                 }
             };
 
-            let ctx = ExecutionContext::new_with_verbose(&config, true, false);
+            let ctx = ExecutionContext::new_with_verbose(&config, true, false)
+                .with_remote_host(host.clone());
 
             match actions::execute(cmd, &ctx) {
                 Ok(result) => {
@@ -1343,7 +1814,8 @@ pub fn execute(cli: Cli) -> Result<()> {
         Commands::Version => {
             let config = config::load_with_override(config_path)?;
             let cmd = Command::Version;
-            let ctx = ExecutionContext::new_with_verbose(&config, true, false);
+            let ctx = ExecutionContext::new_with_verbose(&config, true, false)
+                .with_remote_host(host.clone());
 
             match actions::execute(cmd, &ctx) {
                 Ok(result) => {
@@ -1494,7 +1966,7 @@ pub fn execute(cli: Cli) -> Result<()> {
                         output::print_json(&result);
                     } else if !output_mode.is_quiet() {
                         let value = serde_json::to_value(&result).unwrap_or_default();
-                        match result.action {
+                        match result.action.as_ref() {
                             "spotlight_install" => {
                                 if let Some(res) = value.get("result") {
                                     let status =
@@ -1636,7 +2108,11 @@ pub fn execute(cli: Cli) -> Result<()> {
 
         Commands::Events { command } => match command {
             EventsCommands::Listen { event, app, format } => {
-                super::events::listen(event, app, format, &output_mode)
+                let exit_code = super::events::listen(event, app, format, &output_mode)?;
+                if exit_code != 0 {
+                    std::process::exit(exit_code);
+                }
+                Ok(())
             }
             EventsCommands::Wait {
                 event,
@@ -1654,7 +2130,8 @@ pub fn execute(cli: Cli) -> Result<()> {
         Commands::Undo => {
             let config = config::load_with_override(config_path)?;
             let cmd = Command::Undo;
-            let ctx = ExecutionContext::cli_with_config_path(&config, false, config_path);
+            let ctx = ExecutionContext::cli_with_config_path(&config, false, config_path)
+                .with_remote_host(host.clone());
 
             match actions::execute(cmd, &ctx) {
                 Ok(result) => {
@@ -1701,7 +2178,8 @@ pub fn execute(cli: Cli) -> Result<()> {
         Commands::Redo => {
             let config = config::load_with_override(config_path)?;
             let cmd = Command::Redo;
-            let ctx = ExecutionContext::cli_with_config_path(&config, false, config_path);
+            let ctx = ExecutionContext::cli_with_config_path(&config, false, config_path)
+                .with_remote_host(host.clone());
 
             match actions::execute(cmd, &ctx) {
                 Ok(result) => {
@@ -1748,7 +2226,8 @@ pub fn execute(cli: Cli) -> Result<()> {
         Commands::History { command } => {
             let config = config::load_with_override(config_path)?;
             let cmd = command.to_command();
-            let ctx = ExecutionContext::cli_with_config_path(&config, false, config_path);
+            let ctx = ExecutionContext::cli_with_config_path(&config, false, config_path)
+                .with_remote_host(host.clone());
 
             match actions::execute(cmd, &ctx) {
                 Ok(result) => {
@@ -1756,7 +2235,7 @@ pub fn execute(cli: Cli) -> Result<()> {
                         output::print_json(&result);
                     } else if !output_mode.is_quiet() {
                         let value = serde_json::to_value(&result).unwrap_or_default();
-                        match result.action {
+                        match result.action.as_ref() {
                             "history_list" => {
                                 if let Some(res) = value.get("result") {
                                     let undo_count = res
@@ -1784,6 +2263,289 @@ pub fn execute(cli: Cli) -> Result<()> {
                 Err(err) => handle_action_error(err, &output_mode),
             }
         }
+
+        Commands::Remote { command } => {
+            let config = config::load_with_override(config_path)?;
+            let cmd = command.to_command();
+            let ctx = ExecutionContext::cli_with_config_path(&config, false, config_path);
+
+            match actions::execute(cmd, &ctx) {
+                Ok(result) => {
+                    if output_mode.is_json() {
+                        output::print_json(&result);
+                    } else if !output_mode.is_quiet() {
+                        let value = serde_json::to_value(&result).unwrap_or_default();
+                        let res = value.get("result").cloned().unwrap_or_default();
+                        print_remote_result(result.action.as_ref(), &res);
+                    }
+                    Ok(())
+                }
+                Err(err) => handle_action_error(err, &output_mode),
+            }
+        }
+
+        Commands::Profile { command } => {
+            let config = config::load_with_override(config_path)?;
+            let cmd = command.to_command();
+            let ctx = ExecutionContext::cli_with_config_path(&config, false, config_path);
+
+            match actions::execute(cmd, &ctx) {
+                Ok(result) => {
+                    if output_mode.is_json() {
+                        output::print_json(&result);
+                    } else if !output_mode.is_quiet() {
+                        let value = serde_json::to_value(&result).unwrap_or_default();
+                        let res = value.get("result").cloned().unwrap_or_default();
+                        print_profile_result(result.action.as_ref(), &res);
+                    }
+                    Ok(())
+                }
+                Err(err) => handle_action_error(err, &output_mode),
+            }
+        }
+
+        Commands::Shortcuts { command } => {
+            let config = config::load_with_override(config_path)?;
+            let cmd = command.to_command();
+            let ctx = ExecutionContext::cli_with_config_path(&config, false, config_path);
+
+            match actions::execute(cmd, &ctx) {
+                Ok(result) => {
+                    if output_mode.is_json() {
+                        output::print_json(&result);
+                    } else if !output_mode.is_quiet() {
+                        let value = serde_json::to_value(&result).unwrap_or_default();
+                        let res = value.get("result").cloned().unwrap_or_default();
+                        if result.action == "shortcuts_conflicts" {
+                            print_shortcuts_conflicts(&res);
+                        } else {
+                            print_shortcuts_export(&res);
+                        }
+                    }
+                    Ok(())
+                }
+                Err(err) => handle_action_error(err, &output_mode),
+            }
+        }
+    }
+}
+
+/// print the text output of `cwm shortcuts export`
+fn print_shortcuts_export(res: &serde_json::Value) {
+    // printed as-is so it can be redirected to a file; skipped shortcuts go to
+    // stderr to keep them out of it
+    print!(
+        "{}",
+        res.get("content").and_then(|v| v.as_str()).unwrap_or("")
+    );
+
+    let empty = Vec::new();
+    let skipped = res
+        .get("skipped")
+        .and_then(|v| v.as_array())
+        .unwrap_or(&empty);
+    if skipped.is_empty() {
+        return;
+    }
+    eprintln!("Skipped {}:", skipped.len());
+    for entry in skipped {
+        let field = |key: &str| entry.get(key).and_then(|v| v.as_str()).unwrap_or("");
+        eprintln!("  {}: {}", field("entry"), field("reason"));
+    }
+}
+
+/// print the text output of `cwm shortcuts conflicts`
+fn print_shortcuts_conflicts(res: &serde_json::Value) {
+    let empty = Vec::new();
+    let conflicts = res
+        .get("conflicts")
+        .and_then(|v| v.as_array())
+        .unwrap_or(&empty);
+    if conflicts.is_empty() {
+        println!("No conflicts found");
+        return;
+    }
+
+    for conflict in conflicts {
+        let field = |key: &str| conflict.get(key).and_then(|v| v.as_str()).unwrap_or("");
+        println!("{}: {}", field("path"), field("message"));
+    }
+    println!("\n{} conflict(s)", conflicts.len());
+}
+
+/// print the text output of a `cwm profile` subcommand
+fn print_profile_result(action: &str, res: &serde_json::Value) {
+    let daemon_note = if res.get("daemon").and_then(|v| v.as_bool()) == Some(true) {
+        ""
+    } else {
+        " (takes effect when the daemon starts)"
+    };
+
+    match action {
+        "profile_use" => match res.get("profile").and_then(|v| v.as_str()) {
+            Some(name) => println!("Switched to profile '{}'{}", name, daemon_note),
+            None => println!("Switched to the base config{}", daemon_note),
+        },
+        "profile_list" => {
+            let empty = Vec::new();
+            let profiles = res
+                .get("profiles")
+                .and_then(|v| v.as_array())
+                .unwrap_or(&empty);
+
+            if profiles.is_empty() {
+                println!("No profiles configured");
+                return;
+            }
+
+            for profile in profiles {
+                let marker = if profile.get("active").and_then(|v| v.as_bool()) == Some(true) {
+                    "*"
+                } else {
+                    " "
+                };
+                println!(
+                    "{} {} ({} shortcuts, {} app rules)",
+                    marker,
+                    profile.get("name").and_then(|v| v.as_str()).unwrap_or(""),
+                    profile
+                        .get("shortcuts")
+                        .and_then(|v| v.as_u64())
+                        .unwrap_or(0),
+                    profile
+                        .get("app_rules")
+                        .and_then(|v| v.as_u64())
+                        .unwrap_or(0),
+                );
+            }
+        }
+        _ => println!(
+            "{}",
+            serde_json::to_string_pretty(res).unwrap_or_else(|_| "{}".to_string())
+        ),
+    }
+}
+
+/// print the result of `cwm daemon stats`, either Prometheus text or a summary
+fn print_daemon_stats(res: &serde_json::Value) {
+    use crate::daemon::metrics;
+
+    if let Some(text) = res.get("text").and_then(|v| v.as_str()) {
+        print!("{}", text);
+        return;
+    }
+
+    match serde_json::from_value::<metrics::StatsSnapshot>(res.clone()) {
+        Ok(stats) => print!("{}", metrics::render_text(&stats)),
+        Err(_) => println!(
+            "{}",
+            serde_json::to_string_pretty(res).unwrap_or_else(|_| "{}".to_string())
+        ),
+    }
+}
+
+/// print the text output of a `cwm remote` subcommand
+fn print_remote_result(action: &str, res: &serde_json::Value) {
+    use crate::remote::tls::short_fingerprint;
+
+    let str_field = |v: &serde_json::Value, key: &str| -> String {
+        v.get(key)
+            .and_then(|f| f.as_str())
+            .unwrap_or("")
+            .to_string()
+    };
+
+    match action {
+        "remote_pair" => {
+            let code = str_field(res, "code");
+            let fingerprint = str_field(res, "fingerprint");
+            let expires_in = res.get("expires_in").and_then(|v| v.as_u64()).unwrap_or(0);
+            let port = res.get("port").and_then(|v| v.as_u64()).unwrap_or(0);
+
+            println!("Pairing code: {}", code);
+            println!("Fingerprint:  {}", short_fingerprint(&fingerprint));
+            println!(
+                "Expires in {} minutes. On the other machine, run:",
+                expires_in / 60
+            );
+
+            let port_arg = if port == u64::from(config::DEFAULT_REMOTE_PORT) {
+                String::new()
+            } else {
+                format!(" --port {}", port)
+            };
+            println!(
+                "  cwm remote add <name> <address> --code {} --fingerprint {}{}",
+                code,
+                short_fingerprint(&fingerprint),
+                port_arg
+            );
+        }
+        "remote_add" => {
+            println!(
+                "Paired with {} ({}:{})",
+                str_field(res, "name"),
+                str_field(res, "address"),
+                res.get("port").and_then(|v| v.as_u64()).unwrap_or(0)
+            );
+        }
+        "remote_list" => {
+            let empty = Vec::new();
+            let hosts = res
+                .get("hosts")
+                .and_then(|v| v.as_array())
+                .unwrap_or(&empty);
+            let clients = res
+                .get("clients")
+                .and_then(|v| v.as_array())
+                .unwrap_or(&empty);
+
+            if hosts.is_empty() {
+                println!("No known hosts");
+            } else {
+                println!("Hosts:");
+                for host in hosts {
+                    println!(
+                        "  {}  {}:{}  {}",
+                        str_field(host, "name"),
+                        str_field(host, "address"),
+                        host.get("port").and_then(|v| v.as_u64()).unwrap_or(0),
+                        short_fingerprint(&str_field(host, "fingerprint"))
+                    );
+                }
+            }
+
+            if clients.is_empty() {
+                println!("No paired clients");
+            } else {
+                println!("Paired clients:");
+                for client in clients {
+                    println!(
+                        "  {}  {}  paired {}",
+                        str_field(client, "name"),
+                        short_fingerprint(&str_field(client, "fingerprint")),
+                        str_field(client, "paired_at")
+                    );
+                }
+            }
+        }
+        "remote_remove" => {
+            if let Some(host) = res.get("removed") {
+                println!("Removed host {}", str_field(host, "name"));
+            }
+        }
+        "remote_revoke" => {
+            if let Some(revoked) = res.get("revoked").and_then(|v| v.as_array()) {
+                for client in revoked {
+                    println!(
+                        "Revoked {} ({})",
+                        str_field(client, "name"),
+                        short_fingerprint(&str_field(client, "fingerprint"))
+                    );
+                }
+            }
+        }
+        _ => {}
     }
 }
 
@@ -2106,6 +2868,66 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_cli_parse_global_host() {
+        use clap::Parser;
+        let cli =
+            Cli::try_parse_from(["cwm", "--host", "lab-3", "focus", "--app", "Safari"]).unwrap();
+        assert_eq!(cli.host, Some("lab-3".to_string()));
+        assert!(supports_remote_host(&cli.command));
+
+        // global flags can also follow the subcommand
+        let cli = Cli::try_parse_from(["cwm", "undo", "--host", "lab-3"]).unwrap();
+        assert_eq!(cli.host, Some("lab-3".to_string()));
+    }
+
+    #[test]
+    fn test_supports_remote_host() {
+        use clap::Parser;
+        let cli = Cli::try_parse_from(["cwm", "daemon", "status"]).unwrap();
+        assert!(!supports_remote_host(&cli.command));
+
+        let cli = Cli::try_parse_from(["cwm", "remote", "list"]).unwrap();
+        assert!(!supports_remote_host(&cli.command));
+    }
+
+    #[test]
+    fn test_cli_parse_remote_add() {
+        use clap::Parser;
+        let cli = Cli::try_parse_from([
+            "cwm",
+            "remote",
+            "add",
+            "lab-3",
+            "10.0.0.5",
+            "--code",
+            "123456",
+            "--fingerprint",
+            "3f9a1c0b7d2e4a61",
+        ])
+        .unwrap();
+
+        match cli.command {
+            Commands::Remote {
+                command:
+                    RemoteCommands::Add {
+                        name,
+                        address,
+                        code,
+                        port,
+                        fingerprint,
+                    },
+            } => {
+                assert_eq!(name, "lab-3");
+                assert_eq!(address, "10.0.0.5");
+                assert_eq!(code, "123456");
+                assert_eq!(port, config::DEFAULT_REMOTE_PORT);
+                assert_eq!(fingerprint, "3f9a1c0b7d2e4a61");
+            }
+            _ => panic!("Expected Remote Add command"),
+        }
+    }
+
     #[test]
     fn test_cli_parse_maximize_without_app() {
         use clap::Parser;
@@ -2736,6 +3558,19 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_cli_parse_daemon_stats() {
+        use clap::Parser;
+        let cli = Cli::try_parse_from(["cwm", "daemon", "stats", "--prometheus"]).unwrap();
+
+        match cli.command {
+            Commands::Daemon {
+                command: DaemonCommands::Stats { prometheus },
+            } => assert!(prometheus),
+            _ => panic!("Expected Daemon Stats command"),
+        }
+    }
+
     #[test]
     fn test_cli_parse_daemon_start_with_options() {
         use clap::Parser;
@@ -2879,6 +3714,60 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_cli_parse_config_show_resolved() {
+        use clap::Parser;
+        let cli = Cli::try_parse_from(["cwm", "config", "show", "--resolved"]).unwrap();
+
+        match cli.command {
+            Commands::Config { command } => match command {
+                ConfigCommands::Show { resolved, origin } => {
+                    assert!(resolved);
+                    assert!(!origin);
+                }
+                _ => panic!("Expected Show subcommand"),
+            },
+            _ => panic!("Expected Config command"),
+        }
+    }
+
+    #[test]
+    fn test_cli_parse_profile_use() {
+        use clap::Parser;
+        let cli = Cli::try_parse_from(["cwm", "profile", "use", "presenting"]).unwrap();
+
+        match cli.command {
+            Commands::Profile {
+                command: ProfileCommands::Use { name },
+            } => assert_eq!(name, "presenting"),
+            _ => panic!("Expected Profile Use command"),
+        }
+    }
+
+    #[test]
+    fn test_cli_parse_shortcuts_export() {
+        use clap::Parser;
+        let cli =
+            Cli::try_parse_from(["cwm", "shortcuts", "export", "--format", "karabiner"]).unwrap();
+
+        match cli.command {
+            Commands::Shortcuts {
+                command: ShortcutsCommands::Export { format },
+            } => assert_eq!(format, ExportTarget::Karabiner),
+            _ => panic!("Expected Shortcuts Export command"),
+        }
+
+        assert!(Cli::try_parse_from(["cwm", "shortcuts", "export", "--format", "pdf"]).is_err());
+
+        let cli = Cli::try_parse_from(["cwm", "shortcuts", "conflicts"]).unwrap();
+        assert!(matches!(
+            cli.command,
+            Commands::Shortcuts {
+                command: ShortcutsCommands::Conflicts
+            }
+        ));
+    }
+
     #[test]
     fn test_cli_parse_config_set() {
         use clap::Parser;
@@ -2897,6 +3786,147 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_cli_parse_config_migrate() {
+        use clap::Parser;
+        let cli = Cli::try_parse_from(["cwm", "config", "migrate", "--dry-run"]).unwrap();
+        assert!(matches!(
+            cli.command,
+            Commands::Config {
+                command: ConfigCommands::Migrate { dry_run: true }
+            }
+        ));
+    }
+
+    #[test]
+    fn test_cli_parse_config_import() {
+        use clap::Parser;
+        let cli = Cli::try_parse_from([
+            "cwm",
+            "config",
+            "import",
+            "--from",
+            "rectangle",
+            "RectangleConfig.json",
+            "--dry-run",
+        ])
+        .unwrap();
+        match cli.command {
+            Commands::Config {
+                command:
+                    ConfigCommands::Import {
+                        from,
+                        file,
+                        dry_run,
+                    },
+            } => {
+                assert_eq!(from, ImportSource::Rectangle);
+                assert_eq!(file, "RectangleConfig.json");
+                assert!(dry_run);
+            }
+            _ => panic!("Expected Import subcommand"),
+        }
+
+        assert!(Cli::try_parse_from(["cwm", "config", "import", "--from", "magnet", "x"]).is_err());
+        assert!(Cli::try_parse_from(["cwm", "config", "import", "x"]).is_err());
+    }
+
+    #[test]
+    fn test_cli_parse_config_convert() {
+        use clap::Parser;
+        let cli =
+            Cli::try_parse_from(["cwm", "config", "convert", "--to", "toml", "--write"]).unwrap();
+        assert!(matches!(
+            cli.command,
+            Commands::Config {
+                command: ConfigCommands::Convert {
+                    to: Format::Toml,
+                    write: true
+                }
+            }
+        ));
+
+        assert!(Cli::try_parse_from(["cwm", "config", "convert"]).is_err());
+        assert!(Cli::try_parse_from(["cwm", "config", "convert", "--to", "ini"]).is_err());
+    }
+
+    #[test]
+    fn test_cli_parse_config_lint() {
+        use clap::Parser;
+        let cli = Cli::try_parse_from(["cwm", "config", "lint"]).unwrap();
+        assert!(matches!(
+            cli.command,
+            Commands::Config {
+                command: ConfigCommands::Lint {
+                    severity: Severity::Info
+                }
+            }
+        ));
+
+        let cli = Cli::try_parse_from(["cwm", "config", "lint", "--severity", "warning"]).unwrap();
+        assert!(matches!(
+            cli.command,
+            Commands::Config {
+                command: ConfigCommands::Lint {
+                    severity: Severity::Warning
+                }
+            }
+        ));
+
+        assert!(Cli::try_parse_from(["cwm", "config", "lint", "--severity", "fatal"]).is_err());
+    }
+
+    #[test]
+    fn test_cli_parse_config_schema() {
+        use clap::Parser;
+        let cli = Cli::try_parse_from(["cwm", "config", "schema"]).unwrap();
+        assert!(matches!(
+            cli.command,
+            Commands::Config {
+                command: ConfigCommands::Schema
+            }
+        ));
+    }
+
+    #[test]
+    fn test_cli_parse_config_add_rule() {
+        use clap::Parser;
+        let cli = Cli::try_parse_from([
+            "cwm",
+            "config",
+            "add",
+            "rule",
+            "--app",
+            "Slack",
+            "--action",
+            "maximize",
+            "--delay-ms",
+            "200",
+        ])
+        .unwrap();
+
+        match cli.command {
+            Commands::Config {
+                command:
+                    ConfigCommands::Add {
+                        command:
+                            ConfigAddCommands::Rule {
+                                app,
+                                action,
+                                delay_ms,
+                                when,
+                            },
+                    },
+            } => {
+                assert_eq!(app, "Slack");
+                assert_eq!(action, "maximize");
+                assert_eq!(delay_ms, Some(200));
+                assert!(when.is_none());
+            }
+            _ => panic!("Expected Config Add Rule command"),
+        }
+    }
+
     #[test]
     fn test_cli_parse_list_with_names() {
         use clap::Parser;
@@ -1,11 +1,13 @@
 //! conversion helpers for CLI commands
 
 use crate::actions::{
-    Command, ConfigCommand, DaemonCommand, EventsCommand, HistoryCommand, SpotlightCommand,
+    Command, ConfigCommand, DaemonCommand, EventsCommand, HistoryCommand, ProfileCommand,
+    RemoteCommand, ShortcutsCommand, SpotlightCommand,
 };
 
 use super::commands::{
-    ConfigCommands, DaemonCommands, EventsCommands, HistoryCommands, SpotlightCommands,
+    ConfigAddCommands, ConfigCommands, DaemonCommands, EventsCommands, HistoryCommands,
+    ProfileCommands, RemoteCommands, ShortcutsCommands, SpotlightCommands,
 };
 
 /// convert launch/no_launch flags to Option<bool>
@@ -34,6 +36,9 @@ impl DaemonCommands {
                 log: log.clone(),
             }),
             DaemonCommands::Uninstall => Command::Daemon(DaemonCommand::Uninstall),
+            DaemonCommands::Stats { prometheus } => Command::Daemon(DaemonCommand::Stats {
+                prometheus: *prometheus,
+            }),
             DaemonCommands::RunForeground { log } => Command::Daemon(DaemonCommand::Start {
                 log: log.clone(),
                 foreground: true,
@@ -46,15 +51,94 @@ impl ConfigCommands {
     /// convert CLI config command to unified Command enum
     pub fn to_command(&self) -> Command {
         match self {
-            ConfigCommands::Show => Command::Config(ConfigCommand::Show),
+            ConfigCommands::Show { resolved, origin } => Command::Config(ConfigCommand::Show {
+                resolved: *resolved,
+                origin: *origin,
+            }),
             ConfigCommands::Path => Command::Config(ConfigCommand::Path),
+            ConfigCommands::Get { key } => Command::Config(ConfigCommand::Get { key: key.clone() }),
             ConfigCommands::Set { key, value } => Command::Config(ConfigCommand::Set {
                 key: key.clone(),
                 value: value.clone(),
             }),
+            ConfigCommands::Unset { key } => {
+                Command::Config(ConfigCommand::Unset { key: key.clone() })
+            }
+            ConfigCommands::Add { command } => match command {
+                ConfigAddCommands::Shortcut {
+                    keys,
+                    action,
+                    app,
+                    launch,
+                    no_launch,
+                    when,
+                } => Command::Config(ConfigCommand::AddShortcut {
+                    keys: keys.clone(),
+                    action: action.clone(),
+                    app: app.clone(),
+                    launch: resolve_launch_flags(*launch, *no_launch),
+                    when: when.clone(),
+                }),
+                ConfigAddCommands::Rule {
+                    app,
+                    action,
+                    delay_ms,
+                    when,
+                } => Command::Config(ConfigCommand::AddRule {
+                    app: app.clone(),
+                    action: action.clone(),
+                    delay_ms: *delay_ms,
+                    when: when.clone(),
+                }),
+            },
             ConfigCommands::Reset => Command::Config(ConfigCommand::Reset),
             ConfigCommands::Default => Command::Config(ConfigCommand::Default),
             ConfigCommands::Verify => Command::Config(ConfigCommand::Verify),
+            ConfigCommands::Lint { severity } => Command::Config(ConfigCommand::Lint {
+                severity: *severity,
+            }),
+            ConfigCommands::Migrate { dry_run } => {
+                Command::Config(ConfigCommand::Migrate { dry_run: *dry_run })
+            }
+            ConfigCommands::Import {
+                from,
+                file,
+                dry_run,
+            } => Command::Config(ConfigCommand::Import {
+                from: *from,
+                file: file.clone(),
+                dry_run: *dry_run,
+            }),
+            ConfigCommands::Convert { to, write } => Command::Config(ConfigCommand::Convert {
+                to: *to,
+                write: *write,
+            }),
+            ConfigCommands::Schema => Command::Config(ConfigCommand::Schema),
+        }
+    }
+}
+
+impl ProfileCommands {
+    /// convert CLI profile command to unified Command enum
+    pub fn to_command(&self) -> Command {
+        match self {
+            ProfileCommands::Use { name } => {
+                Command::Profile(ProfileCommand::Use { name: name.clone() })
+            }
+            ProfileCommands::Clear => Command::Profile(ProfileCommand::Clear),
+            ProfileCommands::List => Command::Profile(ProfileCommand::List),
+        }
+    }
+}
+
+impl ShortcutsCommands {
+    /// convert CLI shortcuts command to unified Command enum
+    pub fn to_command(&self) -> Command {
+        match self {
+            ShortcutsCommands::Export { format } => {
+                Command::Shortcuts(ShortcutsCommand::Export { format: *format })
+            }
+            ShortcutsCommands::Conflicts => Command::Shortcuts(ShortcutsCommand::Conflicts),
         }
     }
 }
@@ -116,6 +200,35 @@ impl HistoryCommands {
     }
 }
 
+impl RemoteCommands {
+    /// convert CLI remote command to unified Command enum
+    pub fn to_command(&self) -> Command {
+        match self {
+            RemoteCommands::Pair => Command::Remote(RemoteCommand::Pair),
+            RemoteCommands::Add {
+                name,
+                address,
+                code,
+                port,
+                fingerprint,
+            } => Command::Remote(RemoteCommand::Add {
+                name: name.clone(),
+                address: address.clone(),
+                port: *port,
+                code: code.clone(),
+                fingerprint: fingerprint.clone(),
+            }),
+            RemoteCommands::List => Command::Remote(RemoteCommand::List),
+            RemoteCommands::Remove { name } => {
+                Command::Remote(RemoteCommand::Remove { name: name.clone() })
+            }
+            RemoteCommands::Revoke { client } => Command::Remote(RemoteCommand::Revoke {
+                client: client.clone(),
+            }),
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -156,10 +269,16 @@ mod tests {
 
     #[test]
     fn test_config_commands_to_command() {
-        let cmd = ConfigCommands::Show;
+        let cmd = ConfigCommands::Show {
+            resolved: true,
+            origin: false,
+        };
         assert!(matches!(
             cmd.to_command(),
-            Command::Config(ConfigCommand::Show)
+            Command::Config(ConfigCommand::Show {
+                resolved: true,
+                origin: false
+            })
         ));
 
         let cmd = ConfigCommands::Set {
@@ -8,15 +8,15 @@ use anyhow::{anyhow, Result};
 use crate::daemon::ipc;
 
 use super::exit_codes;
-use super::output::OutputMode;
+use super::output::{from_jsonrpc_code, OutputMode};
 
-/// listen for events and stream to stdout
+/// listen for events and stream to stdout; returns the exit code
 pub fn listen(
     event_filters: Vec<String>,
     app_filters: Vec<String>,
     format: Option<String>,
     output_mode: &OutputMode,
-) -> Result<()> {
+) -> Result<i32> {
     // check if daemon is running
     if !ipc::is_daemon_running() {
         return Err(anyhow!(
@@ -38,7 +38,7 @@ pub fn listen(
 
     let app_param = app_filters.join(",");
 
-    let request = if app_param.is_empty() {
+    let mut request = if app_param.is_empty() {
         serde_json::json!({
             "jsonrpc": "2.0",
             "method": "subscribe",
@@ -54,6 +54,10 @@ pub fn listen(
         })
     };
 
+    if let Some(token) = ipc::client_token() {
+        request["token"] = serde_json::json!(token);
+    }
+
     // send subscribe request
     use std::io::{BufRead, BufReader};
     stream.write_all(request.to_string().as_bytes())?;
@@ -74,6 +78,10 @@ pub fn listen(
             .get("message")
             .and_then(|m| m.as_str())
             .unwrap_or("Unknown error");
+        if is_access_denied(error) {
+            eprintln!("Subscription failed: {}", message);
+            return Ok(exit_codes::ACCESS_DENIED);
+        }
         return Err(anyhow!("Subscription failed: {}", message));
     }
 
@@ -126,7 +134,7 @@ pub fn listen(
         }
     }
 
-    Ok(())
+    Ok(exit_codes::SUCCESS)
 }
 
 /// wait for specific event(s) then exit
@@ -156,7 +164,7 @@ pub fn wait(
 
     let app_param = app_filters.join(",");
 
-    let request = if app_param.is_empty() {
+    let mut request = if app_param.is_empty() {
         serde_json::json!({
             "jsonrpc": "2.0",
             "method": "subscribe",
@@ -172,6 +180,15 @@ pub fn wait(
         })
     };
 
+    if let Some(token) = ipc::client_token() {
+        request["token"] = serde_json::json!(token);
+    }
+
+    // let the daemon end the subscription on its side once the timeout passes
+    if let Some(secs) = timeout {
+        request["deadline_ms"] = serde_json::json!(secs.saturating_mul(1000));
+    }
+
     // send subscribe request
     use std::io::{BufRead, BufReader};
     stream.write_all(request.to_string().as_bytes())?;
@@ -200,6 +217,10 @@ pub fn wait(
             .get("message")
             .and_then(|m| m.as_str())
             .unwrap_or("Unknown error");
+        if is_access_denied(error) {
+            eprintln!("Subscription failed: {}", message);
+            return Ok(exit_codes::ACCESS_DENIED);
+        }
         return Err(anyhow!("Subscription failed: {}", message));
     }
 
@@ -215,7 +236,12 @@ pub fn wait(
         let mut line = String::new();
         match reader.read_line(&mut line) {
             Ok(0) => {
-                // connection closed
+                // connection closed - the daemon ends subscriptions at their deadline
+                if let Some(duration) = timeout_duration {
+                    if start_time.elapsed() >= duration {
+                        return Ok(exit_codes::TIMEOUT);
+                    }
+                }
                 return Err(anyhow!("Connection to daemon closed unexpectedly"));
             }
             Ok(_) => {
@@ -251,6 +277,15 @@ pub fn wait(
     }
 }
 
+/// check whether a daemon error response is an access-control rejection
+fn is_access_denied(error: &serde_json::Value) -> bool {
+    error
+        .get("code")
+        .and_then(|c| c.as_i64())
+        .map(|c| from_jsonrpc_code(c as i32) == exit_codes::ACCESS_DENIED)
+        .unwrap_or(false)
+}
+
 /// output a single event
 fn output_event(event: &serde_json::Value, format: &Option<String>, use_json: bool) -> Result<()> {
     let mut stdout = std::io::stdout().lock();
@@ -373,4 +408,14 @@ mod tests {
         assert_eq!(format_event(&event, "{data.app}"), "{data.app}");
         assert_eq!(format_event(&event, "{data.pid}"), "5678");
     }
+
+    #[test]
+    fn test_is_access_denied() {
+        let denied = serde_json::json!({"code": -32010, "message": "access denied"});
+        let other = serde_json::json!({"code": -32001, "message": "boom"});
+
+        assert!(is_access_denied(&denied));
+        assert!(!is_access_denied(&other));
+        assert!(!is_access_denied(&serde_json::json!({})));
+    }
 }
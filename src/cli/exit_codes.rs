@@ -35,6 +35,12 @@ pub const DAEMON_NOT_RUNNING: i32 = 8;
 /// operation timed out
 pub const TIMEOUT: i32 = 9;
 
+/// daemon refused the request (peer uid or token not allowed)
+pub const ACCESS_DENIED: i32 = 10;
+
+/// request was cancelled by the client before it completed
+pub const CANCELLED: i32 = 11;
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -52,6 +58,8 @@ mod tests {
             DISPLAY_NOT_FOUND,
             DAEMON_NOT_RUNNING,
             TIMEOUT,
+            ACCESS_DENIED,
+            CANCELLED,
         ];
 
         // verify all codes are unique
@@ -171,7 +171,6 @@ fn to_jsonrpc_code(cwm_code: i32) -> i32 {
 }
 
 /// convert JSON-RPC error code back to cwm exit code
-#[allow(dead_code)]
 pub fn from_jsonrpc_code(rpc_code: i32) -> i32 {
     -(rpc_code + 32000)
 }
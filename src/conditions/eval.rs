@@ -37,6 +37,8 @@ pub struct EvalContext<'a> {
     pub target_app: Option<&'a str>,
     /// target window state
     pub target_window: Option<&'a WindowState>,
+    /// active config profile
+    pub profile: Option<&'a str>,
 }
 
 impl<'a> EvalContext<'a> {
@@ -53,6 +55,7 @@ impl<'a> EvalContext<'a> {
             focused_app: None,
             target_app: None,
             target_window: None,
+            profile: None,
         }
     }
 
@@ -73,6 +76,12 @@ impl<'a> EvalContext<'a> {
         self.target_window = window;
         self
     }
+
+    /// set the active profile
+    pub fn with_profile(mut self, profile: Option<&'a str>) -> Self {
+        self.profile = profile;
+        self
+    }
 }
 
 /// evaluate a condition against the given context
@@ -97,26 +106,39 @@ pub fn evaluate(condition: &Condition, ctx: &EvalContext) -> bool {
     }
 }
 
-fn evaluate_field(fc: &FieldCondition, ctx: &EvalContext) -> bool {
-    match fc.field.as_str() {
+type FieldEvaluator = fn(&FieldCondition, &EvalContext) -> bool;
+
+/// the evaluator for a field; every entry in `types::FIELDS` must have one
+fn field_evaluator(field: &str) -> Option<FieldEvaluator> {
+    let evaluator: FieldEvaluator = match field {
         // time conditions
-        "time" => evaluate_time(fc, ctx),
-        "time.day" => evaluate_time_day(fc),
+        "time" => evaluate_time,
+        "time.day" => evaluate_time_day,
 
         // display conditions
-        "display.count" => evaluate_display_count(fc, ctx),
-        "display.connected" => evaluate_display_connected(fc, ctx),
+        "display.count" => evaluate_display_count,
+        "display.connected" => evaluate_display_connected,
 
         // app conditions
-        "app" => evaluate_app(fc, ctx),
-        "app.running" => evaluate_app_running(fc, ctx),
-        "app.focused" => evaluate_app_focused(fc, ctx),
-        "app.fullscreen" => evaluate_app_fullscreen(fc, ctx),
-        "app.minimized" => evaluate_app_minimized(fc, ctx),
-        "app.display" => evaluate_app_display(fc, ctx),
-
-        // unknown field
-        _ => {
+        "app" => evaluate_app,
+        "app.running" => evaluate_app_running,
+        "app.focused" => evaluate_app_focused,
+        "app.fullscreen" => evaluate_app_fullscreen,
+        "app.minimized" => evaluate_app_minimized,
+        "app.display" => evaluate_app_display,
+
+        // profile conditions
+        "profile" => evaluate_profile,
+
+        _ => return None,
+    };
+    Some(evaluator)
+}
+
+fn evaluate_field(fc: &FieldCondition, ctx: &EvalContext) -> bool {
+    match field_evaluator(&fc.field) {
+        Some(evaluator) => evaluator(fc, ctx),
+        None => {
             eprintln!("warning: unknown condition field: {}", fc.field);
             false
         }
@@ -152,7 +174,7 @@ fn evaluate_time(fc: &FieldCondition, _ctx: &EvalContext) -> bool {
     }
 }
 
-fn evaluate_time_day(fc: &FieldCondition) -> bool {
+fn evaluate_time_day(fc: &FieldCondition, _ctx: &EvalContext) -> bool {
     match &fc.value {
         Value::String(s) => is_day_spec_match(s),
         Value::List(list) if fc.op == CompareOp::In => {
@@ -223,6 +245,23 @@ fn is_display_connected(alias: &str, ctx: &EvalContext) -> bool {
         .any(|d| d.name.to_lowercase() == alias_lower)
 }
 
+// ============================================================================
+// Profile Conditions
+// ============================================================================
+
+fn evaluate_profile(fc: &FieldCondition, ctx: &EvalContext) -> bool {
+    let is_active = |name: &str| ctx.profile == Some(name);
+
+    match (&fc.value, fc.op) {
+        (Value::String(name), CompareOp::Eq) => is_active(name),
+        (Value::String(name), CompareOp::Ne) => !is_active(name),
+        (Value::List(list), CompareOp::In) => list
+            .iter()
+            .any(|v| matches!(v, Value::String(name) if is_active(name))),
+        _ => false,
+    }
+}
+
 // ============================================================================
 // App Conditions
 // ============================================================================
@@ -500,6 +539,18 @@ mod tests {
         EvalContext::new(displays, &EMPTY_ALIASES, apps)
     }
 
+    #[test]
+    fn test_every_field_has_evaluator() {
+        for spec in super::super::types::FIELDS {
+            assert!(
+                field_evaluator(spec.name).is_some(),
+                "no evaluator for condition field '{}'",
+                spec.name
+            );
+        }
+        assert!(field_evaluator("nope").is_none());
+    }
+
     #[test]
     fn test_evaluate_all_empty() {
         let cond = Condition::All(vec![]);
@@ -705,6 +756,35 @@ mod tests {
         assert!(evaluate(&cond, &ctx));
     }
 
+    #[test]
+    fn test_evaluate_profile() {
+        let ctx = make_context(&[], &[]);
+        let presenting = Condition::Field(FieldCondition::eq(
+            "profile",
+            Value::String("presenting".to_string()),
+        ));
+        assert!(!evaluate(&presenting, &ctx));
+
+        let ctx = make_context(&[], &[]).with_profile(Some("presenting"));
+        assert!(evaluate(&presenting, &ctx));
+
+        let cond = Condition::Field(FieldCondition::new(
+            "profile",
+            CompareOp::Ne,
+            Value::String("presenting".to_string()),
+        ));
+        assert!(!evaluate(&cond, &ctx));
+
+        let cond = Condition::Field(FieldCondition::is_in(
+            "profile",
+            vec![
+                Value::String("deep-work".to_string()),
+                Value::String("presenting".to_string()),
+            ],
+        ));
+        assert!(evaluate(&cond, &ctx));
+    }
+
     #[test]
     fn test_evaluate_app_fullscreen() {
         let window = WindowState {
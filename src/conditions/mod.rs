@@ -22,3 +22,4 @@ pub use types::Condition;
 pub use parser::ParseError;
 #[allow(unused_imports)]
 pub use types::{CompareOp, FieldCondition, Value};
+pub use types::{FieldSpec, FieldType, FIELDS};
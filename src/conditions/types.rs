@@ -22,18 +22,32 @@ pub enum CompareOp {
 }
 
 impl CompareOp {
+    pub const ALL: [CompareOp; 7] = [
+        CompareOp::Eq,
+        CompareOp::Ne,
+        CompareOp::Gt,
+        CompareOp::Gte,
+        CompareOp::Lt,
+        CompareOp::Lte,
+        CompareOp::In,
+    ];
+
+    /// every spelling accepted for this operator in config files
+    pub fn aliases(self) -> &'static [&'static str] {
+        match self {
+            CompareOp::Eq => &["==", "eq", "equals"],
+            CompareOp::Ne => &["!=", "ne", "not_equals"],
+            CompareOp::Gt => &[">", "gt", "greater_than"],
+            CompareOp::Gte => &[">=", "gte", "greater_than_or_equal"],
+            CompareOp::Lt => &["<", "lt", "less_than"],
+            CompareOp::Lte => &["<=", "lte", "less_than_or_equal"],
+            CompareOp::In => &["in"],
+        }
+    }
+
     /// parse operator from string (supports all forms)
     pub fn parse(s: &str) -> Option<Self> {
-        match s {
-            "==" | "eq" | "equals" => Some(CompareOp::Eq),
-            "!=" | "ne" | "not_equals" => Some(CompareOp::Ne),
-            ">" | "gt" | "greater_than" => Some(CompareOp::Gt),
-            ">=" | "gte" | "greater_than_or_equal" => Some(CompareOp::Gte),
-            "<" | "lt" | "less_than" => Some(CompareOp::Lt),
-            "<=" | "lte" | "less_than_or_equal" => Some(CompareOp::Lte),
-            "in" => Some(CompareOp::In),
-            _ => None,
-        }
+        Self::ALL.into_iter().find(|op| op.aliases().contains(&s))
     }
 }
 
@@ -143,6 +157,98 @@ impl fmt::Display for Value {
     }
 }
 
+/// the JSON values a condition field accepts
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FieldType {
+    /// a string, e.g. `{ "app": "Safari" }`
+    String,
+    /// an integer, also usable with comparison operators: `{ ">=": 2 }`
+    Number,
+    /// `true` or `false`
+    Bool,
+    /// a list of strings with the `in` operator: `{ "in": ["a", "b"] }`
+    InList,
+}
+
+/// a condition field understood by the evaluator
+#[derive(Debug, Clone, Copy)]
+pub struct FieldSpec {
+    pub name: &'static str,
+    pub description: &'static str,
+    pub accepts: &'static [FieldType],
+    pub examples: &'static [&'static str],
+}
+
+/// every condition field, used for evaluation dispatch checks and the JSON schema
+pub const FIELDS: &[FieldSpec] = &[
+    FieldSpec {
+        name: "time",
+        description: "time range(s): '9:00-17:00', '9AM-5PM', '9:00-12:00,14:00-18:00'",
+        accepts: &[FieldType::String, FieldType::InList],
+        examples: &["9:00AM-5:00PM", "22:00-06:00", "9:00-12:00,14:00-18:00"],
+    },
+    FieldSpec {
+        name: "time.day",
+        description: "day(s) of week: 'mon', 'mon-fri', 'mon,wed,fri'",
+        accepts: &[FieldType::String, FieldType::InList],
+        examples: &["mon-fri", "sat,sun", "mon,wed,fri"],
+    },
+    FieldSpec {
+        name: "display.count",
+        description: "number of connected displays",
+        accepts: &[FieldType::Number],
+        examples: &[],
+    },
+    FieldSpec {
+        name: "display.connected",
+        description: "check if a display alias is connected",
+        accepts: &[FieldType::String, FieldType::InList],
+        examples: &[],
+    },
+    FieldSpec {
+        name: "app",
+        description: "target app name or window title match",
+        accepts: &[FieldType::String, FieldType::InList],
+        examples: &[],
+    },
+    FieldSpec {
+        name: "app.running",
+        description: "check if an app is running",
+        accepts: &[FieldType::String, FieldType::InList],
+        examples: &[],
+    },
+    FieldSpec {
+        name: "app.focused",
+        description: "check if an app has focus (true/false: whether any app has focus)",
+        accepts: &[FieldType::String, FieldType::Bool, FieldType::InList],
+        examples: &[],
+    },
+    FieldSpec {
+        name: "app.fullscreen",
+        description: "check if the target window is fullscreen",
+        accepts: &[FieldType::Bool],
+        examples: &[],
+    },
+    FieldSpec {
+        name: "app.minimized",
+        description: "check if the target window is minimized",
+        accepts: &[FieldType::Bool],
+        examples: &[],
+    },
+    FieldSpec {
+        name: "app.display",
+        description: "check which display the target window is on",
+        accepts: &[FieldType::String, FieldType::InList],
+        examples: &[],
+    },
+    FieldSpec {
+        name: "profile",
+        description: "check the active profile",
+        accepts: &[FieldType::String, FieldType::InList],
+        examples: &[],
+    },
+];
+
 /// a single field comparison condition
 #[derive(Debug, Clone, PartialEq)]
 pub struct FieldCondition {
@@ -0,0 +1,721 @@
+//! hotkeys already taken by macOS, common apps or other shortcuts
+//!
+//! `KNOWN_SHORTCUTS` lists well-known macOS and app hotkeys. `check` compares
+//! one shortcut with them and with the config's shortcuts, `conflicts` does so
+//! for every shortcut in the config
+//!
+//! the listener takes a bound hotkey from the frontmost app whether or not the
+//! shortcut's `when` holds, so a condition never resolves a conflict. `when`
+//! is still compared, to point out conflicts it looks like it avoids
+
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fmt;
+
+use super::{keyboard_layout, Config, Shortcut};
+use crate::conditions::{parse_condition, CompareOp, Condition, FieldCondition, Value};
+use crate::daemon::keyboard::Layout;
+use crate::daemon::sequence::KeySequence;
+use crate::daemon::trigger::ModifierKey;
+
+/// who uses a known hotkey
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Owner {
+    /// macOS itself, or nearly every app
+    System,
+    /// these apps, while frontmost
+    Apps(&'static [&'static str]),
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct KnownShortcut {
+    pub keys: &'static str,
+    pub owner: Owner,
+    pub used_for: &'static str,
+}
+
+const fn system(keys: &'static str, used_for: &'static str) -> KnownShortcut {
+    KnownShortcut {
+        keys,
+        owner: Owner::System,
+        used_for,
+    }
+}
+
+const fn apps(
+    keys: &'static str,
+    apps: &'static [&'static str],
+    used_for: &'static str,
+) -> KnownShortcut {
+    KnownShortcut {
+        keys,
+        owner: Owner::Apps(apps),
+        used_for,
+    }
+}
+
+const BROWSERS: &[&str] = &[
+    "Safari",
+    "Google Chrome",
+    "Firefox",
+    "Arc",
+    "Microsoft Edge",
+    "Brave Browser",
+];
+const EDITORS: &[&str] = &["Code", "Cursor", "Zed", "Sublime Text"];
+const TERMINALS: &[&str] = &["Terminal", "iTerm2", "Ghostty", "WezTerm", "kitty"];
+const CHAT: &[&str] = &["Slack", "Discord"];
+
+/// hotkeys macOS and common apps already use, with the default key bindings
+pub const KNOWN_SHORTCUTS: &[KnownShortcut] = &[
+    system("cmd+space", "Spotlight"),
+    system("cmd+alt+space", "Finder search"),
+    system("ctrl+space", "select the previous input source"),
+    system("ctrl+alt+space", "select the next input source"),
+    system("ctrl+cmd+space", "the emoji & symbols viewer"),
+    system("cmd+tab", "the application switcher"),
+    system("cmd+shift+tab", "the application switcher"),
+    system("cmd+`", "cycling through windows of the front app"),
+    system("cmd+shift+3", "screenshots"),
+    system("cmd+shift+4", "screenshots"),
+    system("cmd+shift+5", "screenshots"),
+    system("ctrl+cmd+shift+3", "screenshots to the clipboard"),
+    system("ctrl+cmd+shift+4", "screenshots to the clipboard"),
+    system("cmd+alt+escape", "Force Quit"),
+    system("ctrl+cmd+q", "Lock Screen"),
+    system("cmd+shift+q", "Log Out"),
+    system("ctrl+cmd+f", "toggling full screen"),
+    system("cmd+alt+d", "showing and hiding the Dock"),
+    system("ctrl+up", "Mission Control"),
+    system("ctrl+down", "application windows"),
+    system("ctrl+left", "moving to the previous space"),
+    system("ctrl+right", "moving to the next space"),
+    system("ctrl+1", "switching to desktop 1"),
+    system("ctrl+2", "switching to desktop 2"),
+    system("ctrl+3", "switching to desktop 3"),
+    system("f11", "showing the desktop"),
+    system("cmd+q", "quitting the front app"),
+    system("cmd+w", "closing the front window"),
+    system("cmd+h", "hiding the front app"),
+    system("cmd+alt+h", "hiding other apps"),
+    system("cmd+m", "minimizing the front window"),
+    system("cmd+c", "copy"),
+    system("cmd+v", "paste"),
+    system("cmd+x", "cut"),
+    system("cmd+z", "undo"),
+    system("cmd+shift+z", "redo"),
+    system("cmd+a", "select all"),
+    system("cmd+s", "save"),
+    system("cmd+o", "open"),
+    system("cmd+n", "a new document or window"),
+    system("cmd+p", "print"),
+    system("cmd+f", "find"),
+    system("cmd+,", "the front app's settings"),
+    apps("cmd+t", BROWSERS, "a new tab"),
+    apps("cmd+shift+t", BROWSERS, "reopening the last closed tab"),
+    apps("cmd+l", BROWSERS, "the address bar"),
+    apps("cmd+r", BROWSERS, "reloading the page"),
+    apps("cmd+shift+n", BROWSERS, "a new private window"),
+    apps("ctrl+tab", BROWSERS, "the next tab"),
+    apps("ctrl+shift+tab", BROWSERS, "the previous tab"),
+    apps("cmd+alt+i", BROWSERS, "the developer tools"),
+    apps("cmd+shift+n", &["Finder"], "a new folder"),
+    apps("cmd+shift+g", &["Finder"], "Go to Folder"),
+    apps("cmd+shift+.", &["Finder"], "showing hidden files"),
+    apps("cmd+alt+l", &["Finder"], "the Downloads folder"),
+    apps("cmd+p", EDITORS, "Quick Open"),
+    apps("cmd+shift+p", EDITORS, "the command palette"),
+    apps("cmd+b", EDITORS, "toggling the sidebar"),
+    apps("ctrl+`", EDITORS, "toggling the terminal"),
+    apps("cmd+t", TERMINALS, "a new tab"),
+    apps(
+        "cmd+d",
+        &["iTerm2", "Ghostty", "WezTerm"],
+        "splitting the pane",
+    ),
+    apps("cmd+k", TERMINALS, "clearing the screen"),
+    apps("cmd+k", CHAT, "the quick switcher"),
+    apps("cmd+shift+k", &["Slack"], "direct messages"),
+];
+
+/// what a hotkey conflicts with
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ConflictWith {
+    /// a macOS hotkey
+    System { used_for: String },
+    /// a hotkey of apps the shortcut is active in
+    App { apps: Vec<String>, used_for: String },
+    /// another shortcut in the config, matched first
+    Shortcut { path: String, shortcut: String },
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Conflict {
+    /// config path of the shortcut, e.g. `shortcuts[2]`; empty for one not in the config
+    pub path: String,
+    /// the hotkey both use
+    pub keys: String,
+    #[serde(flatten)]
+    pub with: ConflictWith,
+    pub message: String,
+}
+
+impl fmt::Display for Conflict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.path.is_empty() {
+            f.write_str(&self.message)
+        } else {
+            write!(f, "{}: {}", self.path, self.message)
+        }
+    }
+}
+
+/// conflicts of `shortcut`, which is not in the config yet, with known
+/// hotkeys and the config's shortcuts; it is taken to be matched last, and
+/// `replaces` is the index of a shortcut it overwrites
+pub fn check(config: &Config, shortcut: &Shortcut, replaces: Option<usize>) -> Vec<Conflict> {
+    let checker = Checker::new(config);
+    let others: Vec<usize> = (0..config.shortcuts.len())
+        .filter(|&i| Some(i) != replaces)
+        .collect();
+    checker.check(shortcut, "", &others)
+}
+
+/// conflicts of every shortcut in the config
+pub fn conflicts(config: &Config) -> Vec<Conflict> {
+    let checker = Checker::new(config);
+
+    // double-tap and hold shortcuts are matched before the others
+    let mut order: Vec<usize> = (0..config.shortcuts.len()).collect();
+    order.sort_by_key(|&i| config.shortcuts[i].trigger.is_tap());
+
+    let mut result = Vec::new();
+    for (n, &i) in order.iter().enumerate() {
+        let path = format!("shortcuts[{}]", i);
+        result.extend(checker.check(&config.shortcuts[i], &path, &order[..n]));
+    }
+    result
+}
+
+struct Checker<'a> {
+    config: &'a Config,
+    layout: &'static Layout,
+    definitions: HashMap<String, Condition>,
+    known: Vec<(KeySequence, &'static KnownShortcut)>,
+}
+
+impl<'a> Checker<'a> {
+    fn new(config: &'a Config) -> Self {
+        let layout = keyboard_layout(config);
+        Self {
+            config,
+            layout,
+            definitions: crate::daemon::parse_config_conditions(config),
+            known: KNOWN_SHORTCUTS
+                .iter()
+                .filter_map(|known| Some((normalize(known.keys, layout)?, known)))
+                .collect(),
+        }
+    }
+
+    /// conflicts of `shortcut` at `path`, matched after the shortcuts `earlier`
+    fn check(&self, shortcut: &Shortcut, path: &str, earlier: &[usize]) -> Vec<Conflict> {
+        let mut result = Vec::new();
+        // a lone modifier is not a hotkey, and invalid hotkeys are reported by verify
+        if !shortcut.trigger.is_tap() && ModifierKey::parse(&shortcut.keys).is_some() {
+            return result;
+        }
+        let Some(hotkey) = normalize(&shortcut.keys, self.layout) else {
+            return result;
+        };
+        let when = self.condition(shortcut);
+
+        for &j in earlier {
+            let other = &self.config.shortcuts[j];
+            if normalize(&other.keys, self.layout).as_ref() != Some(&hotkey)
+                || !scopes_overlap(shortcut, other)
+            {
+                continue;
+            }
+
+            let mut message = format!(
+                "never triggers: '{}' is already bound to '{}' by shortcuts[{}]",
+                hotkey,
+                describe_shortcut(other),
+                j
+            );
+            if let (Some(a), Some(b)) = (&when, &self.condition(other)) {
+                if !conditions_overlap(a, b) {
+                    message.push_str(
+                        "; their conditions never hold together, but the first match wins even when its condition is false",
+                    );
+                }
+            }
+            result.push(Conflict {
+                path: path.to_string(),
+                keys: hotkey.to_string(),
+                with: ConflictWith::Shortcut {
+                    path: format!("shortcuts[{}]", j),
+                    shortcut: describe_shortcut(other),
+                },
+                message,
+            });
+            // the first match is the one that takes the hotkey
+            break;
+        }
+
+        // only the first chord of a sequence reaches the listener unmatched
+        let leader = &hotkey.chords[0];
+        for (keys, known) in &self.known {
+            if keys.chords[0] != *leader {
+                continue;
+            }
+
+            match known.owner {
+                Owner::System => result.push(Conflict {
+                    path: path.to_string(),
+                    keys: leader.to_string(),
+                    with: ConflictWith::System {
+                        used_for: known.used_for.to_string(),
+                    },
+                    message: format!(
+                        "'{}' is used by macOS for {}; binding it takes it over or never triggers",
+                        leader, known.used_for
+                    ),
+                }),
+                Owner::Apps(apps) => {
+                    let active: Vec<String> = apps
+                        .iter()
+                        .filter(|app| self.active_in(shortcut, app))
+                        .map(|app| app.to_string())
+                        .collect();
+                    if active.is_empty() {
+                        continue;
+                    }
+
+                    let mut message = format!(
+                        "'{}' is used by {} for {}; the shortcut takes it over while they are frontmost",
+                        leader,
+                        active.join(", "),
+                        known.used_for
+                    );
+                    if let Some(when) = &when {
+                        if active.iter().all(|app| !focus_overlaps(when, app)) {
+                            message.push_str(
+                                " (its condition is false there, but the hotkey is still taken; add them to 'except_in')",
+                            );
+                        }
+                    }
+                    result.push(Conflict {
+                        path: path.to_string(),
+                        keys: leader.to_string(),
+                        with: ConflictWith::App {
+                            apps: active,
+                            used_for: known.used_for.to_string(),
+                        },
+                        message,
+                    });
+                }
+            }
+        }
+
+        result
+    }
+
+    /// the shortcut's parsed `when`; an invalid one is reported by verify
+    fn condition(&self, shortcut: &Shortcut) -> Option<Condition> {
+        parse_condition(shortcut.when.as_ref()?, &self.definitions).ok()
+    }
+
+    /// whether the shortcut is active with `app` frontmost
+    fn active_in(&self, shortcut: &Shortcut, app: &str) -> bool {
+        let contains = |apps: &[String]| apps.iter().any(|a| a.eq_ignore_ascii_case(app));
+        (shortcut.only_in.is_empty() || contains(&shortcut.only_in))
+            && !contains(&shortcut.except_in)
+            && !contains(&self.config.settings.disabled_in)
+    }
+}
+
+/// parses a hotkey with its keys named as on `layout` and the keys of each
+/// chord sorted, so `ctrl+s+f` and `ctrl+f+s` compare equal
+pub(crate) fn normalize(keys: &str, layout: &Layout) -> Option<KeySequence> {
+    let mut sequence = KeySequence::parse_in(keys, layout).ok()?;
+    for chord in &mut sequence.chords {
+        chord.keys.sort();
+        chord.keys.dedup();
+    }
+    Some(sequence)
+}
+
+/// `focus Slack`, `maximize`: how a shortcut is named in messages
+pub(crate) fn describe_shortcut(shortcut: &Shortcut) -> String {
+    let action = if shortcut.cycle.is_empty() {
+        shortcut.action.clone()
+    } else {
+        format!("cycle [{}]", shortcut.cycle.join(", "))
+    };
+    match &shortcut.app {
+        Some(app) => format!("{} {}", action, app),
+        None => action,
+    }
+}
+
+/// whether some frontmost app has both shortcuts active; names are compared
+/// case-insensitively, so the answer errs towards overlapping
+pub(crate) fn scopes_overlap(a: &Shortcut, b: &Shortcut) -> bool {
+    let contains = |apps: &[String], app: &String| apps.iter().any(|a| a.eq_ignore_ascii_case(app));
+    let limited_to = |only_in: &[String], except_in: &[String]| {
+        only_in.is_empty() || only_in.iter().any(|app| !contains(except_in, app))
+    };
+
+    match (a.only_in.is_empty(), b.only_in.is_empty()) {
+        (false, false) => a.only_in.iter().any(|app| contains(&b.only_in, app)),
+        (false, true) => limited_to(&a.only_in, &b.except_in),
+        (true, false) => limited_to(&b.only_in, &a.except_in),
+        (true, true) => true,
+    }
+}
+
+/// whether `condition` can hold while `app` is frontmost
+fn focus_overlaps(condition: &Condition, app: &str) -> bool {
+    let focused = Condition::Field(FieldCondition::eq(
+        "app.focused",
+        Value::String(app.to_string()),
+    ));
+    conditions_overlap(condition, &focused)
+}
+
+/// fields that have one value at a time, so `== a` and `== b` exclude each other
+const SINGLE_VALUED: &[&str] = &[
+    "app.focused",
+    "app.fullscreen",
+    "app.minimized",
+    "app.display",
+    "display.count",
+    "profile",
+];
+
+/// whether `a` and `b` can hold at the same time
+///
+/// only comparisons every match of the conditions needs are looked at, so
+/// the answer is `false` just when they contradict each other on some field
+pub(crate) fn conditions_overlap(a: &Condition, b: &Condition) -> bool {
+    let mut atoms = Vec::new();
+    required(a, false, &mut atoms);
+    required(b, false, &mut atoms);
+
+    let mut by_field: HashMap<&str, Vec<(&FieldCondition, bool)>> = HashMap::new();
+    for (field, negated) in atoms {
+        by_field
+            .entry(field.field.as_str())
+            .or_default()
+            .push((field, negated));
+    }
+    by_field
+        .iter()
+        .all(|(field, atoms)| satisfiable(field, atoms))
+}
+
+/// the comparisons `condition` needs to hold, each with whether it must be false
+fn required<'a>(
+    condition: &'a Condition,
+    negated: bool,
+    out: &mut Vec<(&'a FieldCondition, bool)>,
+) {
+    match condition {
+        Condition::All(items) if !negated => {
+            items.iter().for_each(|item| required(item, false, out))
+        }
+        // not (a or b) needs both to be false
+        Condition::Any(items) if negated => items.iter().for_each(|item| required(item, true, out)),
+        Condition::Any(items) | Condition::All(items) if items.len() == 1 => {
+            required(&items[0], negated, out)
+        }
+        Condition::Not(inner) => required(inner, !negated, out),
+        Condition::Field(field) => out.push((field, negated)),
+        _ => {}
+    }
+}
+
+/// whether the comparisons on one field can all come out as required
+fn satisfiable(field: &str, atoms: &[(&FieldCondition, bool)]) -> bool {
+    let single = SINGLE_VALUED.contains(&field);
+    let mut allowed: Vec<Vec<&Value>> = Vec::new();
+    let mut forbidden: Vec<&Value> = Vec::new();
+    let (mut low, mut high) = (i64::MIN, i64::MAX);
+
+    for &(condition, negated) in atoms {
+        let value = &condition.value;
+        let values: Vec<&Value> = match value {
+            Value::List(items) => items.iter().collect(),
+            other => vec![other],
+        };
+        let number = value.as_i64().filter(|_| !matches!(value, Value::List(_)));
+
+        match (condition.op, negated) {
+            (CompareOp::Eq | CompareOp::In, false) | (CompareOp::Ne, true) => allowed.push(values),
+            (CompareOp::Eq | CompareOp::In, true) | (CompareOp::Ne, false) => {
+                forbidden.extend(values)
+            }
+            (op, negated) => {
+                let Some(n) = number else { continue };
+                // not (x > n) is x <= n, and so on
+                let op = match (op, negated) {
+                    (CompareOp::Gt, true) => CompareOp::Lte,
+                    (CompareOp::Gte, true) => CompareOp::Lt,
+                    (CompareOp::Lt, true) => CompareOp::Gte,
+                    (CompareOp::Lte, true) => CompareOp::Gt,
+                    (op, _) => op,
+                };
+                match op {
+                    CompareOp::Gt => low = low.max(n.saturating_add(1)),
+                    CompareOp::Gte => low = low.max(n),
+                    CompareOp::Lt => high = high.min(n.saturating_sub(1)),
+                    CompareOp::Lte => high = high.min(n),
+                    _ => {}
+                }
+            }
+        }
+    }
+
+    if low > high {
+        return false;
+    }
+    let possible = |value: &Value| {
+        !forbidden.iter().any(|other| same_value(value, other))
+            && value.as_i64().map_or(true, |n| (low..=high).contains(&n))
+    };
+    // a field with several values, like `app.running`, can match each set with a different one
+    if !single {
+        return allowed
+            .iter()
+            .all(|set| set.iter().any(|value| possible(value)));
+    }
+
+    let Some((first, rest)) = allowed.split_first() else {
+        return true;
+    };
+    first.iter().any(|value| {
+        possible(value)
+            && rest
+                .iter()
+                .all(|set| set.iter().any(|other| same_value(value, other)))
+    })
+}
+
+/// whether two condition values are equal; names are compared case-insensitively
+fn same_value(a: &Value, b: &Value) -> bool {
+    match (a, b) {
+        (Value::String(a), Value::String(b)) => a.eq_ignore_ascii_case(b),
+        (Value::Bool(a), Value::Bool(b)) => a == b,
+        _ => match (a.as_f64(), b.as_f64()) {
+            (Some(a), Some(b)) => a == b,
+            _ => false,
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::daemon::{hotkeys, keyboard};
+    use serde_json::json;
+
+    fn config(value: serde_json::Value) -> Config {
+        serde_json::from_value(value).unwrap()
+    }
+
+    fn condition(value: serde_json::Value) -> Condition {
+        parse_condition(&value, &HashMap::new()).unwrap()
+    }
+
+    fn kinds(conflicts: &[Conflict]) -> Vec<(&str, &str)> {
+        conflicts
+            .iter()
+            .map(|c| {
+                let kind = match c.with {
+                    ConflictWith::System { .. } => "system",
+                    ConflictWith::App { .. } => "app",
+                    ConflictWith::Shortcut { .. } => "shortcut",
+                };
+                (c.path.as_str(), kind)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_known_shortcuts_parse() {
+        for known in KNOWN_SHORTCUTS {
+            let keys = normalize(known.keys, &keyboard::US)
+                .unwrap_or_else(|| panic!("'{}' does not parse", known.keys));
+            for key in keys.chords.iter().flat_map(|chord| &chord.keys) {
+                assert!(
+                    hotkeys::is_known_key(key, &keyboard::US),
+                    "'{}' in '{}'",
+                    key,
+                    known.keys
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_conflicts() {
+        let config = config(json!({
+            "shortcuts": [
+                { "keys": "cmd+shift+4", "action": "maximize" },
+                { "keys": "ctrl+alt+m", "action": "maximize" },
+                { "keys": "alt+ctrl+m", "action": "resize:80" },
+                { "keys": "cmd+l", "action": "focus", "app": "Slack" },
+                { "keys": "cmd+l", "action": "resize:50", "only_in": ["Xcode"] },
+                { "keys": "cmd+shift+p", "action": "maximize", "except_in": ["Code", "Cursor", "Zed", "Sublime Text"] },
+                { "keys": "rshift", "action": "maximize", "trigger": "double_tap" }
+            ]
+        }));
+
+        let conflicts = conflicts(&config);
+        assert_eq!(
+            kinds(&conflicts),
+            vec![
+                ("shortcuts[0]", "system"),
+                ("shortcuts[2]", "shortcut"),
+                ("shortcuts[3]", "app"),
+                ("shortcuts[4]", "shortcut"),
+            ]
+        );
+        assert_eq!(conflicts[0].keys, "cmd+shift+4");
+        assert!(conflicts[1].message.contains("shortcuts[1]"));
+        // shortcuts[4] is limited to Xcode, so only shortcuts[3] takes cmd+l from browsers
+        match &conflicts[2].with {
+            ConflictWith::App { apps, used_for } => {
+                assert_eq!(apps.len(), BROWSERS.len());
+                assert_eq!(used_for, "the address bar");
+            }
+            other => panic!("unexpected conflict: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_check() {
+        let config = config(json!({
+            "settings": { "disabled_in": ["Google Chrome"] },
+            "shortcuts": [
+                { "keys": "ctrl+alt+m", "action": "maximize" }
+            ]
+        }));
+
+        let shortcut = |keys: &str| Shortcut {
+            keys: keys.to_string(),
+            action: "resize:80".to_string(),
+            ..Default::default()
+        };
+
+        let conflicts = check(&config, &shortcut("alt+ctrl+m"), None);
+        assert_eq!(kinds(&conflicts), vec![("", "shortcut")]);
+        assert_eq!(
+            conflicts[0].to_string(),
+            "never triggers: 'ctrl+alt+m' is already bound to 'maximize' by shortcuts[0]"
+        );
+
+        // the shortcut it replaces does not count
+        assert!(check(&config, &shortcut("ctrl+alt+m"), Some(0)).is_empty());
+
+        // only the leader of a sequence is taken from other apps
+        let conflicts = check(&config, &shortcut("cmd+t, h"), None);
+        match &conflicts[..] {
+            [browsers, terminals] => {
+                let ConflictWith::App { apps, .. } = &browsers.with else {
+                    panic!("unexpected conflict: {:?}", browsers);
+                };
+                assert!(!apps.contains(&"Google Chrome".to_string()));
+                assert!(matches!(terminals.with, ConflictWith::App { .. }));
+            }
+            other => panic!("unexpected conflicts: {:?}", other),
+        }
+        assert!(check(&config, &shortcut("h, cmd+t"), None).is_empty());
+    }
+
+    #[test]
+    fn test_conditions_do_not_resolve_conflicts() {
+        let config = config(json!({
+            "conditions": { "docked": { "display.count": { ">=": 2 } } },
+            "shortcuts": [
+                { "keys": "ctrl+alt+m", "action": "maximize", "when": { "$ref": "docked" } },
+                { "keys": "ctrl+alt+m", "action": "resize:80", "when": { "display.count": 1 } },
+                { "keys": "cmd+k", "action": "maximize", "when": { "app.focused": "Finder" } },
+                { "keys": "cmd+t", "action": "maximize", "when": { "app.focused": "Safari" } }
+            ]
+        }));
+
+        let conflicts = conflicts(&config);
+        assert_eq!(
+            kinds(&conflicts),
+            vec![
+                ("shortcuts[1]", "shortcut"),
+                ("shortcuts[2]", "app"),
+                ("shortcuts[2]", "app"),
+                ("shortcuts[3]", "app"),
+                ("shortcuts[3]", "app"),
+            ]
+        );
+        assert!(conflicts[0].message.contains("never hold together"));
+        assert!(conflicts[1].message.contains("'except_in'"));
+        assert!(conflicts[2].message.contains("'except_in'"));
+        // the condition holds in Safari, so the hotkey is taken from browsers on purpose
+        assert!(!conflicts[3].message.contains("'except_in'"));
+        assert!(conflicts[4].message.contains("'except_in'"));
+    }
+
+    #[test]
+    fn test_conditions_overlap() {
+        let overlap = |a, b| conditions_overlap(&condition(a), &condition(b));
+
+        assert!(overlap(
+            json!({ "app.focused": "Safari" }),
+            json!({ "display.count": 2 })
+        ));
+        assert!(!overlap(
+            json!({ "app.focused": "Safari" }),
+            json!({ "app.focused": "Finder" })
+        ));
+        assert!(overlap(
+            json!({ "app.focused": "safari" }),
+            json!({ "app.focused": ["Safari", "Mail"] })
+        ));
+        assert!(!overlap(
+            json!({ "app.focused": ["Safari", "Mail"] }),
+            json!({ "not": { "app.focused": { "in": ["safari", "mail"] } } })
+        ));
+        assert!(!overlap(
+            json!({ "display.count": { ">=": 2 } }),
+            json!({ "display.count": { "<": 2 } })
+        ));
+        assert!(!overlap(
+            json!({ "display.count": 1 }),
+            json!({ "not": { "display.count": { "<=": 3 } } })
+        ));
+        assert!(overlap(
+            json!({ "display.count": { ">": 1 } }),
+            json!({ "display.count": 3 })
+        ));
+        assert!(!overlap(
+            json!({ "all": [{ "profile": "work" }, { "time": "9:00-17:00" }] }),
+            json!({ "any": [{ "profile": "home" }] })
+        ));
+        // either side of an 'any' may hold, so it is not known to contradict
+        assert!(overlap(
+            json!({ "profile": "work" }),
+            json!({ "any": [{ "profile": "home" }, { "display.count": 2 }] })
+        ));
+        // several apps run at once
+        assert!(overlap(
+            json!({ "app.running": "Slack" }),
+            json!({ "app.running": "Mail" })
+        ));
+        assert!(!overlap(
+            json!({ "app.running": "Slack" }),
+            json!({ "not": { "app.running": "Slack" } })
+        ));
+    }
+}
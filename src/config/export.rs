@@ -0,0 +1,930 @@
+//! exporting shortcuts to other hotkey tools and as a cheat sheet
+//!
+//! `skhd` and `karabiner` bind each shortcut to the `cwm` command that does
+//! the same. shortcuts that only the daemon can run (sequences, cycles,
+//! double-tap and hold triggers, `when` conditions) are listed as skipped.
+//! `markdown` and `html` render a cheat sheet of every shortcut, grouped by
+//! action
+//!
+//! supported targets:
+//! - `skhd` - lines for an `.skhdrc`
+//! - `karabiner` - a Karabiner-Elements complex modification (JSON)
+//! - `markdown` - a cheat sheet as Markdown tables
+//! - `html` - a cheat sheet as a standalone HTML page
+
+use serde_json::json;
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+
+use super::import::Skipped;
+use super::lint::SYSTEM_ALIASES;
+use super::schema::{Config, Shortcut, Trigger};
+use super::{keyboard_layout, ACTIONS};
+use crate::conditions::{parse_condition, CompareOp, Condition, Value};
+use crate::daemon::hotkeys::Hotkey;
+use crate::daemon::keyboard::{self, Layout};
+use crate::daemon::sequence::KeySequence;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Target {
+    Skhd,
+    Karabiner,
+    Markdown,
+    Html,
+}
+
+impl Target {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Target::Skhd => "skhd",
+            Target::Karabiner => "karabiner",
+            Target::Markdown => "markdown",
+            Target::Html => "html",
+        }
+    }
+}
+
+impl fmt::Display for Target {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for Target {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "skhd" => Ok(Target::Skhd),
+            "karabiner" => Ok(Target::Karabiner),
+            "markdown" | "md" => Ok(Target::Markdown),
+            "html" => Ok(Target::Html),
+            _ => Err(format!(
+                "invalid export format '{}', expected: skhd, karabiner, markdown, html",
+                s
+            )),
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct Export {
+    pub content: String,
+    /// shortcuts the target cannot express; cheat sheets list every shortcut
+    pub skipped: Vec<Skipped>,
+}
+
+/// renders the shortcuts of `config` for `target`
+pub fn export(config: &Config, target: Target) -> Export {
+    match target {
+        Target::Skhd => export_skhd(config),
+        Target::Karabiner => export_karabiner(config),
+        Target::Markdown => Export {
+            content: markdown(&cheat_sheet(config)),
+            skipped: Vec::new(),
+        },
+        Target::Html => Export {
+            content: html(&cheat_sheet(config)),
+            skipped: Vec::new(),
+        },
+    }
+}
+
+// ==================== cwm commands ====================
+
+/// the single chord a tool binds `shortcut` to, or why it cannot
+fn bindable_chord(shortcut: &Shortcut, target: Target, layout: &Layout) -> Result<Hotkey, String> {
+    if !shortcut.cycle.is_empty() {
+        return Err("cycles need the cwm daemon".to_string());
+    }
+    if !shortcut.trigger.is_tap() {
+        return Err("double-tap and hold triggers need the cwm daemon".to_string());
+    }
+    if shortcut.when.is_some() {
+        return Err("'when' conditions need the cwm daemon".to_string());
+    }
+
+    let sequence = KeySequence::parse_in(&shortcut.keys, layout).map_err(|e| e.to_string())?;
+    let [chord] = sequence.chords.as_slice() else {
+        return Err("key sequences need the cwm daemon".to_string());
+    };
+    if chord.keys.len() != 1 {
+        return Err(format!(
+            "chords of several keys are not supported by {}",
+            target
+        ));
+    }
+    Ok(chord.clone())
+}
+
+/// the `cwm` command line that runs the shortcut's action
+fn cwm_command(shortcut: &Shortcut, config: &Config) -> Result<String, String> {
+    let (name, arg) = match shortcut.action.split_once(':') {
+        Some((name, arg)) => (name, Some(arg)),
+        None => (shortcut.action.as_str(), None),
+    };
+
+    let mut args = vec!["cwm".to_string(), name.to_string()];
+    match (name, arg) {
+        ("focus", None) | ("maximize", None) => {}
+        ("move", Some(arg)) => {
+            let (position, display) = move_args(arg, config);
+            if let Some(position) = position {
+                args.extend(["--to".to_string(), shell_quote(position)]);
+            }
+            if let Some(display) = display {
+                args.extend(["--display".to_string(), shell_quote(display)]);
+            }
+        }
+        ("resize", Some(size)) => args.extend(["--to".to_string(), shell_quote(size)]),
+        _ => return Err(format!("action '{}' has no cwm command", shortcut.action)),
+    }
+
+    if let Some(app) = &shortcut.app {
+        args.extend(["--app".to_string(), shell_quote(app)]);
+    }
+    match shortcut.launch {
+        Some(true) => args.push("--launch".to_string()),
+        Some(false) => args.push("--no-launch".to_string()),
+        None => {}
+    }
+    Ok(args.join(" "))
+}
+
+/// `--to` and `--display` of a `move:` argument, read the way the daemon does
+fn move_args<'a>(arg: &'a str, config: &Config) -> (Option<&'a str>, Option<&'a str>) {
+    let arg = arg.trim();
+    if let Some(display) = arg.strip_prefix("display=") {
+        return (None, Some(display));
+    }
+    if let Some((position, display)) = arg.split_once(';') {
+        let display = display.trim();
+        return (
+            Some(position.trim()),
+            Some(display.strip_prefix("display=").unwrap_or(display)),
+        );
+    }
+
+    let is_display = matches!(arg.to_lowercase().as_str(), "next" | "prev")
+        || arg.chars().all(|c| c.is_ascii_digit())
+        || SYSTEM_ALIASES.contains(&arg.to_lowercase().as_str())
+        || config
+            .display_aliases
+            .keys()
+            .any(|name| name.eq_ignore_ascii_case(arg));
+    if is_display {
+        (None, Some(arg))
+    } else {
+        (Some(arg), None)
+    }
+}
+
+/// `s` as one shell word
+fn shell_quote(s: &str) -> String {
+    let plain = !s.is_empty()
+        && s.chars()
+            .all(|c| c.is_ascii_alphanumeric() || "-_.,%+=/:@".contains(c));
+    if plain {
+        s.to_string()
+    } else {
+        format!("'{}'", s.replace('\'', r"'\''"))
+    }
+}
+
+// ==================== skhd ====================
+
+/// skhd's names for keys that type no character
+const SKHD_KEYS: &[&str] = &[
+    "return",
+    "tab",
+    "space",
+    "backspace",
+    "escape",
+    "delete",
+    "home",
+    "end",
+    "pageup",
+    "pagedown",
+    "left",
+    "right",
+    "up",
+    "down",
+];
+
+/// `ctrl + alt - m`; keys without a name in skhd are written as keycodes
+fn skhd_hotkey(chord: &Hotkey, layout: &Layout) -> Result<String, String> {
+    let key = &chord.keys[0];
+    let is_fn_key = key
+        .strip_prefix('f')
+        .is_some_and(|n| n.parse::<u8>().is_ok_and(|n| (1..=20).contains(&n)));
+    let key = if key.len() == 1 && key.chars().all(|c| c.is_ascii_alphanumeric())
+        || SKHD_KEYS.contains(&key.as_str())
+        || is_fn_key
+    {
+        key.clone()
+    } else {
+        let keycode = layout
+            .keycode(key)
+            .ok_or_else(|| format!("unsupported key '{}'", key))?;
+        format!("0x{:02X}", keycode)
+    };
+
+    let mut modifiers = Vec::new();
+    for (on, name) in [
+        (chord.modifiers.ctrl, "ctrl"),
+        (chord.modifiers.alt, "alt"),
+        (chord.modifiers.cmd, "cmd"),
+        (chord.modifiers.shift, "shift"),
+    ] {
+        if on {
+            modifiers.push(name);
+        }
+    }
+
+    if modifiers.is_empty() {
+        Ok(key)
+    } else {
+        Ok(format!("{} - {}", modifiers.join(" + "), key))
+    }
+}
+
+fn export_skhd(config: &Config) -> Export {
+    let mut export = Export {
+        content: "# generated by cwm shortcuts export\n".to_string(),
+        skipped: Vec::new(),
+    };
+    let layout = keyboard_layout(config);
+
+    for shortcut in &config.shortcuts {
+        let binding = bindable_chord(shortcut, Target::Skhd, layout)
+            .and_then(|chord| skhd_hotkey(&chord, layout))
+            .and_then(|hotkey| Ok((hotkey, cwm_command(shortcut, config)?)));
+        let (hotkey, command) = match binding {
+            Ok(binding) => binding,
+            Err(reason) => {
+                export
+                    .skipped
+                    .push(Skipped::new(shortcut.keys.clone(), reason));
+                continue;
+            }
+        };
+
+        let line = if !shortcut.only_in.is_empty() {
+            let apps: Vec<String> = shortcut
+                .only_in
+                .iter()
+                .map(|app| format!("    \"{}\" : {}\n", app, command))
+                .collect();
+            format!("{} [\n{}]\n", hotkey, apps.concat())
+        } else if !shortcut.except_in.is_empty() {
+            // `~` leaves the key to the app
+            let apps: Vec<String> = shortcut
+                .except_in
+                .iter()
+                .map(|app| format!("    \"{}\" ~\n", app))
+                .collect();
+            format!("{} [\n{}    * : {}\n]\n", hotkey, apps.concat(), command)
+        } else {
+            format!("{} : {}\n", hotkey, command)
+        };
+        export.content.push_str(&line);
+    }
+    export
+}
+
+// ==================== Karabiner-Elements ====================
+
+/// Karabiner's `key_code` for the key `name` on `layout`
+fn karabiner_key_code(name: &str, layout: &Layout) -> Result<String, String> {
+    let code = layout
+        .keycode(name)
+        .and_then(keyboard::physical_name)
+        .ok_or_else(|| format!("unsupported key '{}'", name))?;
+
+    if let Some(letter) = code.strip_prefix("Key") {
+        return Ok(letter.to_lowercase());
+    }
+    if let Some(digit) = code.strip_prefix("Digit") {
+        return Ok(digit.to_string());
+    }
+    if let Some(digit) = code
+        .strip_prefix("Numpad")
+        .filter(|d| d.len() == 1 && d.chars().all(|c| c.is_ascii_digit()))
+    {
+        return Ok(format!("keypad_{}", digit));
+    }
+    if code.starts_with('F') && code[1..].chars().all(|c| c.is_ascii_digit()) {
+        return Ok(code.to_lowercase());
+    }
+
+    let key_code = match code {
+        "Enter" => "return_or_enter",
+        "Escape" => "escape",
+        "Backspace" => "delete_or_backspace",
+        "Delete" => "delete_forward",
+        "Tab" => "tab",
+        "Space" => "spacebar",
+        "Minus" => "hyphen",
+        "Equal" => "equal_sign",
+        "BracketLeft" => "open_bracket",
+        "BracketRight" => "close_bracket",
+        "Backslash" => "backslash",
+        "Semicolon" => "semicolon",
+        "Quote" => "quote",
+        "Backquote" => "grave_accent_and_tilde",
+        "Comma" => "comma",
+        "Period" => "period",
+        "Slash" => "slash",
+        "IntlBackslash" => "non_us_backslash",
+        "Home" => "home",
+        "End" => "end",
+        "PageUp" => "page_up",
+        "PageDown" => "page_down",
+        "Help" => "help",
+        "ArrowLeft" => "left_arrow",
+        "ArrowRight" => "right_arrow",
+        "ArrowUp" => "up_arrow",
+        "ArrowDown" => "down_arrow",
+        "NumLock" => "keypad_num_lock",
+        "NumpadDivide" => "keypad_slash",
+        "NumpadMultiply" => "keypad_asterisk",
+        "NumpadSubtract" => "keypad_hyphen",
+        "NumpadAdd" => "keypad_plus",
+        "NumpadEnter" => "keypad_enter",
+        "NumpadDecimal" => "keypad_period",
+        "NumpadEqual" => "keypad_equal_sign",
+        _ => return Err(format!("unsupported key '{}'", name)),
+    };
+    Ok(key_code.to_string())
+}
+
+fn export_karabiner(config: &Config) -> Export {
+    let mut skipped = Vec::new();
+    let mut rules = Vec::new();
+    let layout = keyboard_layout(config);
+
+    for shortcut in &config.shortcuts {
+        let binding = if shortcut.only_in.is_empty() && shortcut.except_in.is_empty() {
+            bindable_chord(shortcut, Target::Karabiner, layout)
+        } else {
+            // Karabiner matches apps by bundle identifier, not by name
+            Err("per-app scoping is not exported to karabiner".to_string())
+        };
+        let binding = binding.and_then(|chord| {
+            let key_code = karabiner_key_code(&chord.keys[0], layout)?;
+            Ok((chord, key_code, cwm_command(shortcut, config)?))
+        });
+        let (chord, key_code, command) = match binding {
+            Ok(binding) => binding,
+            Err(reason) => {
+                skipped.push(Skipped::new(shortcut.keys.clone(), reason));
+                continue;
+            }
+        };
+
+        let mut modifiers = Vec::new();
+        for (on, name) in [
+            (chord.modifiers.ctrl, "control"),
+            (chord.modifiers.alt, "option"),
+            (chord.modifiers.cmd, "command"),
+            (chord.modifiers.shift, "shift"),
+        ] {
+            if on {
+                modifiers.push(name);
+            }
+        }
+
+        rules.push(json!({
+            "description": format!("{}: {}", chord, describe_action(shortcut)),
+            "manipulators": [{
+                "type": "basic",
+                "from": {
+                    "key_code": key_code,
+                    "modifiers": { "mandatory": modifiers },
+                },
+                "to": [{ "shell_command": command }],
+            }],
+        }));
+    }
+
+    let document = json!({
+        "title": "cwm shortcuts",
+        "rules": rules,
+    });
+    Export {
+        content: format!(
+            "{}\n",
+            serde_json::to_string_pretty(&document).unwrap_or_default()
+        ),
+        skipped,
+    }
+}
+
+// ==================== cheat sheet ====================
+
+/// shortcuts of one action type
+#[derive(Debug)]
+struct Group {
+    title: String,
+    description: Option<String>,
+    rows: Vec<Row>,
+}
+
+#[derive(Debug)]
+struct Row {
+    keys: String,
+    action: String,
+    when: String,
+}
+
+/// the shortcuts grouped by action type, in the order actions are documented
+fn cheat_sheet(config: &Config) -> Vec<Group> {
+    let mut groups: Vec<Group> = ACTIONS
+        .iter()
+        .map(|spec| Group {
+            title: capitalize(spec.name),
+            description: Some(summary(spec.description)),
+            rows: Vec::new(),
+        })
+        .collect();
+    groups.push(Group {
+        title: "Cycle".to_string(),
+        description: Some("Each press runs the next step.".to_string()),
+        rows: Vec::new(),
+    });
+    groups.push(Group {
+        title: "Other".to_string(),
+        description: None,
+        rows: Vec::new(),
+    });
+    let cycle_group = ACTIONS.len();
+    let other_group = cycle_group + 1;
+    let layout = keyboard_layout(config);
+
+    // named conditions are shown by name
+    let definitions: HashMap<String, Condition> = config
+        .conditions
+        .keys()
+        .map(|name| (name.clone(), Condition::Ref(name.clone())))
+        .collect();
+
+    for shortcut in &config.shortcuts {
+        let group = if shortcut.cycle.is_empty() {
+            let name = shortcut.action.split(':').next().unwrap_or("");
+            ACTIONS
+                .iter()
+                .position(|spec| spec.name == name)
+                .unwrap_or(other_group)
+        } else {
+            cycle_group
+        };
+
+        let mut when = Vec::new();
+        if let Some(condition) = &shortcut.when {
+            when.push(match parse_condition(condition, &definitions) {
+                Ok(condition) => describe_condition(&condition, false),
+                Err(_) => condition.to_string(),
+            });
+        }
+        if !shortcut.only_in.is_empty() {
+            when.push(format!("in {}", shortcut.only_in.join(" or ")));
+        }
+        if !shortcut.except_in.is_empty() {
+            when.push(format!("not in {}", shortcut.except_in.join(" or ")));
+        }
+
+        groups[group].rows.push(Row {
+            keys: describe_keys(shortcut, layout),
+            action: describe_action(shortcut),
+            when: when.join(", "),
+        });
+    }
+
+    groups.retain(|group| !group.rows.is_empty());
+    groups
+}
+
+fn capitalize(s: &str) -> String {
+    let mut chars = s.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+/// the first sentence of an action description, without a trailing note in parentheses
+fn summary(description: &str) -> String {
+    let sentence = description.split(". ").next().unwrap_or(description);
+    let sentence = sentence.trim_end_matches('.');
+    let sentence = match sentence.rfind(" (") {
+        Some(idx) if sentence.ends_with(')') => &sentence[..idx],
+        _ => sentence,
+    };
+    format!("{}.", capitalize(sentence))
+}
+
+/// the keys as they are matched, with the trigger when it is not a plain press
+fn describe_keys(shortcut: &Shortcut, layout: &Layout) -> String {
+    let keys = KeySequence::parse_in(&shortcut.keys, layout)
+        .map(|sequence| sequence.to_string())
+        .unwrap_or_else(|_| shortcut.keys.clone());
+    match shortcut.trigger {
+        Trigger::Tap => keys,
+        Trigger::DoubleTap => format!("{} (double-tap)", keys),
+        Trigger::Hold => format!("{} (hold)", keys),
+    }
+}
+
+/// `focus Safari`, `move:left`, or the steps of a cycle
+fn describe_action(shortcut: &Shortcut) -> String {
+    let mut action = if shortcut.cycle.is_empty() {
+        shortcut.action.clone()
+    } else {
+        shortcut.cycle.join(" → ")
+    };
+    if let Some(app) = &shortcut.app {
+        action = format!("{} {}", action, app);
+    }
+    if let Some(release) = &shortcut.release_action {
+        action = format!("{}, on release {}", action, release);
+    }
+    action
+}
+
+/// a condition in words, e.g. `display.count >= 2 and not (app is Safari or work)`
+fn describe_condition(condition: &Condition, nested: bool) -> String {
+    match condition {
+        Condition::All(items) if items.is_empty() => "always".to_string(),
+        Condition::Any(items) if items.is_empty() => "never".to_string(),
+        Condition::All(items) => describe_all(items, " and ", nested),
+        Condition::Any(items) => describe_all(items, " or ", nested),
+        Condition::Not(inner) => format!("not {}", describe_condition(inner, true)),
+        Condition::Field(field) => {
+            let op = match field.op {
+                CompareOp::Eq => "is".to_string(),
+                CompareOp::Ne => "is not".to_string(),
+                CompareOp::In => "is one of".to_string(),
+                op => op.to_string(),
+            };
+            format!("{} {} {}", field.field, op, describe_value(&field.value))
+        }
+        Condition::Ref(name) => name.clone(),
+    }
+}
+
+fn describe_all(items: &[Condition], separator: &str, nested: bool) -> String {
+    if let [item] = items {
+        return describe_condition(item, nested);
+    }
+    let text = items
+        .iter()
+        .map(|item| describe_condition(item, true))
+        .collect::<Vec<_>>()
+        .join(separator);
+    if nested {
+        format!("({})", text)
+    } else {
+        text
+    }
+}
+
+fn describe_value(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        Value::List(items) => items
+            .iter()
+            .map(describe_value)
+            .collect::<Vec<_>>()
+            .join(", "),
+        other => other.to_string(),
+    }
+}
+
+fn markdown(groups: &[Group]) -> String {
+    let cell = |s: &str| s.replace('|', r"\|");
+    // a backtick in the keys needs a longer fence
+    let code = |s: &str| {
+        if s.contains('`') {
+            format!("`` {} ``", cell(s))
+        } else {
+            format!("`{}`", cell(s))
+        }
+    };
+
+    let mut out = String::from("# Shortcuts\n");
+    for group in groups {
+        out.push_str(&format!("\n## {}\n\n", group.title));
+        if let Some(description) = &group.description {
+            out.push_str(&format!("{}\n\n", description));
+        }
+        out.push_str("| Keys | Action | When |\n|------|--------|------|\n");
+        for row in &group.rows {
+            out.push_str(&format!(
+                "| {} | {} | {} |\n",
+                code(&row.keys),
+                cell(&row.action),
+                cell(&row.when)
+            ));
+        }
+    }
+    out
+}
+
+fn html(groups: &[Group]) -> String {
+    let mut out = String::from(
+        "<!DOCTYPE html>\n\
+         <html lang=\"en\">\n\
+         <head>\n\
+         <meta charset=\"utf-8\">\n\
+         <title>Shortcuts</title>\n\
+         <style>\n\
+         body { font-family: -apple-system, sans-serif; margin: 2em; }\n\
+         table { border-collapse: collapse; margin-bottom: 1.5em; }\n\
+         th, td { border: 1px solid #ccc; padding: 0.3em 0.8em; text-align: left; }\n\
+         kbd { font-family: ui-monospace, monospace; }\n\
+         </style>\n\
+         </head>\n\
+         <body>\n\
+         <h1>Shortcuts</h1>\n",
+    );
+    for group in groups {
+        out.push_str(&format!("<h2>{}</h2>\n", escape_html(&group.title)));
+        if let Some(description) = &group.description {
+            out.push_str(&format!("<p>{}</p>\n", escape_html(description)));
+        }
+        out.push_str(
+            "<table>\n<thead><tr><th>Keys</th><th>Action</th><th>When</th></tr></thead>\n<tbody>\n",
+        );
+        for row in &group.rows {
+            out.push_str(&format!(
+                "<tr><td><kbd>{}</kbd></td><td>{}</td><td>{}</td></tr>\n",
+                escape_html(&row.keys),
+                escape_html(&row.action),
+                escape_html(&row.when)
+            ));
+        }
+        out.push_str("</tbody>\n</table>\n");
+    }
+    out.push_str("</body>\n</html>\n");
+    out
+}
+
+fn escape_html(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn shortcut(keys: &str, action: &str, app: Option<&str>) -> Shortcut {
+        Shortcut {
+            keys: keys.to_string(),
+            action: action.to_string(),
+            app: app.map(str::to_string),
+            ..Default::default()
+        }
+    }
+
+    fn config(shortcuts: Vec<Shortcut>) -> Config {
+        Config {
+            shortcuts,
+            ..Default::default()
+        }
+    }
+
+    fn skipped(export: &Export, entry: &str) -> String {
+        export
+            .skipped
+            .iter()
+            .find(|s| s.entry == entry)
+            .map(|s| s.reason.clone())
+            .unwrap_or_else(|| panic!("'{}' was not skipped: {:?}", entry, export.skipped))
+    }
+
+    #[test]
+    fn test_target_from_str() {
+        assert_eq!("skhd".parse::<Target>(), Ok(Target::Skhd));
+        assert_eq!("Karabiner".parse::<Target>(), Ok(Target::Karabiner));
+        assert_eq!("md".parse::<Target>(), Ok(Target::Markdown));
+        assert!("pdf".parse::<Target>().is_err());
+    }
+
+    #[test]
+    fn test_cwm_command() {
+        let mut cfg = config(vec![]);
+        cfg.display_aliases
+            .insert("office".to_string(), vec!["DELL U2720Q".to_string()]);
+        let command = |s: &Shortcut| cwm_command(s, &cfg);
+
+        let mut focus = shortcut("ctrl+alt+s", "focus", Some("Google Chrome"));
+        focus.launch = Some(true);
+        assert_eq!(
+            command(&focus).unwrap(),
+            "cwm focus --app 'Google Chrome' --launch"
+        );
+        assert_eq!(
+            command(&shortcut("a", "maximize", None)).unwrap(),
+            "cwm maximize"
+        );
+        assert_eq!(
+            command(&shortcut("a", "move:next", None)).unwrap(),
+            "cwm move --display next"
+        );
+        assert_eq!(
+            command(&shortcut("a", "move:Office", None)).unwrap(),
+            "cwm move --display Office"
+        );
+        assert_eq!(
+            command(&shortcut("a", "move:top-left;display=2", None)).unwrap(),
+            "cwm move --to top-left --display 2"
+        );
+        assert_eq!(
+            command(&shortcut("a", "move:50%,50%", Some("Notes"))).unwrap(),
+            "cwm move --to 50%,50% --app Notes"
+        );
+        assert_eq!(
+            command(&shortcut("a", "resize:80", Some("it's"))).unwrap(),
+            r"cwm resize --to 80 --app 'it'\''s'"
+        );
+        assert!(command(&shortcut("a", "dance", None)).is_err());
+    }
+
+    #[test]
+    fn test_export_skhd() {
+        let mut scoped = shortcut("ctrl+alt+t", "maximize", None);
+        scoped.only_in = vec!["Terminal".to_string(), "iTerm2".to_string()];
+        let mut excluded = shortcut("cmd+shift+up", "maximize", None);
+        excluded.except_in = vec!["Xcode".to_string()];
+        let mut conditional = shortcut("ctrl+alt+c", "maximize", None);
+        conditional.when = Some(json!({ "display.count": { ">=": 2 } }));
+        let mut cycle = shortcut("ctrl+alt+left", "", None);
+        cycle.cycle = vec!["move:left".to_string(), "resize:50".to_string()];
+
+        let cfg = config(vec![
+            shortcut("ctrl+alt+m", "maximize", None),
+            shortcut("ctrl+alt+/", "move:next", None),
+            shortcut("ctrl+alt+f1", "resize:80", None),
+            scoped,
+            excluded,
+            conditional,
+            cycle,
+            shortcut("ctrl+alt+w, h", "move:left", None),
+            shortcut("ctrl+alt+s+f", "maximize", None),
+        ]);
+        let export = export(&cfg, Target::Skhd);
+
+        assert_eq!(
+            export.content,
+            "# generated by cwm shortcuts export\n\
+             ctrl + alt - m : cwm maximize\n\
+             ctrl + alt - 0x2C : cwm move --display next\n\
+             ctrl + alt - f1 : cwm resize --to 80\n\
+             ctrl + alt - t [\n    \"Terminal\" : cwm maximize\n    \"iTerm2\" : cwm maximize\n]\n\
+             cmd + shift - up [\n    \"Xcode\" ~\n    * : cwm maximize\n]\n"
+        );
+        assert_eq!(
+            skipped(&export, "ctrl+alt+c"),
+            "'when' conditions need the cwm daemon"
+        );
+        assert_eq!(
+            skipped(&export, "ctrl+alt+left"),
+            "cycles need the cwm daemon"
+        );
+        assert_eq!(
+            skipped(&export, "ctrl+alt+w, h"),
+            "key sequences need the cwm daemon"
+        );
+        assert_eq!(
+            skipped(&export, "ctrl+alt+s+f"),
+            "chords of several keys are not supported by skhd"
+        );
+    }
+
+    #[test]
+    fn test_export_karabiner() {
+        let mut scoped = shortcut("ctrl+alt+t", "maximize", None);
+        scoped.only_in = vec!["Terminal".to_string()];
+        let mut hold = shortcut("ctrl+alt+h", "maximize", None);
+        hold.trigger = Trigger::Hold;
+        let cfg = config(vec![
+            shortcut("cmd+shift+return", "focus", Some("Terminal")),
+            shortcut("ctrl+alt+-", "resize:50", None),
+            scoped,
+            hold,
+        ]);
+        let export = export(&cfg, Target::Karabiner);
+
+        let document: serde_json::Value = serde_json::from_str(&export.content).unwrap();
+        let rules = document["rules"].as_array().unwrap();
+        assert_eq!(rules.len(), 2);
+        assert_eq!(rules[0]["description"], "cmd+shift+return: focus Terminal");
+        let manipulator = &rules[0]["manipulators"][0];
+        assert_eq!(manipulator["from"]["key_code"], "return_or_enter");
+        assert_eq!(
+            manipulator["from"]["modifiers"]["mandatory"],
+            json!(["command", "shift"])
+        );
+        assert_eq!(
+            manipulator["to"][0]["shell_command"],
+            "cwm focus --app Terminal"
+        );
+        assert_eq!(rules[1]["manipulators"][0]["from"]["key_code"], "hyphen");
+
+        assert_eq!(
+            skipped(&export, "ctrl+alt+t"),
+            "per-app scoping is not exported to karabiner"
+        );
+        assert_eq!(
+            skipped(&export, "ctrl+alt+h"),
+            "double-tap and hold triggers need the cwm daemon"
+        );
+    }
+
+    #[test]
+    fn test_export_uses_the_configured_layout() {
+        let mut cfg = config(vec![shortcut("ctrl+alt+z", "maximize", None)]);
+        cfg.settings.keyboard_layout = Some("de".to_string());
+        let export = export(&cfg, Target::Karabiner);
+
+        // the German z sits where the US y is
+        let document: serde_json::Value = serde_json::from_str(&export.content).unwrap();
+        assert_eq!(
+            document["rules"][0]["manipulators"][0]["from"]["key_code"],
+            "y"
+        );
+    }
+
+    #[test]
+    fn test_describe_condition() {
+        let definitions: HashMap<String, Condition> =
+            [("work".to_string(), Condition::Ref("work".to_string()))].into();
+        let describe = |value: serde_json::Value| {
+            describe_condition(&parse_condition(&value, &definitions).unwrap(), false)
+        };
+
+        assert_eq!(
+            describe(json!({ "display.count": { ">=": 2 } })),
+            "display.count >= 2"
+        );
+        assert_eq!(
+            describe(json!({
+                "all": [
+                    { "app": "Safari" },
+                    { "not": { "any": [{ "$ref": "work" }, { "app": { "in": ["Mail", "Notes"] } }] } },
+                ]
+            })),
+            "app is Safari and not (work or app is one of Mail, Notes)"
+        );
+        assert_eq!(describe(json!(true)), "always");
+    }
+
+    #[test]
+    fn test_cheat_sheet() {
+        let mut conditional = shortcut("ctrl+alt+m", "maximize", None);
+        conditional.when = Some(json!({ "display.count": { ">=": 2 } }));
+        conditional.except_in = vec!["Xcode".to_string()];
+        let mut cycle = shortcut("ctrl+alt+left", "", None);
+        cycle.cycle = vec!["move:left".to_string(), "resize:50".to_string()];
+        let mut double_tap = shortcut("rshift", "focus", Some("Safari"));
+        double_tap.trigger = Trigger::DoubleTap;
+        let cfg = config(vec![
+            cycle,
+            conditional,
+            double_tap,
+            shortcut("ctrl+alt+w, h", "move:next", Some("A|B")),
+        ]);
+
+        let markdown = export(&cfg, Target::Markdown);
+        assert!(markdown.skipped.is_empty());
+        assert_eq!(
+            markdown.content,
+            "# Shortcuts\n\
+             \n## Focus\n\nFocus the application window.\n\n\
+             | Keys | Action | When |\n|------|--------|------|\n\
+             | `rshift (double-tap)` | focus Safari |  |\n\
+             \n## Maximize\n\nMaximize the current or specified window.\n\n\
+             | Keys | Action | When |\n|------|--------|------|\n\
+             | `ctrl+alt+m` | maximize | display.count >= 2, not in Xcode |\n\
+             \n## Move\n\nMove window to a position and/or display.\n\n\
+             | Keys | Action | When |\n|------|--------|------|\n\
+             | `ctrl+alt+w, h` | move:next A\\|B |  |\n\
+             \n## Cycle\n\nEach press runs the next step.\n\n\
+             | Keys | Action | When |\n|------|--------|------|\n\
+             | `ctrl+alt+left` | move:left → resize:50 |  |\n"
+        );
+
+        let html = export(&cfg, Target::Html).content;
+        assert!(html.starts_with("<!DOCTYPE html>"));
+        assert!(
+            html.contains("<h2>Maximize</h2>\n<p>Maximize the current or specified window.</p>")
+        );
+        assert!(html.contains(
+            "<tr><td><kbd>ctrl+alt+m</kbd></td><td>maximize</td><td>display.count &gt;= 2, not in Xcode</td></tr>"
+        ));
+    }
+}
@@ -0,0 +1,659 @@
+//! config file formats
+//!
+//! a config file can be JSON (with comments), YAML or TOML, chosen by its
+//! extension. every format is read into the same JSON value before migrations,
+//! includes and deserialization run, so `when` conditions, `$ref` and every
+//! other setting mean the same thing whichever format a file is written in
+
+use anyhow::{anyhow, Context, Result};
+use serde_json::{Map, Number, Value};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::path::Path;
+use std::str::FromStr;
+use yaml_rust2::parser::{Event, MarkedEventReceiver, Parser};
+use yaml_rust2::scanner::Marker;
+use yaml_rust2::{Yaml, YamlEmitter, YamlLoader};
+
+use super::jsonc;
+use super::schema::DEFAULT_SCHEMA_REF;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Json,
+    Yaml,
+    Toml,
+}
+
+/// a one-based line and column in a config file
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Position {
+    pub line: usize,
+    pub column: usize,
+}
+
+impl fmt::Display for Position {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}, column {}", self.line, self.column)
+    }
+}
+
+/// where each value of a config file starts, keyed by path (`shortcuts[0].keys`)
+pub type Positions = BTreeMap<String, Position>;
+
+impl Format {
+    /// the format of a file, from its extension; anything unknown is JSON
+    pub fn from_path(path: &Path) -> Self {
+        let ext = path
+            .extension()
+            .and_then(|e| e.to_str())
+            .unwrap_or_default()
+            .to_lowercase();
+        match ext.as_str() {
+            "yaml" | "yml" => Format::Yaml,
+            "toml" => Format::Toml,
+            _ => Format::Json,
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Format::Json => "json",
+            Format::Yaml => "yaml",
+            Format::Toml => "toml",
+        }
+    }
+
+    /// parses a config file's text; errors include the line and column
+    pub fn parse(self, text: &str) -> Result<Value> {
+        let value = match self {
+            Format::Json => json5::from_str(text)?,
+            Format::Yaml => parse_yaml(text)?,
+            Format::Toml => toml_edit::de::from_str(text)?,
+        };
+
+        if !value.is_object() {
+            return Err(anyhow!("config must be an object"));
+        }
+        Ok(value)
+    }
+
+    /// renders a config value in this format
+    pub fn render(self, value: &Value) -> Result<String> {
+        match self {
+            Format::Json => {
+                let mut text = serde_json::to_string_pretty(value)?;
+                text.push('\n');
+                Ok(text)
+            }
+            Format::Yaml => render_yaml(value),
+            Format::Toml => toml_edit::ser::to_string_pretty(value)
+                .context("the config cannot be written as TOML"),
+        }
+    }
+
+    /// the position of every value in `text`, for mapping errors back to the file
+    pub fn positions(self, text: &str) -> Result<Positions> {
+        match self {
+            Format::Json => {
+                let line_starts = line_starts(text);
+                Ok(jsonc::offsets(text)?
+                    .into_iter()
+                    .map(|(path, offset)| (path, position_at(text, &line_starts, offset)))
+                    .collect())
+            }
+            Format::Yaml => {
+                let mut receiver = YamlPositions::default();
+                Parser::new_from_str(text).load(&mut receiver, false)?;
+                Ok(receiver.positions)
+            }
+            Format::Toml => {
+                let doc = toml_edit::ImDocument::parse(text)?;
+                let line_starts = line_starts(text);
+                let mut offsets = Vec::new();
+                toml_table_offsets(doc.as_table(), "", &mut offsets);
+                Ok(offsets
+                    .into_iter()
+                    .map(|(path, offset)| (path, position_at(text, &line_starts, offset)))
+                    .collect())
+            }
+        }
+    }
+}
+
+impl fmt::Display for Format {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for Format {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "json" | "jsonc" => Ok(Format::Json),
+            "yaml" | "yml" => Ok(Format::Yaml),
+            "toml" => Ok(Format::Toml),
+            _ => Err(format!(
+                "invalid format '{}', expected: json, yaml, toml",
+                s
+            )),
+        }
+    }
+}
+
+/// reads and parses a config file in the format given by its extension
+pub fn read_value(path: &Path) -> Result<Value> {
+    let text = fs::read_to_string(path)
+        .with_context(|| format!("failed to read config file: {}", path.display()))?;
+    Format::from_path(path)
+        .parse(&text)
+        .with_context(|| format!("failed to parse config file: {}", path.display()))
+}
+
+/// the position of `path` in a file, or of its closest parent that has one
+pub fn locate(positions: &Positions, path: &str) -> Option<Position> {
+    let mut path = path;
+    loop {
+        if let Some(position) = positions.get(path) {
+            return Some(*position);
+        }
+        let cut = path.rfind(['.', '['])?;
+        path = &path[..cut];
+    }
+}
+
+/// appends the file position to an error that starts with a config path
+/// (`shortcuts[0].when: ...`); other errors are returned unchanged
+pub fn annotate_error(error: &str, positions: &Positions) -> String {
+    let path = error.split(": ").next().unwrap_or_default();
+    match locate(positions, path) {
+        Some(position) => format!("{} ({})", error, position),
+        None => error.to_string(),
+    }
+}
+
+/// renders a whole config file in `to`; YAML and TOML have no `$schema` key,
+/// so the schema reference becomes the comment editors look for instead
+pub fn convert(value: &Value, to: Format) -> Result<String> {
+    let mut map = value.as_object().cloned().unwrap_or_default();
+    let schema = map
+        .remove("$schema")
+        .and_then(|v| v.as_str().map(str::to_string))
+        .unwrap_or_else(|| DEFAULT_SCHEMA_REF.to_string());
+
+    match to {
+        Format::Json => {
+            let mut with_schema = Map::new();
+            with_schema.insert("$schema".to_string(), Value::String(schema));
+            with_schema.extend(map);
+            to.render(&Value::Object(with_schema))
+        }
+        Format::Yaml => Ok(format!(
+            "# yaml-language-server: $schema={}\n{}",
+            schema,
+            to.render(&Value::Object(map))?
+        )),
+        Format::Toml => Ok(format!(
+            "#:schema {}\n\n{}",
+            schema,
+            to.render(&Value::Object(map))?
+        )),
+    }
+}
+
+/// applies the changes between `old` and `new` to `target`, leaving parts of
+/// `target` that did not change (including keys `old` does not know) alone
+pub fn patch(target: &mut Value, old: &Value, new: &Value) {
+    match (target, old, new) {
+        (Value::Object(target), Value::Object(old), Value::Object(new)) => {
+            for key in old.keys() {
+                if !new.contains_key(key) {
+                    target.remove(key);
+                }
+            }
+            for (key, new_value) in new {
+                match (target.get_mut(key), old.get(key)) {
+                    (Some(target_value), Some(old_value)) => {
+                        patch(target_value, old_value, new_value)
+                    }
+                    (_, Some(old_value)) if old_value == new_value => {}
+                    _ => {
+                        target.insert(key.clone(), new_value.clone());
+                    }
+                }
+            }
+        }
+        (target, old, new) => {
+            if old != new {
+                *target = new.clone();
+            }
+        }
+    }
+}
+
+fn line_starts(text: &str) -> Vec<usize> {
+    std::iter::once(0)
+        .chain(text.match_indices('\n').map(|(i, _)| i + 1))
+        .collect()
+}
+
+fn position_at(text: &str, line_starts: &[usize], offset: usize) -> Position {
+    let line = line_starts.partition_point(|&start| start <= offset);
+    let start = line_starts[line - 1];
+    Position {
+        line,
+        column: text[start..offset].chars().count() + 1,
+    }
+}
+
+fn parse_yaml(text: &str) -> Result<Value> {
+    let docs = YamlLoader::load_from_str(text)?;
+    match docs.len() {
+        // an empty file is an empty config
+        0 => Ok(Value::Object(Map::new())),
+        1 => yaml_to_json(&docs[0]),
+        _ => Err(anyhow!(
+            "expected a single YAML document, found {}",
+            docs.len()
+        )),
+    }
+}
+
+fn yaml_to_json(yaml: &Yaml) -> Result<Value> {
+    Ok(match yaml {
+        Yaml::Null => Value::Null,
+        Yaml::Boolean(b) => Value::Bool(*b),
+        Yaml::Integer(i) => Value::from(*i),
+        Yaml::Real(s) => s
+            .parse::<f64>()
+            .ok()
+            .and_then(Number::from_f64)
+            .map(Value::Number)
+            .ok_or_else(|| anyhow!("unsupported number: {}", s))?,
+        Yaml::String(s) => Value::String(s.clone()),
+        Yaml::Array(items) => Value::Array(items.iter().map(yaml_to_json).collect::<Result<_>>()?),
+        Yaml::Hash(hash) => {
+            let mut map = Map::new();
+            for (key, value) in hash {
+                let key = match key {
+                    Yaml::String(s) | Yaml::Real(s) => s.clone(),
+                    Yaml::Integer(i) => i.to_string(),
+                    Yaml::Boolean(b) => b.to_string(),
+                    other => return Err(anyhow!("unsupported mapping key: {:?}", other)),
+                };
+                map.insert(key, yaml_to_json(value)?);
+            }
+            Value::Object(map)
+        }
+        Yaml::Alias(_) | Yaml::BadValue => return Err(anyhow!("invalid YAML value")),
+    })
+}
+
+fn json_to_yaml(value: &Value) -> Yaml {
+    match value {
+        Value::Null => Yaml::Null,
+        Value::Bool(b) => Yaml::Boolean(*b),
+        Value::Number(n) => match n.as_i64() {
+            Some(i) => Yaml::Integer(i),
+            None => Yaml::Real(n.to_string()),
+        },
+        Value::String(s) => Yaml::String(s.clone()),
+        Value::Array(items) => Yaml::Array(items.iter().map(json_to_yaml).collect()),
+        Value::Object(map) => Yaml::Hash(
+            map.iter()
+                .map(|(k, v)| (Yaml::String(k.clone()), json_to_yaml(v)))
+                .collect(),
+        ),
+    }
+}
+
+fn render_yaml(value: &Value) -> Result<String> {
+    let mut out = String::new();
+    YamlEmitter::new(&mut out)
+        .dump(&json_to_yaml(value))
+        .map_err(|e| anyhow!("failed to write YAML: {:?}", e))?;
+
+    let mut text = out.strip_prefix("---\n").unwrap_or(&out).to_string();
+    text.push('\n');
+    Ok(text)
+}
+
+/// tracks the path of each YAML event to record where values start
+#[derive(Default)]
+struct YamlPositions {
+    stack: Vec<YamlFrame>,
+    positions: Positions,
+}
+
+enum YamlFrame {
+    /// `key` is the key whose value comes next, None while a key is expected
+    Mapping {
+        path: String,
+        key: Option<String>,
+    },
+    Sequence {
+        path: String,
+        next: usize,
+    },
+}
+
+impl YamlPositions {
+    /// the path of the value starting at `mark`, recording its position
+    fn enter_value(&mut self, mark: Option<Marker>) -> String {
+        let path = match self.stack.last_mut() {
+            None => String::new(),
+            Some(YamlFrame::Mapping { path, key }) => match key.take() {
+                Some(key) if path.is_empty() => key,
+                Some(key) => format!("{}.{}", path, key),
+                // complex keys are not used by configs
+                None => path.clone(),
+            },
+            Some(YamlFrame::Sequence { path, next }) => {
+                *next += 1;
+                format!("{}[{}]", path, *next - 1)
+            }
+        };
+        // mapping values keep the position of their key
+        if let Some(mark) = mark {
+            self.positions.entry(path.clone()).or_insert(Position {
+                line: mark.line(),
+                column: mark.col() + 1,
+            });
+        }
+        path
+    }
+}
+
+impl MarkedEventReceiver for YamlPositions {
+    fn on_event(&mut self, event: Event, mark: Marker) {
+        if let (
+            Event::Scalar(value, ..),
+            Some(YamlFrame::Mapping {
+                path,
+                key: key @ None,
+            }),
+        ) = (&event, self.stack.last_mut())
+        {
+            let key_path = if path.is_empty() {
+                value.clone()
+            } else {
+                format!("{}.{}", path, value)
+            };
+            *key = Some(value.clone());
+            let position = Position {
+                line: mark.line(),
+                column: mark.col() + 1,
+            };
+            // block mappings are marked after their first key, so they start at that key
+            if !path.is_empty() {
+                self.positions.entry(path.clone()).or_insert(position);
+            }
+            self.positions.entry(key_path).or_insert(position);
+            return;
+        }
+
+        match event {
+            Event::Scalar(..) | Event::Alias(_) => {
+                self.enter_value(Some(mark));
+            }
+            Event::MappingStart(..) => {
+                let path = self.enter_value(None);
+                self.stack.push(YamlFrame::Mapping { path, key: None });
+            }
+            Event::SequenceStart(..) => {
+                let path = self.enter_value(Some(mark));
+                self.stack.push(YamlFrame::Sequence { path, next: 0 });
+            }
+            Event::MappingEnd | Event::SequenceEnd => {
+                self.stack.pop();
+            }
+            _ => {}
+        }
+    }
+}
+
+fn toml_table_offsets(table: &toml_edit::Table, path: &str, out: &mut Vec<(String, usize)>) {
+    for (key, item) in table.iter() {
+        let item_path = join_key(path, key);
+        let span = table
+            .key(key)
+            .and_then(|k| k.span())
+            .or_else(|| item.span());
+        if let Some(span) = span {
+            out.push((item_path.clone(), span.start));
+        }
+
+        match item {
+            toml_edit::Item::Table(t) => toml_table_offsets(t, &item_path, out),
+            toml_edit::Item::ArrayOfTables(tables) => {
+                for (i, t) in tables.iter().enumerate() {
+                    let entry_path = format!("{}[{}]", item_path, i);
+                    if let Some(span) = t.span() {
+                        out.push((entry_path.clone(), span.start));
+                    }
+                    toml_table_offsets(t, &entry_path, out);
+                }
+            }
+            toml_edit::Item::Value(v) => toml_value_offsets(v, &item_path, out),
+            toml_edit::Item::None => {}
+        }
+    }
+}
+
+fn toml_value_offsets(value: &toml_edit::Value, path: &str, out: &mut Vec<(String, usize)>) {
+    match value {
+        toml_edit::Value::InlineTable(t) => {
+            for (key, v) in t.iter() {
+                let item_path = join_key(path, key);
+                if let Some(span) = t.key(key).and_then(|k| k.span()).or_else(|| v.span()) {
+                    out.push((item_path.clone(), span.start));
+                }
+                toml_value_offsets(v, &item_path, out);
+            }
+        }
+        toml_edit::Value::Array(items) => {
+            for (i, v) in items.iter().enumerate() {
+                let item_path = format!("{}[{}]", path, i);
+                if let Some(span) = v.span() {
+                    out.push((item_path.clone(), span.start));
+                }
+                toml_value_offsets(v, &item_path, out);
+            }
+        }
+        _ => {}
+    }
+}
+
+fn join_key(path: &str, key: &str) -> String {
+    if path.is_empty() {
+        key.to_string()
+    } else {
+        format!("{}.{}", path, key)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    const YAML: &str = r#"# shortcuts
+shortcuts:
+  - keys: ctrl+alt+s
+    action: focus
+    app: Slack
+    when:
+      all:
+        - $work_hours
+        - { display.count: { ">=": 2 } }
+  - keys: "ctrl+alt+m"
+    action: maximize
+conditions:
+  work_hours:
+    time: 9:00-17:00
+settings:
+  delay_ms: 250
+  animate: no
+"#;
+
+    const TOML: &str = r#"# shortcuts
+[[shortcuts]]
+keys = "ctrl+alt+s"
+action = "focus"
+app = "Slack"
+when = { all = ["$work_hours", { "display.count" = { ">=" = 2 } }] }
+
+[[shortcuts]]
+keys = "ctrl+alt+m"
+action = "maximize"
+
+[conditions.work_hours]
+time = "9:00-17:00"
+
+[settings]
+delay_ms = 250
+animate = "no"
+"#;
+
+    const JSON: &str = r#"{
+  // shortcuts
+  "shortcuts": [
+    {
+      "keys": "ctrl+alt+s",
+      "action": "focus",
+      "app": "Slack",
+      "when": { "all": ["$work_hours", { "display.count": { ">=": 2 } }] }
+    },
+    { "keys": "ctrl+alt+m", "action": "maximize" }
+  ],
+  "conditions": { "work_hours": { "time": "9:00-17:00" } },
+  "settings": { "delay_ms": 250, "animate": "no" }
+}"#;
+
+    #[test]
+    fn test_formats_parse_to_the_same_value() {
+        let json = Format::Json.parse(JSON).unwrap();
+        assert_eq!(Format::Yaml.parse(YAML).unwrap(), json);
+        assert_eq!(Format::Toml.parse(TOML).unwrap(), json);
+        // YAML 1.2: `no` is a string, not a boolean
+        assert_eq!(json["settings"]["animate"], json!("no"));
+    }
+
+    #[test]
+    fn test_render_roundtrip() {
+        let value = Format::Json.parse(JSON).unwrap();
+        for format in [Format::Json, Format::Yaml, Format::Toml] {
+            let text = format.render(&value).unwrap();
+            assert_eq!(format.parse(&text).unwrap(), value, "{}:\n{}", format, text);
+        }
+    }
+
+    #[test]
+    fn test_toml_cannot_hold_null() {
+        assert!(Format::Toml.render(&json!({ "app": null })).is_err());
+    }
+
+    #[test]
+    fn test_from_path() {
+        assert_eq!(Format::from_path(Path::new("config.jsonc")), Format::Json);
+        assert_eq!(Format::from_path(Path::new("a/config.YML")), Format::Yaml);
+        assert_eq!(Format::from_path(Path::new("config.toml")), Format::Toml);
+        assert_eq!("yml".parse::<Format>().unwrap(), Format::Yaml);
+        assert!("xml".parse::<Format>().is_err());
+    }
+
+    #[test]
+    fn test_parse_errors_have_positions() {
+        let yaml = Format::Yaml
+            .parse("shortcuts:\n  - keys: [a\n")
+            .unwrap_err();
+        assert!(format!("{:#}", yaml).contains("line"), "{:#}", yaml);
+        let toml = Format::Toml
+            .parse("[settings]\ndelay_ms = = 1\n")
+            .unwrap_err();
+        assert!(format!("{:#}", toml).contains("line 2"), "{:#}", toml);
+        let json = Format::Json.parse("{\n  \"a\": ,\n}").unwrap_err();
+        assert!(format!("{:#}", json).contains("2:8"), "{:#}", json);
+        assert!(Format::Yaml.parse("- a\n- b\n").is_err());
+    }
+
+    #[test]
+    fn test_positions() {
+        let at = |line, column| Some(Position { line, column });
+        for (format, text) in [
+            (Format::Json, JSON),
+            (Format::Yaml, YAML),
+            (Format::Toml, TOML),
+        ] {
+            let positions = format.positions(text).unwrap();
+            let line_of = |needle: &str| text.lines().position(|l| l.contains(needle)).unwrap() + 1;
+
+            let pos = locate(&positions, "shortcuts[1].action").unwrap();
+            assert_eq!(pos.line, line_of("maximize"), "{}", format);
+            let pos = locate(&positions, "settings.delay_ms").unwrap();
+            assert_eq!(pos.line, line_of("delay_ms"), "{}", format);
+            let pos = locate(&positions, "shortcuts[0].when.all[1]").unwrap();
+            assert_eq!(pos.line, line_of("display.count"), "{}", format);
+            // unknown children fall back to the closest parent
+            assert_eq!(
+                locate(&positions, "conditions.work_hours.nope[3]"),
+                locate(&positions, "conditions.work_hours"),
+                "{}",
+                format
+            );
+        }
+
+        let positions = Format::Yaml.positions(YAML).unwrap();
+        assert_eq!(locate(&positions, "shortcuts[1].keys"), at(10, 5));
+        let positions = Format::Toml.positions(TOML).unwrap();
+        assert_eq!(locate(&positions, "settings.animate"), at(17, 1));
+    }
+
+    #[test]
+    fn test_convert() {
+        let value = Format::Json.parse(JSON).unwrap();
+
+        let yaml = convert(&value, Format::Yaml).unwrap();
+        assert!(yaml.starts_with("# yaml-language-server: $schema=./config.schema.json\n"));
+        let toml = convert(&value, Format::Toml).unwrap();
+        assert!(toml.starts_with("#:schema ./config.schema.json\n"));
+
+        let mut expected = value.clone();
+        expected.as_object_mut().unwrap().remove("$schema");
+        assert_eq!(Format::Yaml.parse(&yaml).unwrap(), expected);
+        assert_eq!(Format::Toml.parse(&toml).unwrap(), expected);
+
+        // converting back restores the `$schema` key, first
+        let json = convert(&Format::Yaml.parse(&yaml).unwrap(), Format::Json).unwrap();
+        assert!(json.starts_with("{\n  \"$schema\": \"./config.schema.json\""));
+    }
+
+    #[test]
+    fn test_annotate_error() {
+        let positions = Format::Yaml.positions(YAML).unwrap();
+        assert_eq!(
+            annotate_error("shortcuts[1].action: invalid action 'x'", &positions),
+            "shortcuts[1].action: invalid action 'x' (line 11, column 5)"
+        );
+        assert_eq!(
+            annotate_error("invalid JSON: x", &positions),
+            "invalid JSON: x"
+        );
+    }
+
+    #[test]
+    fn test_patch_keeps_unknown_keys() {
+        let mut target =
+            json!({ "$schema": "x", "settings": { "a": 1, "b": [1, 2] }, "keep": true });
+        let old = json!({ "settings": { "a": 1, "b": [1, 2] }, "gone": 1 });
+        let new = json!({ "settings": { "a": 2, "b": [1, 2], "c": 3 } });
+        patch(&mut target, &old, &new);
+        assert_eq!(
+            target,
+            json!({ "$schema": "x", "settings": { "a": 2, "b": [1, 2], "c": 3 }, "keep": true })
+        );
+    }
+}
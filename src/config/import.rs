@@ -0,0 +1,907 @@
+//! importing shortcuts from other macOS window managers
+//!
+//! each parser turns an exported config into `Shortcut` entries and lists what
+//! it could not map. only bindings with a cwm equivalent are imported. layouts
+//! like halves, thirds or quarters become a `cycle` step that resizes each side
+//! and then moves the window against the screen edges, as `resize:50x100%;move:left`
+//!
+//! supported sources:
+//! - `rectangle` - the JSON written by Rectangle's "Export" button
+//! - `spectacle` - Spectacle's `Shortcuts.json`
+//! - `skhd` - an `.skhdrc`, mapping `open -a` and common `yabai -m window` commands
+//! - `hammerspoon` - `hs.hotkey.bind(...)` calls in an `init.lua`
+//!
+//! Magnet and Amethyst are not: Magnet has no export, its shortcuts are stored
+//! in its preferences as archived key data, and Amethyst tiles windows, so its
+//! layout, focus and swap commands have no cwm equivalent
+
+use anyhow::{anyhow, Context, Result};
+use regex::Regex;
+use serde::Serialize;
+use serde_json::Value;
+use std::fmt;
+use std::str::FromStr;
+
+use super::conflicts::describe_shortcut;
+use super::keyboard_layout;
+use super::schema::{Config, Shortcut};
+use crate::daemon::hotkeys::{self, Hotkey, Modifiers};
+use crate::daemon::keyboard::Layout;
+use crate::daemon::sequence::KeySequence;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Source {
+    Rectangle,
+    Spectacle,
+    Skhd,
+    Hammerspoon,
+}
+
+impl Source {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Source::Rectangle => "rectangle",
+            Source::Spectacle => "spectacle",
+            Source::Skhd => "skhd",
+            Source::Hammerspoon => "hammerspoon",
+        }
+    }
+}
+
+impl fmt::Display for Source {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for Source {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "rectangle" => Ok(Source::Rectangle),
+            "spectacle" => Ok(Source::Spectacle),
+            "skhd" | "yabai" => Ok(Source::Skhd),
+            "hammerspoon" | "hs" => Ok(Source::Hammerspoon),
+            "magnet" => Err(
+                "importing from Magnet is not supported: it has no export, so bind its layouts with `cycle` steps like resize:50x100%;move:left"
+                    .to_string(),
+            ),
+            "amethyst" => Err(
+                "importing from Amethyst is not supported: it tiles windows, and its layout, focus and swap commands have no cwm equivalent"
+                    .to_string(),
+            ),
+            _ => Err(format!(
+                "invalid import source '{}', expected: rectangle, spectacle, skhd, hammerspoon",
+                s
+            )),
+        }
+    }
+}
+
+/// a binding that was not imported
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Skipped {
+    /// the binding as written in the source, e.g. `leftHalf` or `alt - h`
+    pub entry: String,
+    pub reason: String,
+}
+
+impl Skipped {
+    pub(crate) fn new(entry: impl Into<String>, reason: impl Into<String>) -> Self {
+        Self {
+            entry: entry.into(),
+            reason: reason.into(),
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct Imported {
+    pub shortcuts: Vec<Shortcut>,
+    pub skipped: Vec<Skipped>,
+}
+
+impl Imported {
+    fn add(&mut self, entry: &str, keys: Result<String, String>, action: Result<Action, String>) {
+        match (keys, action) {
+            (Ok(keys), Ok(action)) => self.shortcuts.push(action.into_shortcut(keys)),
+            (Err(reason), _) | (_, Err(reason)) => self.skipped.push(Skipped::new(entry, reason)),
+        }
+    }
+}
+
+/// a cwm action, with the app to focus for `focus`, or `cycle` steps
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Action {
+    Simple(&'static str),
+    Focus(String),
+    Cycle(Vec<String>),
+}
+
+impl Action {
+    fn into_shortcut(self, keys: String) -> Shortcut {
+        let (action, app, launch, cycle) = match self {
+            Action::Simple(action) => (action.to_string(), None, None, Vec::new()),
+            // the source apps launch the app when it is not running
+            Action::Focus(app) => ("focus".to_string(), Some(app), Some(true), Vec::new()),
+            Action::Cycle(steps) => (String::new(), None, None, steps),
+        };
+        Shortcut {
+            keys,
+            action,
+            cycle,
+            app,
+            launch,
+            when: None,
+            ..Default::default()
+        }
+    }
+}
+
+const NO_EQUIVALENT: &str = "no equivalent cwm action";
+
+/// a cycle step putting the window on `width` by `height` cells from column
+/// `x`, row `y` of a `rows` by `cols` grid, as yabai's `--grid` does: each side
+/// is resized, then the window moved against the screen edges its cells touch
+fn grid_step([rows, cols, x, y, width, height]: [u32; 6]) -> Result<String, String> {
+    if rows > 100
+        || cols > 100
+        || width == 0
+        || height == 0
+        || x.saturating_add(width) > cols
+        || y.saturating_add(height) > rows
+    {
+        return Err(format!(
+            "invalid grid {}:{}:{}:{}:{}:{}",
+            rows, cols, x, y, width, height
+        ));
+    }
+
+    enum Edge {
+        Start,
+        Middle,
+        End,
+    }
+    // a side spanning the whole screen counts as centered
+    let edge = |start: u32, len: u32, cells: u32| {
+        if 2 * start + len == cells {
+            Some(Edge::Middle)
+        } else if start == 0 {
+            Some(Edge::Start)
+        } else if start + len == cells {
+            Some(Edge::End)
+        } else {
+            None
+        }
+    };
+    let full_width = width == cols;
+    let anchor = match (edge(x, width, cols), edge(y, height, rows)) {
+        (Some(Edge::Middle), Some(Edge::Middle)) => "center",
+        (Some(Edge::Start), Some(Edge::Middle)) => "left",
+        (Some(Edge::End), Some(Edge::Middle)) => "right",
+        (Some(Edge::Start), Some(Edge::Start)) => "top-left",
+        (Some(Edge::End), Some(Edge::Start)) => "top-right",
+        (Some(Edge::Start), Some(Edge::End)) => "bottom-left",
+        (Some(Edge::End), Some(Edge::End)) => "bottom-right",
+        (Some(Edge::Middle), Some(Edge::Start)) if full_width => "top-left",
+        (Some(Edge::Middle), Some(Edge::End)) if full_width => "bottom-left",
+        _ => return Err(NO_EQUIVALENT.to_string()),
+    };
+
+    let percent = |len: u32, cells: u32| (len * 100 + cells / 2) / cells;
+    Ok(format!(
+        "resize:{}x{}%;move:{}",
+        percent(width, cols),
+        percent(height, rows),
+        anchor
+    ))
+}
+
+/// a cycle with a step per grid placement `[rows, cols, x, y, width, height]`
+fn grid_cycle(placements: &[[u32; 6]]) -> Result<Action, String> {
+    placements
+        .iter()
+        .map(|&placement| grid_step(placement))
+        .collect::<Result<Vec<_>, _>>()
+        .map(Action::Cycle)
+}
+
+/// parses an exported config from `source`, naming keys on `layout`
+pub fn parse(source: Source, text: &str, layout: &Layout) -> Result<Imported> {
+    match source {
+        Source::Rectangle => parse_rectangle(text, layout),
+        Source::Spectacle => parse_spectacle(text, layout),
+        Source::Skhd => Ok(parse_skhd(text, layout)),
+        Source::Hammerspoon => Ok(parse_hammerspoon(text, layout)),
+    }
+}
+
+/// appends `shortcuts` whose keys are not bound yet; keys already in the
+/// config (or earlier in `shortcuts`) are skipped so existing bindings win
+pub fn merge(config: &mut Config, shortcuts: Vec<Shortcut>) -> (Vec<Shortcut>, Vec<Skipped>) {
+    let layout = keyboard_layout(config);
+    let mut bound: Vec<Option<KeySequence>> = config
+        .shortcuts
+        .iter()
+        .map(|s| KeySequence::parse_in(&s.keys, layout).ok())
+        .collect();
+    let mut added = Vec::new();
+    let mut skipped = Vec::new();
+
+    for shortcut in shortcuts {
+        let hotkey = KeySequence::parse_in(&shortcut.keys, layout).ok();
+        if hotkey.is_some() && bound.contains(&hotkey) {
+            skipped.push(Skipped::new(
+                format!("{} ({})", shortcut.keys, describe_shortcut(&shortcut)),
+                "keys are already bound in the config",
+            ));
+            continue;
+        }
+        bound.push(hotkey);
+        config.shortcuts.push(shortcut.clone());
+        added.push(shortcut);
+    }
+
+    (added, skipped)
+}
+
+/// cwm keys for a modifier set and key name, if the listener knows the key
+fn hotkey_string(modifiers: Modifiers, key: &str, layout: &Layout) -> Result<String, String> {
+    let key = key.to_lowercase();
+    if !hotkeys::is_known_key(&key, layout) {
+        return Err(format!("unsupported key '{}'", key));
+    }
+    if modifiers == Modifiers::default() {
+        return Err("bindings without modifiers are not imported".to_string());
+    }
+    Ok(Hotkey {
+        modifiers,
+        keys: vec![key],
+    }
+    .to_string())
+}
+
+// ==================== Rectangle ====================
+
+/// NSEvent modifier flags used in Rectangle's export
+const NS_SHIFT: u64 = 1 << 17;
+const NS_CONTROL: u64 = 1 << 18;
+const NS_OPTION: u64 = 1 << 19;
+const NS_COMMAND: u64 = 1 << 20;
+
+fn rectangle_action(name: &str) -> Result<Action, String> {
+    match name {
+        "maximize" => Ok(Action::Simple("maximize")),
+        // Rectangle's default "almost maximize" is 90% of the screen, centered
+        "almostMaximize" => Ok(Action::Simple("resize:90")),
+        "center" => Ok(Action::Simple("move:center")),
+        "moveLeft" => Ok(Action::Simple("move:left")),
+        "moveRight" => Ok(Action::Simple("move:right")),
+        "nextDisplay" => Ok(Action::Simple("move:next")),
+        "previousDisplay" => Ok(Action::Simple("move:prev")),
+        "maximizeHeight" => Ok(Action::Simple("resize:100%h")),
+        // repeated presses go through a half, two thirds and a third, like
+        // Rectangle's default
+        "leftHalf" => grid_cycle(&[[1, 2, 0, 0, 1, 1], [1, 3, 0, 0, 2, 1], [1, 3, 0, 0, 1, 1]]),
+        "rightHalf" => grid_cycle(&[[1, 2, 1, 0, 1, 1], [1, 3, 1, 0, 2, 1], [1, 3, 2, 0, 1, 1]]),
+        "centerHalf" => grid_cycle(&[[1, 4, 1, 0, 2, 1]]),
+        "topHalf" => grid_cycle(&[[2, 1, 0, 0, 1, 1]]),
+        "bottomHalf" => grid_cycle(&[[2, 1, 0, 1, 1, 1]]),
+        "topLeft" => grid_cycle(&[[2, 2, 0, 0, 1, 1]]),
+        "topRight" => grid_cycle(&[[2, 2, 1, 0, 1, 1]]),
+        "bottomLeft" => grid_cycle(&[[2, 2, 0, 1, 1, 1]]),
+        "bottomRight" => grid_cycle(&[[2, 2, 1, 1, 1, 1]]),
+        "firstThird" => grid_cycle(&[[1, 3, 0, 0, 1, 1]]),
+        "centerThird" => grid_cycle(&[[1, 3, 1, 0, 1, 1]]),
+        "lastThird" => grid_cycle(&[[1, 3, 2, 0, 1, 1]]),
+        "firstTwoThirds" => grid_cycle(&[[1, 3, 0, 0, 2, 1]]),
+        "lastTwoThirds" => grid_cycle(&[[1, 3, 1, 0, 2, 1]]),
+        "firstFourth" => grid_cycle(&[[1, 4, 0, 0, 1, 1]]),
+        "lastFourth" => grid_cycle(&[[1, 4, 3, 0, 1, 1]]),
+        "firstThreeFourths" => grid_cycle(&[[1, 4, 0, 0, 3, 1]]),
+        "lastThreeFourths" => grid_cycle(&[[1, 4, 1, 0, 3, 1]]),
+        _ => Err(NO_EQUIVALENT.to_string()),
+    }
+}
+
+fn parse_rectangle(text: &str, layout: &Layout) -> Result<Imported> {
+    let root: Value = serde_json::from_str(text).context("invalid Rectangle export")?;
+    let shortcuts = root
+        .get("shortcuts")
+        .and_then(|v| v.as_object())
+        .ok_or_else(|| anyhow!("invalid Rectangle export: no 'shortcuts' object"))?;
+
+    let mut imported = Imported::default();
+    for (name, binding) in shortcuts {
+        let key_code = binding.get("keyCode").and_then(|v| v.as_i64());
+        let flags = binding
+            .get("modifierFlags")
+            .and_then(|v| v.as_u64())
+            .unwrap_or(0);
+
+        let keys = match key_code.and_then(|code| layout.key_name(code)) {
+            Some(key) => {
+                let modifiers = Modifiers {
+                    ctrl: flags & NS_CONTROL != 0,
+                    alt: flags & NS_OPTION != 0,
+                    cmd: flags & NS_COMMAND != 0,
+                    shift: flags & NS_SHIFT != 0,
+                };
+                hotkey_string(modifiers, key, layout)
+            }
+            None => Err(format!("unsupported key code {}", key_code.unwrap_or(-1))),
+        };
+        imported.add(name, keys, rectangle_action(name));
+    }
+    Ok(imported)
+}
+
+// ==================== Spectacle ====================
+
+fn spectacle_action(name: &str) -> Result<Action, String> {
+    match name {
+        "MakeFullscreen" => Ok(Action::Simple("maximize")),
+        "MoveToCenter" => Ok(Action::Simple("move:center")),
+        "MoveToNextDisplay" => Ok(Action::Simple("move:next")),
+        "MoveToPreviousDisplay" => Ok(Action::Simple("move:prev")),
+        "MoveToLeftHalf" => grid_cycle(&[[1, 2, 0, 0, 1, 1]]),
+        "MoveToRightHalf" => grid_cycle(&[[1, 2, 1, 0, 1, 1]]),
+        "MoveToTopHalf" => grid_cycle(&[[2, 1, 0, 0, 1, 1]]),
+        "MoveToBottomHalf" => grid_cycle(&[[2, 1, 0, 1, 1, 1]]),
+        "MoveToUpperLeft" => grid_cycle(&[[2, 2, 0, 0, 1, 1]]),
+        "MoveToUpperRight" => grid_cycle(&[[2, 2, 1, 0, 1, 1]]),
+        "MoveToLowerLeft" => grid_cycle(&[[2, 2, 0, 1, 1, 1]]),
+        "MoveToLowerRight" => grid_cycle(&[[2, 2, 1, 1, 1, 1]]),
+        _ => Err(NO_EQUIVALENT.to_string()),
+    }
+}
+
+/// parses a binding like `alt+cmd+f` or `ctrl+alt+cmd+→`
+fn spectacle_keys(binding: &str, layout: &Layout) -> Result<String, String> {
+    let mut modifiers = Modifiers::default();
+    let mut key = None;
+    for part in binding.split('+').map(str::trim) {
+        match part {
+            "ctrl" | "control" => modifiers.ctrl = true,
+            "alt" | "option" => modifiers.alt = true,
+            "cmd" | "command" => modifiers.cmd = true,
+            "shift" => modifiers.shift = true,
+            "←" => key = Some("left"),
+            "→" => key = Some("right"),
+            "↑" => key = Some("up"),
+            "↓" => key = Some("down"),
+            other => key = Some(other),
+        }
+    }
+    match key {
+        Some(key) => hotkey_string(modifiers, key, layout),
+        None => Err(format!("no key in '{}'", binding)),
+    }
+}
+
+fn parse_spectacle(text: &str, layout: &Layout) -> Result<Imported> {
+    let root: Value = serde_json::from_str(text).context("invalid Spectacle shortcuts file")?;
+    let entries = root
+        .as_array()
+        .ok_or_else(|| anyhow!("invalid Spectacle shortcuts file: expected an array"))?;
+
+    let mut imported = Imported::default();
+    for entry in entries {
+        let name = entry
+            .get("shortcut_name")
+            .and_then(|v| v.as_str())
+            .unwrap_or("");
+        // unbound shortcuts have no key binding
+        let Some(binding) = entry
+            .get("shortcut_key_binding")
+            .and_then(|v| v.as_str())
+            .filter(|b| !b.is_empty())
+        else {
+            continue;
+        };
+        imported.add(
+            name,
+            spectacle_keys(binding, layout),
+            spectacle_action(name),
+        );
+    }
+    Ok(imported)
+}
+
+// ==================== skhd ====================
+
+/// joins `\`-continued lines and drops comments and blank lines
+fn skhd_lines(text: &str) -> Vec<String> {
+    let mut lines = Vec::new();
+    let mut current = String::new();
+    for line in text.lines() {
+        let trimmed = line.trim();
+        if current.is_empty() && (trimmed.is_empty() || trimmed.starts_with('#')) {
+            continue;
+        }
+        match trimmed.strip_suffix('\\') {
+            Some(rest) => {
+                current.push_str(rest);
+                current.push(' ');
+            }
+            None => {
+                current.push_str(trimmed);
+                lines.push(std::mem::take(&mut current));
+            }
+        }
+    }
+    if !current.is_empty() {
+        lines.push(current);
+    }
+    lines
+}
+
+/// parses `ctrl + alt - h`
+fn skhd_keys(hotkey: &str, layout: &Layout) -> Result<String, String> {
+    let (mods, key) = match hotkey.rsplit_once('-') {
+        // `alt - -` binds the minus key
+        Some((mods, key)) if !key.trim().is_empty() => (mods, key.trim()),
+        Some((mods, _)) if mods.trim_end().ends_with('-') => {
+            let mods = mods.trim_end();
+            (&mods[..mods.len() - 1], "-")
+        }
+        _ => ("", hotkey.trim()),
+    };
+
+    let mut modifiers = Modifiers::default();
+    for part in mods.split('+').map(str::trim).filter(|p| !p.is_empty()) {
+        match part {
+            "ctrl" | "lctrl" | "rctrl" => modifiers.ctrl = true,
+            "alt" | "lalt" | "ralt" => modifiers.alt = true,
+            "cmd" | "lcmd" | "rcmd" => modifiers.cmd = true,
+            "shift" | "lshift" | "rshift" => modifiers.shift = true,
+            "hyper" => {
+                modifiers = Modifiers {
+                    ctrl: true,
+                    alt: true,
+                    cmd: true,
+                    shift: true,
+                }
+            }
+            "meh" => {
+                modifiers.ctrl = true;
+                modifiers.alt = true;
+                modifiers.shift = true;
+            }
+            other => return Err(format!("unsupported modifier '{}'", other)),
+        }
+    }
+
+    let key = match key.strip_prefix("0x") {
+        Some(hex) => i64::from_str_radix(hex, 16)
+            .ok()
+            .and_then(|code| layout.key_name(code))
+            .ok_or_else(|| format!("unsupported key code {}", key))?,
+        None => key,
+    };
+    hotkey_string(modifiers, key, layout)
+}
+
+fn skhd_action(command: &str) -> Result<Action, String> {
+    let open = Regex::new(r#"^open\s+-n?a\s+(?:"([^"]+)"|'([^']+)'|(\S+))\s*$"#).unwrap();
+    if let Some(caps) = open.captures(command) {
+        let app = caps
+            .get(1)
+            .or_else(|| caps.get(2))
+            .or_else(|| caps.get(3))
+            .map(|m| m.as_str().trim_end_matches(".app").to_string())
+            .unwrap_or_default();
+        return Ok(Action::Focus(app));
+    }
+
+    if command.contains(';') || command.contains("&&") || command.contains('|') {
+        return Err("only single commands are imported".to_string());
+    }
+
+    let args: Vec<&str> = command.split_whitespace().collect();
+    match args.as_slice() {
+        ["yabai", "-m", "window", "--display", "next"] => Ok(Action::Simple("move:next")),
+        ["yabai", "-m", "window", "--display", "prev"] => Ok(Action::Simple("move:prev")),
+        ["yabai", "-m", "window", "--toggle", "zoom-fullscreen"]
+        | ["yabai", "-m", "window", "--grid", "1:1:0:0:1:1"] => Ok(Action::Simple("maximize")),
+        ["yabai", "-m", "window", "--grid", grid] => {
+            let cells: Vec<u32> = grid
+                .split(':')
+                .map(|n| n.parse().map_err(|_| format!("invalid grid {}", grid)))
+                .collect::<Result<_, _>>()?;
+            let placement: [u32; 6] = cells
+                .try_into()
+                .map_err(|_| format!("invalid grid {}", grid))?;
+            grid_cycle(&[placement])
+        }
+        _ => Err(NO_EQUIVALENT.to_string()),
+    }
+}
+
+fn parse_skhd(text: &str, layout: &Layout) -> Imported {
+    let mut imported = Imported::default();
+    for line in skhd_lines(text) {
+        let entry = line.split(':').next().unwrap_or(&line).trim().to_string();
+
+        if line.starts_with("::") || entry.contains('<') {
+            let entry = if entry.is_empty() {
+                line.clone()
+            } else {
+                entry
+            };
+            imported
+                .skipped
+                .push(Skipped::new(entry, "skhd modes are not supported"));
+            continue;
+        }
+
+        // `hotkey : command`, or `hotkey [ ... ]` for per-app commands
+        let Some((hotkey, command)) = line.split_once(':') else {
+            let reason = if line.contains('[') {
+                "per-application bindings are not supported"
+            } else {
+                "not a hotkey binding"
+            };
+            imported.skipped.push(Skipped::new(entry, reason));
+            continue;
+        };
+        if hotkey.contains('[') || hotkey.contains("->") {
+            imported.skipped.push(Skipped::new(
+                entry,
+                "per-application and passthrough bindings are not supported",
+            ));
+            continue;
+        }
+
+        imported.add(
+            &entry,
+            skhd_keys(hotkey.trim(), layout),
+            skhd_action(command.trim()),
+        );
+    }
+    imported
+}
+
+// ==================== Hammerspoon ====================
+
+fn hammerspoon_action(body: &str) -> Result<Action, String> {
+    let launch = Regex::new(r#"hs\.application\.launchOrFocus\(\s*["']([^"']+)["']\s*\)"#).unwrap();
+    if let Some(caps) = launch.captures(body) {
+        return Ok(Action::Focus(caps[1].to_string()));
+    }
+
+    if body.contains(":maximize(") {
+        Ok(Action::Simple("maximize"))
+    } else if body.contains(":centerOnScreen(") {
+        Ok(Action::Simple("move:center"))
+    } else if body.contains(":moveToScreen(") && body.contains(":next()") {
+        Ok(Action::Simple("move:next"))
+    } else if body.contains(":moveToScreen(") && body.contains(":previous()") {
+        Ok(Action::Simple("move:prev"))
+    } else {
+        Err(NO_EQUIVALENT.to_string())
+    }
+}
+
+fn hammerspoon_keys(mods: &str, key: &str, layout: &Layout) -> Result<String, String> {
+    let mut modifiers = Modifiers::default();
+    let quoted = Regex::new(r#"["']([^"']+)["']"#).unwrap();
+    for caps in quoted.captures_iter(mods) {
+        match caps[1].to_lowercase().as_str() {
+            "ctrl" | "control" | "⌃" => modifiers.ctrl = true,
+            "alt" | "option" | "⌥" => modifiers.alt = true,
+            "cmd" | "command" | "⌘" => modifiers.cmd = true,
+            "shift" | "⇧" => modifiers.shift = true,
+            other => return Err(format!("unsupported modifier '{}'", other)),
+        }
+    }
+    hotkey_string(modifiers, key, layout)
+}
+
+fn parse_hammerspoon(text: &str, layout: &Layout) -> Imported {
+    // modifiers must be a literal table; a variable like `hyper` can't be resolved
+    let bind = Regex::new(
+        r#"hs\.hotkey\.bind\(\s*(\{[^}]*\}|[A-Za-z_][A-Za-z0-9_.]*)\s*,\s*["']([^"']+)["']"#,
+    )
+    .unwrap();
+
+    let starts: Vec<_> = bind.captures_iter(text).collect();
+    let mut imported = Imported::default();
+    for (i, caps) in starts.iter().enumerate() {
+        let whole = caps.get(0).unwrap();
+        let end = starts
+            .get(i + 1)
+            .map(|next| next.get(0).unwrap().start())
+            .unwrap_or(text.len());
+        let body = &text[whole.end()..end];
+        let (mods, key) = (&caps[1], &caps[2]);
+        let entry = format!("{} {}", mods, key);
+
+        let keys = if mods.starts_with('{') {
+            hammerspoon_keys(mods, key, layout)
+        } else {
+            Err(format!("modifiers '{}' are not a literal table", mods))
+        };
+        imported.add(&entry, keys, hammerspoon_action(body));
+    }
+    imported
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::daemon::keyboard::{GERMAN, US};
+
+    fn actions(imported: &Imported) -> Vec<(String, String, Option<String>)> {
+        imported
+            .shortcuts
+            .iter()
+            .map(|s| (s.keys.clone(), s.action.clone(), s.app.clone()))
+            .collect()
+    }
+
+    fn entry(keys: &str, action: &str, app: Option<&str>) -> (String, String, Option<String>) {
+        (
+            keys.to_string(),
+            action.to_string(),
+            app.map(str::to_string),
+        )
+    }
+
+    fn cycle(imported: &Imported, keys: &str) -> Vec<String> {
+        imported
+            .shortcuts
+            .iter()
+            .find(|s| s.keys == keys)
+            .map(|s| s.cycle.clone())
+            .unwrap_or_default()
+    }
+
+    fn skipped(imported: &Imported, entry: &str) -> String {
+        imported
+            .skipped
+            .iter()
+            .find(|s| s.entry == entry)
+            .map(|s| s.reason.clone())
+            .unwrap_or_else(|| panic!("'{}' was not skipped: {:?}", entry, imported.skipped))
+    }
+
+    #[test]
+    fn test_parse_rectangle() {
+        let text = include_str!("../../tests/fixtures/import/rectangle.json");
+        let imported = parse(Source::Rectangle, text, &US).unwrap();
+
+        assert_eq!(
+            actions(&imported),
+            vec![
+                entry("ctrl+alt+left", "", None),
+                entry("ctrl+alt+return", "maximize", None),
+                entry("ctrl+alt+c", "move:center", None),
+                entry("ctrl+alt+u", "", None),
+                entry("ctrl+alt+cmd+right", "move:next", None),
+                entry("ctrl+alt+cmd+left", "move:prev", None),
+                entry("ctrl+alt+shift+return", "resize:90", None),
+            ]
+        );
+        assert_eq!(
+            cycle(&imported, "ctrl+alt+left"),
+            vec![
+                "resize:50x100%;move:left",
+                "resize:67x100%;move:left",
+                "resize:33x100%;move:left"
+            ]
+        );
+        assert_eq!(
+            cycle(&imported, "ctrl+alt+u"),
+            vec!["resize:50x50%;move:top-left"]
+        );
+        assert_eq!(skipped(&imported, "restore"), NO_EQUIVALENT);
+        assert_eq!(imported.skipped.len(), 1);
+    }
+
+    #[test]
+    fn test_parse_spectacle() {
+        let text = include_str!("../../tests/fixtures/import/spectacle.json");
+        let imported = parse(Source::Spectacle, text, &US).unwrap();
+
+        assert_eq!(
+            actions(&imported),
+            vec![
+                entry("alt+cmd+f", "maximize", None),
+                entry("alt+cmd+c", "move:center", None),
+                entry("alt+cmd+left", "", None),
+                entry("ctrl+alt+cmd+right", "move:next", None),
+            ]
+        );
+        assert_eq!(
+            cycle(&imported, "alt+cmd+left"),
+            vec!["resize:50x100%;move:left"]
+        );
+        assert_eq!(skipped(&imported, "MakeLarger"), NO_EQUIVALENT);
+        // unbound shortcuts are ignored
+        assert!(!imported
+            .skipped
+            .iter()
+            .any(|s| s.entry == "MoveToPreviousDisplay"));
+    }
+
+    #[test]
+    fn test_parse_skhd() {
+        let text = include_str!("../../tests/fixtures/import/skhdrc");
+        let imported = parse(Source::Skhd, text, &US).unwrap();
+
+        assert_eq!(
+            actions(&imported),
+            vec![
+                entry("alt+return", "focus", Some("iTerm")),
+                entry("ctrl+alt+s", "focus", Some("Visual Studio Code")),
+                entry("ctrl+alt+cmd+shift+f", "maximize", None),
+                entry("alt+shift+n", "move:next", None),
+                entry("alt+shift+p", "move:prev", None),
+                entry("ctrl+alt+m", "maximize", None),
+                entry("alt+l", "", None),
+                entry("alt+left", "move:prev", None),
+            ]
+        );
+        assert_eq!(cycle(&imported, "alt+l"), vec!["resize:50x100%;move:right"]);
+        assert_eq!(skipped(&imported, "alt - h"), NO_EQUIVALENT);
+        // the middle of the top row touches no screen corner or side
+        assert_eq!(skipped(&imported, "alt - u"), NO_EQUIVALENT);
+        assert_eq!(
+            skipped(&imported, "shift + alt - x"),
+            "only single commands are imported"
+        );
+        assert_eq!(
+            skipped(&imported, "resize < h"),
+            "skhd modes are not supported"
+        );
+        assert_eq!(skipped(&imported, "fn - k"), "unsupported modifier 'fn'");
+        assert!(imported.shortcuts[0].launch.unwrap());
+    }
+
+    #[test]
+    fn test_parse_hammerspoon() {
+        let text = include_str!("../../tests/fixtures/import/hammerspoon.lua");
+        let imported = parse(Source::Hammerspoon, text, &US).unwrap();
+
+        assert_eq!(
+            actions(&imported),
+            vec![
+                entry("ctrl+alt+cmd+f", "maximize", None),
+                entry("ctrl+alt+cmd+c", "move:center", None),
+                entry("ctrl+alt+cmd+n", "move:next", None),
+                entry("alt+cmd+s", "focus", Some("Safari")),
+            ]
+        );
+        assert_eq!(
+            skipped(&imported, "{\"cmd\", \"alt\", \"ctrl\"} left"),
+            NO_EQUIVALENT
+        );
+        assert_eq!(
+            skipped(&imported, "hyper T"),
+            "modifiers 'hyper' are not a literal table"
+        );
+    }
+
+    #[test]
+    fn test_grid_step() {
+        let step = |placement| grid_step(placement).unwrap();
+        assert_eq!(step([1, 2, 0, 0, 1, 1]), "resize:50x100%;move:left");
+        assert_eq!(step([1, 3, 1, 0, 2, 1]), "resize:67x100%;move:right");
+        assert_eq!(step([1, 3, 1, 0, 1, 1]), "resize:33x100%;move:center");
+        assert_eq!(step([2, 1, 0, 1, 1, 1]), "resize:100x50%;move:bottom-left");
+        assert_eq!(step([2, 2, 1, 1, 1, 1]), "resize:50x50%;move:bottom-right");
+        assert_eq!(step([1, 4, 0, 0, 3, 1]), "resize:75x100%;move:left");
+
+        // the second of four columns touches no side of the screen
+        assert_eq!(
+            grid_step([1, 4, 1, 0, 1, 1]),
+            Err(NO_EQUIVALENT.to_string())
+        );
+        assert!(grid_step([1, 2, 2, 0, 1, 1]).is_err());
+        assert!(grid_step([1, 2, 0, 0, 0, 1]).is_err());
+        assert!(grid_step([1, 200, 0, 0, 1, 1]).is_err());
+    }
+
+    #[test]
+    fn test_imported_actions_are_valid() {
+        let sources = [
+            (
+                Source::Rectangle,
+                include_str!("../../tests/fixtures/import/rectangle.json"),
+            ),
+            (
+                Source::Spectacle,
+                include_str!("../../tests/fixtures/import/spectacle.json"),
+            ),
+            (
+                Source::Skhd,
+                include_str!("../../tests/fixtures/import/skhdrc"),
+            ),
+            (
+                Source::Hammerspoon,
+                include_str!("../../tests/fixtures/import/hammerspoon.lua"),
+            ),
+        ];
+        for (source, text) in sources {
+            let config = Config {
+                shortcuts: parse(source, text, &US).unwrap().shortcuts,
+                ..Config::default()
+            };
+            assert_eq!(
+                super::super::validate_config(&config),
+                Vec::<String>::new(),
+                "{}",
+                source
+            );
+        }
+    }
+
+    #[test]
+    fn test_merge_keeps_existing_bindings() {
+        let mut config = Config::default();
+        config.shortcuts.push(Shortcut {
+            keys: "alt+ctrl+c".to_string(),
+            action: "maximize".to_string(),
+            app: None,
+            launch: None,
+            when: None,
+            ..Default::default()
+        });
+
+        let imported = parse(
+            Source::Spectacle,
+            r#"[
+                {"shortcut_key_binding": "ctrl+alt+c", "shortcut_name": "MoveToCenter"},
+                {"shortcut_key_binding": "alt+cmd+f", "shortcut_name": "MakeFullscreen"},
+                {"shortcut_key_binding": "cmd+alt+f", "shortcut_name": "MoveToNextDisplay"}
+            ]"#,
+            &US,
+        )
+        .unwrap();
+        let (added, skipped) = merge(&mut config, imported.shortcuts);
+
+        assert_eq!(added.len(), 1);
+        assert_eq!(added[0].keys, "alt+cmd+f");
+        assert_eq!(skipped.len(), 2);
+        assert_eq!(skipped[0].entry, "ctrl+alt+c (move:center)");
+        assert_eq!(config.shortcuts.len(), 2);
+    }
+
+    #[test]
+    fn test_parse_names_keys_on_the_layout() {
+        // Rectangle stores the key code, which is z on a German keyboard
+        let text = r#"{"shortcuts": {"maximize": {"keyCode": 16, "modifierFlags": 786432}}}"#;
+        let imported = parse(Source::Rectangle, text, &GERMAN).unwrap();
+        assert_eq!(
+            actions(&imported),
+            vec![entry("ctrl+alt+z", "maximize", None)]
+        );
+
+        let imported = parse(
+            Source::Skhd,
+            "ctrl + alt - 0x10 : yabai -m window --toggle zoom-fullscreen",
+            &GERMAN,
+        )
+        .unwrap();
+        assert_eq!(
+            actions(&imported),
+            vec![entry("ctrl+alt+z", "maximize", None)]
+        );
+    }
+
+    #[test]
+    fn test_source_from_str() {
+        assert_eq!("Rectangle".parse::<Source>(), Ok(Source::Rectangle));
+        assert_eq!("yabai".parse::<Source>(), Ok(Source::Skhd));
+        assert!("magnet"
+            .parse::<Source>()
+            .unwrap_err()
+            .contains("Magnet is not supported"));
+        assert!("amethyst"
+            .parse::<Source>()
+            .unwrap_err()
+            .contains("tiles windows"));
+    }
+}
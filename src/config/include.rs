@@ -0,0 +1,554 @@
+//! config includes and layered merging
+//!
+//! a config file can pull in other files with
+//! `"include": ["./team.jsonc", "~/.cwm/local.jsonc"]`. included files are
+//! applied in order and the including file is applied last, so later layers win:
+//! - objects (`settings`, `display_aliases`, `conditions`, `vars`, `modes`) are merged key by key
+//! - `shortcuts`, `mouse_bindings`, `app_rules` and `spotlight` are appended; an
+//!   entry replaces entries from earlier layers with the same `keys`, `mouse`,
+//!   `app` or `name`. keys and mouse bindings are compared by what they bind,
+//!   read with the merged `settings.keyboard_layout`, so `alt+ctrl+s` replaces
+//!   `ctrl+alt+s`
+//! - any other value, including other arrays, is replaced
+//!
+//! relative include paths are resolved against the including file's directory.
+//! included files can include other files; cycles are an error
+
+use anyhow::{anyhow, Context, Result};
+use serde_json::{Map, Value};
+use std::collections::{BTreeMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use super::conflicts;
+use crate::daemon::keyboard::{self, Layout};
+use crate::daemon::mouse::MouseChord;
+
+const INCLUDE_KEY: &str = "include";
+
+/// top-level arrays merged by key, with the field that identifies an entry
+const KEYED_ARRAYS: &[(&str, &str)] = &[
+    ("shortcuts", "keys"),
+    ("mouse_bindings", "mouse"),
+    ("app_rules", "app"),
+    ("spotlight", "name"),
+];
+
+/// file each part of the resolved config came from, keyed by JSON path
+/// (e.g. `shortcuts[2]`, `settings.history.limit`, `display_aliases.office`)
+pub type Origins = BTreeMap<String, PathBuf>;
+
+/// a config merged from a file and everything it includes
+#[derive(Debug, Clone)]
+pub struct ResolvedConfig {
+    pub value: Value,
+    pub origins: Origins,
+    /// files in the order they were applied (the top-level file is last)
+    pub files: Vec<PathBuf>,
+}
+
+/// read a config file in any supported format into a JSON value
+fn read_value(path: &Path) -> Result<Value> {
+    let value = super::format::read_value(path)?;
+
+    // each file carries its own version, so layers are migrated before merging
+    let migrated = super::migrate::migrate(value)
+        .with_context(|| format!("failed to migrate config file: {}", path.display()))?;
+    Ok(migrated.value)
+}
+
+/// load a config file, merging in everything it includes
+pub fn resolve(path: &Path) -> Result<ResolvedConfig> {
+    let mut layers = Vec::new();
+    collect_layers(path, None, &mut Vec::new(), &mut layers)?;
+
+    let files = layers.iter().map(|(path, _)| path.clone()).collect();
+    let (value, origins) = merge_layers(layers)?;
+
+    Ok(ResolvedConfig {
+        value,
+        origins,
+        files,
+    })
+}
+
+/// merge `overlay` on top of `base` with the same rules as included files
+pub fn merge_overlay(base: Value, overlay: Value) -> Result<Value> {
+    let layers = vec![(PathBuf::new(), base), (PathBuf::new(), overlay)];
+    merge_layers(layers).map(|(value, _)| value)
+}
+
+/// depth-first: a file's includes come before the file itself
+fn collect_layers(
+    path: &Path,
+    included_from: Option<&Path>,
+    stack: &mut Vec<PathBuf>,
+    layers: &mut Vec<(PathBuf, Value)>,
+) -> Result<()> {
+    let canonical = fs::canonicalize(path).map_err(|_| match included_from {
+        Some(parent) => anyhow!(
+            "included file not found: {} (included from {})",
+            path.display(),
+            parent.display()
+        ),
+        None => anyhow!("config file not found: {}", path.display()),
+    })?;
+
+    if let Some(pos) = stack.iter().position(|p| p == &canonical) {
+        let chain: Vec<String> = stack[pos..]
+            .iter()
+            .chain(std::iter::once(&canonical))
+            .map(|p| p.display().to_string())
+            .collect();
+        return Err(anyhow!("include cycle: {}", chain.join(" -> ")));
+    }
+
+    let mut value = read_value(&canonical)?;
+    let includes = match value.as_object_mut().and_then(|m| m.remove(INCLUDE_KEY)) {
+        Some(includes) => include_paths(&includes, &canonical)?,
+        None => Vec::new(),
+    };
+
+    stack.push(canonical.clone());
+    for include in &includes {
+        collect_layers(include, Some(&canonical), stack, layers)?;
+    }
+    stack.pop();
+
+    layers.push((canonical, value));
+    Ok(())
+}
+
+/// parse the `include` value of `file` into paths
+fn include_paths(value: &Value, file: &Path) -> Result<Vec<PathBuf>> {
+    let entries: Vec<&str> = match value {
+        Value::String(s) => vec![s.as_str()],
+        Value::Array(items) => items
+            .iter()
+            .map(|item| item.as_str())
+            .collect::<Option<_>>()
+            .ok_or_else(|| include_type_error(file))?,
+        _ => return Err(include_type_error(file)),
+    };
+
+    let base = file.parent().unwrap_or_else(|| Path::new("."));
+    Ok(entries
+        .into_iter()
+        .map(|entry| resolve_include_path(entry, base))
+        .collect())
+}
+
+fn include_type_error(file: &Path) -> anyhow::Error {
+    anyhow!(
+        "'include' must be a string or an array of strings in {}",
+        file.display()
+    )
+}
+
+/// expand `~` and resolve relative paths against `base`
+fn resolve_include_path(entry: &str, base: &Path) -> PathBuf {
+    if let Some(rest) = entry.strip_prefix("~/") {
+        if let Some(home) = dirs::home_dir() {
+            return home.join(rest);
+        }
+    }
+
+    let path = Path::new(entry);
+    if path.is_absolute() {
+        path.to_path_buf()
+    } else {
+        base.join(path)
+    }
+}
+
+/// identity of a keyed array entry: the normalised keys or mouse chord it
+/// binds, or else (other fields, or keys that don't parse) its value compared
+/// case-insensitively
+fn entry_key(item: &Value, field: &str, layout: &Layout) -> Option<String> {
+    let raw = item.get(field)?.as_str()?;
+    let normalized = match field {
+        "keys" => conflicts::normalize(raw, layout).map(|sequence| sequence.to_string()),
+        "mouse" => MouseChord::parse(raw).ok().map(|chord| chord.to_string()),
+        _ => None,
+    };
+    Some(normalized.unwrap_or_else(|| raw.to_lowercase()))
+}
+
+/// the layout keys are read with: the last layer's `settings.keyboard_layout` wins
+fn layers_layout(layers: &[(PathBuf, Value)]) -> &'static Layout {
+    let id = layers.iter().rev().find_map(|(_, value)| {
+        value
+            .get("settings")
+            .and_then(|settings| settings.get("keyboard_layout"))
+            .and_then(|id| id.as_str())
+    });
+    keyboard::configured(id)
+}
+
+fn merge_layers(layers: Vec<(PathBuf, Value)>) -> Result<(Value, Origins)> {
+    let mut merged = Map::new();
+    let mut origins = Origins::new();
+    let mut keyed: BTreeMap<&'static str, Vec<(Value, PathBuf)>> = BTreeMap::new();
+    let layout = layers_layout(&layers);
+
+    for (file, value) in layers {
+        let Value::Object(map) = value else {
+            continue;
+        };
+
+        for (key, value) in map {
+            let Some(&(name, field)) = KEYED_ARRAYS.iter().find(|(name, _)| *name == key) else {
+                merge_value(&mut merged, key.clone(), value, &key, &file, &mut origins);
+                continue;
+            };
+
+            let Value::Array(items) = value else {
+                return Err(anyhow!("'{}' must be an array in {}", key, file.display()));
+            };
+
+            // entries defined in this layer replace same-keyed entries from earlier layers
+            let entries = keyed.entry(name).or_default();
+            let replaced: HashSet<String> = items
+                .iter()
+                .filter_map(|i| entry_key(i, field, layout))
+                .collect();
+            entries.retain(|(item, _)| {
+                !matches!(entry_key(item, field, layout), Some(k) if replaced.contains(&k))
+            });
+            entries.extend(items.into_iter().map(|item| (item, file.clone())));
+        }
+    }
+
+    for (name, entries) in keyed {
+        let mut items = Vec::with_capacity(entries.len());
+        for (i, (item, file)) in entries.into_iter().enumerate() {
+            origins.insert(format!("{}[{}]", name, i), file);
+            items.push(item);
+        }
+        merged.insert(name.to_string(), Value::Array(items));
+    }
+
+    Ok((Value::Object(merged), origins))
+}
+
+/// merge `value` into `target[key]`: objects merge recursively, anything else replaces
+fn merge_value(
+    target: &mut Map<String, Value>,
+    key: String,
+    value: Value,
+    path: &str,
+    file: &Path,
+    origins: &mut Origins,
+) {
+    if let (Some(Value::Object(existing)), Value::Object(incoming)) = (target.get_mut(&key), &value)
+    {
+        if !incoming.is_empty() {
+            let Value::Object(incoming) = value else {
+                unreachable!()
+            };
+            for (child_key, child_value) in incoming {
+                let child_path = format!("{}.{}", path, child_key);
+                merge_value(existing, child_key, child_value, &child_path, file, origins);
+            }
+            return;
+        }
+    }
+
+    // replaced values drop the origins of whatever they replaced
+    let nested = format!("{}.", path);
+    origins.retain(|k, _| k != path && !k.starts_with(&nested));
+    record_origins(&value, path, file, origins);
+    target.insert(key, value);
+}
+
+/// record `file` as the origin of every leaf under `path`
+fn record_origins(value: &Value, path: &str, file: &Path, origins: &mut Origins) {
+    match value {
+        Value::Object(map) if !map.is_empty() => {
+            for (key, child) in map {
+                record_origins(child, &format!("{}.{}", path, key), file, origins);
+            }
+        }
+        _ => {
+            origins.insert(path.to_string(), file.to_path_buf());
+        }
+    }
+}
+
+/// annotate the entries of a resolved config with where they came from
+///
+/// keyed array entries get a `$origin` field; everything else is listed in a
+/// top-level `$origins` map
+pub fn annotate_origins(value: &Value, origins: &Origins) -> Value {
+    let mut annotated = value.clone();
+    let mut rest = Map::new();
+
+    for (path, file) in origins {
+        let file = Value::String(file.display().to_string());
+        let entry = path.split_once('[').and_then(|(name, index)| {
+            Some((name, index.strip_suffix(']')?.parse::<usize>().ok()?))
+        });
+
+        match entry {
+            Some((name, index)) => {
+                if let Some(Value::Object(item)) = annotated
+                    .get_mut(name)
+                    .and_then(|items| items.get_mut(index))
+                {
+                    item.insert("$origin".to_string(), file);
+                }
+            }
+            None => {
+                rest.insert(path.clone(), file);
+            }
+        }
+    }
+
+    if let Value::Object(map) = &mut annotated {
+        map.insert("$origins".to_string(), Value::Object(rest));
+    }
+    annotated
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn write(dir: &Path, name: &str, content: &str) -> PathBuf {
+        let path = dir.join(name);
+        fs::write(&path, content).unwrap();
+        path
+    }
+
+    #[test]
+    fn test_resolve_without_includes() {
+        let dir = tempdir().unwrap();
+        let main = write(dir.path(), "config.json", r#"{"shortcuts": []}"#);
+
+        let resolved = resolve(&main).unwrap();
+        assert_eq!(resolved.files.len(), 1);
+        assert_eq!(resolved.value["shortcuts"], serde_json::json!([]));
+    }
+
+    #[test]
+    fn test_maps_merge_and_later_wins() {
+        let dir = tempdir().unwrap();
+        write(
+            dir.path(),
+            "team.jsonc",
+            r#"{
+                // shared settings
+                "settings": { "fuzzy_threshold": 3, "history": { "limit": 10 } },
+                "display_aliases": { "office": ["A"], "home": ["B"] }
+            }"#,
+        );
+        let main = write(
+            dir.path(),
+            "config.jsonc",
+            r#"{
+                "include": ["./team.jsonc"],
+                "settings": { "history": { "limit": 99 } },
+                "display_aliases": { "home": ["C"] }
+            }"#,
+        );
+
+        let resolved = resolve(&main).unwrap();
+        let value = &resolved.value;
+        assert_eq!(value["settings"]["fuzzy_threshold"], 3);
+        assert_eq!(value["settings"]["history"]["limit"], 99);
+        assert_eq!(value["display_aliases"]["office"], serde_json::json!(["A"]));
+        // arrays inside maps are replaced, not appended
+        assert_eq!(value["display_aliases"]["home"], serde_json::json!(["C"]));
+        assert!(value.get(INCLUDE_KEY).is_none());
+
+        let team = fs::canonicalize(dir.path().join("team.jsonc")).unwrap();
+        let main = fs::canonicalize(&main).unwrap();
+        assert_eq!(resolved.origins["settings.fuzzy_threshold"], team);
+        assert_eq!(resolved.origins["settings.history.limit"], main);
+        assert_eq!(resolved.origins["display_aliases.home"], main);
+        assert_eq!(resolved.files, vec![team, main]);
+    }
+
+    #[test]
+    fn test_keyed_arrays_append_and_replace() {
+        let dir = tempdir().unwrap();
+        write(
+            dir.path(),
+            "team.json",
+            r#"{
+                "shortcuts": [
+                    { "keys": "ctrl+alt+s", "action": "focus", "app": "Safari" },
+                    { "keys": "ctrl+alt+m", "action": "maximize" }
+                ],
+                "app_rules": [
+                    { "app": "Slack", "action": "maximize" },
+                    { "app": "Slack", "action": "move:left" }
+                ]
+            }"#,
+        );
+        let main = write(
+            dir.path(),
+            "config.json",
+            r#"{
+                "include": "team.json",
+                "shortcuts": [
+                    { "keys": "Alt+Ctrl+S", "action": "focus", "app": "Firefox" },
+                    { "keys": "ctrl+alt+t", "action": "focus", "app": "Terminal" }
+                ]
+            }"#,
+        );
+
+        let resolved = resolve(&main).unwrap();
+        let shortcuts = resolved.value["shortcuts"].as_array().unwrap();
+        let apps: Vec<&str> = shortcuts
+            .iter()
+            .map(|s| s["app"].as_str().unwrap_or("-"))
+            .collect();
+        assert_eq!(apps, vec!["-", "Firefox", "Terminal"]);
+
+        // both Slack rules come from the same layer and are kept
+        assert_eq!(resolved.value["app_rules"].as_array().unwrap().len(), 2);
+
+        let team = fs::canonicalize(dir.path().join("team.json")).unwrap();
+        assert_eq!(resolved.origins["shortcuts[0]"], team);
+        assert_eq!(
+            resolved.origins["shortcuts[1]"],
+            fs::canonicalize(&main).unwrap()
+        );
+        assert_eq!(resolved.origins["app_rules[1]"], team);
+    }
+
+    #[test]
+    fn test_overlay_replaces_by_what_is_bound() {
+        let base = serde_json::json!({
+            "settings": { "keyboard_layout": "de" },
+            "shortcuts": [
+                { "keys": "ctrl+alt+y", "action": "undo" },
+                { "keys": "rshift, rshift", "action": "maximize" }
+            ],
+            "mouse_bindings": [{ "mouse": "cmd+ctrl+left", "drag": "move" }]
+        });
+        let overlay = serde_json::json!({
+            "shortcuts": [
+                // the key labelled y on a German keyboard
+                { "keys": "alt+ctrl+key:KeyZ", "action": "redo" },
+                { "keys": "RShift, RShift", "action": "center" }
+            ],
+            "mouse_bindings": [{ "mouse": "ctrl+cmd+button1", "drag": "resize" }]
+        });
+
+        let merged = merge_overlay(base, overlay).unwrap();
+        let actions: Vec<&str> = merged["shortcuts"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|s| s["action"].as_str().unwrap())
+            .collect();
+        assert_eq!(actions, vec!["redo", "center"]);
+        assert_eq!(
+            merged["mouse_bindings"],
+            serde_json::json!([{ "mouse": "ctrl+cmd+button1", "drag": "resize" }])
+        );
+    }
+
+    #[test]
+    fn test_nested_includes_apply_in_order() {
+        let dir = tempdir().unwrap();
+        fs::create_dir(dir.path().join("team")).unwrap();
+        write(
+            dir.path(),
+            "team/base.json",
+            r#"{"settings": {"delay_ms": 1}}"#,
+        );
+        write(
+            dir.path(),
+            "team/team.json",
+            r#"{"include": ["base.json"], "settings": {"delay_ms": 2}}"#,
+        );
+        write(dir.path(), "local.json", r#"{"settings": {"delay_ms": 3}}"#);
+        let main = write(
+            dir.path(),
+            "config.json",
+            r#"{"include": ["team/team.json", "local.json"]}"#,
+        );
+
+        let resolved = resolve(&main).unwrap();
+        assert_eq!(resolved.value["settings"]["delay_ms"], 3);
+        assert_eq!(resolved.files.len(), 4);
+        assert!(resolved.files[0].ends_with("team/base.json"));
+    }
+
+    #[test]
+    fn test_include_cycle() {
+        let dir = tempdir().unwrap();
+        write(dir.path(), "a.json", r#"{"include": ["b.json"]}"#);
+        write(dir.path(), "b.json", r#"{"include": ["a.json"]}"#);
+
+        let err = resolve(&dir.path().join("a.json")).unwrap_err().to_string();
+        assert!(err.contains("include cycle"));
+        assert!(err.contains("a.json -> "));
+        assert!(err.contains("b.json"));
+
+        write(dir.path(), "self.json", r#"{"include": "self.json"}"#);
+        assert!(resolve(&dir.path().join("self.json")).is_err());
+    }
+
+    #[test]
+    fn test_missing_include() {
+        let dir = tempdir().unwrap();
+        let main = write(dir.path(), "config.json", r#"{"include": ["nope.json"]}"#);
+
+        let err = resolve(&main).unwrap_err().to_string();
+        assert!(err.contains("included file not found"));
+        assert!(err.contains("nope.json"));
+    }
+
+    #[test]
+    fn test_invalid_include_value() {
+        let dir = tempdir().unwrap();
+        let main = write(dir.path(), "config.json", r#"{"include": [1, 2]}"#);
+        assert!(resolve(&main)
+            .unwrap_err()
+            .to_string()
+            .contains("must be a string or an array of strings"));
+    }
+
+    #[test]
+    fn test_resolve_include_path() {
+        let base = Path::new("/etc/cwm");
+        assert_eq!(
+            resolve_include_path("team.json", base),
+            PathBuf::from("/etc/cwm/team.json")
+        );
+        assert_eq!(
+            resolve_include_path("/abs/x.json", base),
+            PathBuf::from("/abs/x.json")
+        );
+        if let Some(home) = dirs::home_dir() {
+            assert_eq!(
+                resolve_include_path("~/.cwm/local.jsonc", base),
+                home.join(".cwm/local.jsonc")
+            );
+        }
+    }
+
+    #[test]
+    fn test_annotate_origins() {
+        let value = serde_json::json!({
+            "shortcuts": [{ "keys": "ctrl+a", "action": "maximize" }],
+            "settings": { "delay_ms": 5 }
+        });
+        let mut origins = Origins::new();
+        origins.insert("shortcuts[0]".to_string(), PathBuf::from("/team.json"));
+        origins.insert(
+            "settings.delay_ms".to_string(),
+            PathBuf::from("/local.json"),
+        );
+
+        let annotated = annotate_origins(&value, &origins);
+        assert_eq!(annotated["shortcuts"][0]["$origin"], "/team.json");
+        assert_eq!(annotated["$origins"]["settings.delay_ms"], "/local.json");
+        assert!(annotated["$origins"].get("shortcuts[0]").is_none());
+    }
+}
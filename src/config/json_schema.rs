@@ -1,496 +1,544 @@
-use anyhow::{Context, Result};
+//! the config JSON schema, generated from the config types
+//!
+//! structs in `config::schema` derive `JsonSchema`; the parts serde can't
+//! describe (the action grammar, conditions) are built from `config::ACTIONS`
+//! and `conditions::FIELDS` so the schema follows the code that parses them.
+//! the generated schema is committed as `config.schema.json`
+
+use anyhow::{anyhow, Context, Result};
+use regex::Regex;
+use schemars::generate::SchemaSettings;
+use schemars::{json_schema, JsonSchema, Schema, SchemaGenerator};
+use serde_json::{json, Map, Value};
+use std::borrow::Cow;
 use std::fs;
 use std::path::Path;
 
-pub const JSON_SCHEMA: &str = r##"{
-  "$schema": "http://json-schema.org/draft-07/schema#",
-  "title": "CWM Configuration",
-  "description": "Configuration file for cwm (Cool Window Manager)",
-  "type": "object",
-  "properties": {
-    "$schema": {
-      "type": "string",
-      "description": "JSON schema reference"
-    },
-    "conditions": {
-      "type": "object",
-      "description": "Global condition definitions that can be referenced by $ref in shortcuts and app_rules",
-      "additionalProperties": {
-        "$ref": "#/$defs/Condition"
-      },
-      "default": {},
-      "examples": [
-        {
-          "work_hours": { "time": "9:00AM-5:00PM", "time.day": "mon-fri" },
-          "docked": { "display.count": { ">=": 2 } }
+use super::path::{ConfigPath, Segment};
+use super::schema::Config;
+use super::ACTIONS;
+use crate::conditions::{CompareOp, FieldSpec, FieldType, FIELDS};
+
+const META_SCHEMA: &str = "http://json-schema.org/draft-07/schema#";
+
+/// generates the config schema
+pub fn generate() -> Value {
+    let generator = SchemaSettings::draft2020_12()
+        .with(|settings| settings.meta_schema = Some(META_SCHEMA.into()))
+        .into_generator();
+    let mut root = match generator.into_root_schema_for::<Config>().to_value() {
+        Value::Object(root) => root,
+        other => return other,
+    };
+
+    // header keywords first, as editors and readers expect
+    let mut schema = Map::new();
+    for key in ["$schema", "title", "description"] {
+        if let Some(value) = root.remove(key) {
+            schema.insert(key.to_string(), value);
         }
-      ]
-    },
-    "shortcuts": {
-      "type": "array",
-      "description": "Global hotkey shortcuts",
-      "items": {
-        "$ref": "#/$defs/Shortcut"
-      },
-      "default": []
-    },
-    "app_rules": {
-      "type": "array",
-      "description": "Rules to apply when applications launch",
-      "items": {
-        "$ref": "#/$defs/AppRule"
-      },
-      "default": []
-    },
-    "spotlight": {
-      "type": "array",
-      "description": "Spotlight shortcuts that appear in macOS Spotlight search",
-      "items": {
-        "$ref": "#/$defs/SpotlightShortcut"
-      },
-      "default": []
-    },
-     "settings": {
-       "$ref": "#/$defs/Settings",
-       "description": "Global settings"
-     },
-     "display_aliases": {
-       "type": "object",
-       "description": "Map display aliases to unique IDs for multi-location setups",
-       "additionalProperties": {
-         "type": "array",
-         "items": {
-           "type": "string",
-           "description": "Unique display ID (vendor_model_serial) or display name"
-         }
-       },
-       "default": {},
-       "examples": [
-         {
-           "office": ["10AC_D0B3_67890"],
-           "home": ["1E6D_5B11_12345", "10AC_D0B3_67890"]
-         }
-       ]
-     }
-   },
-   "$defs": {
-    "Shortcut": {
-      "type": "object",
-      "description": "A global hotkey shortcut binding",
-      "required": ["keys", "action"],
-      "properties": {
-        "keys": {
-          "type": "string",
-          "description": "Hotkey combination (e.g., ctrl+alt+s, cmd+shift+m)",
-          "examples": ["ctrl+alt+s", "cmd+shift+m", "ctrl+alt+right"]
-        },
-        "action": {
-          "$ref": "#/$defs/Action"
-        },
-        "app": {
-          "type": "string",
-          "description": "Target application name (fuzzy matched). Required for focus action."
-        },
-        "launch": {
-          "type": "boolean",
-          "description": "Launch the app if not running. Overrides global settings.launch."
-        },
-        "when": {
-          "$ref": "#/$defs/Condition",
-          "description": "Condition that must be true for this shortcut to execute"
+    }
+    schema.extend(root);
+    Value::Object(schema)
+}
+
+/// schema for action strings such as `maximize` or `move:top-left`
+pub struct ActionSchema;
+
+impl JsonSchema for ActionSchema {
+    fn schema_name() -> Cow<'static, str> {
+        "Action".into()
+    }
+
+    fn json_schema(_: &mut SchemaGenerator) -> Schema {
+        let forms: Vec<Value> = ACTIONS
+            .iter()
+            .map(|spec| match spec.arg_pattern {
+                None => json!({ "const": spec.name, "description": spec.description }),
+                Some(arg) => json!({
+                    "pattern": format!("^{}:{}$", spec.name, arg),
+                    "description": spec.description,
+                }),
+            })
+            .collect();
+        let examples: Vec<&str> = ACTIONS
+            .iter()
+            .flat_map(|spec| spec.examples.iter().copied())
+            .collect();
+
+        json_schema!({
+            "type": "string",
+            "description": "window action to perform",
+            "oneOf": forms,
+            "examples": examples,
+        })
+    }
+}
+
+/// schema for `when` conditions and `conditions` definitions
+pub struct ConditionSchema;
+
+impl JsonSchema for ConditionSchema {
+    fn schema_name() -> Cow<'static, str> {
+        "Condition".into()
+    }
+
+    fn json_schema(generator: &mut SchemaGenerator) -> Schema {
+        let condition = generator.subschema_for::<ConditionSchema>().to_value();
+        let compare = generator.subschema_for::<CompareOpSchema>().to_value();
+        let in_op = generator.subschema_for::<InOpSchema>().to_value();
+
+        let mut properties = Map::new();
+        properties.insert(
+            "all".to_string(),
+            json!({
+                "type": "array",
+                "description": "all conditions must be true (AND)",
+                "items": condition,
+            }),
+        );
+        properties.insert(
+            "any".to_string(),
+            json!({
+                "type": "array",
+                "description": "any condition must be true (OR)",
+                "items": condition,
+            }),
+        );
+        properties.insert(
+            "not".to_string(),
+            json!({ "$ref": condition["$ref"], "description": "negate a condition" }),
+        );
+        properties.insert(
+            "$ref".to_string(),
+            json!({
+                "type": "string",
+                "description": "reference to a named condition defined in 'conditions'",
+            }),
+        );
+        for field in FIELDS {
+            properties.insert(
+                field.name.to_string(),
+                field_schema(field, &compare, &in_op),
+            );
         }
-      },
-      "if": {
-        "properties": {
-          "action": { "const": "focus" }
+
+        json_schema!({
+            "description": "a condition that evaluates to true or false: logical operators (all, any, not), references to named conditions, and field conditions",
+            "oneOf": [
+                {
+                    "type": "object",
+                    "description": "field conditions (implicit AND) or logical operators",
+                    "properties": properties,
+                    "additionalProperties": true,
+                },
+                {
+                    "type": "boolean",
+                    "description": "true = always, false = never",
+                },
+            ],
+            "examples": [
+                { "display.count": { ">=": 2 } },
+                { "time": "9AM-5PM", "time.day": "mon-fri" },
+                { "all": [{ "$ref": "work_hours" }, { "display.connected": "external" }] },
+                { "not": { "app.fullscreen": true } },
+            ],
+        })
+    }
+}
+
+fn field_schema(field: &FieldSpec, compare: &Value, in_op: &Value) -> Value {
+    let forms: Vec<Value> = field
+        .accepts
+        .iter()
+        .flat_map(|accepts| match accepts {
+            FieldType::String => vec![json!({ "type": "string" })],
+            FieldType::Number => vec![json!({ "type": "integer" }), compare.clone()],
+            FieldType::Bool => vec![json!({ "type": "boolean" })],
+            FieldType::InList => vec![in_op.clone()],
+        })
+        .collect();
+
+    let mut schema = match <[Value; 1]>::try_from(forms) {
+        Ok([Value::Object(only)]) => only,
+        Ok([_]) => unreachable!("field forms are objects"),
+        Err(forms) => {
+            let mut schema = Map::new();
+            schema.insert("oneOf".to_string(), Value::Array(forms));
+            schema
         }
-      },
-      "then": {
-        "required": ["app"]
-      }
-    },
-    "AppRule": {
-      "type": "object",
-      "description": "A rule that applies an action when an application launches",
-      "required": ["app", "action"],
-      "properties": {
-        "app": {
-          "type": "string",
-          "description": "Application name (exact match)"
-        },
-        "action": {
-          "$ref": "#/$defs/Action"
-        },
-        "delay_ms": {
-          "type": "integer",
-          "minimum": 0,
-          "description": "Delay in milliseconds before executing the action. Overrides global settings.delay_ms."
-        },
-        "when": {
-          "$ref": "#/$defs/Condition",
-          "description": "Condition that must be true for this rule to execute"
+    };
+    schema.insert("description".to_string(), json!(field.description));
+    if !field.examples.is_empty() {
+        schema.insert("examples".to_string(), json!(field.examples));
+    }
+    Value::Object(schema)
+}
+
+/// schema for comparison operator objects such as `{ ">=": 2 }`
+struct CompareOpSchema;
+
+impl JsonSchema for CompareOpSchema {
+    fn schema_name() -> Cow<'static, str> {
+        "CompareOp".into()
+    }
+
+    fn json_schema(_: &mut SchemaGenerator) -> Schema {
+        let properties: Map<String, Value> = CompareOp::ALL
+            .into_iter()
+            .filter(|op| *op != CompareOp::In)
+            .flat_map(|op| op.aliases().iter())
+            .map(|alias| (alias.to_string(), json!({ "type": "number" })))
+            .collect();
+
+        json_schema!({
+            "type": "object",
+            "description": "comparison operator object",
+            "properties": properties,
+            "additionalProperties": false,
+        })
+    }
+}
+
+/// schema for set membership objects: `{ "in": [...] }`
+struct InOpSchema;
+
+impl JsonSchema for InOpSchema {
+    fn schema_name() -> Cow<'static, str> {
+        "InOp".into()
+    }
+
+    fn json_schema(_: &mut SchemaGenerator) -> Schema {
+        let properties: Map<String, Value> = CompareOp::In
+            .aliases()
+            .iter()
+            .map(|alias| {
+                (
+                    alias.to_string(),
+                    json!({ "type": "array", "items": { "type": "string" } }),
+                )
+            })
+            .collect();
+
+        json_schema!({
+            "type": "object",
+            "description": "set membership operator",
+            "properties": properties,
+            "required": CompareOp::In.aliases(),
+            "additionalProperties": false,
+        })
+    }
+}
+
+/// `include` accepts a single path as well as a list
+pub fn include_schema(_: &mut SchemaGenerator) -> Schema {
+    json_schema!({
+        "oneOf": [
+            { "type": "string" },
+            { "type": "array", "items": { "type": "string" } },
+        ],
+    })
+}
+
+lazy_static::lazy_static! {
+    static ref PARSED_SCHEMA: Value = generate();
+
+    /// the generated schema as written to `config.schema.json`
+    pub static ref JSON_SCHEMA: String = format!(
+        "{}\n",
+        serde_json::to_string_pretty(&*PARSED_SCHEMA).expect("schema serializes")
+    );
+}
+
+/// writes the JSON schema to the specified path
+pub fn write_schema_file(path: &Path) -> Result<()> {
+    fs::write(path, JSON_SCHEMA.as_str())
+        .with_context(|| format!("failed to write schema file: {}", path.display()))
+}
+
+/// follows a local `#/$defs/...` reference
+fn resolve(schema: &Value) -> &Value {
+    let target = schema
+        .get("$ref")
+        .and_then(|r| r.as_str())
+        .and_then(|r| r.strip_prefix("#/"))
+        .and_then(|r| {
+            r.split('/')
+                .try_fold(&*PARSED_SCHEMA, |node, part| node.get(part))
+        });
+
+    match target {
+        Some(target) => resolve(target),
+        None => schema,
+    }
+}
+
+/// the schema itself plus every oneOf/anyOf branch, with references resolved
+fn alternatives(schema: &Value) -> Vec<&Value> {
+    let schema = resolve(schema);
+    let mut out = vec![schema];
+    for keyword in ["oneOf", "anyOf"] {
+        if let Some(branches) = schema.get(keyword).and_then(|b| b.as_array()) {
+            for branch in branches {
+                out.extend(alternatives(branch));
+            }
         }
-      }
-    },
-    "Condition": {
-      "description": "A condition that evaluates to true or false. Supports logical operators (all, any, not), comparison operators, and field conditions.",
-      "oneOf": [
-        {
-          "type": "object",
-          "description": "Object with field conditions (implicit AND) or logical operators",
-          "properties": {
-            "all": {
-              "type": "array",
-              "description": "All conditions must be true (AND)",
-              "items": { "$ref": "#/$defs/Condition" }
-            },
-            "any": {
-              "type": "array",
-              "description": "Any condition must be true (OR)",
-              "items": { "$ref": "#/$defs/Condition" }
-            },
-            "not": {
-              "$ref": "#/$defs/Condition",
-              "description": "Negate a condition"
-            },
-            "$ref": {
-              "type": "string",
-              "description": "Reference to a named condition defined in 'conditions'"
-            },
-            "time": {
-              "type": "string",
-              "description": "Time range(s): '9:00-17:00', '9AM-5PM', '9:00-12:00,14:00-18:00'",
-              "examples": ["9:00AM-5:00PM", "22:00-06:00", "9:00-12:00,14:00-18:00"]
-            },
-            "time.day": {
-              "type": "string",
-              "description": "Day(s) of week: 'mon', 'mon-fri', 'mon,wed,fri'",
-              "examples": ["mon-fri", "sat,sun", "mon,wed,fri"]
-            },
-            "display.count": {
-              "description": "Number of connected displays",
-              "oneOf": [
-                { "type": "integer" },
-                { "$ref": "#/$defs/CompareOp" }
-              ]
-            },
-            "display.connected": {
-              "description": "Check if display alias is connected",
-              "oneOf": [
-                { "type": "string" },
-                { "$ref": "#/$defs/InOp" }
-              ]
-            },
-            "app": {
-              "description": "Target app name or title match",
-              "oneOf": [
-                { "type": "string" },
-                { "$ref": "#/$defs/InOp" }
-              ]
-            },
-            "app.running": {
-              "description": "Check if app is running",
-              "oneOf": [
-                { "type": "string" },
-                { "$ref": "#/$defs/InOp" }
-              ]
-            },
-            "app.focused": {
-              "description": "Check if app has focus",
-              "oneOf": [
-                { "type": "string" },
-                { "type": "boolean" },
-                { "$ref": "#/$defs/InOp" }
-              ]
-            },
-            "app.fullscreen": {
-              "type": "boolean",
-              "description": "Check if target window is fullscreen"
-            },
-            "app.minimized": {
-              "type": "boolean",
-              "description": "Check if target window is minimized"
-            },
-            "app.display": {
-              "description": "Check which display target window is on",
-              "oneOf": [
-                { "type": "string" },
-                { "$ref": "#/$defs/InOp" }
-              ]
+    }
+    out
+}
+
+fn child_schema<'a>(schema: &'a Value, segment: &Segment) -> Option<&'a Value> {
+    const ANY: &Value = &Value::Bool(true);
+
+    if schema == ANY {
+        return Some(ANY);
+    }
+
+    match segment {
+        Segment::Key(key) => {
+            if let Some(prop) = schema.get("properties").and_then(|p| p.get(key)) {
+                return Some(prop);
+            }
+            match schema.get("additionalProperties") {
+                Some(Value::Bool(false)) => None,
+                Some(Value::Bool(true)) => Some(ANY),
+                Some(additional) => Some(additional),
+                // open objects without declared properties (e.g. profile settings)
+                None if schema.get("properties").is_none()
+                    && schema.get("type").and_then(|t| t.as_str()) == Some("object") =>
+                {
+                    Some(ANY)
+                }
+                None => None,
             }
-          },
-          "additionalProperties": true
-        },
-        {
-          "type": "boolean",
-          "description": "true = always, false = never"
         }
-      ],
-      "examples": [
-        { "display.count": { ">=": 2 } },
-        { "time": "9AM-5PM", "time.day": "mon-fri" },
-        { "all": [{ "$ref": "work_hours" }, { "display.connected": "external" }] },
-        { "not": { "app.fullscreen": true } }
-      ]
-    },
-    "CompareOp": {
-      "type": "object",
-      "description": "Comparison operator object",
-      "properties": {
-        "==": { "type": "number" },
-        "eq": { "type": "number" },
-        "equals": { "type": "number" },
-        "!=": { "type": "number" },
-        "ne": { "type": "number" },
-        "not_equals": { "type": "number" },
-        ">": { "type": "number" },
-        "gt": { "type": "number" },
-        "greater_than": { "type": "number" },
-        ">=": { "type": "number" },
-        "gte": { "type": "number" },
-        "greater_than_or_equal": { "type": "number" },
-        "<": { "type": "number" },
-        "lt": { "type": "number" },
-        "less_than": { "type": "number" },
-        "<=": { "type": "number" },
-        "lte": { "type": "number" },
-        "less_than_or_equal": { "type": "number" }
-      },
-      "additionalProperties": false
-    },
-    "InOp": {
-      "type": "object",
-      "description": "Set membership operator",
-      "properties": {
-        "in": {
-          "type": "array",
-          "items": { "type": "string" }
+        _ => schema.get("items"),
+    }
+}
+
+/// returns the schema describing the value at `path`
+///
+/// fails for keys the schema doesn't know about
+pub fn schema_at(path: &ConfigPath) -> Result<&'static Value> {
+    let mut current: &Value = &PARSED_SCHEMA;
+
+    for (i, segment) in path.segments().iter().enumerate() {
+        let candidates = alternatives(current);
+        current = candidates
+            .iter()
+            .find_map(|candidate| child_schema(candidate, segment))
+            .ok_or_else(|| {
+                let known: Vec<&str> = candidates
+                    .iter()
+                    .filter_map(|c| c.get("properties").and_then(|p| p.as_object()))
+                    .flat_map(|p| p.keys().map(|k| k.as_str()))
+                    .filter(|k| !k.starts_with('$'))
+                    .collect();
+                let at = if i == 0 { "top level" } else { "this level" };
+                if known.is_empty() {
+                    anyhow!("unknown config key: {}", path)
+                } else {
+                    anyhow!(
+                        "unknown config key: {}. Valid keys at {}: {}",
+                        path,
+                        at,
+                        known.join(", ")
+                    )
+                }
+            })?;
+    }
+
+    Ok(resolve(current))
+}
+
+/// the declared `type` of a schema, if it has exactly one besides `null`
+/// (optional fields are typed `["string", "null"]`)
+pub fn schema_type(schema: &Value) -> Option<&str> {
+    match resolve(schema).get("type")? {
+        Value::String(t) => Some(t),
+        Value::Array(types) => {
+            let mut types = types
+                .iter()
+                .filter_map(|t| t.as_str())
+                .filter(|t| *t != "null");
+            match (types.next(), types.next()) {
+                (Some(t), None) => Some(t),
+                _ => None,
+            }
         }
-      },
-      "required": ["in"],
-      "additionalProperties": false
-    },
-    "SpotlightShortcut": {
-      "type": "object",
-      "description": "A shortcut that appears in macOS Spotlight search",
-      "required": ["name", "action"],
-      "properties": {
-        "name": {
-          "type": "string",
-          "description": "Name displayed in Spotlight (will be prefixed with cwm: )",
-          "minLength": 1
-        },
-        "action": {
-          "$ref": "#/$defs/Action"
-        },
-        "app": {
-          "type": "string",
-          "description": "Target application name (fuzzy matched). Required for focus action."
-        },
-        "launch": {
-          "type": "boolean",
-          "description": "Launch the app if not running"
-        },
-        "icon": {
-          "type": "string",
-          "description": "Custom icon for the Spotlight shortcut. Can be: path to .icns file, path to .png file, or app name to extract icon from. If not specified, uses target app's icon (if app is set) or default cwm icon.",
-          "examples": ["/path/to/icon.icns", "Safari", "~/icons/custom.png"]
+        _ => None,
+    }
+}
+
+/// validates `value` against `schema`, returning one message per problem
+///
+/// supports the subset of JSON schema used by the cwm schema: $ref, type,
+/// enum, const, minimum, maximum, minLength, pattern, properties,
+/// additionalProperties, required, items, oneOf and anyOf
+pub fn validate_against(value: &Value, schema: &Value, path: &str) -> Vec<String> {
+    let mut errors = Vec::new();
+    validate_node(value, schema, path, &mut errors);
+    errors
+}
+
+fn type_name(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(n) if n.is_f64() => "number",
+        Value::Number(_) => "integer",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
+
+fn matches_type(value: &Value, expected: &str) -> bool {
+    match expected {
+        "number" => value.is_number(),
+        "integer" => value.is_i64() || value.is_u64(),
+        other => type_name(value) == other,
+    }
+}
+
+fn validate_node(value: &Value, schema: &Value, path: &str, errors: &mut Vec<String>) {
+    let schema = resolve(schema);
+
+    match schema {
+        Value::Bool(true) => return,
+        Value::Bool(false) => {
+            errors.push(format!("{}: not allowed", path));
+            return;
         }
-      },
-      "if": {
-        "properties": {
-          "action": { "const": "focus" }
+        _ => {}
+    }
+
+    let expected: Vec<&str> = match schema.get("type") {
+        Some(Value::String(t)) => vec![t.as_str()],
+        Some(Value::Array(types)) => types.iter().filter_map(|t| t.as_str()).collect(),
+        _ => Vec::new(),
+    };
+    if !expected.is_empty() && !expected.iter().any(|t| matches_type(value, t)) {
+        // optional fields also allow null, which isn't worth suggesting
+        let shown: Vec<&str> = expected.iter().copied().filter(|t| *t != "null").collect();
+        errors.push(format!(
+            "{}: expected {}, got {}",
+            path,
+            shown.join(" or "),
+            type_name(value)
+        ));
+        return;
+    }
+
+    if let Some(allowed) = schema.get("enum").and_then(|e| e.as_array()) {
+        if !allowed.contains(value) {
+            let allowed: Vec<String> = allowed.iter().map(|v| v.to_string()).collect();
+            errors.push(format!("{}: must be one of {}", path, allowed.join(", ")));
+        }
+    }
+
+    if let Some(expected) = schema.get("const") {
+        if value != expected {
+            errors.push(format!("{}: must be {}", path, expected));
         }
-      },
-      "then": {
-        "required": ["app"]
-      }
-    },
-     "Action": {
-       "type": "string",
-       "description": "Window action to perform",
-       "oneOf": [
-         {
-           "const": "focus",
-           "description": "Focus the application window (requires app field)"
-         },
-         {
-           "const": "maximize",
-           "description": "Maximize the current or specified window"
-         },
-          {
-           "pattern": "^move:.+$",
-           "description": "Move window to a position and/or display. Positions: top-left, top-right, bottom-left, bottom-right, left, right, 50%,50%, 100,200px. Displays: next, prev, 0, display=next, or combined: top-left;display=2 (semicolon separates arguments)"
-         },
-         {
-           "pattern": "^resize:(100|[1-9][0-9]?|full)$",
-           "description": "Resize window to a percentage of the screen (1-100) or full. Window is centered."
-         }
-       ],
-       "examples": ["focus", "maximize", "move:next", "move:top-left", "move:50%,50%", "move:display=external", "move:top-left;display=2", "resize:80", "resize:full"]
-     },
-    "Settings": {
-      "type": "object",
-      "description": "Global settings",
-      "properties": {
-        "fuzzy_threshold": {
-          "type": "integer",
-          "minimum": 0,
-          "default": 2,
-          "description": "Maximum Levenshtein distance for fuzzy app name matching"
-        },
-        "launch": {
-          "type": "boolean",
-          "default": false,
-          "description": "Launch apps if not running (can be overridden per shortcut)"
-        },
-        "animate": {
-          "type": "boolean",
-          "default": false,
-          "description": "Animate window movements"
-        },
-        "delay_ms": {
-          "type": "integer",
-          "minimum": 0,
-          "default": 500,
-          "description": "Default delay in milliseconds before executing app rule actions"
-        },
-        "retry": {
-          "$ref": "#/$defs/Retry"
-        },
-        "update": {
-          "$ref": "#/$defs/UpdateSettings"
-        },
-        "history": {
-          "$ref": "#/$defs/HistorySettings"
+    }
+
+    if let Some(n) = value.as_f64() {
+        if let Some(min) = schema.get("minimum").and_then(|m| m.as_f64()) {
+            if n < min {
+                errors.push(format!("{}: must be at least {}", path, min));
+            }
         }
-      }
-    },
-    "HistorySettings": {
-      "type": "object",
-      "description": "Undo/redo history settings",
-      "properties": {
-        "enabled": {
-          "type": "boolean",
-          "default": true,
-          "description": "Enable undo/redo history tracking"
-        },
-        "limit": {
-          "type": "integer",
-          "minimum": 1,
-          "default": 50,
-          "description": "Maximum number of entries in the undo stack"
-        },
-        "flush_delay_ms": {
-          "type": "integer",
-          "minimum": 0,
-          "default": 2000,
-          "description": "Delay in milliseconds before flushing history to disk"
+        if let Some(max) = schema.get("maximum").and_then(|m| m.as_f64()) {
+            if n > max {
+                errors.push(format!("{}: must be at most {}", path, max));
+            }
         }
-      }
-    },
-    "Retry": {
-      "type": "object",
-      "description": "Retry settings for window operations",
-      "properties": {
-        "count": {
-          "type": "integer",
-          "minimum": 0,
-          "default": 10,
-          "description": "Number of retry attempts"
-        },
-        "delay_ms": {
-          "type": "integer",
-          "minimum": 0,
-          "default": 100,
-          "description": "Initial delay between retries in milliseconds"
-        },
-        "backoff": {
-          "type": "number",
-          "minimum": 1.0,
-          "default": 1.5,
-          "description": "Backoff multiplier for retry delays"
+    }
+
+    if let Some(s) = value.as_str() {
+        if let Some(min) = schema.get("minLength").and_then(|m| m.as_u64()) {
+            if (s.chars().count() as u64) < min {
+                errors.push(format!("{}: must be at least {} characters", path, min));
+            }
         }
-      }
-    },
-    "UpdateSettings": {
-      "type": "object",
-      "description": "Update checking and auto-update settings",
-      "properties": {
-        "enabled": {
-          "type": "boolean",
-          "default": true,
-          "description": "Enable update checking"
-        },
-        "check_frequency": {
-          "type": "string",
-          "enum": ["daily", "weekly", "manual"],
-          "default": "daily",
-          "description": "How often to check for updates"
-        },
-        "auto_update": {
-          "type": "string",
-          "enum": ["always", "prompt", "never"],
-          "default": "prompt",
-          "description": "Auto-update behavior: always install, prompt user, or never auto-update"
-        },
-        "channels": {
-          "$ref": "#/$defs/UpdateChannels"
-        },
-        "telemetry": {
-          "$ref": "#/$defs/TelemetrySettings"
-        },
-        "last_check": {
-          "type": "string",
-          "format": "date-time",
-          "description": "Timestamp of last update check (managed automatically)"
+        if let Some(pattern) = schema.get("pattern").and_then(|p| p.as_str()) {
+            if Regex::new(pattern).is_ok_and(|re| !re.is_match(s)) {
+                errors.push(format!("{}: '{}' does not match {}", path, s, pattern));
+            }
         }
-      }
-    },
-    "UpdateChannels": {
-      "type": "object",
-      "description": "Which release channels to consider for updates",
-      "properties": {
-        "dev": {
-          "type": "boolean",
-          "default": false,
-          "description": "Include development releases"
-        },
-        "beta": {
-          "type": "boolean",
-          "default": false,
-          "description": "Include beta releases"
-        },
-        "stable": {
-          "type": "boolean",
-          "default": true,
-          "description": "Include stable releases"
+    }
+
+    if let Some(obj) = value.as_object() {
+        let properties = schema.get("properties").and_then(|p| p.as_object());
+        for (key, child) in obj {
+            let child_path = format!("{}.{}", path, key);
+            match (
+                properties.and_then(|p| p.get(key)),
+                schema.get("additionalProperties"),
+            ) {
+                (Some(prop), _) => validate_node(child, prop, &child_path, errors),
+                (None, Some(Value::Bool(false))) => {
+                    errors.push(format!("{}: unknown key", child_path))
+                }
+                (None, Some(additional)) => validate_node(child, additional, &child_path, errors),
+                (None, None) => {}
+            }
         }
-      }
-    },
-    "TelemetrySettings": {
-      "type": "object",
-      "description": "Error reporting and telemetry settings",
-      "properties": {
-        "enabled": {
-          "type": "boolean",
-          "default": false,
-          "description": "Enable error reporting"
-        },
-        "include_system_info": {
-          "type": "boolean",
-          "default": false,
-          "description": "Include system information in error reports"
+
+        if let Some(required) = schema.get("required").and_then(|r| r.as_array()) {
+            for key in required.iter().filter_map(|k| k.as_str()) {
+                if !obj.contains_key(key) {
+                    errors.push(format!("{}: missing required field '{}'", path, key));
+                }
+            }
         }
-      }
     }
-  }
-}"##;
 
-/// writes the JSON schema to the specified path
-pub fn write_schema_file(path: &Path) -> Result<()> {
-    fs::write(path, JSON_SCHEMA)
-        .with_context(|| format!("failed to write schema file: {}", path.display()))
+    if let (Some(items), Some(item_schema)) = (value.as_array(), schema.get("items")) {
+        for (i, item) in items.iter().enumerate() {
+            validate_node(item, item_schema, &format!("{}[{}]", path, i), errors);
+        }
+    }
+
+    for keyword in ["oneOf", "anyOf"] {
+        let Some(branches) = schema.get(keyword).and_then(|b| b.as_array()) else {
+            continue;
+        };
+
+        let branch_errors: Vec<Vec<String>> = branches
+            .iter()
+            .map(|branch| validate_against(value, branch, path))
+            .collect();
+
+        if branch_errors.iter().any(|e| e.is_empty()) {
+            continue;
+        }
+
+        // branches that each need another field, like a shortcut's `action` or `cycle`
+        let prefix = format!("{}: missing required field ", path);
+        let missing: Option<Vec<&str>> = branch_errors
+            .iter()
+            .map(|e| match e.as_slice() {
+                [only] => only.strip_prefix(prefix.as_str()),
+                _ => None,
+            })
+            .collect();
+        if let Some(fields) = missing {
+            errors.push(format!("{}{}", prefix, fields.join(" or ")));
+            continue;
+        }
+
+        // report the closest branch when only one has the right type
+        let typed: Vec<&Vec<String>> = branch_errors
+            .iter()
+            .filter(|e| !e.iter().any(|msg| msg.contains(": expected ")))
+            .collect();
+        match typed.as_slice() {
+            [only] => errors.extend(only.iter().cloned()),
+            _ => errors.push(format!("{}: {} is not an allowed value", path, value)),
+        }
+    }
 }
 
 #[cfg(test)]
@@ -499,7 +547,7 @@ mod tests {
 
     #[test]
     fn test_schema_is_valid_json() {
-        let parsed: serde_json::Value = serde_json::from_str(JSON_SCHEMA).unwrap();
+        let parsed: serde_json::Value = serde_json::from_str(&JSON_SCHEMA).unwrap();
         assert!(parsed.is_object());
         assert_eq!(
             parsed.get("$schema").and_then(|v| v.as_str()),
@@ -507,9 +555,68 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_committed_schema_is_up_to_date() {
+        let committed = include_str!("../../config.schema.json");
+        assert!(
+            committed == JSON_SCHEMA.as_str(),
+            "config.schema.json differs from the schema generated from the config types, \
+             regenerate it with `cargo run -- config schema > config.schema.json`"
+        );
+    }
+
+    #[test]
+    fn test_action_examples_match_schema() {
+        let action = schema_for("shortcuts[0].action");
+        for spec in ACTIONS {
+            for example in spec.examples {
+                let errors = validate_against(&json!(example), action, "action");
+                assert!(errors.is_empty(), "{}: {:?}", example, errors);
+            }
+        }
+    }
+
+    #[test]
+    fn test_condition_fields_in_schema() {
+        let fields = &PARSED_SCHEMA["$defs"]["Condition"]["oneOf"][0]["properties"];
+        for field in FIELDS {
+            assert!(fields.get(field.name).is_some(), "{}", field.name);
+        }
+
+        let compare = &PARSED_SCHEMA["$defs"]["CompareOp"]["properties"];
+        for op in CompareOp::ALL {
+            for alias in op.aliases() {
+                assert_eq!(
+                    compare.get(*alias).is_some(),
+                    op != CompareOp::In,
+                    "{}",
+                    alias
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_optional_field_type() {
+        assert_eq!(
+            schema_type(schema_for("shortcuts[0].launch")),
+            Some("boolean")
+        );
+        let errors = validate_against(
+            &json!("yes"),
+            schema_for("shortcuts[0].launch"),
+            "shortcuts[0].launch",
+        );
+        assert_eq!(
+            errors,
+            vec!["shortcuts[0].launch: expected boolean, got string".to_string()]
+        );
+        assert!(validate_against(&Value::Null, schema_for("shortcuts[0].launch"), "x").is_empty());
+    }
+
     #[test]
     fn test_schema_has_required_definitions() {
-        let parsed: serde_json::Value = serde_json::from_str(JSON_SCHEMA).unwrap();
+        let parsed: serde_json::Value = serde_json::from_str(&JSON_SCHEMA).unwrap();
         let defs = parsed.get("$defs").unwrap();
 
         assert!(defs.get("Shortcut").is_some());
@@ -535,7 +642,7 @@ mod tests {
         write_schema_file(&path).unwrap();
 
         let content = std::fs::read_to_string(&path).unwrap();
-        assert_eq!(content, JSON_SCHEMA);
+        assert_eq!(content, *JSON_SCHEMA);
 
         std::fs::remove_file(&path).ok();
     }
@@ -546,7 +653,7 @@ mod tests {
 
     #[test]
     fn test_schema_action_has_all_types() {
-        let parsed: serde_json::Value = serde_json::from_str(JSON_SCHEMA).unwrap();
+        let parsed: serde_json::Value = serde_json::from_str(&JSON_SCHEMA).unwrap();
         let action = parsed.get("$defs").and_then(|d| d.get("Action")).unwrap();
 
         // action should have oneOf with multiple options
@@ -580,7 +687,7 @@ mod tests {
 
     #[test]
     fn test_schema_action_examples_include_move_variants() {
-        let parsed: serde_json::Value = serde_json::from_str(JSON_SCHEMA).unwrap();
+        let parsed: serde_json::Value = serde_json::from_str(&JSON_SCHEMA).unwrap();
         let action = parsed.get("$defs").and_then(|d| d.get("Action")).unwrap();
 
         let examples = action.get("examples").unwrap().as_array().unwrap();
@@ -607,7 +714,7 @@ mod tests {
 
     #[test]
     fn test_schema_move_action_description_mentions_semicolon() {
-        let parsed: serde_json::Value = serde_json::from_str(JSON_SCHEMA).unwrap();
+        let parsed: serde_json::Value = serde_json::from_str(&JSON_SCHEMA).unwrap();
         let action = parsed.get("$defs").and_then(|d| d.get("Action")).unwrap();
 
         let one_of = action.get("oneOf").unwrap().as_array().unwrap();
@@ -646,7 +753,7 @@ mod tests {
     #[test]
     fn test_schema_move_action_no_move_display() {
         // ensure old move_display action is not in schema
-        let schema_str = JSON_SCHEMA;
+        let schema_str = JSON_SCHEMA.as_str();
         assert!(
             !schema_str.contains("move_display"),
             "schema should not contain old move_display action"
@@ -663,7 +770,7 @@ mod tests {
 
     #[test]
     fn test_schema_display_aliases_structure() {
-        let parsed: serde_json::Value = serde_json::from_str(JSON_SCHEMA).unwrap();
+        let parsed: serde_json::Value = serde_json::from_str(&JSON_SCHEMA).unwrap();
         let display_aliases = parsed
             .get("properties")
             .and_then(|p| p.get("display_aliases"))
@@ -685,7 +792,7 @@ mod tests {
 
     #[test]
     fn test_schema_display_aliases_examples() {
-        let parsed: serde_json::Value = serde_json::from_str(JSON_SCHEMA).unwrap();
+        let parsed: serde_json::Value = serde_json::from_str(&JSON_SCHEMA).unwrap();
         let display_aliases = parsed
             .get("properties")
             .and_then(|p| p.get("display_aliases"))
@@ -699,4 +806,92 @@ mod tests {
         assert!(first_example.get("office").is_some());
         assert!(first_example.get("home").is_some());
     }
+
+    fn schema_for(path: &str) -> &'static Value {
+        schema_at(&ConfigPath::parse(path).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_schema_at() {
+        assert_eq!(schema_type(schema_for("settings.launch")), Some("boolean"));
+        assert_eq!(schema_type(schema_for("shortcuts[0].keys")), Some("string"));
+        assert_eq!(
+            schema_type(schema_for("app_rules[app=Slack].delay_ms")),
+            Some("integer")
+        );
+        assert_eq!(
+            schema_type(schema_for("display_aliases.office[+]")),
+            Some("string")
+        );
+        assert_eq!(schema_type(schema_for("include[+]")), Some("string"));
+
+        // conditions accept arbitrary fields
+        schema_for("shortcuts[0].when.time");
+        schema_for("app_rules[0].when.all[0].custom.nested");
+        schema_for("profiles.work.settings.animate");
+    }
+
+    #[test]
+    fn test_schema_at_unknown_key() {
+        let err = schema_at(&ConfigPath::parse("settings.unknown").unwrap())
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("unknown config key: settings.unknown"));
+        assert!(err.contains("fuzzy_threshold"));
+
+        assert!(schema_at(&ConfigPath::parse("invalid.key").unwrap()).is_err());
+        assert!(schema_at(&ConfigPath::parse("shortcuts[0].nope").unwrap()).is_err());
+        assert!(schema_at(&ConfigPath::parse("settings.launch[0]").unwrap()).is_err());
+    }
+
+    #[test]
+    fn test_validate_against() {
+        let shortcut = schema_for("shortcuts[0]");
+
+        let ok = serde_json::json!({ "keys": "ctrl+alt+m", "action": "maximize" });
+        assert!(validate_against(&ok, shortcut, "shortcuts[0]").is_empty());
+
+        let missing = serde_json::json!({ "keys": "ctrl+alt+m" });
+        let errors = validate_against(&missing, shortcut, "shortcuts[0]");
+        assert_eq!(
+            errors,
+            vec!["shortcuts[0]: missing required field 'action' or 'cycle'".to_string()]
+        );
+
+        let cycle = serde_json::json!({ "keys": "ctrl+alt+left", "cycle": ["move:left"] });
+        assert!(validate_against(&cycle, shortcut, "shortcuts[0]").is_empty());
+
+        let bad_action = serde_json::json!({ "keys": "ctrl+alt+m", "action": "explode" });
+        assert!(!validate_against(&bad_action, shortcut, "shortcuts[0]").is_empty());
+
+        let errors = validate_against(
+            &serde_json::json!(-1),
+            schema_for("settings.fuzzy_threshold"),
+            "settings.fuzzy_threshold",
+        );
+        assert_eq!(
+            errors,
+            vec!["settings.fuzzy_threshold: must be at least 0".to_string()]
+        );
+
+        let errors = validate_against(
+            &serde_json::json!("hourly"),
+            schema_for("settings.update.check_frequency"),
+            "settings.update.check_frequency",
+        );
+        assert!(errors[0].contains("must be one of"));
+    }
+
+    #[test]
+    fn test_validate_condition() {
+        let when = schema_for("shortcuts[0].when");
+        assert!(validate_against(&serde_json::json!(true), when, "when").is_empty());
+        assert!(validate_against(
+            &serde_json::json!({ "display.count": { ">=": 2 } }),
+            when,
+            "when"
+        )
+        .is_empty());
+        assert!(!validate_against(&serde_json::json!(5), when, "when").is_empty());
+    }
 }
@@ -0,0 +1,926 @@
+//! comment-preserving JSONC editing
+//!
+//! the config file is parsed into a lossless tree of byte spans so changes can
+//! be spliced into the original text. comments, whitespace, trailing commas and
+//! key order outside the edited values are left untouched
+
+use anyhow::{anyhow, Result};
+use serde::Serialize;
+use serde_json::{Map, Value};
+
+/// a parsed value and where it sits in the source
+#[derive(Debug)]
+struct Node {
+    start: usize,
+    end: usize,
+    kind: NodeKind,
+}
+
+#[derive(Debug)]
+enum NodeKind {
+    Object(Container),
+    Array(Container),
+    Scalar,
+}
+
+/// object or array contents; `open` and `close` are the bracket positions
+#[derive(Debug)]
+struct Container {
+    open: usize,
+    close: usize,
+    entries: Vec<Entry>,
+}
+
+/// an object member or array item
+#[derive(Debug)]
+struct Entry {
+    key: Option<String>,
+    key_quoted: bool,
+    /// start of the key (members) or value (items)
+    start: usize,
+    value: Node,
+    /// position of the comma following the value, if any
+    comma: Option<usize>,
+}
+
+struct Parser<'a> {
+    text: &'a str,
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn new(text: &'a str) -> Self {
+        Self {
+            text,
+            bytes: text.as_bytes(),
+            pos: 0,
+        }
+    }
+
+    fn error(&self, message: &str) -> anyhow::Error {
+        let line = self.text[..self.pos].matches('\n').count() + 1;
+        anyhow!("{} at line {}", message, line)
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.bytes.get(self.pos).copied()
+    }
+
+    /// skips whitespace and comments
+    fn skip_trivia(&mut self) -> Result<()> {
+        loop {
+            if self.text[self.pos..].starts_with('\u{feff}') {
+                self.pos += '\u{feff}'.len_utf8();
+                continue;
+            }
+            match self.peek() {
+                Some(b' ' | b'\t' | b'\n' | b'\r') => self.pos += 1,
+                Some(b'/') if self.bytes.get(self.pos + 1) == Some(&b'/') => {
+                    self.pos = self.text[self.pos..]
+                        .find('\n')
+                        .map_or(self.text.len(), |i| self.pos + i);
+                }
+                Some(b'/') if self.bytes.get(self.pos + 1) == Some(&b'*') => {
+                    let close = self.text[self.pos + 2..]
+                        .find("*/")
+                        .ok_or_else(|| self.error("unterminated comment"))?;
+                    self.pos += close + 4;
+                }
+                _ => return Ok(()),
+            }
+        }
+    }
+
+    fn parse_document(mut self) -> Result<Node> {
+        self.skip_trivia()?;
+        let root = self.parse_value()?;
+        self.skip_trivia()?;
+        if self.pos != self.text.len() {
+            return Err(self.error("unexpected content after value"));
+        }
+        Ok(root)
+    }
+
+    fn parse_value(&mut self) -> Result<Node> {
+        let start = self.pos;
+        match self.peek() {
+            Some(b'{') => self.parse_container(b'}').map(|c| Node {
+                start,
+                end: self.pos,
+                kind: NodeKind::Object(c),
+            }),
+            Some(b'[') => self.parse_container(b']').map(|c| Node {
+                start,
+                end: self.pos,
+                kind: NodeKind::Array(c),
+            }),
+            Some(b'"' | b'\'') => {
+                self.parse_string()?;
+                Ok(Node {
+                    start,
+                    end: self.pos,
+                    kind: NodeKind::Scalar,
+                })
+            }
+            Some(_) => {
+                self.parse_bare()?;
+                Ok(Node {
+                    start,
+                    end: self.pos,
+                    kind: NodeKind::Scalar,
+                })
+            }
+            None => Err(self.error("unexpected end of input")),
+        }
+    }
+
+    fn parse_string(&mut self) -> Result<()> {
+        let quote = self.bytes[self.pos];
+        self.pos += 1;
+        while let Some(b) = self.peek() {
+            self.pos += 1;
+            match b {
+                b'\\' => self.pos += 1,
+                b if b == quote => return Ok(()),
+                _ => {}
+            }
+        }
+        Err(self.error("unterminated string"))
+    }
+
+    /// numbers, literals and unquoted keys
+    fn parse_bare(&mut self) -> Result<()> {
+        let start = self.pos;
+        while let Some(b) = self.peek() {
+            if matches!(
+                b,
+                b' ' | b'\t' | b'\n' | b'\r' | b',' | b':' | b'{' | b'}' | b'[' | b']' | b'/'
+            ) {
+                break;
+            }
+            self.pos += 1;
+        }
+        if self.pos == start {
+            return Err(self.error("unexpected character"));
+        }
+        Ok(())
+    }
+
+    fn parse_container(&mut self, close: u8) -> Result<Container> {
+        let open = self.pos;
+        let is_object = close == b'}';
+        self.pos += 1;
+        let mut entries = Vec::new();
+
+        loop {
+            self.skip_trivia()?;
+            match self.peek() {
+                Some(b) if b == close => break,
+                None => return Err(self.error("unterminated container")),
+                _ => {}
+            }
+
+            let start = self.pos;
+            let (key, key_quoted) = if is_object {
+                let quoted = matches!(self.peek(), Some(b'"' | b'\''));
+                if quoted {
+                    self.parse_string()?;
+                } else {
+                    self.parse_bare()?;
+                }
+                let raw = &self.text[start..self.pos];
+                let key = if quoted {
+                    json5::from_str::<String>(raw).map_err(|_| self.error("invalid key"))?
+                } else {
+                    raw.to_string()
+                };
+
+                self.skip_trivia()?;
+                if self.peek() != Some(b':') {
+                    return Err(self.error("expected ':'"));
+                }
+                self.pos += 1;
+                self.skip_trivia()?;
+                (Some(key), quoted)
+            } else {
+                (None, false)
+            };
+
+            let value = self.parse_value()?;
+            self.skip_trivia()?;
+
+            let comma = match self.peek() {
+                Some(b',') => {
+                    self.pos += 1;
+                    Some(self.pos - 1)
+                }
+                Some(b) if b == close => None,
+                _ => return Err(self.error("expected ',' or closing bracket")),
+            };
+
+            entries.push(Entry {
+                key,
+                key_quoted,
+                start,
+                value,
+                comma,
+            });
+
+            if comma.is_none() {
+                self.skip_trivia()?;
+                if self.peek() != Some(close) {
+                    return Err(self.error("expected closing bracket"));
+                }
+                break;
+            }
+        }
+
+        let close_pos = self.pos;
+        self.pos += 1;
+        Ok(Container {
+            open,
+            close: close_pos,
+            entries,
+        })
+    }
+}
+
+/// a pending splice into the source text
+struct Splice {
+    start: usize,
+    end: usize,
+    text: String,
+}
+
+/// the source text plus the layout details new content should follow
+struct Editor<'a> {
+    text: &'a str,
+    indent_unit: String,
+    splices: Vec<Splice>,
+}
+
+/// rewrites `text` so it holds `new` instead of `old`, touching only what changed
+///
+/// `old` must be the value `text` parses to (after any normalization the caller
+/// applies to both sides, e.g. round-tripping through the config types)
+pub fn update(text: &str, old: &Value, new: &Value) -> Result<String> {
+    let root = Parser::new(text).parse_document()?;
+
+    let mut editor = Editor {
+        text,
+        indent_unit: detect_indent_unit(text),
+        splices: Vec::new(),
+    };
+    editor.diff(&root, old, new);
+
+    // apply back to front so earlier offsets stay valid; the sort is stable so
+    // splices at the same position keep their order
+    let mut splices = editor.splices;
+    splices.sort_by_key(|s| s.start);
+    let mut out = text.to_string();
+    for splice in splices.iter().rev() {
+        out.replace_range(splice.start..splice.end, &splice.text);
+    }
+    Ok(out)
+}
+
+/// byte offset of every value in `text`, keyed by path (`shortcuts[0].keys`);
+/// object members point at their key
+pub fn offsets(text: &str) -> Result<Vec<(String, usize)>> {
+    fn walk(node: &Node, path: &str, out: &mut Vec<(String, usize)>) {
+        let container = match &node.kind {
+            NodeKind::Object(c) | NodeKind::Array(c) => c,
+            NodeKind::Scalar => return,
+        };
+        for (i, entry) in container.entries.iter().enumerate() {
+            let entry_path = match &entry.key {
+                Some(key) if path.is_empty() => key.clone(),
+                Some(key) => format!("{}.{}", path, key),
+                None => format!("{}[{}]", path, i),
+            };
+            out.push((entry_path.clone(), entry.start));
+            walk(&entry.value, &entry_path, out);
+        }
+    }
+
+    let root = Parser::new(text).parse_document()?;
+    let mut out = Vec::new();
+    walk(&root, "", &mut out);
+    Ok(out)
+}
+
+/// the indentation of the first indented line, defaulting to two spaces
+fn detect_indent_unit(text: &str) -> String {
+    text.lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| &line[..line.len() - line.trim_start().len()])
+        .find(|indent| !indent.is_empty())
+        .unwrap_or("  ")
+        .to_string()
+}
+
+/// the subset of `new` that differs from `old`, used when inserting a key the
+/// file doesn't have yet (everything else comes from defaults)
+fn sparse(old: Option<&Value>, new: &Value) -> Value {
+    match (old, new) {
+        (Some(Value::Object(old)), Value::Object(new)) => Value::Object(
+            new.iter()
+                .filter(|(k, v)| old.get(*k) != Some(v))
+                .map(|(k, v)| (k.clone(), sparse(old.get(k), v)))
+                .collect(),
+        ),
+        _ => new.clone(),
+    }
+}
+
+impl<'a> Editor<'a> {
+    fn splice(&mut self, start: usize, end: usize, text: impl Into<String>) {
+        self.splices.push(Splice {
+            start,
+            end,
+            text: text.into(),
+        });
+    }
+
+    fn line_start(&self, pos: usize) -> usize {
+        self.text[..pos].rfind('\n').map_or(0, |i| i + 1)
+    }
+
+    fn line_indent(&self, pos: usize) -> &'a str {
+        let line = &self.text[self.line_start(pos)..];
+        &line[..line.len() - line.trim_start_matches([' ', '\t']).len()]
+    }
+
+    /// true if only whitespace precedes `pos` on its line
+    fn starts_line(&self, pos: usize) -> bool {
+        self.text[self.line_start(pos)..pos].trim().is_empty()
+    }
+
+    fn is_multiline(&self, start: usize, end: usize) -> bool {
+        self.text[start..end].contains('\n')
+    }
+
+    /// end of the line containing `pos`, if the rest of it is only whitespace
+    /// or a line comment; otherwise `pos` itself
+    fn rest_of_line(&self, pos: usize) -> usize {
+        let rest = &self.text[pos..];
+        let line = rest.find('\n').map_or(rest, |i| &rest[..i]);
+        let trimmed = line.trim();
+        if trimmed.is_empty() || trimmed.starts_with("//") {
+            pos + line.trim_end_matches('\r').len()
+        } else {
+            pos
+        }
+    }
+
+    fn diff(&mut self, node: &Node, old: &Value, new: &Value) {
+        if old == new {
+            return;
+        }
+
+        match (&node.kind, old, new) {
+            (NodeKind::Object(container), Value::Object(old), Value::Object(new)) => {
+                self.diff_object(container, old, new)
+            }
+            (NodeKind::Array(container), Value::Array(old), Value::Array(new))
+                if container.entries.len() == old.len() =>
+            {
+                self.diff_array(container, old, new)
+            }
+            _ => self.replace(node, new),
+        }
+    }
+
+    fn diff_object(
+        &mut self,
+        container: &Container,
+        old: &Map<String, Value>,
+        new: &Map<String, Value>,
+    ) {
+        let find = |key: &str| {
+            container
+                .entries
+                .iter()
+                .rposition(|e| e.key.as_deref() == Some(key))
+        };
+
+        let mut removals = Vec::new();
+        let mut inserts = Vec::new();
+
+        for (key, old_value) in old {
+            if !new.contains_key(key) {
+                if let Some(idx) = find(key) {
+                    removals.push(idx);
+                }
+            } else if let Some(idx) = find(key) {
+                let entry = &container.entries[idx];
+                self.diff(&entry.value, old_value, &new[key]);
+            } else if old_value != &new[key] {
+                inserts.push((Some(key.clone()), sparse(Some(old_value), &new[key])));
+            }
+        }
+
+        for (key, value) in new {
+            if old.contains_key(key) {
+                continue;
+            }
+            match find(key) {
+                Some(idx) => self.replace(&container.entries[idx].value, value),
+                None => inserts.push((Some(key.clone()), value.clone())),
+            }
+        }
+
+        self.edit_container(container, b'{', removals, inserts);
+    }
+
+    fn diff_array(&mut self, container: &Container, old: &[Value], new: &[Value]) {
+        // a contiguous run of removed items keeps the comments on the others
+        if new.len() < old.len() {
+            let removed = old.len() - new.len();
+            let first = old.iter().zip(new).take_while(|(a, b)| a == b).count();
+            if old[first + removed..] == new[first..] {
+                let removals = (first..first + removed).collect();
+                self.edit_container(container, b'[', removals, Vec::new());
+                return;
+            }
+        }
+
+        let common = old.len().min(new.len());
+        for i in 0..common {
+            self.diff(&container.entries[i].value, &old[i], &new[i]);
+        }
+
+        let removals = (common..old.len()).collect();
+        let inserts = new[common..].iter().map(|v| (None, v.clone())).collect();
+        self.edit_container(container, b'[', removals, inserts);
+    }
+
+    fn replace(&mut self, node: &Node, value: &Value) {
+        let indent = self.line_indent(node.start);
+        let inline = !self.is_multiline(node.start, node.end);
+        let text = self.render(value, indent, inline);
+        self.splice(node.start, node.end, text);
+    }
+
+    /// removes entries (by index) and appends new ones in a single pass so
+    /// commas stay consistent
+    fn edit_container(
+        &mut self,
+        container: &Container,
+        open: u8,
+        removals: Vec<usize>,
+        inserts: Vec<(Option<String>, Value)>,
+    ) {
+        if removals.is_empty() && inserts.is_empty() {
+            return;
+        }
+
+        let inline_container = !self.is_multiline(container.open, container.close);
+        let scalar_inserts = inserts.iter().all(|(_, v)| !is_container(v));
+        if inline_container && (!container.entries.is_empty() || open == b'[' && scalar_inserts) {
+            self.rebuild_inline(container, open, &removals, inserts);
+            return;
+        }
+
+        let entries = &container.entries;
+        let trailing_comma = entries.last().is_some_and(|e| e.comma.is_some());
+
+        for &idx in &removals {
+            let (start, end) = self.removal_range(container, idx);
+            self.splice(start, end, "");
+        }
+
+        let last_remaining = (0..entries.len()).rev().find(|i| !removals.contains(i));
+
+        let outer = self.line_indent(container.open);
+        let inner = match entries.first() {
+            Some(first) if self.starts_line(first.start) => {
+                self.line_indent(first.start).to_string()
+            }
+            _ => format!("{}{}", outer, self.indent_unit),
+        };
+
+        if inserts.is_empty() {
+            // the new last entry must not keep a comma the old last one didn't have
+            if let Some(idx) = last_remaining {
+                if let (Some(comma), false) = (entries[idx].comma, trailing_comma) {
+                    if idx + 1 != entries.len() {
+                        self.splice(comma, comma + 1, "");
+                    }
+                }
+            }
+            return;
+        }
+
+        // keys are quoted unless the existing ones aren't
+        let quote_keys = !entries
+            .first()
+            .is_some_and(|e| e.key.is_some() && !e.key_quoted);
+        // follow siblings written as one-line objects/arrays
+        let inline_values = last_remaining.is_some_and(|i| {
+            let value = &entries[i].value;
+            !matches!(value.kind, NodeKind::Scalar) && !self.is_multiline(value.start, value.end)
+        });
+
+        let count = inserts.len();
+        let mut text = String::new();
+        for (i, (key, value)) in inserts.into_iter().enumerate() {
+            text.push('\n');
+            text.push_str(&inner);
+            if let Some(key) = key {
+                text.push_str(&render_key(&key, quote_keys));
+                text.push_str(": ");
+            }
+            let inline = inline_values || !is_container(&value);
+            text.push_str(&self.render(&value, &inner, inline));
+            if i + 1 < count || trailing_comma {
+                text.push(',');
+            }
+        }
+
+        match last_remaining.map(|i| &entries[i]) {
+            Some(last) => {
+                let after = match last.comma {
+                    Some(comma) => comma + 1,
+                    None => {
+                        self.splice(last.value.end, last.value.end, ",");
+                        last.value.end
+                    }
+                };
+                let at = self.rest_of_line(after);
+                self.splice(at, at, text);
+            }
+            None => {
+                if inline_container {
+                    text.push('\n');
+                    text.push_str(outer);
+                }
+                self.splice(container.open + 1, container.open + 1, text);
+            }
+        }
+    }
+
+    /// the span to delete for an entry: its own lines, any comment lines
+    /// directly above it and a comment trailing it on the same line
+    fn removal_range(&self, container: &Container, idx: usize) -> (usize, usize) {
+        let entry = &container.entries[idx];
+        let after = entry.comma.map_or(entry.value.end, |c| c + 1);
+
+        if !self.starts_line(entry.start) {
+            let end = after
+                + (self.text[after..].len()
+                    - self.text[after..].trim_start_matches([' ', '\t']).len());
+            return (entry.start, end);
+        }
+
+        let floor = match idx {
+            0 => container.open + 1,
+            _ => {
+                let prev = &container.entries[idx - 1];
+                prev.comma.map_or(prev.value.end, |c| c + 1)
+            }
+        };
+
+        let mut start = self.line_start(entry.start);
+        while start > floor {
+            let prev_start = self.line_start(start - 1);
+            if prev_start < floor {
+                break;
+            }
+            let line = self.text[prev_start..start].trim();
+            if !line.starts_with("//") {
+                break;
+            }
+            start = prev_start;
+        }
+
+        let mut end = self.rest_of_line(after);
+        if self.text[end..].starts_with("\r\n") {
+            end += 2;
+        } else if self.text[end..].starts_with('\n') {
+            end += 1;
+        }
+        (start, end)
+    }
+
+    /// re-renders a single-line container, keeping the source text of the
+    /// entries that stay
+    fn rebuild_inline(
+        &mut self,
+        container: &Container,
+        open: u8,
+        removals: &[usize],
+        inserts: Vec<(Option<String>, Value)>,
+    ) {
+        let quote_keys = !container
+            .entries
+            .first()
+            .is_some_and(|e| e.key.is_some() && !e.key_quoted);
+        let mut parts: Vec<String> = container
+            .entries
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| !removals.contains(i))
+            .map(|(_, e)| (e.start, e.value.end))
+            .collect::<Vec<_>>()
+            .into_iter()
+            .map(|(start, end)| self.take_splices(start, end))
+            .collect();
+
+        for (key, value) in inserts {
+            let value = self.render(&value, "", true);
+            parts.push(match key {
+                Some(key) => format!("{}: {}", render_key(&key, quote_keys), value),
+                None => value,
+            });
+        }
+
+        let padded = self.text.as_bytes().get(container.open + 1) == Some(&b' ');
+        let close = if open == b'{' { '}' } else { ']' };
+        let text = match (parts.is_empty(), padded) {
+            (true, _) => format!("{}{}", open as char, close),
+            (false, true) => format!("{} {} {}", open as char, parts.join(", "), close),
+            (false, false) => format!("{}{}{}", open as char, parts.join(", "), close),
+        };
+
+        // nested edits in removed entries are dropped along with them
+        self.splices
+            .retain(|s| s.end <= container.open || s.start > container.close);
+        self.splice(container.open, container.close + 1, text);
+    }
+
+    /// the source text of `start..end` with the pending splices inside it applied
+    fn take_splices(&mut self, start: usize, end: usize) -> String {
+        let (mut inside, rest): (Vec<Splice>, Vec<Splice>) = std::mem::take(&mut self.splices)
+            .into_iter()
+            .partition(|s| s.start >= start && s.end <= end);
+        self.splices = rest;
+
+        inside.sort_by_key(|s| s.start);
+        let mut out = self.text[start..end].to_string();
+        for splice in inside.iter().rev() {
+            out.replace_range(splice.start - start..splice.end - start, &splice.text);
+        }
+        out
+    }
+
+    fn render(&self, value: &Value, indent: &str, inline: bool) -> String {
+        if inline {
+            return render_inline(value);
+        }
+
+        let mut buf = Vec::new();
+        let formatter = serde_json::ser::PrettyFormatter::with_indent(self.indent_unit.as_bytes());
+        let mut ser = serde_json::Serializer::with_formatter(&mut buf, formatter);
+        if value.serialize(&mut ser).is_err() {
+            return render_inline(value);
+        }
+        let pretty = String::from_utf8(buf).unwrap_or_default();
+        pretty.replace('\n', &format!("\n{}", indent))
+    }
+}
+
+fn is_container(value: &Value) -> bool {
+    matches!(value, Value::Object(_) | Value::Array(_))
+}
+
+fn render_key(key: &str, quoted: bool) -> String {
+    let identifier = !key.is_empty()
+        && !key.starts_with(|c: char| c.is_ascii_digit())
+        && key
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '$');
+    if quoted || !identifier {
+        Value::String(key.to_string()).to_string()
+    } else {
+        key.to_string()
+    }
+}
+
+/// single-line rendering matching the style of the example configs:
+/// `{ "keys": "ctrl+alt+s", "action": "focus" }`
+fn render_inline(value: &Value) -> String {
+    match value {
+        Value::Object(map) if map.is_empty() => "{}".to_string(),
+        Value::Object(map) => {
+            let members: Vec<String> = map
+                .iter()
+                .map(|(k, v)| format!("{}: {}", render_key(k, true), render_inline(v)))
+                .collect();
+            format!("{{ {} }}", members.join(", "))
+        }
+        Value::Array(items) => {
+            let items: Vec<String> = items.iter().map(render_inline).collect();
+            format!("[{}]", items.join(", "))
+        }
+        other => other.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::fs;
+    use std::path::PathBuf;
+
+    fn examples() -> Vec<(PathBuf, String)> {
+        let dir = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("examples");
+        let mut files: Vec<PathBuf> = fs::read_dir(dir)
+            .unwrap()
+            .map(|e| e.unwrap().path())
+            .filter(|p| p.extension().is_some_and(|ext| ext == "jsonc"))
+            .collect();
+        files.sort();
+        assert!(!files.is_empty());
+        files
+            .into_iter()
+            .map(|p| {
+                let text = fs::read_to_string(&p).unwrap();
+                (p, text)
+            })
+            .collect()
+    }
+
+    fn parse(text: &str) -> Value {
+        json5::from_str(text).unwrap()
+    }
+
+    /// applies `change` to the parsed document and checks the edited text
+    /// parses back to the changed value
+    fn edit(text: &str, change: impl FnOnce(&mut Value)) -> String {
+        let old = parse(text);
+        let mut new = old.clone();
+        change(&mut new);
+        let out = update(text, &old, &new).unwrap();
+        assert_eq!(parse(&out), new, "edited text:\n{}", out);
+        out
+    }
+
+    fn comments(text: &str) -> Vec<&str> {
+        text.lines()
+            .map(str::trim)
+            .filter(|l| l.starts_with("//"))
+            .collect()
+    }
+
+    #[test]
+    fn test_examples_roundtrip_unchanged() {
+        for (path, text) in examples() {
+            let value = parse(&text);
+            assert_eq!(
+                update(&text, &value, &value).unwrap(),
+                text,
+                "{}",
+                path.display()
+            );
+        }
+    }
+
+    #[test]
+    fn test_examples_set_setting_keeps_comments() {
+        for (path, text) in examples() {
+            let out = edit(&text, |v| {
+                v["settings"]["animate"] = json!(true);
+                v["settings"]["fuzzy_threshold"] = json!(4);
+            });
+            assert_eq!(comments(&out), comments(&text), "{}", path.display());
+        }
+    }
+
+    #[test]
+    fn test_examples_append_and_remove_shortcuts() {
+        for (path, text) in examples() {
+            if parse(&text)["shortcuts"].as_array().map_or(0, |s| s.len()) < 2 {
+                continue;
+            }
+
+            let out = edit(&text, |v| {
+                v["shortcuts"]
+                    .as_array_mut()
+                    .unwrap()
+                    .push(json!({ "keys": "ctrl+alt+q", "action": "maximize" }));
+            });
+            assert_eq!(comments(&out), comments(&text), "{}", path.display());
+
+            let out = edit(&out, |v| {
+                v["shortcuts"].as_array_mut().unwrap().pop();
+            });
+            assert_eq!(out, text, "{}", path.display());
+
+            edit(&text, |v| {
+                v["shortcuts"].as_array_mut().unwrap().remove(1);
+            });
+        }
+    }
+
+    #[test]
+    fn test_insert_key_follows_indentation() {
+        let text = "{\n    // settings\n    \"settings\": {\n        \"launch\": false\n    }\n}\n";
+        let out = edit(text, |v| v["settings"]["animate"] = json!(true));
+        assert_eq!(
+            out,
+            "{\n    // settings\n    \"settings\": {\n        \"launch\": false,\n        \"animate\": true\n    }\n}\n"
+        );
+    }
+
+    #[test]
+    fn test_insert_keeps_trailing_commas_and_comments() {
+        let text = "{\n  \"a\": 1, // first\n  \"b\": 2,\n}\n";
+        let out = edit(text, |v| v["c"] = json!({ "x": true }));
+        assert_eq!(
+            out,
+            "{\n  \"a\": 1, // first\n  \"b\": 2,\n  \"c\": {\n    \"x\": true\n  },\n}\n"
+        );
+
+        let text = "{\n  \"a\": 1 // only\n}\n";
+        let out = edit(text, |v| v["b"] = json!(2));
+        assert_eq!(out, "{\n  \"a\": 1, // only\n  \"b\": 2\n}\n");
+    }
+
+    #[test]
+    fn test_insert_into_inline_object() {
+        let text = "{\n  \"shortcuts\": [\n    { \"keys\": \"ctrl+alt+m\", \"action\": \"maximize\" }\n  ]\n}\n";
+        let out = edit(text, |v| v["shortcuts"][0]["launch"] = json!(true));
+        assert!(out
+            .contains("{ \"keys\": \"ctrl+alt+m\", \"action\": \"maximize\", \"launch\": true }"));
+
+        // appended items follow the inline style of their siblings
+        let out = edit(text, |v| {
+            v["shortcuts"]
+                .as_array_mut()
+                .unwrap()
+                .push(json!({ "action": "focus", "app": "Slack", "keys": "ctrl+alt+s" }));
+        });
+        assert!(out.contains(
+            "    { \"keys\": \"ctrl+alt+m\", \"action\": \"maximize\" },\n    { \"action\": \"focus\", \"app\": \"Slack\", \"keys\": \"ctrl+alt+s\" }\n  ]"
+        ));
+    }
+
+    #[test]
+    fn test_remove_entry_with_leading_comment() {
+        let text = "{\n  \"shortcuts\": [\n    // first\n    { \"keys\": \"a\" },\n    // second\n    { \"keys\": \"b\" }\n  ]\n}\n";
+        let out = edit(text, |v| {
+            v["shortcuts"].as_array_mut().unwrap().remove(0);
+        });
+        assert_eq!(
+            out,
+            "{\n  \"shortcuts\": [\n    // second\n    { \"keys\": \"b\" }\n  ]\n}\n"
+        );
+
+        let out = edit(text, |v| {
+            v["shortcuts"].as_array_mut().unwrap().pop();
+        });
+        assert_eq!(
+            out,
+            "{\n  \"shortcuts\": [\n    // first\n    { \"keys\": \"a\" }\n  ]\n}\n"
+        );
+    }
+
+    #[test]
+    fn test_insert_missing_parent_is_sparse() {
+        let text = "{\n  \"settings\": {}\n}\n";
+        let old = json!({ "settings": { "retry": { "count": 10, "delay_ms": 100 } } });
+        let new = json!({ "settings": { "retry": { "count": 5, "delay_ms": 100 } } });
+        let out = update(text, &old, &new).unwrap();
+        assert_eq!(
+            out,
+            "{\n  \"settings\": {\n    \"retry\": {\n      \"count\": 5\n    }\n  }\n}\n"
+        );
+    }
+
+    #[test]
+    fn test_unquoted_keys_and_single_quotes() {
+        let text = "{\n  settings: { launch: 'yes' == 'no' ? 1 : 0 }\n}";
+        assert!(update(text, &json!({}), &json!({})).is_err());
+
+        let text = "{\n  settings: {\n    launch: false,\n  },\n  name: 'cwm',\n}\n";
+        let out = edit(text, |v| v["settings"]["animate"] = json!(true));
+        assert_eq!(
+            out,
+            "{\n  settings: {\n    launch: false,\n    animate: true,\n  },\n  name: 'cwm',\n}\n"
+        );
+    }
+
+    #[test]
+    fn test_offsets() {
+        let text = "{\n  // comment\n  \"a\": { b: [1, {c: 2}] }\n}";
+        let offsets: std::collections::HashMap<String, usize> =
+            offsets(text).unwrap().into_iter().collect();
+        assert_eq!(offsets["a"], text.find("\"a\"").unwrap());
+        assert_eq!(offsets["a.b"], text.find("b:").unwrap());
+        assert_eq!(offsets["a.b[0]"], text.find('1').unwrap());
+        assert_eq!(offsets["a.b[1].c"], text.find("c:").unwrap());
+    }
+
+    #[test]
+    fn test_replace_value_keeps_surroundings() {
+        let text = "/* header */\n{\n  \"settings\": { \"delay_ms\": 500 /* ms */ }\n}\n";
+        let out = edit(text, |v| v["settings"]["delay_ms"] = json!(250));
+        assert_eq!(
+            out,
+            "/* header */\n{\n  \"settings\": { \"delay_ms\": 250 /* ms */ }\n}\n"
+        );
+    }
+}
@@ -0,0 +1,1253 @@
+//! semantic config checks
+//!
+//! `verify` checks each entry on its own; lint looks at how entries interact:
+//! hotkeys bound twice, sequences behind a bound prefix, modes that cannot be
+//! entered, shortcuts and app rules that never take effect, unused
+//! or unresolvable condition definitions, display aliases and hotkeys macOS
+//! already uses. every finding has a severity and a stable `code`
+
+use serde::Serialize;
+use serde_json::Value;
+use std::cmp::Reverse;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::str::FromStr;
+
+use super::conflicts::{describe_shortcut, normalize, scopes_overlap, Owner, KNOWN_SHORTCUTS};
+use super::{keyboard_layout, profile, AppRule, Config, Shortcut};
+use crate::daemon::keyboard::Layout;
+use crate::daemon::sequence::KeySequence;
+use crate::daemon::trigger::ModifierKey;
+use crate::daemon::{cycle, hotkeys};
+use crate::display::{self, DisplayInfo, DisplayTarget};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    /// worth knowing, but harmless
+    Info,
+    /// probably not what was intended
+    Warning,
+    /// an entry that never works as configured
+    Error,
+}
+
+impl Severity {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Severity::Info => "info",
+            Severity::Warning => "warning",
+            Severity::Error => "error",
+        }
+    }
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for Severity {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "info" => Ok(Severity::Info),
+            "warning" | "warn" => Ok(Severity::Warning),
+            "error" => Ok(Severity::Error),
+            _ => Err(format!(
+                "invalid severity '{}', expected: info, warning, error",
+                s
+            )),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Finding {
+    pub severity: Severity,
+    /// identifies the check, e.g. `hotkey-conflict`
+    pub code: &'static str,
+    /// config path of the offending entry, e.g. `shortcuts[2]`
+    pub path: String,
+    pub message: String,
+}
+
+impl Finding {
+    fn new(
+        severity: Severity,
+        code: &'static str,
+        path: impl Into<String>,
+        message: impl Into<String>,
+    ) -> Self {
+        Finding {
+            severity,
+            code,
+            path: path.into(),
+            message: message.into(),
+        }
+    }
+}
+
+/// display aliases that resolve without a `display_aliases` entry
+pub(crate) const SYSTEM_ALIASES: &[&str] = &["builtin", "external", "main", "secondary"];
+
+/// an entry as seen by the daemon in one scope (the base config or a profile)
+struct Entry<'a, T> {
+    path: String,
+    item: &'a T,
+    /// whether the entry is defined by this scope; findings that only involve
+    /// entries from the base config are reported once, for the base config
+    own: bool,
+}
+
+/// runs every check on `config`; `displays` are the connected displays,
+/// when known, used to check `display_aliases`
+///
+/// findings are sorted by severity, most severe first
+pub fn lint(config: &Config, displays: Option<&[DisplayInfo]>) -> Vec<Finding> {
+    let mut findings = Vec::new();
+
+    let shortcuts = base_entries("shortcuts", &config.shortcuts);
+    let app_rules = base_entries("app_rules", &config.app_rules);
+    lint_scope(config, &shortcuts, &app_rules, &mut findings);
+
+    // profiles change which entries the daemon sees, so check each effective config
+    for name in profile::names(config) {
+        let Ok(effective) = profile::apply(config, &name) else {
+            continue; // reported by verify
+        };
+        let overrides = &config.profiles[&name];
+        let prefix = format!("profiles.{}", name);
+
+        let shortcuts = profile_entries(
+            &effective.shortcuts,
+            &format!("{}.shortcuts", prefix),
+            &overrides.shortcuts,
+            "shortcuts",
+            &config.shortcuts,
+        );
+        let app_rules = profile_entries(
+            &effective.app_rules,
+            &format!("{}.app_rules", prefix),
+            &overrides.app_rules,
+            "app_rules",
+            &config.app_rules,
+        );
+        lint_scope(config, &shortcuts, &app_rules, &mut findings);
+    }
+
+    for (i, spotlight) in config.spotlight.iter().enumerate() {
+        check_action_aliases(
+            config,
+            &format!("spotlight[{}]", i),
+            &spotlight.action,
+            &mut findings,
+        );
+    }
+
+    lint_modes(config, &mut findings);
+    lint_conditions(config, &mut findings);
+    if let Some(displays) = displays {
+        lint_display_aliases(config, displays, &mut findings);
+    }
+
+    findings.sort_by_key(|f| Reverse(f.severity));
+    findings
+}
+
+fn base_entries<'a, T>(name: &str, items: &'a [T]) -> Vec<Entry<'a, T>> {
+    items
+        .iter()
+        .enumerate()
+        .map(|(i, item)| Entry {
+            path: format!("{}[{}]", name, i),
+            item,
+            own: true,
+        })
+        .collect()
+}
+
+/// maps the entries of a profile's effective config back to where they are defined
+fn profile_entries<'a, T: Serialize>(
+    effective: &'a [T],
+    own_name: &str,
+    own: &[T],
+    base_name: &str,
+    base: &[T],
+) -> Vec<Entry<'a, T>> {
+    let position = |items: &[T], item: &Value| {
+        items
+            .iter()
+            .position(|other| serde_json::to_value(other).ok().as_ref() == Some(item))
+    };
+
+    effective
+        .iter()
+        .map(|item| {
+            let value = serde_json::to_value(item).unwrap_or_default();
+            match position(own, &value) {
+                Some(j) => Entry {
+                    path: format!("{}[{}]", own_name, j),
+                    item,
+                    own: true,
+                },
+                None => Entry {
+                    path: match position(base, &value) {
+                        Some(i) => format!("{}[{}]", base_name, i),
+                        None => base_name.to_string(),
+                    },
+                    item,
+                    own: false,
+                },
+            }
+        })
+        .collect()
+}
+
+fn lint_scope(
+    config: &Config,
+    shortcuts: &[Entry<Shortcut>],
+    app_rules: &[Entry<AppRule>],
+    findings: &mut Vec<Finding>,
+) {
+    lint_shortcuts(shortcuts, keyboard_layout(config), findings);
+    lint_app_rules(app_rules, findings);
+
+    for entry in shortcuts.iter().filter(|e| e.own) {
+        check_action_aliases(config, &entry.path, &entry.item.action, findings);
+        for (j, step) in entry.item.cycle.iter().enumerate() {
+            let path = format!("{}.cycle[{}]", entry.path, j);
+            for action in cycle::step_actions(step) {
+                check_action_aliases(config, &path, &action, findings);
+            }
+        }
+    }
+    for entry in app_rules.iter().filter(|e| e.own) {
+        check_action_aliases(config, &entry.path, &entry.item.action, findings);
+    }
+}
+
+fn lint_shortcuts(shortcuts: &[Entry<Shortcut>], layout: &Layout, findings: &mut Vec<Finding>) {
+    let reserved: Vec<(KeySequence, &str)> = KNOWN_SHORTCUTS
+        .iter()
+        .filter(|known| known.owner == Owner::System)
+        .filter_map(|known| Some((normalize(known.keys, layout)?, known.used_for)))
+        .collect();
+
+    // the listener runs the first shortcut whose hotkey matches in the frontmost app
+    let mut bound: HashMap<KeySequence, Vec<usize>> = HashMap::new();
+    // the daemon looks up `when` and `launch` by action, again taking the first match
+    let mut by_action: HashMap<(&str, Option<&str>), usize> = HashMap::new();
+
+    // double-tap and hold shortcuts are matched before the others
+    let mut order: Vec<usize> = (0..shortcuts.len()).collect();
+    order.sort_by_key(|&i| shortcuts[i].item.trigger.is_tap());
+
+    for i in order {
+        let entry = &shortcuts[i];
+        let shortcut = entry.item;
+        // a lone modifier is not a hotkey, so it cannot clash with one
+        if !shortcut.trigger.is_tap() && ModifierKey::parse(&shortcut.keys).is_some() {
+            continue;
+        }
+        // invalid hotkeys are reported by verify
+        let Some(hotkey) = normalize(&shortcut.keys, layout) else {
+            continue;
+        };
+
+        let first = bound.get(&hotkey).and_then(|earlier| {
+            earlier
+                .iter()
+                .find(|&&j| scopes_overlap(shortcut, shortcuts[j].item))
+        });
+        if let Some(&first) = first {
+            let earlier = &shortcuts[first];
+            if entry.own || earlier.own {
+                findings.push(hotkey_conflict(entry, earlier, &hotkey));
+            }
+            continue;
+        }
+        bound.entry(hotkey.clone()).or_default().push(i);
+
+        let key = (shortcut.action.as_str(), shortcut.app.as_deref());
+        if !shortcut.cycle.is_empty() {
+            // cycles are found by their position instead
+        } else if let Some(&first) = by_action.get(&key) {
+            let earlier = &shortcuts[first];
+            let ignored: Vec<&str> = [
+                ("when", shortcut.when != earlier.item.when),
+                ("launch", shortcut.launch != earlier.item.launch),
+            ]
+            .iter()
+            .filter(|(_, differs)| *differs)
+            .map(|(field, _)| *field)
+            .collect();
+
+            if !ignored.is_empty() && (entry.own || earlier.own) {
+                findings.push(Finding::new(
+                    Severity::Warning,
+                    "shadowed-shortcut",
+                    &entry.path,
+                    format!(
+                        "its {} is ignored: '{}' runs the same action as {}, whose settings are used instead",
+                        ignored.join(" and "),
+                        describe_shortcut(shortcut),
+                        earlier.path
+                    ),
+                ));
+            }
+        } else {
+            by_action.insert(key, i);
+        }
+
+        if !entry.own {
+            continue;
+        }
+
+        for key in hotkey.chords.iter().flat_map(|chord| &chord.keys) {
+            if !hotkeys::is_known_key(key, layout) {
+                findings.push(Finding::new(
+                    Severity::Error,
+                    "unknown-key",
+                    &entry.path,
+                    format!(
+                        "'{}' is not a key cwm can detect, so '{}' never triggers",
+                        key, shortcut.keys
+                    ),
+                ));
+            }
+        }
+
+        // only the first chord of a sequence reaches the listener unmatched
+        let leader = &hotkey.chords[0];
+        if let Some((_, used_for)) = reserved.iter().find(|(r, _)| r.chords[0] == *leader) {
+            findings.push(Finding::new(
+                Severity::Warning,
+                "reserved-shortcut",
+                &entry.path,
+                format!(
+                    "'{}' is used by macOS for {}; binding it takes it over or never triggers",
+                    leader, used_for
+                ),
+            ));
+        }
+    }
+
+    // a complete match runs at once, so a sequence never gets past a bound prefix
+    let mut in_order: Vec<(&KeySequence, usize)> = bound
+        .iter()
+        .flat_map(|(k, entries)| entries.iter().map(move |&i| (k, i)))
+        .collect();
+    in_order.sort_by_key(|&(_, i)| (shortcuts[i].item.trigger.is_tap(), i));
+    for &(sequence, i) in &in_order {
+        let Some(&(prefix, first)) = in_order.iter().find(|&&(other, j)| {
+            other.is_prefix_of(sequence) && scopes_overlap(shortcuts[i].item, shortcuts[j].item)
+        }) else {
+            continue;
+        };
+        let (entry, earlier) = (&shortcuts[i], &shortcuts[first]);
+        if entry.own || earlier.own {
+            findings.push(Finding::new(
+                Severity::Error,
+                "shadowed-sequence",
+                &entry.path,
+                format!(
+                    "never triggers: '{}' runs '{}' ({}) before the rest of '{}' is pressed",
+                    prefix,
+                    describe_shortcut(earlier.item),
+                    earlier.path,
+                    sequence
+                ),
+            ));
+        }
+    }
+}
+
+fn hotkey_conflict(
+    entry: &Entry<Shortcut>,
+    earlier: &Entry<Shortcut>,
+    hotkey: &KeySequence,
+) -> Finding {
+    let shortcut = entry.item;
+    let first = earlier.item;
+
+    let same_binding = shortcut.action == first.action
+        && shortcut.cycle == first.cycle
+        && shortcut.app == first.app;
+    if same_binding && shortcut.when == first.when && shortcut.launch == first.launch {
+        return Finding::new(
+            Severity::Warning,
+            "duplicate-shortcut",
+            &entry.path,
+            format!("duplicate of {} ('{}')", earlier.path, hotkey),
+        );
+    }
+
+    let mut message = format!(
+        "never triggers: '{}' is already bound to '{}' by {}",
+        hotkey,
+        describe_shortcut(first),
+        earlier.path
+    );
+    if !first.trigger.is_tap() && shortcut.trigger.is_tap() {
+        message.push_str(" (double-tap and hold shortcuts are matched first)");
+    }
+    if first.when.is_some() && first.when != shortcut.when {
+        message.push_str(
+            " (the first match wins even when its condition is false, so conditions cannot share a hotkey)",
+        );
+    }
+    Finding::new(Severity::Error, "hotkey-conflict", &entry.path, message)
+}
+
+/// mode `enter` keys share the listener with the base shortcuts, which come first
+fn lint_modes(config: &Config, findings: &mut Vec<Finding>) {
+    let layout = keyboard_layout(config);
+    let mut names: Vec<&String> = config.modes.keys().collect();
+    names.sort();
+
+    // what each bound sequence does, in the listener's order
+    // a shortcut limited to some apps leaves the keys to the mode elsewhere
+    let mut bound: Vec<(KeySequence, String, String)> = config
+        .shortcuts
+        .iter()
+        .enumerate()
+        .filter(|(_, shortcut)| shortcut.only_in.is_empty() && shortcut.except_in.is_empty())
+        .filter_map(|(i, shortcut)| {
+            Some((
+                normalize(&shortcut.keys, layout)?,
+                format!("runs '{}'", describe_shortcut(shortcut)),
+                format!("shortcuts[{}]", i),
+            ))
+        })
+        .collect();
+
+    for name in names {
+        let mode = &config.modes[name];
+        let prefix = format!("modes.{}", name);
+        let enter_path = format!("{}.enter", prefix);
+
+        let mut all_keys = vec![
+            (enter_path.clone(), mode.enter.as_str()),
+            (format!("{}.exit", prefix), mode.exit.as_str()),
+        ];
+        for keys in mode.bindings.keys() {
+            all_keys.push((format!("{}.bindings.{}", prefix, keys), keys));
+        }
+        for (path, keys) in &all_keys {
+            let Some(sequence) = normalize(keys, layout) else {
+                continue; // reported by verify
+            };
+            for key in sequence.chords.iter().flat_map(|chord| &chord.keys) {
+                if !hotkeys::is_known_key(key, layout) {
+                    findings.push(Finding::new(
+                        Severity::Error,
+                        "unknown-key",
+                        path,
+                        format!(
+                            "'{}' is not a key cwm can detect, so '{}' never triggers",
+                            key, keys
+                        ),
+                    ));
+                }
+            }
+        }
+
+        let exit = normalize(&mode.exit, layout);
+        for (keys, action) in &mode.bindings {
+            let path = format!("{}.bindings.{}", prefix, keys);
+            if exit.is_some() && normalize(keys, layout) == exit {
+                findings.push(Finding::new(
+                    Severity::Error,
+                    "hotkey-conflict",
+                    &path,
+                    format!("never runs: '{}' is the mode's exit key", keys),
+                ));
+            }
+            check_action_aliases(config, &path, action, findings);
+        }
+
+        let Some(enter) = normalize(&mode.enter, layout) else {
+            continue;
+        };
+        if let Some((_, does, by)) = bound.iter().find(|(other, _, _)| *other == enter) {
+            findings.push(Finding::new(
+                Severity::Error,
+                "hotkey-conflict",
+                &enter_path,
+                format!(
+                    "never enters the mode: '{}' already {} ({})",
+                    enter, does, by
+                ),
+            ));
+            continue;
+        }
+        if let Some((other, does, by)) = bound
+            .iter()
+            .find(|(other, _, _)| other.is_prefix_of(&enter))
+        {
+            findings.push(Finding::new(
+                Severity::Error,
+                "shadowed-sequence",
+                &enter_path,
+                format!(
+                    "never enters the mode: '{}' {} ({}) before the rest of '{}' is pressed",
+                    other, does, by, enter
+                ),
+            ));
+            continue;
+        }
+        // an exact match wins, so shortcuts that continue the enter keys never trigger
+        for (other, _, by) in bound
+            .iter()
+            .filter(|(other, _, _)| enter.is_prefix_of(other))
+        {
+            findings.push(Finding::new(
+                Severity::Error,
+                "shadowed-sequence",
+                by,
+                format!(
+                    "never triggers: '{}' enters mode '{}' ({}) before the rest of '{}' is pressed",
+                    enter, name, enter_path, other
+                ),
+            ));
+        }
+
+        bound.push((enter, format!("enters mode '{}'", name), enter_path));
+    }
+}
+
+fn lint_app_rules(rules: &[Entry<AppRule>], findings: &mut Vec<Finding>) {
+    for (i, entry) in rules.iter().enumerate() {
+        let rule = entry.item;
+        let app = rule.app.to_lowercase();
+
+        // the app watcher runs the first rule whose app is a prefix of the launched app's name
+        let shadowing = rules[..i]
+            .iter()
+            .find(|earlier| app.starts_with(&earlier.item.app.to_lowercase()));
+        if let Some(earlier) = shadowing {
+            if entry.own || earlier.own {
+                let mut message = if earlier.item.app.is_empty() {
+                    format!("never runs: {} matches every application", earlier.path)
+                } else {
+                    format!(
+                        "never runs: apps named '{}' are matched by {} ('{}') first",
+                        rule.app, earlier.path, earlier.item.app
+                    )
+                };
+                if earlier.item.when.is_some() {
+                    message.push_str(", even when its condition is false");
+                }
+                let severity =
+                    if earlier.item.action == rule.action && earlier.item.when == rule.when {
+                        Severity::Warning
+                    } else {
+                        Severity::Error
+                    };
+                findings.push(Finding::new(
+                    severity,
+                    "shadowed-app-rule",
+                    &entry.path,
+                    message,
+                ));
+            }
+        }
+
+        if !entry.own {
+            continue;
+        }
+
+        if let Some(reason) = unmatchable_app(&rule.app) {
+            findings.push(Finding::new(
+                Severity::Error,
+                "unmatchable-app",
+                &entry.path,
+                format!("'{}' never matches: {}", rule.app, reason),
+            ));
+        } else if rule.app.is_empty() {
+            findings.push(Finding::new(
+                Severity::Warning,
+                "unmatchable-app",
+                &entry.path,
+                "an empty app matches every application",
+            ));
+        }
+    }
+}
+
+/// why an app rule's `app` can never be a prefix of an application name
+fn unmatchable_app(app: &str) -> Option<&'static str> {
+    if app.is_empty() {
+        None
+    } else if app.trim() != app {
+        Some("application names do not start or end with whitespace")
+    } else if app.to_lowercase().ends_with(".app") {
+        Some("application names do not include the '.app' extension")
+    } else if app.contains('/') {
+        Some("app rules match application names, not paths")
+    } else if app.contains(['*', '?', '^', '$']) {
+        Some("apps are matched literally by name prefix, not as patterns")
+    } else {
+        None
+    }
+}
+
+/// reports `display=<alias>` arguments naming an alias that is not defined
+fn check_action_aliases(config: &Config, path: &str, action: &str, findings: &mut Vec<Finding>) {
+    let Some(args) = action.strip_prefix("move:") else {
+        return;
+    };
+
+    for arg in args.split(';') {
+        let Some(target) = arg.trim().strip_prefix("display=") else {
+            continue;
+        };
+        let Ok(DisplayTarget::Alias(alias)) = DisplayTarget::parse(target) else {
+            continue;
+        };
+
+        let defined = SYSTEM_ALIASES.contains(&alias.as_str())
+            || config
+                .display_aliases
+                .keys()
+                .any(|name| name.to_lowercase() == alias);
+        if !defined {
+            findings.push(Finding::new(
+                Severity::Error,
+                "undefined-alias",
+                path,
+                format!(
+                    "display alias '{}' is not defined in display_aliases and is not one of: {}",
+                    alias,
+                    SYSTEM_ALIASES.join(", ")
+                ),
+            ));
+        }
+    }
+}
+
+fn lint_display_aliases(config: &Config, displays: &[DisplayInfo], findings: &mut Vec<Finding>) {
+    let mut names: Vec<&String> = config.display_aliases.keys().collect();
+    names.sort();
+
+    for name in names {
+        let identifiers = &config.display_aliases[name];
+        let connected = identifiers
+            .iter()
+            .any(|id| display::find_display_by_id_or_name(id, displays).is_some());
+        if connected {
+            continue;
+        }
+
+        let message = if identifiers.is_empty() {
+            "lists no displays".to_string()
+        } else {
+            format!(
+                "none of {} is a connected display (see `cwm list displays`)",
+                identifiers
+                    .iter()
+                    .map(|id| format!("'{}'", id))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            )
+        };
+        findings.push(Finding::new(
+            Severity::Warning,
+            "unknown-display",
+            format!("display_aliases.{}", name),
+            message,
+        ));
+    }
+}
+
+/// names referenced with `$ref` (or the `"$name"` shorthand) in a condition
+fn condition_refs<'a>(value: &'a Value, refs: &mut Vec<&'a str>) {
+    match value {
+        Value::String(s) => {
+            if let Some(name) = s.strip_prefix('$') {
+                refs.push(name);
+            }
+        }
+        Value::Object(obj) => {
+            if let Some(Value::String(name)) = obj.get("$ref") {
+                refs.push(name);
+            }
+            for key in ["all", "any"] {
+                if let Some(Value::Array(items)) = obj.get(key) {
+                    for item in items {
+                        condition_refs(item, refs);
+                    }
+                }
+            }
+            if let Some(inner) = obj.get("not") {
+                condition_refs(inner, refs);
+            }
+        }
+        _ => {}
+    }
+}
+
+fn lint_conditions(config: &Config, findings: &mut Vec<Finding>) {
+    let defs = &config.conditions;
+    let mut names: Vec<&String> = defs.keys().collect();
+    names.sort();
+
+    let refs: HashMap<&str, Vec<&str>> = names
+        .iter()
+        .map(|name| {
+            let mut refs = Vec::new();
+            condition_refs(&defs[*name], &mut refs);
+            (name.as_str(), refs)
+        })
+        .collect();
+
+    // conditions reachable from a shortcut or app rule
+    let mut used: HashSet<&str> = HashSet::new();
+    let mut pending: Vec<&str> = Vec::new();
+    let whens = config
+        .shortcuts
+        .iter()
+        .map(|s| &s.when)
+        .chain(config.app_rules.iter().map(|r| &r.when))
+        .chain(config.profiles.values().flat_map(|p| {
+            p.shortcuts
+                .iter()
+                .map(|s| &s.when)
+                .chain(p.app_rules.iter().map(|r| &r.when))
+        }));
+    for when in whens.flatten() {
+        condition_refs(when, &mut pending);
+    }
+    while let Some(name) = pending.pop() {
+        if used.insert(name) {
+            if let Some(next) = refs.get(name) {
+                pending.extend(next);
+            }
+        }
+    }
+
+    for name in &names {
+        if !used.contains(name.as_str()) {
+            findings.push(Finding::new(
+                Severity::Info,
+                "unused-condition",
+                format!("conditions.{}", name),
+                "not referenced by any shortcut, app rule or used condition",
+            ));
+        }
+
+        // the daemon resolves definitions in two passes, so a definition may only
+        // reference definitions that themselves reference nothing
+        let mut chain = vec![name.as_str()];
+        if let Some(problem) = longest_ref_chain(&refs, &mut chain) {
+            let (code, detail) = match problem {
+                RefProblem::Cycle => ("ref-cycle", "is a reference cycle and never resolves"),
+                RefProblem::TooDeep => (
+                    "ref-chain",
+                    "is never resolved: a condition may only $ref conditions that use no $ref themselves",
+                ),
+            };
+            findings.push(Finding::new(
+                Severity::Error,
+                code,
+                format!("conditions.{}", name),
+                format!("{} {}", chain.join(" -> "), detail),
+            ));
+        }
+    }
+}
+
+enum RefProblem {
+    Cycle,
+    TooDeep,
+}
+
+/// follows references from the last name in `chain`, leaving the offending
+/// chain in `chain` when it is a cycle or more than one reference deep
+fn longest_ref_chain<'a>(
+    refs: &HashMap<&'a str, Vec<&'a str>>,
+    chain: &mut Vec<&'a str>,
+) -> Option<RefProblem> {
+    let last = *chain.last()?;
+    for &next in refs.get(last).into_iter().flatten() {
+        if !refs.contains_key(next) {
+            continue; // undefined references are reported by verify
+        }
+        chain.push(next);
+        if chain[..chain.len() - 1].contains(&next) {
+            return Some(RefProblem::Cycle);
+        }
+        if chain.len() > 2 {
+            // keep following to report a cycle rather than a long chain
+            return Some(longest_ref_chain(refs, chain).unwrap_or(RefProblem::TooDeep));
+        }
+        if let Some(problem) = longest_ref_chain(refs, chain) {
+            return Some(problem);
+        }
+        chain.pop();
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(json: &str) -> Config {
+        json5::from_str(json).unwrap()
+    }
+
+    fn codes(findings: &[Finding]) -> Vec<(&str, &str)> {
+        findings.iter().map(|f| (f.code, f.path.as_str())).collect()
+    }
+
+    #[test]
+    fn test_clean_config_has_no_findings() {
+        let config = config(
+            r#"{
+                "shortcuts": [
+                    { "keys": "ctrl+alt+s", "action": "focus", "app": "Slack", "when": { "$ref": "work" } },
+                    { "keys": "ctrl+alt+m", "action": "maximize" }
+                ],
+                "app_rules": [
+                    { "app": "Slack", "action": "maximize" },
+                    { "app": "Safari", "action": "move:display=office" }
+                ],
+                "conditions": { "work": { "time.day": "mon-fri" } },
+                "display_aliases": { "office": ["Built-in Display"] }
+            }"#,
+        );
+        assert!(lint(&config, None).is_empty());
+    }
+
+    #[test]
+    fn test_hotkey_conflict_after_normalisation() {
+        let config = config(
+            r#"{ "shortcuts": [
+                { "keys": "ctrl+alt+s+f", "action": "focus", "app": "Slack", "when": { "app.running": true } },
+                { "keys": "Alt+Ctrl+F+S", "action": "maximize" }
+            ] }"#,
+        );
+        let findings = lint(&config, None);
+        assert_eq!(codes(&findings), vec![("hotkey-conflict", "shortcuts[1]")]);
+        assert_eq!(findings[0].severity, Severity::Error);
+        assert!(findings[0].message.contains("shortcuts[0]"));
+        assert!(findings[0].message.contains("condition is false"));
+    }
+
+    #[test]
+    fn test_duplicate_shortcut() {
+        let config = config(
+            r#"{ "shortcuts": [
+                { "keys": "ctrl+alt+m", "action": "maximize" },
+                { "keys": "alt+ctrl+m", "action": "maximize" }
+            ] }"#,
+        );
+        let findings = lint(&config, None);
+        assert_eq!(
+            codes(&findings),
+            vec![("duplicate-shortcut", "shortcuts[1]")]
+        );
+        assert_eq!(findings[0].severity, Severity::Warning);
+    }
+
+    #[test]
+    fn test_shadowed_shortcut() {
+        let config = config(
+            r#"{ "shortcuts": [
+                { "keys": "ctrl+alt+s", "action": "focus", "app": "Slack" },
+                { "keys": "ctrl+alt+k", "action": "focus", "app": "Slack", "launch": true }
+            ] }"#,
+        );
+        let findings = lint(&config, None);
+        assert_eq!(
+            codes(&findings),
+            vec![("shadowed-shortcut", "shortcuts[1]")]
+        );
+        assert!(findings[0].message.starts_with("its launch is ignored"));
+    }
+
+    #[test]
+    fn test_unknown_and_reserved_keys() {
+        let config = config(
+            r#"{ "shortcuts": [
+                { "keys": "ctrl+alt+enter", "action": "maximize" },
+                { "keys": "command+space", "action": "resize:80" }
+            ] }"#,
+        );
+        let findings = lint(&config, None);
+        assert_eq!(
+            codes(&findings),
+            vec![
+                ("unknown-key", "shortcuts[0]"),
+                ("reserved-shortcut", "shortcuts[1]")
+            ]
+        );
+        assert!(findings[1].message.contains("Spotlight"));
+    }
+
+    #[test]
+    fn test_keys_read_with_config_layout() {
+        let with_layout = |layout: &str| {
+            config(&format!(
+                r#"{{ "shortcuts": [{{ "keys": "ctrl+alt+ö", "action": "maximize" }}],
+                   "settings": {{ "keyboard_layout": "{}" }} }}"#,
+                layout
+            ))
+        };
+        assert_eq!(codes(&lint(&with_layout("de"), None)), vec![]);
+        assert_eq!(
+            codes(&lint(&with_layout("us"), None)),
+            vec![("unknown-key", "shortcuts[0]")]
+        );
+        // linting a config does not switch the layout keys are read with
+        assert_eq!(crate::daemon::keyboard::active().id, "us");
+    }
+
+    #[test]
+    fn test_sequences() {
+        let config = config(
+            r#"{ "shortcuts": [
+                { "keys": "ctrl+alt+w, h", "action": "move:left" },
+                { "keys": "ctrl+alt+w, l", "action": "move:right" },
+                { "keys": "alt+ctrl+w, H", "action": "maximize" },
+                { "keys": "ctrl+alt+q", "action": "maximize" },
+                { "keys": "ctrl+alt+q, h", "action": "move:left" },
+                { "keys": "cmd+space, enter", "action": "resize:80" }
+            ] }"#,
+        );
+        let findings = lint(&config, None);
+        assert_eq!(
+            codes(&findings),
+            vec![
+                ("hotkey-conflict", "shortcuts[2]"),
+                ("unknown-key", "shortcuts[5]"),
+                ("shadowed-sequence", "shortcuts[4]"),
+                ("reserved-shortcut", "shortcuts[5]")
+            ]
+        );
+        assert!(findings[0].message.contains("'ctrl+alt+w, h'"));
+        assert_eq!(
+            findings[2].message,
+            "never triggers: 'ctrl+alt+q' runs 'maximize' (shortcuts[3]) before the rest of 'ctrl+alt+q, h' is pressed"
+        );
+        assert!(findings[3]
+            .message
+            .starts_with("'cmd+space' is used by macOS"));
+    }
+
+    #[test]
+    fn test_app_scopes() {
+        let config = config(
+            r#"{
+                "shortcuts": [
+                    { "keys": "cmd+shift+m", "action": "maximize", "only_in": ["Safari"] },
+                    { "keys": "cmd+shift+m", "action": "resize:80", "only_in": ["Xcode"] },
+                    { "keys": "cmd+shift+m", "action": "move:next", "except_in": ["safari", "Xcode"] },
+                    { "keys": "cmd+shift+m", "action": "move:prev", "only_in": ["Xcode", "Notes"] },
+                    { "keys": "cmd+shift+m, h", "action": "move:left", "except_in": ["Terminal"] }
+                ],
+                "modes": { "resize": { "enter": "cmd+shift+m", "bindings": {} } }
+            }"#,
+        );
+        let findings = lint(&config, None);
+        assert_eq!(
+            codes(&findings),
+            vec![
+                ("hotkey-conflict", "shortcuts[3]"),
+                ("shadowed-sequence", "shortcuts[4]"),
+            ]
+        );
+        assert!(findings[0].message.contains("shortcuts[1]"));
+        assert!(findings[1].message.contains("shortcuts[0]"));
+    }
+
+    #[test]
+    fn test_triggers() {
+        let config = config(
+            r#"{ "shortcuts": [
+                { "keys": "ctrl+alt+c", "action": "maximize" },
+                { "keys": "ctrl+alt+c", "action": "move:next", "trigger": "double_tap" },
+                { "keys": "rshift", "action": "focus", "app": "Terminal", "trigger": "double_tap" },
+                { "keys": "rshift", "action": "maximize", "trigger": "hold" },
+                { "keys": "ctrl+alt+g, h", "action": "move:left" },
+                { "keys": "ctrl+alt+g", "action": "resize:80", "trigger": "hold" }
+            ] }"#,
+        );
+        let findings = lint(&config, None);
+        assert_eq!(
+            codes(&findings),
+            vec![
+                ("hotkey-conflict", "shortcuts[0]"),
+                ("shadowed-sequence", "shortcuts[4]"),
+            ]
+        );
+        assert!(findings[0].message.contains("by shortcuts[1]"));
+        assert!(findings[0].message.contains("matched first"));
+        assert!(findings[1].message.contains("(shortcuts[5])"));
+    }
+
+    #[test]
+    fn test_cycles() {
+        let config = config(
+            r#"{ "shortcuts": [
+                { "keys": "ctrl+alt+left", "cycle": ["move:left;resize:50", "resize:33;move:left;display=work"] },
+                { "keys": "ctrl+alt+right", "cycle": ["move:right"], "when": { "display.count": 2 } },
+                { "keys": "ctrl+alt+left", "action": "maximize" }
+            ] }"#,
+        );
+        let findings = lint(&config, None);
+        // cycles do not share an action, so neither shadows the other
+        assert_eq!(
+            codes(&findings),
+            vec![
+                ("hotkey-conflict", "shortcuts[2]"),
+                ("undefined-alias", "shortcuts[0].cycle[1]"),
+            ]
+        );
+        assert!(findings[0].message.contains("cycle [move:left;resize:50"));
+    }
+
+    #[test]
+    fn test_modes() {
+        let config = config(
+            r#"{
+                "shortcuts": [
+                    { "keys": "ctrl+alt+m", "action": "maximize" },
+                    { "keys": "ctrl+alt+w", "action": "resize:80" },
+                    { "keys": "ctrl+alt+r, h", "action": "move:left" }
+                ],
+                "modes": {
+                    "move": { "enter": "ctrl+alt+w, m", "bindings": { "h": "move:left" } },
+                    "other": { "enter": "alt+ctrl+m", "bindings": {} },
+                    "resize": {
+                        "enter": "ctrl+alt+r",
+                        "bindings": { "escape": "resize:50", "enter": "maximize", "f": "resize:full" }
+                    }
+                }
+            }"#,
+        );
+        let findings = lint(&config, None);
+        assert_eq!(
+            codes(&findings),
+            vec![
+                ("shadowed-sequence", "modes.move.enter"),
+                ("hotkey-conflict", "modes.other.enter"),
+                ("unknown-key", "modes.resize.bindings.enter"),
+                ("hotkey-conflict", "modes.resize.bindings.escape"),
+                ("shadowed-sequence", "shortcuts[2]"),
+            ]
+        );
+        assert_eq!(
+            findings[1].message,
+            "never enters the mode: 'ctrl+alt+m' already runs 'maximize' (shortcuts[0])"
+        );
+        assert!(findings[4].message.contains("enters mode 'resize'"));
+    }
+
+    #[test]
+    fn test_shadowed_app_rule() {
+        let config = config(
+            r#"{ "app_rules": [
+                { "app": "Google", "action": "maximize" },
+                { "app": "google chrome", "action": "move:next" },
+                { "app": "Google", "action": "maximize" }
+            ] }"#,
+        );
+        let findings = lint(&config, None);
+        assert_eq!(
+            codes(&findings),
+            vec![
+                ("shadowed-app-rule", "app_rules[1]"),
+                ("shadowed-app-rule", "app_rules[2]")
+            ]
+        );
+        assert_eq!(findings[0].severity, Severity::Error);
+        assert_eq!(findings[1].severity, Severity::Warning);
+    }
+
+    #[test]
+    fn test_unmatchable_app() {
+        for app in [
+            "Safari.app",
+            "/Applications/Safari.app",
+            "Safari*",
+            " Safari",
+        ] {
+            let config = config(&format!(
+                r#"{{ "app_rules": [{{ "app": {:?}, "action": "maximize" }}] }}"#,
+                app
+            ));
+            assert_eq!(
+                codes(&lint(&config, None)),
+                vec![("unmatchable-app", "app_rules[0]")],
+                "{}",
+                app
+            );
+        }
+    }
+
+    #[test]
+    fn test_unused_conditions() {
+        let config = config(
+            r#"{
+                "shortcuts": [{ "keys": "ctrl+alt+m", "action": "maximize", "when": "$docked" }],
+                "conditions": {
+                    "docked": { "any": [{ "$ref": "external" }] },
+                    "external": { "display.count": { ">=": 2 } },
+                    "orphan": { "not": "$external" }
+                }
+            }"#,
+        );
+        let findings = lint(&config, None);
+        assert_eq!(
+            codes(&findings),
+            vec![("unused-condition", "conditions.orphan")]
+        );
+        assert_eq!(findings[0].severity, Severity::Info);
+    }
+
+    #[test]
+    fn test_profile_conditions_are_used() {
+        let config = config(
+            r#"{
+                "conditions": { "docked": { "display.count": 2 } },
+                "profiles": { "desk": { "app_rules": [{ "app": "Slack", "action": "maximize", "when": "$docked" }] } }
+            }"#,
+        );
+        assert!(lint(&config, None).is_empty());
+    }
+
+    #[test]
+    fn test_ref_chain_and_cycle() {
+        let config = config(
+            r#"{
+                "shortcuts": [{ "keys": "ctrl+alt+m", "action": "maximize", "when": { "all": ["$a", "$x"] } }],
+                "conditions": {
+                    "a": "$b",
+                    "b": { "not": { "$ref": "c" } },
+                    "c": { "app": "Slack" },
+                    "x": "$y",
+                    "y": "$x"
+                }
+            }"#,
+        );
+        let findings = lint(&config, None);
+        assert_eq!(
+            codes(&findings),
+            vec![
+                ("ref-chain", "conditions.a"),
+                ("ref-cycle", "conditions.x"),
+                ("ref-cycle", "conditions.y"),
+            ]
+        );
+        assert!(findings[0].message.starts_with("a -> b -> c"));
+        assert!(findings[1].message.starts_with("x -> y -> x"));
+    }
+
+    #[test]
+    fn test_undefined_alias() {
+        let config = config(
+            r#"{
+                "shortcuts": [
+                    { "keys": "ctrl+alt+1", "action": "move:top-left;display=office" },
+                    { "keys": "ctrl+alt+2", "action": "move:display=External" },
+                    { "keys": "ctrl+alt+3", "action": "move:display=2" }
+                ],
+                "spotlight": [{ "name": "Home", "action": "move:display=home" }]
+            }"#,
+        );
+        assert_eq!(
+            codes(&lint(&config, None)),
+            vec![
+                ("undefined-alias", "shortcuts[0]"),
+                ("undefined-alias", "spotlight[0]")
+            ]
+        );
+    }
+
+    fn display(name: &str) -> DisplayInfo {
+        DisplayInfo {
+            index: 0,
+            name: name.to_string(),
+            width: 1920,
+            height: 1080,
+            x: 0,
+            y: 0,
+            is_main: true,
+            display_id: 1,
+            vendor_id: Some(0x10AC),
+            model_id: Some(0xD0B3),
+            serial_number: Some(67890),
+            unit_number: 0,
+            is_builtin: false,
+        }
+    }
+
+    #[test]
+    fn test_unknown_display() {
+        let config = config(
+            r#"{ "display_aliases": {
+                "office": ["10AC_D0B3_67890"],
+                "desk": ["dell u2720q"],
+                "home": ["1E6D_5B11_12345"]
+            } }"#,
+        );
+        let findings = lint(&config, Some(&[display("DELL U2720Q")]));
+        assert_eq!(
+            codes(&findings),
+            vec![("unknown-display", "display_aliases.home")]
+        );
+        assert!(lint(&config, None).is_empty());
+    }
+
+    #[test]
+    fn test_profile_findings_are_not_repeated() {
+        let config = config(
+            r#"{
+                "shortcuts": [
+                    { "keys": "ctrl+alt+m", "action": "maximize" },
+                    { "keys": "ctrl+alt+m", "action": "resize:80" }
+                ],
+                "profiles": {
+                    "work": { "shortcuts": [{ "keys": "alt+ctrl+s", "action": "focus", "app": "Slack" }] },
+                    "play": { "shortcuts": [{ "keys": "alt+ctrl+m", "action": "move:next" }] }
+                }
+            }"#,
+        );
+        assert_eq!(
+            codes(&lint(&config, None)),
+            vec![
+                ("hotkey-conflict", "shortcuts[1]"),
+                ("hotkey-conflict", "profiles.play.shortcuts[0]")
+            ]
+        );
+    }
+
+    #[test]
+    fn test_findings_sorted_by_severity() {
+        let config = config(
+            r#"{
+                "shortcuts": [{ "keys": "cmd+space", "action": "maximize" }],
+                "app_rules": [{ "app": "Safari.app", "action": "maximize" }],
+                "conditions": { "unused": { "app": "Slack" } }
+            }"#,
+        );
+        let severities: Vec<Severity> = lint(&config, None).iter().map(|f| f.severity).collect();
+        assert_eq!(
+            severities,
+            vec![Severity::Error, Severity::Warning, Severity::Info]
+        );
+    }
+
+    #[test]
+    fn test_severity_from_str() {
+        assert_eq!("warn".parse::<Severity>().unwrap(), Severity::Warning);
+        assert_eq!("ERROR".parse::<Severity>().unwrap(), Severity::Error);
+        assert!("fatal".parse::<Severity>().is_err());
+        assert!(Severity::Error > Severity::Warning && Severity::Warning > Severity::Info);
+    }
+}
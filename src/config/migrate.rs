@@ -0,0 +1,439 @@
+//! config format versions and migrations
+//!
+//! every config file carries a `version`; files without one are version 1.
+//! migrations are `vN -> vN+1` functions on the raw JSON value, run in order
+//! before the value is deserialized into `Config`
+
+use anyhow::{anyhow, Context, Result};
+use serde_json::Value;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use super::format::Format;
+use super::jsonc;
+
+/// the config format version written by this build
+pub const CURRENT_VERSION: u32 = 2;
+
+const VERSION_KEY: &str = "version";
+
+/// migrates a value from version `N` to `N + 1`, describing each change made
+type Migration = fn(&mut Value, &mut Vec<String>);
+
+/// `MIGRATIONS[i]` migrates version `i + 1` to `i + 2`
+const MIGRATIONS: &[Migration] = &[v1_to_v2];
+
+/// the result of running the migration pipeline on a config value
+#[derive(Debug)]
+pub struct Migrated {
+    pub from: u32,
+    pub to: u32,
+    pub value: Value,
+    /// one line per change, e.g. `shortcuts[0].action: move_display:next -> move:display=next`
+    pub changes: Vec<String>,
+}
+
+impl Migrated {
+    pub fn is_needed(&self) -> bool {
+        self.from != self.to
+    }
+}
+
+/// the format version of a raw config value
+pub fn version_of(value: &Value) -> Result<u32> {
+    match value.get(VERSION_KEY) {
+        None => Ok(1),
+        Some(v) => v
+            .as_u64()
+            .and_then(|v| u32::try_from(v).ok())
+            .filter(|v| *v >= 1)
+            .ok_or_else(|| anyhow!("version must be a positive integer, got {}", v)),
+    }
+}
+
+/// runs every migration needed to bring `value` to the current version
+pub fn migrate(mut value: Value) -> Result<Migrated> {
+    let from = version_of(&value)?;
+    if from > CURRENT_VERSION {
+        return Err(anyhow!(
+            "config version {} is newer than this cwm supports (version {}), update cwm",
+            from,
+            CURRENT_VERSION
+        ));
+    }
+
+    let mut changes = Vec::new();
+    for migration in &MIGRATIONS[(from - 1) as usize..] {
+        migration(&mut value, &mut changes);
+    }
+
+    if from != CURRENT_VERSION {
+        if let Some(obj) = value.as_object_mut() {
+            obj.insert(VERSION_KEY.to_string(), Value::from(CURRENT_VERSION));
+        }
+    }
+
+    Ok(Migrated {
+        from,
+        to: CURRENT_VERSION,
+        value,
+        changes,
+    })
+}
+
+/// deprecation warnings for a raw config value: everything a migration would change
+pub fn deprecations(value: &Value) -> Vec<String> {
+    match migrate(value.clone()) {
+        Ok(migrated) if migrated.is_needed() => {
+            let mut warnings = vec![format!(
+                "version: config uses format version {} (current is {}), run 'cwm config migrate'",
+                migrated.from, migrated.to
+            )];
+            warnings.extend(
+                migrated
+                    .changes
+                    .into_iter()
+                    .map(|change| format!("{} (deprecated)", change)),
+            );
+            warnings
+        }
+        _ => Vec::new(),
+    }
+}
+
+/// a migrated config file, ready to be written back
+#[derive(Debug)]
+pub struct FileMigration {
+    pub path: PathBuf,
+    pub migrated: Migrated,
+    pub original: String,
+    pub content: String,
+    /// whether `content` keeps the original's comments and formatting, which
+    /// only an edit of a JSON file can
+    pub in_place: bool,
+}
+
+impl FileMigration {
+    /// where the original is kept, e.g. `config.jsonc.v1.bak`
+    pub fn backup_path(&self) -> PathBuf {
+        let mut name = self.path.file_name().unwrap_or_default().to_os_string();
+        name.push(format!(".v{}.bak", self.migrated.from));
+        self.path.with_file_name(name)
+    }
+
+    /// backs up the original file and writes the migrated content
+    pub fn apply(&self) -> Result<PathBuf> {
+        let backup = self.backup_path();
+        if !backup.exists() {
+            fs::write(&backup, &self.original)
+                .with_context(|| format!("failed to write backup: {}", backup.display()))?;
+        }
+        fs::write(&self.path, &self.content)
+            .with_context(|| format!("failed to write config file: {}", self.path.display()))?;
+        Ok(backup)
+    }
+}
+
+/// computes the migration of a config file, keeping its comments and formatting
+pub fn plan(path: &Path) -> Result<FileMigration> {
+    let original = fs::read_to_string(path)
+        .with_context(|| format!("failed to read config file: {}", path.display()))?;
+    let format = Format::from_path(path);
+    let value = format
+        .parse(&original)
+        .with_context(|| format!("failed to parse config file: {}", path.display()))?;
+
+    let migrated = migrate(value.clone())?;
+    let edited = match format {
+        // only JSON files can be edited in place
+        Format::Json if migrated.is_needed() => {
+            jsonc::update(&original, &value, &migrated.value).ok()
+        }
+        _ if migrated.is_needed() => None,
+        _ => Some(original.clone()),
+    };
+    let in_place = edited.is_some();
+    let content = match edited {
+        Some(content) => content,
+        None if format == Format::Json => {
+            serde_json::to_string_pretty(&migrated.value).context("failed to serialize config")?
+        }
+        None => format.render(&migrated.value)?,
+    };
+
+    Ok(FileMigration {
+        path: path.to_path_buf(),
+        migrated,
+        original,
+        content,
+        in_place,
+    })
+}
+
+/// a line diff between two texts, `-`/`+` for removed/added lines and two
+/// lines of context around each change
+pub fn diff_lines(old: &str, new: &str) -> String {
+    let a: Vec<&str> = old.lines().collect();
+    let b: Vec<&str> = new.lines().collect();
+
+    // longest common subsequence table
+    let mut lcs = vec![vec![0usize; b.len() + 1]; a.len() + 1];
+    for i in (0..a.len()).rev() {
+        for j in (0..b.len()).rev() {
+            lcs[i][j] = if a[i] == b[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let mut ops = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < a.len() || j < b.len() {
+        if i < a.len() && j < b.len() && a[i] == b[j] {
+            ops.push((' ', a[i]));
+            i += 1;
+            j += 1;
+        } else if i < a.len() && (j == b.len() || lcs[i + 1][j] >= lcs[i][j + 1]) {
+            ops.push(('-', a[i]));
+            i += 1;
+        } else {
+            ops.push(('+', b[j]));
+            j += 1;
+        }
+    }
+
+    const CONTEXT: usize = 2;
+    let changed: Vec<usize> = (0..ops.len()).filter(|&k| ops[k].0 != ' ').collect();
+    let mut out = String::new();
+    let mut last_printed: Option<usize> = None;
+    for (k, (op, line)) in ops.iter().enumerate() {
+        let near = changed
+            .iter()
+            .any(|&c| k + CONTEXT >= c && k <= c + CONTEXT);
+        if !near {
+            continue;
+        }
+        if last_printed.is_some_and(|p| k > p + 1) {
+            out.push_str("...\n");
+        }
+        out.push_str(&format!("{} {}\n", op, line));
+        last_printed = Some(k);
+    }
+    out
+}
+
+/// v2 replaces the `move_display:<target>` action with `move:display=<target>`
+fn v1_to_v2(value: &mut Value, changes: &mut Vec<String>) {
+    for_each_action(value, &mut |path, action| {
+        // cycle steps chain several actions with `;`
+        let replacement = action
+            .split(';')
+            .map(|part| match part.strip_prefix("move_display:") {
+                Some(target) => format!("move:display={}", target),
+                None => part.to_string(),
+            })
+            .collect::<Vec<_>>()
+            .join(";");
+        if replacement != *action {
+            changes.push(format!("{}: {} -> {}", path, action, replacement));
+            *action = replacement;
+        }
+    });
+}
+
+/// calls `f` with every action string: the `action`, `release_action` and
+/// `cycle` steps of shortcuts, app rules, spotlight shortcuts and mouse
+/// bindings, mode bindings, and the shortcuts and app rules of profiles
+fn for_each_action(value: &mut Value, f: &mut dyn FnMut(&str, &mut String)) {
+    fn visit_list(value: &mut Value, prefix: &str, f: &mut dyn FnMut(&str, &mut String)) {
+        let Some(items) = value.as_array_mut() else {
+            return;
+        };
+        for (i, item) in items.iter_mut().enumerate() {
+            for field in ["action", "release_action"] {
+                if let Some(Value::String(action)) = item.get_mut(field) {
+                    f(&format!("{}[{}].{}", prefix, i, field), action);
+                }
+            }
+            if let Some(Value::Array(steps)) = item.get_mut("cycle") {
+                for (j, step) in steps.iter_mut().enumerate() {
+                    if let Value::String(action) = step {
+                        f(&format!("{}[{}].cycle[{}]", prefix, i, j), action);
+                    }
+                }
+            }
+        }
+    }
+
+    for list in ["shortcuts", "app_rules", "spotlight", "mouse_bindings"] {
+        if let Some(items) = value.get_mut(list) {
+            visit_list(items, list, f);
+        }
+    }
+
+    if let Some(Value::Object(modes)) = value.get_mut("modes") {
+        for (name, mode) in modes.iter_mut() {
+            let Some(Value::Object(bindings)) = mode.get_mut("bindings") else {
+                continue;
+            };
+            for (key, binding) in bindings.iter_mut() {
+                if let Value::String(action) = binding {
+                    f(&format!("modes.{}.bindings.{}", name, key), action);
+                }
+            }
+        }
+    }
+
+    if let Some(Value::Object(profiles)) = value.get_mut("profiles") {
+        for (name, profile) in profiles.iter_mut() {
+            for list in ["shortcuts", "app_rules"] {
+                if let Some(items) = profile.get_mut(list) {
+                    visit_list(items, &format!("profiles.{}.{}", name, list), f);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn v1_config() -> Value {
+        json!({
+            "shortcuts": [
+                { "keys": "ctrl+alt+n", "action": "move_display:next" },
+                { "keys": "ctrl+alt+m", "action": "maximize" }
+            ],
+            "app_rules": [{ "app": "Slack", "action": "move_display:external" }],
+            "profiles": {
+                "desk": { "shortcuts": [{ "keys": "ctrl+alt+p", "action": "move_display:prev" }] }
+            }
+        })
+    }
+
+    #[test]
+    fn test_version_of() {
+        assert_eq!(version_of(&json!({})).unwrap(), 1);
+        assert_eq!(version_of(&json!({ "version": 2 })).unwrap(), 2);
+        assert!(version_of(&json!({ "version": 0 })).is_err());
+        assert!(version_of(&json!({ "version": "2" })).is_err());
+    }
+
+    #[test]
+    fn test_migrate_v1() {
+        let migrated = migrate(v1_config()).unwrap();
+        assert_eq!(migrated.from, 1);
+        assert_eq!(migrated.to, CURRENT_VERSION);
+        assert_eq!(migrated.value["version"], CURRENT_VERSION);
+        assert_eq!(
+            migrated.value["shortcuts"][0]["action"],
+            "move:display=next"
+        );
+        assert_eq!(migrated.value["shortcuts"][1]["action"], "maximize");
+        assert_eq!(
+            migrated.value["app_rules"][0]["action"],
+            "move:display=external"
+        );
+        assert_eq!(
+            migrated.value["profiles"]["desk"]["shortcuts"][0]["action"],
+            "move:display=prev"
+        );
+        assert_eq!(
+            migrated.changes,
+            vec![
+                "shortcuts[0].action: move_display:next -> move:display=next",
+                "app_rules[0].action: move_display:external -> move:display=external",
+                "profiles.desk.shortcuts[0].action: move_display:prev -> move:display=prev",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_migrate_v1_every_action_field() {
+        let migrated = migrate(json!({
+            "shortcuts": [{
+                "keys": "ctrl+alt+c",
+                "cycle": ["maximize", "resize:50;move_display:next"],
+                "trigger": "hold",
+                "release_action": "move_display:prev"
+            }],
+            "modes": { "move": { "enter": "ctrl+alt+w", "bindings": { "n": "move_display:next" } } },
+            "mouse_bindings": [{ "mouse": "ctrl+alt+button3", "action": "move_display:next" }]
+        }))
+        .unwrap();
+
+        let value = &migrated.value;
+        assert_eq!(value["shortcuts"][0]["cycle"][0], "maximize");
+        assert_eq!(
+            value["shortcuts"][0]["cycle"][1],
+            "resize:50;move:display=next"
+        );
+        assert_eq!(value["shortcuts"][0]["release_action"], "move:display=prev");
+        assert_eq!(value["modes"]["move"]["bindings"]["n"], "move:display=next");
+        assert_eq!(value["mouse_bindings"][0]["action"], "move:display=next");
+        assert_eq!(migrated.changes.len(), 4);
+    }
+
+    #[test]
+    fn test_migrate_current_is_noop() {
+        let value = json!({ "version": CURRENT_VERSION, "shortcuts": [] });
+        let migrated = migrate(value.clone()).unwrap();
+        assert!(!migrated.is_needed());
+        assert_eq!(migrated.value, value);
+        assert!(deprecations(&value).is_empty());
+    }
+
+    #[test]
+    fn test_migrate_newer_version_fails() {
+        let err = migrate(json!({ "version": CURRENT_VERSION + 1 }))
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("newer than this cwm supports"));
+    }
+
+    #[test]
+    fn test_deprecations() {
+        let warnings = deprecations(&v1_config());
+        assert_eq!(warnings.len(), 4);
+        assert!(warnings[0].contains("run 'cwm config migrate'"));
+        assert!(warnings[1].ends_with("(deprecated)"));
+    }
+
+    #[test]
+    fn test_plan_and_apply_keeps_comments() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.jsonc");
+        let original = "// my config\n{\n  \"shortcuts\": [\n    // next display\n    { \"keys\": \"ctrl+alt+n\", \"action\": \"move_display:next\" }\n  ]\n}\n";
+        fs::write(&path, original).unwrap();
+
+        let plan = plan(&path).unwrap();
+        assert!(plan.migrated.is_needed());
+        assert!(plan.in_place);
+        assert!(plan.content.starts_with("// my config\n"));
+        assert!(plan.content.contains("// next display"));
+        assert!(plan.content.contains("\"move:display=next\""));
+        assert!(plan.content.contains("\"version\": 2"));
+
+        let diff = diff_lines(&plan.original, &plan.content);
+        assert!(diff.contains("- "));
+        assert!(diff.contains("+ "));
+
+        let backup = plan.apply().unwrap();
+        assert_eq!(backup, dir.path().join("config.jsonc.v1.bak"));
+        assert_eq!(fs::read_to_string(&backup).unwrap(), original);
+        assert_eq!(fs::read_to_string(&path).unwrap(), plan.content);
+
+        // nothing left to do
+        assert!(!super::plan(&path).unwrap().migrated.is_needed());
+    }
+
+    #[test]
+    fn test_diff_lines() {
+        let diff = diff_lines("a\nb\nc\nd\ne\nf\ng\n", "a\nb\nc\nD\ne\nf\ng\n");
+        assert_eq!(diff, "  b\n  c\n- d\n+ D\n  e\n  f\n");
+    }
+}
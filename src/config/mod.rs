@@ -1,10 +1,25 @@
+pub mod conflicts;
+pub mod export;
+pub mod format;
+pub mod import;
+mod include;
 mod json_schema;
+mod jsonc;
+pub mod lint;
+pub mod migrate;
+mod path;
+pub mod profile;
 mod schema;
+pub mod vars;
 
-pub use json_schema::write_schema_file;
+pub use include::{annotate_origins, Origins, ResolvedConfig};
+
+pub use json_schema::{generate as generate_schema, write_schema_file};
 pub use schema::{
-    should_launch, AppRule, AutoUpdateMode, Config, DisplayAliases, Settings, Shortcut,
-    SpotlightShortcut, TelemetrySettings, UpdateFrequency, UpdateSettings,
+    should_launch, AppRule, AutoUpdateMode, Config, DisplayAliases, IpcSettings, IpcToken, Mode,
+    MouseBinding, MouseDrag, MouseWindow, Profile, RemoteSettings, Settings, Shortcut,
+    SpotlightShortcut, TelemetrySettings, Trigger, UpdateFrequency, UpdateSettings,
+    DEFAULT_REMOTE_PORT,
 };
 
 use anyhow::{anyhow, Context, Result};
@@ -15,14 +30,29 @@ use std::io::Read;
 use std::path::{Path, PathBuf};
 
 use crate::conditions::{parse_condition, Condition};
-use crate::daemon::hotkeys::Hotkey;
+use crate::daemon::cycle;
+use crate::daemon::keyboard::{self, Layout};
+use crate::daemon::keymap::Repeat;
+use crate::daemon::mouse::{MouseChord, MouseInput};
+use crate::daemon::sequence::KeySequence;
+use crate::daemon::trigger::{ModifierKey, TriggerKey};
+use crate::window::ResizeTarget;
+use format::Format;
 
 const CONFIG_ENV_VAR: &str = "CWM_CONFIG";
 const CONFIG_FILE_JSON: &str = "config.json";
-const CONFIG_FILE_JSONC: &str = "config.jsonc";
 
-/// returns the config file path, checking for both .json and .jsonc extensions
-/// returns an error if both files exist
+/// config file names looked for in ~/.cwm; at most one may exist
+const CONFIG_FILES: &[&str] = &[
+    CONFIG_FILE_JSON,
+    "config.jsonc",
+    "config.yaml",
+    "config.yml",
+    "config.toml",
+];
+
+/// returns the config file path, checking every supported extension
+/// returns an error if more than one config file exists
 pub fn get_config_path() -> Result<PathBuf> {
     get_config_path_with_override(None)
 }
@@ -45,21 +75,25 @@ pub fn get_config_path_with_override(override_path: Option<&Path>) -> Result<Pat
         .ok_or_else(|| anyhow!("could not find home directory"))?
         .join(".cwm");
 
-    let json_path = cwm_dir.join(CONFIG_FILE_JSON);
-    let jsonc_path = cwm_dir.join(CONFIG_FILE_JSONC);
-
-    let json_exists = json_path.exists();
-    let jsonc_exists = jsonc_path.exists();
+    find_config_file(&cwm_dir)
+}
 
-    match (json_exists, jsonc_exists) {
-        (true, true) => Err(anyhow!(
-            "both {} and {} exist in ~/.cwm - please remove one",
-            CONFIG_FILE_JSON,
-            CONFIG_FILE_JSONC
+/// the config file in `dir`; defaults to config.json for new configs
+fn find_config_file(dir: &Path) -> Result<PathBuf> {
+    let existing: Vec<&str> = CONFIG_FILES
+        .iter()
+        .copied()
+        .filter(|name| dir.join(name).exists())
+        .collect();
+
+    match existing.as_slice() {
+        [] => Ok(dir.join(CONFIG_FILE_JSON)),
+        [name] => Ok(dir.join(name)),
+        names => Err(anyhow!(
+            "{} all exist in {} - please remove all but one",
+            names.join(", "),
+            dir.display()
         )),
-        (true, false) => Ok(json_path),
-        (false, true) => Ok(jsonc_path),
-        (false, false) => Ok(json_path), // default to .json for new configs
     }
 }
 
@@ -72,11 +106,39 @@ fn get_default_config_path() -> PathBuf {
         .join(CONFIG_FILE_JSON)
 }
 
-/// parses JSONC content (JSON with comments) into a Config
+/// parses JSONC content (JSON with comments) into a Config, migrating older
+/// config versions in memory
 fn parse_jsonc<R: Read>(mut reader: R) -> Result<Config> {
     let mut contents = String::new();
     reader.read_to_string(&mut contents)?;
-    json5::from_str(&contents).context("failed to parse config")
+    parse_config(&contents, Format::Json)
+}
+
+/// parses config text in any supported format, migrating older config versions in memory
+fn parse_config(contents: &str, format: Format) -> Result<Config> {
+    let value = format.parse(contents).context("failed to parse config")?;
+    let migrated = migrate::migrate(value)?;
+    config_from_value(migrated.value)
+}
+
+/// expands variables in a single file's value and deserializes it
+fn config_from_value(mut value: serde_json::Value) -> Result<Config> {
+    let errors = vars::expand(&mut value);
+    // a file with includes may use vars defined in them; `read_config` reports
+    // references that stay undefined once everything is merged
+    if let Some(error) = errors.first() {
+        if !has_includes(&value) {
+            return Err(anyhow!("{}", error));
+        }
+    }
+    serde_json::from_value(value).context("failed to parse config")
+}
+
+fn has_includes(value: &serde_json::Value) -> bool {
+    value
+        .get("include")
+        .and_then(|v| v.as_array())
+        .is_some_and(|a| !a.is_empty())
 }
 
 /// ensures the schema file is up to date with the current version
@@ -127,7 +189,23 @@ pub fn load() -> Result<Config> {
 /// loads config with optional path override
 /// if override is specified and file doesn't exist, returns an error
 /// if no override, falls back to default behavior (create if missing)
+/// the active profile, if any, is applied on top of the default config
 pub fn load_with_override(override_path: Option<&Path>) -> Result<Config> {
+    profile::apply_active(load_base_with_override(override_path)?, override_path)
+}
+
+/// like `load_with_override`, but never rewrites an outdated config file
+/// (older versions are still migrated in memory)
+pub fn load_in_memory_with_override(override_path: Option<&Path>) -> Result<Config> {
+    profile::apply_active(load_base(override_path, false)?, override_path)
+}
+
+/// loads config merged with its includes, without applying the active profile
+pub fn load_base_with_override(override_path: Option<&Path>) -> Result<Config> {
+    load_base(override_path, true)
+}
+
+fn load_base(override_path: Option<&Path>, migrate_file: bool) -> Result<Config> {
     let path = get_config_path_with_override(override_path)?;
 
     if !path.exists() {
@@ -146,13 +224,85 @@ pub fn load_with_override(override_path: Option<&Path>) -> Result<Config> {
         }
     }
 
-    let file = fs::File::open(&path)
-        .with_context(|| format!("failed to read config file: {}", path.display()))?;
+    if migrate_file {
+        migrate_in_place(&path);
+    }
+
+    read_config(&path)
+}
+
+/// upgrades an outdated config file on disk, keeping a backup of the original;
+/// failures are reported but not fatal since the config is migrated in memory anyway.
+/// files that would lose their comments (YAML, TOML) are left to `cwm config migrate`
+fn migrate_in_place(path: &Path) {
+    let plan = match migrate::plan(path) {
+        Ok(plan) if plan.migrated.is_needed() && plan.in_place => plan,
+        _ => return,
+    };
 
-    let config: Config = parse_jsonc(file)
-        .with_context(|| format!("failed to parse config file: {}", path.display()))?;
+    match plan.apply() {
+        Ok(backup) => eprintln!(
+            "migrated config {} from version {} to {} (backup: {})",
+            path.display(),
+            plan.migrated.from,
+            plan.migrated.to,
+            backup.display()
+        ),
+        Err(e) => eprintln!(
+            "warning: failed to migrate config {}: {:#}",
+            path.display(),
+            e
+        ),
+    }
+}
+
+/// loads only the main config file, without merging its includes
+/// use this when the config is modified and saved back, so included files
+/// are not flattened into the main file
+pub fn load_main_with_override(override_path: Option<&Path>) -> Result<Config> {
+    let path = get_config_path_with_override(override_path)?;
+
+    if !path.exists() {
+        return load_base_with_override(override_path);
+    }
 
-    Ok(config)
+    read_config_file(&path)
+}
+
+/// loads the config merged with its includes, along with where each part came from
+pub fn load_resolved_with_override(override_path: Option<&Path>) -> Result<ResolvedConfig> {
+    let path = get_config_path_with_override(override_path)?;
+
+    if !path.exists() {
+        load_base_with_override(override_path)?;
+    }
+
+    include::resolve(&path)
+}
+
+/// reads a config file, merging in any files it includes
+fn read_config(path: &Path) -> Result<Config> {
+    let config = read_config_file(path)?;
+    if config.include.is_empty() {
+        return Ok(config);
+    }
+
+    let mut value = include::resolve(path)?.value;
+    if let Some(error) = vars::expand(&mut value).first() {
+        return Err(anyhow!("{}", error))
+            .with_context(|| format!("failed to parse config file: {}", path.display()));
+    }
+    serde_json::from_value(value)
+        .with_context(|| format!("failed to parse config file: {}", path.display()))
+}
+
+/// reads a single config file, ignoring includes
+fn read_config_file(path: &Path) -> Result<Config> {
+    let contents = fs::read_to_string(path)
+        .with_context(|| format!("failed to read config file: {}", path.display()))?;
+
+    parse_config(&contents, Format::from_path(path))
+        .with_context(|| format!("failed to parse config file: {}", path.display()))
 }
 
 pub fn save(config: &Config) -> Result<()> {
@@ -173,7 +323,37 @@ pub fn save_with_override(config: &Config, override_path: Option<&Path>) -> Resu
         fs::create_dir_all(parent)?;
     }
 
-    let content = serde_json::to_string_pretty(config).context("failed to serialize config")?;
+    // edit the existing file in place so comments and formatting survive
+    let format = Format::from_path(&path);
+    let existing = match fs::read_to_string(&path) {
+        Ok(existing) => Some(existing),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => None,
+        Err(e) => {
+            return Err(e)
+                .with_context(|| format!("failed to read config file: {}", path.display()))
+        }
+    };
+    let fresh = || -> Result<String> {
+        let value = serde_json::to_value(config).context("failed to serialize config")?;
+        format.render(&value)
+    };
+    let content = match existing.map(|existing| update_config_text(&existing, format, config)) {
+        Some(Ok(content)) => content,
+        Some(Err(e)) => {
+            // writing the file from scratch loses its comments, so keep the original
+            let backup = save_backup_path(&path);
+            fs::copy(&path, &backup)
+                .with_context(|| format!("failed to write backup: {}", backup.display()))?;
+            eprintln!(
+                "warning: could not edit {} in place ({:#}), rewrote it (backup: {})",
+                path.display(),
+                e,
+                backup.display()
+            );
+            fresh()?
+        }
+        None => fresh()?,
+    };
 
     fs::write(&path, content)
         .with_context(|| format!("failed to write config file: {}", path.display()))?;
@@ -181,74 +361,258 @@ pub fn save_with_override(config: &Config, override_path: Option<&Path>) -> Resu
     Ok(())
 }
 
+/// applies the difference between the config stored in `text` and `config`
+/// as targeted edits to `text`
+///
+/// YAML and TOML files are written out again with the changes applied; only
+/// JSON files keep their comments
+/// where `save` keeps a file it had to rewrite, e.g. `config.jsonc.bak`
+fn save_backup_path(path: &Path) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(".bak");
+    path.with_file_name(name)
+}
+
+fn update_config_text(text: &str, format: Format, config: &Config) -> Result<String> {
+    let raw = format.parse(text).context("failed to parse config")?;
+    if format != Format::Json {
+        let migrated = migrate::migrate(raw)?;
+        let current = config_from_value(migrated.value.clone())?;
+        let old = serde_json::to_value(current).context("failed to serialize config")?;
+        let new = serde_json::to_value(config).context("failed to serialize config")?;
+
+        let mut value = migrated.value;
+        format::patch(&mut value, &old, &new);
+        return format.render(&value);
+    }
+
+    // bring an outdated file up to date first, so the diff below only
+    // contains the caller's changes
+    let migrated = migrate::migrate(raw.clone())?;
+    let text = if migrated.is_needed() {
+        jsonc::update(text, &raw, &migrated.value)?
+    } else {
+        text.to_string()
+    };
+
+    // compared with expanded variables, so only entries the caller changed are
+    // written and unchanged `${...}` references stay as they are
+    let current = config_from_value(migrated.value)?;
+    let old = serde_json::to_value(current).context("failed to serialize config")?;
+    let new = serde_json::to_value(config).context("failed to serialize config")?;
+    jsonc::update(&text, &old, &new)
+}
+
 /// Verify configuration file and return a list of errors
-pub fn verify(path: &Path) -> Result<Vec<String>> {
+/// result of `verify`: errors make the config unusable, warnings point at
+/// deprecated settings that still work and at hotkeys that are already taken
+#[derive(Debug, Default)]
+pub struct Verification {
+    pub errors: Vec<String>,
+    pub warnings: Vec<String>,
+}
+
+pub fn verify(path: &Path) -> Result<Verification> {
     let mut errors = Vec::new();
 
     if !path.exists() {
         return Err(anyhow!("config file not found: {}", path.display()));
     }
 
-    let file = fs::File::open(path)
+    let contents = fs::read_to_string(path)
         .with_context(|| format!("failed to read config file: {}", path.display()))?;
 
-    let config: Config = match parse_jsonc(file) {
-        Ok(c) => c,
+    let format = Format::from_path(path);
+    let raw = match format.parse(&contents) {
+        Ok(v) => v,
         Err(e) => {
-            return Err(anyhow!("invalid JSON: {}", e));
+            return Err(anyhow!(
+                "invalid {}: {:#}",
+                format.as_str().to_uppercase(),
+                e
+            ));
         }
     };
 
-    // parse condition definitions first (needed for $ref resolution)
-    let condition_defs = parse_condition_definitions(&config.conditions, &mut errors);
+    // errors point at the line they are about, unless entries may come from included files
+    let positions = if has_includes(&raw) {
+        format::Positions::new()
+    } else {
+        format.positions(&contents).unwrap_or_default()
+    };
+    let finish = |errors: Vec<String>, warnings: Vec<String>| Verification {
+        errors: errors
+            .iter()
+            .map(|e| format::annotate_error(e, &positions))
+            .collect(),
+        warnings,
+    };
 
-    // validate shortcuts
-    for (i, shortcut) in config.shortcuts.iter().enumerate() {
-        let prefix = format!("shortcuts[{}]", i);
+    let mut warnings = migrate::deprecations(&raw);
 
-        // validate hotkey format
-        if let Err(e) = Hotkey::parse(&shortcut.keys) {
-            errors.push(format!(
-                "{}: invalid keys '{}': {}",
-                prefix, shortcut.keys, e
-            ));
+    let migrated = match migrate::migrate(raw) {
+        Ok(m) => m,
+        Err(e) => {
+            errors.push(format!("version: {:#}", e));
+            return Ok(finish(errors, warnings));
         }
+    };
 
-        // validate action
-        if let Err(e) = validate_action(&shortcut.action) {
-            errors.push(format!("{}: {}", prefix, e));
+    // validate the merged config so errors in included files are reported too
+    let mut value = if has_includes(&migrated.value) {
+        match include::resolve(path) {
+            Ok(resolved) => resolved.value,
+            Err(e) => {
+                errors.push(format!("include: {:#}", e));
+                return Ok(finish(errors, warnings));
+            }
         }
+    } else {
+        migrated.value
+    };
 
-        // focus requires app
-        if shortcut.action == "focus" && shortcut.app.is_none() {
-            errors.push(format!("{}: action 'focus' requires 'app' field", prefix));
+    // expanded first, so the checks below see the values that will be used
+    errors.extend(vars::expand(&mut value));
+
+    let config: Config = match serde_path_to_error::deserialize(value) {
+        Ok(c) => c,
+        Err(e) => {
+            errors.push(format!("{}: {}", e.path(), e.inner()));
+            return Ok(finish(errors, warnings));
         }
+    };
 
-        // validate when condition
-        if let Some(when) = &shortcut.when {
-            if let Err(e) = parse_condition(when, &condition_defs) {
-                errors.push(format!("{}.when: {}", prefix, e));
-            }
+    errors.extend(validate_config(&config));
+    warnings.extend(conflicts::conflicts(&config).iter().map(|c| c.to_string()));
+
+    Ok(finish(errors, warnings))
+}
+
+/// lints the config file at `path` (merged with its includes); see `lint::lint`
+pub fn lint(
+    path: &Path,
+    displays: Option<&[crate::display::DisplayInfo]>,
+) -> Result<Vec<lint::Finding>> {
+    if !path.exists() {
+        return Err(anyhow!("config file not found: {}", path.display()));
+    }
+
+    let config = read_config(path)?;
+    Ok(lint::lint(&config, displays))
+}
+
+/// a config file rendered in another format
+#[derive(Debug)]
+pub struct Conversion {
+    pub from: PathBuf,
+    pub to: PathBuf,
+    pub content: String,
+}
+
+impl Conversion {
+    /// where the original file is moved when the conversion is written
+    pub fn backup_path(&self) -> PathBuf {
+        let mut name = self.from.file_name().unwrap_or_default().to_os_string();
+        name.push(".bak");
+        self.from.with_file_name(name)
+    }
+
+    /// writes the converted file and moves the original out of the way, so
+    /// only one config file is left for discovery to find
+    pub fn apply(&self) -> Result<PathBuf> {
+        if self.to != self.from && self.to.exists() {
+            return Err(anyhow!("{} already exists", self.to.display()));
         }
+
+        let backup = self.backup_path();
+        fs::copy(&self.from, &backup)
+            .with_context(|| format!("failed to write backup: {}", backup.display()))?;
+        fs::write(&self.to, &self.content)
+            .with_context(|| format!("failed to write config file: {}", self.to.display()))?;
+        if self.to != self.from {
+            fs::remove_file(&self.from)
+                .with_context(|| format!("failed to remove {}", self.from.display()))?;
+        }
+        Ok(backup)
     }
+}
 
-    // validate app_rules
-    for (i, rule) in config.app_rules.iter().enumerate() {
-        let prefix = format!("app_rules[{}]", i);
+/// converts a config file to another format; included files are left as they are
+pub fn convert(path: &Path, to: Format) -> Result<Conversion> {
+    let value = format::read_value(path)?;
+    let migrated = migrate::migrate(value)?;
+    let content = format::convert(&migrated.value, to)?;
 
-        // validate action
-        if let Err(e) = validate_action(&rule.action) {
-            errors.push(format!("{}: {}", prefix, e));
+    // make sure nothing was lost on the way
+    parse_config(&content, to).context("the converted config does not load")?;
+
+    Ok(Conversion {
+        from: path.to_path_buf(),
+        to: path.with_extension(to.as_str()),
+        content,
+    })
+}
+
+/// the layout `config`'s shortcut keys are read with; checking a config does
+/// not change the layout the daemon or the CLI reads keys with
+pub(crate) fn keyboard_layout(config: &Config) -> &'static Layout {
+    keyboard::configured(config.settings.keyboard_layout.as_deref())
+}
+
+/// semantic checks on a parsed config (actions, conditions, aliases, ipc settings)
+pub fn validate_config(config: &Config) -> Vec<String> {
+    let mut errors = Vec::new();
+
+    // shortcut keys are read with the config's keyboard layout
+    if let Some(id) = &config.settings.keyboard_layout {
+        if let Err(e) = Layout::find(id) {
+            errors.push(format!("settings.keyboard_layout: {}", e));
         }
+    }
+    let layout = keyboard_layout(config);
 
-        // validate when condition
-        if let Some(when) = &rule.when {
-            if let Err(e) = parse_condition(when, &condition_defs) {
-                errors.push(format!("{}.when: {}", prefix, e));
-            }
+    // parse condition definitions first (needed for $ref resolution)
+    let condition_defs = parse_condition_definitions(&config.conditions, &mut errors);
+
+    validate_shortcuts(
+        "shortcuts",
+        &config.shortcuts,
+        layout,
+        &condition_defs,
+        &mut errors,
+    );
+    validate_app_rules("app_rules", &config.app_rules, &condition_defs, &mut errors);
+
+    // validate profiles
+    for name in profile::names(config) {
+        let prefix = format!("profiles.{}", name);
+        let overrides = &config.profiles[&name];
+        validate_shortcuts(
+            &format!("{}.shortcuts", prefix),
+            &overrides.shortcuts,
+            layout,
+            &condition_defs,
+            &mut errors,
+        );
+        validate_app_rules(
+            &format!("{}.app_rules", prefix),
+            &overrides.app_rules,
+            &condition_defs,
+            &mut errors,
+        );
+        if let Err(e) = profile::apply(config, &name) {
+            errors.push(format!("{}: {:#}", prefix, e));
         }
     }
 
+    let mut mode_names: Vec<&String> = config.modes.keys().collect();
+    mode_names.sort();
+    for name in mode_names {
+        validate_mode(name, &config.modes[name], layout, &mut errors);
+    }
+
+    validate_mouse_bindings(&config.mouse_bindings, &mut errors);
+
     // validate spotlight shortcuts
     for (i, spotlight) in config.spotlight.iter().enumerate() {
         let prefix = format!("spotlight[{}]", i);
@@ -279,13 +643,311 @@ pub fn verify(path: &Path) -> Result<Vec<String>> {
         }
     }
 
-    Ok(errors)
+    // validate ipc access control
+    let ipc = &config.settings.ipc;
+    if let Err(e) = crate::daemon::auth::parse_socket_mode(&ipc.socket_mode) {
+        errors.push(format!("settings.ipc.socket_mode: {}", e));
+    }
+    let mut seen_tokens = std::collections::HashSet::new();
+    for (i, token) in ipc.tokens.iter().enumerate() {
+        let prefix = format!("settings.ipc.tokens[{}]", i);
+        if token.name.trim().is_empty() {
+            errors.push(format!("{}: name cannot be empty", prefix));
+        }
+        if token.token.trim().is_empty() {
+            errors.push(format!("{}: token cannot be empty", prefix));
+        } else if !seen_tokens.insert(token.token.as_str()) {
+            errors.push(format!("{}: duplicate token", prefix));
+        }
+    }
+    if ipc.require_token && ipc.tokens.is_empty() {
+        errors.push("settings.ipc: require_token is set but no tokens are configured".to_string());
+    }
+
+    validate_app_list(
+        "settings.disabled_in",
+        &config.settings.disabled_in,
+        &mut errors,
+    );
+
+    errors
 }
 
-fn validate_action(action: &str) -> Result<(), String> {
-    let valid_base_actions = ["focus", "maximize"];
+fn validate_app_list(path: &str, apps: &[String], errors: &mut Vec<String>) {
+    for (i, app) in apps.iter().enumerate() {
+        if app.trim().is_empty() {
+            errors.push(format!("{}[{}]: app name cannot be empty", path, i));
+        }
+    }
+}
+
+fn validate_mode(name: &str, mode: &Mode, layout: &Layout, errors: &mut Vec<String>) {
+    let prefix = format!("modes.{}", name);
+
+    if let Err(e) = KeySequence::parse_in(&mode.enter, layout) {
+        errors.push(format!(
+            "{}.enter: invalid keys '{}': {}",
+            prefix, mode.enter, e
+        ));
+    }
+    if let Err(e) = KeySequence::parse_chord_in(&mode.exit, layout) {
+        errors.push(format!(
+            "{}.exit: invalid keys '{}': {}",
+            prefix, mode.exit, e
+        ));
+    }
+    if mode.timeout_ms == Some(0) {
+        errors.push(format!("{}.timeout_ms: must be at least 1", prefix));
+    }
+
+    for (keys, action) in &mode.bindings {
+        let path = format!("{}.bindings.{}", prefix, keys);
+        if let Err(e) = KeySequence::parse_chord_in(keys, layout) {
+            errors.push(format!("{}: invalid keys '{}': {}", path, keys, e));
+        }
+        if action == "focus" {
+            errors.push(format!(
+                "{}: action 'focus' requires an app, which mode bindings cannot set",
+                path
+            ));
+        } else if let Err(e) = validate_action(action) {
+            errors.push(format!("{}: {}", path, e));
+        }
+    }
+}
+
+fn validate_mouse_bindings(bindings: &[MouseBinding], errors: &mut Vec<String>) {
+    for (i, binding) in bindings.iter().enumerate() {
+        let prefix = format!("mouse_bindings[{}]", i);
+
+        let chord = match MouseChord::parse(&binding.mouse) {
+            Ok(chord) => Some(chord),
+            Err(e) => {
+                errors.push(format!(
+                    "{}: invalid mouse binding '{}': {}",
+                    prefix, binding.mouse, e
+                ));
+                None
+            }
+        };
+
+        match (binding.drag, binding.action.is_empty()) {
+            (Some(_), false) => {
+                errors.push(format!(
+                    "{}: set either 'action' or 'drag', not both",
+                    prefix
+                ));
+            }
+            (None, true) => errors.push(format!("{}: set either 'action' or 'drag'", prefix)),
+            (Some(_), true) => {
+                if chord.is_some_and(|chord| matches!(chord.input, MouseInput::Scroll(_))) {
+                    errors.push(format!(
+                        "{}: 'drag' needs a mouse button, not a scroll direction",
+                        prefix
+                    ));
+                }
+            }
+            (None, false) => {
+                // the app under the cursor is the only app a mouse binding can focus
+                if binding.action == "focus" {
+                    if binding.window.is_focused() {
+                        errors.push(format!(
+                            "{}: action 'focus' requires window 'under_cursor'",
+                            prefix
+                        ));
+                    }
+                } else if let Err(e) = validate_action(&binding.action) {
+                    errors.push(format!("{}: {}", prefix, e));
+                }
+            }
+        }
+    }
+}
+
+fn validate_shortcuts(
+    name: &str,
+    shortcuts: &[Shortcut],
+    layout: &Layout,
+    condition_defs: &HashMap<String, Condition>,
+    errors: &mut Vec<String>,
+) {
+    for (i, shortcut) in shortcuts.iter().enumerate() {
+        let prefix = format!("{}[{}]", name, i);
 
-    if valid_base_actions.contains(&action) {
+        // validate hotkey format
+        let keys = if shortcut.trigger.is_tap() {
+            KeySequence::parse_in(&shortcut.keys, layout).map(|_| ())
+        } else {
+            TriggerKey::parse_in(&shortcut.keys, layout).map(|_| ())
+        };
+        if let Err(e) = keys {
+            errors.push(format!(
+                "{}: invalid keys '{}': {}",
+                prefix, shortcut.keys, e
+            ));
+        } else if shortcut.trigger.is_tap() && ModifierKey::parse(&shortcut.keys).is_some() {
+            errors.push(format!(
+                "{}: a lone modifier needs trigger 'double_tap' or 'hold'",
+                prefix
+            ));
+        }
+        if shortcut.trigger_ms == Some(0) {
+            errors.push(format!("{}.trigger_ms: must be at least 1", prefix));
+        } else if shortcut.trigger_ms.is_some() && shortcut.trigger.is_tap() {
+            errors.push(format!(
+                "{}: trigger_ms needs trigger 'double_tap' or 'hold'",
+                prefix
+            ));
+        }
+        if let Some(release) = &shortcut.release_action {
+            if shortcut.trigger != Trigger::Hold {
+                errors.push(format!("{}: release_action needs trigger 'hold'", prefix));
+            } else if let Err(e) = validate_action(release) {
+                errors.push(format!("{}.release_action: {}", prefix, e));
+            }
+        }
+        if let Some(repeat) = &shortcut.repeat {
+            if !shortcut.trigger.is_tap() {
+                errors.push(format!(
+                    "{}: repeat cannot be used with trigger 'double_tap' or 'hold'",
+                    prefix
+                ));
+            } else if let Err(e) = Repeat::parse(repeat) {
+                errors.push(format!("{}.repeat: {}", prefix, e));
+            }
+        }
+
+        // validate action, or each action of each cycle step
+        if !shortcut.cycle.is_empty() {
+            if !shortcut.action.is_empty() {
+                errors.push(format!(
+                    "{}: set either 'action' or 'cycle', not both",
+                    prefix
+                ));
+            }
+            for (j, step) in shortcut.cycle.iter().enumerate() {
+                for action in cycle::step_actions(step) {
+                    if let Err(e) = validate_action(&action) {
+                        errors.push(format!("{}.cycle[{}]: {}", prefix, j, e));
+                    } else if action == "focus" && shortcut.app.is_none() {
+                        errors.push(format!(
+                            "{}.cycle[{}]: action 'focus' requires 'app' field",
+                            prefix, j
+                        ));
+                    }
+                }
+            }
+        } else if shortcut.action.is_empty() {
+            errors.push(format!("{}: set either 'action' or 'cycle'", prefix));
+        } else {
+            if let Err(e) = validate_action(&shortcut.action) {
+                errors.push(format!("{}: {}", prefix, e));
+            }
+
+            // focus requires app
+            if shortcut.action == "focus" && shortcut.app.is_none() {
+                errors.push(format!("{}: action 'focus' requires 'app' field", prefix));
+            }
+        }
+
+        // validate when condition
+        if let Some(when) = &shortcut.when {
+            if let Err(e) = parse_condition(when, condition_defs) {
+                errors.push(format!("{}.when: {}", prefix, e));
+            }
+        }
+
+        if !shortcut.only_in.is_empty() && !shortcut.except_in.is_empty() {
+            errors.push(format!(
+                "{}: set either 'only_in' or 'except_in', not both",
+                prefix
+            ));
+        }
+        validate_app_list(&format!("{}.only_in", prefix), &shortcut.only_in, errors);
+        validate_app_list(
+            &format!("{}.except_in", prefix),
+            &shortcut.except_in,
+            errors,
+        );
+    }
+}
+
+fn validate_app_rules(
+    name: &str,
+    rules: &[AppRule],
+    condition_defs: &HashMap<String, Condition>,
+    errors: &mut Vec<String>,
+) {
+    for (i, rule) in rules.iter().enumerate() {
+        let prefix = format!("{}[{}]", name, i);
+
+        // validate action
+        if let Err(e) = validate_action(&rule.action) {
+            errors.push(format!("{}: {}", prefix, e));
+        }
+
+        // validate when condition
+        if let Some(when) = &rule.when {
+            if let Err(e) = parse_condition(when, condition_defs) {
+                errors.push(format!("{}.when: {}", prefix, e));
+            }
+        }
+    }
+}
+
+/// one form of the action grammar, used by `validate_action` and the JSON schema
+pub struct ActionSpec {
+    pub name: &'static str,
+    /// regex for the argument after `name:`, `None` for actions without one
+    pub arg_pattern: Option<&'static str>,
+    /// how the action is written in error messages, e.g. `move:<target>`
+    pub usage: &'static str,
+    pub description: &'static str,
+    pub examples: &'static [&'static str],
+}
+
+pub const ACTIONS: &[ActionSpec] = &[
+    ActionSpec {
+        name: "focus",
+        arg_pattern: None,
+        usage: "focus",
+        description: "focus the application window (requires app field)",
+        examples: &["focus"],
+    },
+    ActionSpec {
+        name: "maximize",
+        arg_pattern: None,
+        usage: "maximize",
+        description: "maximize the current or specified window",
+        examples: &["maximize"],
+    },
+    ActionSpec {
+        name: "move",
+        arg_pattern: Some(".+"),
+        usage: "move:<target>",
+        description: "move window to a position and/or display. positions: top-left, top-right, bottom-left, bottom-right, left, right, 50%,50%, 100,200px. displays: next, prev, 0, display=next, or combined: top-left;display=2 (semicolon separates arguments)",
+        examples: &[
+            "move:next",
+            "move:top-left",
+            "move:50%,50%",
+            "move:display=external",
+            "move:top-left;display=2",
+        ],
+    },
+    ActionSpec {
+        name: "resize",
+        arg_pattern: Some("(100|[1-9][0-9]?|full|[+-]?(100|[1-9][0-9]?)%[wh]|(100|[1-9][0-9]?)x(100|[1-9][0-9]?)%)"),
+        usage: "resize:<size>",
+        description: "resize window to a percentage of the screen (1-100) or full. window is centered. one side: 50%w, 50%h, or both: 50x100%; +5%w grows a side and -5%h shrinks one. these keep the screen edges the window touches",
+        examples: &["resize:80", "resize:full", "resize:50x100%", "resize:+5%w"],
+    },
+];
+
+fn validate_action(action: &str) -> Result<(), String> {
+    let is_base_action = ACTIONS
+        .iter()
+        .any(|spec| spec.arg_pattern.is_none() && spec.name == action);
+    if is_base_action {
         return Ok(());
     }
 
@@ -317,20 +979,31 @@ fn validate_action(action: &str) -> Result<(), String> {
                 Ok(n) => {
                     return Err(format!("resize size {} out of range (1-100)", n));
                 }
-                Err(_) => {
-                    return Err(format!(
-                        "invalid resize size '{}': use a number 1-100 or 'full'",
-                        arg
-                    ));
-                }
+                Err(_) => match ResizeTarget::parse(arg) {
+                    Ok(
+                        ResizeTarget::Width(_)
+                        | ResizeTarget::Height(_)
+                        | ResizeTarget::Sides { .. }
+                        | ResizeTarget::GrowWidth(_)
+                        | ResizeTarget::GrowHeight(_),
+                    ) => {}
+                    _ => {
+                        return Err(format!(
+                            "invalid resize size '{}': use a number 1-100, 'full', one side like 50%w, both like 50x100% or a change like +5%w",
+                            arg
+                        ));
+                    }
+                },
             }
         }
         return Ok(());
     }
 
+    let usages: Vec<&str> = ACTIONS.iter().map(|spec| spec.usage).collect();
     Err(format!(
-        "invalid action '{}': valid actions are focus, maximize, move:<target>, resize:<size>",
-        action
+        "invalid action '{}': valid actions are {}",
+        action,
+        usages.join(", ")
     ))
 }
 
@@ -375,100 +1048,106 @@ fn parse_condition_definitions(
     parsed
 }
 
+/// reads the value at a config path (e.g. `settings.animate`, `shortcuts[0].keys`)
+pub fn get_value(config: &Config, key: &str) -> Result<serde_json::Value> {
+    let path = path::ConfigPath::parse(key)?;
+    json_schema::schema_at(&path)?;
+
+    let root = serde_json::to_value(config).context("failed to serialize config")?;
+    path::get(&root, &path).cloned()
+}
+
+/// sets a config path from a command-line string
+///
+/// the string is interpreted according to the schema type at the path:
+/// strings are taken as-is, booleans accept yes/no/on/off, everything else
+/// is parsed as JSON5 (falling back to a plain string)
 pub fn set_value(config: &mut Config, key: &str, value: &str) -> Result<()> {
-    let parts: Vec<&str> = key.split('.').collect();
+    let path = path::ConfigPath::parse(key)?;
+    let schema = json_schema::schema_at(&path)?;
+    set_path(config, &path, coerce_value(value, schema)?)
+}
 
-    match parts.as_slice() {
-        ["settings", "launch"] => {
-            config.settings.launch = parse_bool(value)?;
-        }
-        ["settings", "animate"] => {
-            config.settings.animate = parse_bool(value)?;
-        }
-        ["settings", "fuzzy_threshold"] => {
-            config.settings.fuzzy_threshold = value
-                .parse()
-                .with_context(|| format!("Invalid number: {}", value))?;
-        }
-        ["settings", "delay_ms"] => {
-            config.settings.delay_ms = value
-                .parse()
-                .with_context(|| format!("Invalid number: {}", value))?;
-        }
-        ["settings", "retry", "count"] => {
-            config.settings.retry.count = value
-                .parse()
-                .with_context(|| format!("Invalid number: {}", value))?;
-        }
-        ["settings", "retry", "delay_ms"] => {
-            config.settings.retry.delay_ms = value
-                .parse()
-                .with_context(|| format!("Invalid number: {}", value))?;
-        }
-        ["settings", "retry", "backoff"] => {
-            config.settings.retry.backoff = value
-                .parse()
-                .with_context(|| format!("Invalid number: {}", value))?;
-        }
-        ["settings", "update", "enabled"] => {
-            config.settings.update.enabled = parse_bool(value)?;
-        }
-        ["settings", "update", "check_frequency"] => {
-            config.settings.update.check_frequency = match value.to_lowercase().as_str() {
-                "daily" => UpdateFrequency::Daily,
-                "weekly" => UpdateFrequency::Weekly,
-                "manual" => UpdateFrequency::Manual,
-                _ => {
-                    return Err(anyhow!(
-                        "Invalid check_frequency: {}. Use daily, weekly, or manual",
-                        value
-                    ))
-                }
-            };
-        }
-        ["settings", "update", "auto_update"] => {
-            config.settings.update.auto_update = match value.to_lowercase().as_str() {
-                "always" => AutoUpdateMode::Always,
-                "prompt" => AutoUpdateMode::Prompt,
-                "never" => AutoUpdateMode::Never,
-                _ => {
-                    return Err(anyhow!(
-                        "Invalid auto_update: {}. Use always, prompt, or never",
-                        value
-                    ))
-                }
-            };
-        }
-        ["settings", "update", "channels", "dev"] => {
-            config.settings.update.channels.dev = parse_bool(value)?;
-        }
-        ["settings", "update", "channels", "beta"] => {
-            config.settings.update.channels.beta = parse_bool(value)?;
-        }
-        ["settings", "update", "channels", "stable"] => {
-            config.settings.update.channels.stable = parse_bool(value)?;
-        }
-        ["settings", "update", "telemetry", "enabled"] => {
-            config.settings.update.telemetry.enabled = parse_bool(value)?;
-        }
-        ["settings", "update", "telemetry", "include_system_info"] => {
-            config.settings.update.telemetry.include_system_info = parse_bool(value)?;
+/// sets a config path to a JSON value
+pub fn set_json_value(config: &mut Config, key: &str, value: serde_json::Value) -> Result<()> {
+    set_path(config, &path::ConfigPath::parse(key)?, value)
+}
+
+/// removes a config path; settings fall back to their defaults
+pub fn unset_value(config: &mut Config, key: &str) -> Result<()> {
+    let path = path::ConfigPath::parse(key)?;
+    json_schema::schema_at(&path)?;
+    edit_config(config, key, |root| path::unset(root, &path).map(|_| ()))
+}
+
+fn set_path(config: &mut Config, path: &path::ConfigPath, value: serde_json::Value) -> Result<()> {
+    let schema = json_schema::schema_at(path)?;
+    let errors = json_schema::validate_against(&value, schema, &path.to_string());
+    if !errors.is_empty() {
+        return Err(anyhow!("{}", errors.join("; ")));
+    }
+
+    edit_config(config, &path.to_string(), |root| {
+        path::set(root, path, value)
+    })
+}
+
+/// applies an edit to the serialized config and only keeps it if the result
+/// still deserializes and doesn't introduce new validation errors
+fn edit_config(
+    config: &mut Config,
+    key: &str,
+    edit: impl FnOnce(&mut serde_json::Value) -> Result<()>,
+) -> Result<()> {
+    let mut root = serde_json::to_value(&*config).context("failed to serialize config")?;
+    edit(&mut root)?;
+
+    let updated: Config =
+        serde_json::from_value(root).map_err(|e| anyhow!("invalid value for {}: {}", key, e))?;
+
+    let existing = validate_config(config);
+    let introduced: Vec<String> = validate_config(&updated)
+        .into_iter()
+        .filter(|e| !existing.contains(e))
+        .collect();
+    if !introduced.is_empty() {
+        return Err(anyhow!("{}", introduced.join("; ")));
+    }
+
+    *config = updated;
+    Ok(())
+}
+
+fn coerce_value(raw: &str, schema: &serde_json::Value) -> Result<serde_json::Value> {
+    // match enum values case-insensitively so `Weekly` works like `weekly`
+    if let Some(allowed) = schema.get("enum").and_then(|e| e.as_array()) {
+        if let Some(value) = allowed
+            .iter()
+            .find(|v| v.as_str().is_some_and(|v| v.eq_ignore_ascii_case(raw)))
+        {
+            return Ok(value.clone());
         }
+    }
+
+    match json_schema::schema_type(schema) {
+        Some("string") => Ok(serde_json::Value::String(raw.to_string())),
+        Some("boolean") => parse_bool(raw).map(serde_json::Value::Bool),
         _ => {
-            return Err(anyhow!(
-                "Unknown config key: {}. Valid keys include: settings.launch, settings.animate, settings.fuzzy_threshold, settings.update.enabled, settings.update.channels.stable, etc.",
-                key
-            ));
+            Ok(json5::from_str(raw).unwrap_or_else(|_| serde_json::Value::String(raw.to_string())))
         }
     }
-
-    Ok(())
 }
 
 /// generates a default config with example shortcuts and rules
 pub fn default_with_examples() -> Config {
     Config {
         schema: Some(schema::DEFAULT_SCHEMA_REF.to_string()),
+        version: migrate::CURRENT_VERSION,
+        include: Vec::new(),
+        vars: schema::Vars::new(),
+        profiles: schema::Profiles::new(),
+        modes: schema::Modes::new(),
+        mouse_bindings: Vec::new(),
         conditions: schema::ConditionDefinitions::new(),
         shortcuts: vec![
             Shortcut {
@@ -477,6 +1156,7 @@ pub fn default_with_examples() -> Config {
                 app: Some("Slack".to_string()),
                 launch: Some(true),
                 when: None,
+                ..Default::default()
             },
             Shortcut {
                 keys: "ctrl+alt+t".to_string(),
@@ -484,6 +1164,7 @@ pub fn default_with_examples() -> Config {
                 app: Some("Terminal".to_string()),
                 launch: None,
                 when: None,
+                ..Default::default()
             },
             // match by window title instead of app name
             Shortcut {
@@ -492,6 +1173,7 @@ pub fn default_with_examples() -> Config {
                 app: Some("GitHub".to_string()),
                 launch: None,
                 when: None,
+                ..Default::default()
             },
             Shortcut {
                 keys: "ctrl+alt+m".to_string(),
@@ -499,6 +1181,7 @@ pub fn default_with_examples() -> Config {
                 app: None,
                 launch: None,
                 when: None,
+                ..Default::default()
             },
             Shortcut {
                 keys: "ctrl+alt+right".to_string(),
@@ -506,6 +1189,7 @@ pub fn default_with_examples() -> Config {
                 app: None,
                 launch: None,
                 when: None,
+                ..Default::default()
             },
             Shortcut {
                 keys: "ctrl+alt+8".to_string(),
@@ -513,6 +1197,7 @@ pub fn default_with_examples() -> Config {
                 app: None,
                 launch: None,
                 when: None,
+                ..Default::default()
             },
         ],
         app_rules: vec![AppRule {
@@ -697,6 +1382,193 @@ mod tests {
         assert!(set_value(&mut config, "settings.fuzzy_threshold", "abc").is_err());
     }
 
+    #[test]
+    fn test_set_value_enum_case_insensitive() {
+        let mut config = Config::default();
+        set_value(&mut config, "settings.update.check_frequency", "Weekly").unwrap();
+        assert!(matches!(
+            config.settings.update.check_frequency,
+            UpdateFrequency::Weekly
+        ));
+        assert!(set_value(&mut config, "settings.update.check_frequency", "hourly").is_err());
+    }
+
+    #[test]
+    fn test_set_value_paths() {
+        let mut config = Config::default();
+
+        set_value(
+            &mut config,
+            "shortcuts[+]",
+            "{keys: 'ctrl+alt+m', action: 'maximize'}",
+        )
+        .unwrap();
+        set_value(&mut config, "shortcuts[0].when", "{time: '9AM-5PM'}").unwrap();
+        assert!(config.shortcuts[0].when.is_some());
+
+        set_value(&mut config, "display_aliases.office[+]", "10AC_D0B3_67890").unwrap();
+        assert_eq!(
+            config.display_aliases["office"],
+            vec!["10AC_D0B3_67890".to_string()]
+        );
+
+        set_value(
+            &mut config,
+            "app_rules[+]",
+            "{app: 'Slack', action: 'maximize'}",
+        )
+        .unwrap();
+        set_value(&mut config, "app_rules[app=slack].delay_ms", "250").unwrap();
+        assert_eq!(config.app_rules[0].delay_ms, Some(250));
+    }
+
+    #[test]
+    fn test_set_value_rejects_invalid_entries() {
+        let mut config = Config::default();
+
+        // missing action
+        assert!(set_value(&mut config, "shortcuts[+]", "{keys: 'ctrl+alt+m'}").is_err());
+        // unknown action
+        assert!(set_value(
+            &mut config,
+            "shortcuts[+]",
+            "{keys: 'ctrl+alt+m', action: 'explode'}"
+        )
+        .is_err());
+        // focus without app passes the schema but fails validation
+        assert!(set_value(
+            &mut config,
+            "shortcuts[+]",
+            "{keys: 'ctrl+alt+m', action: 'focus'}"
+        )
+        .is_err());
+        assert!(config.shortcuts.is_empty());
+
+        assert!(set_value(&mut config, "settings.fuzzy_threshold", "-1").is_err());
+    }
+
+    #[test]
+    fn test_get_value() {
+        let config = default_with_examples();
+        assert_eq!(
+            get_value(&config, "shortcuts[0].keys").unwrap(),
+            serde_json::json!(config.shortcuts[0].keys)
+        );
+        assert_eq!(
+            get_value(&config, "settings.fuzzy_threshold").unwrap(),
+            serde_json::json!(config.settings.fuzzy_threshold)
+        );
+        assert!(get_value(&config, "settings.unknown").is_err());
+    }
+
+    #[test]
+    fn test_unset_value() {
+        let mut config = Config::default();
+        set_value(&mut config, "settings.delay_ms", "10").unwrap();
+        unset_value(&mut config, "settings.delay_ms").unwrap();
+        assert_eq!(config.settings.delay_ms, 500);
+
+        set_value(
+            &mut config,
+            "app_rules[+]",
+            "{app: 'Slack', action: 'maximize', delay_ms: 100}",
+        )
+        .unwrap();
+        unset_value(&mut config, "app_rules[app=Slack].delay_ms").unwrap();
+        assert_eq!(config.app_rules[0].delay_ms, None);
+
+        // required fields can't be removed
+        assert!(unset_value(&mut config, "app_rules[0].app").is_err());
+
+        unset_value(&mut config, "app_rules[0]").unwrap();
+        assert!(config.app_rules.is_empty());
+    }
+
+    #[test]
+    fn test_save_preserves_comments() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.jsonc");
+        let example = include_str!("../../examples/02-work-hours-conditions.jsonc");
+        fs::write(&path, example).unwrap();
+
+        let mut config = load_main_with_override(Some(&path)).unwrap();
+        set_value(&mut config, "settings.animate", "true").unwrap();
+        set_value(&mut config, "shortcuts[0].launch", "false").unwrap();
+        config.shortcuts.remove(2);
+        save_with_override(&config, Some(&path)).unwrap();
+
+        let saved = fs::read_to_string(&path).unwrap();
+        assert!(saved.starts_with("// work hours conditions\n// shortcuts that only work"));
+        assert!(saved.contains("\"$schema\": \"../config.schema.json\""));
+        assert!(saved.contains("\"when\": { \"$ref\": \"work_hours\" }"));
+        assert!(!saved.contains("Outlook"));
+
+        let reloaded = load_main_with_override(Some(&path)).unwrap();
+        assert!(reloaded.settings.animate);
+        assert_eq!(reloaded.shortcuts[0].launch, Some(false));
+        assert_eq!(reloaded.shortcuts.len(), 2);
+    }
+
+    #[test]
+    fn test_load_migrates_old_version() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.jsonc");
+        let original = r#"{
+            // no version: written before versioning
+            "shortcuts": [{"keys": "ctrl+alt+n", "action": "move_display:next"}]
+        }"#;
+        fs::write(&path, original).unwrap();
+
+        // verify reports the deprecation without failing
+        let verification = verify(&path).unwrap();
+        assert!(verification.errors.is_empty(), "{:?}", verification.errors);
+        assert_eq!(verification.warnings.len(), 2);
+        assert!(verification.warnings[1].contains("move_display:next -> move:display=next"));
+
+        // in-memory loading leaves the file alone
+        let config = load_base(Some(&path), false).unwrap();
+        assert_eq!(config.version, migrate::CURRENT_VERSION);
+        assert_eq!(config.shortcuts[0].action, "move:display=next");
+        assert_eq!(fs::read_to_string(&path).unwrap(), original);
+
+        // regular loading rewrites it and keeps a backup
+        load_base_with_override(Some(&path)).unwrap();
+        let migrated = fs::read_to_string(&path).unwrap();
+        assert!(migrated.contains("// no version: written before versioning"));
+        assert!(migrated.contains("\"move:display=next\""));
+        assert_eq!(
+            fs::read_to_string(dir.path().join("config.jsonc.v1.bak")).unwrap(),
+            original
+        );
+        assert!(verify(&path).unwrap().warnings.is_empty());
+    }
+
+    #[test]
+    fn test_load_leaves_old_yaml_alone() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.yaml");
+        let original =
+            "# next display\nshortcuts:\n  - keys: ctrl+alt+n\n    action: move_display:next\n";
+        fs::write(&path, original).unwrap();
+
+        // rewriting would drop the comment, so only `cwm config migrate` does
+        let config = load_base_with_override(Some(&path)).unwrap();
+        assert_eq!(config.shortcuts[0].action, "move:display=next");
+        assert_eq!(fs::read_to_string(&path).unwrap(), original);
+        assert!(!dir.path().join("config.yaml.v1.bak").exists());
+    }
+
+    #[test]
+    fn test_verify_newer_version() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.jsonc");
+        fs::write(&path, r#"{"version": 99}"#).unwrap();
+
+        let errors = verify(&path).unwrap().errors;
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0].starts_with("version: "));
+    }
+
     #[test]
     fn test_validate_action_valid() {
         assert!(validate_action("focus").is_ok());
@@ -715,6 +1587,20 @@ mod tests {
         assert!(validate_action("resize:1").is_ok());
         assert!(validate_action("resize:full").is_ok());
         assert!(validate_action("resize:FULL").is_ok());
+        assert!(validate_action("resize:50%w").is_ok());
+        assert!(validate_action("resize:33%h").is_ok());
+        assert!(validate_action("resize:50x100%").is_ok());
+        assert!(validate_action("resize:+5%w").is_ok());
+        assert!(validate_action("resize:-5%h").is_ok());
+    }
+
+    #[test]
+    fn test_action_examples_are_valid() {
+        for spec in ACTIONS {
+            for example in spec.examples {
+                assert!(validate_action(example).is_ok(), "{}", example);
+            }
+        }
     }
 
     #[test]
@@ -725,6 +1611,11 @@ mod tests {
         assert!(validate_action("resize:0").is_err());
         assert!(validate_action("resize:101").is_err());
         assert!(validate_action("resize:abc").is_err());
+        assert!(validate_action("resize:0%w").is_err());
+        assert!(validate_action("resize:50x101%").is_err());
+        assert!(validate_action("resize:1920px").is_err());
+        assert!(validate_action("resize:+0%w").is_err());
+        assert!(validate_action("resize:-5%").is_err());
     }
 
     // ========================================================================
@@ -861,7 +1752,7 @@ mod tests {
         }"#;
 
         std::fs::write(&path, config).unwrap();
-        let errors = verify(&path).unwrap();
+        let errors = verify(&path).unwrap().errors;
         std::fs::remove_file(&path).ok();
 
         assert!(errors.is_empty(), "expected no errors, got: {:?}", errors);
@@ -881,7 +1772,7 @@ mod tests {
         }"#;
 
         std::fs::write(&path, config).unwrap();
-        let errors = verify(&path).unwrap();
+        let errors = verify(&path).unwrap().errors;
         std::fs::remove_file(&path).ok();
 
         assert_eq!(errors.len(), 1);
@@ -903,7 +1794,7 @@ mod tests {
         }"#;
 
         std::fs::write(&path, config).unwrap();
-        let errors = verify(&path).unwrap();
+        let errors = verify(&path).unwrap().errors;
         std::fs::remove_file(&path).ok();
 
         assert_eq!(errors.len(), 1);
@@ -925,7 +1816,7 @@ mod tests {
         }"#;
 
         std::fs::write(&path, config).unwrap();
-        let errors = verify(&path).unwrap();
+        let errors = verify(&path).unwrap().errors;
         std::fs::remove_file(&path).ok();
 
         assert_eq!(errors.len(), 1);
@@ -948,7 +1839,7 @@ mod tests {
         }"#;
 
         std::fs::write(&path, config).unwrap();
-        let errors = verify(&path).unwrap();
+        let errors = verify(&path).unwrap().errors;
         std::fs::remove_file(&path).ok();
 
         assert_eq!(errors.len(), 3);
@@ -971,7 +1862,7 @@ mod tests {
         }"#;
 
         std::fs::write(&path, config).unwrap();
-        let errors = verify(&path).unwrap();
+        let errors = verify(&path).unwrap().errors;
         std::fs::remove_file(&path).ok();
 
         assert_eq!(errors.len(), 1);
@@ -992,7 +1883,7 @@ mod tests {
         }"#;
 
         std::fs::write(&path, config).unwrap();
-        let errors = verify(&path).unwrap();
+        let errors = verify(&path).unwrap().errors;
         std::fs::remove_file(&path).ok();
 
         assert_eq!(errors.len(), 1);
@@ -1018,12 +1909,200 @@ mod tests {
         }"#;
 
         std::fs::write(&path, config).unwrap();
-        let errors = verify(&path).unwrap();
+        let errors = verify(&path).unwrap().errors;
         std::fs::remove_file(&path).ok();
 
         assert_eq!(errors.len(), 4);
     }
 
+    #[test]
+    fn test_verify_app_scopes() {
+        let dir = std::env::temp_dir();
+        let path = dir.join("cwm_test_app_scopes.json");
+
+        let config = r#"{
+            "shortcuts": [
+                {"keys": "cmd+shift+m", "action": "maximize", "only_in": ["Safari"]},
+                {"keys": "cmd+shift+r", "action": "resize:80", "except_in": ["Xcode", " "]},
+                {"keys": "cmd+shift+f", "action": "maximize", "only_in": ["Safari"], "except_in": ["Xcode"]}
+            ],
+            "settings": {"disabled_in": ["Steam", ""]}
+        }"#;
+
+        std::fs::write(&path, config).unwrap();
+        let errors = verify(&path).unwrap().errors;
+        std::fs::remove_file(&path).ok();
+
+        assert_eq!(errors.len(), 3, "{:?}", errors);
+        assert!(errors[0].starts_with("shortcuts[1].except_in[1]: app name cannot be empty"));
+        assert!(
+            errors[1].starts_with("shortcuts[2]: set either 'only_in' or 'except_in', not both")
+        );
+        assert!(errors[2].starts_with("settings.disabled_in[1]: app name cannot be empty"));
+    }
+
+    #[test]
+    fn test_verify_keyboard_layout() {
+        let dir = std::env::temp_dir();
+        let path = dir.join("cwm_test_keyboard_layout.json");
+
+        let config = r#"{
+            "shortcuts": [
+                {"keys": "ctrl+alt+key:KeyZ", "action": "maximize"},
+                {"keys": "ctrl+alt+key:Nope", "action": "maximize"}
+            ],
+            "settings": {"keyboard_layout": "colemak"}
+        }"#;
+
+        std::fs::write(&path, config).unwrap();
+        let errors = verify(&path).unwrap().errors;
+        std::fs::remove_file(&path).ok();
+
+        assert_eq!(errors.len(), 2, "{:?}", errors);
+        assert!(errors[0].starts_with(
+            "settings.keyboard_layout: unknown keyboard layout 'colemak', expected: us, de, fr, dvorak"
+        ));
+        assert!(
+            errors[1].contains("unknown physical key 'key:nope'"),
+            "{}",
+            errors[1]
+        );
+    }
+
+    #[test]
+    fn test_verify_cycle() {
+        let dir = std::env::temp_dir();
+        let path = dir.join("cwm_test_cycle.json");
+
+        let config = r#"{
+            "shortcuts": [
+                {"keys": "ctrl+alt+left", "cycle": ["move:left;resize:50", "move:top-left;display=next;resize:33", "move:prev"]},
+                {"keys": "ctrl+alt+right", "action": "maximize", "cycle": ["maximize"]},
+                {"keys": "ctrl+alt+up", "cycle": ["maximize;explode", "focus"]},
+                {"keys": "ctrl+alt+down"}
+            ]
+        }"#;
+
+        std::fs::write(&path, config).unwrap();
+        let errors = verify(&path).unwrap().errors;
+        std::fs::remove_file(&path).ok();
+
+        assert_eq!(errors.len(), 4, "{:?}", errors);
+        assert!(errors[0].starts_with("shortcuts[1]: set either 'action' or 'cycle', not both"));
+        // `explode` is no action name, so it is read as part of `maximize`
+        assert!(errors[1].starts_with("shortcuts[2].cycle[0]: invalid action 'maximize;explode'"));
+        assert!(errors[2].starts_with("shortcuts[2].cycle[1]: action 'focus' requires 'app' field"));
+        assert!(errors[3].starts_with("shortcuts[3]: set either 'action' or 'cycle'"));
+    }
+
+    #[test]
+    fn test_verify_triggers() {
+        let dir = std::env::temp_dir();
+        let path = dir.join("cwm_test_triggers.json");
+
+        let config = r#"{
+            "shortcuts": [
+                {"keys": "rshift", "action": "focus", "app": "Terminal", "trigger": "double_tap"},
+                {"keys": "ctrl+alt+space", "action": "maximize", "trigger": "hold", "trigger_ms": 400, "release_action": "resize:50"},
+                {"keys": "rshift", "action": "maximize"},
+                {"keys": "ctrl+alt+w, h", "action": "maximize", "trigger": "hold"},
+                {"keys": "ctrl+alt+m", "action": "maximize", "trigger": "double_tap", "release_action": "resize:50"},
+                {"keys": "ctrl+alt+n", "action": "maximize", "trigger": "hold", "trigger_ms": 0, "release_action": "bad"},
+                {"keys": "ctrl+alt+b", "action": "maximize", "trigger_ms": 200},
+                {"keys": "ctrl+alt+l", "action": "resize:80", "repeat": "throttle:200ms"},
+                {"keys": "ctrl+alt+k", "action": "maximize", "trigger": "hold", "repeat": "allow"},
+                {"keys": "ctrl+alt+j", "action": "maximize", "repeat": "always"}
+            ]
+        }"#;
+
+        std::fs::write(&path, config).unwrap();
+        let errors = verify(&path).unwrap().errors;
+        std::fs::remove_file(&path).ok();
+
+        assert_eq!(errors.len(), 8, "{:?}", errors);
+        assert!(errors[0]
+            .starts_with("shortcuts[2]: a lone modifier needs trigger 'double_tap' or 'hold'"));
+        assert!(errors[1].starts_with("shortcuts[3]: invalid keys 'ctrl+alt+w, h'"));
+        assert!(errors[2].starts_with("shortcuts[4]: release_action needs trigger 'hold'"));
+        assert!(errors[3].starts_with("shortcuts[5].trigger_ms: must be at least 1"));
+        assert!(errors[4].starts_with("shortcuts[5].release_action: invalid action"));
+        assert!(
+            errors[5].starts_with("shortcuts[6]: trigger_ms needs trigger 'double_tap' or 'hold'")
+        );
+        assert!(errors[6].starts_with(
+            "shortcuts[8]: repeat cannot be used with trigger 'double_tap' or 'hold'"
+        ));
+        assert!(errors[7].starts_with("shortcuts[9].repeat: Invalid repeat 'always'"));
+    }
+
+    #[test]
+    fn test_verify_modes() {
+        let dir = std::env::temp_dir();
+        let path = dir.join("cwm_test_modes.json");
+
+        let config = r#"{
+            "modes": {
+                "resize": {
+                    "enter": "ctrl+alt+r",
+                    "bindings": {"h": "resize:50", "f": "resize:full", "left": "move:-50,+0"},
+                    "timeout_ms": 3000
+                },
+                "broken": {
+                    "enter": "ctrl+alt",
+                    "exit": "escape, q",
+                    "bindings": {"a, b": "maximize", "s": "focus", "x": "bad"},
+                    "timeout_ms": 0
+                }
+            }
+        }"#;
+
+        std::fs::write(&path, config).unwrap();
+        let errors = verify(&path).unwrap().errors;
+        std::fs::remove_file(&path).ok();
+
+        assert_eq!(errors.len(), 6, "{:?}", errors);
+        assert!(errors.iter().all(|e| e.contains("modes.broken")));
+        assert!(errors[0].starts_with("modes.broken.enter: invalid keys"));
+        assert!(errors[1].starts_with("modes.broken.exit: invalid keys"));
+        assert!(errors[2].starts_with("modes.broken.timeout_ms"));
+        assert!(errors[3].starts_with("modes.broken.bindings.a, b: invalid keys"));
+        assert!(errors[4].contains("'focus' requires an app"));
+        assert!(errors[5].starts_with("modes.broken.bindings.x:"));
+    }
+
+    #[test]
+    fn test_verify_mouse_bindings() {
+        let dir = std::env::temp_dir();
+        let path = dir.join("cwm_test_mouse_bindings.json");
+
+        let config = r#"{
+            "mouse_bindings": [
+                {"mouse": "ctrl+alt+scroll-up", "action": "resize:80"},
+                {"mouse": "cmd+ctrl+button3", "action": "maximize", "window": "under_cursor"},
+                {"mouse": "ctrl+alt+left", "drag": "move"},
+                {"mouse": "cmd+alt+right", "action": "focus", "window": "under_cursor"},
+                {"mouse": "button1", "drag": "resize"},
+                {"mouse": "ctrl+scroll-down", "drag": "move"},
+                {"mouse": "ctrl+middle", "action": "maximize", "drag": "move"},
+                {"mouse": "ctrl+right"},
+                {"mouse": "alt+left", "action": "focus"},
+                {"mouse": "alt+right", "action": "explode"}
+            ]
+        }"#;
+
+        std::fs::write(&path, config).unwrap();
+        let errors = verify(&path).unwrap().errors;
+        std::fs::remove_file(&path).ok();
+
+        assert_eq!(errors.len(), 6, "{:?}", errors);
+        assert!(errors[0].starts_with("mouse_bindings[4]: invalid mouse binding 'button1'"));
+        assert!(errors[1].starts_with("mouse_bindings[5]: 'drag' needs a mouse button"));
+        assert!(errors[2].starts_with("mouse_bindings[6]: set either 'action' or 'drag', not both"));
+        assert!(errors[3].starts_with("mouse_bindings[7]: set either 'action' or 'drag'"));
+        assert!(errors[4].starts_with("mouse_bindings[8]: action 'focus' requires window"));
+        assert!(errors[5].starts_with("mouse_bindings[9]:"));
+    }
+
     #[test]
     fn test_verify_file_not_found() {
         let path = PathBuf::from("/nonexistent/path/config.json");
@@ -1046,6 +2125,69 @@ mod tests {
         assert!(result.unwrap_err().to_string().contains("invalid JSON"));
     }
 
+    #[test]
+    fn test_load_with_includes() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            dir.path().join("team.json"),
+            r#"{"shortcuts": [{"keys": "ctrl+alt+m", "action": "maximize"}], "settings": {"fuzzy_threshold": 5}}"#,
+        )
+        .unwrap();
+        let path = dir.path().join("config.json");
+        std::fs::write(
+            &path,
+            r#"{"include": ["team.json"], "shortcuts": [{"keys": "ctrl+alt+c", "action": "move:center"}]}"#,
+        )
+        .unwrap();
+
+        let config = load_with_override(Some(&path)).unwrap();
+        assert_eq!(config.shortcuts.len(), 2);
+        assert_eq!(config.settings.fuzzy_threshold, 5);
+
+        let main = load_main_with_override(Some(&path)).unwrap();
+        assert_eq!(main.include, vec!["team.json".to_string()]);
+        assert_eq!(main.shortcuts.len(), 1);
+    }
+
+    #[test]
+    fn test_verify_profiles() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.json");
+        std::fs::write(
+            &path,
+            r#"{
+                "profiles": {
+                    "presenting": {
+                        "shortcuts": [{ "keys": "ctrl+alt+p", "action": "explode" }],
+                        "settings": { "fuzzy_threshold": "high" }
+                    }
+                }
+            }"#,
+        )
+        .unwrap();
+
+        let errors = verify(&path).unwrap().errors;
+        assert_eq!(errors.len(), 2);
+        assert!(errors[0].starts_with("profiles.presenting.shortcuts[0]: invalid action"));
+        assert!(errors[1].starts_with("profiles.presenting: invalid profile"));
+    }
+
+    #[test]
+    fn test_verify_include_cycle() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.json");
+        std::fs::write(&path, r#"{"include": ["other.json"]}"#).unwrap();
+        std::fs::write(
+            dir.path().join("other.json"),
+            r#"{"include": ["config.json"]}"#,
+        )
+        .unwrap();
+
+        let errors = verify(&path).unwrap().errors;
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0].contains("include cycle"));
+    }
+
     #[test]
     fn test_verify_valid_spotlight() {
         let dir = std::env::temp_dir();
@@ -1064,7 +2206,7 @@ mod tests {
         }"#;
 
         std::fs::write(&path, config).unwrap();
-        let errors = verify(&path).unwrap();
+        let errors = verify(&path).unwrap().errors;
         std::fs::remove_file(&path).ok();
 
         assert!(errors.is_empty(), "expected no errors, got: {:?}", errors);
@@ -1085,7 +2227,7 @@ mod tests {
         }"#;
 
         std::fs::write(&path, config).unwrap();
-        let errors = verify(&path).unwrap();
+        let errors = verify(&path).unwrap().errors;
         std::fs::remove_file(&path).ok();
 
         assert_eq!(errors.len(), 1);
@@ -1107,7 +2249,7 @@ mod tests {
         }"#;
 
         std::fs::write(&path, config).unwrap();
-        let errors = verify(&path).unwrap();
+        let errors = verify(&path).unwrap().errors;
         std::fs::remove_file(&path).ok();
 
         assert_eq!(errors.len(), 1);
@@ -1129,7 +2271,7 @@ mod tests {
         }"#;
 
         std::fs::write(&path, config).unwrap();
-        let errors = verify(&path).unwrap();
+        let errors = verify(&path).unwrap().errors;
         std::fs::remove_file(&path).ok();
 
         assert_eq!(errors.len(), 1);
@@ -1153,7 +2295,7 @@ mod tests {
         }"#;
 
         std::fs::write(&path, config).unwrap();
-        let errors = verify(&path).unwrap();
+        let errors = verify(&path).unwrap().errors;
         std::fs::remove_file(&path).ok();
 
         assert_eq!(errors.len(), 3);
@@ -1174,7 +2316,7 @@ mod tests {
         }"#;
 
         std::fs::write(&path, config).unwrap();
-        let errors = verify(&path).unwrap();
+        let errors = verify(&path).unwrap().errors;
         std::fs::remove_file(&path).ok();
 
         assert_eq!(errors.len(), 1);
@@ -1198,7 +2340,7 @@ mod tests {
         }"#;
 
         std::fs::write(&path, config).unwrap();
-        let errors = verify(&path).unwrap();
+        let errors = verify(&path).unwrap().errors;
         std::fs::remove_file(&path).ok();
 
         assert_eq!(errors.len(), 2);
@@ -1298,7 +2440,7 @@ mod tests {
         }"#;
 
         std::fs::write(&path, config).unwrap();
-        let errors = verify(&path).unwrap();
+        let errors = verify(&path).unwrap().errors;
         std::fs::remove_file(&path).ok();
 
         assert!(errors.is_empty(), "expected no errors, got: {:?}", errors);
@@ -1435,6 +2577,199 @@ mod tests {
         assert!(config.settings.launch);
     }
 
+    #[test]
+    fn test_load_yaml_and_toml() {
+        let dir = tempfile::tempdir().unwrap();
+        let json = dir.path().join("config.json");
+        let yaml = dir.path().join("config.yaml");
+        let toml = dir.path().join("config.toml");
+        std::fs::write(
+            &json,
+            r#"{"shortcuts": [{"keys": "ctrl+alt+m", "action": "maximize"}], "settings": {"fuzzy_threshold": 5}}"#,
+        )
+        .unwrap();
+        std::fs::write(
+            &yaml,
+            "shortcuts:\n  - keys: ctrl+alt+m\n    action: maximize\nsettings:\n  fuzzy_threshold: 5\n",
+        )
+        .unwrap();
+        std::fs::write(
+            &toml,
+            "[[shortcuts]]\nkeys = \"ctrl+alt+m\"\naction = \"maximize\"\n\n[settings]\nfuzzy_threshold = 5\n",
+        )
+        .unwrap();
+
+        let expected = serde_json::to_value(load_with_override(Some(&json)).unwrap()).unwrap();
+        for path in [&yaml, &toml] {
+            let config = load_with_override(Some(path)).unwrap();
+            assert_eq!(serde_json::to_value(config).unwrap(), expected);
+        }
+    }
+
+    #[test]
+    fn test_save_keeps_file_format() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.yaml");
+        std::fs::write(&path, "settings:\n  fuzzy_threshold: 5\n").unwrap();
+
+        let mut config = load_with_override(Some(&path)).unwrap();
+        config.settings.fuzzy_threshold = 7;
+        save_with_override(&config, Some(&path)).unwrap();
+
+        let text = std::fs::read_to_string(&path).unwrap();
+        let value = Format::Yaml.parse(&text).unwrap();
+        assert_eq!(value["settings"]["fuzzy_threshold"], 7);
+        // defaults that weren't in the file are not written out
+        assert!(value.get("shortcuts").is_none());
+    }
+
+    #[test]
+    fn test_convert_writes_new_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.json");
+        std::fs::write(
+            &path,
+            r#"{
+                // comments are not carried over
+                "shortcuts": [{"keys": "ctrl+alt+m", "action": "maximize"}]
+            }"#,
+        )
+        .unwrap();
+        let before = serde_json::to_value(load_with_override(Some(&path)).unwrap()).unwrap();
+
+        let conversion = convert(&path, Format::Toml).unwrap();
+        assert_eq!(conversion.to, dir.path().join("config.toml"));
+        let backup = conversion.apply().unwrap();
+
+        assert!(!path.exists());
+        assert_eq!(backup, dir.path().join("config.json.bak"));
+        assert_eq!(
+            find_config_file(dir.path()).unwrap(),
+            dir.path().join("config.toml")
+        );
+        let after = load_with_override(Some(&conversion.to)).unwrap();
+        assert_eq!(serde_json::to_value(after).unwrap(), before);
+
+        // converting again to the same target refuses to overwrite it
+        std::fs::copy(&backup, &path).unwrap();
+        assert!(convert(&path, Format::Toml).unwrap().apply().is_err());
+    }
+
+    #[test]
+    fn test_vars_are_expanded_on_load_and_kept_on_save() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.json");
+        std::fs::write(
+            &path,
+            r#"{
+                "vars": { "editor": "Visual Studio Code" },
+                "shortcuts": [{ "keys": "ctrl+alt+e", "action": "focus", "app": "${editor}" }]
+            }"#,
+        )
+        .unwrap();
+
+        let mut config = load_main_with_override(Some(&path)).unwrap();
+        assert_eq!(
+            config.shortcuts[0].app.as_deref(),
+            Some("Visual Studio Code")
+        );
+
+        config.settings.fuzzy_threshold = 4;
+        save_with_override(&config, Some(&path)).unwrap();
+        let saved = std::fs::read_to_string(&path).unwrap();
+        assert!(saved.contains(r#""app": "${editor}""#));
+        assert!(saved.contains(r#""fuzzy_threshold": 4"#));
+    }
+
+    #[test]
+    fn test_vars_from_included_files() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("team.json"), r#"{"vars": {"big": "80"}}"#).unwrap();
+        let path = dir.path().join("config.json");
+        std::fs::write(
+            &path,
+            r#"{"include": ["team.json"], "shortcuts": [{"keys": "ctrl+alt+b", "action": "resize:${big}"}]}"#,
+        )
+        .unwrap();
+
+        let config = load_with_override(Some(&path)).unwrap();
+        assert_eq!(config.shortcuts[0].action, "resize:80");
+        // the main file alone leaves the reference for the merged config to resolve
+        let main = load_main_with_override(Some(&path)).unwrap();
+        assert_eq!(main.shortcuts[0].action, "resize:${big}");
+        assert_eq!(verify(&path).unwrap().errors, Vec::<String>::new());
+    }
+
+    #[test]
+    fn test_verify_expands_vars() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.yaml");
+        std::fs::write(
+            &path,
+            "vars:\n  big: huge\nshortcuts:\n  - keys: ctrl+alt+b\n    action: resize:${big}\n  - keys: ctrl+alt+e\n    action: focus\n    app: ${editor}\n",
+        )
+        .unwrap();
+
+        let errors = verify(&path).unwrap().errors;
+        assert_eq!(errors.len(), 2, "{:?}", errors);
+        assert_eq!(
+            errors[0],
+            "shortcuts[1].app: undefined variable 'editor' (line 8, column 5)"
+        );
+        assert_eq!(
+            errors[1],
+            "shortcuts[0]: invalid resize size 'huge': use a number 1-100, 'full', one side like 50%w, both like 50x100% or a change like +5%w (line 4, column 5)"
+        );
+
+        assert!(load_with_override(Some(&path))
+            .unwrap_err()
+            .chain()
+            .any(|e| e.to_string().contains("undefined variable 'editor'")));
+    }
+
+    #[test]
+    fn test_find_config_file() {
+        let dir = tempfile::tempdir().unwrap();
+        assert_eq!(
+            find_config_file(dir.path()).unwrap(),
+            dir.path().join("config.json")
+        );
+
+        std::fs::write(dir.path().join("config.yml"), "{}").unwrap();
+        assert_eq!(
+            find_config_file(dir.path()).unwrap(),
+            dir.path().join("config.yml")
+        );
+
+        std::fs::write(dir.path().join("config.toml"), "").unwrap();
+        let err = find_config_file(dir.path()).unwrap_err().to_string();
+        assert!(err.contains("config.yml, config.toml"));
+    }
+
+    #[test]
+    fn test_verify_reports_locations() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.yaml");
+        std::fs::write(
+            &path,
+            "shortcuts:\n  - keys: ctrl+alt+m\n    action: maximize\n  - keys: ctrl+alt+x\n    action: explode\n",
+        )
+        .unwrap();
+
+        let errors = verify(&path).unwrap().errors;
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0].starts_with("shortcuts[1]: invalid action"));
+        assert!(errors[0].ends_with("(line 4, column 5)"));
+
+        let path = dir.path().join("config.toml");
+        std::fs::write(&path, "[settings]\nlaunch = \"yes\"\n").unwrap();
+
+        let errors = verify(&path).unwrap().errors;
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0].starts_with("settings.launch: invalid type"));
+        assert!(errors[0].ends_with("(line 2, column 1)"));
+    }
+
     #[test]
     fn test_save_with_override_saves_to_custom_path() {
         let dir = std::env::temp_dir();
@@ -1492,9 +2827,14 @@ mod tests {
 
         let content = std::fs::read_to_string(&path).unwrap();
         std::fs::remove_file(&path).ok();
+        // the file could not be edited in place, so the original is kept
+        let backup = dir.join("cwm_test_save_overwrite.json.bak");
+        let backup_content = std::fs::read_to_string(&backup).unwrap();
+        std::fs::remove_file(&backup).ok();
 
         assert!(content.contains("\"fuzzy_threshold\": 77"));
         assert!(!content.contains("old content"));
+        assert_eq!(backup_content, "old content");
     }
 
     #[test]
@@ -1512,6 +2852,7 @@ mod tests {
             app: Some("Terminal".to_string()),
             launch: Some(true),
             when: None,
+            ..Default::default()
         });
 
         // save it
@@ -0,0 +1,438 @@
+//! JSON paths into the config, used by `cwm config get/set/unset`
+//!
+//! syntax:
+//! - `settings.history.limit` - object keys separated by dots
+//! - `shortcuts[2]`, `shortcuts[-1]` - array index (negative counts from the end)
+//! - `display_aliases.office[+]` - append to an array (set only)
+//! - `app_rules[app=Slack]` - the array entry whose field matches (case-insensitive)
+//! - `display_aliases["desk.left"]` - an object key that contains dots
+//!
+//! dots inside `[...]` are part of the selector, e.g. `app_rules[app=com.apple.Safari]`
+
+use anyhow::{anyhow, Result};
+use serde_json::{Map, Value};
+use std::fmt;
+
+/// one step of a config path
+#[derive(Debug, Clone, PartialEq)]
+pub enum Segment {
+    Key(String),
+    Index(i64),
+    Append,
+    Match { field: String, value: String },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConfigPath {
+    raw: String,
+    segments: Vec<Segment>,
+}
+
+impl fmt::Display for ConfigPath {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.raw)
+    }
+}
+
+impl ConfigPath {
+    pub fn parse(raw: &str) -> Result<Self> {
+        let mut segments = Vec::new();
+
+        for part in split_parts(raw) {
+            let (key, mut selectors) = match part.find('[') {
+                Some(idx) => (&part[..idx], &part[idx..]),
+                None => (part, ""),
+            };
+
+            if key.is_empty() {
+                return Err(anyhow!("invalid path '{}': empty key", raw));
+            }
+            segments.push(Segment::Key(key.to_string()));
+
+            while !selectors.is_empty() {
+                let end = selectors
+                    .find(']')
+                    .ok_or_else(|| anyhow!("invalid path '{}': missing ']'", raw))?;
+                if !selectors.starts_with('[') {
+                    return Err(anyhow!("invalid path '{}': expected '['", raw));
+                }
+                segments.push(parse_selector(&selectors[1..end], raw)?);
+                selectors = &selectors[end + 1..];
+            }
+        }
+
+        if segments[..segments.len() - 1].contains(&Segment::Append) {
+            return Err(anyhow!(
+                "invalid path '{}': '[+]' can only be the last segment",
+                raw
+            ));
+        }
+
+        Ok(Self {
+            raw: raw.to_string(),
+            segments,
+        })
+    }
+
+    pub fn segments(&self) -> &[Segment] {
+        &self.segments
+    }
+}
+
+/// splits `raw` on the dots outside `[...]`
+fn split_parts(raw: &str) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut start = 0;
+    let mut in_selector = false;
+    for (i, c) in raw.char_indices() {
+        match c {
+            '[' => in_selector = true,
+            ']' => in_selector = false,
+            '.' if !in_selector => {
+                parts.push(&raw[start..i]);
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    parts.push(&raw[start..]);
+    parts
+}
+
+fn parse_selector(selector: &str, raw: &str) -> Result<Segment> {
+    if selector == "+" {
+        return Ok(Segment::Append);
+    }
+
+    if let Some(key) = selector
+        .strip_prefix('"')
+        .and_then(|rest| rest.strip_suffix('"'))
+    {
+        return Ok(Segment::Key(key.to_string()));
+    }
+
+    if let Some((field, value)) = selector.split_once('=') {
+        if field.is_empty() {
+            return Err(anyhow!(
+                "invalid path '{}': empty field in [{}]",
+                raw,
+                selector
+            ));
+        }
+        return Ok(Segment::Match {
+            field: field.to_string(),
+            value: value.to_string(),
+        });
+    }
+
+    selector.parse::<i64>().map(Segment::Index).map_err(|_| {
+        anyhow!(
+            "invalid path '{}': expected [index], [+], [field=value] or [\"key\"], got [{}]",
+            raw,
+            selector
+        )
+    })
+}
+
+/// resolve an index or match selector to a position in `items`
+fn find_index(items: &[Value], segment: &Segment, path: &ConfigPath) -> Result<usize> {
+    match segment {
+        Segment::Index(i) => {
+            let len = items.len() as i64;
+            let idx = if *i < 0 { len + i } else { *i };
+            if idx < 0 || idx >= len {
+                return Err(anyhow!(
+                    "{}: index {} out of range ({} entries)",
+                    path,
+                    i,
+                    items.len()
+                ));
+            }
+            Ok(idx as usize)
+        }
+        Segment::Match { field, value } => {
+            let matches: Vec<usize> = items
+                .iter()
+                .enumerate()
+                .filter(|(_, item)| {
+                    item.get(field)
+                        .and_then(|v| v.as_str())
+                        .is_some_and(|v| v.eq_ignore_ascii_case(value))
+                })
+                .map(|(i, _)| i)
+                .collect();
+
+            match matches.as_slice() {
+                [idx] => Ok(*idx),
+                [] => Err(anyhow!("{}: no entry with {} = '{}'", path, field, value)),
+                _ => Err(anyhow!(
+                    "{}: {} entries have {} = '{}', use an index instead",
+                    path,
+                    matches.len(),
+                    field,
+                    value
+                )),
+            }
+        }
+        _ => Err(anyhow!("{}: expected an array selector", path)),
+    }
+}
+
+/// read the value at `path`
+pub fn get<'a>(root: &'a Value, path: &ConfigPath) -> Result<&'a Value> {
+    let mut current = root;
+
+    for segment in &path.segments {
+        current = match (segment, current) {
+            (Segment::Key(key), Value::Object(map)) => map
+                .get(key)
+                .ok_or_else(|| anyhow!("{}: '{}' is not set", path, key))?,
+            (Segment::Append, _) => {
+                return Err(anyhow!("{}: '[+]' can only be used with set", path))
+            }
+            (Segment::Key(key), _) => {
+                return Err(anyhow!("{}: cannot read '{}' of a non-object", path, key))
+            }
+            (segment, Value::Array(items)) => &items[find_index(items, segment, path)?],
+            (_, _) => return Err(anyhow!("{}: not an array", path)),
+        };
+    }
+
+    Ok(current)
+}
+
+/// set the value at `path`, creating missing objects along the way
+pub fn set(root: &mut Value, path: &ConfigPath, value: Value) -> Result<()> {
+    let (last, parents) = path
+        .segments
+        .split_last()
+        .ok_or_else(|| anyhow!("empty path"))?;
+    let parent = walk_mut(root, parents, path, true)?;
+
+    match (last, parent) {
+        (Segment::Key(key), Value::Object(map)) => {
+            map.insert(key.clone(), value);
+        }
+        (Segment::Append, Value::Array(items)) => items.push(value),
+        (Segment::Key(key), _) => {
+            return Err(anyhow!("{}: cannot set '{}' on a non-object", path, key))
+        }
+        (segment, Value::Array(items)) => {
+            let idx = find_index(items, segment, path)?;
+            items[idx] = value;
+        }
+        (_, _) => return Err(anyhow!("{}: not an array", path)),
+    }
+
+    Ok(())
+}
+
+/// remove the value at `path`, returning it
+pub fn unset(root: &mut Value, path: &ConfigPath) -> Result<Value> {
+    let (last, parents) = path
+        .segments
+        .split_last()
+        .ok_or_else(|| anyhow!("empty path"))?;
+    let parent = walk_mut(root, parents, path, false)?;
+
+    match (last, parent) {
+        (Segment::Key(key), Value::Object(map)) => map
+            .remove(key)
+            .ok_or_else(|| anyhow!("{}: '{}' is not set", path, key)),
+        (Segment::Append, _) => Err(anyhow!("{}: '[+]' can only be used with set", path)),
+        (Segment::Key(key), _) => Err(anyhow!("{}: cannot remove '{}' of a non-object", path, key)),
+        (segment, Value::Array(items)) => {
+            let idx = find_index(items, segment, path)?;
+            Ok(items.remove(idx))
+        }
+        (_, _) => Err(anyhow!("{}: not an array", path)),
+    }
+}
+
+fn walk_mut<'a>(
+    root: &'a mut Value,
+    segments: &[Segment],
+    path: &ConfigPath,
+    create: bool,
+) -> Result<&'a mut Value> {
+    let mut current = root;
+
+    for (i, segment) in segments.iter().enumerate() {
+        current = match (segment, current) {
+            (Segment::Key(key), Value::Object(map)) => {
+                if !map.contains_key(key) {
+                    if !create {
+                        return Err(anyhow!("{}: '{}' is not set", path, key));
+                    }
+                    // the next segment decides whether the new container is an array
+                    let container = match path.segments.get(i + 1) {
+                        Some(Segment::Key(_)) | None => Value::Object(Map::new()),
+                        Some(_) => Value::Array(Vec::new()),
+                    };
+                    map.insert(key.clone(), container);
+                }
+                map.get_mut(key).expect("inserted above")
+            }
+            (Segment::Key(key), _) => {
+                return Err(anyhow!("{}: cannot read '{}' of a non-object", path, key))
+            }
+            (segment, Value::Array(items)) => {
+                let idx = find_index(items, segment, path)?;
+                &mut items[idx]
+            }
+            (_, _) => return Err(anyhow!("{}: not an array", path)),
+        };
+    }
+
+    Ok(current)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn path(s: &str) -> ConfigPath {
+        ConfigPath::parse(s).unwrap()
+    }
+
+    fn sample() -> Value {
+        json!({
+            "shortcuts": [
+                { "keys": "ctrl+alt+s", "action": "focus", "app": "Slack" },
+                { "keys": "ctrl+alt+m", "action": "maximize" }
+            ],
+            "app_rules": [{ "app": "Slack", "action": "maximize" }],
+            "display_aliases": { "office": ["A"] },
+            "settings": { "history": { "limit": 50 } }
+        })
+    }
+
+    #[test]
+    fn test_parse_path() {
+        assert_eq!(
+            path("app_rules[app=Slack].delay_ms").segments(),
+            &[
+                Segment::Key("app_rules".to_string()),
+                Segment::Match {
+                    field: "app".to_string(),
+                    value: "Slack".to_string()
+                },
+                Segment::Key("delay_ms".to_string()),
+            ]
+        );
+        assert_eq!(
+            path("display_aliases.office[+]").segments().last(),
+            Some(&Segment::Append)
+        );
+        assert_eq!(path("shortcuts[-1]").segments()[1], Segment::Index(-1));
+    }
+
+    #[test]
+    fn test_parse_path_with_dots_in_selectors() {
+        assert_eq!(
+            path("app_rules[app=com.apple.Safari].action").segments(),
+            &[
+                Segment::Key("app_rules".to_string()),
+                Segment::Match {
+                    field: "app".to_string(),
+                    value: "com.apple.Safari".to_string()
+                },
+                Segment::Key("action".to_string()),
+            ]
+        );
+        assert_eq!(
+            path(r#"display_aliases["desk.left"][0]"#).segments(),
+            &[
+                Segment::Key("display_aliases".to_string()),
+                Segment::Key("desk.left".to_string()),
+                Segment::Index(0),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_invalid_path() {
+        assert!(ConfigPath::parse("").is_err());
+        assert!(ConfigPath::parse("settings..launch").is_err());
+        assert!(ConfigPath::parse("shortcuts[2").is_err());
+        assert!(ConfigPath::parse("shortcuts[x]").is_err());
+        assert!(ConfigPath::parse("shortcuts[+].keys").is_err());
+    }
+
+    #[test]
+    fn test_get() {
+        let root = sample();
+        assert_eq!(
+            get(&root, &path("shortcuts[1].action")).unwrap(),
+            "maximize"
+        );
+        assert_eq!(
+            get(&root, &path("shortcuts[-1].keys")).unwrap(),
+            "ctrl+alt+m"
+        );
+        assert_eq!(
+            get(&root, &path("app_rules[app=slack].action")).unwrap(),
+            "maximize"
+        );
+        assert_eq!(get(&root, &path("settings.history.limit")).unwrap(), 50);
+
+        assert!(get(&root, &path("shortcuts[5]")).is_err());
+        assert!(get(&root, &path("app_rules[app=Mail]")).is_err());
+        assert!(get(&root, &path("settings.nope")).is_err());
+    }
+
+    #[test]
+    fn test_set() {
+        let mut root = sample();
+
+        set(
+            &mut root,
+            &path("app_rules[app=Slack].delay_ms"),
+            json!(200),
+        )
+        .unwrap();
+        assert_eq!(root["app_rules"][0]["delay_ms"], 200);
+
+        set(&mut root, &path("display_aliases.office[+]"), json!("B")).unwrap();
+        assert_eq!(root["display_aliases"]["office"], json!(["A", "B"]));
+
+        set(&mut root, &path("display_aliases.home[+]"), json!("C")).unwrap();
+        assert_eq!(root["display_aliases"]["home"], json!(["C"]));
+
+        set(
+            &mut root,
+            &path("shortcuts[0].when"),
+            json!({ "profile": "x" }),
+        )
+        .unwrap();
+        assert_eq!(root["shortcuts"][0]["when"]["profile"], "x");
+
+        set(&mut root, &path("settings.retry.count"), json!(3)).unwrap();
+        assert_eq!(root["settings"]["retry"]["count"], 3);
+    }
+
+    #[test]
+    fn test_set_ambiguous_match() {
+        let mut root = json!({ "app_rules": [{ "app": "Slack" }, { "app": "slack" }] });
+        let err = set(&mut root, &path("app_rules[app=Slack].delay_ms"), json!(1))
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("2 entries"));
+    }
+
+    #[test]
+    fn test_unset() {
+        let mut root = sample();
+
+        let removed = unset(&mut root, &path("shortcuts[0]")).unwrap();
+        assert_eq!(removed["app"], "Slack");
+        assert_eq!(root["shortcuts"].as_array().unwrap().len(), 1);
+
+        unset(&mut root, &path("settings.history.limit")).unwrap();
+        assert!(root["settings"]["history"].get("limit").is_none());
+
+        assert!(unset(&mut root, &path("settings.history.limit")).is_err());
+        assert!(unset(&mut root, &path("nope.deeper")).is_err());
+    }
+}
@@ -0,0 +1,203 @@
+//! named config profiles
+//!
+//! a profile overrides a subset of the base config (shortcuts, app rules and
+//! settings) using the same merge rules as included files. the active profile
+//! is stored in the runtime directory (`~/.cwm/profile` by default) so it
+//! survives daemon restarts, and only applies to the default config file
+
+use anyhow::{anyhow, Context, Result};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use super::include;
+use super::Config;
+use crate::daemon::ipc;
+
+const ACTIVE_PROFILE_FILE: &str = "profile";
+
+/// returns the config with profile `name` applied
+pub fn apply(config: &Config, name: &str) -> Result<Config> {
+    let profile = config.profiles.get(name).ok_or_else(|| {
+        anyhow!(
+            "unknown profile '{}', available: {}",
+            name,
+            names(config).join(", ")
+        )
+    })?;
+
+    let base = serde_json::to_value(config).context("failed to serialize config")?;
+    let overlay = serde_json::to_value(profile).context("failed to serialize profile")?;
+    let merged = include::merge_overlay(base, overlay)?;
+
+    serde_json::from_value(merged).with_context(|| format!("invalid profile '{}'", name))
+}
+
+/// returns the config with the given profile applied, or the base config for None
+pub fn effective(config: &Config, name: Option<&str>) -> Result<Config> {
+    match name {
+        Some(name) => apply(config, name),
+        None => Ok(config.clone()),
+    }
+}
+
+/// profile names, sorted
+pub fn names(config: &Config) -> Vec<String> {
+    let mut names: Vec<String> = config.profiles.keys().cloned().collect();
+    names.sort();
+    names
+}
+
+/// next to the daemon's socket, so a daemon in `CWM_RUNTIME_DIR` has its own
+fn active_profile_path() -> PathBuf {
+    ipc::get_runtime_dir().join(ACTIVE_PROFILE_FILE)
+}
+
+/// returns the persisted active profile, if any
+pub fn load_active() -> Option<String> {
+    load_active_from(&active_profile_path())
+}
+
+/// persists the active profile; None clears it
+pub fn save_active(name: Option<&str>) -> Result<()> {
+    save_active_to(&active_profile_path(), name)
+}
+
+fn load_active_from(path: &Path) -> Option<String> {
+    let name = fs::read_to_string(path).ok()?;
+    let name = name.trim();
+    (!name.is_empty()).then(|| name.to_string())
+}
+
+fn save_active_to(path: &Path, name: Option<&str>) -> Result<()> {
+    match name {
+        Some(name) => {
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            fs::write(path, format!("{}\n", name))
+                .with_context(|| format!("failed to write {}", path.display()))
+        }
+        None => match fs::remove_file(path) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e.into()),
+            _ => Ok(()),
+        },
+    }
+}
+
+/// applies the persisted active profile, ignoring it if the config no longer
+/// defines it; a config loaded from an explicit path is used as is
+pub(super) fn apply_active(config: Config, override_path: Option<&Path>) -> Result<Config> {
+    if override_path.is_some() {
+        return Ok(config);
+    }
+    match load_active() {
+        Some(name) if config.profiles.contains_key(&name) => apply(&config, &name),
+        _ => Ok(config),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config_with_profiles() -> Config {
+        json5::from_str(
+            r#"{
+                "shortcuts": [
+                    { "keys": "ctrl+alt+m", "action": "maximize" },
+                    { "keys": "ctrl+alt+s", "action": "focus", "app": "Slack" }
+                ],
+                "app_rules": [{ "app": "Slack", "action": "maximize" }],
+                "settings": { "fuzzy_threshold": 2, "animate": false },
+                "profiles": {
+                    "presenting": {
+                        "shortcuts": [{ "keys": "ctrl+alt+s", "action": "focus", "app": "Keynote" }],
+                        "app_rules": [{ "app": "Slack", "action": "move:bottom-right" }],
+                        "settings": { "animate": true }
+                    },
+                    "deep-work": {}
+                }
+            }"#,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_apply_profile_overrides() {
+        let config = config_with_profiles();
+        let presenting = apply(&config, "presenting").unwrap();
+
+        assert_eq!(presenting.shortcuts.len(), 2);
+        assert_eq!(presenting.shortcuts[0].keys, "ctrl+alt+m");
+        assert_eq!(presenting.shortcuts[1].app.as_deref(), Some("Keynote"));
+
+        assert_eq!(presenting.app_rules.len(), 1);
+        assert_eq!(presenting.app_rules[0].action, "move:bottom-right");
+
+        assert!(presenting.settings.animate);
+        assert_eq!(presenting.settings.fuzzy_threshold, 2);
+
+        // profiles stay available for switching
+        assert_eq!(presenting.profiles.len(), 2);
+    }
+
+    #[test]
+    fn test_apply_empty_profile() {
+        let config = config_with_profiles();
+        let deep_work = apply(&config, "deep-work").unwrap();
+        assert_eq!(deep_work.shortcuts.len(), 2);
+        assert!(!deep_work.settings.animate);
+    }
+
+    #[test]
+    fn test_apply_unknown_profile() {
+        let config = config_with_profiles();
+        let err = apply(&config, "nope").unwrap_err().to_string();
+        assert!(err.contains("unknown profile 'nope'"));
+        assert!(err.contains("deep-work, presenting"));
+    }
+
+    #[test]
+    fn test_apply_invalid_settings() {
+        let mut config = config_with_profiles();
+        config
+            .profiles
+            .get_mut("deep-work")
+            .unwrap()
+            .settings
+            .insert("fuzzy_threshold".to_string(), serde_json::json!("high"));
+
+        assert!(apply(&config, "deep-work").is_err());
+    }
+
+    #[test]
+    fn test_effective_without_profile() {
+        let config = config_with_profiles();
+        let base = effective(&config, None).unwrap();
+        assert_eq!(base.app_rules[0].action, "maximize");
+    }
+
+    #[test]
+    fn test_explicit_config_ignores_active_profile() {
+        let config =
+            apply_active(config_with_profiles(), Some(Path::new("/tmp/cwm.json"))).unwrap();
+        assert_eq!(config.app_rules[0].action, "maximize");
+    }
+
+    #[test]
+    fn test_active_profile_roundtrip() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("cwm").join(ACTIVE_PROFILE_FILE);
+
+        assert_eq!(load_active_from(&path), None);
+
+        save_active_to(&path, Some("presenting")).unwrap();
+        assert_eq!(load_active_from(&path), Some("presenting".to_string()));
+
+        save_active_to(&path, None).unwrap();
+        assert_eq!(load_active_from(&path), None);
+
+        // clearing twice is fine
+        save_active_to(&path, None).unwrap();
+    }
+}
@@ -1,88 +1,328 @@
 use chrono::{DateTime, Utc};
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
+
+use super::json_schema::{ActionSchema, ConditionSchema};
 
 pub const DEFAULT_SCHEMA_REF: &str = "./config.schema.json";
 
 pub type DisplayAliases = HashMap<String, Vec<String>>;
 
+/// variable name -> value, referenced as `${name}` in config strings
+pub type Vars = HashMap<String, String>;
+
 /// global condition definitions that can be referenced by $ref
 pub type ConditionDefinitions = HashMap<String, serde_json::Value>;
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// profile name -> overrides applied while the profile is active
+pub type Profiles = HashMap<String, Profile>;
+
+/// mode name -> keymap active while the mode is
+pub type Modes = HashMap<String, Mode>;
+
+/// configuration file for cwm (cool window manager)
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[schemars(title = "CWM Configuration")]
 pub struct Config {
+    /// JSON schema reference
     #[serde(
         rename = "$schema",
         default = "default_schema",
         skip_serializing_if = "Option::is_none"
     )]
     pub schema: Option<String>,
+    /// config format version, older files are migrated on load (see `cwm config migrate`)
+    #[serde(default = "default_version")]
+    #[schemars(range(min = 1))]
+    pub version: u32,
+    /// other config files merged in before this one, relative to this file; see `config::include`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    #[schemars(
+        schema_with = "super::json_schema::include_schema",
+        extend("examples" = [["./team.jsonc", "~/.cwm/local.jsonc"]])
+    )]
+    pub include: Vec<String>,
+    /// values referenced as `${name}` in app, action and when strings, next to
+    /// `${env:NAME}` and `${host}`; see `config::vars`
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    #[schemars(extend("examples" = [{
+        "editor": "Visual Studio Code",
+        "big": "80"
+    }]))]
+    pub vars: Vars,
     /// global condition definitions that can be referenced by $ref in shortcuts and app_rules
     #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    #[schemars(
+        with = "HashMap<String, ConditionSchema>",
+        extend("examples" = [{
+            "work_hours": { "time": "9:00AM-5:00PM", "time.day": "mon-fri" },
+            "docked": { "display.count": { ">=": 2 } }
+        }])
+    )]
     pub conditions: ConditionDefinitions,
+    /// global hotkey shortcuts
     #[serde(default)]
     pub shortcuts: Vec<Shortcut>,
+    /// named modes: while one is active, its bindings take plain key presses
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    #[schemars(extend("examples" = [{
+        "resize": {
+            "enter": "ctrl+alt+r",
+            "bindings": { "h": "resize:-5%w", "l": "resize:+5%w", "f": "resize:full" },
+            "timeout_ms": 5000
+        }
+    }]))]
+    pub modes: Modes,
+    /// modifier + mouse button, scroll or drag bindings
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    #[schemars(extend("examples" = [[
+        { "mouse": "ctrl+alt+scroll-up", "action": "resize:80" },
+        { "mouse": "cmd+ctrl+button3", "action": "maximize", "window": "under_cursor" },
+        { "mouse": "ctrl+alt+left", "drag": "move" },
+        { "mouse": "ctrl+alt+right", "drag": "resize" }
+    ]]))]
+    pub mouse_bindings: Vec<MouseBinding>,
+    /// rules to apply when applications launch
     #[serde(default)]
     pub app_rules: Vec<AppRule>,
+    /// global settings
     #[serde(default)]
     pub settings: Settings,
+    /// shortcuts that appear in macOS Spotlight search
     #[serde(default)]
     pub spotlight: Vec<SpotlightShortcut>,
+    /// display aliases mapped to unique display IDs (vendor_model_serial) or names
     #[serde(default)]
+    #[schemars(extend("examples" = [{
+        "office": ["10AC_D0B3_67890"],
+        "home": ["1E6D_5B11_12345", "10AC_D0B3_67890"]
+    }]))]
     pub display_aliases: DisplayAliases,
+    /// named profiles, switched with `cwm profile use <name>`
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub profiles: Profiles,
 }
 
 fn default_schema() -> Option<String> {
     Some(DEFAULT_SCHEMA_REF.to_string())
 }
 
+/// files written before versioning was introduced are version 1
+fn default_version() -> u32 {
+    1
+}
+
 impl Default for Config {
     fn default() -> Self {
         Self {
             schema: Some(DEFAULT_SCHEMA_REF.to_string()),
+            version: super::migrate::CURRENT_VERSION,
+            include: Vec::new(),
+            vars: Vars::new(),
             conditions: ConditionDefinitions::new(),
             shortcuts: Vec::new(),
+            modes: Modes::new(),
+            mouse_bindings: Vec::new(),
             app_rules: Vec::new(),
             settings: Settings::default(),
             spotlight: Vec::new(),
             display_aliases: DisplayAliases::new(),
+            profiles: Profiles::new(),
         }
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// a global hotkey shortcut binding
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+#[schemars(extend(
+    "if" = { "properties": { "action": { "const": "focus" } } },
+    "then" = { "required": ["app"] },
+    "anyOf" = [{ "required": ["action"] }, { "required": ["cycle"] }]
+))]
 pub struct Shortcut {
+    /// hotkey combination, or a sequence of them separated by commas; a `double_tap` or
+    /// `hold` trigger takes one combination or a lone modifier like "rshift"
+    #[schemars(extend("examples" = ["ctrl+alt+s", "cmd+shift+m", "ctrl+alt+right", "ctrl+alt+w, h", "rshift"]))]
     pub keys: String,
+    /// action to run; a shortcut has either this or `cycle`
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    #[schemars(with = "ActionSchema")]
     pub action: String,
+    /// steps run in turn by repeated presses, each one action or several separated by ';'
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    #[schemars(extend("examples" = [["resize:50x100%;move:left", "resize:33x100%;move:left", "resize:67x100%;move:left", "move:prev"]]))]
+    pub cycle: Vec<String>,
+    /// target application name (fuzzy matched), required for focus
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub app: Option<String>,
+    /// launch the app if not running, overrides settings.launch
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub launch: Option<bool>,
     /// condition that must be true for this shortcut to execute
     #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schemars(with = "Option<ConditionSchema>")]
     pub when: Option<serde_json::Value>,
+    /// apps (matched like `app`) the shortcut is limited to; elsewhere the key goes to the app
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    #[schemars(extend("examples" = [["Safari", "Google Chrome"]]))]
+    pub only_in: Vec<String>,
+    /// apps (matched like `app`) the shortcut is off in; the key goes to the app instead
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    #[schemars(extend("examples" = [["Xcode"]]))]
+    pub except_in: Vec<String>,
+    /// how the keys are pressed to run the shortcut
+    #[serde(default, skip_serializing_if = "Trigger::is_tap")]
+    pub trigger: Trigger,
+    /// most time between the two presses of a `double_tap`, or how long to press a `hold`,
+    /// in milliseconds; defaults to settings.double_tap_ms or settings.hold_ms
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schemars(range(min = 1))]
+    pub trigger_ms: Option<u64>,
+    /// for a `hold`: action to run when the keys are let go after the shortcut ran
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schemars(with = "Option<ActionSchema>")]
+    pub release_action: Option<String>,
+    /// what holding the keys down does: "ignore" runs the action once (the default),
+    /// "allow" runs it on every key repeat, "throttle:200ms" at most once per interval
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schemars(regex(pattern = r"^(ignore|allow|throttle:([0-9]+ms|[0-9]+(\.[0-9]+)?s))$"))]
+    #[schemars(extend("examples" = ["allow", "throttle:200ms"]))]
+    pub repeat: Option<String>,
+}
+
+/// how a shortcut's keys are pressed to run it
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum Trigger {
+    /// when the keys are pressed
+    #[default]
+    Tap,
+    /// when the keys are pressed twice in quick succession
+    DoubleTap,
+    /// when the keys have been held down for a while
+    Hold,
+}
+
+impl Trigger {
+    pub fn is_tap(&self) -> bool {
+        *self == Trigger::Tap
+    }
+}
+
+/// a keymap that is active from its `enter` hotkey until its `exit` key or timeout
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct Mode {
+    /// hotkey (or sequence) that activates the mode
+    #[schemars(extend("examples" = ["ctrl+alt+r", "ctrl+alt+w, r"]))]
+    pub enter: String,
+    /// key that leaves the mode
+    #[serde(default = "default_mode_exit")]
+    pub exit: String,
+    /// keys mapped to actions while the mode is active; other keys are not captured
+    #[serde(default)]
+    #[schemars(with = "BTreeMap<String, ActionSchema>")]
+    pub bindings: BTreeMap<String, String>,
+    /// leave the mode when no binding is pressed for this long, in milliseconds
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schemars(range(min = 1))]
+    pub timeout_ms: Option<u64>,
+}
+
+fn default_mode_exit() -> String {
+    "escape".to_string()
+}
+
+/// a mouse button or scroll direction, held with modifiers, bound to an action or a drag
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+#[schemars(extend("anyOf" = [{ "required": ["action"] }, { "required": ["drag"] }]))]
+pub struct MouseBinding {
+    /// modifiers and one of `button1`-`button32` (`left`, `right`, `middle`) or
+    /// `scroll-up`, `scroll-down`, `scroll-left`, `scroll-right`
+    #[schemars(extend("examples" = ["ctrl+alt+scroll-up", "cmd+ctrl+button3", "ctrl+alt+left"]))]
+    pub mouse: String,
+    /// action to run; a binding has either this or `drag`
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    #[schemars(with = "ActionSchema")]
+    pub action: String,
+    /// move or resize the window under the cursor while the button is held
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub drag: Option<MouseDrag>,
+    /// the window `action` is for
+    #[serde(default, skip_serializing_if = "MouseWindow::is_focused")]
+    pub window: MouseWindow,
+}
+
+/// what dragging with a mouse binding's button held does
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum MouseDrag {
+    /// move the window with the cursor
+    Move,
+    /// move the window edges nearest to where the drag started
+    Resize,
+}
+
+/// the window a mouse binding's action is for
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum MouseWindow {
+    /// the focused window
+    #[default]
+    Focused,
+    /// the window under the cursor, which is brought to the front
+    UnderCursor,
+}
+
+impl MouseWindow {
+    pub fn is_focused(&self) -> bool {
+        *self == MouseWindow::Focused
+    }
 }
 
 /// rule to apply an action when an app launches
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct AppRule {
+    /// application name (exact match)
     pub app: String,
+    #[schemars(with = "ActionSchema")]
     pub action: String,
     /// delay in milliseconds before executing the action (overrides global)
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub delay_ms: Option<u64>,
     /// condition that must be true for this rule to execute
     #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schemars(with = "Option<ConditionSchema>")]
     pub when: Option<serde_json::Value>,
 }
 
+/// overrides applied on top of the base config while a profile is active
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+pub struct Profile {
+    /// shortcuts added to the base shortcuts, replacing any with the same keys
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub shortcuts: Vec<Shortcut>,
+    /// app rules added to the base rules, replacing any for the same app
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub app_rules: Vec<AppRule>,
+    /// settings merged key by key into the base settings
+    #[serde(default, skip_serializing_if = "serde_json::Map::is_empty")]
+    pub settings: serde_json::Map<String, serde_json::Value>,
+}
+
 /// spotlight shortcut that appears in macOS Spotlight search
-/// uses the same action format as shortcuts: focus, maximize, move_display:next, resize:80
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// uses the same action format as shortcuts: focus, maximize, move:next, resize:80
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[schemars(extend(
+    "if" = { "properties": { "action": { "const": "focus" } } },
+    "then" = { "required": ["app"] }
+))]
 pub struct SpotlightShortcut {
     /// name displayed in Spotlight (will be prefixed with "cwm: ")
+    #[schemars(length(min = 1))]
     pub name: String,
-    /// action in same format as shortcuts: focus, maximize, move_display:next, resize:80
+    /// action in same format as shortcuts: focus, maximize, move:next, resize:80
+    #[schemars(with = "ActionSchema")]
     pub action: String,
     /// target application (required for focus, optional for others)
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -94,6 +334,7 @@ pub struct SpotlightShortcut {
     /// can be: path to .icns file, path to .png file, or app name to extract icon from
     /// if not specified, uses target app's icon (if app is set) or default cwm icon
     #[serde(skip_serializing_if = "Option::is_none")]
+    #[schemars(extend("examples" = ["/path/to/icon.icns", "Safari", "~/icons/custom.png"]))]
     pub icon: Option<String>,
 }
 
@@ -116,29 +357,72 @@ impl SpotlightShortcut {
 }
 
 pub const DEFAULT_DELAY_MS: u64 = 500;
+pub const DEFAULT_SEQUENCE_TIMEOUT_MS: u64 = 1000;
+pub const DEFAULT_DOUBLE_TAP_MS: u64 = 300;
+pub const DEFAULT_HOLD_MS: u64 = 500;
+pub const DEFAULT_CYCLE_TIMEOUT_MS: u64 = 2000;
 pub const DEFAULT_RETRY_COUNT: u32 = 10;
 pub const DEFAULT_RETRY_DELAY_MS: u64 = 100;
 pub const DEFAULT_RETRY_BACKOFF: f64 = 1.5;
 pub const DEFAULT_HISTORY_LIMIT: usize = 50;
 pub const DEFAULT_HISTORY_FLUSH_DELAY_MS: u64 = 2000;
+pub const DEFAULT_SOCKET_MODE: &str = "0600";
+pub const DEFAULT_REMOTE_BIND: &str = "127.0.0.1";
+pub const DEFAULT_REMOTE_PORT: u16 = 7878;
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// global settings
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct Settings {
+    /// maximum Levenshtein distance for fuzzy app name matching
     #[serde(default = "default_fuzzy_threshold")]
     pub fuzzy_threshold: usize,
+    /// launch apps if not running (can be overridden per shortcut)
     #[serde(default)]
     pub launch: bool,
+    /// animate window movements
     #[serde(default)]
     pub animate: bool,
     /// default delay in milliseconds before executing app rule actions
     #[serde(default = "default_delay_ms")]
     pub delay_ms: u64,
+    /// how long a hotkey sequence like "ctrl+alt+w, h" waits for its next chord, in milliseconds
+    #[serde(default = "default_sequence_timeout_ms")]
+    #[schemars(range(min = 1))]
+    pub sequence_timeout_ms: u64,
+    /// most time between the two presses of a `double_tap` shortcut, in milliseconds
+    #[serde(default = "default_double_tap_ms")]
+    #[schemars(range(min = 1))]
+    pub double_tap_ms: u64,
+    /// how long the keys of a `hold` shortcut are held before it runs, in milliseconds
+    #[serde(default = "default_hold_ms")]
+    #[schemars(range(min = 1))]
+    pub hold_ms: u64,
+    /// longest pause between presses of a `cycle` shortcut before it starts over, in milliseconds
+    #[serde(default = "default_cycle_timeout_ms")]
+    #[schemars(range(min = 1))]
+    pub cycle_timeout_ms: u64,
+    /// drop a hotkey action still waiting for its window when a newer one for the same window comes in
+    #[serde(default)]
+    pub cancel_stale_actions: bool,
+    /// apps (matched like a shortcut's `app`) in which every cwm hotkey is off, e.g. games or remote desktops
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    #[schemars(extend("examples" = [["Steam", "Parallels Desktop", "Microsoft Remote Desktop"]]))]
+    pub disabled_in: Vec<String>,
+    /// keyboard layout whose characters shortcut keys are written with: "us", "de", "fr" or "dvorak";
+    /// detected from the current macOS input source when unset
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schemars(extend("examples" = ["de"]))]
+    pub keyboard_layout: Option<String>,
     #[serde(default)]
     pub retry: Retry,
     #[serde(default)]
     pub update: UpdateSettings,
     #[serde(default)]
     pub history: HistorySettings,
+    #[serde(default)]
+    pub ipc: IpcSettings,
+    #[serde(default)]
+    pub remote: RemoteSettings,
 }
 
 fn default_fuzzy_threshold() -> usize {
@@ -149,6 +433,22 @@ fn default_delay_ms() -> u64 {
     DEFAULT_DELAY_MS
 }
 
+fn default_sequence_timeout_ms() -> u64 {
+    DEFAULT_SEQUENCE_TIMEOUT_MS
+}
+
+fn default_double_tap_ms() -> u64 {
+    DEFAULT_DOUBLE_TAP_MS
+}
+
+fn default_hold_ms() -> u64 {
+    DEFAULT_HOLD_MS
+}
+
+fn default_cycle_timeout_ms() -> u64 {
+    DEFAULT_CYCLE_TIMEOUT_MS
+}
+
 impl Default for Settings {
     fn default() -> Self {
         Self {
@@ -156,20 +456,34 @@ impl Default for Settings {
             launch: false,
             animate: false,
             delay_ms: DEFAULT_DELAY_MS,
+            sequence_timeout_ms: DEFAULT_SEQUENCE_TIMEOUT_MS,
+            double_tap_ms: DEFAULT_DOUBLE_TAP_MS,
+            hold_ms: DEFAULT_HOLD_MS,
+            cycle_timeout_ms: DEFAULT_CYCLE_TIMEOUT_MS,
+            cancel_stale_actions: false,
+            disabled_in: Vec::new(),
+            keyboard_layout: None,
             retry: Retry::default(),
             update: UpdateSettings::default(),
             history: HistorySettings::default(),
+            ipc: IpcSettings::default(),
+            remote: RemoteSettings::default(),
         }
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// retry settings for window operations
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct Retry {
+    /// number of retry attempts
     #[serde(default = "default_retry_count")]
     pub count: u32,
+    /// initial delay between retries in milliseconds
     #[serde(default = "default_retry_delay_ms")]
     pub delay_ms: u64,
+    /// backoff multiplier for retry delays
     #[serde(default = "default_retry_backoff")]
+    #[schemars(range(min = 1.0))]
     pub backoff: f64,
 }
 
@@ -195,13 +509,15 @@ impl Default for Retry {
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// undo/redo history settings
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct HistorySettings {
     /// enable undo/redo history tracking
     #[serde(default = "default_true")]
     pub enabled: bool,
     /// maximum number of entries in the undo stack
     #[serde(default = "default_history_limit")]
+    #[schemars(range(min = 1))]
     pub limit: usize,
     /// delay in milliseconds before flushing history to disk
     #[serde(default = "default_history_flush_delay_ms")]
@@ -226,6 +542,91 @@ impl Default for HistorySettings {
     }
 }
 
+/// access control for the daemon IPC socket
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct IpcSettings {
+    /// octal permission bits applied to the socket file after binding
+    #[serde(default = "default_socket_mode")]
+    #[schemars(regex(pattern = r"^(0o?)?[67][0-7]{2}$"))]
+    pub socket_mode: String,
+    /// additional uids allowed to connect (the daemon's own uid is always allowed)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub allowed_uids: Vec<u32>,
+    /// reject requests that don't carry a configured token
+    #[serde(default)]
+    pub require_token: bool,
+    /// per-client tokens with method allow/deny lists
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tokens: Vec<IpcToken>,
+}
+
+fn default_socket_mode() -> String {
+    DEFAULT_SOCKET_MODE.to_string()
+}
+
+impl Default for IpcSettings {
+    fn default() -> Self {
+        Self {
+            socket_mode: DEFAULT_SOCKET_MODE.to_string(),
+            allowed_uids: Vec::new(),
+            require_token: false,
+            tokens: Vec::new(),
+        }
+    }
+}
+
+/// opt-in TCP listener for controlling the daemon from other machines
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct RemoteSettings {
+    /// start the TLS listener with the daemon
+    #[serde(default)]
+    pub enabled: bool,
+    /// address to listen on; the default only accepts connections from this Mac,
+    /// "0.0.0.0" accepts them from the network
+    #[serde(default = "default_remote_bind")]
+    pub bind: String,
+    /// TCP port to listen on
+    #[serde(default = "default_remote_port")]
+    #[schemars(range(min = 1))]
+    pub port: u16,
+}
+
+fn default_remote_bind() -> String {
+    DEFAULT_REMOTE_BIND.to_string()
+}
+
+fn default_remote_port() -> u16 {
+    DEFAULT_REMOTE_PORT
+}
+
+impl Default for RemoteSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            bind: DEFAULT_REMOTE_BIND.to_string(),
+            port: DEFAULT_REMOTE_PORT,
+        }
+    }
+}
+
+/// a client token and the methods it may call
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct IpcToken {
+    /// label used in logs and error messages
+    pub name: String,
+    /// shared secret sent by the client in the request's `token` field
+    #[schemars(length(min = 1))]
+    pub token: String,
+    /// methods this token may call (empty = all); supports `*` and trailing `prefix*`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    #[schemars(extend("examples" = [["focus", "move", "resize"]]))]
+    pub allow: Vec<String>,
+    /// methods this token may never call, checked before `allow`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    #[schemars(extend("examples" = [["kill", "close", "config*"]]))]
+    pub deny: Vec<String>,
+}
+
 /// determines if an app should be launched based on CLI flags, shortcut config, and global config
 pub fn should_launch(
     cli_launch: bool,
@@ -242,18 +643,23 @@ pub fn should_launch(
     shortcut_launch.unwrap_or(global_launch)
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// update checking and auto-update settings
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct UpdateSettings {
+    /// enable update checking
     #[serde(default = "default_true")]
     pub enabled: bool,
+    /// how often to check for updates
     #[serde(default = "default_daily")]
     pub check_frequency: UpdateFrequency,
+    /// always install, prompt, or never auto-update
     #[serde(default = "default_prompt")]
     pub auto_update: AutoUpdateMode,
     #[serde(default)]
     pub channels: UpdateChannels,
     #[serde(default)]
     pub telemetry: TelemetrySettings,
+    /// timestamp of the last update check (managed automatically)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub last_check: Option<DateTime<Utc>>,
 }
@@ -271,12 +677,16 @@ impl Default for UpdateSettings {
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// which release channels to consider for updates
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct UpdateChannels {
+    /// include development releases
     #[serde(default)]
     pub dev: bool,
+    /// include beta releases
     #[serde(default)]
     pub beta: bool,
+    /// include stable releases
     #[serde(default = "default_true")]
     pub stable: bool,
 }
@@ -291,15 +701,18 @@ impl Default for UpdateChannels {
     }
 }
 
-#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+/// error reporting and telemetry settings
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
 pub struct TelemetrySettings {
+    /// enable error reporting
     #[serde(default)]
     pub enabled: bool,
+    /// include system information in error reports
     #[serde(default)]
     pub include_system_info: bool,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum UpdateFrequency {
     Daily,
@@ -307,7 +720,7 @@ pub enum UpdateFrequency {
     Manual,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum AutoUpdateMode {
     Always,
@@ -372,9 +785,13 @@ mod tests {
         assert!(!settings.launch);
         assert!(!settings.animate);
         assert_eq!(settings.delay_ms, DEFAULT_DELAY_MS);
+        assert_eq!(settings.sequence_timeout_ms, DEFAULT_SEQUENCE_TIMEOUT_MS);
         assert_eq!(settings.retry.count, DEFAULT_RETRY_COUNT);
         assert_eq!(settings.retry.delay_ms, DEFAULT_RETRY_DELAY_MS);
         assert_eq!(settings.retry.backoff, DEFAULT_RETRY_BACKOFF);
+        // the remote listener is off and, once enabled, local-only unless bound elsewhere
+        assert!(!settings.remote.enabled);
+        assert_eq!(settings.remote.bind, "127.0.0.1");
     }
 
     #[test]
@@ -395,6 +812,7 @@ mod tests {
             app: Some("Slack".to_string()),
             launch: Some(true),
             when: None,
+            ..Default::default()
         });
         config.app_rules.push(AppRule {
             app: "Terminal".to_string(),
@@ -0,0 +1,218 @@
+//! variable interpolation in config values
+//!
+//! `app`, `action`, `release_action` and `when` strings (and named `conditions`,
+//! mode `bindings`, `cycle` steps and the `only_in`, `except_in` and `disabled_in`
+//! app lists) can reference:
+//! - `${name}` - an entry of the top-level `vars` map
+//! - `${env:NAME}` - an environment variable
+//! - `${host}` - this machine's host name, without the domain
+//!
+//! `${name:-fallback}` uses `fallback` when the variable is not defined.
+//! `vars` entries may use `${env:...}` and `${host}` but not other vars.
+//!
+//! expansion runs on the raw config value after includes are merged and before
+//! it is deserialized, so validation sees the expanded values while errors
+//! keep the path (and so the file location) of the original string
+
+use serde_json::Value;
+
+use super::schema::Vars;
+
+const VARS_KEY: &str = "vars";
+
+/// fields whose strings (at any depth) are expanded
+const FIELDS: &[&str] = &[
+    "app",
+    "action",
+    "release_action",
+    "cycle",
+    "when",
+    "bindings",
+    "only_in",
+    "except_in",
+    "disabled_in",
+];
+
+/// top-level keys whose strings are all expanded
+const SECTIONS: &[&str] = &["conditions"];
+
+/// expands references in `value` in place; returns one `path: message` error
+/// per string that references an undefined variable, which is left as written
+pub fn expand(value: &mut Value) -> Vec<String> {
+    let mut errors = Vec::new();
+    let Value::Object(map) = value else {
+        return errors;
+    };
+
+    let mut vars = Vars::new();
+    if let Some(Value::Object(defined)) = map.get(VARS_KEY) {
+        for (name, var) in defined {
+            let Some(text) = var.as_str() else {
+                continue;
+            };
+            match expand_str(text, &Vars::new()) {
+                Ok(expanded) => {
+                    vars.insert(name.clone(), expanded);
+                }
+                Err(e) => errors.push(format!("{}.{}: {}", VARS_KEY, name, e)),
+            }
+        }
+    }
+
+    for (key, child) in map.iter_mut() {
+        if key == VARS_KEY {
+            continue;
+        }
+        let active = FIELDS.contains(&key.as_str()) || SECTIONS.contains(&key.as_str());
+        expand_value(child, key, active, &vars, &mut errors);
+    }
+
+    errors
+}
+
+fn expand_value(
+    value: &mut Value,
+    path: &str,
+    active: bool,
+    vars: &Vars,
+    errors: &mut Vec<String>,
+) {
+    match value {
+        Value::String(text) if active => match expand_str(text, vars) {
+            Ok(expanded) => *text = expanded,
+            Err(e) => errors.push(format!("{}: {}", path, e)),
+        },
+        Value::Array(items) => {
+            for (i, item) in items.iter_mut().enumerate() {
+                expand_value(item, &format!("{}[{}]", path, i), active, vars, errors);
+            }
+        }
+        Value::Object(map) => {
+            for (key, child) in map.iter_mut() {
+                let active = active || FIELDS.contains(&key.as_str());
+                expand_value(child, &format!("{}.{}", path, key), active, vars, errors);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// expands one string; on error the string is not changed
+fn expand_str(text: &str, vars: &Vars) -> Result<String, String> {
+    if !text.contains('$') {
+        return Ok(text.to_string());
+    }
+
+    let mut missing = Vec::new();
+    let expanded = shellexpand::env_with_context(text, |name: &str| {
+        let found = lookup(name, vars);
+        if found.is_none() {
+            missing.push(name.to_string());
+        }
+        Ok::<_, std::convert::Infallible>(found)
+    })
+    .map_err(|e| e.to_string())?
+    .into_owned();
+
+    // an unknown `$word` without braces is just text; `${word}` must resolve
+    match missing
+        .iter()
+        .find(|name| text.contains(&format!("${{{}}}", name)))
+    {
+        Some(name) => match name.strip_prefix("env:") {
+            Some(var) => Err(format!("environment variable '{}' is not set", var)),
+            None => Err(format!("undefined variable '{}'", name)),
+        },
+        None => Ok(expanded),
+    }
+}
+
+fn lookup(name: &str, vars: &Vars) -> Option<String> {
+    if let Some(var) = name.strip_prefix("env:") {
+        return std::env::var(var).ok();
+    }
+    if let Some(value) = vars.get(name) {
+        return Some(value.clone());
+    }
+    (name == "host").then(crate::remote::client::local_hostname)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_expand_fields() {
+        let mut value = json!({
+            "vars": { "editor": "Visual Studio Code", "big": "80" },
+            "shortcuts": [
+                { "keys": "ctrl+alt+e", "action": "focus", "app": "${editor}" },
+                { "keys": "ctrl+alt+b", "action": "resize:${big}", "when": { "app": "${editor}" } },
+                { "keys": "ctrl+alt+m", "action": "maximize", "except_in": ["${editor}"] },
+                { "keys": "ctrl+alt+h", "trigger": "hold", "release_action": "resize:${big}" }
+            ],
+            "conditions": { "coding": { "app": { "in": ["${editor}", "Terminal"] } } },
+            "modes": { "resize": { "enter": "ctrl+alt+r", "bindings": { "b": "resize:${big}" } } },
+            "settings": { "note": "${editor}" }
+        });
+
+        assert!(expand(&mut value).is_empty());
+        assert_eq!(value["shortcuts"][0]["app"], "Visual Studio Code");
+        assert_eq!(value["shortcuts"][1]["action"], "resize:80");
+        assert_eq!(value["shortcuts"][1]["when"]["app"], "Visual Studio Code");
+        assert_eq!(value["modes"]["resize"]["bindings"]["b"], "resize:80");
+        assert_eq!(value["shortcuts"][2]["except_in"][0], "Visual Studio Code");
+        assert_eq!(value["shortcuts"][3]["release_action"], "resize:80");
+        assert_eq!(
+            value["conditions"]["coding"]["app"]["in"][0],
+            "Visual Studio Code"
+        );
+        // only app, action, when, bindings and app list strings are expanded
+        assert_eq!(value["settings"]["note"], "${editor}");
+        assert_eq!(value["vars"]["editor"], "Visual Studio Code");
+    }
+
+    #[test]
+    fn test_expand_env_and_host() {
+        std::env::set_var("CWM_TEST_VARS_APP", "Safari");
+        let mut value = json!({
+            "vars": { "browser": "${env:CWM_TEST_VARS_APP}" },
+            "app_rules": [
+                { "app": "${browser}", "action": "maximize" },
+                { "app": "Notes", "action": "focus", "when": { "app": "${host}" } }
+            ]
+        });
+
+        assert!(expand(&mut value).is_empty());
+        assert_eq!(value["app_rules"][0]["app"], "Safari");
+        assert_eq!(
+            value["app_rules"][1]["when"]["app"],
+            crate::remote::client::local_hostname()
+        );
+    }
+
+    #[test]
+    fn test_expand_errors() {
+        let mut value = json!({
+            "vars": { "home": "${env:CWM_TEST_VARS_UNSET}" },
+            "shortcuts": [
+                { "keys": "ctrl+alt+e", "action": "focus", "app": "${editor}" },
+                { "keys": "ctrl+alt+f", "action": "focus", "app": "${editor:-TextEdit}" },
+                { "keys": "ctrl+alt+c", "action": "focus", "app": "Ca$h" }
+            ]
+        });
+
+        let errors = expand(&mut value);
+        assert_eq!(
+            errors,
+            vec![
+                "vars.home: environment variable 'CWM_TEST_VARS_UNSET' is not set",
+                "shortcuts[0].app: undefined variable 'editor'",
+            ]
+        );
+        assert_eq!(value["shortcuts"][0]["app"], "${editor}");
+        assert_eq!(value["shortcuts"][1]["app"], "TextEdit");
+        assert_eq!(value["shortcuts"][2]["app"], "Ca$h");
+    }
+}
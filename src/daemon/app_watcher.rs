@@ -180,6 +180,15 @@ mod macos {
         Ok(())
     }
 
+    /// replace the rules of a running watcher
+    pub fn set_rules(rules: Vec<AppRule>) {
+        if let Ok(mut rules_guard) = APP_RULES.lock() {
+            if rules_guard.is_some() {
+                *rules_guard = Some(rules);
+            }
+        }
+    }
+
     pub fn stop_watching() {
         // remove observer
         let observer_ptr = {
@@ -229,4 +238,4 @@ mod macos {
     }
 }
 
-pub use macos::{start_watching, stop_watching};
+pub use macos::{set_rules, start_watching, stop_watching};
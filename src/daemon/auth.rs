@@ -0,0 +1,413 @@
+//! access control for the daemon IPC socket
+//!
+//! the socket is protected in three layers:
+//! - file mode: the socket file is created owner-only, then chmod'ed to
+//!   `settings.ipc.socket_mode`, so nobody else can connect in between
+//! - peer credentials: only the daemon's own uid (plus `allowed_uids`) may connect
+//! - tokens: clients may present a token whose allow/deny lists restrict callable methods
+//!
+//! remote peers are paired rather than local users, so they need a token to
+//! quit apps or touch the config
+
+use anyhow::{anyhow, Result};
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::Path;
+
+use crate::config::{IpcSettings, IpcToken};
+
+use super::ipc::IpcRequest;
+
+/// resolved access policy for incoming IPC connections
+#[derive(Debug, Clone)]
+pub struct AccessPolicy {
+    daemon_uid: u32,
+    allowed_uids: Vec<u32>,
+    require_token: bool,
+    tokens: Vec<IpcToken>,
+}
+
+impl AccessPolicy {
+    /// build a policy from config settings for a daemon running as `daemon_uid`
+    pub fn from_settings(settings: &IpcSettings, daemon_uid: u32) -> Self {
+        Self {
+            daemon_uid,
+            allowed_uids: settings.allowed_uids.clone(),
+            require_token: settings.require_token,
+            tokens: settings.tokens.clone(),
+        }
+    }
+
+    /// build a policy for the current process uid
+    pub fn for_current_user(settings: &IpcSettings) -> Self {
+        let uid = unsafe { libc::getuid() };
+        Self::from_settings(settings, uid)
+    }
+
+    /// check that a connecting peer's uid is allowed
+    pub fn check_peer(&self, uid: u32) -> Result<(), String> {
+        if uid == self.daemon_uid || self.allowed_uids.contains(&uid) {
+            Ok(())
+        } else {
+            Err(format!("access denied: uid {} may not connect", uid))
+        }
+    }
+
+    /// check that a request may call its method with the token it carries
+    pub fn authorize(&self, request: &IpcRequest) -> Result<(), String> {
+        let method = effective_method(request);
+
+        let token = match request.token.as_deref() {
+            Some(presented) => match self.find_token(presented) {
+                Some(token) => token,
+                None => return Err("access denied: unknown token".to_string()),
+            },
+            None if self.require_token => {
+                return Err("access denied: this socket requires a token".to_string());
+            }
+            None => return Ok(()),
+        };
+
+        if token.deny.iter().any(|p| method_matches(p, method)) {
+            return Err(format!(
+                "access denied: token '{}' may not call '{}'",
+                token.name, method
+            ));
+        }
+
+        if !token.allow.is_empty() && !token.allow.iter().any(|p| method_matches(p, method)) {
+            return Err(format!(
+                "access denied: token '{}' may not call '{}'",
+                token.name, method
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// like `authorize`, but a remote request without a token may not call
+    /// `REMOTE_TOKEN_METHODS`
+    pub fn authorize_remote(&self, request: &IpcRequest) -> Result<(), String> {
+        let method = effective_method(request);
+        let needs_token = REMOTE_TOKEN_METHODS
+            .iter()
+            .any(|p| method_matches(p, method));
+        if needs_token && request.token.is_none() {
+            return Err(format!(
+                "access denied: '{}' needs a token over remote connections",
+                method
+            ));
+        }
+        self.authorize(request)
+    }
+
+    fn find_token(&self, presented: &str) -> Option<&IpcToken> {
+        self.tokens
+            .iter()
+            .find(|t| constant_time_eq(t.token.as_bytes(), presented.as_bytes()))
+    }
+}
+
+/// methods that quit apps, close windows or read and change the config
+const REMOTE_TOKEN_METHODS: &[&str] = &["kill", "close", "config*"];
+
+/// the method name used for access checks
+///
+/// the raw `action` method carries a hotkey-style action string, so it is
+/// checked against the action's verb (e.g. `focus` for `focus:Safari`)
+fn effective_method(request: &IpcRequest) -> &str {
+    if request.method == "action" {
+        if let Some(action) = request.params.get("action") {
+            return action.split(':').next().unwrap_or(action);
+        }
+    }
+    &request.method
+}
+
+/// match a method against an allow/deny pattern (`*`, `prefix*` or exact)
+fn method_matches(pattern: &str, method: &str) -> bool {
+    if pattern == "*" {
+        return true;
+    }
+    if let Some(prefix) = pattern.strip_suffix('*') {
+        return method.starts_with(prefix);
+    }
+    pattern == method
+}
+
+pub(crate) fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+/// parse an octal socket mode like "600", "0600" or "0o600"
+pub fn parse_socket_mode(mode: &str) -> Result<u32> {
+    let trimmed = mode.trim();
+    let digits = trimmed
+        .strip_prefix("0o")
+        .or_else(|| trimmed.strip_prefix("0O"))
+        .unwrap_or(trimmed);
+
+    let value = u32::from_str_radix(digits, 8)
+        .map_err(|_| anyhow!("invalid socket mode '{}': expected octal like 0600", mode))?;
+
+    if value > 0o777 {
+        return Err(anyhow!(
+            "invalid socket mode '{}': only permission bits (up to 0777) are allowed",
+            mode
+        ));
+    }
+
+    if value & 0o600 != 0o600 {
+        return Err(anyhow!(
+            "invalid socket mode '{}': owner needs read and write access",
+            mode
+        ));
+    }
+
+    Ok(value)
+}
+
+/// bind a Unix socket at `path`, created readable and writable by its owner only
+pub fn bind_private(path: &Path) -> std::io::Result<UnixListener> {
+    // the umask is process-wide, but only files created during the bind are affected
+    let previous = unsafe { libc::umask(0o177) };
+    let result = UnixListener::bind(path);
+    unsafe { libc::umask(previous) };
+    result
+}
+
+/// get the uid of the process on the other end of a Unix socket
+#[cfg(target_os = "linux")]
+pub fn peer_uid(stream: &UnixStream) -> std::io::Result<u32> {
+    use std::os::unix::io::AsRawFd;
+
+    let mut cred = libc::ucred {
+        pid: 0,
+        uid: 0,
+        gid: 0,
+    };
+    let mut len = std::mem::size_of::<libc::ucred>() as libc::socklen_t;
+
+    let ret = unsafe {
+        libc::getsockopt(
+            stream.as_raw_fd(),
+            libc::SOL_SOCKET,
+            libc::SO_PEERCRED,
+            &mut cred as *mut libc::ucred as *mut libc::c_void,
+            &mut len,
+        )
+    };
+
+    if ret == 0 {
+        Ok(cred.uid)
+    } else {
+        Err(std::io::Error::last_os_error())
+    }
+}
+
+/// get the uid of the process on the other end of a Unix socket
+#[cfg(not(target_os = "linux"))]
+pub fn peer_uid(stream: &UnixStream) -> std::io::Result<u32> {
+    use std::os::unix::io::AsRawFd;
+
+    let mut uid: libc::uid_t = 0;
+    let mut gid: libc::gid_t = 0;
+
+    let ret = unsafe { libc::getpeereid(stream.as_raw_fd(), &mut uid, &mut gid) };
+
+    if ret == 0 {
+        Ok(uid)
+    } else {
+        Err(std::io::Error::last_os_error())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn token(name: &str, secret: &str, allow: &[&str], deny: &[&str]) -> IpcToken {
+        IpcToken {
+            name: name.to_string(),
+            token: secret.to_string(),
+            allow: allow.iter().map(|s| s.to_string()).collect(),
+            deny: deny.iter().map(|s| s.to_string()).collect(),
+        }
+    }
+
+    fn policy(require_token: bool, tokens: Vec<IpcToken>) -> AccessPolicy {
+        let settings = IpcSettings {
+            require_token,
+            tokens,
+            allowed_uids: vec![501],
+            ..Default::default()
+        };
+        AccessPolicy::from_settings(&settings, 1000)
+    }
+
+    fn request(json: &str) -> IpcRequest {
+        IpcRequest::parse(json).unwrap()
+    }
+
+    #[test]
+    fn test_check_peer_own_uid() {
+        let p = policy(false, vec![]);
+        assert!(p.check_peer(1000).is_ok());
+    }
+
+    #[test]
+    fn test_check_peer_allowed_uid() {
+        let p = policy(false, vec![]);
+        assert!(p.check_peer(501).is_ok());
+    }
+
+    #[test]
+    fn test_check_peer_other_uid_denied() {
+        let p = policy(false, vec![]);
+        let err = p.check_peer(0).unwrap_err();
+        assert!(err.contains("uid 0"));
+    }
+
+    #[test]
+    fn test_authorize_without_token_when_not_required() {
+        let p = policy(false, vec![token("stub", "s3cret", &["focus"], &[])]);
+        assert!(p.authorize(&request(r#"{"method":"kill","id":1}"#)).is_ok());
+    }
+
+    #[test]
+    fn test_authorize_without_token_when_required() {
+        let p = policy(true, vec![]);
+        let err = p
+            .authorize(&request(r#"{"method":"ping","id":1}"#))
+            .unwrap_err();
+        assert!(err.contains("requires a token"));
+    }
+
+    #[test]
+    fn test_authorize_unknown_token() {
+        let p = policy(false, vec![token("stub", "s3cret", &[], &[])]);
+        let err = p
+            .authorize(&request(r#"{"method":"ping","token":"nope","id":1}"#))
+            .unwrap_err();
+        assert!(err.contains("unknown token"));
+    }
+
+    #[test]
+    fn test_authorize_allow_list() {
+        let p = policy(
+            true,
+            vec![token("spotlight", "abc", &["focus", "move", "resize"], &[])],
+        );
+        assert!(p
+            .authorize(&request(r#"{"method":"focus","token":"abc","id":1}"#))
+            .is_ok());
+        assert!(p
+            .authorize(&request(r#"{"method":"resize","token":"abc","id":1}"#))
+            .is_ok());
+
+        let err = p
+            .authorize(&request(r#"{"method":"kill","token":"abc","id":1}"#))
+            .unwrap_err();
+        assert!(err.contains("spotlight"));
+        assert!(err.contains("kill"));
+    }
+
+    #[test]
+    fn test_authorize_deny_wins_over_allow() {
+        let p = policy(true, vec![token("ci", "abc", &["*"], &["config*", "kill"])]);
+        assert!(p
+            .authorize(&request(r#"{"method":"focus","token":"abc","id":1}"#))
+            .is_ok());
+        assert!(p
+            .authorize(&request(r#"{"method":"kill","token":"abc","id":1}"#))
+            .is_err());
+        assert!(p
+            .authorize(&request(r#"{"method":"config_set","token":"abc","id":1}"#))
+            .is_err());
+    }
+
+    #[test]
+    fn test_authorize_action_uses_action_verb() {
+        let p = policy(true, vec![token("stub", "abc", &["focus"], &[])]);
+        assert!(p
+            .authorize(&request(
+                r#"{"method":"action","params":{"action":"focus:Safari"},"token":"abc","id":1}"#
+            ))
+            .is_ok());
+        assert!(p
+            .authorize(&request(
+                r#"{"method":"action","params":{"action":"kill:Safari"},"token":"abc","id":1}"#
+            ))
+            .is_err());
+    }
+
+    #[test]
+    fn test_authorize_remote_needs_a_token_for_destructive_methods() {
+        let p = policy(false, vec![token("laptop", "abc", &[], &[])]);
+        assert!(p
+            .authorize_remote(&request(r#"{"method":"focus","id":1}"#))
+            .is_ok());
+        for json in [
+            r#"{"method":"kill","id":1}"#,
+            r#"{"method":"config_set","id":1}"#,
+            r#"{"method":"action","params":{"action":"close:Safari"},"id":1}"#,
+        ] {
+            let err = p.authorize_remote(&request(json)).unwrap_err();
+            assert!(err.contains("needs a token"), "{}", err);
+        }
+        assert!(p
+            .authorize_remote(&request(r#"{"method":"kill","token":"abc","id":1}"#))
+            .is_ok());
+    }
+
+    #[test]
+    fn test_method_matches() {
+        assert!(method_matches("*", "anything"));
+        assert!(method_matches("history*", "history_list"));
+        assert!(method_matches("focus", "focus"));
+        assert!(!method_matches("focus", "focused"));
+        assert!(!method_matches("history*", "undo"));
+    }
+
+    #[test]
+    fn test_constant_time_eq() {
+        assert!(constant_time_eq(b"abc", b"abc"));
+        assert!(!constant_time_eq(b"abc", b"abd"));
+        assert!(!constant_time_eq(b"abc", b"abcd"));
+    }
+
+    #[test]
+    fn test_parse_socket_mode() {
+        assert_eq!(parse_socket_mode("600").unwrap(), 0o600);
+        assert_eq!(parse_socket_mode("0600").unwrap(), 0o600);
+        assert_eq!(parse_socket_mode("0o660").unwrap(), 0o660);
+        assert!(parse_socket_mode("0999").is_err());
+        assert!(parse_socket_mode("1777").is_err());
+        assert!(parse_socket_mode("0400").is_err());
+        assert!(parse_socket_mode("rw").is_err());
+    }
+
+    #[test]
+    fn test_bind_private() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let path = std::env::temp_dir().join(format!("cwm_test_bind_{}.sock", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let listener = bind_private(&path).unwrap();
+
+        let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+
+        drop(listener);
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_peer_uid_socketpair() {
+        let (a, _b) = UnixStream::pair().unwrap();
+        let uid = unsafe { libc::getuid() };
+        assert_eq!(peer_uid(&a).unwrap(), uid);
+    }
+}
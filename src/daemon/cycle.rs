@@ -0,0 +1,201 @@
+//! repeated-press cycling through a shortcut's `cycle` steps
+//!
+//! each press of a cycle shortcut runs its next step, as long as the press
+//! targets the window the previous step ran on, that window is still where the
+//! step left it, and the press comes within `settings.cycle_timeout_ms`.
+//! otherwise the cycle starts over at its first step
+
+use std::time::{Duration, Instant};
+
+use crate::config::ACTIONS;
+
+/// listener action of a cycle shortcut, followed by the shortcut's index in `shortcuts`
+pub const ACTION_PREFIX: &str = "cycle:";
+
+/// the actions of a cycle step: `resize:50x100%;move:left` runs `resize:50x100%`,
+/// then `move:left`. a `;` part that does not start with an action name belongs to
+/// the action before it, as in `move:top-left;display=next`
+pub fn step_actions(step: &str) -> Vec<String> {
+    let mut actions: Vec<String> = Vec::new();
+    for part in step.split(';') {
+        let part = part.trim();
+        match actions.last_mut() {
+            Some(action) if !starts_action(part) => {
+                action.push(';');
+                action.push_str(part);
+            }
+            _ => actions.push(part.to_string()),
+        }
+    }
+    actions
+}
+
+fn starts_action(part: &str) -> bool {
+    ACTIONS.iter().any(|spec| {
+        part.strip_prefix(spec.name)
+            .is_some_and(|rest| rest.is_empty() || rest.starts_with(':'))
+    })
+}
+
+/// a window and where it is, to tell whether it changed between presses
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WindowSnapshot {
+    pub pid: i32,
+    pub title: Option<String>,
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32,
+}
+
+#[derive(Debug)]
+struct Last {
+    shortcut: usize,
+    step: usize,
+    window: WindowSnapshot,
+    at: Instant,
+}
+
+/// the last step a cycle shortcut ran
+#[derive(Debug, Default)]
+pub struct Cycles {
+    last: Option<Last>,
+}
+
+impl Cycles {
+    pub const fn new() -> Self {
+        Self { last: None }
+    }
+
+    /// the step a press of `shortcut` (with `len` steps) runs on `window` at `now`
+    pub fn next(
+        &self,
+        shortcut: usize,
+        len: usize,
+        window: Option<&WindowSnapshot>,
+        now: Instant,
+        timeout: Duration,
+    ) -> usize {
+        match &self.last {
+            Some(last)
+                if last.shortcut == shortcut
+                    && Some(&last.window) == window
+                    && now.saturating_duration_since(last.at) <= timeout =>
+            {
+                (last.step + 1) % len.max(1)
+            }
+            _ => 0,
+        }
+    }
+
+    /// remember that `step` of `shortcut` left the window as `window`; without
+    /// a window the next press starts over
+    pub fn ran(
+        &mut self,
+        shortcut: usize,
+        step: usize,
+        window: Option<WindowSnapshot>,
+        now: Instant,
+    ) {
+        self.last = window.map(|window| Last {
+            shortcut,
+            step,
+            window,
+            at: now,
+        });
+    }
+
+    /// forget the last step, e.g. when the shortcuts change
+    pub fn reset(&mut self) {
+        self.last = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TIMEOUT: Duration = Duration::from_millis(2000);
+
+    fn window(x: i32) -> WindowSnapshot {
+        WindowSnapshot {
+            pid: 42,
+            title: Some("notes".to_string()),
+            x,
+            y: 0,
+            width: 800,
+            height: 600,
+        }
+    }
+
+    #[test]
+    fn test_step_actions() {
+        assert_eq!(step_actions("maximize"), vec!["maximize"]);
+        assert_eq!(
+            step_actions("resize:50x100%;move:left"),
+            vec!["resize:50x100%", "move:left"]
+        );
+        assert_eq!(
+            step_actions("move:top-left;display=next; resize:33"),
+            vec!["move:top-left;display=next", "resize:33"]
+        );
+        assert_eq!(
+            step_actions("resize:80;maximize"),
+            vec!["resize:80", "maximize"]
+        );
+        // an action name only counts as a whole word
+        assert_eq!(step_actions("move:left;focused"), vec!["move:left;focused"]);
+    }
+
+    #[test]
+    fn test_cycle_advances_and_wraps() {
+        let mut cycles = Cycles::default();
+        let start = Instant::now();
+
+        assert_eq!(cycles.next(0, 3, Some(&window(0)), start, TIMEOUT), 0);
+        cycles.ran(0, 0, Some(window(0)), start);
+
+        let t = start + Duration::from_millis(500);
+        assert_eq!(cycles.next(0, 3, Some(&window(0)), t, TIMEOUT), 1);
+        cycles.ran(0, 1, Some(window(10)), t);
+
+        let t = t + Duration::from_millis(500);
+        assert_eq!(cycles.next(0, 3, Some(&window(10)), t, TIMEOUT), 2);
+        cycles.ran(0, 2, Some(window(20)), t);
+
+        let t = t + Duration::from_millis(500);
+        assert_eq!(cycles.next(0, 3, Some(&window(20)), t, TIMEOUT), 0);
+    }
+
+    #[test]
+    fn test_cycle_starts_over() {
+        let mut cycles = Cycles::default();
+        let start = Instant::now();
+        cycles.ran(0, 1, Some(window(0)), start);
+        let soon = start + Duration::from_millis(100);
+
+        // the window was moved or resized since
+        assert_eq!(cycles.next(0, 3, Some(&window(5)), soon, TIMEOUT), 0);
+        // another window is targeted
+        let other = WindowSnapshot {
+            pid: 7,
+            ..window(0)
+        };
+        assert_eq!(cycles.next(0, 3, Some(&other), soon, TIMEOUT), 0);
+        assert_eq!(cycles.next(0, 3, None, soon, TIMEOUT), 0);
+        // another shortcut
+        assert_eq!(cycles.next(1, 3, Some(&window(0)), soon, TIMEOUT), 0);
+        // too late
+        let late = start + TIMEOUT + Duration::from_millis(1);
+        assert_eq!(cycles.next(0, 3, Some(&window(0)), late, TIMEOUT), 0);
+        // still the same
+        assert_eq!(cycles.next(0, 3, Some(&window(0)), soon, TIMEOUT), 2);
+
+        cycles.ran(0, 2, None, soon);
+        assert_eq!(cycles.next(0, 3, Some(&window(0)), soon, TIMEOUT), 0);
+
+        cycles.ran(0, 1, Some(window(0)), start);
+        cycles.reset();
+        assert_eq!(cycles.next(0, 3, Some(&window(0)), soon, TIMEOUT), 0);
+    }
+}
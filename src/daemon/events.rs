@@ -11,6 +11,8 @@ use chrono::{DateTime, Utc};
 use serde::Serialize;
 use tokio::sync::mpsc;
 
+use super::metrics::{SubscriberStats, METRICS};
+
 // ============================================================================
 // Event Types
 // ============================================================================
@@ -46,6 +48,18 @@ pub enum EventType {
     /// display was disconnected
     #[serde(rename = "display.disconnected")]
     DisplayDisconnected,
+    /// the first chords of a hotkey sequence were pressed
+    #[serde(rename = "hotkey.pending")]
+    HotkeyPending,
+    /// a pending hotkey sequence was abandoned
+    #[serde(rename = "hotkey.cancelled")]
+    HotkeyCancelled,
+    /// a hotkey mode was entered
+    #[serde(rename = "mode.entered")]
+    ModeEntered,
+    /// a hotkey mode was left
+    #[serde(rename = "mode.exited")]
+    ModeExited,
 }
 
 impl EventType {
@@ -61,6 +75,10 @@ impl EventType {
             EventType::WindowClosed => "window.closed",
             EventType::DisplayConnected => "display.connected",
             EventType::DisplayDisconnected => "display.disconnected",
+            EventType::HotkeyPending => "hotkey.pending",
+            EventType::HotkeyCancelled => "hotkey.cancelled",
+            EventType::ModeEntered => "mode.entered",
+            EventType::ModeExited => "mode.exited",
         }
     }
 
@@ -76,6 +94,10 @@ impl EventType {
             EventType::WindowClosed,
             EventType::DisplayConnected,
             EventType::DisplayDisconnected,
+            EventType::HotkeyPending,
+            EventType::HotkeyCancelled,
+            EventType::ModeEntered,
+            EventType::ModeExited,
         ]
     }
 
@@ -91,6 +113,10 @@ impl EventType {
             EventType::WindowClosed => "Window was closed by cwm",
             EventType::DisplayConnected => "Display was connected",
             EventType::DisplayDisconnected => "Display was disconnected",
+            EventType::HotkeyPending => "Hotkey sequence is waiting for its next chord",
+            EventType::HotkeyCancelled => "Hotkey sequence was cancelled",
+            EventType::ModeEntered => "Hotkey mode was entered",
+            EventType::ModeExited => "Hotkey mode was exited",
         }
     }
 
@@ -106,6 +132,10 @@ impl EventType {
             "window.closed" => Some(EventType::WindowClosed),
             "display.connected" => Some(EventType::DisplayConnected),
             "display.disconnected" => Some(EventType::DisplayDisconnected),
+            "hotkey.pending" => Some(EventType::HotkeyPending),
+            "hotkey.cancelled" => Some(EventType::HotkeyCancelled),
+            "mode.entered" => Some(EventType::ModeEntered),
+            "mode.exited" => Some(EventType::ModeExited),
             _ => None,
         }
     }
@@ -187,6 +217,22 @@ pub enum EventData {
         is_builtin: bool,
         aliases: Vec<String>,
     },
+
+    /// hotkey sequence event data (hotkey.pending, hotkey.cancelled)
+    Hotkey {
+        keys: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        next: Option<Vec<String>>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        reason: Option<String>,
+    },
+
+    /// hotkey mode event data (mode.entered, mode.exited)
+    Mode {
+        mode: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        reason: Option<String>,
+    },
 }
 
 impl EventData {
@@ -344,6 +390,8 @@ impl EventData {
             EventData::AppTerminated { app, .. } => Some(app),
             EventData::Window { app, .. } => Some(app),
             EventData::Display { .. } => None,
+            EventData::Hotkey { .. } => None,
+            EventData::Mode { .. } => None,
         }
     }
 
@@ -354,6 +402,8 @@ impl EventData {
             EventData::AppTerminated { .. } => None,
             EventData::Window { titles, .. } => titles.as_deref(),
             EventData::Display { .. } => None,
+            EventData::Hotkey { .. } => None,
+            EventData::Mode { .. } => None,
         }
     }
 }
@@ -513,6 +563,49 @@ impl Event {
         )
     }
 
+    /// create hotkey.pending event
+    pub fn hotkey_pending(keys: String, next: Vec<String>) -> Self {
+        Self::new(
+            EventType::HotkeyPending,
+            EventData::Hotkey {
+                keys,
+                next: Some(next),
+                reason: None,
+            },
+        )
+    }
+
+    /// create hotkey.cancelled event
+    pub fn hotkey_cancelled(keys: String, reason: String) -> Self {
+        Self::new(
+            EventType::HotkeyCancelled,
+            EventData::Hotkey {
+                keys,
+                next: None,
+                reason: Some(reason),
+            },
+        )
+    }
+
+    /// create mode.entered event
+    pub fn mode_entered(mode: String) -> Self {
+        Self::new(
+            EventType::ModeEntered,
+            EventData::Mode { mode, reason: None },
+        )
+    }
+
+    /// create mode.exited event
+    pub fn mode_exited(mode: String, reason: String) -> Self {
+        Self::new(
+            EventType::ModeExited,
+            EventData::Mode {
+                mode,
+                reason: Some(reason),
+            },
+        )
+    }
+
     /// check if this event matches the given filters
     pub fn matches_filters(&self, event_filters: &[String], app_filters: &[String]) -> bool {
         // check event type filter
@@ -628,6 +721,10 @@ struct Subscriber {
     app_filters: Vec<String>,
     /// channel to send events to
     sender: mpsc::UnboundedSender<Event>,
+    /// events sent to this subscriber
+    delivered: AtomicU64,
+    /// events that couldn't be sent (subscriber disconnected)
+    dropped: AtomicU64,
 }
 
 impl Subscriber {
@@ -650,6 +747,8 @@ pub struct EventBus {
     /// recent events for debugging (limited buffer)
     #[allow(dead_code)]
     recent_events: Mutex<Vec<Event>>,
+    /// events dropped across all subscribers, including removed ones
+    dropped: AtomicU64,
 }
 
 impl EventBus {
@@ -659,6 +758,7 @@ impl EventBus {
             next_id: AtomicU64::new(1),
             subscribers: RwLock::new(HashMap::new()),
             recent_events: Mutex::new(Vec::new()),
+            dropped: AtomicU64::new(0),
         }
     }
 
@@ -677,6 +777,8 @@ impl EventBus {
             event_filters,
             app_filters,
             sender,
+            delivered: AtomicU64::new(0),
+            dropped: AtomicU64::new(0),
         };
 
         if let Ok(mut subs) = self.subscribers.write() {
@@ -708,8 +810,13 @@ impl EventBus {
         if let Ok(subs) = self.subscribers.read() {
             for subscriber in subs.values() {
                 if subscriber.wants_event(&event) {
-                    // ignore send errors (subscriber disconnected)
-                    let _ = subscriber.sender.send(event.clone());
+                    // send errors mean the subscriber disconnected and is about to be removed
+                    if subscriber.sender.send(event.clone()).is_ok() {
+                        subscriber.delivered.fetch_add(1, Ordering::Relaxed);
+                    } else {
+                        subscriber.dropped.fetch_add(1, Ordering::Relaxed);
+                        self.dropped.fetch_add(1, Ordering::Relaxed);
+                    }
                 }
             }
         }
@@ -721,6 +828,31 @@ impl EventBus {
         self.subscribers.read().map(|s| s.len()).unwrap_or(0)
     }
 
+    /// delivery counters for each active subscriber, ordered by id
+    pub fn subscriber_stats(&self) -> Vec<SubscriberStats> {
+        let mut stats: Vec<SubscriberStats> = self
+            .subscribers
+            .read()
+            .map(|subs| {
+                subs.values()
+                    .map(|s| SubscriberStats {
+                        id: s.id,
+                        events: s.event_filters.clone(),
+                        delivered: s.delivered.load(Ordering::Relaxed),
+                        dropped: s.dropped.load(Ordering::Relaxed),
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+        stats.sort_by_key(|s| s.id);
+        stats
+    }
+
+    /// total events dropped since the bus was created
+    pub fn dropped_count(&self) -> u64 {
+        self.dropped.load(Ordering::Relaxed)
+    }
+
     /// expand filter patterns to actual event types
     pub fn expand_filters(filters: &[String]) -> Vec<String> {
         if filters.is_empty() {
@@ -775,6 +907,7 @@ lazy_static::lazy_static! {
 
 /// emit an event to the global event bus
 pub fn emit(event: Event) {
+    METRICS.record_event(event.event_type.as_str());
     EVENT_BUS.emit(event);
 }
 
@@ -989,6 +1122,34 @@ mod tests {
         assert!(rx.try_recv().is_err());
     }
 
+    #[test]
+    fn test_event_bus_subscriber_stats() {
+        let bus = EventBus::new();
+
+        let (first, rx) = bus.subscribe(vec!["app.*".to_string()], vec![]);
+        let (second, _rx2) = bus.subscribe(vec![], vec![]);
+
+        bus.emit(Event::app_launched("Safari".to_string(), 1234));
+
+        // a disconnected subscriber that hasn't been removed yet
+        drop(rx);
+        bus.emit(Event::app_launched("Slack".to_string(), 5678));
+
+        let stats = bus.subscriber_stats();
+        assert_eq!(stats.len(), 2);
+        assert_eq!(stats[0].id, first);
+        assert_eq!(stats[0].events, vec!["app.*"]);
+        assert_eq!(stats[0].delivered, 1);
+        assert_eq!(stats[0].dropped, 1);
+        assert_eq!(stats[1].id, second);
+        assert_eq!(stats[1].delivered, 2);
+        assert_eq!(stats[1].dropped, 0);
+
+        // the drop total survives unsubscribing
+        bus.unsubscribe(first);
+        assert_eq!(bus.dropped_count(), 1);
+    }
+
     #[test]
     fn test_event_bus_expand_filters() {
         // empty = all events
@@ -1083,6 +1244,49 @@ mod tests {
         assert!(!event.matches_filters(&[], &["Safari".to_string()]));
     }
 
+    #[test]
+    fn test_hotkey_event_serialization() {
+        let pending = Event::hotkey_pending(
+            "ctrl+alt+w".to_string(),
+            vec!["h".to_string(), "l".to_string()],
+        );
+        let json = serde_json::to_value(&pending).unwrap();
+        assert_eq!(json["type"], "hotkey.pending");
+        assert_eq!(
+            json["data"],
+            serde_json::json!({"keys": "ctrl+alt+w", "next": ["h", "l"]})
+        );
+
+        let cancelled = Event::hotkey_cancelled("ctrl+alt+w, x".to_string(), "unbound".to_string());
+        let json = serde_json::to_value(&cancelled).unwrap();
+        assert_eq!(json["type"], "hotkey.cancelled");
+        assert_eq!(
+            json["data"],
+            serde_json::json!({"keys": "ctrl+alt+w, x", "reason": "unbound"})
+        );
+
+        assert!(cancelled.matches_filters(&["hotkey.*".to_string()], &[]));
+        assert!(!cancelled.matches_filters(&[], &["Safari".to_string()]));
+    }
+
+    #[test]
+    fn test_mode_event_serialization() {
+        let entered = Event::mode_entered("resize".to_string());
+        let json = serde_json::to_value(&entered).unwrap();
+        assert_eq!(json["type"], "mode.entered");
+        assert_eq!(json["data"], serde_json::json!({"mode": "resize"}));
+
+        let exited = Event::mode_exited("resize".to_string(), "timeout".to_string());
+        let json = serde_json::to_value(&exited).unwrap();
+        assert_eq!(json["type"], "mode.exited");
+        assert_eq!(
+            json["data"],
+            serde_json::json!({"mode": "resize", "reason": "timeout"})
+        );
+
+        assert!(exited.matches_filters(&["mode.*".to_string()], &[]));
+    }
+
     #[test]
     fn test_event_bus_expand_filters_display() {
         let expanded = EventBus::expand_filters(&["display.*".to_string()]);
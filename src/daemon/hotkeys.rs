@@ -1,6 +1,10 @@
 use anyhow::{anyhow, Result};
 use std::fmt;
 
+use super::keyboard::{self, Layout};
+use super::keymap::{Keymap, Press, Signal};
+use super::mouse::{MouseEvent, ScrollDirection};
+
 #[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
 pub struct Modifiers {
     pub ctrl: bool,
@@ -17,7 +21,15 @@ pub struct Hotkey {
 
 impl Hotkey {
     /// Parse a hotkey string like "ctrl+alt+s" or "cmd+shift+s+f"
+    ///
+    /// keys are the characters on the active keyboard layout; `key:KeyZ` names
+    /// a physical key and is stored as the layout's name for it
     pub fn parse(s: &str) -> Result<Self> {
+        Self::parse_in(s, keyboard::active())
+    }
+
+    /// Parse a hotkey string with its keys named as on `layout`
+    pub fn parse_in(s: &str, layout: &Layout) -> Result<Self> {
         let trimmed = s.trim();
         if trimmed.is_empty() {
             return Err(anyhow!("Empty hotkey string"));
@@ -39,7 +51,7 @@ impl Hotkey {
                 "shift" => modifiers.shift = true,
                 "" => {} // skip empty parts
                 _ => {
-                    keys.push(part.clone());
+                    keys.push(layout.canonical(part)?);
                 }
             }
         }
@@ -50,6 +62,20 @@ impl Hotkey {
 
         Ok(Hotkey { modifiers, keys })
     }
+
+    /// the hotkey with physical key names, e.g. "ctrl+alt+key:KeyY" for
+    /// "ctrl+alt+z" on a German layout; keys without a character stay as they are
+    pub fn physical(&self) -> Hotkey {
+        let layout = keyboard::active();
+        Hotkey {
+            modifiers: self.modifiers,
+            keys: self
+                .keys
+                .iter()
+                .map(|key| layout.physical(key).unwrap_or_else(|| key.clone()))
+                .collect(),
+        }
+    }
 }
 
 impl fmt::Display for Hotkey {
@@ -77,96 +103,16 @@ impl fmt::Display for Hotkey {
     }
 }
 
-/// Convert a macOS virtual keycode to a key name
-fn keycode_to_string(keycode: i64) -> Option<String> {
+/// Convert a macOS virtual keycode to a key name on the active keyboard layout
+pub(crate) fn keycode_to_string(keycode: i64) -> Option<String> {
     let key = match keycode {
-        0 => "a",
-        1 => "s",
-        2 => "d",
-        3 => "f",
-        4 => "h",
-        5 => "g",
-        6 => "z",
-        7 => "x",
-        8 => "c",
-        9 => "v",
-        11 => "b",
-        12 => "q",
-        13 => "w",
-        14 => "e",
-        15 => "r",
-        16 => "y",
-        17 => "t",
-        18 => "1",
-        19 => "2",
-        20 => "3",
-        21 => "4",
-        22 => "6",
-        23 => "5",
-        24 => "=",
-        25 => "9",
-        26 => "7",
-        27 => "-",
-        28 => "8",
-        29 => "0",
-        30 => "]",
-        31 => "o",
-        32 => "u",
-        33 => "[",
-        34 => "i",
-        35 => "p",
-        36 => "return",
-        37 => "l",
-        38 => "j",
-        39 => "'",
-        40 => "k",
-        41 => ";",
-        42 => "\\",
-        43 => ",",
-        44 => "/",
-        45 => "n",
-        46 => "m",
-        47 => ".",
-        48 => "tab",
-        49 => "space",
-        50 => "`",
-        51 => "backspace",
-        53 => "escape",
         55 => "cmd",
-        56 => "shift",
+        56 | 60 => "shift",
         57 => "capslock",
-        58 => "alt",
-        59 => "ctrl",
-        60 => "shift",
-        61 => "alt",
-        62 => "ctrl",
+        58 | 61 => "alt",
+        59 | 62 => "ctrl",
         63 => "fn",
-        96 => "f5",
-        97 => "f6",
-        98 => "f7",
-        99 => "f3",
-        100 => "f8",
-        101 => "f9",
-        103 => "f11",
-        105 => "f13",
-        107 => "f14",
-        109 => "f10",
-        111 => "f12",
-        113 => "f15",
-        114 => "help",
-        115 => "home",
-        116 => "pageup",
-        117 => "delete",
-        118 => "f4",
-        119 => "end",
-        120 => "f2",
-        121 => "pagedown",
-        122 => "f1",
-        123 => "left",
-        124 => "right",
-        125 => "down",
-        126 => "up",
-        _ => return None,
+        _ => keyboard::active().key_name(keycode)?,
     };
 
     Some(key.to_string())
@@ -177,11 +123,24 @@ fn is_modifier_key(keycode: i64) -> bool {
     matches!(keycode, 55..=63)
 }
 
+/// whether the listener can report `key` (a lowercase key name on `layout`, not a modifier)
+pub fn is_known_key(key: &str, layout: &Layout) -> bool {
+    layout
+        .keycode(key)
+        .is_some_and(|keycode| !is_modifier_key(keycode))
+}
+
 mod macos {
     use super::*;
     use std::collections::BTreeSet;
     use std::io::{self, Write};
     use std::sync::atomic::{AtomicBool, AtomicI32, AtomicPtr, Ordering};
+    use std::sync::Mutex;
+    use std::time::Instant;
+
+    use super::super::trigger::{ModifierKey, ModifierKind, Side};
+    use crate::window::drag::Point;
+    use crate::window::matching;
 
     // modifier flags
     const K_CG_EVENT_FLAG_MASK_CONTROL: u64 = 0x00040000;
@@ -189,6 +148,16 @@ mod macos {
     const K_CG_EVENT_FLAG_MASK_COMMAND: u64 = 0x00100000;
     const K_CG_EVENT_FLAG_MASK_SHIFT: u64 = 0x00020000;
 
+    // device-dependent flags telling the left and right modifier keys apart
+    const NX_DEVICE_LCTL_KEY_MASK: u64 = 0x00000001;
+    const NX_DEVICE_LSHIFT_KEY_MASK: u64 = 0x00000002;
+    const NX_DEVICE_RSHIFT_KEY_MASK: u64 = 0x00000004;
+    const NX_DEVICE_LCMD_KEY_MASK: u64 = 0x00000008;
+    const NX_DEVICE_RCMD_KEY_MASK: u64 = 0x00000010;
+    const NX_DEVICE_LALT_KEY_MASK: u64 = 0x00000020;
+    const NX_DEVICE_RALT_KEY_MASK: u64 = 0x00000040;
+    const NX_DEVICE_RCTL_KEY_MASK: u64 = 0x00002000;
+
     /// Extract modifiers from flags
     fn extract_modifiers(flags: u64) -> Modifiers {
         Modifiers {
@@ -199,6 +168,26 @@ mod macos {
         }
     }
 
+    /// the modifier key a flags-changed event is about, and whether it is now down
+    fn modifier_change(keycode: i64, flags: u64) -> Option<(ModifierKey, bool)> {
+        let (kind, side, mask) = match keycode {
+            54 => (ModifierKind::Cmd, Side::Right, NX_DEVICE_RCMD_KEY_MASK),
+            55 => (ModifierKind::Cmd, Side::Left, NX_DEVICE_LCMD_KEY_MASK),
+            56 => (ModifierKind::Shift, Side::Left, NX_DEVICE_LSHIFT_KEY_MASK),
+            58 => (ModifierKind::Alt, Side::Left, NX_DEVICE_LALT_KEY_MASK),
+            59 => (ModifierKind::Ctrl, Side::Left, NX_DEVICE_LCTL_KEY_MASK),
+            60 => (ModifierKind::Shift, Side::Right, NX_DEVICE_RSHIFT_KEY_MASK),
+            61 => (ModifierKind::Alt, Side::Right, NX_DEVICE_RALT_KEY_MASK),
+            62 => (ModifierKind::Ctrl, Side::Right, NX_DEVICE_RCTL_KEY_MASK),
+            _ => return None,
+        };
+        let key = ModifierKey {
+            kind,
+            side: Some(side),
+        };
+        Some((key, flags & mask != 0))
+    }
+
     /// Build display string for current state
     fn build_display_string(modifiers: &Modifiers, keys: &BTreeSet<String>) -> String {
         let mut parts: Vec<&str> = Vec::new();
@@ -233,6 +222,7 @@ mod macos {
     type CFMachPortRef = *mut std::ffi::c_void;
     type CFRunLoopSourceRef = *mut std::ffi::c_void;
     type CFRunLoopRef = *mut std::ffi::c_void;
+    type CFRunLoopTimerRef = *mut std::ffi::c_void;
 
     type CGEventTapCallBack = extern "C" fn(
         proxy: CGEventTapProxy,
@@ -241,9 +231,22 @@ mod macos {
         user_info: *mut std::ffi::c_void,
     ) -> CGEventRef;
 
+    type CFRunLoopTimerCallBack =
+        extern "C" fn(timer: CFRunLoopTimerRef, info: *mut std::ffi::c_void);
+
+    const K_CG_EVENT_LEFT_MOUSE_DOWN: u32 = 1;
+    const K_CG_EVENT_LEFT_MOUSE_UP: u32 = 2;
+    const K_CG_EVENT_RIGHT_MOUSE_DOWN: u32 = 3;
+    const K_CG_EVENT_RIGHT_MOUSE_UP: u32 = 4;
+    const K_CG_EVENT_LEFT_MOUSE_DRAGGED: u32 = 6;
+    const K_CG_EVENT_RIGHT_MOUSE_DRAGGED: u32 = 7;
     const K_CG_EVENT_KEY_DOWN: u32 = 10;
     const K_CG_EVENT_KEY_UP: u32 = 11;
     const K_CG_EVENT_FLAGS_CHANGED: u32 = 12;
+    const K_CG_EVENT_SCROLL_WHEEL: u32 = 22;
+    const K_CG_EVENT_OTHER_MOUSE_DOWN: u32 = 25;
+    const K_CG_EVENT_OTHER_MOUSE_UP: u32 = 26;
+    const K_CG_EVENT_OTHER_MOUSE_DRAGGED: u32 = 27;
     const K_CG_EVENT_TAP_DISABLED_BY_TIMEOUT: u32 = 0xFFFFFFFE;
     const K_CG_HID_EVENT_TAP: u32 = 0;
     const K_CG_HEAD_INSERT_EVENT_TAP: u32 = 0;
@@ -263,6 +266,7 @@ mod macos {
         fn CGEventTapEnable(tap: CFMachPortRef, enable: bool);
         fn CGEventGetIntegerValueField(event: CGEventRef, field: u32) -> i64;
         fn CGEventGetFlags(event: CGEventRef) -> u64;
+        fn CGEventGetLocation(event: CGEventRef) -> core_graphics::geometry::CGPoint;
     }
 
     #[link(name = "CoreFoundation", kind = "framework")]
@@ -279,17 +283,49 @@ mod macos {
             source: CFRunLoopSourceRef,
             mode: *const std::ffi::c_void,
         );
+        fn CFRunLoopRemoveSource(
+            rl: CFRunLoopRef,
+            source: CFRunLoopSourceRef,
+            mode: *const std::ffi::c_void,
+        );
+        fn CFMachPortInvalidate(port: CFMachPortRef);
         fn CFRunLoopRun();
         fn CFRunLoopStop(rl: CFRunLoopRef);
         fn CFRelease(cf: *const std::ffi::c_void);
 
+        fn CFAbsoluteTimeGetCurrent() -> f64;
+        fn CFRunLoopTimerCreate(
+            allocator: *const std::ffi::c_void,
+            fire_date: f64,
+            interval: f64,
+            flags: u64,
+            order: i64,
+            callout: CFRunLoopTimerCallBack,
+            context: *mut std::ffi::c_void,
+        ) -> CFRunLoopTimerRef;
+        fn CFRunLoopAddTimer(
+            rl: CFRunLoopRef,
+            timer: CFRunLoopTimerRef,
+            mode: *const std::ffi::c_void,
+        );
+        fn CFRunLoopTimerSetNextFireDate(timer: CFRunLoopTimerRef, fire_date: f64);
+        fn CFRunLoopTimerInvalidate(timer: CFRunLoopTimerRef);
+
         static kCFRunLoopCommonModes: *const std::ffi::c_void;
     }
 
     #[link(name = "AppKit", kind = "framework")]
     extern "C" {}
 
+    const K_CG_KEYBOARD_EVENT_AUTOREPEAT: u32 = 8;
     const K_CG_KEYBOARD_EVENT_KEYCODE: u32 = 9;
+    const K_CG_MOUSE_EVENT_BUTTON_NUMBER: u32 = 3;
+    const K_CG_SCROLL_WHEEL_EVENT_DELTA_AXIS_1: u32 = 11;
+    const K_CG_SCROLL_WHEEL_EVENT_DELTA_AXIS_2: u32 = 12;
+    const K_CG_SCROLL_WHEEL_EVENT_MOMENTUM_PHASE: u32 = 123;
+
+    // the sequence timeout timer repeats this rarely, so it only fires when rescheduled
+    const IDLE_TIMER_INTERVAL_SECS: f64 = 1.0e9;
 
     // recording state -- only accessed from the event callback (single run loop thread)
     // and from record_hotkey_impl before/after the run loop runs
@@ -551,16 +587,19 @@ mod macos {
     }
 
     // type alias for listener callback to reduce complexity
-    type ListenerCallback = Box<dyn Fn(&str, &Hotkey) + Send>;
+    type ListenerCallback = Box<dyn Fn(&Signal) + Send>;
 
     // listener cross-thread state -- accessed from start/stop on different threads
     static LISTENER_RUNNING: AtomicBool = AtomicBool::new(false);
     static LISTENER_RUN_LOOP: AtomicPtr<std::ffi::c_void> = AtomicPtr::new(std::ptr::null_mut());
     static LISTENER_EVENT_TAP: AtomicPtr<std::ffi::c_void> = AtomicPtr::new(std::ptr::null_mut());
+    static LISTENER_SOURCE: AtomicPtr<std::ffi::c_void> = AtomicPtr::new(std::ptr::null_mut());
+    static LISTENER_TIMER: AtomicPtr<std::ffi::c_void> = AtomicPtr::new(std::ptr::null_mut());
+    // updated from other threads when the daemon switches profiles
+    static LISTENER_KEYMAP: Mutex<Option<Keymap>> = Mutex::new(None);
 
     // listener callback-only state -- only accessed from listener_callback or during
     // init/cleanup when the run loop is not running
-    static mut LISTENER_SHORTCUTS: Option<Vec<(Hotkey, String)>> = None;
     static mut LISTENER_CALLBACK: Option<ListenerCallback> = None;
     static mut LISTENER_PRESSED_KEYS: Option<BTreeSet<String>> = None;
     static mut LISTENER_MODIFIERS: Modifiers = Modifiers {
@@ -570,32 +609,119 @@ mod macos {
         shift: false,
     };
 
+    /// fire the sequence or mode timeout timer at `deadline`
+    fn schedule_timeout(deadline: Instant) {
+        let timer = LISTENER_TIMER.load(Ordering::SeqCst);
+        if timer.is_null() {
+            return;
+        }
+        let delay = deadline.saturating_duration_since(Instant::now());
+        unsafe {
+            CFRunLoopTimerSetNextFireDate(timer, CFAbsoluteTimeGetCurrent() + delay.as_secs_f64());
+        }
+    }
+
     #[allow(static_mut_refs)]
-    fn check_hotkey_match(
-        modifiers: &Modifiers,
-        keys: &BTreeSet<String>,
-    ) -> Option<(String, Hotkey)> {
+    fn notify(signals: &[Signal]) {
         unsafe {
-            if let Some(ref shortcuts) = LISTENER_SHORTCUTS {
-                for (hotkey, action) in shortcuts {
-                    // check modifiers match
-                    if hotkey.modifiers.ctrl != modifiers.ctrl
-                        || hotkey.modifiers.alt != modifiers.alt
-                        || hotkey.modifiers.cmd != modifiers.cmd
-                        || hotkey.modifiers.shift != modifiers.shift
-                    {
-                        continue;
-                    }
+            if let Some(ref callback) = LISTENER_CALLBACK {
+                for signal in signals {
+                    callback(signal);
+                }
+            }
+        }
+    }
 
-                    // check all hotkey keys are pressed
-                    let hotkey_keys: BTreeSet<String> = hotkey.keys.iter().cloned().collect();
-                    if hotkey_keys == *keys {
-                        return Some((action.clone(), hotkey.clone()));
+    /// expire the keymap, pass it an event and reschedule the timer, then report the signals
+    fn dispatch(event: impl FnOnce(&mut Keymap, Instant) -> Vec<Signal>) {
+        let now = Instant::now();
+        let signals = match LISTENER_KEYMAP.lock() {
+            Ok(mut guard) => match guard.as_mut() {
+                Some(keymap) => {
+                    let mut signals = keymap.expire(now);
+                    signals.extend(event(keymap, now));
+                    if let Some(deadline) = keymap.deadline() {
+                        schedule_timeout(deadline);
                     }
+                    signals
+                }
+                None => Vec::new(),
+            },
+            Err(_) => Vec::new(),
+        };
+
+        // the lock is released: handling a signal can replace the keymap
+        notify(&signals);
+    }
+
+    extern "C" fn timeout_callback(_timer: CFRunLoopTimerRef, _info: *mut std::ffi::c_void) {
+        // the timer can fire a little early, and a mode can outlive a sequence;
+        // dispatch reschedules it for the next deadline
+        dispatch(|_, _| Vec::new());
+    }
+
+    /// the mouse event `event` is, with buttons numbered from 1; trackpad
+    /// momentum scrolling after the fingers are lifted is not
+    unsafe fn mouse_event(event_type: u32, event: CGEventRef) -> Option<MouseEvent> {
+        let button =
+            || CGEventGetIntegerValueField(event, K_CG_MOUSE_EVENT_BUTTON_NUMBER) as u32 + 1;
+        match event_type {
+            K_CG_EVENT_LEFT_MOUSE_DOWN
+            | K_CG_EVENT_RIGHT_MOUSE_DOWN
+            | K_CG_EVENT_OTHER_MOUSE_DOWN => Some(MouseEvent::Down(button())),
+            K_CG_EVENT_LEFT_MOUSE_UP | K_CG_EVENT_RIGHT_MOUSE_UP | K_CG_EVENT_OTHER_MOUSE_UP => {
+                Some(MouseEvent::Up(button()))
+            }
+            K_CG_EVENT_LEFT_MOUSE_DRAGGED
+            | K_CG_EVENT_RIGHT_MOUSE_DRAGGED
+            | K_CG_EVENT_OTHER_MOUSE_DRAGGED => Some(MouseEvent::Dragged(button())),
+            K_CG_EVENT_SCROLL_WHEEL => {
+                if CGEventGetIntegerValueField(event, K_CG_SCROLL_WHEEL_EVENT_MOMENTUM_PHASE) != 0 {
+                    return None;
                 }
+                let vertical =
+                    CGEventGetIntegerValueField(event, K_CG_SCROLL_WHEEL_EVENT_DELTA_AXIS_1);
+                let horizontal =
+                    CGEventGetIntegerValueField(event, K_CG_SCROLL_WHEEL_EVENT_DELTA_AXIS_2);
+                let direction = if vertical > 0 {
+                    ScrollDirection::Up
+                } else if vertical < 0 {
+                    ScrollDirection::Down
+                } else if horizontal > 0 {
+                    ScrollDirection::Left
+                } else if horizontal < 0 {
+                    ScrollDirection::Right
+                } else {
+                    return None;
+                };
+                Some(MouseEvent::Scroll(direction))
+            }
+            _ => None,
+        }
+    }
+
+    /// pass a mouse event to the keymap's mouse bindings, swallowing it if one takes it
+    unsafe fn handle_mouse(mouse: MouseEvent, event: CGEventRef) -> CGEventRef {
+        let modifiers = extract_modifiers(CGEventGetFlags(event));
+        let location = CGEventGetLocation(event);
+        let at = Point::new(location.x, location.y);
+
+        let press = match LISTENER_KEYMAP.lock() {
+            Ok(mut guard) => match guard.as_mut() {
+                Some(keymap) => keymap.mouse(modifiers, mouse, at, matching::get_frontmost_app),
+                None => Press::Pass,
+            },
+            Err(_) => Press::Pass,
+        };
+
+        // the lock is released: handling a signal can replace the keymap
+        match press {
+            Press::Consumed(signals) => {
+                notify(&signals);
+                std::ptr::null_mut()
             }
+            Press::Pass => event,
         }
-        None
     }
 
     #[allow(static_mut_refs)]
@@ -615,6 +741,10 @@ mod macos {
                 return event;
             }
 
+            if let Some(mouse) = mouse_event(event_type, event) {
+                return handle_mouse(mouse, event);
+            }
+
             let keycode = CGEventGetIntegerValueField(event, K_CG_KEYBOARD_EVENT_KEYCODE);
             let flags = CGEventGetFlags(event);
 
@@ -633,13 +763,45 @@ mod macos {
                             if let Some(ref mut keys) = LISTENER_PRESSED_KEYS {
                                 keys.insert(key);
 
-                                // check for hotkey match
-                                if let Some((action, hotkey)) =
-                                    check_hotkey_match(&LISTENER_MODIFIERS, keys)
-                                {
-                                    if let Some(ref callback) = LISTENER_CALLBACK {
-                                        callback(&action, &hotkey);
-                                    }
+                                let chord = Hotkey {
+                                    modifiers: LISTENER_MODIFIERS,
+                                    keys: keys.iter().cloned().collect(),
+                                };
+                                let autorepeat = CGEventGetIntegerValueField(
+                                    event,
+                                    K_CG_KEYBOARD_EVENT_AUTOREPEAT,
+                                ) != 0;
+
+                                let Ok(mut guard) = LISTENER_KEYMAP.lock() else {
+                                    return event;
+                                };
+                                let Some(keymap) = guard.as_mut() else {
+                                    return event;
+                                };
+
+                                // holding the leader or a double-tap or hold down is
+                                // not pressing it again
+                                if autorepeat && keymap.swallows_repeat(&chord) {
+                                    return std::ptr::null_mut();
+                                }
+
+                                let now = Instant::now();
+                                let expired = keymap.expire(now);
+                                // a shortcut held down repeats by its `repeat`
+                                let press = if autorepeat {
+                                    keymap.repeat(&chord, now, matching::get_frontmost_app)
+                                } else {
+                                    keymap.press(&chord, now, matching::get_frontmost_app)
+                                };
+                                let deadline = keymap.deadline();
+                                drop(guard);
+
+                                if let Some(deadline) = deadline {
+                                    schedule_timeout(deadline);
+                                }
+                                notify(&expired);
+                                if let Press::Consumed(signals) = press {
+                                    notify(&signals);
                                     // consume the event to prevent it from propagating
                                     return std::ptr::null_mut();
                                 }
@@ -653,11 +815,17 @@ mod macos {
                             if let Some(ref mut keys) = LISTENER_PRESSED_KEYS {
                                 keys.remove(&key);
                             }
+                            dispatch(|keymap, now| keymap.release(&key, now));
                         }
                     }
                 }
                 K_CG_EVENT_FLAGS_CHANGED => {
-                    // modifiers already updated above
+                    // modifiers already updated above; this is for lone modifier triggers
+                    if let Some((key, down)) = modifier_change(keycode, flags) {
+                        dispatch(|keymap, now| {
+                            keymap.modifier(key, down, now, matching::get_frontmost_app)
+                        });
+                    }
                 }
                 _ => {}
             }
@@ -668,16 +836,17 @@ mod macos {
     }
 
     #[allow(static_mut_refs)]
-    pub fn start_listener_impl<F>(shortcuts: Vec<(Hotkey, String)>, callback: F) -> Result<()>
+    pub fn start_listener_impl<F>(keymap: Keymap, callback: F) -> Result<()>
     where
-        F: Fn(&str, &Hotkey) + Send + 'static,
+        F: Fn(&Signal) + Send + 'static,
     {
         if LISTENER_RUNNING.load(Ordering::SeqCst) {
             return Err(anyhow!("Listener already running"));
         }
 
+        let mouse = keymap.has_mouse();
         unsafe {
-            LISTENER_SHORTCUTS = Some(shortcuts);
+            set_keymap(Some(keymap));
             LISTENER_CALLBACK = Some(Box::new(callback));
             LISTENER_PRESSED_KEYS = Some(BTreeSet::new());
             LISTENER_MODIFIERS = Modifiers::default();
@@ -685,61 +854,54 @@ mod macos {
         LISTENER_RUNNING.store(true, Ordering::SeqCst);
 
         unsafe {
-            // event mask for key events
-            let event_mask: u64 = (1 << K_CG_EVENT_KEY_DOWN)
-                | (1 << K_CG_EVENT_KEY_UP)
-                | (1 << K_CG_EVENT_FLAGS_CHANGED);
+            let run_loop = CFRunLoopGetCurrent();
+            let (tap, source) = match add_tap(run_loop, mouse) {
+                Ok(tap) => tap,
+                Err(e) => {
+                    LISTENER_RUNNING.store(false, Ordering::SeqCst);
+                    set_keymap(None);
+                    LISTENER_CALLBACK = None;
+                    return Err(e);
+                }
+            };
+            LISTENER_EVENT_TAP.store(tap, Ordering::SeqCst);
+            LISTENER_SOURCE.store(source, Ordering::SeqCst);
+            LISTENER_RUN_LOOP.store(run_loop, Ordering::SeqCst);
 
-            // create event tap
-            let tap = CGEventTapCreate(
-                K_CG_HID_EVENT_TAP,
-                K_CG_HEAD_INSERT_EVENT_TAP,
-                K_CG_EVENT_TAP_OPTION_DEFAULT,
-                event_mask,
-                listener_callback,
+            // timer for pending sequences and mode timeouts, rescheduled on each chord
+            let timer = CFRunLoopTimerCreate(
+                std::ptr::null(),
+                CFAbsoluteTimeGetCurrent() + IDLE_TIMER_INTERVAL_SECS,
+                IDLE_TIMER_INTERVAL_SECS,
+                0,
+                0,
+                timeout_callback,
                 std::ptr::null_mut(),
             );
-
-            if tap.is_null() {
-                LISTENER_RUNNING.store(false, Ordering::SeqCst);
-                LISTENER_SHORTCUTS = None;
-                LISTENER_CALLBACK = None;
-                return Err(anyhow!(
-                    "Failed to create event tap. Make sure accessibility permissions are granted."
-                ));
-            }
-
-            LISTENER_EVENT_TAP.store(tap, Ordering::SeqCst);
-
-            // create run loop source
-            let source = CFMachPortCreateRunLoopSource(std::ptr::null(), tap, 0);
-            if source.is_null() {
-                CFRelease(tap);
-                LISTENER_EVENT_TAP.store(std::ptr::null_mut(), Ordering::SeqCst);
-                LISTENER_RUNNING.store(false, Ordering::SeqCst);
-                LISTENER_SHORTCUTS = None;
-                LISTENER_CALLBACK = None;
-                return Err(anyhow!("Failed to create run loop source"));
+            if !timer.is_null() {
+                CFRunLoopAddTimer(run_loop, timer, kCFRunLoopCommonModes);
+                LISTENER_TIMER.store(timer, Ordering::SeqCst);
             }
 
-            // add to run loop
-            let run_loop = CFRunLoopGetCurrent();
-            LISTENER_RUN_LOOP.store(run_loop, Ordering::SeqCst);
-            CFRunLoopAddSource(run_loop, source, kCFRunLoopCommonModes);
-
-            // enable tap
-            CGEventTapEnable(tap, true);
-
             // run the loop - this blocks until stopped
             CFRunLoopRun();
 
-            // cleanup
+            // cleanup; the keymap lock keeps an update from replacing the tap meanwhile
+            let mut keymap = LISTENER_KEYMAP.lock().unwrap_or_else(|e| e.into_inner());
             LISTENER_RUNNING.store(false, Ordering::SeqCst);
             LISTENER_RUN_LOOP.store(std::ptr::null_mut(), Ordering::SeqCst);
-            LISTENER_EVENT_TAP.store(std::ptr::null_mut(), Ordering::SeqCst);
-            CFRelease(source);
-            CFRelease(tap);
-            LISTENER_SHORTCUTS = None;
+            LISTENER_TIMER.store(std::ptr::null_mut(), Ordering::SeqCst);
+            if !timer.is_null() {
+                CFRunLoopTimerInvalidate(timer);
+                CFRelease(timer);
+            }
+            remove_tap(
+                run_loop,
+                LISTENER_EVENT_TAP.swap(std::ptr::null_mut(), Ordering::SeqCst),
+                LISTENER_SOURCE.swap(std::ptr::null_mut(), Ordering::SeqCst),
+            );
+            *keymap = None;
+            drop(keymap);
             LISTENER_CALLBACK = None;
             LISTENER_PRESSED_KEYS = None;
         }
@@ -747,6 +909,114 @@ mod macos {
         Ok(())
     }
 
+    /// the events to tap: key events, and mouse events only with mouse
+    /// bindings, so that without them clicks and scrolls never wait on the tap
+    fn event_mask(mouse: bool) -> u64 {
+        let keys: u64 =
+            (1 << K_CG_EVENT_KEY_DOWN) | (1 << K_CG_EVENT_KEY_UP) | (1 << K_CG_EVENT_FLAGS_CHANGED);
+        if !mouse {
+            return keys;
+        }
+        keys | (1 << K_CG_EVENT_LEFT_MOUSE_DOWN)
+            | (1 << K_CG_EVENT_LEFT_MOUSE_UP)
+            | (1 << K_CG_EVENT_RIGHT_MOUSE_DOWN)
+            | (1 << K_CG_EVENT_RIGHT_MOUSE_UP)
+            | (1 << K_CG_EVENT_LEFT_MOUSE_DRAGGED)
+            | (1 << K_CG_EVENT_RIGHT_MOUSE_DRAGGED)
+            | (1 << K_CG_EVENT_SCROLL_WHEEL)
+            | (1 << K_CG_EVENT_OTHER_MOUSE_DOWN)
+            | (1 << K_CG_EVENT_OTHER_MOUSE_UP)
+            | (1 << K_CG_EVENT_OTHER_MOUSE_DRAGGED)
+    }
+
+    /// create an enabled event tap, with mouse events if `mouse`, and add it to `run_loop`
+    unsafe fn add_tap(
+        run_loop: CFRunLoopRef,
+        mouse: bool,
+    ) -> Result<(CFMachPortRef, CFRunLoopSourceRef)> {
+        let tap = CGEventTapCreate(
+            K_CG_HID_EVENT_TAP,
+            K_CG_HEAD_INSERT_EVENT_TAP,
+            K_CG_EVENT_TAP_OPTION_DEFAULT,
+            event_mask(mouse),
+            listener_callback,
+            std::ptr::null_mut(),
+        );
+        if tap.is_null() {
+            return Err(anyhow!(
+                "Failed to create event tap. Make sure accessibility permissions are granted."
+            ));
+        }
+
+        let source = CFMachPortCreateRunLoopSource(std::ptr::null(), tap, 0);
+        if source.is_null() {
+            CFRelease(tap);
+            return Err(anyhow!("Failed to create run loop source"));
+        }
+
+        CFRunLoopAddSource(run_loop, source, kCFRunLoopCommonModes);
+        CGEventTapEnable(tap, true);
+        Ok((tap, source))
+    }
+
+    /// take a tap added with `add_tap` off `run_loop` and release it
+    unsafe fn remove_tap(run_loop: CFRunLoopRef, tap: CFMachPortRef, source: CFRunLoopSourceRef) {
+        if tap.is_null() || source.is_null() {
+            return;
+        }
+        CGEventTapEnable(tap, false);
+        CFRunLoopRemoveSource(run_loop, source, kCFRunLoopCommonModes);
+        CFMachPortInvalidate(tap);
+        CFRelease(source);
+        CFRelease(tap);
+    }
+
+    fn set_keymap(keymap: Option<Keymap>) {
+        if let Ok(mut guard) = LISTENER_KEYMAP.lock() {
+            *guard = keymap;
+        }
+    }
+
+    pub fn update_listener_impl(mut keymap: Keymap) -> Vec<Signal> {
+        if !LISTENER_RUNNING.load(Ordering::SeqCst) {
+            return Vec::new();
+        }
+        let Ok(mut guard) = LISTENER_KEYMAP.lock() else {
+            return Vec::new();
+        };
+        let Some(previous) = guard.as_ref() else {
+            return Vec::new();
+        };
+
+        let signals = keymap.carry_over(previous, Instant::now());
+        let deadline = keymap.deadline();
+        let mouse = keymap.has_mouse();
+        let retap = mouse != previous.has_mouse();
+        *guard = Some(keymap);
+
+        // the tap's events are fixed when it is created, so mouse bindings
+        // coming or going need a new one; the lock keeps its events waiting
+        let run_loop = LISTENER_RUN_LOOP.load(Ordering::SeqCst);
+        if retap && !run_loop.is_null() {
+            unsafe {
+                // on failure the old tap stays, with or without mouse events
+                if let Ok((tap, source)) = add_tap(run_loop, mouse) {
+                    remove_tap(
+                        run_loop,
+                        LISTENER_EVENT_TAP.swap(tap, Ordering::SeqCst),
+                        LISTENER_SOURCE.swap(source, Ordering::SeqCst),
+                    );
+                }
+            }
+        }
+        drop(guard);
+
+        if let Some(deadline) = deadline {
+            schedule_timeout(deadline);
+        }
+        signals
+    }
+
     pub fn stop_listener_impl() {
         let run_loop = LISTENER_RUN_LOOP.load(Ordering::SeqCst);
         if LISTENER_RUNNING.load(Ordering::SeqCst) && !run_loop.is_null() {
@@ -760,6 +1030,27 @@ mod macos {
     mod tests {
         use super::*;
 
+        #[test]
+        fn test_modifier_change() {
+            let rshift = ModifierKey::parse("rshift").unwrap();
+            assert_eq!(
+                modifier_change(60, NX_DEVICE_RSHIFT_KEY_MASK | K_CG_EVENT_FLAG_MASK_SHIFT),
+                Some((rshift, true))
+            );
+            // left shift still down
+            assert_eq!(
+                modifier_change(60, NX_DEVICE_LSHIFT_KEY_MASK | K_CG_EVENT_FLAG_MASK_SHIFT),
+                Some((rshift, false))
+            );
+            assert_eq!(
+                modifier_change(54, NX_DEVICE_RCMD_KEY_MASK),
+                Some((ModifierKey::parse("rcmd").unwrap(), true))
+            );
+            // caps lock and fn are not modifiers for triggers
+            assert_eq!(modifier_change(57, 0), None);
+            assert_eq!(modifier_change(63, 0), None);
+        }
+
         #[test]
         fn test_extract_modifiers_none() {
             let mods = extract_modifiers(0);
@@ -898,18 +1189,29 @@ mod macos {
     }
 }
 
-/// Record a single keypress and return the hotkey string
-pub fn record_hotkey() -> Result<String> {
-    let hotkey = macos::record_hotkey_impl()?;
-    Ok(hotkey.to_string())
+/// Record a single keypress and return the hotkey
+pub fn record_hotkey() -> Result<Hotkey> {
+    macos::record_hotkey_impl()
 }
 
-/// Start listening for global hotkeys and call the callback when one is pressed
-pub fn start_hotkey_listener<F>(shortcuts: Vec<(Hotkey, String)>, callback: F) -> Result<()>
+/// Start listening for global hotkeys
+///
+/// the callback gets a signal for every action to run, and for sequences and
+/// modes as they start and end. it is called from the event tap, which macOS
+/// disables if it takes too long, so it should hand the signal off and return
+pub fn start_hotkey_listener<F>(keymap: Keymap, callback: F) -> Result<()>
 where
-    F: Fn(&str, &Hotkey) + Send + 'static,
+    F: Fn(&Signal) + Send + 'static,
 {
-    macos::start_listener_impl(shortcuts, callback)
+    macos::start_listener_impl(keymap, callback)
+}
+
+/// Replace the keymap of a running hotkey listener
+///
+/// a pending sequence is dropped; the active mode stays active if it still
+/// exists, and otherwise is left with the returned signal
+pub fn update_hotkey_listener(keymap: Keymap) -> Vec<Signal> {
+    macos::update_listener_impl(keymap)
 }
 
 /// Stop the hotkey listener
@@ -1025,6 +1327,19 @@ mod tests {
         assert!(!is_modifier_key(64)); // just after modifier range
     }
 
+    #[test]
+    fn test_is_known_key() {
+        assert!(is_known_key("a", &keyboard::US));
+        assert!(is_known_key("return", &keyboard::US));
+        assert!(is_known_key("left", &keyboard::US));
+        assert!(is_known_key("f12", &keyboard::US));
+        assert!(!is_known_key("enter", &keyboard::US));
+        assert!(!is_known_key("esc", &keyboard::US));
+        // modifier keycodes never trigger a match
+        assert!(!is_known_key("capslock", &keyboard::US));
+        assert!(!is_known_key("fn", &keyboard::US));
+    }
+
     // ========================================================================
     // Hotkey parsing tests (existing)
     // ========================================================================
@@ -1065,6 +1380,34 @@ mod tests {
         assert_eq!(hk.keys, vec!["a"]);
     }
 
+    #[test]
+    fn test_parse_physical_key() {
+        // stored as the layout's name, so it matches what the listener reports
+        let hk = Hotkey::parse("ctrl+alt+key:KeyZ").unwrap();
+        assert!(hk.modifiers.ctrl);
+        assert!(hk.modifiers.alt);
+        assert_eq!(hk.keys, vec!["z"]);
+        assert_eq!(Hotkey::parse("cmd+Key:Digit1").unwrap().keys, vec!["1"]);
+        assert_eq!(Hotkey::parse("cmd+key:Enter").unwrap().keys, vec!["return"]);
+
+        let err = Hotkey::parse("ctrl+key:KeyZZ").unwrap_err().to_string();
+        assert!(
+            err.starts_with("unknown physical key 'key:keyzz'"),
+            "{}",
+            err
+        );
+    }
+
+    #[test]
+    fn test_hotkey_physical() {
+        let hk = Hotkey::parse("ctrl+alt+z+return").unwrap();
+        assert_eq!(hk.physical().to_string(), "ctrl+alt+key:KeyZ+return");
+        assert_eq!(
+            Hotkey::parse("cmd+[").unwrap().physical().to_string(),
+            "cmd+key:BracketLeft"
+        );
+    }
+
     #[test]
     fn test_hotkey_to_string() {
         let hk = Hotkey {
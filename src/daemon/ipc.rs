@@ -6,13 +6,22 @@
 use anyhow::{anyhow, Result};
 use serde::Deserialize;
 use std::collections::HashMap;
-use std::path::PathBuf;
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
 
 use crate::cli::exit_codes;
 use crate::cli::output::{JsonRpcError, JsonRpcResponse};
 
-const PID_FILE: &str = "/tmp/cwm.pid";
+const PID_FILE: &str = "cwm.pid";
 const SOCKET_FILE: &str = "cwm.sock";
+const LOCK_FILE: &str = "cwm.lock";
+
+/// environment variable overriding the runtime directory (socket, pid file and lock)
+pub const RUNTIME_DIR_ENV: &str = "CWM_RUNTIME_DIR";
+
+/// how long to wait for the daemon to answer a liveness probe
+const PROBE_TIMEOUT: Duration = Duration::from_millis(1000);
 
 // ============================================================================
 // IPC Request parsing
@@ -25,8 +34,16 @@ pub struct IpcRequest {
     pub method: String,
     /// parameters as key-value pairs
     pub params: HashMap<String, String>,
+    /// parameters as sent by the client, with their original JSON types
+    pub raw_params: serde_json::Value,
     /// request id (if provided) - when absent, treated as notification
     pub id: Option<serde_json::Value>,
+    /// client token for socket access control (see `settings.ipc.tokens`)
+    pub token: Option<String>,
+    /// per-request deadline in milliseconds, measured from when the daemon reads it
+    pub deadline_ms: Option<u64>,
+    /// whether the client wants `$/progress` notifications before the response
+    pub progress: bool,
 }
 
 /// JSON request structure (jsonrpc field is optional for convenience)
@@ -37,9 +54,38 @@ struct JsonRequest {
     jsonrpc: Option<String>,
     method: String,
     #[serde(default)]
-    params: HashMap<String, String>,
+    params: HashMap<String, serde_json::Value>,
     /// id can be string, number, or null
     id: Option<serde_json::Value>,
+    #[serde(default)]
+    token: Option<String>,
+    #[serde(default)]
+    deadline_ms: Option<u64>,
+    #[serde(default)]
+    progress: bool,
+}
+
+/// Convert a JSON param value to the string form used by handlers
+///
+/// numbers and booleans are stringified, arrays of scalars are joined with commas
+/// and nulls are dropped
+fn param_to_string(value: serde_json::Value) -> Option<String> {
+    use serde_json::Value;
+
+    match value {
+        Value::Null => None,
+        Value::String(s) => Some(s),
+        Value::Number(n) => Some(n.to_string()),
+        Value::Bool(b) => Some(b.to_string()),
+        Value::Array(items) => Some(
+            items
+                .into_iter()
+                .filter_map(param_to_string)
+                .collect::<Vec<_>>()
+                .join(","),
+        ),
+        other => Some(other.to_string()),
+    }
 }
 
 impl IpcRequest {
@@ -59,10 +105,21 @@ impl IpcRequest {
         let req: JsonRequest = serde_json::from_str(trimmed)
             .map_err(|e| anyhow!("Invalid JSON-RPC request: {}", e))?;
 
+        let raw_params = serde_json::to_value(&req.params)?;
+        let params = req
+            .params
+            .into_iter()
+            .filter_map(|(k, v)| param_to_string(v).map(|v| (k, v)))
+            .collect();
+
         Ok(Self {
             method: req.method,
-            params: req.params,
+            params,
+            raw_params,
             id: req.id,
+            token: req.token,
+            deadline_ms: req.deadline_ms,
+            progress: req.progress,
         })
     }
 
@@ -112,6 +169,24 @@ pub fn format_error_response(request: &IpcRequest, code: i32, message: &str) ->
     serde_json::to_string(&error).ok()
 }
 
+/// Format a `$/progress` notification for a running request
+pub fn format_progress_notification<T: serde::Serialize>(
+    request: &IpcRequest,
+    progress: &T,
+) -> Option<String> {
+    let id = request.id.as_ref()?;
+
+    let mut params = serde_json::to_value(progress).ok()?;
+    params.as_object_mut()?.insert("id".to_string(), id.clone());
+
+    serde_json::to_string(&serde_json::json!({
+        "jsonrpc": "2.0",
+        "method": "$/progress",
+        "params": params,
+    }))
+    .ok()
+}
+
 /// Format an error response with default error code
 #[allow(dead_code)]
 pub fn format_error(request: &IpcRequest, message: &str) -> Option<String> {
@@ -122,43 +197,176 @@ pub fn format_error(request: &IpcRequest, message: &str) -> Option<String> {
 // PID and socket file management
 // ============================================================================
 
+/// directory holding the socket, pid file and daemon lock
+///
+/// `CWM_RUNTIME_DIR` overrides it so tests can run isolated daemons side by
+/// side; otherwise it's the per-user ~/.cwm directory
+pub fn get_runtime_dir() -> PathBuf {
+    runtime_dir_from(std::env::var_os(RUNTIME_DIR_ENV))
+}
+
+fn runtime_dir_from(env_override: Option<OsString>) -> PathBuf {
+    if let Some(dir) = env_override.filter(|d| !d.is_empty()) {
+        return PathBuf::from(dir);
+    }
+
+    dirs::home_dir()
+        .map(|h| h.join(".cwm"))
+        .unwrap_or_else(|| std::env::temp_dir().join(format!("cwm-{}", current_uid())))
+}
+
+fn current_uid() -> u32 {
+    unsafe { libc::getuid() }
+}
+
+/// create the runtime directory and make sure it belongs to the current user
+pub fn ensure_runtime_dir() -> Result<PathBuf> {
+    use std::os::unix::fs::MetadataExt;
+
+    let dir = get_runtime_dir();
+    std::fs::create_dir_all(&dir).map_err(|e| {
+        anyhow!(
+            "Failed to create runtime directory {}: {}",
+            dir.display(),
+            e
+        )
+    })?;
+
+    let owner = std::fs::metadata(&dir)?.uid();
+    if owner != current_uid() {
+        return Err(anyhow!(
+            "Runtime directory {} is owned by another user (uid {})",
+            dir.display(),
+            owner
+        ));
+    }
+
+    Ok(dir)
+}
+
 pub fn get_pid_file_path() -> PathBuf {
-    PathBuf::from(PID_FILE)
+    get_runtime_dir().join(PID_FILE)
 }
 
-/// returns the socket path in the cwm config directory (~/.cwm/cwm.sock)
+/// returns the socket path in the runtime directory (~/.cwm/cwm.sock by default)
 pub fn get_socket_path() -> PathBuf {
-    dirs::home_dir()
-        .map(|h| h.join(".cwm").join(SOCKET_FILE))
-        .unwrap_or_else(|| PathBuf::from("/tmp").join(SOCKET_FILE))
+    get_runtime_dir().join(SOCKET_FILE)
 }
 
-/// Check if daemon is running by checking PID file
+pub fn get_lock_file_path() -> PathBuf {
+    get_runtime_dir().join(LOCK_FILE)
+}
+
+/// Check if the daemon is running by pinging it over the socket
+///
+/// a stale socket file or a PID reused by another process doesn't count: only a
+/// daemon that answers is considered running
 pub fn is_daemon_running() -> bool {
-    use std::process::Command;
+    probe_socket(&get_socket_path())
+}
+
+/// send a `ping` and check that a JSON-RPC response comes back
+///
+/// any response counts, including an access-denied error
+fn probe_socket(socket_path: &Path) -> bool {
+    use std::io::{BufRead, BufReader, Write};
+    use std::os::unix::net::UnixStream;
 
-    let pid_path = get_pid_file_path();
+    let Ok(mut stream) = UnixStream::connect(socket_path) else {
+        return false;
+    };
+    if stream.set_read_timeout(Some(PROBE_TIMEOUT)).is_err()
+        || stream.set_write_timeout(Some(PROBE_TIMEOUT)).is_err()
+    {
+        return false;
+    }
+
+    let mut request = serde_json::json!({
+        "jsonrpc": "2.0",
+        "method": "ping",
+        "id": "probe",
+    });
+    if let Some(token) = client_token() {
+        request["token"] = serde_json::json!(token);
+    }
 
-    if !pid_path.exists() {
+    if writeln!(stream, "{}", request).is_err() {
         return false;
     }
+    let _ = stream.shutdown(std::net::Shutdown::Write);
 
-    // read PID and check if process is running
-    if let Ok(pid_str) = std::fs::read_to_string(&pid_path) {
-        if let Ok(pid) = pid_str.trim().parse::<u32>() {
-            // check if process exists using kill -0
-            return Command::new("kill")
-                .arg("-0")
-                .arg(pid.to_string())
-                .output()
-                .map(|o| o.status.success())
-                .unwrap_or(false);
-        }
+    let mut line = String::new();
+    if BufReader::new(&stream).read_line(&mut line).unwrap_or(0) == 0 {
+        return false;
     }
 
-    false
+    serde_json::from_str::<serde_json::Value>(&line)
+        .map(|v| v.get("jsonrpc").is_some())
+        .unwrap_or(false)
+}
+
+/// exclusive lock held by the running daemon for its whole lifetime
+///
+/// the lock is released by the kernel when the process exits, so a crashed
+/// daemon never leaves a stale lock behind
+#[derive(Debug)]
+pub struct DaemonLock {
+    _file: std::fs::File,
+}
+
+/// take the daemon lock, failing if another daemon holds it
+pub fn acquire_daemon_lock() -> Result<DaemonLock> {
+    lock_file_at(&get_lock_file_path())?.ok_or_else(|| match read_pid() {
+        Some(pid) => anyhow!("Daemon is already running (PID {})", pid),
+        None => anyhow!("Daemon is already running"),
+    })
 }
 
+/// Check whether a daemon currently holds the lock
+pub fn is_daemon_lock_held() -> bool {
+    let path = get_lock_file_path();
+    path.exists() && matches!(lock_file_at(&path), Ok(None))
+}
+
+/// try to take an exclusive `flock` on `path`
+///
+/// returns `Ok(None)` if another open file holds the lock
+fn lock_file_at(path: &Path) -> Result<Option<DaemonLock>> {
+    use std::os::unix::fs::OpenOptionsExt;
+    use std::os::unix::io::AsRawFd;
+
+    let file = std::fs::OpenOptions::new()
+        .read(true)
+        .write(true)
+        .create(true)
+        .truncate(false)
+        .mode(0o600)
+        .open(path)
+        .map_err(|e| anyhow!("Failed to open lock file {}: {}", path.display(), e))?;
+
+    let ret = unsafe { libc::flock(file.as_raw_fd(), libc::LOCK_EX | libc::LOCK_NB) };
+    if ret == 0 {
+        return Ok(Some(DaemonLock { _file: file }));
+    }
+
+    let err = std::io::Error::last_os_error();
+    if err.raw_os_error() == Some(libc::EWOULDBLOCK) {
+        Ok(None)
+    } else {
+        Err(anyhow!("Failed to lock {}: {}", path.display(), err))
+    }
+}
+
+/// read the daemon PID from the pid file
+pub fn read_pid() -> Option<i32> {
+    std::fs::read_to_string(get_pid_file_path())
+        .ok()?
+        .trim()
+        .parse()
+        .ok()
+}
+
+/// write the PID file; only call while holding the daemon lock
 pub fn write_pid_file() -> Result<()> {
     let pid = std::process::id();
     std::fs::write(get_pid_file_path(), pid.to_string())?;
@@ -185,6 +393,14 @@ pub fn remove_socket_file() -> Result<()> {
 // Client functions for sending requests to the daemon
 // ============================================================================
 
+/// environment variable holding the client token sent with IPC requests
+pub const TOKEN_ENV_VAR: &str = "CWM_IPC_TOKEN";
+
+/// Get the client token to present to the daemon, if one is configured
+pub fn client_token() -> Option<String> {
+    std::env::var(TOKEN_ENV_VAR).ok().filter(|t| !t.is_empty())
+}
+
 /// Send a JSON-RPC 2.0 request to the daemon
 /// Returns the raw JSON response string
 #[allow(dead_code)]
@@ -195,7 +411,6 @@ pub fn send_jsonrpc(
 ) -> Result<String> {
     use std::io::{BufRead, BufReader, Write};
     use std::os::unix::net::UnixStream;
-    use std::time::Duration;
 
     let socket_path = get_socket_path();
 
@@ -213,12 +428,15 @@ pub fn send_jsonrpc(
     stream.set_write_timeout(Some(Duration::from_secs(5)))?;
 
     // build JSON-RPC request
-    let request = serde_json::json!({
+    let mut request = serde_json::json!({
         "jsonrpc": "2.0",
         "method": method,
         "params": params,
         "id": id,
     });
+    if let Some(token) = client_token() {
+        request["token"] = serde_json::json!(token);
+    }
 
     let json = serde_json::to_string(&request)?;
     stream.write_all(json.as_bytes())?;
@@ -234,12 +452,21 @@ pub fn send_jsonrpc(
     // shutdown write side to signal we're done sending
     stream.shutdown(std::net::Shutdown::Write)?;
 
-    // read JSON response
+    // read JSON response, skipping any notifications (e.g. `$/progress`) sent before it
     let mut reader = BufReader::new(&stream);
-    let mut response_line = String::new();
-    reader.read_line(&mut response_line)?;
+    loop {
+        let mut response_line = String::new();
+        if reader.read_line(&mut response_line)? == 0 {
+            return Ok(response_line);
+        }
 
-    Ok(response_line)
+        let is_notification = serde_json::from_str::<serde_json::Value>(&response_line)
+            .map(|v| v.get("method").is_some())
+            .unwrap_or(false);
+        if !is_notification {
+            return Ok(response_line);
+        }
+    }
 }
 
 /// Helper to send a simple JSON-RPC command without parameters
@@ -327,6 +554,70 @@ mod tests {
         assert!(req.id.is_none());
     }
 
+    #[test]
+    fn test_parse_json_token() {
+        let req = IpcRequest::parse(r#"{"method":"focus","token":"abc","id":1}"#).unwrap();
+        assert_eq!(req.token, Some("abc".to_string()));
+
+        let req = IpcRequest::parse(r#"{"method":"focus","id":1}"#).unwrap();
+        assert!(req.token.is_none());
+    }
+
+    #[test]
+    fn test_parse_non_string_params() {
+        let req = IpcRequest::parse(
+            r#"{"method":"subscribe","params":{"events":["app.*","window.*"],"id":7,"force":true,"skip":null},"id":1}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            req.params.get("events"),
+            Some(&"app.*,window.*".to_string())
+        );
+        assert_eq!(req.params.get("id"), Some(&"7".to_string()));
+        assert_eq!(req.params.get("force"), Some(&"true".to_string()));
+        assert!(!req.params.contains_key("skip"));
+        assert_eq!(req.raw_params["force"], serde_json::json!(true));
+        assert_eq!(req.raw_params["events"][1], "window.*");
+    }
+
+    #[test]
+    fn test_parse_deadline() {
+        let req = IpcRequest::parse(r#"{"method":"kill","deadline_ms":2500,"id":1}"#).unwrap();
+        assert_eq!(req.deadline_ms, Some(2500));
+
+        let req = IpcRequest::parse(r#"{"method":"kill","id":1}"#).unwrap();
+        assert!(req.deadline_ms.is_none());
+        assert!(!req.progress);
+    }
+
+    #[test]
+    fn test_parse_progress_opt_in() {
+        let req = IpcRequest::parse(r#"{"method":"kill","progress":true,"id":1}"#).unwrap();
+        assert!(req.progress);
+    }
+
+    #[test]
+    fn test_format_progress_notification() {
+        let req = IpcRequest::parse(r#"{"method":"kill","id":3}"#).unwrap();
+        let notification = format_progress_notification(
+            &req,
+            &serde_json::json!({"message": "retrying", "current": 2, "total": 10}),
+        )
+        .unwrap();
+
+        let parsed: serde_json::Value = serde_json::from_str(&notification).unwrap();
+        assert_eq!(parsed["method"], "$/progress");
+        assert_eq!(parsed["params"]["id"], 3);
+        assert_eq!(parsed["params"]["current"], 2);
+        assert!(parsed.get("id").is_none());
+    }
+
+    #[test]
+    fn test_format_progress_notification_without_id() {
+        let req = IpcRequest::parse(r#"{"method":"kill"}"#).unwrap();
+        assert!(format_progress_notification(&req, &serde_json::json!({"message": "x"})).is_none());
+    }
+
     #[test]
     fn test_parse_plain_text_rejected() {
         let input = "focus:Safari";
@@ -393,7 +684,68 @@ mod tests {
     #[test]
     fn test_get_pid_file_path() {
         let path = get_pid_file_path();
-        assert_eq!(path.to_string_lossy(), "/tmp/cwm.pid");
+        assert!(path.starts_with(get_runtime_dir()));
+        assert!(path.to_string_lossy().ends_with("cwm.pid"));
+    }
+
+    #[test]
+    fn test_runtime_dir_override() {
+        let dir = runtime_dir_from(Some(OsString::from("/tmp/cwm-test-123")));
+        assert_eq!(dir, PathBuf::from("/tmp/cwm-test-123"));
+
+        // an empty override falls back to the per-user default
+        let default = runtime_dir_from(None);
+        assert_eq!(runtime_dir_from(Some(OsString::new())), default);
+        assert!(default.to_string_lossy().contains("cwm"));
+    }
+
+    #[test]
+    fn test_lock_is_exclusive() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(LOCK_FILE);
+
+        let lock = lock_file_at(&path).unwrap();
+        assert!(lock.is_some());
+        // a second open file description can't take it, even in the same process
+        assert!(lock_file_at(&path).unwrap().is_none());
+
+        drop(lock);
+        assert!(lock_file_at(&path).unwrap().is_some());
+    }
+
+    #[test]
+    fn test_probe_socket_without_listener() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(SOCKET_FILE);
+        assert!(!probe_socket(&path));
+
+        // a stale socket file left by a crashed daemon
+        drop(std::os::unix::net::UnixListener::bind(&path).unwrap());
+        assert!(path.exists());
+        assert!(!probe_socket(&path));
+    }
+
+    #[test]
+    fn test_probe_socket_with_responder() {
+        use std::io::{BufRead, BufReader, Write};
+        use std::os::unix::net::UnixListener;
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(SOCKET_FILE);
+        let listener = UnixListener::bind(&path).unwrap();
+
+        let handle = std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut line = String::new();
+            BufReader::new(&stream).read_line(&mut line).unwrap();
+            let req = IpcRequest::parse(&line).unwrap();
+            assert_eq!(req.method, "ping");
+            let response = format_success_response(&req, "pong").unwrap();
+            writeln!(stream, "{}", response).unwrap();
+        });
+
+        assert!(probe_socket(&path));
+        handle.join().unwrap();
     }
 
     #[test]
@@ -0,0 +1,828 @@
+//! keycodes, key names and keyboard layouts
+//!
+//! macOS reports keys by virtual keycode, which identifies the physical key:
+//! keycode 6 is the key right of left shift whatever is printed on it. a
+//! `Layout` maps keycodes to the characters they type (`z` on a US keyboard,
+//! `y` on a German one), so hotkeys are written with the characters on the
+//! keys. `key:KeyZ` names a physical key instead, using the web's
+//! `KeyboardEvent.code` names, and works the same on every layout
+//!
+//! keys that type no character (`return`, `left`, `f1`, ...) have the same name
+//! on every layout
+
+use anyhow::{anyhow, Result};
+use std::sync::RwLock;
+
+/// prefix of a physical key name in a hotkey, e.g. `key:KeyZ`
+pub const PHYSICAL_PREFIX: &str = "key:";
+
+/// physical key names by keycode
+const PHYSICAL: &[(i64, &str)] = &[
+    (0, "KeyA"),
+    (1, "KeyS"),
+    (2, "KeyD"),
+    (3, "KeyF"),
+    (4, "KeyH"),
+    (5, "KeyG"),
+    (6, "KeyZ"),
+    (7, "KeyX"),
+    (8, "KeyC"),
+    (9, "KeyV"),
+    (10, "IntlBackslash"),
+    (11, "KeyB"),
+    (12, "KeyQ"),
+    (13, "KeyW"),
+    (14, "KeyE"),
+    (15, "KeyR"),
+    (16, "KeyY"),
+    (17, "KeyT"),
+    (18, "Digit1"),
+    (19, "Digit2"),
+    (20, "Digit3"),
+    (21, "Digit4"),
+    (22, "Digit6"),
+    (23, "Digit5"),
+    (24, "Equal"),
+    (25, "Digit9"),
+    (26, "Digit7"),
+    (27, "Minus"),
+    (28, "Digit8"),
+    (29, "Digit0"),
+    (30, "BracketRight"),
+    (31, "KeyO"),
+    (32, "KeyU"),
+    (33, "BracketLeft"),
+    (34, "KeyI"),
+    (35, "KeyP"),
+    (36, "Enter"),
+    (37, "KeyL"),
+    (38, "KeyJ"),
+    (39, "Quote"),
+    (40, "KeyK"),
+    (41, "Semicolon"),
+    (42, "Backslash"),
+    (43, "Comma"),
+    (44, "Slash"),
+    (45, "KeyN"),
+    (46, "KeyM"),
+    (47, "Period"),
+    (48, "Tab"),
+    (49, "Space"),
+    (50, "Backquote"),
+    (51, "Backspace"),
+    (53, "Escape"),
+    (64, "F17"),
+    (65, "NumpadDecimal"),
+    (67, "NumpadMultiply"),
+    (69, "NumpadAdd"),
+    (71, "NumLock"),
+    (75, "NumpadDivide"),
+    (76, "NumpadEnter"),
+    (78, "NumpadSubtract"),
+    (79, "F18"),
+    (80, "F19"),
+    (81, "NumpadEqual"),
+    (82, "Numpad0"),
+    (83, "Numpad1"),
+    (84, "Numpad2"),
+    (85, "Numpad3"),
+    (86, "Numpad4"),
+    (87, "Numpad5"),
+    (88, "Numpad6"),
+    (89, "Numpad7"),
+    (90, "F20"),
+    (91, "Numpad8"),
+    (92, "Numpad9"),
+    (96, "F5"),
+    (97, "F6"),
+    (98, "F7"),
+    (99, "F3"),
+    (100, "F8"),
+    (101, "F9"),
+    (103, "F11"),
+    (105, "F13"),
+    (106, "F16"),
+    (107, "F14"),
+    (109, "F10"),
+    (111, "F12"),
+    (113, "F15"),
+    (114, "Help"),
+    (115, "Home"),
+    (116, "PageUp"),
+    (117, "Delete"),
+    (118, "F4"),
+    (119, "End"),
+    (120, "F2"),
+    (121, "PageDown"),
+    (122, "F1"),
+    (123, "ArrowLeft"),
+    (124, "ArrowRight"),
+    (125, "ArrowDown"),
+    (126, "ArrowUp"),
+];
+
+/// names of the keys that type no character, the same on every layout
+const NAMED: &[(i64, &str)] = &[
+    (36, "return"),
+    (48, "tab"),
+    (49, "space"),
+    (51, "backspace"),
+    (53, "escape"),
+    (64, "f17"),
+    (65, "keypad."),
+    (67, "keypad*"),
+    (69, "keypadplus"),
+    (71, "clear"),
+    (75, "keypad/"),
+    (76, "keypadenter"),
+    (78, "keypad-"),
+    (79, "f18"),
+    (80, "f19"),
+    (81, "keypad="),
+    (82, "keypad0"),
+    (83, "keypad1"),
+    (84, "keypad2"),
+    (85, "keypad3"),
+    (86, "keypad4"),
+    (87, "keypad5"),
+    (88, "keypad6"),
+    (89, "keypad7"),
+    (90, "f20"),
+    (91, "keypad8"),
+    (92, "keypad9"),
+    (96, "f5"),
+    (97, "f6"),
+    (98, "f7"),
+    (99, "f3"),
+    (100, "f8"),
+    (101, "f9"),
+    (103, "f11"),
+    (105, "f13"),
+    (106, "f16"),
+    (107, "f14"),
+    (109, "f10"),
+    (111, "f12"),
+    (113, "f15"),
+    (114, "help"),
+    (115, "home"),
+    (116, "pageup"),
+    (117, "delete"),
+    (118, "f4"),
+    (119, "end"),
+    (120, "f2"),
+    (121, "pagedown"),
+    (122, "f1"),
+    (123, "left"),
+    (124, "right"),
+    (125, "down"),
+    (126, "up"),
+];
+
+/// the characters a layout types, by keycode
+#[derive(Debug)]
+pub struct Layout {
+    /// name used in `settings.keyboard_layout`
+    pub id: &'static str,
+    /// macOS input source ids that use this table
+    input_sources: &'static [&'static str],
+    /// the first entry for a keycode is its name; later ones are accepted too.
+    /// hotkeys are split on `+`, so the key typing it is named `plus`
+    chars: &'static [(i64, &'static str)],
+}
+
+pub static US: Layout = Layout {
+    id: "us",
+    input_sources: &[
+        "com.apple.keylayout.US",
+        "com.apple.keylayout.ABC",
+        "com.apple.keylayout.USExtended",
+        "com.apple.keylayout.British",
+    ],
+    chars: &[
+        (0, "a"),
+        (1, "s"),
+        (2, "d"),
+        (3, "f"),
+        (4, "h"),
+        (5, "g"),
+        (6, "z"),
+        (7, "x"),
+        (8, "c"),
+        (9, "v"),
+        (10, "§"),
+        (11, "b"),
+        (12, "q"),
+        (13, "w"),
+        (14, "e"),
+        (15, "r"),
+        (16, "y"),
+        (17, "t"),
+        (18, "1"),
+        (19, "2"),
+        (20, "3"),
+        (21, "4"),
+        (22, "6"),
+        (23, "5"),
+        (24, "="),
+        (25, "9"),
+        (26, "7"),
+        (27, "-"),
+        (28, "8"),
+        (29, "0"),
+        (30, "]"),
+        (31, "o"),
+        (32, "u"),
+        (33, "["),
+        (34, "i"),
+        (35, "p"),
+        (37, "l"),
+        (38, "j"),
+        (39, "'"),
+        (40, "k"),
+        (41, ";"),
+        (42, "\\"),
+        (43, ","),
+        (44, "/"),
+        (45, "n"),
+        (46, "m"),
+        (47, "."),
+        (50, "`"),
+    ],
+};
+
+pub static GERMAN: Layout = Layout {
+    id: "de",
+    input_sources: &[
+        "com.apple.keylayout.German",
+        "com.apple.keylayout.German-DIN-2137",
+        "com.apple.keylayout.Austrian",
+        "com.apple.keylayout.SwissGerman",
+    ],
+    chars: &[
+        (0, "a"),
+        (1, "s"),
+        (2, "d"),
+        (3, "f"),
+        (4, "h"),
+        (5, "g"),
+        (6, "y"),
+        (7, "x"),
+        (8, "c"),
+        (9, "v"),
+        (10, "^"),
+        (11, "b"),
+        (12, "q"),
+        (13, "w"),
+        (14, "e"),
+        (15, "r"),
+        (16, "z"),
+        (17, "t"),
+        (18, "1"),
+        (19, "2"),
+        (20, "3"),
+        (21, "4"),
+        (22, "6"),
+        (23, "5"),
+        (24, "´"),
+        (25, "9"),
+        (26, "7"),
+        (27, "ß"),
+        (28, "8"),
+        (29, "0"),
+        (30, "plus"),
+        (31, "o"),
+        (32, "u"),
+        (33, "ü"),
+        (34, "i"),
+        (35, "p"),
+        (37, "l"),
+        (38, "j"),
+        (39, "ä"),
+        (40, "k"),
+        (41, "ö"),
+        (42, "#"),
+        (43, ","),
+        (44, "-"),
+        (45, "n"),
+        (46, "m"),
+        (47, "."),
+        (50, "<"),
+    ],
+};
+
+pub static FRENCH: Layout = Layout {
+    id: "fr",
+    input_sources: &[
+        "com.apple.keylayout.French",
+        "com.apple.keylayout.French-numerical",
+        "com.apple.keylayout.Belgian",
+    ],
+    chars: &[
+        (0, "q"),
+        (1, "s"),
+        (2, "d"),
+        (3, "f"),
+        (4, "h"),
+        (5, "g"),
+        (6, "w"),
+        (7, "x"),
+        (8, "c"),
+        (9, "v"),
+        (10, "@"),
+        (11, "b"),
+        (12, "a"),
+        (13, "z"),
+        (14, "e"),
+        (15, "r"),
+        (16, "y"),
+        (17, "t"),
+        // the digit row types symbols; the digits (with shift) name the keys too
+        (18, "&"),
+        (18, "1"),
+        (19, "é"),
+        (19, "2"),
+        (20, "\""),
+        (20, "3"),
+        (21, "'"),
+        (21, "4"),
+        (22, "§"),
+        (22, "6"),
+        (23, "("),
+        (23, "5"),
+        (24, "-"),
+        (25, "ç"),
+        (25, "9"),
+        (26, "è"),
+        (26, "7"),
+        (27, ")"),
+        (28, "!"),
+        (28, "8"),
+        (29, "à"),
+        (29, "0"),
+        (30, "$"),
+        (31, "o"),
+        (32, "u"),
+        (33, "^"),
+        (34, "i"),
+        (35, "p"),
+        (37, "l"),
+        (38, "j"),
+        (39, "ù"),
+        (40, "k"),
+        (41, "m"),
+        (42, "`"),
+        (43, ";"),
+        (44, "="),
+        (45, "n"),
+        (46, ","),
+        (47, ":"),
+        (50, "<"),
+    ],
+};
+
+pub static DVORAK: Layout = Layout {
+    id: "dvorak",
+    input_sources: &["com.apple.keylayout.Dvorak"],
+    chars: &[
+        (0, "a"),
+        (1, "o"),
+        (2, "e"),
+        (3, "u"),
+        (4, "d"),
+        (5, "i"),
+        (6, ";"),
+        (7, "q"),
+        (8, "j"),
+        (9, "k"),
+        (10, "§"),
+        (11, "x"),
+        (12, "'"),
+        (13, ","),
+        (14, "."),
+        (15, "p"),
+        (16, "f"),
+        (17, "y"),
+        (18, "1"),
+        (19, "2"),
+        (20, "3"),
+        (21, "4"),
+        (22, "6"),
+        (23, "5"),
+        (24, "]"),
+        (25, "9"),
+        (26, "7"),
+        (27, "["),
+        (28, "8"),
+        (29, "0"),
+        (30, "="),
+        (31, "r"),
+        (32, "g"),
+        (33, "/"),
+        (34, "c"),
+        (35, "l"),
+        (37, "n"),
+        (38, "h"),
+        (39, "-"),
+        (40, "t"),
+        (41, "s"),
+        (42, "\\"),
+        (43, "w"),
+        (44, "z"),
+        (45, "b"),
+        (46, "m"),
+        (47, "v"),
+        (50, "`"),
+    ],
+};
+
+/// the built-in layouts
+pub const LAYOUTS: &[&Layout] = &[&US, &GERMAN, &FRENCH, &DVORAK];
+
+impl Layout {
+    /// the built-in layout with `id`
+    pub fn find(id: &str) -> Result<&'static Layout> {
+        LAYOUTS
+            .iter()
+            .copied()
+            .find(|layout| layout.id.eq_ignore_ascii_case(id))
+            .ok_or_else(|| {
+                let ids: Vec<&str> = LAYOUTS.iter().map(|layout| layout.id).collect();
+                anyhow!(
+                    "unknown keyboard layout '{}', expected: {}",
+                    id,
+                    ids.join(", ")
+                )
+            })
+    }
+
+    /// the layout macOS uses for the input source `id`
+    fn for_input_source(id: &str) -> Option<&'static Layout> {
+        LAYOUTS
+            .iter()
+            .copied()
+            .find(|layout| layout.input_sources.contains(&id))
+    }
+
+    /// the name of the key with `keycode`
+    pub fn key_name(&self, keycode: i64) -> Option<&'static str> {
+        lookup(self.chars, keycode).or_else(|| lookup(NAMED, keycode))
+    }
+
+    /// the keycode of the key `name` (a character, key name or `key:` physical name)
+    pub fn keycode(&self, name: &str) -> Option<i64> {
+        if let Some(code) = name.strip_prefix(PHYSICAL_PREFIX) {
+            return physical_keycode(code);
+        }
+        let name = name.to_lowercase();
+        self.chars
+            .iter()
+            .chain(NAMED)
+            .find(|(_, n)| *n == name)
+            .map(|&(keycode, _)| keycode)
+    }
+
+    /// the name a key is matched by: `key:` physical names and alternative
+    /// names become the layout's name for the key
+    pub fn canonical(&self, name: &str) -> Result<String> {
+        match self
+            .keycode(name)
+            .and_then(|keycode| self.key_name(keycode))
+        {
+            Some(canonical) => Ok(canonical.to_string()),
+            None if name.starts_with(PHYSICAL_PREFIX) => Err(anyhow!(
+                "unknown physical key '{}', use names like key:KeyZ, key:Digit1 or key:Slash",
+                name
+            )),
+            // unknown keys are kept, so the error can name them
+            None => Ok(name.to_string()),
+        }
+    }
+
+    /// `key:` name of a key that types a character on this layout; keys like
+    /// `return` are the same everywhere and have none
+    pub fn physical(&self, name: &str) -> Option<String> {
+        let keycode = self.keycode(name)?;
+        lookup(self.chars, keycode)?;
+        let code = lookup(PHYSICAL, keycode)?;
+        Some(format!("{}{}", PHYSICAL_PREFIX, code))
+    }
+}
+
+fn lookup(table: &[(i64, &'static str)], keycode: i64) -> Option<&'static str> {
+    table
+        .iter()
+        .find(|(k, _)| *k == keycode)
+        .map(|(_, name)| *name)
+}
+
+/// the physical key name (like `KeyZ`) of `keycode`
+pub fn physical_name(keycode: i64) -> Option<&'static str> {
+    lookup(PHYSICAL, keycode)
+}
+
+/// the keycode of a physical key name like `KeyZ`, in any case
+pub fn physical_keycode(code: &str) -> Option<i64> {
+    PHYSICAL
+        .iter()
+        .find(|(_, name)| name.eq_ignore_ascii_case(code))
+        .map(|&(keycode, _)| keycode)
+}
+
+// the layout chosen with `select`, and the input source's, detected when first needed
+static SELECTED: RwLock<Option<&'static Layout>> = RwLock::new(None);
+static DETECTED: RwLock<Option<&'static Layout>> = RwLock::new(None);
+
+/// the layout hotkeys are read with: the one selected, or else the current
+/// macOS input source's (US when it has no table)
+pub fn active() -> &'static Layout {
+    if let Some(layout) = SELECTED.read().ok().and_then(|selected| *selected) {
+        return layout;
+    }
+    if let Some(layout) = DETECTED.read().ok().and_then(|detected| *detected) {
+        return layout;
+    }
+    let layout = detect();
+    if let Ok(mut detected) = DETECTED.write() {
+        *detected = Some(layout);
+    }
+    layout
+}
+
+/// use `settings.keyboard_layout`; without one (or an invalid one, which
+/// verify reports) the current input source's layout is detected again
+pub fn select(id: Option<&str>) {
+    let layout = id.and_then(|id| Layout::find(id).ok());
+    if let Ok(mut selected) = SELECTED.write() {
+        *selected = layout;
+    }
+    if let Ok(mut detected) = DETECTED.write() {
+        *detected = None;
+    }
+}
+
+/// detect the input source's layout again after it changed; true if that
+/// changed the active layout, so hotkeys have to be read again
+pub fn redetect() -> bool {
+    let layout = detect();
+    let previous = match DETECTED.write() {
+        Ok(mut detected) => detected.replace(layout),
+        Err(_) => return false,
+    };
+    let selected = SELECTED.read().ok().and_then(|selected| *selected);
+    selected.is_none() && previous.is_some_and(|previous| previous.id != layout.id)
+}
+
+/// call `on_change` when the user switches input sources. the notification
+/// comes on the main thread's run loop, so it needs one running
+pub fn watch_input_source(on_change: fn()) {
+    macos::watch_input_source(on_change);
+}
+
+/// the layout `settings.keyboard_layout` names, without making it the active
+/// one: the current input source's when unset or invalid
+pub fn configured(id: Option<&str>) -> &'static Layout {
+    id.and_then(|id| Layout::find(id).ok())
+        .unwrap_or_else(detect)
+}
+
+fn detect() -> &'static Layout {
+    // tests do not depend on the machine's layout
+    if cfg!(test) {
+        return &US;
+    }
+    macos::input_source_id()
+        .and_then(|id| Layout::for_input_source(&id))
+        .unwrap_or(&US)
+}
+
+mod macos {
+    use core_foundation::base::TCFType;
+    use core_foundation::string::{CFString, CFStringRef};
+
+    type TISInputSourceRef = *mut std::ffi::c_void;
+
+    #[link(name = "Carbon", kind = "framework")]
+    extern "C" {
+        fn TISCopyCurrentKeyboardLayoutInputSource() -> TISInputSourceRef;
+        fn TISGetInputSourceProperty(
+            source: TISInputSourceRef,
+            key: CFStringRef,
+        ) -> *const std::ffi::c_void;
+        static kTISPropertyInputSourceID: CFStringRef;
+    }
+
+    type CFNotificationCenterRef = *mut std::ffi::c_void;
+    type CFNotificationCallback = extern "C" fn(
+        center: CFNotificationCenterRef,
+        observer: *mut std::ffi::c_void,
+        name: CFStringRef,
+        object: *const std::ffi::c_void,
+        user_info: *const std::ffi::c_void,
+    );
+
+    const CF_NOTIFICATION_SUSPENSION_BEHAVIOR_DELIVER_IMMEDIATELY: isize = 4;
+
+    #[link(name = "CoreFoundation", kind = "framework")]
+    extern "C" {
+        fn CFRelease(cf: *const std::ffi::c_void);
+        fn CFNotificationCenterGetDistributedCenter() -> CFNotificationCenterRef;
+        fn CFNotificationCenterAddObserver(
+            center: CFNotificationCenterRef,
+            observer: *const std::ffi::c_void,
+            callback: CFNotificationCallback,
+            name: CFStringRef,
+            object: *const std::ffi::c_void,
+            suspension_behavior: isize,
+        );
+    }
+
+    // what `watch_input_source` calls; the observer pointer is this static's address
+    static ON_CHANGE: std::sync::OnceLock<fn()> = std::sync::OnceLock::new();
+
+    extern "C" fn input_source_changed(
+        _center: CFNotificationCenterRef,
+        _observer: *mut std::ffi::c_void,
+        _name: CFStringRef,
+        _object: *const std::ffi::c_void,
+        _user_info: *const std::ffi::c_void,
+    ) {
+        if let Some(on_change) = ON_CHANGE.get() {
+            on_change();
+        }
+    }
+
+    /// observe `kTISNotifySelectedKeyboardInputSourceChanged`, once per process
+    pub fn watch_input_source(on_change: fn()) {
+        if ON_CHANGE.set(on_change).is_err() {
+            return;
+        }
+        let name = CFString::new("com.apple.Carbon.TISNotifySelectedKeyboardInputSourceChanged");
+        unsafe {
+            CFNotificationCenterAddObserver(
+                CFNotificationCenterGetDistributedCenter(),
+                &ON_CHANGE as *const _ as *const std::ffi::c_void,
+                input_source_changed,
+                name.as_concrete_TypeRef(),
+                std::ptr::null(),
+                CF_NOTIFICATION_SUSPENSION_BEHAVIOR_DELIVER_IMMEDIATELY,
+            );
+        }
+    }
+
+    /// the current keyboard layout's input source id, e.g. `com.apple.keylayout.German`
+    pub fn input_source_id() -> Option<String> {
+        unsafe {
+            let source = TISCopyCurrentKeyboardLayoutInputSource();
+            if source.is_null() {
+                return None;
+            }
+            let id = TISGetInputSourceProperty(source, kTISPropertyInputSourceID);
+            let result = (!id.is_null())
+                .then(|| CFString::wrap_under_get_rule(id as CFStringRef).to_string());
+            CFRelease(source);
+            result
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_every_keycode_has_a_physical_name() {
+        for layout in LAYOUTS {
+            for (keycode, name) in layout.chars.iter().chain(NAMED) {
+                assert!(
+                    lookup(PHYSICAL, *keycode).is_some(),
+                    "{} '{}' ({}) has no physical name",
+                    layout.id,
+                    name,
+                    keycode
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_layouts_cover_the_same_keys() {
+        let keycodes = |layout: &Layout| {
+            let mut keycodes: Vec<i64> = layout.chars.iter().map(|(k, _)| *k).collect();
+            keycodes.dedup();
+            keycodes
+        };
+        for layout in LAYOUTS {
+            assert_eq!(keycodes(layout), keycodes(&US), "{}", layout.id);
+        }
+    }
+
+    #[test]
+    fn test_names_are_unique_per_layout() {
+        for layout in LAYOUTS {
+            for (keycode, name) in layout.chars.iter().chain(NAMED) {
+                assert_eq!(
+                    layout.keycode(name),
+                    Some(*keycode),
+                    "{} '{}'",
+                    layout.id,
+                    name
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_every_name_parses_in_a_hotkey() {
+        use super::super::hotkeys::Hotkey;
+
+        for layout in LAYOUTS {
+            for (keycode, name) in layout.chars.iter().chain(NAMED) {
+                let hotkey = Hotkey::parse_in(&format!("ctrl+{}", name), layout)
+                    .unwrap_or_else(|e| panic!("{} '{}': {}", layout.id, name, e));
+                assert_eq!(
+                    hotkey.keys,
+                    vec![layout.key_name(*keycode).unwrap().to_string()],
+                    "{} '{}'",
+                    layout.id,
+                    name
+                );
+                let alone = Hotkey::parse_in(name, layout).unwrap();
+                assert_eq!(alone.keys, hotkey.keys, "{} '{}'", layout.id, name);
+            }
+        }
+    }
+
+    #[test]
+    fn test_key_name() {
+        assert_eq!(US.key_name(6), Some("z"));
+        assert_eq!(GERMAN.key_name(6), Some("y"));
+        assert_eq!(GERMAN.key_name(27), Some("ß"));
+        assert_eq!(FRENCH.key_name(12), Some("a"));
+        assert_eq!(FRENCH.key_name(18), Some("&"));
+        assert_eq!(DVORAK.key_name(40), Some("t"));
+        // keys without a character are named the same everywhere
+        for layout in LAYOUTS {
+            assert_eq!(layout.key_name(36), Some("return"));
+            assert_eq!(layout.key_name(123), Some("left"));
+            assert_eq!(layout.key_name(82), Some("keypad0"));
+        }
+        assert_eq!(US.key_name(52), None);
+        assert_eq!(US.key_name(55), None); // modifiers are not keys
+    }
+
+    #[test]
+    fn test_keycode() {
+        assert_eq!(US.keycode("z"), Some(6));
+        assert_eq!(GERMAN.keycode("z"), Some(16));
+        assert_eq!(GERMAN.keycode("Ö"), Some(41));
+        assert_eq!(FRENCH.keycode("é"), Some(19));
+        assert_eq!(FRENCH.keycode("2"), Some(19));
+        assert_eq!(US.keycode("key:KeyZ"), Some(6));
+        assert_eq!(GERMAN.keycode("key:keyz"), Some(6));
+        assert_eq!(US.keycode("key:Numpad5"), Some(87));
+        assert_eq!(US.keycode("ß"), None);
+        assert_eq!(US.keycode("enter"), None);
+        assert_eq!(US.keycode("key:Enterr"), None);
+    }
+
+    #[test]
+    fn test_canonical() {
+        assert_eq!(US.canonical("key:KeyZ").unwrap(), "z");
+        assert_eq!(GERMAN.canonical("key:KeyZ").unwrap(), "y");
+        assert_eq!(GERMAN.canonical("key:Minus").unwrap(), "ß");
+        assert_eq!(FRENCH.canonical("1").unwrap(), "&");
+        assert_eq!(US.canonical("key:Enter").unwrap(), "return");
+        assert_eq!(US.canonical("return").unwrap(), "return");
+        // unknown names are left for validation to report
+        assert_eq!(US.canonical("ß").unwrap(), "ß");
+        assert!(US.canonical("key:Nope").is_err());
+    }
+
+    #[test]
+    fn test_physical() {
+        assert_eq!(US.physical("z").as_deref(), Some("key:KeyZ"));
+        assert_eq!(GERMAN.physical("z").as_deref(), Some("key:KeyY"));
+        assert_eq!(GERMAN.physical("ß").as_deref(), Some("key:Minus"));
+        assert_eq!(DVORAK.physical(";").as_deref(), Some("key:KeyZ"));
+        assert_eq!(US.physical("return"), None);
+        assert_eq!(US.physical("ß"), None);
+    }
+
+    #[test]
+    fn test_find() {
+        assert_eq!(Layout::find("DE").unwrap().id, "de");
+        assert_eq!(
+            Layout::find("colemak").unwrap_err().to_string(),
+            "unknown keyboard layout 'colemak', expected: us, de, fr, dvorak"
+        );
+        assert_eq!(
+            Layout::for_input_source("com.apple.keylayout.ABC").map(|l| l.id),
+            Some("us")
+        );
+        assert_eq!(
+            Layout::for_input_source("com.apple.keylayout.German").map(|l| l.id),
+            Some("de")
+        );
+        assert!(Layout::for_input_source("com.apple.keylayout.Colemak").is_none());
+    }
+}
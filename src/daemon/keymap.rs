@@ -0,0 +1,1232 @@
+//! the keys the hotkey listener responds to: shortcuts, triggers and modes
+//!
+//! a mode is entered with its `enter` hotkey. while it is active, key presses
+//! matching one of its bindings run that binding's action and are swallowed;
+//! every other key goes to the shortcuts as usual, and from there to the
+//! focused app. the mode is left with its `exit` key, after its timeout, or by
+//! entering another mode
+//!
+//! shortcuts can be limited to some apps or turned off in others, and every
+//! key is let through while an app in `disabled_in` is frontmost. the
+//! frontmost app is only looked up for key presses that could be consumed
+//!
+//! double-tap and hold shortcuts are handled by `trigger`, and are checked
+//! right after the active mode's bindings. mouse bindings are handled by
+//! `mouse` and are off in `disabled_in` apps too
+//!
+//! like `sequence`, this is a pure state machine: the listener passes in each
+//! key event and the current time, and reports the returned signals
+
+use std::fmt;
+use std::time::{Duration, Instant};
+
+use anyhow::{anyhow, Result};
+
+use super::hotkeys::{Hotkey, Modifiers};
+use super::mouse::{MouseBinding, MouseEvent, MouseMap, MouseSignal};
+use super::sequence::{same_chord, CancelReason, KeySequence, SequenceMatcher, Step};
+use super::trigger::{ModifierKey, TriggerBinding, Triggers};
+use crate::window::drag::Point;
+use crate::window::matching::{self, AppInfo};
+
+/// which apps a shortcut applies in; app names are matched like a shortcut's `app`
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct AppScope {
+    /// when not empty, the shortcut only applies while one of these apps is frontmost
+    pub only_in: Vec<String>,
+    /// the shortcut does not apply while one of these apps is frontmost
+    pub except_in: Vec<String>,
+}
+
+impl AppScope {
+    /// whether the shortcut applies with `app` frontmost (`None` when unknown)
+    pub(crate) fn includes(&self, app: Option<&AppInfo>, fuzzy_threshold: usize) -> bool {
+        let matches = |apps: &[String]| {
+            app.is_some_and(|app| matching::matches_any(apps, app, fuzzy_threshold))
+        };
+        (self.only_in.is_empty() || matches(&self.only_in)) && !matches(&self.except_in)
+    }
+}
+
+/// a shortcut as the listener sees it
+#[derive(Debug, Clone, PartialEq)]
+pub struct ShortcutKeys {
+    pub keys: KeySequence,
+    pub action: String,
+    pub scope: AppScope,
+    pub repeat: Repeat,
+}
+
+/// what key repeats do while a shortcut's keys are held down
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Repeat {
+    /// swallow them; the action runs once per press
+    #[default]
+    Ignore,
+    /// run the action for every repeat
+    Allow,
+    /// run the action at most once per interval
+    Throttle(Duration),
+}
+
+impl Repeat {
+    /// Parse "ignore", "allow" or "throttle:<n>ms" (or "<n>s")
+    pub fn parse(s: &str) -> Result<Self> {
+        let s = s.trim().to_lowercase();
+        match s.as_str() {
+            "ignore" => return Ok(Repeat::Ignore),
+            "allow" => return Ok(Repeat::Allow),
+            _ => {}
+        }
+
+        let interval = s.strip_prefix("throttle:").ok_or_else(|| {
+            anyhow!(
+                "Invalid repeat '{}': expected ignore, allow or throttle:<interval>",
+                s
+            )
+        })?;
+        let duration = if let Some(ms) = interval.strip_suffix("ms") {
+            ms.trim().parse().ok().map(Duration::from_millis)
+        } else if let Some(secs) = interval.strip_suffix('s') {
+            let secs: Option<f64> = secs.trim().parse().ok();
+            secs.and_then(|secs| Duration::try_from_secs_f64(secs).ok())
+        } else {
+            None
+        };
+        match duration {
+            Some(duration) if !duration.is_zero() => Ok(Repeat::Throttle(duration)),
+            _ => Err(anyhow!(
+                "Invalid throttle interval '{}': expected e.g. 200ms or 1.5s",
+                interval
+            )),
+        }
+    }
+}
+
+impl fmt::Display for Repeat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Repeat::Ignore => write!(f, "ignore"),
+            Repeat::Allow => write!(f, "allow"),
+            Repeat::Throttle(interval) => write!(f, "throttle:{}ms", interval.as_millis()),
+        }
+    }
+}
+
+/// a mode as the listener sees it
+#[derive(Debug, Clone, PartialEq)]
+pub struct ModeKeys {
+    pub name: String,
+    pub enter: KeySequence,
+    pub exit: Hotkey,
+    /// keys and the actions they run, first match wins
+    pub bindings: Vec<(Hotkey, String)>,
+    /// leave the mode after this long without a binding being pressed
+    pub timeout: Option<Duration>,
+}
+
+/// why a mode was left
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExitReason {
+    /// the mode's exit key was pressed
+    Exit,
+    /// no binding was pressed within the mode's timeout
+    Timeout,
+    /// another mode was entered
+    Switched,
+    /// the config changed and the mode no longer exists
+    Removed,
+}
+
+impl ExitReason {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ExitReason::Exit => "exit",
+            ExitReason::Timeout => "timeout",
+            ExitReason::Switched => "switched",
+            ExitReason::Removed => "removed",
+        }
+    }
+}
+
+/// something the daemon should act on or report
+#[derive(Debug, Clone, PartialEq)]
+pub enum Signal {
+    /// run an action; `mode` is set when it is a mode binding
+    Run {
+        action: String,
+        keys: String,
+        mode: Option<String>,
+    },
+    /// the first chords of a sequence were pressed
+    SequencePending {
+        pressed: KeySequence,
+        next: Vec<Hotkey>,
+    },
+    /// a pending sequence was abandoned
+    SequenceCancelled {
+        pressed: KeySequence,
+        reason: CancelReason,
+    },
+    ModeEntered {
+        mode: String,
+    },
+    ModeExited {
+        mode: String,
+        reason: ExitReason,
+    },
+    /// a mouse binding was used
+    Mouse(MouseSignal),
+}
+
+/// what the listener should do with a key press
+#[derive(Debug, Clone, PartialEq)]
+pub enum Press {
+    /// not ours; let the key through
+    Pass,
+    /// swallow the key and report the signals
+    Consumed(Vec<Signal>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Target {
+    Action(String, AppScope, Repeat),
+    Mode(usize),
+}
+
+/// the shortcut whose keys are held down, for its key repeats
+struct Held {
+    chord: Hotkey,
+    action: String,
+    keys: String,
+    repeat: Repeat,
+    last_run: Instant,
+}
+
+struct Active {
+    mode: usize,
+    deadline: Option<Instant>,
+}
+
+/// settings that apply to every key
+#[derive(Debug, Clone, PartialEq)]
+pub struct KeymapSettings {
+    /// how long a sequence waits for its next chord
+    pub sequence_timeout: Duration,
+    /// apps in which every key is let through
+    pub disabled_in: Vec<String>,
+    /// maximum distance for fuzzy app name matching
+    pub fuzzy_threshold: usize,
+}
+
+/// looks the frontmost app up the first time it is needed
+struct Frontmost<F> {
+    lookup: Option<F>,
+    app: Option<AppInfo>,
+}
+
+impl<F: FnOnce() -> Option<AppInfo>> Frontmost<F> {
+    fn new(lookup: F) -> Self {
+        Self {
+            lookup: Some(lookup),
+            app: None,
+        }
+    }
+
+    fn get(&mut self) -> Option<&AppInfo> {
+        if let Some(lookup) = self.lookup.take() {
+            self.app = lookup();
+        }
+        self.app.as_ref()
+    }
+}
+
+/// shortcuts and modes, and which mode is active
+pub struct Keymap {
+    matcher: SequenceMatcher<Target>,
+    triggers: Triggers,
+    modes: Vec<ModeKeys>,
+    active: Option<Active>,
+    held: Option<Held>,
+    mouse: MouseMap,
+    disabled_in: Vec<String>,
+    fuzzy_threshold: usize,
+}
+
+impl Keymap {
+    /// shortcuts come before mode `enter` keys, so a shortcut wins when both use the same keys
+    pub fn new(
+        shortcuts: Vec<ShortcutKeys>,
+        triggers: Vec<TriggerBinding>,
+        modes: Vec<ModeKeys>,
+        settings: KeymapSettings,
+    ) -> Self {
+        let mut bindings: Vec<(KeySequence, Target)> = shortcuts
+            .into_iter()
+            .map(|shortcut| {
+                (
+                    shortcut.keys,
+                    Target::Action(shortcut.action, shortcut.scope, shortcut.repeat),
+                )
+            })
+            .collect();
+        bindings.extend(
+            modes
+                .iter()
+                .enumerate()
+                .map(|(i, mode)| (mode.enter.clone(), Target::Mode(i))),
+        );
+
+        Self {
+            matcher: SequenceMatcher::new(bindings, settings.sequence_timeout),
+            triggers: Triggers::new(triggers),
+            modes,
+            active: None,
+            held: None,
+            mouse: MouseMap::default(),
+            disabled_in: settings.disabled_in,
+            fuzzy_threshold: settings.fuzzy_threshold,
+        }
+    }
+
+    /// add mouse bindings
+    pub fn with_mouse(mut self, bindings: Vec<MouseBinding>) -> Self {
+        self.mouse = MouseMap::new(bindings);
+        self
+    }
+
+    /// whether there are mouse bindings, so mouse events need to be seen
+    pub fn has_mouse(&self) -> bool {
+        !self.mouse.is_empty()
+    }
+
+    /// the active mode's name
+    pub fn active_mode(&self) -> Option<&str> {
+        self.active
+            .as_ref()
+            .map(|active| self.modes[active.mode].name.as_str())
+    }
+
+    /// when `expire` next has something to do
+    pub fn deadline(&self) -> Option<Instant> {
+        let mode = self.active.as_ref().and_then(|active| active.deadline);
+        [self.matcher.deadline(), mode, self.triggers.deadline()]
+            .into_iter()
+            .flatten()
+            .min()
+    }
+
+    /// whether a key repeat of `chord` belongs to a sequence, double-tap or hold
+    /// in progress, rather than pressing the keys again
+    pub fn swallows_repeat(&self, chord: &Hotkey) -> bool {
+        self.matcher.is_pending() || self.triggers.swallows_repeat(chord)
+    }
+
+    /// handle a key press: `chord` is the modifiers held and the keys down,
+    /// `frontmost` looks up the frontmost app
+    pub fn press(
+        &mut self,
+        chord: &Hotkey,
+        now: Instant,
+        frontmost: impl FnOnce() -> Option<AppInfo>,
+    ) -> Press {
+        self.triggers.interrupt();
+        self.held = None;
+
+        // most key presses are typing, which needs no app lookup
+        if self.active.is_none()
+            && !self.matcher.is_pending()
+            && !self.matcher.is_leader(chord)
+            && !self.triggers.wants(chord)
+        {
+            return Press::Pass;
+        }
+
+        let mut frontmost = Frontmost::new(frontmost);
+        let threshold = self.fuzzy_threshold;
+        if !self.disabled_in.is_empty() {
+            if let Some(app) = frontmost.get() {
+                if matching::matches_any(&self.disabled_in, app, threshold) {
+                    return Press::Pass;
+                }
+            }
+        }
+
+        // a started sequence takes precedence over the mode's keys
+        if !self.matcher.is_pending() {
+            if let Some(press) = self.press_in_mode(chord, now) {
+                return press;
+            }
+            let active = |scope: &AppScope| scope.includes(frontmost.get(), threshold);
+            if let Some(signals) = self.triggers.press(chord, now, active) {
+                return Press::Consumed(signals);
+            }
+        }
+
+        let step = self.matcher.press(chord, now, |target| match target {
+            Target::Action(_, scope, _) => scope.includes(frontmost.get(), threshold),
+            Target::Mode(_) => true,
+        });
+        match step {
+            Step::Pass => Press::Pass,
+            Step::Matched {
+                value: Target::Action(action, _, repeat),
+                sequence,
+            } => {
+                let keys = sequence.to_string();
+                self.held = Some(Held {
+                    chord: chord.clone(),
+                    action: action.clone(),
+                    keys: keys.clone(),
+                    repeat,
+                    last_run: now,
+                });
+                Press::Consumed(vec![Signal::Run {
+                    action,
+                    keys,
+                    mode: None,
+                }])
+            }
+            Step::Matched {
+                value: Target::Mode(mode),
+                ..
+            } => Press::Consumed(self.enter(mode, now)),
+            Step::Pending { pressed, next } => {
+                Press::Consumed(vec![Signal::SequencePending { pressed, next }])
+            }
+            Step::Cancelled { pressed, reason } => {
+                Press::Consumed(vec![Signal::SequenceCancelled { pressed, reason }])
+            }
+        }
+    }
+
+    /// handle a key repeat of `chord`: a repeat of the shortcut last run goes
+    /// by its `repeat`, anything else (like a mode binding) is handled like a press
+    pub fn repeat(
+        &mut self,
+        chord: &Hotkey,
+        now: Instant,
+        frontmost: impl FnOnce() -> Option<AppInfo>,
+    ) -> Press {
+        let held = match self.held.as_mut() {
+            Some(held) if same_chord(&held.chord, chord) => held,
+            _ => return self.press(chord, now, frontmost),
+        };
+
+        let run = match held.repeat {
+            Repeat::Ignore => false,
+            Repeat::Allow => true,
+            Repeat::Throttle(interval) => now >= held.last_run + interval,
+        };
+        if !run {
+            return Press::Consumed(Vec::new());
+        }
+        held.last_run = now;
+        Press::Consumed(vec![Signal::Run {
+            action: held.action.clone(),
+            keys: held.keys.clone(),
+            mode: None,
+        }])
+    }
+
+    fn press_in_mode(&mut self, chord: &Hotkey, now: Instant) -> Option<Press> {
+        let active = self.active.as_mut()?;
+        let mode = &self.modes[active.mode];
+
+        if same_chord(&mode.exit, chord) {
+            let name = mode.name.clone();
+            self.active = None;
+            return Some(Press::Consumed(vec![Signal::ModeExited {
+                mode: name,
+                reason: ExitReason::Exit,
+            }]));
+        }
+
+        let (keys, action) = mode
+            .bindings
+            .iter()
+            .find(|(keys, _)| same_chord(keys, chord))?;
+        active.deadline = mode.timeout.map(|timeout| now + timeout);
+        Some(Press::Consumed(vec![Signal::Run {
+            action: action.clone(),
+            keys: keys.to_string(),
+            mode: Some(mode.name.clone()),
+        }]))
+    }
+
+    /// handle the key named `key` being let go
+    pub fn release(&mut self, key: &str, now: Instant) -> Vec<Signal> {
+        self.held = None;
+        self.triggers.release(key, now)
+    }
+
+    /// handle a modifier key going down or up; these are never swallowed
+    pub fn modifier(
+        &mut self,
+        key: ModifierKey,
+        down: bool,
+        now: Instant,
+        frontmost: impl FnOnce() -> Option<AppInfo>,
+    ) -> Vec<Signal> {
+        let mut frontmost = Frontmost::new(frontmost);
+        let (disabled_in, threshold) = (&self.disabled_in, self.fuzzy_threshold);
+        self.triggers.modifier(key, down, now, |scope| {
+            let app = frontmost.get();
+            let disabled =
+                app.is_some_and(|app| matching::matches_any(disabled_in, app, threshold));
+            !disabled && scope.includes(app, threshold)
+        })
+    }
+
+    /// handle a mouse button, drag or scroll event with `modifiers` held and
+    /// the cursor at `at`; `frontmost` looks up the frontmost app
+    pub fn mouse(
+        &mut self,
+        modifiers: Modifiers,
+        event: MouseEvent,
+        at: Point,
+        frontmost: impl FnOnce() -> Option<AppInfo>,
+    ) -> Press {
+        // a drag or click already started is finished even if the app changed
+        if self.mouse.wants(modifiers, event) && !self.disabled_in.is_empty() {
+            if let Some(app) = frontmost() {
+                if matching::matches_any(&self.disabled_in, &app, self.fuzzy_threshold) {
+                    return Press::Pass;
+                }
+            }
+        }
+
+        match self.mouse.event(modifiers, event, at) {
+            Some(signals) => Press::Consumed(signals.into_iter().map(Signal::Mouse).collect()),
+            None => Press::Pass,
+        }
+    }
+
+    fn enter(&mut self, mode: usize, now: Instant) -> Vec<Signal> {
+        let deadline = self.modes[mode].timeout.map(|timeout| now + timeout);
+        let previous = self.active.replace(Active { mode, deadline });
+
+        match previous {
+            // entering the active mode again only restarts its timeout
+            Some(previous) if previous.mode == mode => Vec::new(),
+            Some(previous) => vec![
+                Signal::ModeExited {
+                    mode: self.modes[previous.mode].name.clone(),
+                    reason: ExitReason::Switched,
+                },
+                Signal::ModeEntered {
+                    mode: self.modes[mode].name.clone(),
+                },
+            ],
+            None => vec![Signal::ModeEntered {
+                mode: self.modes[mode].name.clone(),
+            }],
+        }
+    }
+
+    /// cancel a pending sequence, leave the active mode or run a hold if their time is up
+    pub fn expire(&mut self, now: Instant) -> Vec<Signal> {
+        let mut signals = self.triggers.expire(now);
+
+        if let Some(Step::Cancelled { pressed, reason }) = self.matcher.expire(now) {
+            signals.push(Signal::SequenceCancelled { pressed, reason });
+        }
+
+        let timed_out = self
+            .active
+            .as_ref()
+            .and_then(|active| active.deadline)
+            .is_some_and(|deadline| now >= deadline);
+        if timed_out {
+            if let Some(active) = self.active.take() {
+                signals.push(Signal::ModeExited {
+                    mode: self.modes[active.mode].name.clone(),
+                    reason: ExitReason::Timeout,
+                });
+            }
+        }
+
+        signals
+    }
+
+    /// take over the state of `previous`: a mode stays active if `self` still
+    /// defines it, with its timeout restarted; a pending sequence is dropped
+    pub fn carry_over(&mut self, previous: &Keymap, now: Instant) -> Vec<Signal> {
+        self.mouse.carry_over(&previous.mouse);
+
+        let Some(name) = previous.active_mode() else {
+            return Vec::new();
+        };
+
+        match self.modes.iter().position(|mode| mode.name == name) {
+            Some(mode) => {
+                let deadline = self.modes[mode].timeout.map(|timeout| now + timeout);
+                self.active = Some(Active { mode, deadline });
+                Vec::new()
+            }
+            None => vec![Signal::ModeExited {
+                mode: name.to_string(),
+                reason: ExitReason::Removed,
+            }],
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SEQUENCE_TIMEOUT: Duration = Duration::from_millis(1000);
+    const MODE_TIMEOUT: Duration = Duration::from_millis(3000);
+
+    fn chord(s: &str) -> Hotkey {
+        Hotkey::parse(s).unwrap()
+    }
+
+    fn seq(s: &str) -> KeySequence {
+        KeySequence::parse(s).unwrap()
+    }
+
+    fn mode(name: &str, enter: &str, bindings: &[(&str, &str)]) -> ModeKeys {
+        ModeKeys {
+            name: name.to_string(),
+            enter: seq(enter),
+            exit: chord("escape"),
+            bindings: bindings
+                .iter()
+                .map(|(keys, action)| (chord(keys), action.to_string()))
+                .collect(),
+            timeout: None,
+        }
+    }
+
+    fn settings() -> KeymapSettings {
+        KeymapSettings {
+            sequence_timeout: SEQUENCE_TIMEOUT,
+            disabled_in: Vec::new(),
+            fuzzy_threshold: 2,
+        }
+    }
+
+    fn shortcut(keys: &str, action: &str) -> ShortcutKeys {
+        ShortcutKeys {
+            keys: seq(keys),
+            action: action.to_string(),
+            scope: AppScope::default(),
+            repeat: Repeat::Ignore,
+        }
+    }
+
+    fn keymap(shortcuts: &[(&str, &str)], modes: Vec<ModeKeys>) -> Keymap {
+        Keymap::new(
+            shortcuts
+                .iter()
+                .map(|(keys, action)| shortcut(keys, action))
+                .collect(),
+            vec![],
+            modes,
+            settings(),
+        )
+    }
+
+    fn app(name: &str) -> Option<AppInfo> {
+        Some(AppInfo {
+            name: name.to_string(),
+            pid: 1,
+            bundle_id: None,
+            titles: Vec::new(),
+        })
+    }
+
+    fn resize_mode() -> ModeKeys {
+        mode(
+            "resize",
+            "ctrl+alt+r",
+            &[
+                ("h", "resize:50"),
+                ("l", "resize:80"),
+                ("shift+f", "resize:full"),
+            ],
+        )
+    }
+
+    fn run(action: &str, keys: &str, mode: Option<&str>) -> Signal {
+        Signal::Run {
+            action: action.to_string(),
+            keys: seq(keys).to_string(),
+            mode: mode.map(str::to_string),
+        }
+    }
+
+    fn entered(mode: &str) -> Signal {
+        Signal::ModeEntered {
+            mode: mode.to_string(),
+        }
+    }
+
+    fn exited(mode: &str, reason: ExitReason) -> Signal {
+        Signal::ModeExited {
+            mode: mode.to_string(),
+            reason,
+        }
+    }
+
+    #[test]
+    fn test_shortcuts_without_modes() {
+        let mut k = keymap(&[("ctrl+alt+m", "maximize")], vec![]);
+        let now = Instant::now();
+
+        assert_eq!(
+            k.press(&chord("ctrl+alt+m"), now, || None),
+            Press::Consumed(vec![run("maximize", "ctrl+alt+m", None)])
+        );
+        assert_eq!(k.press(&chord("h"), now, || None), Press::Pass);
+        assert_eq!(k.active_mode(), None);
+    }
+
+    #[test]
+    fn test_mode_bindings_only_while_active() {
+        let mut k = keymap(&[], vec![resize_mode()]);
+        let now = Instant::now();
+
+        assert_eq!(k.press(&chord("h"), now, || None), Press::Pass);
+
+        assert_eq!(
+            k.press(&chord("ctrl+alt+r"), now, || None),
+            Press::Consumed(vec![entered("resize")])
+        );
+        assert_eq!(k.active_mode(), Some("resize"));
+
+        assert_eq!(
+            k.press(&chord("h"), now, || None),
+            Press::Consumed(vec![run("resize:50", "h", Some("resize"))])
+        );
+        assert_eq!(
+            k.press(&chord("shift+f"), now, || None),
+            Press::Consumed(vec![run("resize:full", "shift+f", Some("resize"))])
+        );
+        // bindings stay active until the mode is left
+        assert_eq!(
+            k.press(&chord("l"), now, || None),
+            Press::Consumed(vec![run("resize:80", "l", Some("resize"))])
+        );
+    }
+
+    #[test]
+    fn test_unbound_keys_pass_through_in_mode() {
+        let mut k = keymap(&[], vec![resize_mode()]);
+        let now = Instant::now();
+
+        k.press(&chord("ctrl+alt+r"), now, || None);
+        assert_eq!(k.press(&chord("j"), now, || None), Press::Pass);
+        assert_eq!(k.press(&chord("cmd+h"), now, || None), Press::Pass);
+        assert_eq!(k.press(&chord("f"), now, || None), Press::Pass);
+        assert_eq!(k.active_mode(), Some("resize"));
+    }
+
+    #[test]
+    fn test_shortcuts_work_in_mode() {
+        let mut k = keymap(&[("ctrl+alt+m", "maximize")], vec![resize_mode()]);
+        let now = Instant::now();
+
+        k.press(&chord("ctrl+alt+r"), now, || None);
+        assert_eq!(
+            k.press(&chord("ctrl+alt+m"), now, || None),
+            Press::Consumed(vec![run("maximize", "ctrl+alt+m", None)])
+        );
+        assert_eq!(k.active_mode(), Some("resize"));
+    }
+
+    #[test]
+    fn test_mode_binding_beats_shortcut() {
+        let mut k = keymap(&[("h", "maximize")], vec![resize_mode()]);
+        let now = Instant::now();
+
+        assert_eq!(
+            k.press(&chord("h"), now, || None),
+            Press::Consumed(vec![run("maximize", "h", None)])
+        );
+        k.press(&chord("ctrl+alt+r"), now, || None);
+        assert_eq!(
+            k.press(&chord("h"), now, || None),
+            Press::Consumed(vec![run("resize:50", "h", Some("resize"))])
+        );
+    }
+
+    #[test]
+    fn test_exit_key() {
+        let mut k = keymap(&[], vec![resize_mode()]);
+        let now = Instant::now();
+
+        // escape is not ours until a mode is active
+        assert_eq!(k.press(&chord("escape"), now, || None), Press::Pass);
+
+        k.press(&chord("ctrl+alt+r"), now, || None);
+        assert_eq!(
+            k.press(&chord("escape"), now, || None),
+            Press::Consumed(vec![exited("resize", ExitReason::Exit)])
+        );
+        assert_eq!(k.active_mode(), None);
+        assert_eq!(k.press(&chord("h"), now, || None), Press::Pass);
+    }
+
+    #[test]
+    fn test_custom_exit_key() {
+        let mut resize = resize_mode();
+        resize.exit = chord("return");
+        let mut k = keymap(&[], vec![resize]);
+        let now = Instant::now();
+
+        k.press(&chord("ctrl+alt+r"), now, || None);
+        assert_eq!(k.press(&chord("escape"), now, || None), Press::Pass);
+        assert_eq!(
+            k.press(&chord("return"), now, || None),
+            Press::Consumed(vec![exited("resize", ExitReason::Exit)])
+        );
+    }
+
+    #[test]
+    fn test_switching_modes() {
+        let mut k = keymap(
+            &[],
+            vec![
+                resize_mode(),
+                mode("move", "ctrl+alt+v", &[("h", "move:left")]),
+            ],
+        );
+        let now = Instant::now();
+
+        k.press(&chord("ctrl+alt+r"), now, || None);
+        assert_eq!(
+            k.press(&chord("ctrl+alt+v"), now, || None),
+            Press::Consumed(vec![
+                exited("resize", ExitReason::Switched),
+                entered("move")
+            ])
+        );
+        assert_eq!(
+            k.press(&chord("h"), now, || None),
+            Press::Consumed(vec![run("move:left", "h", Some("move"))])
+        );
+    }
+
+    #[test]
+    fn test_entering_active_mode_again() {
+        let mut resize = resize_mode();
+        resize.timeout = Some(MODE_TIMEOUT);
+        let mut k = keymap(&[], vec![resize]);
+        let start = Instant::now();
+
+        k.press(&chord("ctrl+alt+r"), start, || None);
+        let later = start + Duration::from_millis(2000);
+        assert_eq!(
+            k.press(&chord("ctrl+alt+r"), later, || None),
+            Press::Consumed(vec![])
+        );
+        assert_eq!(k.deadline(), Some(later + MODE_TIMEOUT));
+    }
+
+    #[test]
+    fn test_mode_timeout() {
+        let mut resize = resize_mode();
+        resize.timeout = Some(MODE_TIMEOUT);
+        let mut k = keymap(&[], vec![resize]);
+        let start = Instant::now();
+
+        k.press(&chord("ctrl+alt+r"), start, || None);
+        assert_eq!(k.deadline(), Some(start + MODE_TIMEOUT));
+
+        // pressing a binding restarts the timeout
+        let later = start + Duration::from_millis(2000);
+        k.press(&chord("h"), later, || None);
+        assert_eq!(k.deadline(), Some(later + MODE_TIMEOUT));
+        assert_eq!(k.expire(start + MODE_TIMEOUT), vec![]);
+
+        // keys that pass through do not
+        k.press(&chord("j"), later + Duration::from_millis(1000), || None);
+        assert_eq!(k.deadline(), Some(later + MODE_TIMEOUT));
+
+        assert_eq!(
+            k.expire(later + MODE_TIMEOUT),
+            vec![exited("resize", ExitReason::Timeout)]
+        );
+        assert_eq!(k.active_mode(), None);
+        assert_eq!(k.deadline(), None);
+    }
+
+    #[test]
+    fn test_mode_without_timeout_stays() {
+        let mut k = keymap(&[], vec![resize_mode()]);
+        let now = Instant::now();
+
+        k.press(&chord("ctrl+alt+r"), now, || None);
+        assert_eq!(k.deadline(), None);
+        assert_eq!(k.expire(now + Duration::from_secs(3600)), vec![]);
+        assert_eq!(k.active_mode(), Some("resize"));
+    }
+
+    #[test]
+    fn test_sequence_enters_mode() {
+        let mut k = keymap(
+            &[("ctrl+alt+w, h", "move:left")],
+            vec![mode("resize", "ctrl+alt+w, r", &[("h", "resize:50")])],
+        );
+        let now = Instant::now();
+
+        assert_eq!(
+            k.press(&chord("ctrl+alt+w"), now, || None),
+            Press::Consumed(vec![Signal::SequencePending {
+                pressed: seq("ctrl+alt+w"),
+                next: vec![chord("h"), chord("r")],
+            }])
+        );
+        assert_eq!(
+            k.press(&chord("r"), now, || None),
+            Press::Consumed(vec![entered("resize")])
+        );
+    }
+
+    #[test]
+    fn test_pending_sequence_beats_mode_keys() {
+        let mut k = keymap(&[("ctrl+alt+w, h", "move:left")], vec![resize_mode()]);
+        let now = Instant::now();
+
+        k.press(&chord("ctrl+alt+r"), now, || None);
+        k.press(&chord("ctrl+alt+w"), now, || None);
+        assert_eq!(
+            k.press(&chord("h"), now, || None),
+            Press::Consumed(vec![run("move:left", "ctrl+alt+w, h", None)])
+        );
+
+        // escape cancels the sequence, not the mode
+        k.press(&chord("ctrl+alt+w"), now, || None);
+        assert_eq!(
+            k.press(&chord("escape"), now, || None),
+            Press::Consumed(vec![Signal::SequenceCancelled {
+                pressed: seq("ctrl+alt+w"),
+                reason: CancelReason::Escape,
+            }])
+        );
+        assert_eq!(k.active_mode(), Some("resize"));
+    }
+
+    #[test]
+    fn test_shortcut_beats_mode_enter() {
+        let mut k = keymap(&[("ctrl+alt+r", "maximize")], vec![resize_mode()]);
+        assert_eq!(
+            k.press(&chord("ctrl+alt+r"), Instant::now(), || None),
+            Press::Consumed(vec![run("maximize", "ctrl+alt+r", None)])
+        );
+        assert_eq!(k.active_mode(), None);
+    }
+
+    #[test]
+    fn test_expire_sequence_and_mode_together() {
+        let mut resize = resize_mode();
+        resize.timeout = Some(SEQUENCE_TIMEOUT);
+        let mut k = keymap(&[("ctrl+alt+w, h", "move:left")], vec![resize]);
+        let now = Instant::now();
+
+        k.press(&chord("ctrl+alt+r"), now, || None);
+        k.press(&chord("ctrl+alt+w"), now, || None);
+        assert_eq!(k.deadline(), Some(now + SEQUENCE_TIMEOUT));
+        assert_eq!(
+            k.expire(now + SEQUENCE_TIMEOUT),
+            vec![
+                Signal::SequenceCancelled {
+                    pressed: seq("ctrl+alt+w"),
+                    reason: CancelReason::Timeout,
+                },
+                exited("resize", ExitReason::Timeout),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_app_scopes() {
+        let now = Instant::now();
+        let mut only = shortcut("cmd+shift+m", "maximize");
+        only.scope.only_in = vec!["Safari".to_string()];
+        let mut except = shortcut("cmd+shift+m", "resize:80");
+        except.scope.except_in = vec!["Xcode".to_string()];
+        let mut k = Keymap::new(vec![only, except], vec![], vec![], settings());
+
+        assert_eq!(
+            k.press(&chord("cmd+shift+m"), now, || app("Safari")),
+            Press::Consumed(vec![run("maximize", "cmd+shift+m", None)])
+        );
+        assert_eq!(
+            k.press(&chord("cmd+shift+m"), now, || app("Terminal")),
+            Press::Consumed(vec![run("resize:80", "cmd+shift+m", None)])
+        );
+        assert_eq!(
+            k.press(&chord("cmd+shift+m"), now, || app("Xcode")),
+            Press::Pass
+        );
+        // with no frontmost app known, only unrestricted shortcuts apply
+        assert_eq!(
+            k.press(&chord("cmd+shift+m"), now, || None),
+            Press::Consumed(vec![run("resize:80", "cmd+shift+m", None)])
+        );
+    }
+
+    #[test]
+    fn test_disabled_in() {
+        let now = Instant::now();
+        let mut k = Keymap::new(
+            vec![shortcut("ctrl+alt+m", "maximize")],
+            vec![],
+            vec![resize_mode()],
+            KeymapSettings {
+                disabled_in: vec!["Parallels Desktop".to_string()],
+                ..settings()
+            },
+        );
+
+        assert_eq!(
+            k.press(&chord("ctrl+alt+m"), now, || app("Parallels Desktop")),
+            Press::Pass
+        );
+        k.press(&chord("ctrl+alt+r"), now, || app("Finder"));
+        assert_eq!(
+            k.press(&chord("h"), now, || app("Parallels Desktop")),
+            Press::Pass
+        );
+        assert_eq!(k.active_mode(), Some("resize"));
+    }
+
+    #[test]
+    fn test_mouse() {
+        use super::super::mouse::{MouseAction, MouseChord};
+        use crate::window::drag::DragKind;
+
+        let chord = MouseChord::parse("alt+left").unwrap();
+        let mut k = Keymap::new(
+            vec![],
+            vec![],
+            vec![],
+            KeymapSettings {
+                disabled_in: vec!["Steam".to_string()],
+                ..settings()
+            },
+        )
+        .with_mouse(vec![MouseBinding {
+            chord,
+            action: MouseAction::Drag(DragKind::Move),
+        }]);
+        let at = Point::new(5.0, 5.0);
+
+        assert_eq!(
+            k.mouse(chord.modifiers, MouseEvent::Down(1), at, || app("Steam")),
+            Press::Pass
+        );
+        // other clicks need no app lookup
+        assert_eq!(
+            k.mouse(Modifiers::default(), MouseEvent::Down(1), at, || {
+                panic!("looked up for a plain click")
+            }),
+            Press::Pass
+        );
+        assert_eq!(
+            k.mouse(chord.modifiers, MouseEvent::Down(1), at, || app("Finder")),
+            Press::Consumed(vec![Signal::Mouse(MouseSignal::DragStarted {
+                kind: DragKind::Move,
+                binding: "alt+button1".to_string(),
+                at,
+            })])
+        );
+        assert_eq!(
+            k.mouse(Modifiers::default(), MouseEvent::Up(1), at, || app("Steam")),
+            Press::Consumed(vec![Signal::Mouse(MouseSignal::DragEnded { at })])
+        );
+    }
+
+    #[test]
+    fn test_frontmost_looked_up_only_when_needed() {
+        let now = Instant::now();
+        let mut k = Keymap::new(
+            vec![shortcut("ctrl+alt+m", "maximize")],
+            vec![],
+            vec![],
+            KeymapSettings {
+                disabled_in: vec!["Steam".to_string()],
+                ..settings()
+            },
+        );
+
+        assert_eq!(
+            k.press(&chord("a"), now, || panic!("looked up for typing")),
+            Press::Pass
+        );
+        let mut lookups = 0;
+        k.press(&chord("ctrl+alt+m"), now, || {
+            lookups += 1;
+            app("Finder")
+        });
+        assert_eq!(lookups, 1);
+    }
+
+    #[test]
+    fn test_triggers() {
+        use super::super::trigger::{Gesture, TriggerKey};
+
+        let now = Instant::now();
+        let trigger = |keys: &str, action: &str| TriggerBinding {
+            key: TriggerKey::parse(keys).unwrap(),
+            gesture: Gesture::DoubleTap {
+                within: Duration::from_millis(300),
+            },
+            action: action.to_string(),
+            release_action: None,
+            scope: AppScope::default(),
+        };
+        let mut k = Keymap::new(
+            vec![shortcut("ctrl+alt+m", "maximize")],
+            vec![
+                trigger("ctrl+alt+c", "center"),
+                trigger("rshift", "focus:Terminal"),
+            ],
+            vec![],
+            KeymapSettings {
+                disabled_in: vec!["Steam".to_string()],
+                ..settings()
+            },
+        );
+
+        assert_eq!(
+            k.press(&chord("ctrl+alt+c"), now, || None),
+            Press::Consumed(vec![])
+        );
+        assert!(k.swallows_repeat(&chord("ctrl+alt+c")));
+        assert_eq!(
+            k.press(&chord("ctrl+alt+c"), now, || None),
+            Press::Consumed(vec![Signal::Run {
+                action: "center".to_string(),
+                keys: "ctrl+alt+c (double_tap)".to_string(),
+                mode: None,
+            }])
+        );
+
+        let rshift = ModifierKey::parse("rshift").unwrap();
+        let mut tap = |at: Instant, front: &str| {
+            k.modifier(rshift, true, at, || app(front));
+            k.modifier(rshift, false, at, || app(front))
+        };
+        assert_eq!(tap(now, "Finder"), vec![]);
+        assert_eq!(
+            tap(now + Duration::from_millis(100), "Finder"),
+            vec![Signal::Run {
+                action: "focus:Terminal".to_string(),
+                keys: "rshift (double_tap)".to_string(),
+                mode: None,
+            }]
+        );
+        // off while a disabled_in app is frontmost
+        tap(now + Duration::from_millis(500), "Steam");
+        assert_eq!(tap(now + Duration::from_millis(600), "Steam"), vec![]);
+    }
+
+    #[test]
+    fn test_parse_repeat() {
+        assert_eq!(Repeat::parse("ignore").unwrap(), Repeat::Ignore);
+        assert_eq!(Repeat::parse(" Allow ").unwrap(), Repeat::Allow);
+        assert_eq!(
+            Repeat::parse("throttle:200ms").unwrap(),
+            Repeat::Throttle(Duration::from_millis(200))
+        );
+        assert_eq!(
+            Repeat::parse("throttle:1.5s").unwrap(),
+            Repeat::Throttle(Duration::from_millis(1500))
+        );
+        assert_eq!(
+            Repeat::parse("throttle:200ms").unwrap().to_string(),
+            "throttle:200ms"
+        );
+        assert!(Repeat::parse("throttle").is_err());
+        assert!(Repeat::parse("throttle:0ms").is_err());
+        assert!(Repeat::parse("throttle:fast").is_err());
+        assert!(Repeat::parse("sometimes").is_err());
+    }
+
+    #[test]
+    fn test_repeat() {
+        let ms = Duration::from_millis;
+        let now = Instant::now();
+        let with_repeat = |keys: &str, action: &str, repeat: Repeat| ShortcutKeys {
+            repeat,
+            ..shortcut(keys, action)
+        };
+        let mut k = Keymap::new(
+            vec![
+                with_repeat("ctrl+alt+m", "maximize", Repeat::Ignore),
+                with_repeat("ctrl+alt+right", "move:next", Repeat::Allow),
+                with_repeat("ctrl+alt+l", "resize:+10", Repeat::Throttle(ms(200))),
+            ],
+            vec![],
+            vec![resize_mode()],
+            settings(),
+        );
+
+        k.press(&chord("ctrl+alt+m"), now, || None);
+        assert_eq!(
+            k.repeat(&chord("ctrl+alt+m"), now + ms(50), || None),
+            Press::Consumed(vec![])
+        );
+
+        k.press(&chord("ctrl+alt+right"), now, || None);
+        assert_eq!(
+            k.repeat(&chord("ctrl+alt+right"), now + ms(50), || None),
+            Press::Consumed(vec![run("move:next", "ctrl+alt+right", None)])
+        );
+
+        k.press(&chord("ctrl+alt+l"), now, || None);
+        let mut repeat = |at| k.repeat(&chord("ctrl+alt+l"), now + ms(at), || None);
+        assert_eq!(repeat(100), Press::Consumed(vec![]));
+        assert_eq!(
+            repeat(200),
+            Press::Consumed(vec![run("resize:+10", "ctrl+alt+l", None)])
+        );
+        assert_eq!(repeat(300), Press::Consumed(vec![]));
+
+        // after letting go, the keys down again are a new press
+        k.release("l", now + ms(350));
+        assert_eq!(
+            k.repeat(&chord("ctrl+alt+l"), now + ms(400), || None),
+            Press::Consumed(vec![run("resize:+10", "ctrl+alt+l", None)])
+        );
+        assert_eq!(k.repeat(&chord("l"), now + ms(400), || None), Press::Pass);
+
+        // mode bindings always repeat
+        k.press(&chord("ctrl+alt+r"), now, || None);
+        k.press(&chord("h"), now, || None);
+        assert_eq!(
+            k.repeat(&chord("h"), now + ms(10), || None),
+            Press::Consumed(vec![run("resize:50", "h", Some("resize"))])
+        );
+    }
+
+    #[test]
+    fn test_carry_over() {
+        let now = Instant::now();
+        let mut old = keymap(&[], vec![resize_mode()]);
+        old.press(&chord("ctrl+alt+r"), now, || None);
+
+        let mut kept = resize_mode();
+        kept.timeout = Some(MODE_TIMEOUT);
+        let mut new = keymap(&[], vec![kept]);
+        assert_eq!(new.carry_over(&old, now), vec![]);
+        assert_eq!(new.active_mode(), Some("resize"));
+        assert_eq!(new.deadline(), Some(now + MODE_TIMEOUT));
+
+        let mut without = keymap(&[], vec![]);
+        assert_eq!(
+            without.carry_over(&old, now),
+            vec![exited("resize", ExitReason::Removed)]
+        );
+        assert_eq!(without.active_mode(), None);
+
+        let mut idle = keymap(&[], vec![resize_mode()]);
+        assert_eq!(idle.carry_over(&keymap(&[], vec![]), now), vec![]);
+        assert_eq!(idle.active_mode(), None);
+    }
+}
@@ -0,0 +1,822 @@
+//! daemon metrics
+//!
+//! in-memory counters and latency histograms for IPC requests, actions, hotkeys,
+//! condition evaluations and events. they reset when the daemon restarts and are
+//! read with the `stats` IPC method (`cwm daemon stats`)
+
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+
+/// upper bounds of the latency histogram buckets, in milliseconds
+pub const LATENCY_BUCKETS_MS: &[u64] = &[1, 5, 10, 25, 50, 100, 250, 500, 1000, 2500, 5000];
+
+/// IPC methods counted under their own name
+const IPC_METHODS: &[&str] = &[
+    "$/cancelRequest",
+    "action",
+    "check_permissions",
+    "close",
+    "config",
+    "daemon",
+    "focus",
+    "get",
+    "history",
+    "history_clear",
+    "history_list",
+    "install",
+    "kill",
+    "list",
+    "maximize",
+    "move",
+    "ping",
+    "profile",
+    "record_layout",
+    "record_shortcut",
+    "redo",
+    "remote",
+    "remote_pair",
+    "resize",
+    "shortcuts",
+    "spotlight",
+    "stats",
+    "status",
+    "subscribe",
+    "undo",
+    "uninstall",
+    "update",
+    "version",
+];
+
+/// the label for every other method, so clients can't add a series per
+/// made-up method name
+const OTHER_METHOD: &str = "other";
+
+// ============================================================================
+// Histogram
+// ============================================================================
+
+/// latency histogram with fixed buckets
+///
+/// `buckets[i]` counts observations <= `LATENCY_BUCKETS_MS[i]` (not cumulative);
+/// slower observations are only reflected in `count` and `sum_ms`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Histogram {
+    pub count: u64,
+    pub sum_ms: f64,
+    pub max_ms: f64,
+    pub buckets: Vec<u64>,
+}
+
+impl Histogram {
+    pub fn observe(&mut self, elapsed: Duration) {
+        let ms = elapsed.as_secs_f64() * 1000.0;
+
+        if self.buckets.is_empty() {
+            self.buckets = vec![0; LATENCY_BUCKETS_MS.len()];
+        }
+        if let Some(i) = LATENCY_BUCKETS_MS.iter().position(|&b| ms <= b as f64) {
+            self.buckets[i] += 1;
+        }
+
+        self.count += 1;
+        self.sum_ms += ms;
+        self.max_ms = self.max_ms.max(ms);
+    }
+
+    pub fn mean_ms(&self) -> f64 {
+        if self.count == 0 {
+            0.0
+        } else {
+            self.sum_ms / self.count as f64
+        }
+    }
+
+    /// approximate quantile (0.0..=1.0) as the upper bound of the bucket it falls in
+    pub fn quantile_ms(&self, q: f64) -> Option<u64> {
+        if self.count == 0 {
+            return None;
+        }
+
+        let target = (q * self.count as f64).ceil().max(1.0) as u64;
+        let mut seen = 0;
+        for (i, n) in self.buckets.iter().enumerate() {
+            seen += n;
+            if seen >= target {
+                return Some(LATENCY_BUCKETS_MS[i]);
+            }
+        }
+
+        // beyond the last bucket
+        Some(self.max_ms.ceil() as u64)
+    }
+}
+
+/// count, errors and latency for one IPC method or action
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CallStats {
+    pub count: u64,
+    pub errors: u64,
+    pub latency: Histogram,
+}
+
+impl CallStats {
+    fn record(&mut self, ok: bool, elapsed: Duration) {
+        self.count += 1;
+        if !ok {
+            self.errors += 1;
+        }
+        self.latency.observe(elapsed);
+    }
+}
+
+/// outcomes of `when` condition evaluations
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+pub struct ConditionStats {
+    pub passed: u64,
+    pub failed: u64,
+    /// conditions that couldn't be parsed (treated as not met)
+    pub errors: u64,
+}
+
+/// result of evaluating a `when` condition
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConditionOutcome {
+    Passed,
+    Failed,
+    Error,
+}
+
+impl ConditionOutcome {
+    pub fn from_bool(passed: bool) -> Self {
+        if passed {
+            Self::Passed
+        } else {
+            Self::Failed
+        }
+    }
+}
+
+// ============================================================================
+// Metrics
+// ============================================================================
+
+#[derive(Debug, Default)]
+struct MetricsData {
+    ipc_requests: BTreeMap<String, CallStats>,
+    actions: BTreeMap<String, CallStats>,
+    hotkey_triggers: BTreeMap<String, u64>,
+    conditions: BTreeMap<&'static str, ConditionStats>,
+    events_emitted: BTreeMap<&'static str, u64>,
+}
+
+/// metrics collected by the daemon
+pub struct Metrics {
+    started_at: Instant,
+    data: Mutex<MetricsData>,
+}
+
+impl Metrics {
+    pub fn new() -> Self {
+        Self {
+            started_at: Instant::now(),
+            data: Mutex::new(MetricsData::default()),
+        }
+    }
+
+    /// record a handled IPC request; unknown methods are counted as `other`
+    pub fn record_ipc_request(&self, method: &str, ok: bool, elapsed: Duration) {
+        let method = if IPC_METHODS.contains(&method) {
+            method
+        } else {
+            OTHER_METHOD
+        };
+        if let Ok(mut data) = self.data.lock() {
+            data.ipc_requests
+                .entry(method.to_string())
+                .or_default()
+                .record(ok, elapsed);
+        }
+    }
+
+    /// record an executed action, keyed by action name (e.g. "focus", "move")
+    pub fn record_action(&self, action: &str, ok: bool, elapsed: Duration) {
+        if let Ok(mut data) = self.data.lock() {
+            data.actions
+                .entry(action.to_string())
+                .or_default()
+                .record(ok, elapsed);
+        }
+    }
+
+    /// record a hotkey press, keyed by the hotkey string
+    pub fn record_hotkey(&self, hotkey: &str) {
+        if let Ok(mut data) = self.data.lock() {
+            *data.hotkey_triggers.entry(hotkey.to_string()).or_default() += 1;
+        }
+    }
+
+    /// record a condition evaluation; `source` is "shortcut" or "app_rule"
+    pub fn record_condition(&self, source: &'static str, outcome: ConditionOutcome) {
+        if let Ok(mut data) = self.data.lock() {
+            let stats = data.conditions.entry(source).or_default();
+            match outcome {
+                ConditionOutcome::Passed => stats.passed += 1,
+                ConditionOutcome::Failed => stats.failed += 1,
+                ConditionOutcome::Error => stats.errors += 1,
+            }
+        }
+    }
+
+    /// record an event emitted on the event bus
+    pub fn record_event(&self, event_type: &'static str) {
+        if let Ok(mut data) = self.data.lock() {
+            *data.events_emitted.entry(event_type).or_default() += 1;
+        }
+    }
+
+    /// take a snapshot of all metrics plus the gauges only the daemon knows about
+    pub fn snapshot(&self, gauges: Gauges) -> StatsSnapshot {
+        let data = match self.data.lock() {
+            Ok(data) => data,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+
+        StatsSnapshot {
+            uptime_secs: self.started_at.elapsed().as_secs(),
+            ipc_requests: data.ipc_requests.clone(),
+            actions: data.actions.clone(),
+            hotkey_triggers: data.hotkey_triggers.clone(),
+            conditions: data
+                .conditions
+                .iter()
+                .map(|(k, v)| (k.to_string(), *v))
+                .collect(),
+            events_emitted: data
+                .events_emitted
+                .iter()
+                .map(|(k, v)| (k.to_string(), *v))
+                .collect(),
+            subscribers: gauges.subscribers,
+            events_dropped: gauges.events_dropped,
+            history: gauges.history,
+        }
+    }
+}
+
+impl Default for Metrics {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// current values sampled when a snapshot is taken
+#[derive(Debug, Clone, Default)]
+pub struct Gauges {
+    pub subscribers: Vec<SubscriberStats>,
+    /// events that couldn't be delivered, including to subscribers that are gone
+    pub events_dropped: u64,
+    pub history: Option<HistoryStats>,
+}
+
+/// delivery counters for one event subscriber
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SubscriberStats {
+    pub id: u64,
+    pub events: Vec<String>,
+    pub delivered: u64,
+    pub dropped: u64,
+}
+
+/// undo/redo stack sizes
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct HistoryStats {
+    pub undo: usize,
+    pub redo: usize,
+    pub limit: usize,
+}
+
+/// all metrics at a point in time, as returned by the `stats` IPC method
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StatsSnapshot {
+    pub uptime_secs: u64,
+    pub ipc_requests: BTreeMap<String, CallStats>,
+    pub actions: BTreeMap<String, CallStats>,
+    pub hotkey_triggers: BTreeMap<String, u64>,
+    pub conditions: BTreeMap<String, ConditionStats>,
+    pub events_emitted: BTreeMap<String, u64>,
+    pub events_dropped: u64,
+    pub subscribers: Vec<SubscriberStats>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub history: Option<HistoryStats>,
+}
+
+// ============================================================================
+// Prometheus text format
+// ============================================================================
+
+/// escape a label value for the Prometheus text format
+fn escape_label(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+fn write_header(out: &mut String, name: &str, kind: &str, help: &str) {
+    let _ = writeln!(out, "# HELP {} {}", name, help);
+    let _ = writeln!(out, "# TYPE {} {}", name, kind);
+}
+
+/// write call counters and a latency histogram labelled with `label`
+fn write_calls(
+    out: &mut String,
+    prefix: &str,
+    label: &str,
+    what: &str,
+    calls: &BTreeMap<String, CallStats>,
+) {
+    let total = format!("{}_total", prefix);
+    write_header(out, &total, "counter", &format!("{} handled", what));
+    for (name, stats) in calls {
+        let _ = writeln!(
+            out,
+            "{}{{{}=\"{}\"}} {}",
+            total,
+            label,
+            escape_label(name),
+            stats.count
+        );
+    }
+
+    let errors = format!("{}_errors_total", prefix);
+    write_header(out, &errors, "counter", &format!("{} that failed", what));
+    for (name, stats) in calls {
+        let _ = writeln!(
+            out,
+            "{}{{{}=\"{}\"}} {}",
+            errors,
+            label,
+            escape_label(name),
+            stats.errors
+        );
+    }
+
+    let hist = format!("{}_duration_seconds", prefix);
+    write_header(
+        out,
+        &hist,
+        "histogram",
+        &format!("latency of {}", what.to_lowercase()),
+    );
+    for (name, stats) in calls {
+        let name = escape_label(name);
+        let mut cumulative = 0;
+        for (bound, n) in LATENCY_BUCKETS_MS.iter().zip(&stats.latency.buckets) {
+            cumulative += n;
+            let _ = writeln!(
+                out,
+                "{}_bucket{{{}=\"{}\",le=\"{}\"}} {}",
+                hist,
+                label,
+                name,
+                *bound as f64 / 1000.0,
+                cumulative
+            );
+        }
+        let _ = writeln!(
+            out,
+            "{}_bucket{{{}=\"{}\",le=\"+Inf\"}} {}",
+            hist, label, name, stats.latency.count
+        );
+        let _ = writeln!(
+            out,
+            "{}_sum{{{}=\"{}\"}} {}",
+            hist,
+            label,
+            name,
+            stats.latency.sum_ms / 1000.0
+        );
+        let _ = writeln!(
+            out,
+            "{}_count{{{}=\"{}\"}} {}",
+            hist, label, name, stats.latency.count
+        );
+    }
+}
+
+/// render a snapshot in the Prometheus text exposition format
+pub fn render_prometheus(stats: &StatsSnapshot) -> String {
+    let mut out = String::new();
+
+    write_header(
+        &mut out,
+        "cwm_uptime_seconds",
+        "gauge",
+        "Seconds since the daemon started",
+    );
+    let _ = writeln!(out, "cwm_uptime_seconds {}", stats.uptime_secs);
+
+    write_calls(
+        &mut out,
+        "cwm_ipc_requests",
+        "method",
+        "IPC requests",
+        &stats.ipc_requests,
+    );
+    write_calls(&mut out, "cwm_actions", "action", "Actions", &stats.actions);
+
+    write_header(
+        &mut out,
+        "cwm_hotkey_triggers_total",
+        "counter",
+        "Hotkey presses",
+    );
+    for (hotkey, n) in &stats.hotkey_triggers {
+        let _ = writeln!(
+            out,
+            "cwm_hotkey_triggers_total{{hotkey=\"{}\"}} {}",
+            escape_label(hotkey),
+            n
+        );
+    }
+
+    write_header(
+        &mut out,
+        "cwm_condition_evaluations_total",
+        "counter",
+        "Condition evaluations by outcome",
+    );
+    for (source, c) in &stats.conditions {
+        for (outcome, n) in [
+            ("passed", c.passed),
+            ("failed", c.failed),
+            ("error", c.errors),
+        ] {
+            let _ = writeln!(
+                out,
+                "cwm_condition_evaluations_total{{source=\"{}\",outcome=\"{}\"}} {}",
+                source, outcome, n
+            );
+        }
+    }
+
+    write_header(
+        &mut out,
+        "cwm_events_emitted_total",
+        "counter",
+        "Events emitted on the event bus",
+    );
+    for (event, n) in &stats.events_emitted {
+        let _ = writeln!(out, "cwm_events_emitted_total{{event=\"{}\"}} {}", event, n);
+    }
+
+    write_header(
+        &mut out,
+        "cwm_events_dropped_total",
+        "counter",
+        "Events that could not be delivered to a subscriber",
+    );
+    let _ = writeln!(out, "cwm_events_dropped_total {}", stats.events_dropped);
+
+    write_header(
+        &mut out,
+        "cwm_subscribers",
+        "gauge",
+        "Active event subscribers",
+    );
+    let _ = writeln!(out, "cwm_subscribers {}", stats.subscribers.len());
+
+    write_header(
+        &mut out,
+        "cwm_subscriber_events_delivered_total",
+        "counter",
+        "Events delivered per subscriber",
+    );
+    for sub in &stats.subscribers {
+        let _ = writeln!(
+            out,
+            "cwm_subscriber_events_delivered_total{{subscriber=\"{}\"}} {}",
+            sub.id, sub.delivered
+        );
+    }
+
+    write_header(
+        &mut out,
+        "cwm_subscriber_events_dropped_total",
+        "counter",
+        "Events dropped per subscriber",
+    );
+    for sub in &stats.subscribers {
+        let _ = writeln!(
+            out,
+            "cwm_subscriber_events_dropped_total{{subscriber=\"{}\"}} {}",
+            sub.id, sub.dropped
+        );
+    }
+
+    if let Some(history) = stats.history {
+        write_header(
+            &mut out,
+            "cwm_history_entries",
+            "gauge",
+            "Entries on the undo/redo stacks",
+        );
+        let _ = writeln!(
+            out,
+            "cwm_history_entries{{stack=\"undo\"}} {}",
+            history.undo
+        );
+        let _ = writeln!(
+            out,
+            "cwm_history_entries{{stack=\"redo\"}} {}",
+            history.redo
+        );
+    }
+
+    out
+}
+
+// ============================================================================
+// Text summary
+// ============================================================================
+
+fn format_duration(secs: u64) -> String {
+    let (h, m, s) = (secs / 3600, (secs % 3600) / 60, secs % 60);
+    if h > 0 {
+        format!("{}h {}m {}s", h, m, s)
+    } else if m > 0 {
+        format!("{}m {}s", m, s)
+    } else {
+        format!("{}s", s)
+    }
+}
+
+fn write_call_table(
+    out: &mut String,
+    title: &str,
+    column: &str,
+    calls: &BTreeMap<String, CallStats>,
+) {
+    if calls.is_empty() {
+        return;
+    }
+
+    let width = calls
+        .keys()
+        .map(|k| k.len())
+        .max()
+        .unwrap_or(0)
+        .max(column.len());
+    let _ = writeln!(out, "\n{}:", title);
+    let _ = writeln!(
+        out,
+        "  {:<width$}  {:>7}  {:>6}  {:>9}  {:>7}",
+        column,
+        "COUNT",
+        "ERRORS",
+        "MEAN",
+        "P95",
+        width = width
+    );
+    for (name, stats) in calls {
+        let p95 = stats
+            .latency
+            .quantile_ms(0.95)
+            .map(|ms| format!("{}ms", ms))
+            .unwrap_or_else(|| "-".to_string());
+        let _ = writeln!(
+            out,
+            "  {:<width$}  {:>7}  {:>6}  {:>7.1}ms  {:>7}",
+            name,
+            stats.count,
+            stats.errors,
+            stats.latency.mean_ms(),
+            p95,
+            width = width
+        );
+    }
+}
+
+/// human-readable summary printed by `cwm daemon stats`
+pub fn render_text(stats: &StatsSnapshot) -> String {
+    let mut out = String::new();
+    let _ = writeln!(out, "Uptime: {}", format_duration(stats.uptime_secs));
+
+    write_call_table(&mut out, "IPC requests", "METHOD", &stats.ipc_requests);
+    write_call_table(&mut out, "Actions", "ACTION", &stats.actions);
+
+    if !stats.hotkey_triggers.is_empty() {
+        let _ = writeln!(out, "\nHotkeys:");
+        for (hotkey, n) in &stats.hotkey_triggers {
+            let _ = writeln!(out, "  {}  {}", hotkey, n);
+        }
+    }
+
+    if !stats.conditions.is_empty() {
+        let _ = writeln!(out, "\nConditions:");
+        for (source, c) in &stats.conditions {
+            let _ = writeln!(
+                out,
+                "  {}  passed {}  failed {}  errors {}",
+                source, c.passed, c.failed, c.errors
+            );
+        }
+    }
+
+    let emitted: u64 = stats.events_emitted.values().sum();
+    let _ = writeln!(
+        out,
+        "\nEvents: {} emitted, {} dropped",
+        emitted, stats.events_dropped
+    );
+    for (event, n) in &stats.events_emitted {
+        let _ = writeln!(out, "  {}  {}", event, n);
+    }
+
+    if stats.subscribers.is_empty() {
+        let _ = writeln!(out, "\nSubscribers: none");
+    } else {
+        let _ = writeln!(out, "\nSubscribers:");
+        for sub in &stats.subscribers {
+            let events = if sub.events.is_empty() {
+                "*".to_string()
+            } else {
+                sub.events.join(",")
+            };
+            let _ = writeln!(
+                out,
+                "  #{}  {}  delivered {}  dropped {}",
+                sub.id, events, sub.delivered, sub.dropped
+            );
+        }
+    }
+
+    match stats.history {
+        Some(h) => {
+            let _ = writeln!(
+                out,
+                "\nHistory: {} undo, {} redo (limit {})",
+                h.undo, h.redo, h.limit
+            );
+        }
+        None => {
+            let _ = writeln!(out, "\nHistory: disabled");
+        }
+    }
+
+    out
+}
+
+// ============================================================================
+// Global Metrics
+// ============================================================================
+
+lazy_static::lazy_static! {
+    /// global metrics instance
+    pub static ref METRICS: Metrics = Metrics::new();
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_histogram_buckets() {
+        let mut h = Histogram::default();
+        h.observe(Duration::from_micros(500));
+        h.observe(Duration::from_millis(7));
+        h.observe(Duration::from_millis(7));
+        h.observe(Duration::from_secs(10));
+
+        assert_eq!(h.count, 4);
+        assert_eq!(h.buckets[0], 1); // <= 1ms
+        assert_eq!(h.buckets[2], 2); // <= 10ms
+                                     // 10s is past the last bucket
+        assert_eq!(h.buckets.iter().sum::<u64>(), 3);
+        assert!(h.max_ms >= 10_000.0);
+    }
+
+    #[test]
+    fn test_histogram_quantiles() {
+        let mut h = Histogram::default();
+        assert_eq!(h.quantile_ms(0.5), None);
+
+        for _ in 0..9 {
+            h.observe(Duration::from_millis(3));
+        }
+        h.observe(Duration::from_millis(300));
+
+        assert_eq!(h.quantile_ms(0.5), Some(5));
+        assert_eq!(h.quantile_ms(0.9), Some(5));
+        assert_eq!(h.quantile_ms(0.99), Some(500));
+    }
+
+    #[test]
+    fn test_record_and_snapshot() {
+        let metrics = Metrics::new();
+        metrics.record_ipc_request("focus", true, Duration::from_millis(12));
+        metrics.record_ipc_request("focus", false, Duration::from_millis(3));
+        metrics.record_action("maximize", true, Duration::from_millis(40));
+        metrics.record_hotkey("ctrl+alt+m");
+        metrics.record_hotkey("ctrl+alt+m");
+        metrics.record_condition("shortcut", ConditionOutcome::Passed);
+        metrics.record_condition("shortcut", ConditionOutcome::Error);
+        metrics.record_event("app.focused");
+
+        let snapshot = metrics.snapshot(Gauges {
+            history: Some(HistoryStats {
+                undo: 3,
+                redo: 1,
+                limit: 50,
+            }),
+            ..Default::default()
+        });
+
+        let focus = &snapshot.ipc_requests["focus"];
+        assert_eq!(focus.count, 2);
+        assert_eq!(focus.errors, 1);
+        assert_eq!(snapshot.actions["maximize"].count, 1);
+        assert_eq!(snapshot.hotkey_triggers["ctrl+alt+m"], 2);
+        assert_eq!(snapshot.conditions["shortcut"].passed, 1);
+        assert_eq!(snapshot.conditions["shortcut"].errors, 1);
+        assert_eq!(snapshot.events_emitted["app.focused"], 1);
+
+        let json = serde_json::to_value(&snapshot).unwrap();
+        assert_eq!(json["ipc_requests"]["focus"]["errors"], 1);
+        assert_eq!(json["history"]["undo"], 3);
+    }
+
+    #[test]
+    fn test_unknown_ipc_methods_share_a_label() {
+        let metrics = Metrics::new();
+        metrics.record_ipc_request("ping", true, Duration::from_millis(1));
+        metrics.record_ipc_request("made_up_1", false, Duration::from_millis(1));
+        metrics.record_ipc_request("made_up_2", false, Duration::from_millis(1));
+
+        let snapshot = metrics.snapshot(Gauges::default());
+        assert_eq!(snapshot.ipc_requests.len(), 2);
+        assert_eq!(snapshot.ipc_requests["ping"].count, 1);
+        assert_eq!(snapshot.ipc_requests["other"].count, 2);
+    }
+    #[test]
+    fn test_render_text() {
+        let metrics = Metrics::new();
+        metrics.record_ipc_request("focus", true, Duration::from_millis(12));
+        metrics.record_event("app.focused");
+
+        let text = render_text(&metrics.snapshot(Gauges::default()));
+        assert!(text.starts_with("Uptime: 0s"));
+        assert!(text.contains("IPC requests:"));
+        assert!(text.contains("METHOD"));
+        assert!(text.contains("25ms"));
+        // empty sections are left out
+        assert!(!text.contains("Actions:"));
+        assert!(!text.contains("Hotkeys:"));
+        assert!(text.contains("Events: 1 emitted, 0 dropped"));
+        assert!(text.contains("Subscribers: none"));
+        assert!(text.contains("History: disabled"));
+    }
+
+    #[test]
+    fn test_format_duration() {
+        assert_eq!(format_duration(42), "42s");
+        assert_eq!(format_duration(125), "2m 5s");
+        assert_eq!(format_duration(3725), "1h 2m 5s");
+    }
+
+    #[test]
+    fn test_render_prometheus() {
+        let metrics = Metrics::new();
+        metrics.record_ipc_request("focus", true, Duration::from_millis(12));
+        metrics.record_hotkey("ctrl+\"x\"");
+
+        let text = render_prometheus(&metrics.snapshot(Gauges {
+            subscribers: vec![SubscriberStats {
+                id: 4,
+                events: vec!["app.focused".to_string()],
+                delivered: 7,
+                dropped: 0,
+            }],
+            ..Default::default()
+        }));
+
+        assert!(text.contains("# TYPE cwm_ipc_requests_total counter"));
+        assert!(text.contains("cwm_ipc_requests_total{method=\"focus\"} 1"));
+        assert!(text
+            .contains("cwm_ipc_requests_duration_seconds_bucket{method=\"focus\",le=\"0.025\"} 1"));
+        assert!(text
+            .contains("cwm_ipc_requests_duration_seconds_bucket{method=\"focus\",le=\"0.01\"} 0"));
+        assert!(text.contains("cwm_ipc_requests_duration_seconds_count{method=\"focus\"} 1"));
+        assert!(text.contains("cwm_hotkey_triggers_total{hotkey=\"ctrl+\\\"x\\\"\"} 1"));
+        assert!(text.contains("cwm_subscriber_events_delivered_total{subscriber=\"4\"} 7"));
+        assert!(!text.contains("cwm_history_entries"));
+    }
+}
@@ -1,9 +1,28 @@
+//! the background daemon: hotkey and mouse listeners, app and display
+//! watchers, and the IPC and remote sockets
+//!
+//! what a key press, click or drag does is decided by state machines that do
+//! no I/O (`sequence`, `trigger`, `mouse`, `cycle`, `queue`, and
+//! `window::drag` for the geometry). the listeners feed them events with the
+//! current time or cursor position and this module carries out what they hand
+//! back, so they are tested without an event tap or real windows
+
 pub mod app_watcher;
+pub mod auth;
+pub mod cycle;
 pub mod display_watcher;
 pub mod events;
 pub mod hotkeys;
 pub mod ipc;
+pub mod keyboard;
+pub mod keymap;
 mod launchd;
+pub mod metrics;
+pub mod mouse;
+pub mod queue;
+mod requests;
+pub mod sequence;
+pub mod trigger;
 
 use anyhow::{anyhow, Result};
 use std::fs::{File, OpenOptions};
@@ -11,28 +30,67 @@ use std::io::Write;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, Mutex};
 
+use crate::actions::{Progress, RequestControl};
 use crate::conditions::{evaluate, parse_condition, EvalContext, WindowState};
-use crate::config::{self, should_launch, Config, Shortcut};
+use crate::config::{self, should_launch, Config, MouseDrag, MouseWindow, Shortcut, Trigger};
 use crate::display;
+use crate::window::drag::{Drag, DragKind, Frame, Point};
 use crate::window::{manager, matching};
 
 use std::collections::HashMap;
 
+use cycle::{Cycles, WindowSnapshot};
 use events::Event;
 
-use hotkeys::Hotkey;
 use ipc::{
-    format_error_response, format_success_response, get_pid_file_path, get_socket_path,
-    is_daemon_running, remove_pid_file, remove_socket_file, write_pid_file, IpcRequest,
+    acquire_daemon_lock, ensure_runtime_dir, format_error_response, format_progress_notification,
+    format_success_response, get_socket_path, is_daemon_lock_held, is_daemon_running, read_pid,
+    remove_pid_file, remove_socket_file, write_pid_file, IpcRequest,
 };
+use keymap::{AppScope, Keymap, KeymapSettings, ModeKeys, Repeat, ShortcutKeys, Signal};
 pub use launchd::{install, uninstall};
+use metrics::{ConditionOutcome, METRICS};
+use mouse::{MouseAction, MouseChord, MouseSignal};
+use queue::ActionQueue;
+use requests::Peer;
+use sequence::KeySequence;
+use trigger::{Gesture, TriggerBinding, TriggerKey};
 
 use crate::cli::exit_codes;
 use crate::history::HistoryManager;
+use crate::remote;
 
 static DAEMON_SHOULD_STOP: AtomicBool = AtomicBool::new(false);
 static LOG_FILE: Mutex<Option<File>> = Mutex::new(None);
 static HISTORY_MANAGER: Mutex<Option<HistoryManager>> = Mutex::new(None);
+static REMOTE_STATE: Mutex<Option<Arc<remote::server::ServerState>>> = Mutex::new(None);
+static PROFILE_STATE: Mutex<Option<ProfileState>> = Mutex::new(None);
+static CYCLES: Mutex<Cycles> = Mutex::new(Cycles::new());
+static DRAG: Mutex<Option<WindowDrag>> = Mutex::new(None);
+static ACTION_QUEUE: Mutex<ActionQueue<QueuedAction>> = Mutex::new(ActionQueue::new());
+
+/// base config and the profile applied on top of it
+struct ProfileState {
+    base: Config,
+    active: Option<String>,
+    effective: Arc<Config>,
+}
+
+/// a mouse binding's drag moving or resizing a window
+struct WindowDrag {
+    drag: Drag,
+    app: matching::AppInfo,
+    window: manager::Window,
+    /// the frame last applied to the window
+    frame: Frame,
+    /// the window before the drag, for undo
+    history: Option<crate::history::HistoryEntry>,
+}
+
+lazy_static::lazy_static! {
+    /// IPC requests currently being handled, for `$/cancelRequest`
+    static ref ACTIVE_REQUESTS: requests::RequestRegistry = requests::RequestRegistry::new();
+}
 
 fn log(msg: &str) {
     let timestamp = chrono::Local::now().format("%Y-%m-%d %H:%M:%S");
@@ -81,9 +139,11 @@ fn setup_logging(log_path: Option<String>) -> Result<()> {
 
 /// Start the daemon in the foreground (blocking)
 pub fn start_foreground(log_path: Option<String>) -> Result<()> {
-    if is_daemon_running() {
-        return Err(anyhow!("Daemon is already running"));
-    }
+    ensure_runtime_dir()?;
+
+    // held until this function returns; a second daemon for the same runtime
+    // directory fails here instead of stealing the socket
+    let _lock = acquire_daemon_lock()?;
 
     setup_logging(log_path)?;
 
@@ -95,8 +155,31 @@ pub fn start_foreground(log_path: Option<String>) -> Result<()> {
 
     log("cwm daemon starting...");
 
-    // load config
-    let config = config::load()?;
+    // load config and apply the persisted profile
+    let base = config::load_base_with_override(None)?;
+    let mut active = config::profile::load_active();
+    let config = match config::profile::effective(&base, active.as_deref()) {
+        Ok(config) => config,
+        Err(e) => {
+            log_err(&format!(
+                "Failed to apply profile, using base config: {}",
+                e
+            ));
+            active = None;
+            base.clone()
+        }
+    };
+    if let Some(ref name) = active {
+        log(&format!("Active profile: {}", name));
+    }
+    let has_profile_rules = base.profiles.values().any(|p| !p.app_rules.is_empty());
+    if let Ok(mut guard) = PROFILE_STATE.lock() {
+        *guard = Some(ProfileState {
+            base,
+            active,
+            effective: Arc::new(config.clone()),
+        });
+    }
 
     // initialize history manager if enabled
     if config.settings.history.enabled {
@@ -131,28 +214,63 @@ pub fn start_foreground(log_path: Option<String>) -> Result<()> {
         }
     }
 
-    // parse shortcuts into hotkeys
+    // parse shortcuts into hotkeys, read with the configured keyboard layout
+    keyboard::select(config.settings.keyboard_layout.as_deref());
     let shortcuts = parse_shortcuts(&config)?;
+    let triggers = parse_triggers(&config);
+    let mouse_bindings = parse_mouse_bindings(&config);
 
-    let has_shortcuts = !shortcuts.is_empty();
-    let has_app_rules = !config.app_rules.is_empty();
+    let has_shortcuts = !shortcuts.is_empty() || !triggers.is_empty() || !mouse_bindings.is_empty();
+    // profiles can add rules later, so watch whenever any rules exist
+    let has_app_rules = !config.app_rules.is_empty() || has_profile_rules;
 
     if has_shortcuts {
-        for (hotkey, action) in &shortcuts {
-            log(&format!("  {} -> {}", hotkey, action));
+        for shortcut in &shortcuts {
+            log(&format!(
+                "  {} -> {}",
+                shortcut.keys,
+                describe_action(&shortcut.action, &config)
+            ));
+        }
+        for trigger in &triggers {
+            log(&format!(
+                "  {} ({}) -> {}",
+                trigger.key,
+                trigger.gesture,
+                describe_action(&trigger.action, &config)
+            ));
+        }
+        for binding in &mouse_bindings {
+            log(&format!(
+                "  {} -> {}",
+                binding.chord,
+                describe_mouse_action(&binding.action, &config)
+            ));
         }
     }
 
+    let modes = parse_modes(&config);
+    for mode in &modes {
+        log(&format!(
+            "Mode '{}': {} to enter, {} binding(s)",
+            mode.name,
+            mode.enter,
+            mode.bindings.len()
+        ));
+    }
+
     // start display watcher for connect/disconnect events
     display_watcher::start_watching(config.display_aliases.clone())?;
     log("Watching for display changes...");
 
     // start app watcher if we have rules
     if has_app_rules {
-        let config_for_watcher = config.clone();
-        let global_delay = config.settings.delay_ms;
+        let config_for_watcher = Arc::new(config.clone());
         app_watcher::start_watching(config.app_rules.clone(), move |rule, _pid| {
-            let delay = rule.delay_ms.unwrap_or(global_delay);
+            let config_for_watcher = current_config(&config_for_watcher);
+            let delay = rule
+                .delay_ms
+                .unwrap_or(config_for_watcher.settings.delay_ms);
             log(&format!(
                 "App '{}' launched, executing: {} (delay: {}ms)",
                 rule.app_name, rule.action, delay
@@ -169,9 +287,9 @@ pub fn start_foreground(log_path: Option<String>) -> Result<()> {
                 return;
             }
 
-            if let Err(e) =
+            if let Err(e) = timed_action(&rule.action, || {
                 execute_action_for_app(&rule.action, &rule.app_name, &config_for_watcher)
-            {
+            }) {
                 log_err(&format!(
                     "Failed to execute '{}' for '{}': {}",
                     rule.action, rule.app_name, e
@@ -181,7 +299,7 @@ pub fn start_foreground(log_path: Option<String>) -> Result<()> {
         log("Watching for app launches...");
     }
 
-    if has_shortcuts {
+    if has_shortcuts || !modes.is_empty() {
         log("Listening for hotkeys... (Ctrl+C to stop)");
     } else if has_app_rules {
         log("Watching for app launches... (Ctrl+C to stop)");
@@ -190,7 +308,7 @@ pub fn start_foreground(log_path: Option<String>) -> Result<()> {
     }
 
     // clone config for the callback
-    let config_for_callback = config.clone();
+    let config_for_callback = Arc::new(config.clone());
     let config_for_socket = Arc::new(config.clone());
 
     // start socket listener in a separate thread
@@ -205,32 +323,46 @@ pub fn start_foreground(log_path: Option<String>) -> Result<()> {
         get_socket_path().display()
     ));
 
-    // start the hotkey listener (this runs the main run loop)
-    // even with no shortcuts, we need the run loop for app watcher notifications
-    hotkeys::start_hotkey_listener(shortcuts, move |action, hotkey| {
-        log(&format!("Hotkey triggered: {} -> {}", hotkey, action));
-
-        // check condition before executing
-        if let Some(shortcut) = find_shortcut_with_condition(&config_for_callback, action) {
-            if !check_shortcut_condition(shortcut, &config_for_callback) {
-                log(&format!(
-                    "Condition not met for shortcut '{}', skipping",
-                    shortcut.keys
-                ));
-                return;
+    // start the remote control listener if enabled
+    let remote_handle = if config.settings.remote.enabled {
+        let remote_config = Arc::clone(&config_for_socket);
+        Some(std::thread::spawn(move || {
+            if let Err(e) = start_remote_listener(remote_config) {
+                log_err(&format!("Remote listener error: {}", e));
             }
-        }
+        }))
+    } else {
+        None
+    };
 
-        if let Err(e) = execute_action(action, &config_for_callback) {
-            log_err(&format!("Failed to execute '{}': {}", action, e));
+    // start the hotkey listener (this runs the main run loop)
+    // even with no shortcuts, we need the run loop for app watcher notifications
+    let keymap = Keymap::new(shortcuts, triggers, modes, keymap_settings(&config))
+        .with_mouse(mouse_bindings);
+    keyboard::watch_input_source(keyboard_layout_changed);
+    // the event tap only hands signals over: looking up windows can take long
+    // enough for macOS to disable the tap, so they are handled in order on a
+    // dispatcher thread
+    let (signal_sender, signal_receiver) = std::sync::mpsc::channel::<Signal>();
+    let dispatcher_handle = std::thread::spawn(move || {
+        for signal in signal_receiver {
+            handle_hotkey_signal(&signal, &current_config(&config_for_callback));
         }
+    });
+    hotkeys::start_hotkey_listener(keymap, move |signal| {
+        let _ = signal_sender.send(signal.clone());
     })?;
 
     // cleanup
     display_watcher::stop_watching();
     app_watcher::stop_watching();
     stop_socket_listener();
+    // the listener dropped its sender, so the dispatcher ends once it is done
+    let _ = dispatcher_handle.join();
     let _ = socket_handle.join();
+    if let Some(handle) = remote_handle {
+        let _ = handle.join();
+    }
     remove_socket_file()?;
     remove_pid_file()?;
 
@@ -291,9 +423,7 @@ pub fn stop() -> Result<()> {
         return Err(anyhow!("Daemon is not running"));
     }
 
-    let pid_path = get_pid_file_path();
-    let pid_str = std::fs::read_to_string(&pid_path)?;
-    let pid: i32 = pid_str.trim().parse()?;
+    let pid = read_pid().ok_or_else(|| anyhow!("Daemon is running but its PID file is missing"))?;
 
     let status = Command::new("kill")
         .arg("-TERM")
@@ -303,11 +433,14 @@ pub fn stop() -> Result<()> {
     if status.success() {
         println!("Sent stop signal to daemon (PID {})", pid);
 
-        std::thread::sleep(std::time::Duration::from_millis(500));
+        // the lock is released when the daemon process exits
+        let deadline = std::time::Instant::now() + std::time::Duration::from_secs(5);
+        while is_daemon_lock_held() && std::time::Instant::now() < deadline {
+            std::thread::sleep(std::time::Duration::from_millis(100));
+        }
 
-        if !is_daemon_running() {
+        if !is_daemon_lock_held() {
             println!("Daemon stopped");
-            let _ = remove_pid_file();
         } else {
             println!("Daemon may still be stopping...");
         }
@@ -318,18 +451,22 @@ pub fn stop() -> Result<()> {
     Ok(())
 }
 
-fn parse_shortcuts(config: &Config) -> Result<Vec<(Hotkey, String)>> {
+fn parse_shortcuts(config: &Config) -> Result<Vec<ShortcutKeys>> {
     let mut result = Vec::new();
 
-    for shortcut in &config.shortcuts {
-        match Hotkey::parse(&shortcut.keys) {
-            Ok(hotkey) => {
-                let action = if let Some(ref app) = shortcut.app {
-                    format!("{}:{}", shortcut.action, app)
-                } else {
-                    shortcut.action.clone()
-                };
-                result.push((hotkey, action));
+    // double-tap and hold shortcuts are parse_triggers'
+    for (i, shortcut) in config.shortcuts.iter().enumerate() {
+        if !shortcut.trigger.is_tap() {
+            continue;
+        }
+        match KeySequence::parse(&shortcut.keys) {
+            Ok(keys) => {
+                result.push(ShortcutKeys {
+                    keys,
+                    action: listener_action(i, shortcut),
+                    scope: shortcut_scope(shortcut),
+                    repeat: shortcut_repeat(shortcut),
+                });
             }
             Err(e) => {
                 log_err(&format!("Invalid shortcut '{}': {}", shortcut.keys, e));
@@ -340,7 +477,593 @@ fn parse_shortcuts(config: &Config) -> Result<Vec<(Hotkey, String)>> {
     Ok(result)
 }
 
-fn execute_action(action: &str, config: &Config) -> Result<()> {
+/// the config's double-tap and hold shortcuts; ones with invalid keys are skipped
+fn parse_triggers(config: &Config) -> Vec<TriggerBinding> {
+    let settings = &config.settings;
+    let mut result = Vec::new();
+
+    for (i, shortcut) in config.shortcuts.iter().enumerate() {
+        let ms = |default| std::time::Duration::from_millis(shortcut.trigger_ms.unwrap_or(default));
+        let gesture = match shortcut.trigger {
+            Trigger::Tap => continue,
+            Trigger::DoubleTap => Gesture::DoubleTap {
+                within: ms(settings.double_tap_ms),
+            },
+            Trigger::Hold => Gesture::Hold {
+                after: ms(settings.hold_ms),
+            },
+        };
+        match TriggerKey::parse(&shortcut.keys) {
+            Ok(key) => result.push(TriggerBinding {
+                key,
+                gesture,
+                action: listener_action(i, shortcut),
+                release_action: shortcut
+                    .release_action
+                    .as_ref()
+                    .map(|action| with_app(action, shortcut)),
+                scope: shortcut_scope(shortcut),
+            }),
+            Err(e) => log_err(&format!("Invalid shortcut '{}': {}", shortcut.keys, e)),
+        }
+    }
+
+    result
+}
+
+/// what the listener runs for `config.shortcuts[index]`: its action, or its cycle
+fn listener_action(index: usize, shortcut: &Shortcut) -> String {
+    if shortcut.cycle.is_empty() {
+        with_app(&shortcut.action, shortcut)
+    } else {
+        format!("{}{}", cycle::ACTION_PREFIX, index)
+    }
+}
+
+/// the cycle shortcut a listener action runs, with its index in `config.shortcuts`
+fn cycle_shortcut<'a>(config: &'a Config, action: &str) -> Option<(usize, &'a Shortcut)> {
+    let index: usize = action.strip_prefix(cycle::ACTION_PREFIX)?.parse().ok()?;
+    config
+        .shortcuts
+        .get(index)
+        .filter(|shortcut| !shortcut.cycle.is_empty())
+        .map(|shortcut| (index, shortcut))
+}
+
+/// a listener action as logged: cycles are shown with their steps
+fn describe_action(action: &str, config: &Config) -> String {
+    match cycle_shortcut(config, action) {
+        Some((_, shortcut)) => format!("cycle [{}]", shortcut.cycle.join(", ")),
+        None => action.to_string(),
+    }
+}
+
+/// `action` as the listener runs it: with the shortcut's app, if it has one
+fn with_app(action: &str, shortcut: &Shortcut) -> String {
+    match shortcut.app {
+        Some(ref app) => format!("{}:{}", action, app),
+        None => action.to_string(),
+    }
+}
+
+fn shortcut_scope(shortcut: &Shortcut) -> AppScope {
+    AppScope {
+        only_in: shortcut.only_in.clone(),
+        except_in: shortcut.except_in.clone(),
+    }
+}
+
+/// what key repeats of `shortcut` do; an invalid `repeat` (which verify
+/// reports) is ignored
+fn shortcut_repeat(shortcut: &Shortcut) -> Repeat {
+    match shortcut.repeat.as_deref().map(Repeat::parse) {
+        Some(Ok(repeat)) => repeat,
+        Some(Err(e)) => {
+            log_err(&format!("Shortcut '{}': {}", shortcut.keys, e));
+            Repeat::default()
+        }
+        None => Repeat::default(),
+    }
+}
+
+/// the config's modes, sorted by name; modes with invalid keys are skipped
+fn parse_modes(config: &Config) -> Vec<ModeKeys> {
+    let mut names: Vec<&String> = config.modes.keys().collect();
+    names.sort();
+
+    let mut result = Vec::new();
+    for name in names {
+        let mode = &config.modes[name];
+        let keys = KeySequence::parse(&mode.enter)
+            .and_then(|enter| Ok((enter, KeySequence::parse_chord(&mode.exit)?)));
+        let (enter, exit) = match keys {
+            Ok(keys) => keys,
+            Err(e) => {
+                log_err(&format!("Invalid mode '{}': {}", name, e));
+                continue;
+            }
+        };
+
+        let mut bindings = Vec::new();
+        for (keys, action) in &mode.bindings {
+            match KeySequence::parse_chord(keys) {
+                Ok(chord) => bindings.push((chord, action.clone())),
+                Err(e) => log_err(&format!(
+                    "Invalid binding '{}' in mode '{}': {}",
+                    keys, name, e
+                )),
+            }
+        }
+
+        result.push(ModeKeys {
+            name: name.clone(),
+            enter,
+            exit,
+            bindings,
+            timeout: mode.timeout_ms.map(std::time::Duration::from_millis),
+        });
+    }
+
+    result
+}
+
+/// the listener's keymap for `config`
+fn build_keymap(config: &Config) -> Result<Keymap> {
+    keyboard::select(config.settings.keyboard_layout.as_deref());
+    Ok(Keymap::new(
+        parse_shortcuts(config)?,
+        parse_triggers(config),
+        parse_modes(config),
+        keymap_settings(config),
+    )
+    .with_mouse(parse_mouse_bindings(config)))
+}
+
+/// the config's mouse bindings, skipping invalid ones
+fn parse_mouse_bindings(config: &Config) -> Vec<mouse::MouseBinding> {
+    let mut result = Vec::new();
+
+    for binding in &config.mouse_bindings {
+        let chord = match MouseChord::parse(&binding.mouse) {
+            Ok(chord) => chord,
+            Err(e) => {
+                log_err(&format!("Invalid mouse binding '{}': {}", binding.mouse, e));
+                continue;
+            }
+        };
+
+        let action = match binding.drag {
+            Some(MouseDrag::Move) => MouseAction::Drag(DragKind::Move),
+            Some(MouseDrag::Resize) => MouseAction::Drag(DragKind::Resize),
+            None => MouseAction::Run {
+                action: binding.action.clone(),
+                under_cursor: binding.window == MouseWindow::UnderCursor,
+            },
+        };
+
+        result.push(mouse::MouseBinding { chord, action });
+    }
+
+    result
+}
+
+fn describe_mouse_action(action: &MouseAction, config: &Config) -> String {
+    match action {
+        MouseAction::Run {
+            action,
+            under_cursor: true,
+        } => format!("{} (window under cursor)", describe_action(action, config)),
+        MouseAction::Run { action, .. } => describe_action(action, config),
+        MouseAction::Drag(kind) => format!("drag to {}", kind.as_str()),
+    }
+}
+
+/// act on a signal from the hotkey listener
+fn handle_hotkey_signal(signal: &Signal, config: &Arc<Config>) {
+    match signal {
+        Signal::Run { action, keys, mode } => {
+            match mode {
+                Some(mode) => log(&format!(
+                    "Hotkey triggered in mode '{}': {} -> {}",
+                    mode, keys, action
+                )),
+                None => log(&format!(
+                    "Hotkey triggered: {} -> {}",
+                    keys,
+                    describe_action(action, config)
+                )),
+            }
+            METRICS.record_hotkey(&keys.to_string());
+
+            queue_action(QueuedAction {
+                action: action.clone(),
+                source: Source::Key { mode: mode.clone() },
+                config: Arc::clone(config),
+            });
+        }
+        Signal::SequencePending { pressed, next } => {
+            events::emit(Event::hotkey_pending(
+                pressed.to_string(),
+                next.iter().map(|chord| chord.to_string()).collect(),
+            ));
+        }
+        Signal::SequenceCancelled { pressed, reason } => {
+            log(&format!(
+                "Hotkey sequence cancelled ({}): {}",
+                reason.as_str(),
+                pressed
+            ));
+            events::emit(Event::hotkey_cancelled(
+                pressed.to_string(),
+                reason.as_str().to_string(),
+            ));
+        }
+        Signal::ModeEntered { mode } => {
+            log(&format!("Entered mode '{}'", mode));
+            events::emit(Event::mode_entered(mode.clone()));
+        }
+        Signal::ModeExited { mode, reason } => {
+            log(&format!("Exited mode '{}' ({})", mode, reason.as_str()));
+            events::emit(Event::mode_exited(
+                mode.clone(),
+                reason.as_str().to_string(),
+            ));
+        }
+        Signal::Mouse(signal) => handle_mouse_signal(signal, config),
+    }
+}
+
+/// a hotkey action waiting for its window, with the config it was pressed under
+struct QueuedAction {
+    action: String,
+    source: Source,
+    config: Arc<Config>,
+}
+
+/// what triggered a queued action
+enum Source {
+    /// a shortcut, or a binding of `mode`
+    Key { mode: Option<String> },
+    /// a mouse binding, for the window under the cursor or the focused one
+    Mouse {
+        window: Option<(matching::AppInfo, manager::Window)>,
+    },
+}
+
+/// run a hotkey's action once the actions before it for the same window are
+/// done, on a thread of its own so the dispatcher is not held up
+fn queue_action(queued: QueuedAction) {
+    let window = match &queued.source {
+        Source::Key { mode } => action_window(&queued.action, mode.as_deref(), &queued.config),
+        Source::Mouse {
+            window: Some((_, window)),
+        } => window_key(None, Some((window.pid(), window.id()))),
+        Source::Mouse { window: None } => window_key(None, manager::target_window_id(None).ok()),
+    };
+    let cancel_stale = queued.config.settings.cancel_stale_actions;
+
+    let mut queue = ACTION_QUEUE.lock().unwrap_or_else(|e| e.into_inner());
+    let push = queue.push(&window, queued, cancel_stale);
+    drop(queue);
+
+    for dropped in &push.dropped {
+        log(&format!(
+            "Dropped '{}': a newer action for the same window came in",
+            describe_action(&dropped.action, &dropped.config)
+        ));
+    }
+
+    if let Some(first) = push.start {
+        std::thread::spawn(move || {
+            queue::run_queued(&ACTION_QUEUE, &window, first, |queued| {
+                match &queued.source {
+                    Source::Key { mode } => {
+                        run_hotkey_action(&queued.action, mode.as_deref(), &queued.config)
+                    }
+                    Source::Mouse { window } => {
+                        run_mouse_action(&queued.action, window.as_ref(), &queued.config)
+                    }
+                }
+            });
+        });
+    }
+}
+
+/// the window a hotkey action is queued for, looked up when it is pressed:
+/// the frontmost window of its shortcut's app, or the focused window
+fn action_window(action: &str, mode: Option<&str>, config: &Config) -> String {
+    let app_name = match mode {
+        Some(_) => None,
+        None => find_shortcut_with_condition(config, action)
+            .and_then(|shortcut| shortcut.app.as_deref()),
+    };
+    let app = app_name.and_then(|name| {
+        let running_apps = matching::get_running_apps().ok()?;
+        matching::find_app(name, &running_apps, config.settings.fuzzy_threshold)
+            .map(|found| found.app)
+    });
+    // an app that is not running (yet) has no window to look up
+    let window = match (app_name, &app) {
+        (Some(_), None) => None,
+        _ => manager::target_window_id(app.as_ref()).ok(),
+    };
+    window_key(app_name, window)
+}
+
+/// the queue key of a window, given as (pid, window id), of `app`: without
+/// one, actions for an app are keyed by its name, and any others share a key
+fn window_key(app: Option<&str>, window: Option<(i32, u32)>) -> String {
+    match (window, app) {
+        (Some((pid, window_id)), _) => format!("{}:{}", pid, window_id),
+        (None, Some(app)) => app.to_lowercase(),
+        (None, None) => String::new(),
+    }
+}
+
+/// check a hotkey's condition and run its action, or its cycle's next step
+fn run_hotkey_action(action: &str, mode: Option<&str>, config: &Config) {
+    // mode bindings have no condition; check a shortcut's before executing
+    if mode.is_none() {
+        if let Some(shortcut) = find_shortcut_with_condition(config, action) {
+            if !check_shortcut_condition(shortcut, config) {
+                log(&format!(
+                    "Condition not met for shortcut '{}', skipping",
+                    shortcut.keys
+                ));
+                return;
+            }
+        }
+    }
+
+    let result = match cycle_shortcut(config, action) {
+        Some((index, shortcut)) if mode.is_none() => run_cycle(index, shortcut, config),
+        _ => execute_action(action, config, None),
+    };
+    if let Err(e) = result {
+        log_err(&format!(
+            "Failed to execute '{}': {}",
+            describe_action(action, config),
+            e
+        ));
+    }
+}
+
+/// run a mouse binding's action on `window`, the window under the cursor, or
+/// on the focused window without one
+fn run_mouse_action(
+    action: &str,
+    window: Option<&(matching::AppInfo, manager::Window)>,
+    config: &Config,
+) {
+    let result = match window {
+        Some((app, window)) => {
+            window.raise();
+            manager::with_window(window, || {
+                timed_action(action, || {
+                    execute_action_for_app_info(action, app, config, None)
+                })
+            })
+        }
+        None => execute_action(action, config, None),
+    };
+    if let Err(e) = result {
+        log_err(&format!("Mouse binding failed: {}", e));
+    }
+}
+
+/// act on a mouse binding's click, scroll or drag
+fn handle_mouse_signal(signal: &MouseSignal, config: &Arc<Config>) {
+    let result = match signal {
+        MouseSignal::Run {
+            action,
+            binding,
+            at,
+        } => {
+            log(&format!(
+                "Mouse binding triggered: {} -> {}",
+                binding,
+                describe_action(action, config)
+            ));
+            METRICS.record_hotkey(&binding.to_string());
+
+            // the window is looked up now, before the cursor moves on
+            at.map(|at| manager::window_at_point(at.x, at.y))
+                .transpose()
+                .map(|window| {
+                    queue_action(QueuedAction {
+                        action: action.clone(),
+                        source: Source::Mouse { window },
+                        config: Arc::clone(config),
+                    })
+                })
+        }
+        MouseSignal::DragStarted { kind, binding, at } => {
+            log(&format!(
+                "Mouse drag started: {} -> {}",
+                binding,
+                kind.as_str()
+            ));
+            METRICS.record_hotkey(&binding.to_string());
+            start_drag(*kind, *at, config)
+        }
+        MouseSignal::DragMoved { at } => update_drag(*at, false),
+        MouseSignal::DragEnded { at } => update_drag(*at, true),
+    };
+
+    if let Err(e) = result {
+        log_err(&format!("Mouse binding failed: {}", e));
+    }
+}
+
+/// start dragging the window under the cursor at `at`
+fn start_drag(kind: DragKind, at: Point, config: &Config) -> Result<()> {
+    let (app, window) = manager::window_at_point(at.x, at.y)?;
+    window.raise();
+    let (x, y, width, height) = window.frame()?;
+    let frame = Frame::new(x, y, width, height);
+    let history = manager::with_window(&window, || capture_window_state(&app.name, config));
+
+    let mut drag = DRAG
+        .lock()
+        .map_err(|_| anyhow!("Failed to lock drag state"))?;
+    *drag = Some(WindowDrag {
+        drag: Drag::new(kind, frame, at),
+        app,
+        window,
+        frame,
+        history,
+    });
+    Ok(())
+}
+
+/// follow the cursor at `at` with the dragged window, ending the drag if `done`
+fn update_drag(at: Point, done: bool) -> Result<()> {
+    let mut guard = DRAG
+        .lock()
+        .map_err(|_| anyhow!("Failed to lock drag state"))?;
+    // the drag did not start, e.g. there was no window under the cursor
+    let Some(state) = guard.as_mut() else {
+        return Ok(());
+    };
+
+    let frame = state.drag.frame_at(at);
+    let mut result = Ok(());
+    if frame.differs_from(&state.frame) {
+        result = state.window.set_frame(
+            frame.x,
+            frame.y,
+            frame.width,
+            frame.height,
+            frame.resized_from(&state.frame),
+        );
+        if result.is_ok() {
+            state.frame = frame;
+        }
+    }
+
+    if !done {
+        return result;
+    }
+    let Some(state) = guard.take() else {
+        return result;
+    };
+    if !state.frame.differs_from(&state.drag.start_frame()) {
+        return result;
+    }
+
+    if let Some(entry) = state.history {
+        push_to_history(entry);
+    }
+
+    let (app, frame) = (&state.app, &state.frame);
+    log(&format!(
+        "Dragged {} to {}x{} at ({}, {})",
+        app.name,
+        frame.width.round(),
+        frame.height.round(),
+        frame.x.round(),
+        frame.y.round()
+    ));
+    events::emit(match state.drag.kind() {
+        DragKind::Move => Event::window_moved(
+            app.name.clone(),
+            app.pid,
+            Some(app.titles.clone()),
+            frame.x.round() as i32,
+            frame.y.round() as i32,
+            None,
+        ),
+        DragKind::Resize => Event::window_resized(
+            app.name.clone(),
+            app.pid,
+            Some(app.titles.clone()),
+            frame.width.round() as i32,
+            frame.height.round() as i32,
+        ),
+    });
+
+    result
+}
+
+fn keymap_settings(config: &Config) -> KeymapSettings {
+    KeymapSettings {
+        sequence_timeout: std::time::Duration::from_millis(config.settings.sequence_timeout_ms),
+        disabled_in: config.settings.disabled_in.clone(),
+        fuzzy_threshold: config.settings.fuzzy_threshold,
+    }
+}
+
+fn execute_action(action: &str, config: &Config, control: Option<&RequestControl>) -> Result<()> {
+    timed_action(action, || run_action(action, config, control))
+}
+
+/// run the next step of the cycle shortcut `config.shortcuts[index]`
+fn run_cycle(index: usize, shortcut: &Shortcut, config: &Config) -> Result<()> {
+    // held while the step runs, so quick presses take their steps in turn
+    let mut cycles = CYCLES
+        .lock()
+        .map_err(|_| anyhow!("Failed to lock cycle state"))?;
+
+    let timeout = std::time::Duration::from_millis(config.settings.cycle_timeout_ms);
+    let window = cycle_window(shortcut, config);
+    let step = cycles.next(
+        index,
+        shortcut.cycle.len(),
+        window.as_ref(),
+        std::time::Instant::now(),
+        timeout,
+    );
+    log(&format!(
+        "Cycle step {}/{}: {}",
+        step + 1,
+        shortcut.cycle.len(),
+        shortcut.cycle[step]
+    ));
+
+    let result = cycle::step_actions(&shortcut.cycle[step])
+        .iter()
+        .try_for_each(|action| execute_action(&with_app(action, shortcut), config, None));
+
+    // after a failed step the next press starts over
+    let window = result
+        .is_ok()
+        .then(|| cycle_window(shortcut, config))
+        .flatten();
+    cycles.ran(index, step, window, std::time::Instant::now());
+    result
+}
+
+/// the window a cycle shortcut acts on: its app's, or the focused one
+fn cycle_window(shortcut: &Shortcut, config: &Config) -> Option<WindowSnapshot> {
+    let (app, window, _display) = match &shortcut.app {
+        Some(name) => {
+            let running_apps = matching::get_running_apps().ok()?;
+            let found = matching::find_app(name, &running_apps, config.settings.fuzzy_threshold)?;
+            manager::get_window_info_for_app(&found.app).ok()?
+        }
+        None => manager::get_focused_window_info().ok()?,
+    };
+
+    Some(WindowSnapshot {
+        pid: app.pid,
+        title: window.title,
+        x: window.x,
+        y: window.y,
+        width: window.width,
+        height: window.height,
+    })
+}
+
+/// run an action and record its latency under the action name (the part before ':')
+fn timed_action<T>(action: &str, f: impl FnOnce() -> Result<T>) -> Result<T> {
+    let name = action.split(':').next().unwrap_or(action);
+    let started = std::time::Instant::now();
+    let result = f();
+    METRICS.record_action(name, result.is_ok(), started.elapsed());
+    result
+}
+
+fn run_action(action: &str, config: &Config, control: Option<&RequestControl>) -> Result<()> {
     let (action_type, action_arg) = if let Some(idx) = action.find(':') {
         (&action[..idx], Some(&action[idx + 1..]))
     } else {
@@ -373,6 +1096,12 @@ fn execute_action(action: &str, config: &Config) -> Result<()> {
                         should_launch(false, false, shortcut_launch, config.settings.launch);
                     if do_launch {
                         manager::launch_app(app_name, false)?;
+
+                        // IPC clients wait for the launched app's window and can follow progress
+                        if let Some(control) = control {
+                            let app = wait_for_launched_app(app_name, config, control)?;
+                            execute_action_for_app_info("focus", &app, config, Some(control))?;
+                        }
                     }
                 }
             }
@@ -608,7 +1337,44 @@ fn execute_action_for_app(action: &str, app_name: &str, config: &Config) -> Resu
         }
     };
 
-    execute_action_for_app_info(action, &target_app, config)
+    execute_action_for_app_info(action, &target_app, config, None)
+}
+
+/// Wait for a just-launched app to show up in the running apps list
+///
+/// uses the same schedule as `settings.retry` and reports each attempt as progress
+fn wait_for_launched_app(
+    app_name: &str,
+    config: &Config,
+    control: &RequestControl,
+) -> Result<matching::AppInfo> {
+    let max_retries = config.settings.retry.count.max(1);
+    let mut current_delay = config.settings.retry.delay_ms as f64;
+
+    for attempt in 1..=max_retries {
+        control.check()?;
+
+        let running_apps = matching::get_running_apps()?;
+        if let Some(result) =
+            matching::find_app(app_name, &running_apps, config.settings.fuzzy_threshold)
+        {
+            return Ok(result.app);
+        }
+
+        control.report(Progress::step(
+            format!("waiting for {} to launch", app_name),
+            attempt,
+            max_retries,
+        ));
+        control.sleep(std::time::Duration::from_millis(current_delay as u64))?;
+        current_delay *= config.settings.retry.backoff;
+    }
+
+    Err(anyhow!(
+        "{} did not start after {} attempts",
+        app_name,
+        max_retries
+    ))
 }
 
 /// Execute an action for a specific app (used by app watcher)
@@ -616,6 +1382,7 @@ fn execute_action_for_app_info(
     action: &str,
     target_app: &matching::AppInfo,
     config: &Config,
+    control: Option<&RequestControl>,
 ) -> Result<()> {
     let (action_type, action_arg) = if let Some(idx) = action.find(':') {
         (&action[..idx], Some(&action[idx + 1..]))
@@ -646,6 +1413,10 @@ fn execute_action_for_app_info(
     let mut current_delay = initial_delay as f64;
 
     for attempt in 0..max_retries {
+        if let Some(control) = control {
+            control.check()?;
+        }
+
         let result: Result<Option<Event>> = match action_type {
             "focus" => manager::focus_app(target_app, false).map(|()| {
                 Some(Event::app_focused(
@@ -738,7 +1509,18 @@ fn execute_action_for_app_info(
                             attempt + 1,
                             max_retries
                         ));
-                        std::thread::sleep(std::time::Duration::from_millis(delay_ms));
+                        let delay = std::time::Duration::from_millis(delay_ms);
+                        match control {
+                            Some(control) => {
+                                control.report(Progress::step(
+                                    format!("waiting for {} window to appear", target_app.name),
+                                    attempt + 1,
+                                    max_retries,
+                                ));
+                                control.sleep(delay)?;
+                            }
+                            None => std::thread::sleep(delay),
+                        }
                         current_delay *= backoff;
                         continue;
                     }
@@ -818,13 +1600,7 @@ fn parse_move_action_arg(
 
 fn find_shortcut_launch(config: &Config, action: &str) -> Option<bool> {
     for shortcut in &config.shortcuts {
-        let shortcut_action = if let Some(ref app) = shortcut.app {
-            format!("{}:{}", shortcut.action, app)
-        } else {
-            shortcut.action.clone()
-        };
-
-        if shortcut_action == action {
+        if with_app(&shortcut.action, shortcut) == action {
             return shortcut.launch;
         }
     }
@@ -832,7 +1608,9 @@ fn find_shortcut_launch(config: &Config, action: &str) -> Option<bool> {
 }
 
 /// parse config condition definitions (JSON) into parsed conditions
-fn parse_config_conditions(config: &Config) -> HashMap<String, crate::conditions::Condition> {
+pub(crate) fn parse_config_conditions(
+    config: &Config,
+) -> HashMap<String, crate::conditions::Condition> {
     use crate::conditions::Condition;
 
     let mut parsed = HashMap::new();
@@ -875,6 +1653,7 @@ fn check_shortcut_condition(shortcut: &Shortcut, config: &Config) -> bool {
                 "Failed to parse condition for shortcut '{}': {}",
                 shortcut.keys, e
             ));
+            METRICS.record_condition("shortcut", ConditionOutcome::Error);
             return false; // invalid condition = don't execute
         }
     };
@@ -908,16 +1687,24 @@ fn check_shortcut_condition(shortcut: &Shortcut, config: &Config) -> bool {
         is_minimized: ws.is_minimized,
     });
 
+    let profile = active_profile();
     let ctx = EvalContext::new(&displays, &config.display_aliases, &running_apps)
         .with_focused_app(focused_app_name)
         .with_target_app(target_app_name)
-        .with_target_window(window_state.as_ref());
+        .with_target_window(window_state.as_ref())
+        .with_profile(profile.as_deref());
 
-    evaluate(&condition, &ctx)
+    let passed = evaluate(&condition, &ctx);
+    METRICS.record_condition("shortcut", ConditionOutcome::from_bool(passed));
+    passed
 }
 
 /// find shortcut by action string and check its condition
 fn find_shortcut_with_condition<'a>(config: &'a Config, action: &str) -> Option<&'a Shortcut> {
+    if let Some((_, shortcut)) = cycle_shortcut(config, action) {
+        return Some(shortcut);
+    }
+
     for shortcut in &config.shortcuts {
         let shortcut_action = if let Some(ref app) = shortcut.app {
             format!("{}:{}", shortcut.action, app)
@@ -951,6 +1738,7 @@ fn check_app_rule_condition(rule: &app_watcher::MatchedRule, config: &Config) ->
                 "Failed to parse condition for app rule '{}': {}",
                 rule.app_name, e
             ));
+            METRICS.record_condition("app_rule", ConditionOutcome::Error);
             return false; // invalid condition = don't execute
         }
     };
@@ -987,12 +1775,16 @@ fn check_app_rule_condition(rule: &app_watcher::MatchedRule, config: &Config) ->
         is_minimized: ws.is_minimized,
     });
 
+    let profile = active_profile();
     let ctx = EvalContext::new(&displays, &config.display_aliases, &running_apps)
         .with_focused_app(focused_app_name)
         .with_target_app(target_app_name)
-        .with_target_window(window_state.as_ref());
+        .with_target_window(window_state.as_ref())
+        .with_profile(profile.as_deref());
 
-    evaluate(&condition, &ctx)
+    let passed = evaluate(&condition, &ctx);
+    METRICS.record_condition("app_rule", ConditionOutcome::from_bool(passed));
+    passed
 }
 
 fn setup_signal_handlers() -> Result<()> {
@@ -1018,20 +1810,30 @@ static SOCKET_SHOULD_STOP: AtomicBool = AtomicBool::new(false);
 /// Start the Unix socket listener for IPC
 fn start_socket_listener(config: Arc<Config>) -> Result<()> {
     use std::io::{BufRead, BufReader, Write};
-    use std::os::unix::net::UnixListener;
 
     let socket_path = get_socket_path();
 
-    // ensure parent directory exists
-    if let Some(parent) = socket_path.parent() {
-        std::fs::create_dir_all(parent)?;
-    }
-
-    // remove stale socket file
+    // remove stale socket file (safe: we hold the daemon lock)
     let _ = std::fs::remove_file(&socket_path);
 
     let listener =
-        UnixListener::bind(&socket_path).map_err(|e| anyhow!("Failed to bind socket: {}", e))?;
+        auth::bind_private(&socket_path).map_err(|e| anyhow!("Failed to bind socket: {}", e))?;
+
+    // apply the configured socket file permissions
+    let socket_mode = match auth::parse_socket_mode(&config.settings.ipc.socket_mode) {
+        Ok(mode) => mode,
+        Err(e) => {
+            log_err(&format!("{}, using 0600", e));
+            0o600
+        }
+    };
+    {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(&socket_path, std::fs::Permissions::from_mode(socket_mode))
+            .map_err(|e| anyhow!("Failed to set socket permissions: {}", e))?;
+    }
+
+    let policy = auth::AccessPolicy::for_current_user(&config.settings.ipc);
 
     // set non-blocking so we can check for stop signal
     listener.set_nonblocking(true)?;
@@ -1044,31 +1846,88 @@ fn start_socket_listener(config: Arc<Config>) -> Result<()> {
                 // set blocking for this connection
                 stream.set_nonblocking(false)?;
 
+                // reject peers running as a different user before reading anything
+                let peer_check = auth::peer_uid(&stream)
+                    .map_err(|e| format!("access denied: unable to verify peer: {}", e))
+                    .and_then(|uid| policy.check_peer(uid).map(|()| Peer::Local(uid)));
+                let peer = match peer_check {
+                    Ok(peer) => peer,
+                    Err(msg) => {
+                        log_err(&msg);
+                        let error =
+                            crate::cli::output::JsonRpcError::new(exit_codes::ACCESS_DENIED, msg);
+                        if let Ok(response) = serde_json::to_string(&error) {
+                            let _ = stream.write_all(response.as_bytes());
+                            let _ = stream.write_all(b"\n");
+                        }
+                        continue;
+                    }
+                };
+
                 // read command
                 let mut reader = BufReader::new(&stream);
                 let mut line = String::new();
                 if reader.read_line(&mut line).is_ok() {
                     let line = line.trim();
                     if !line.is_empty() {
-                        // check if this is a subscribe request
-                        if let Ok(request) = IpcRequest::parse(line) {
-                            if request.method == "subscribe" {
-                                // handle subscription in a separate thread
-                                let config_clone = Arc::clone(&config);
-                                let request_clone = request.clone();
-                                std::thread::spawn(move || {
-                                    handle_subscription(stream, request_clone, &config_clone);
-                                });
+                        let request = match IpcRequest::parse(line) {
+                            Ok(request) => request,
+                            Err(e) => {
+                                // can't parse - return JSON-RPC error without an id
+                                let error = crate::cli::output::JsonRpcError::new(
+                                    exit_codes::INVALID_ARGS,
+                                    format!("Invalid request: {}", e),
+                                );
+                                if let Ok(response) = serde_json::to_string(&error) {
+                                    let _ = stream.write_all(response.as_bytes());
+                                    let _ = stream.write_all(b"\n");
+                                }
                                 continue;
                             }
+                        };
+
+                        // enforce token allow/deny lists
+                        if let Err(msg) = policy.authorize(&request) {
+                            log_err(&format!(
+                                "IPC request '{}' rejected: {}",
+                                request.method, msg
+                            ));
+                            if let Some(response) =
+                                format_error_response(&request, exit_codes::ACCESS_DENIED, &msg)
+                            {
+                                let _ = stream.write_all(response.as_bytes());
+                                let _ = stream.write_all(b"\n");
+                            }
+                            continue;
                         }
 
-                        // normal request-response handling
-                        if let Some(response) = handle_ipc_message(line, &config) {
-                            let _ = stream.write_all(response.as_bytes());
-                            let _ = stream.write_all(b"\n");
+                        // cancellation is answered inline so it never waits behind the
+                        // request it cancels
+                        if request.method == "$/cancelRequest" {
+                            let result = handle_cancel_request(&request, &peer);
+                            let response = match result {
+                                Ok(value) => format_success_response(&request, value),
+                                Err((code, msg)) => format_error_response(&request, code, &msg),
+                            };
+                            if let Some(response) = response {
+                                let _ = stream.write_all(response.as_bytes());
+                                let _ = stream.write_all(b"\n");
+                            }
+                            continue;
+                        }
+
+                        // subscriptions stream events, everything else runs to completion;
+                        // both get their own thread so long operations don't block the socket
+                        let config_clone = current_config(&config);
+                        if request.method == "subscribe" {
+                            std::thread::spawn(move || {
+                                handle_subscription(stream, request, &peer, &config_clone);
+                            });
+                        } else {
+                            std::thread::spawn(move || {
+                                handle_request_connection(stream, request, &peer, &config_clone);
+                            });
                         }
-                        // if None, it was a notification - no response needed
                     }
                 }
             }
@@ -1088,10 +1947,141 @@ fn start_socket_listener(config: Arc<Config>) -> Result<()> {
     Ok(())
 }
 
+/// Listen for remote JSON-RPC requests over TLS (`settings.remote`)
+fn start_remote_listener(config: Arc<Config>) -> Result<()> {
+    use remote::server::{self, RequestHandler, ServerState};
+    use remote::{pairing, tls};
+
+    let settings = &config.settings.remote;
+
+    let identity = tls::load_or_create_identity(&remote::remote_dir()?)?;
+    let acceptor = Arc::new(tls::acceptor(&identity)?);
+    let state = Arc::new(ServerState::new(
+        identity.fingerprint.clone(),
+        pairing::clients_path()?,
+    ));
+
+    let listener = std::net::TcpListener::bind((settings.bind.as_str(), settings.port))
+        .map_err(|e| anyhow!("Failed to bind {}:{}: {}", settings.bind, settings.port, e))?;
+
+    if let Ok(mut guard) = REMOTE_STATE.lock() {
+        *guard = Some(Arc::clone(&state));
+    }
+
+    log(&format!(
+        "Remote listener on {}:{} (certificate {})",
+        settings.bind,
+        settings.port,
+        tls::short_fingerprint(&identity.fingerprint)
+    ));
+
+    let policy = auth::AccessPolicy::for_current_user(&config.settings.ipc);
+    let handler_config = Arc::clone(&config);
+    let handler: Arc<RequestHandler> = Arc::new(move |line: &str, client: &str| {
+        let peer = Peer::Remote(client.to_string());
+        handle_remote_line(line, &peer, &current_config(&handler_config), &policy)
+    });
+
+    let result = server::serve(listener, acceptor, state, handler, &|| {
+        SOCKET_SHOULD_STOP.load(Ordering::SeqCst) || DAEMON_SHOULD_STOP.load(Ordering::SeqCst)
+    });
+
+    if let Ok(mut guard) = REMOTE_STATE.lock() {
+        *guard = None;
+    }
+
+    result
+}
+
+/// Handle a request from a paired remote client
+///
+/// remote requests go through the same path as socket requests, except that
+/// subscriptions, progress notifications and `remote_*` methods are local-only
+fn handle_remote_line(
+    line: &str,
+    peer: &Peer,
+    config: &Config,
+    policy: &auth::AccessPolicy,
+) -> Option<String> {
+    let request = match IpcRequest::parse(line) {
+        Ok(request) => request,
+        Err(e) => {
+            let error = crate::cli::output::JsonRpcError::new(
+                exit_codes::INVALID_ARGS,
+                format!("Invalid request: {}", e),
+            );
+            return serde_json::to_string(&error).ok();
+        }
+    };
+
+    if request.method == "subscribe" || request.method.starts_with("remote_") {
+        return format_error_response(
+            &request,
+            exit_codes::ERROR,
+            &format!(
+                "'{}' is not available over remote connections",
+                request.method
+            ),
+        );
+    }
+
+    if let Err(msg) = policy.authorize_remote(&request) {
+        log_err(&format!(
+            "Remote request '{}' rejected: {}",
+            request.method, msg
+        ));
+        return format_error_response(&request, exit_codes::ACCESS_DENIED, &msg);
+    }
+
+    if request.method == "$/cancelRequest" {
+        return match handle_cancel_request(&request, peer) {
+            Ok(value) => format_success_response(&request, value),
+            Err((code, msg)) => format_error_response(&request, code, &msg),
+        };
+    }
+
+    let control = Arc::new(new_request_control(&request, None));
+    let registration = register_request(&request, peer, &control);
+
+    let response = handle_ipc_message(&request, config, &control);
+
+    unregister_request(registration);
+    response
+}
+
+/// Handle `remote_pair` - create a one-time pairing code for a new remote client
+fn handle_remote_pair(config: &Config) -> Result<serde_json::Value, (i32, String)> {
+    let state = REMOTE_STATE
+        .lock()
+        .ok()
+        .and_then(|guard| guard.clone())
+        .ok_or_else(|| {
+            (
+                exit_codes::ERROR,
+                "Remote control is not enabled. Set settings.remote.enabled and restart the daemon"
+                    .to_string(),
+            )
+        })?;
+
+    let code = state.start_pairing();
+    log(&format!(
+        "Pairing code created (expires in {}s)",
+        code.expires_in()
+    ));
+
+    Ok(serde_json::json!({
+        "code": code.code(),
+        "expires_in": code.expires_in(),
+        "fingerprint": state.fingerprint(),
+        "port": config.settings.remote.port,
+    }))
+}
+
 /// Handle a subscription request - keeps connection open and streams events
 fn handle_subscription(
     mut stream: std::os::unix::net::UnixStream,
     request: IpcRequest,
+    peer: &Peer,
     _config: &Config,
 ) {
     use std::io::Write;
@@ -1119,11 +2109,17 @@ fn handle_subscription(
     // subscribe to events
     let (sub_id, mut receiver) = events::subscribe(event_filters, app_filters);
 
+    // subscriptions can be ended with $/cancelRequest or a deadline
+    let control = Arc::new(new_request_control(&request, Some(&stream)));
+    let registration = register_request(&request, peer, &control);
+
     log(&format!(
         "Subscription {} started: {:?}",
         sub_id, subscribed
     ));
 
+    METRICS.record_ipc_request(&request.method, true, std::time::Duration::ZERO);
+
     // send success response
     if let Some(response) =
         format_success_response(&request, serde_json::json!({ "subscribed": subscribed }))
@@ -1149,6 +2145,11 @@ fn handle_subscription(
             break;
         }
 
+        if let Err(reason) = control.check() {
+            log(&format!("Subscription {} stopped: {}", sub_id, reason));
+            break;
+        }
+
         // try to receive an event (non-blocking with timeout)
         match receiver.try_recv() {
             Ok(event) => {
@@ -1175,38 +2176,298 @@ fn handle_subscription(
     }
 
     // cleanup
+    unregister_request(registration);
     events::unsubscribe(sub_id);
     log(&format!("Subscription {} ended", sub_id));
 }
 
-/// Handle an IPC message and return the response string (or None for notifications)
-fn handle_ipc_message(line: &str, config: &Config) -> Option<String> {
-    // parse the request (JSON-RPC only)
-    let request = match IpcRequest::parse(line) {
-        Ok(req) => req,
-        Err(e) => {
-            // can't parse - return JSON-RPC error
-            // create a minimal error response without a valid request
-            let error = crate::cli::output::JsonRpcError::new(
-                exit_codes::INVALID_ARGS,
-                format!("Invalid request: {}", e),
-            );
-            return serde_json::to_string(&error).ok();
+/// Handle a single request-response connection on its own thread
+fn handle_request_connection(
+    mut stream: std::os::unix::net::UnixStream,
+    request: IpcRequest,
+    peer: &Peer,
+    config: &Config,
+) {
+    use std::io::Write;
+
+    let control = Arc::new(new_request_control(&request, Some(&stream)));
+    let registration = register_request(&request, peer, &control);
+
+    let response = handle_ipc_message(&request, config, &control);
+
+    unregister_request(registration);
+
+    // if None, it was a notification - no response needed
+    if let Some(response) = response {
+        let _ = stream.write_all(response.as_bytes());
+        let _ = stream.write_all(b"\n");
+    }
+}
+
+/// Build the cancellation/deadline/progress control for a request
+///
+/// progress notifications are only sent when the client opts in with `"progress": true`
+/// and the request arrived on a stream they can be written to
+fn new_request_control(
+    request: &IpcRequest,
+    progress_stream: Option<&std::os::unix::net::UnixStream>,
+) -> RequestControl {
+    use std::io::Write;
+
+    let mut control = RequestControl::new();
+
+    if let Some(ms) = request.deadline_ms {
+        control = control.with_deadline(std::time::Duration::from_millis(ms));
+    }
+
+    if request.progress && !request.is_notification() {
+        if let Some(Ok(writer)) = progress_stream.map(|s| s.try_clone()) {
+            let writer = Mutex::new(writer);
+            let request = request.clone();
+            control = control.with_progress(move |progress| {
+                if let Some(notification) = format_progress_notification(&request, progress) {
+                    if let Ok(mut writer) = writer.lock() {
+                        let _ = writer.write_all(notification.as_bytes());
+                        let _ = writer.write_all(b"\n");
+                        let _ = writer.flush();
+                    }
+                }
+            });
         }
-    };
+    }
+
+    control
+}
+
+/// a request tracked for `$/cancelRequest`
+type Registration = (Peer, String, requests::RequestHandle);
 
+/// Track a request so `$/cancelRequest` from the same peer can reach it
+/// (requests without an id can't be cancelled)
+fn register_request(
+    request: &IpcRequest,
+    peer: &Peer,
+    control: &Arc<RequestControl>,
+) -> Option<Registration> {
+    request.id_string().map(|id| {
+        let handle = ACTIVE_REQUESTS.register(peer, &id, Arc::clone(control));
+        (peer.clone(), id, handle)
+    })
+}
+
+fn unregister_request(registration: Option<Registration>) {
+    if let Some((peer, id, handle)) = registration {
+        ACTIVE_REQUESTS.unregister(&peer, &id, handle);
+    }
+}
+
+/// Handle `$/cancelRequest` - cancel `peer`'s in-flight requests with the given id
+fn handle_cancel_request(
+    request: &IpcRequest,
+    peer: &Peer,
+) -> Result<serde_json::Value, (i32, String)> {
+    let id = request.params.get("id").ok_or_else(|| {
+        (
+            exit_codes::INVALID_ARGS,
+            "$/cancelRequest requires 'id' parameter".to_string(),
+        )
+    })?;
+
+    let cancelled = ACTIVE_REQUESTS.cancel(peer, id);
+    log(&format!(
+        "Cancel request for id {}: {} in-flight request(s) cancelled",
+        id, cancelled
+    ));
+
+    Ok(serde_json::json!({ "id": id, "cancelled": cancelled }))
+}
+
+/// Handle an IPC request and return the response string (or None for notifications)
+fn handle_ipc_message(
+    request: &IpcRequest,
+    config: &Config,
+    control: &RequestControl,
+) -> Option<String> {
     log(&format!(
         "IPC request: {} {:?}",
         request.method, request.params
     ));
 
     // handle the request and get result
-    let result = handle_ipc_request(&request, config);
+    let started = std::time::Instant::now();
+    let result = handle_ipc_request(request, config, control);
+    METRICS.record_ipc_request(&request.method, result.is_ok(), started.elapsed());
 
     // format response based on input format
     match result {
-        Ok(value) => format_success_response(&request, value),
-        Err((code, msg)) => format_error_response(&request, code, &msg),
+        Ok(value) => format_success_response(request, value),
+        Err((code, msg)) => format_error_response(request, code, &msg),
+    }
+}
+
+/// Handle `stats` - daemon metrics as JSON, or Prometheus text with `format: "prometheus"`
+fn handle_stats(request: &IpcRequest, config: &Config) -> Result<serde_json::Value, (i32, String)> {
+    let history = HISTORY_MANAGER.lock().ok().and_then(|guard| {
+        let (undo, redo) = guard.as_ref()?.stack_lengths().ok()?;
+        Some(metrics::HistoryStats {
+            undo,
+            redo,
+            limit: config.settings.history.limit,
+        })
+    });
+
+    let snapshot = METRICS.snapshot(metrics::Gauges {
+        subscribers: events::EVENT_BUS.subscriber_stats(),
+        events_dropped: events::EVENT_BUS.dropped_count(),
+        history,
+    });
+
+    match request.params.get("format").map(String::as_str) {
+        None | Some("json") => {
+            serde_json::to_value(&snapshot).map_err(|e| (exit_codes::ERROR, e.to_string()))
+        }
+        Some("prometheus") => Ok(serde_json::json!({
+            "format": "prometheus",
+            "text": metrics::render_prometheus(&snapshot),
+        })),
+        Some(other) => Err((
+            exit_codes::INVALID_ARGS,
+            format!(
+                "unknown stats format '{}', expected: json, prometheus",
+                other
+            ),
+        )),
+    }
+}
+
+/// the config with the active profile applied, or `fallback` before startup finishes
+fn current_config(fallback: &Arc<Config>) -> Arc<Config> {
+    PROFILE_STATE
+        .lock()
+        .ok()
+        .and_then(|guard| guard.as_ref().map(|state| Arc::clone(&state.effective)))
+        .unwrap_or_else(|| Arc::clone(fallback))
+}
+
+fn active_profile() -> Option<String> {
+    PROFILE_STATE
+        .lock()
+        .ok()
+        .and_then(|guard| guard.as_ref().and_then(|state| state.active.clone()))
+}
+
+/// Switch to profile `name` (None = base config) and re-register shortcuts and app rules
+fn switch_profile(name: Option<&str>) -> Result<Arc<Config>> {
+    let mut guard = PROFILE_STATE
+        .lock()
+        .map_err(|_| anyhow!("Failed to lock profile state"))?;
+    let state = guard
+        .as_mut()
+        .ok_or_else(|| anyhow!("Daemon is still starting"))?;
+
+    let effective = Arc::new(config::profile::effective(&state.base, name)?);
+    config::profile::save_active(name)?;
+
+    let signals = hotkeys::update_hotkey_listener(build_keymap(&effective)?);
+    app_watcher::set_rules(effective.app_rules.clone());
+    // cycles are tracked by shortcut index, which the profile may change
+    if let Ok(mut cycles) = CYCLES.lock() {
+        cycles.reset();
+    }
+
+    state.active = name.map(str::to_string);
+    state.effective = Arc::clone(&effective);
+
+    drop(guard);
+
+    log(&format!(
+        "Switched to profile: {}",
+        name.unwrap_or("(none)")
+    ));
+    for signal in &signals {
+        handle_hotkey_signal(signal, &effective);
+    }
+    Ok(effective)
+}
+
+/// the input source changed: without `settings.keyboard_layout`, hotkeys are
+/// read with its layout, so re-register them if that changed
+fn keyboard_layout_changed() {
+    if !keyboard::redetect() {
+        return;
+    }
+    // off the run loop, which also runs the event tap
+    std::thread::spawn(|| {
+        let Ok(guard) = PROFILE_STATE.lock() else {
+            return;
+        };
+        let Some(effective) = guard.as_ref().map(|state| Arc::clone(&state.effective)) else {
+            return;
+        };
+        log(&format!(
+            "Keyboard layout changed to '{}', re-registering shortcuts",
+            keyboard::active().id
+        ));
+        let signals = match build_keymap(&effective) {
+            Ok(keymap) => hotkeys::update_hotkey_listener(keymap),
+            Err(e) => {
+                log_err(&format!("Failed to re-register shortcuts: {}", e));
+                return;
+            }
+        };
+        drop(guard);
+
+        for signal in &signals {
+            handle_hotkey_signal(signal, &effective);
+        }
+    });
+}
+
+/// Handle `profile` - `command: "use"` (with `name`), `"clear"` or `"list"`
+fn handle_profile(request: &IpcRequest) -> Result<serde_json::Value, (i32, String)> {
+    let command = request.params.get("command").map(String::as_str);
+
+    match command {
+        Some("use") | Some("clear") => {
+            let name = match command {
+                Some("use") => Some(request.params.get("name").ok_or_else(|| {
+                    (
+                        exit_codes::INVALID_ARGS,
+                        "profile use requires 'name' parameter".to_string(),
+                    )
+                })?),
+                _ => None,
+            };
+            let effective = switch_profile(name.map(String::as_str))
+                .map_err(|e| (exit_codes::ERROR, e.to_string()))?;
+            Ok(serde_json::json!({
+                "profile": name,
+                "shortcuts": effective.shortcuts.len(),
+                "app_rules": effective.app_rules.len(),
+            }))
+        }
+        Some("list") | None => {
+            let guard = PROFILE_STATE.lock().map_err(|_| {
+                (
+                    exit_codes::ERROR,
+                    "Failed to lock profile state".to_string(),
+                )
+            })?;
+            let state = guard
+                .as_ref()
+                .ok_or_else(|| (exit_codes::ERROR, "Daemon is still starting".to_string()))?;
+            Ok(serde_json::json!({
+                "active": state.active,
+                "profiles": config::profile::names(&state.base),
+            }))
+        }
+        Some(other) => Err((
+            exit_codes::INVALID_ARGS,
+            format!(
+                "unknown profile command '{}', expected: use, clear, list",
+                other
+            ),
+        )),
     }
 }
 
@@ -1495,9 +2756,15 @@ fn push_to_history(entry: crate::history::HistoryEntry) {
 fn handle_ipc_request(
     request: &IpcRequest,
     config: &Config,
+    control: &RequestControl,
 ) -> Result<serde_json::Value, (i32, String)> {
     use crate::actions::{execute, ExecutionContext, JsonRpcRequest};
 
+    // the deadline may already have passed while the request was queued
+    control
+        .check()
+        .map_err(|reason| (reason.code(), reason.message().to_string()))?;
+
     // handle special "action" method for raw action strings (hotkey-style)
     if request.method == "action" {
         let action = request.params.get("action").ok_or_else(|| {
@@ -1507,9 +2774,12 @@ fn handle_ipc_request(
             )
         })?;
 
-        return execute_action(action, config)
+        return execute_action(action, config, Some(control))
             .map(|()| serde_json::json!({"message": "Action executed"}))
-            .map_err(|e| (exit_codes::ERROR, e.to_string()));
+            .map_err(|e| match e.downcast_ref::<crate::actions::Interrupted>() {
+                Some(reason) => (reason.code(), reason.message().to_string()),
+                None => (exit_codes::ERROR, e.to_string()),
+            });
     }
 
     // handle history commands directly (they need access to daemon state)
@@ -1518,6 +2788,9 @@ fn handle_ipc_request(
         "redo" => return handle_redo(config),
         "history_list" => return handle_history_list(),
         "history_clear" => return handle_history_clear(),
+        "remote_pair" => return handle_remote_pair(config),
+        "stats" => return handle_stats(request, config),
+        "profile" => return handle_profile(request),
         "history" => {
             // handle history with command param
             if let Some(cmd) = request.params.get("command") {
@@ -1545,7 +2818,7 @@ fn handle_ipc_request(
     // convert IpcRequest to JSON string and parse with JsonRpcRequest
     let json_str = serde_json::json!({
         "method": request.method,
-        "params": request.params,
+        "params": request.raw_params,
         "id": request.id,
     })
     .to_string();
@@ -1556,9 +2829,14 @@ fn handle_ipc_request(
     // convert to Command and execute
     let cmd = json_request.to_command().map_err(|e| (e.code, e.message))?;
 
-    let ctx = ExecutionContext::new(config, false);
+    let ctx = ExecutionContext::new(config, false).with_control(control);
+
+    let action_name = cmd.method_name();
+    let started = std::time::Instant::now();
+    let result = execute(cmd, &ctx);
+    METRICS.record_action(action_name, result.is_ok(), started.elapsed());
 
-    match execute(cmd, &ctx) {
+    match result {
         Ok(result) => {
             // push pre-action state to history on success
             if let Some(entry) = pre_action_state {
@@ -1589,6 +2867,12 @@ mod tests {
             display_aliases: std::collections::HashMap::new(),
             settings: Settings::default(),
             schema: None,
+            version: crate::config::migrate::CURRENT_VERSION,
+            include: vec![],
+            vars: std::collections::HashMap::new(),
+            profiles: std::collections::HashMap::new(),
+            modes: std::collections::HashMap::new(),
+            mouse_bindings: vec![],
         }
     }
 
@@ -1611,12 +2895,14 @@ mod tests {
             app: Some("Safari".to_string()),
             launch: None,
             when: None,
+            ..Default::default()
         }]);
 
         let result = parse_shortcuts(&config).unwrap();
         assert_eq!(result.len(), 1);
 
-        let (hotkey, action) = &result[0];
+        let ShortcutKeys { keys, action, .. } = &result[0];
+        let hotkey = &keys.chords[0];
         assert!(hotkey.modifiers.ctrl);
         assert!(hotkey.modifiers.alt);
         assert_eq!(hotkey.keys, vec!["s"]);
@@ -1631,13 +2917,54 @@ mod tests {
             app: None,
             launch: None,
             when: None,
+            ..Default::default()
         }]);
 
         let result = parse_shortcuts(&config).unwrap();
         assert_eq!(result.len(), 1);
 
-        let (_, action) = &result[0];
-        assert_eq!(action, "maximize");
+        assert_eq!(result[0].action, "maximize");
+    }
+
+    #[test]
+    fn test_shortcut_repeat_and_window() {
+        let config = create_test_config(vec![
+            Shortcut {
+                keys: "ctrl+alt+s".to_string(),
+                action: "focus".to_string(),
+                app: Some("Safari".to_string()),
+                ..Default::default()
+            },
+            Shortcut {
+                keys: "ctrl+alt+right".to_string(),
+                action: "move:next".to_string(),
+                repeat: Some("throttle:250ms".to_string()),
+                ..Default::default()
+            },
+            Shortcut {
+                keys: "ctrl+alt+m".to_string(),
+                action: "maximize".to_string(),
+                repeat: Some("sometimes".to_string()),
+                ..Default::default()
+            },
+        ]);
+
+        let result = parse_shortcuts(&config).unwrap();
+        assert_eq!(result[0].repeat, Repeat::Ignore);
+        assert_eq!(
+            result[1].repeat,
+            Repeat::Throttle(std::time::Duration::from_millis(250))
+        );
+        // an invalid repeat falls back to the default
+        assert_eq!(result[2].repeat, Repeat::Ignore);
+
+        // actions queue per window, whichever app or shortcut they come from
+        assert_eq!(window_key(Some("Safari"), Some((42, 7))), "42:7");
+        assert_eq!(window_key(None, Some((42, 7))), "42:7");
+        assert_ne!(window_key(None, Some((42, 8))), "42:7");
+        // an app without a window yet is keyed by its name
+        assert_eq!(window_key(Some("Safari"), None), "safari");
+        assert_eq!(window_key(None, None), "");
     }
 
     #[test]
@@ -1649,6 +2976,7 @@ mod tests {
                 app: Some("Safari".to_string()),
                 launch: Some(true),
                 when: None,
+                ..Default::default()
             },
             Shortcut {
                 keys: "ctrl+alt+m".to_string(),
@@ -1656,6 +2984,7 @@ mod tests {
                 app: None,
                 launch: None,
                 when: None,
+                ..Default::default()
             },
             Shortcut {
                 keys: "ctrl+alt+n".to_string(),
@@ -1663,6 +2992,7 @@ mod tests {
                 app: Some("next".to_string()),
                 launch: None,
                 when: None,
+                ..Default::default()
             },
         ]);
 
@@ -1679,6 +3009,7 @@ mod tests {
                 app: Some("Safari".to_string()),
                 launch: None,
                 when: None,
+                ..Default::default()
             },
             Shortcut {
                 keys: "ctrl+alt+m".to_string(), // valid
@@ -1686,13 +3017,14 @@ mod tests {
                 app: None,
                 launch: None,
                 when: None,
+                ..Default::default()
             },
         ]);
 
         let result = parse_shortcuts(&config).unwrap();
         // invalid shortcut should be skipped, valid one should be included
         assert_eq!(result.len(), 1);
-        assert_eq!(result[0].1, "maximize");
+        assert_eq!(result[0].action, "maximize");
     }
 
     // ========================================================================
@@ -1707,6 +3039,7 @@ mod tests {
             app: Some("Safari".to_string()),
             launch: Some(true),
             when: None,
+            ..Default::default()
         }]);
 
         let result = find_shortcut_launch(&config, "focus:Safari");
@@ -1721,6 +3054,7 @@ mod tests {
             app: Some("Safari".to_string()),
             launch: Some(false),
             when: None,
+            ..Default::default()
         }]);
 
         let result = find_shortcut_launch(&config, "focus:Safari");
@@ -1735,6 +3069,7 @@ mod tests {
             app: Some("Safari".to_string()),
             launch: None,
             when: None,
+            ..Default::default()
         }]);
 
         let result = find_shortcut_launch(&config, "focus:Safari");
@@ -1749,6 +3084,7 @@ mod tests {
             app: Some("Safari".to_string()),
             launch: Some(true),
             when: None,
+            ..Default::default()
         }]);
 
         let result = find_shortcut_launch(&config, "focus:Chrome");
@@ -1763,6 +3099,7 @@ mod tests {
             app: None,
             launch: None,
             when: None,
+            ..Default::default()
         }]);
 
         let result = find_shortcut_launch(&config, "maximize");
@@ -1778,6 +3115,7 @@ mod tests {
                 app: Some("Safari".to_string()),
                 launch: Some(true),
                 when: None,
+                ..Default::default()
             },
             Shortcut {
                 keys: "ctrl+alt+c".to_string(),
@@ -1785,6 +3123,7 @@ mod tests {
                 app: Some("Chrome".to_string()),
                 launch: Some(false),
                 when: None,
+                ..Default::default()
             },
         ]);
 
@@ -1805,11 +3144,12 @@ mod tests {
             app: Some("next".to_string()),
             launch: None,
             when: None,
+            ..Default::default()
         }]);
 
         let result = parse_shortcuts(&config).unwrap();
         assert_eq!(result.len(), 1);
-        assert_eq!(result[0].1, "move_display:next");
+        assert_eq!(result[0].action, "move_display:next");
     }
 
     #[test]
@@ -1820,11 +3160,12 @@ mod tests {
             app: Some("80".to_string()),
             launch: None,
             when: None,
+            ..Default::default()
         }]);
 
         let result = parse_shortcuts(&config).unwrap();
         assert_eq!(result.len(), 1);
-        assert_eq!(result[0].1, "resize:80");
+        assert_eq!(result[0].action, "resize:80");
     }
 
     #[test]
@@ -1835,12 +3176,13 @@ mod tests {
             app: Some("Safari".to_string()),
             launch: None,
             when: None,
+            ..Default::default()
         }]);
 
         let result = parse_shortcuts(&config).unwrap();
         assert_eq!(result.len(), 1);
 
-        let (hotkey, _) = &result[0];
+        let hotkey = &result[0].keys.chords[0];
         assert!(hotkey.modifiers.ctrl);
         assert!(hotkey.modifiers.alt);
         assert!(hotkey.modifiers.shift);
@@ -1855,11 +3197,171 @@ mod tests {
             app: None,
             launch: None,
             when: None,
+            ..Default::default()
         }]);
 
         let result = parse_shortcuts(&config).unwrap();
         assert_eq!(result.len(), 1);
-        assert_eq!(result[0].0.keys, vec!["f1"]);
+        assert_eq!(result[0].keys.chords[0].keys, vec!["f1"]);
+    }
+
+    #[test]
+    fn test_parse_shortcuts_sequence() {
+        let config = create_test_config(vec![Shortcut {
+            keys: "ctrl+alt+w, h".to_string(),
+            action: "move:left".to_string(),
+            app: None,
+            launch: None,
+            when: None,
+            ..Default::default()
+        }]);
+
+        let result = parse_shortcuts(&config).unwrap();
+        assert_eq!(result.len(), 1);
+        assert_eq!(result[0].keys.chords.len(), 2);
+        assert_eq!(result[0].keys.to_string(), "ctrl+alt+w, h");
+        assert_eq!(result[0].action, "move:left");
+    }
+
+    #[test]
+    fn test_parse_shortcuts_app_scope() {
+        let config = create_test_config(vec![Shortcut {
+            keys: "cmd+shift+m".to_string(),
+            action: "maximize".to_string(),
+            app: None,
+            launch: None,
+            when: None,
+            only_in: vec!["Safari".to_string()],
+            except_in: vec!["Xcode".to_string()],
+            ..Default::default()
+        }]);
+
+        let result = parse_shortcuts(&config).unwrap();
+        assert_eq!(result[0].scope.only_in, vec!["Safari"]);
+        assert_eq!(result[0].scope.except_in, vec!["Xcode"]);
+    }
+
+    #[test]
+    fn test_parse_triggers() {
+        let config = create_test_config(vec![
+            Shortcut {
+                keys: "ctrl+alt+m".to_string(),
+                action: "maximize".to_string(),
+                ..Default::default()
+            },
+            Shortcut {
+                keys: "rshift".to_string(),
+                action: "focus".to_string(),
+                app: Some("Terminal".to_string()),
+                trigger: Trigger::DoubleTap,
+                ..Default::default()
+            },
+            Shortcut {
+                keys: "ctrl+alt+space".to_string(),
+                action: "resize:80".to_string(),
+                trigger: Trigger::Hold,
+                trigger_ms: Some(700),
+                release_action: Some("resize:50".to_string()),
+                ..Default::default()
+            },
+        ]);
+
+        // only the tap shortcut is a plain hotkey
+        let shortcuts = parse_shortcuts(&config).unwrap();
+        assert_eq!(shortcuts.len(), 1);
+        assert_eq!(shortcuts[0].action, "maximize");
+
+        let triggers = parse_triggers(&config);
+        assert_eq!(triggers.len(), 2);
+        assert_eq!(triggers[0].key.to_string(), "rshift");
+        assert_eq!(triggers[0].action, "focus:Terminal");
+        assert_eq!(
+            triggers[0].gesture,
+            Gesture::DoubleTap {
+                within: std::time::Duration::from_millis(config.settings.double_tap_ms),
+            }
+        );
+        assert_eq!(
+            triggers[1].gesture,
+            Gesture::Hold {
+                after: std::time::Duration::from_millis(700),
+            }
+        );
+        assert_eq!(triggers[1].release_action.as_deref(), Some("resize:50"));
+    }
+
+    #[test]
+    fn test_parse_cycles() {
+        let config = create_test_config(vec![
+            Shortcut {
+                keys: "ctrl+alt+m".to_string(),
+                action: "maximize".to_string(),
+                ..Default::default()
+            },
+            Shortcut {
+                keys: "ctrl+alt+left".to_string(),
+                cycle: vec!["move:left;resize:50".to_string(), "move:prev".to_string()],
+                ..Default::default()
+            },
+        ]);
+
+        // a cycle is run by its index, so shortcuts with the same steps stay apart
+        let shortcuts = parse_shortcuts(&config).unwrap();
+        assert_eq!(shortcuts.len(), 2);
+        assert_eq!(shortcuts[1].action, "cycle:1");
+
+        let (index, shortcut) = cycle_shortcut(&config, &shortcuts[1].action).unwrap();
+        assert_eq!(index, 1);
+        assert_eq!(shortcut.keys, "ctrl+alt+left");
+        assert!(cycle_shortcut(&config, "cycle:0").is_none());
+        assert!(cycle_shortcut(&config, "cycle:9").is_none());
+        assert!(cycle_shortcut(&config, "maximize").is_none());
+
+        assert_eq!(
+            describe_action("cycle:1", &config),
+            "cycle [move:left;resize:50, move:prev]"
+        );
+        assert_eq!(describe_action("maximize", &config), "maximize");
+    }
+
+    #[test]
+    fn test_parse_modes() {
+        let mut config = create_test_config(vec![]);
+        config.modes.insert(
+            "resize".to_string(),
+            crate::config::Mode {
+                enter: "ctrl+alt+r".to_string(),
+                exit: "escape".to_string(),
+                bindings: [
+                    ("h".to_string(), "resize:50".to_string()),
+                    ("ctrl+alt+x, y".to_string(), "maximize".to_string()),
+                ]
+                .into_iter()
+                .collect(),
+                timeout_ms: Some(3000),
+            },
+        );
+        config.modes.insert(
+            "broken".to_string(),
+            crate::config::Mode {
+                enter: "ctrl+alt+b".to_string(),
+                exit: "escape, q".to_string(),
+                bindings: Default::default(),
+                timeout_ms: None,
+            },
+        );
+
+        let modes = parse_modes(&config);
+        assert_eq!(modes.len(), 1);
+        assert_eq!(modes[0].name, "resize");
+        assert_eq!(modes[0].enter.to_string(), "ctrl+alt+r");
+        // the sequence is not a single key combination, so it is skipped
+        assert_eq!(modes[0].bindings.len(), 1);
+        assert_eq!(modes[0].bindings[0].1, "resize:50");
+        assert_eq!(
+            modes[0].timeout,
+            Some(std::time::Duration::from_millis(3000))
+        );
     }
 
     #[test]
@@ -1871,6 +3373,7 @@ mod tests {
                 app: None,
                 launch: None,
                 when: None,
+                ..Default::default()
             },
             Shortcut {
                 keys: "ctrl+tab".to_string(),
@@ -1878,6 +3381,7 @@ mod tests {
                 app: Some("Safari".to_string()),
                 launch: None,
                 when: None,
+                ..Default::default()
             },
         ]);
 
@@ -1897,6 +3401,7 @@ mod tests {
             app: Some("Safari".to_string()),
             launch: Some(true),
             when: None,
+            ..Default::default()
         }]);
 
         // action matching is case-sensitive
@@ -1922,6 +3427,7 @@ mod tests {
                 app: Some("Safari".to_string()),
                 launch: Some(true),
                 when: None,
+                ..Default::default()
             },
             Shortcut {
                 keys: "ctrl+shift+s".to_string(),
@@ -1929,6 +3435,7 @@ mod tests {
                 app: Some("Safari".to_string()),
                 launch: Some(false),
                 when: None,
+                ..Default::default()
             },
         ]);
 
@@ -1945,7 +3452,7 @@ mod tests {
         let config = create_test_config(vec![]);
         let request = IpcRequest::parse(r#"{"method": "ping"}"#).unwrap();
 
-        let result = handle_ipc_request(&request, &config);
+        let result = handle_ipc_request(&request, &config, &RequestControl::new());
         assert!(result.is_ok());
         let value = result.unwrap();
         // ping returns ActionResult with action="ping" and flattened data
@@ -1962,6 +3469,7 @@ mod tests {
                 app: Some("Safari".to_string()),
                 launch: None,
                 when: None,
+                ..Default::default()
             },
             Shortcut {
                 keys: "ctrl+alt+m".to_string(),
@@ -1969,11 +3477,12 @@ mod tests {
                 app: None,
                 launch: None,
                 when: None,
+                ..Default::default()
             },
         ]);
         let request = IpcRequest::parse(r#"{"method": "status"}"#).unwrap();
 
-        let result = handle_ipc_request(&request, &config);
+        let result = handle_ipc_request(&request, &config, &RequestControl::new());
         assert!(result.is_ok());
 
         let value = result.unwrap();
@@ -1991,7 +3500,7 @@ mod tests {
         let config = create_test_config(vec![]);
         let request = IpcRequest::parse(r#"{"method": "unknown_method"}"#).unwrap();
 
-        let result = handle_ipc_request(&request, &config);
+        let result = handle_ipc_request(&request, &config, &RequestControl::new());
         assert!(result.is_err());
 
         let (code, msg) = result.unwrap_err();
@@ -1999,12 +3508,151 @@ mod tests {
         assert!(msg.contains("unknown") || msg.contains("Unknown"));
     }
 
+    #[test]
+    fn test_handle_ipc_request_deadline_exceeded() {
+        let config = create_test_config(vec![]);
+        let request = IpcRequest::parse(r#"{"method":"ping","id":1}"#).unwrap();
+        let control = RequestControl::new().with_deadline(std::time::Duration::ZERO);
+
+        let result = handle_ipc_request(&request, &config, &control);
+        assert_eq!(result.unwrap_err().0, exit_codes::TIMEOUT);
+    }
+
+    #[test]
+    fn test_handle_ipc_request_cancelled() {
+        let config = create_test_config(vec![]);
+        let request = IpcRequest::parse(r#"{"method":"ping","id":1}"#).unwrap();
+        let control = RequestControl::new();
+        control.cancel();
+
+        let result = handle_ipc_request(&request, &config, &control);
+        assert_eq!(result.unwrap_err().0, exit_codes::CANCELLED);
+    }
+
+    fn remote_peer() -> Peer {
+        Peer::Remote("client-fp".to_string())
+    }
+
+    #[test]
+    fn test_handle_remote_line_ping() {
+        let config = create_test_config(vec![]);
+        let policy = auth::AccessPolicy::for_current_user(&config.settings.ipc);
+
+        let response = handle_remote_line(
+            r#"{"method":"ping","id":1}"#,
+            &remote_peer(),
+            &config,
+            &policy,
+        )
+        .unwrap();
+        let value: serde_json::Value = serde_json::from_str(&response).unwrap();
+        assert_eq!(value["result"]["result"], "pong");
+    }
+
+    #[test]
+    fn test_handle_remote_line_rejects_local_only_methods() {
+        let config = create_test_config(vec![]);
+        let policy = auth::AccessPolicy::for_current_user(&config.settings.ipc);
+
+        for method in ["subscribe", "remote_pair"] {
+            let line = format!(r#"{{"method":"{}","id":1}}"#, method);
+            let response = handle_remote_line(&line, &remote_peer(), &config, &policy).unwrap();
+            assert!(response.contains("not available over remote connections"));
+        }
+    }
+
+    #[test]
+    fn test_handle_remote_line_needs_a_token_to_kill() {
+        let config = create_test_config(vec![]);
+        let policy = auth::AccessPolicy::for_current_user(&config.settings.ipc);
+
+        let line = r#"{"method":"kill","params":{"app":"Safari"},"id":1}"#;
+        let response = handle_remote_line(line, &remote_peer(), &config, &policy).unwrap();
+        assert!(response.contains("needs a token"));
+    }
+
+    #[test]
+    fn test_handle_remote_pair_when_disabled() {
+        let config = create_test_config(vec![]);
+        let request = IpcRequest::parse(r#"{"method":"remote_pair","id":1}"#).unwrap();
+
+        let err = handle_ipc_request(&request, &config, &RequestControl::new()).unwrap_err();
+        assert!(err.1.contains("not enabled"));
+    }
+
+    #[test]
+    fn test_handle_stats_counts_requests() {
+        let config = create_test_config(vec![]);
+        let ping = IpcRequest::parse(r#"{"method":"ping","id":1}"#).unwrap();
+        handle_ipc_message(&ping, &config, &RequestControl::new());
+
+        let request = IpcRequest::parse(r#"{"method":"stats","id":2}"#).unwrap();
+        let stats = handle_ipc_request(&request, &config, &RequestControl::new()).unwrap();
+
+        // other tests share the global metrics, so only check lower bounds
+        assert!(stats["ipc_requests"]["ping"]["count"].as_u64().unwrap() >= 1);
+        assert!(stats["actions"]["ping"]["count"].as_u64().unwrap() >= 1);
+        assert!(stats["uptime_secs"].is_u64());
+        assert!(stats["subscribers"].is_array());
+    }
+
+    #[test]
+    fn test_handle_stats_prometheus() {
+        let config = create_test_config(vec![]);
+        let request =
+            IpcRequest::parse(r#"{"method":"stats","params":{"format":"prometheus"},"id":1}"#)
+                .unwrap();
+        let stats = handle_ipc_request(&request, &config, &RequestControl::new()).unwrap();
+
+        assert_eq!(stats["format"], "prometheus");
+        assert!(stats["text"]
+            .as_str()
+            .unwrap()
+            .contains("# TYPE cwm_uptime_seconds gauge"));
+
+        let request =
+            IpcRequest::parse(r#"{"method":"stats","params":{"format":"xml"},"id":1}"#).unwrap();
+        let err = handle_ipc_request(&request, &config, &RequestControl::new()).unwrap_err();
+        assert_eq!(err.0, exit_codes::INVALID_ARGS);
+    }
+
+    #[test]
+    fn test_handle_cancel_request_missing_id() {
+        let request = IpcRequest::parse(r#"{"method":"$/cancelRequest","id":2}"#).unwrap();
+        let result = handle_cancel_request(&request, &Peer::Local(501));
+        assert_eq!(result.unwrap_err().0, exit_codes::INVALID_ARGS);
+    }
+
+    #[test]
+    fn test_handle_cancel_request_cancels_registered() {
+        let control = Arc::new(RequestControl::new());
+        let peer = Peer::Local(501);
+        let request = IpcRequest::parse(r#"{"method":"kill","id":"cancel-test-42"}"#).unwrap();
+        let registration = register_request(&request, &peer, &control);
+
+        let cancel = IpcRequest::parse(
+            r#"{"method":"$/cancelRequest","params":{"id":"cancel-test-42"},"id":3}"#,
+        )
+        .unwrap();
+        // another peer can't cancel it
+        let remote = Peer::Remote("client-fp".to_string());
+        let result = handle_cancel_request(&cancel, &remote).unwrap();
+        assert_eq!(result["cancelled"], 0);
+        assert!(!control.is_cancelled());
+
+        let result = handle_cancel_request(&cancel, &peer).unwrap();
+        assert_eq!(result["cancelled"], 1);
+        assert!(control.is_cancelled());
+
+        unregister_request(registration);
+    }
+
     #[test]
     fn test_handle_ipc_request_focus_missing_app() {
         let config = create_test_config(vec![]);
         let request = IpcRequest::parse(r#"{"method": "focus"}"#).unwrap();
 
-        let result = handle_ipc_request(&request, &config);
+        let result = handle_ipc_request(&request, &config, &RequestControl::new());
         assert!(result.is_err());
 
         let (code, msg) = result.unwrap_err();
@@ -2017,7 +3665,7 @@ mod tests {
         let config = create_test_config(vec![]);
         let request = IpcRequest::parse(r#"{"method": "resize"}"#).unwrap();
 
-        let result = handle_ipc_request(&request, &config);
+        let result = handle_ipc_request(&request, &config, &RequestControl::new());
         assert!(result.is_err());
 
         let (code, msg) = result.unwrap_err();
@@ -2030,7 +3678,7 @@ mod tests {
         let config = create_test_config(vec![]);
         let request = IpcRequest::parse(r#"{"method": "move"}"#).unwrap();
 
-        let result = handle_ipc_request(&request, &config);
+        let result = handle_ipc_request(&request, &config, &RequestControl::new());
         assert!(result.is_err());
 
         let (code, msg) = result.unwrap_err();
@@ -2043,7 +3691,7 @@ mod tests {
         let config = create_test_config(vec![]);
         let request = IpcRequest::parse(r#"{"method": "action"}"#).unwrap();
 
-        let result = handle_ipc_request(&request, &config);
+        let result = handle_ipc_request(&request, &config, &RequestControl::new());
         assert!(result.is_err());
 
         let (code, msg) = result.unwrap_err();
@@ -0,0 +1,507 @@
+//! modifier + mouse button, scroll and drag bindings
+//!
+//! `"mouse": "ctrl+alt+scroll-up"` runs an action when the wheel turns up with
+//! exactly ctrl and alt held, and `"mouse": "cmd+ctrl+button3"` when the
+//! middle button is pressed. a binding with `drag` instead of an action moves
+//! or resizes the window under the cursor while its button is held, without
+//! grabbing the title bar; see `window::drag` for the geometry
+//!
+//! a matched press is swallowed together with the drags and release of the
+//! same button, so the app under the cursor never sees half a click. plain
+//! clicks and scrolls, without modifiers, are never bound
+//!
+//! the listener passes `MouseMap` button presses, releases, drags and scrolls
+//! with the cursor position
+
+use anyhow::{anyhow, Result};
+use std::fmt;
+
+use super::hotkeys::Modifiers;
+use crate::window::drag::{DragKind, Point};
+
+/// highest button number a binding can use
+pub const MAX_BUTTON: u32 = 32;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScrollDirection {
+    Up,
+    Down,
+    Left,
+    Right,
+}
+
+impl ScrollDirection {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ScrollDirection::Up => "up",
+            ScrollDirection::Down => "down",
+            ScrollDirection::Left => "left",
+            ScrollDirection::Right => "right",
+        }
+    }
+}
+
+/// the mouse part of a binding
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MouseInput {
+    /// a button, numbered from 1: 1 is left, 2 is right and 3 is middle
+    Button(u32),
+    Scroll(ScrollDirection),
+}
+
+impl MouseInput {
+    /// Parse `button1`-`button32`, `left`, `right`, `middle` or `scroll-up`/`-down`/`-left`/`-right`
+    pub fn parse(s: &str) -> Result<Self> {
+        let s = s.trim().to_lowercase();
+        match s.as_str() {
+            "left" => return Ok(MouseInput::Button(1)),
+            "right" => return Ok(MouseInput::Button(2)),
+            "middle" => return Ok(MouseInput::Button(3)),
+            "scroll-up" => return Ok(MouseInput::Scroll(ScrollDirection::Up)),
+            "scroll-down" => return Ok(MouseInput::Scroll(ScrollDirection::Down)),
+            "scroll-left" => return Ok(MouseInput::Scroll(ScrollDirection::Left)),
+            "scroll-right" => return Ok(MouseInput::Scroll(ScrollDirection::Right)),
+            _ => {}
+        }
+
+        let button = s
+            .strip_prefix("button")
+            .and_then(|n| n.parse::<u32>().ok())
+            .filter(|n| (1..=MAX_BUTTON).contains(n));
+        button.map(MouseInput::Button).ok_or_else(|| {
+            anyhow!(
+                "unknown mouse input '{}': use button1-button{}, left, right, middle, \
+                 scroll-up, scroll-down, scroll-left or scroll-right",
+                s,
+                MAX_BUTTON
+            )
+        })
+    }
+}
+
+impl fmt::Display for MouseInput {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MouseInput::Button(n) => write!(f, "button{}", n),
+            MouseInput::Scroll(direction) => write!(f, "scroll-{}", direction.as_str()),
+        }
+    }
+}
+
+/// modifiers held with a mouse input, like "ctrl+alt+scroll-up"
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MouseChord {
+    pub modifiers: Modifiers,
+    pub input: MouseInput,
+}
+
+impl MouseChord {
+    /// Parse modifiers and one mouse input; at least one modifier is required
+    pub fn parse(s: &str) -> Result<Self> {
+        let trimmed = s.trim();
+        if trimmed.is_empty() {
+            return Err(anyhow!("Empty mouse binding"));
+        }
+
+        let mut parts: Vec<String> = trimmed
+            .split('+')
+            .map(|p| p.trim().to_lowercase())
+            .filter(|p| !p.is_empty())
+            .collect();
+        let input = parts
+            .pop()
+            .ok_or_else(|| anyhow!("No mouse input in binding: '{}'", trimmed))?;
+
+        let mut modifiers = Modifiers::default();
+        for part in &parts {
+            match part.as_str() {
+                "ctrl" | "control" => modifiers.ctrl = true,
+                "alt" | "option" | "opt" => modifiers.alt = true,
+                "cmd" | "command" | "meta" | "super" => modifiers.cmd = true,
+                "shift" => modifiers.shift = true,
+                _ => {
+                    return Err(anyhow!(
+                        "'{}' is not a modifier; a mouse binding is modifiers and one mouse input",
+                        part
+                    ))
+                }
+            }
+        }
+        if modifiers == Modifiers::default() {
+            return Err(anyhow!(
+                "'{}' needs at least one modifier, plain clicks and scrolls are not bound",
+                trimmed
+            ));
+        }
+
+        Ok(MouseChord {
+            modifiers,
+            input: MouseInput::parse(&input)?,
+        })
+    }
+}
+
+impl fmt::Display for MouseChord {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let modifiers = [
+            (self.modifiers.ctrl, "ctrl"),
+            (self.modifiers.alt, "alt"),
+            (self.modifiers.cmd, "cmd"),
+            (self.modifiers.shift, "shift"),
+        ];
+        for (_, name) in modifiers.iter().filter(|(held, _)| *held) {
+            write!(f, "{}+", name)?;
+        }
+        write!(f, "{}", self.input)
+    }
+}
+
+/// what a mouse binding does
+#[derive(Debug, Clone, PartialEq)]
+pub enum MouseAction {
+    /// run an action on the focused window, or on the window under the cursor
+    Run { action: String, under_cursor: bool },
+    /// move or resize the window under the cursor until the button is let go
+    Drag(DragKind),
+}
+
+/// a mouse binding as the listener sees it
+#[derive(Debug, Clone, PartialEq)]
+pub struct MouseBinding {
+    pub chord: MouseChord,
+    pub action: MouseAction,
+}
+
+/// a mouse event from the listener
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MouseEvent {
+    Down(u32),
+    Up(u32),
+    Dragged(u32),
+    Scroll(ScrollDirection),
+}
+
+/// something the daemon should do for a mouse binding
+#[derive(Debug, Clone, PartialEq)]
+pub enum MouseSignal {
+    /// run an action; `at` is the cursor position when it is for the window under it
+    Run {
+        action: String,
+        binding: String,
+        at: Option<Point>,
+    },
+    /// a drag started on the window under `at`
+    DragStarted {
+        kind: DragKind,
+        binding: String,
+        at: Point,
+    },
+    /// the cursor moved during a drag
+    DragMoved { at: Point },
+    /// the button was let go at `at`
+    DragEnded { at: Point },
+}
+
+/// mouse bindings, and the buttons whose events are being swallowed
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MouseMap {
+    bindings: Vec<MouseBinding>,
+    /// buttons whose press was consumed, so their drags and release are too
+    held: Vec<u32>,
+    /// the button dragging a window
+    dragging: Option<u32>,
+}
+
+impl MouseMap {
+    /// the first of several bindings for the same chord wins
+    pub fn new(bindings: Vec<MouseBinding>) -> Self {
+        Self {
+            bindings,
+            held: Vec::new(),
+            dragging: None,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.bindings.is_empty()
+    }
+
+    fn binding(&self, modifiers: Modifiers, input: MouseInput) -> Option<&MouseBinding> {
+        self.bindings
+            .iter()
+            .find(|binding| binding.chord.modifiers == modifiers && binding.chord.input == input)
+    }
+
+    /// whether `event` starts something, so the frontmost app is worth looking up
+    pub fn wants(&self, modifiers: Modifiers, event: MouseEvent) -> bool {
+        match event {
+            MouseEvent::Down(button) => self
+                .binding(modifiers, MouseInput::Button(button))
+                .is_some(),
+            MouseEvent::Scroll(direction) => self
+                .binding(modifiers, MouseInput::Scroll(direction))
+                .is_some(),
+            MouseEvent::Up(_) | MouseEvent::Dragged(_) => false,
+        }
+    }
+
+    /// handle a mouse event with `modifiers` held and the cursor at `at`;
+    /// `None` lets the event through, `Some` swallows it
+    pub fn event(
+        &mut self,
+        modifiers: Modifiers,
+        event: MouseEvent,
+        at: Point,
+    ) -> Option<Vec<MouseSignal>> {
+        match event {
+            MouseEvent::Down(button) => {
+                let binding = self.binding(modifiers, MouseInput::Button(button))?.clone();
+                self.held.push(button);
+                let binding_name = binding.chord.to_string();
+                match binding.action {
+                    MouseAction::Run {
+                        action,
+                        under_cursor,
+                    } => Some(vec![MouseSignal::Run {
+                        action,
+                        binding: binding_name,
+                        at: under_cursor.then_some(at),
+                    }]),
+                    // one window at a time; a second drag button is only swallowed
+                    MouseAction::Drag(_) if self.dragging.is_some() => Some(Vec::new()),
+                    MouseAction::Drag(kind) => {
+                        self.dragging = Some(button);
+                        Some(vec![MouseSignal::DragStarted {
+                            kind,
+                            binding: binding_name,
+                            at,
+                        }])
+                    }
+                }
+            }
+            MouseEvent::Dragged(button) => {
+                if self.dragging == Some(button) {
+                    Some(vec![MouseSignal::DragMoved { at }])
+                } else if self.held.contains(&button) {
+                    Some(Vec::new())
+                } else {
+                    None
+                }
+            }
+            MouseEvent::Up(button) => {
+                let position = self.held.iter().position(|held| *held == button)?;
+                self.held.remove(position);
+                if self.dragging == Some(button) {
+                    self.dragging = None;
+                    Some(vec![MouseSignal::DragEnded { at }])
+                } else {
+                    Some(Vec::new())
+                }
+            }
+            MouseEvent::Scroll(direction) => {
+                let binding = self.binding(modifiers, MouseInput::Scroll(direction))?;
+                match &binding.action {
+                    MouseAction::Run {
+                        action,
+                        under_cursor,
+                    } => Some(vec![MouseSignal::Run {
+                        action: action.clone(),
+                        binding: binding.chord.to_string(),
+                        at: under_cursor.then_some(at),
+                    }]),
+                    // config validation rejects these
+                    MouseAction::Drag(_) => None,
+                }
+            }
+        }
+    }
+
+    /// keep swallowing the buttons `previous` was, e.g. across a profile switch
+    pub fn carry_over(&mut self, previous: &MouseMap) {
+        self.held = previous.held.clone();
+        self.dragging = previous.dragging;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chord(s: &str) -> MouseChord {
+        MouseChord::parse(s).unwrap()
+    }
+
+    fn run(mouse: &str, action: &str, under_cursor: bool) -> MouseBinding {
+        MouseBinding {
+            chord: chord(mouse),
+            action: MouseAction::Run {
+                action: action.to_string(),
+                under_cursor,
+            },
+        }
+    }
+
+    fn drag(mouse: &str, kind: DragKind) -> MouseBinding {
+        MouseBinding {
+            chord: chord(mouse),
+            action: MouseAction::Drag(kind),
+        }
+    }
+
+    fn mods(s: &str) -> Modifiers {
+        chord(&format!("{}+left", s)).modifiers
+    }
+
+    const AT: Point = Point { x: 10.0, y: 20.0 };
+
+    #[test]
+    fn test_parse() {
+        let c = chord("ctrl+alt+scroll-up");
+        assert!(c.modifiers.ctrl && c.modifiers.alt && !c.modifiers.cmd);
+        assert_eq!(c.input, MouseInput::Scroll(ScrollDirection::Up));
+
+        assert_eq!(chord("cmd+ctrl+button3").input, MouseInput::Button(3));
+        assert_eq!(chord("Option + Middle").input, MouseInput::Button(3));
+        assert_eq!(chord("shift+left").input, MouseInput::Button(1));
+        assert_eq!(chord("alt+right").input, MouseInput::Button(2));
+        assert_eq!(
+            chord("alt+scroll-left").input,
+            MouseInput::Scroll(ScrollDirection::Left)
+        );
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        for bad in [
+            "",
+            "button1",
+            "scroll-up",
+            "ctrl+alt",
+            "ctrl+button0",
+            "ctrl+button33",
+            "ctrl+a+button1",
+            "rshift+button1",
+            "ctrl+scroll",
+            "ctrl+button1+button2",
+        ] {
+            assert!(MouseChord::parse(bad).is_err(), "{}", bad);
+        }
+        let err = MouseChord::parse("button2").unwrap_err().to_string();
+        assert!(err.contains("at least one modifier"), "{}", err);
+    }
+
+    #[test]
+    fn test_display() {
+        assert_eq!(
+            chord("alt+ctrl+scroll-up").to_string(),
+            "ctrl+alt+scroll-up"
+        );
+        assert_eq!(chord("shift+cmd+middle").to_string(), "cmd+shift+button3");
+    }
+
+    #[test]
+    fn test_click_and_scroll() {
+        let mut map = MouseMap::new(vec![
+            run("cmd+ctrl+button3", "maximize", true),
+            run("ctrl+alt+scroll-up", "resize:80", false),
+        ]);
+
+        // the modifiers have to match exactly
+        assert_eq!(map.event(mods("ctrl"), MouseEvent::Down(3), AT), None);
+        assert_eq!(
+            map.event(mods("ctrl+cmd+shift"), MouseEvent::Down(3), AT),
+            None
+        );
+        assert!(!map.wants(mods("ctrl"), MouseEvent::Down(3)));
+        assert!(map.wants(mods("ctrl+cmd"), MouseEvent::Down(3)));
+
+        assert_eq!(
+            map.event(mods("ctrl+cmd"), MouseEvent::Down(3), AT),
+            Some(vec![MouseSignal::Run {
+                action: "maximize".to_string(),
+                binding: "ctrl+cmd+button3".to_string(),
+                at: Some(AT),
+            }])
+        );
+        // the rest of the click is swallowed, even with the modifiers let go
+        assert_eq!(
+            map.event(Modifiers::default(), MouseEvent::Dragged(3), AT),
+            Some(vec![])
+        );
+        assert_eq!(
+            map.event(Modifiers::default(), MouseEvent::Up(3), AT),
+            Some(vec![])
+        );
+        assert_eq!(map.event(Modifiers::default(), MouseEvent::Up(3), AT), None);
+
+        assert_eq!(
+            map.event(
+                mods("ctrl+alt"),
+                MouseEvent::Scroll(ScrollDirection::Up),
+                AT
+            ),
+            Some(vec![MouseSignal::Run {
+                action: "resize:80".to_string(),
+                binding: "ctrl+alt+scroll-up".to_string(),
+                at: None,
+            }])
+        );
+        assert_eq!(
+            map.event(
+                mods("ctrl+alt"),
+                MouseEvent::Scroll(ScrollDirection::Down),
+                AT
+            ),
+            None
+        );
+    }
+
+    #[test]
+    fn test_drag() {
+        let mut map = MouseMap::new(vec![
+            drag("alt+left", DragKind::Move),
+            drag("alt+right", DragKind::Resize),
+        ]);
+        let to = Point { x: 30.0, y: 40.0 };
+
+        assert_eq!(map.event(mods("alt"), MouseEvent::Dragged(1), AT), None);
+        assert_eq!(
+            map.event(mods("alt"), MouseEvent::Down(1), AT),
+            Some(vec![MouseSignal::DragStarted {
+                kind: DragKind::Move,
+                binding: "alt+button1".to_string(),
+                at: AT,
+            }])
+        );
+        // a second drag button while dragging is swallowed but does nothing
+        assert_eq!(
+            map.event(mods("alt"), MouseEvent::Down(2), AT),
+            Some(vec![])
+        );
+        assert_eq!(
+            map.event(mods("alt"), MouseEvent::Dragged(2), to),
+            Some(vec![])
+        );
+        assert_eq!(map.event(mods("alt"), MouseEvent::Up(2), to), Some(vec![]));
+
+        // letting go of the modifier does not end the drag
+        assert_eq!(
+            map.event(Modifiers::default(), MouseEvent::Dragged(1), to),
+            Some(vec![MouseSignal::DragMoved { at: to }])
+        );
+        assert_eq!(
+            map.event(Modifiers::default(), MouseEvent::Up(1), to),
+            Some(vec![MouseSignal::DragEnded { at: to }])
+        );
+        assert_eq!(map.event(mods("alt"), MouseEvent::Dragged(1), to), None);
+
+        // a new map keeps swallowing the drag of the one it replaces
+        map.event(mods("alt"), MouseEvent::Down(2), AT);
+        let mut replaced = MouseMap::new(vec![]);
+        replaced.carry_over(&map);
+        assert_eq!(
+            replaced.event(Modifiers::default(), MouseEvent::Up(2), to),
+            Some(vec![MouseSignal::DragEnded { at: to }])
+        );
+    }
+}
@@ -0,0 +1,177 @@
+//! hotkey actions queued per target window
+//!
+//! actions for the same window run one after another, so a slow one (say a
+//! launch that retries until the window shows up) does not overlap with the
+//! next press of its shortcut. actions for different windows run side by side.
+//! with `cancel_stale`, an action still waiting is dropped when a newer one for
+//! its window comes in; the one already running always finishes
+//!
+//! the daemon pushes each action and runs the ones handed back with
+//! `run_queued`, which reports back when each is done
+
+use std::collections::VecDeque;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::Mutex;
+
+#[derive(Debug)]
+struct Window<T> {
+    key: String,
+    waiting: VecDeque<T>,
+}
+
+/// what to do after pushing an action
+#[derive(Debug, PartialEq)]
+pub struct Push<T> {
+    /// the action to run now, if its window was idle
+    pub start: Option<T>,
+    /// stale actions dropped in favour of the new one
+    pub dropped: Vec<T>,
+}
+
+/// the windows with an action running, and the actions waiting for them
+#[derive(Debug, Default)]
+pub struct ActionQueue<T> {
+    busy: Vec<Window<T>>,
+}
+
+impl<T> ActionQueue<T> {
+    pub const fn new() -> Self {
+        Self { busy: Vec::new() }
+    }
+
+    /// queue `action` for `window`: it starts right away if nothing runs on
+    /// the window, and otherwise waits its turn
+    pub fn push(&mut self, window: &str, action: T, cancel_stale: bool) -> Push<T> {
+        let Some(busy) = self.busy.iter_mut().find(|busy| busy.key == window) else {
+            self.busy.push(Window {
+                key: window.to_string(),
+                waiting: VecDeque::new(),
+            });
+            return Push {
+                start: Some(action),
+                dropped: Vec::new(),
+            };
+        };
+
+        let dropped = if cancel_stale {
+            busy.waiting.drain(..).collect()
+        } else {
+            Vec::new()
+        };
+        busy.waiting.push_back(action);
+        Push {
+            start: None,
+            dropped,
+        }
+    }
+
+    /// the action running on `window` is done; returns the next one to run on it
+    pub fn finished(&mut self, window: &str) -> Option<T> {
+        let i = self.busy.iter().position(|busy| busy.key == window)?;
+        let next = self.busy[i].waiting.pop_front();
+        if next.is_none() {
+            self.busy.remove(i);
+        }
+        next
+    }
+}
+
+/// run `first` on `window`, then each action queued behind it until the window
+/// is idle. an action that panics counts as done, so its window is not left busy
+pub fn run_queued<T>(
+    queue: &Mutex<ActionQueue<T>>,
+    window: &str,
+    first: T,
+    mut run: impl FnMut(T),
+) {
+    let mut next = Some(first);
+    while let Some(action) = next {
+        // the panic itself is reported by the panic hook
+        let _ = panic::catch_unwind(AssertUnwindSafe(|| run(action)));
+        next = queue
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .finished(window);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn started<T>(start: T) -> Push<T> {
+        Push {
+            start: Some(start),
+            dropped: Vec::new(),
+        }
+    }
+
+    fn queued<T>(dropped: Vec<T>) -> Push<T> {
+        Push {
+            start: None,
+            dropped,
+        }
+    }
+
+    #[test]
+    fn test_serialises_per_window() {
+        let mut queue = ActionQueue::new();
+
+        assert_eq!(queue.push("safari", "maximize", false), started("maximize"));
+        assert_eq!(queue.push("safari", "center", false), queued(vec![]));
+        assert_eq!(queue.push("safari", "resize:50", false), queued(vec![]));
+        // another window does not wait
+        assert_eq!(queue.push("terminal", "focus", false), started("focus"));
+
+        assert_eq!(queue.finished("safari"), Some("center"));
+        assert_eq!(queue.finished("safari"), Some("resize:50"));
+        assert_eq!(queue.finished("safari"), None);
+        assert_eq!(queue.finished("terminal"), None);
+
+        // the window is idle again
+        assert_eq!(queue.push("safari", "center", false), started("center"));
+    }
+
+    #[test]
+    fn test_cancel_stale() {
+        let mut queue = ActionQueue::new();
+
+        assert_eq!(queue.push("safari", "maximize", true), started("maximize"));
+        assert_eq!(queue.push("safari", "move:left", true), queued(vec![]));
+        assert_eq!(
+            queue.push("safari", "move:right", true),
+            queued(vec!["move:left"])
+        );
+
+        assert_eq!(queue.finished("safari"), Some("move:right"));
+        assert_eq!(queue.finished("safari"), None);
+    }
+
+    #[test]
+    fn test_run_queued_survives_panic() {
+        let queue = Mutex::new(ActionQueue::new());
+        let first = queue.lock().unwrap().push("safari", "explode", false);
+        queue.lock().unwrap().push("safari", "center", false);
+
+        let mut ran = Vec::new();
+        run_queued(&queue, "safari", first.start.unwrap(), |action| {
+            if action == "explode" {
+                panic!("action failed");
+            }
+            ran.push(action);
+        });
+
+        // the action queued behind the panic still ran, and the window is idle
+        assert_eq!(ran, vec!["center"]);
+        assert_eq!(
+            queue.lock().unwrap().push("safari", "maximize", false),
+            started("maximize")
+        );
+    }
+
+    #[test]
+    fn test_finished_unknown_window() {
+        let mut queue: ActionQueue<&str> = ActionQueue::new();
+        assert_eq!(queue.finished("safari"), None);
+    }
+}
@@ -0,0 +1,176 @@
+//! in-flight IPC request tracking for `$/cancelRequest`
+//!
+//! each request with an id is registered while it runs so a client can cancel
+//! it from another connection. ids are client-chosen, so requests are keyed by
+//! the peer that sent them too: a peer can only cancel its own requests, and
+//! cancelling an id cancels every one of them that uses it
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use crate::actions::RequestControl;
+
+/// controls registered under one request id, tagged with their handle
+type Entries = Vec<(u64, Arc<RequestControl>)>;
+
+/// who sent a request
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Peer {
+    /// a local client, by user id
+    Local(u32),
+    /// a paired remote client, by key fingerprint
+    Remote(String),
+}
+
+/// registry of in-flight requests keyed by peer and request id
+#[derive(Default)]
+pub struct RequestRegistry {
+    entries: Mutex<HashMap<(Peer, String), Entries>>,
+    next_handle: Mutex<u64>,
+}
+
+/// handle returned by `register`, used to remove exactly that entry later
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RequestHandle(u64);
+
+impl RequestRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// track a running request
+    pub fn register(&self, peer: &Peer, id: &str, control: Arc<RequestControl>) -> RequestHandle {
+        let handle = {
+            let mut next = self.next_handle.lock().unwrap_or_else(|e| e.into_inner());
+            *next += 1;
+            *next
+        };
+
+        let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        entries
+            .entry((peer.clone(), id.to_string()))
+            .or_default()
+            .push((handle, control));
+
+        RequestHandle(handle)
+    }
+
+    /// stop tracking a request once it has completed
+    pub fn unregister(&self, peer: &Peer, id: &str, handle: RequestHandle) {
+        let key = (peer.clone(), id.to_string());
+        let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(list) = entries.get_mut(&key) {
+            list.retain(|(h, _)| *h != handle.0);
+            if list.is_empty() {
+                entries.remove(&key);
+            }
+        }
+    }
+
+    /// cancel every in-flight request `peer` sent with this id, returning how
+    /// many were cancelled
+    pub fn cancel(&self, peer: &Peer, id: &str) -> usize {
+        let entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        match entries.get(&(peer.clone(), id.to_string())) {
+            Some(list) => {
+                for (_, control) in list {
+                    control.cancel();
+                }
+                list.len()
+            }
+            None => 0,
+        }
+    }
+
+    /// number of in-flight requests
+    #[cfg(test)]
+    pub fn len(&self) -> usize {
+        let entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        entries.values().map(|l| l.len()).sum()
+    }
+
+    #[cfg(test)]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ME: Peer = Peer::Local(501);
+
+    #[test]
+    fn test_register_and_cancel() {
+        let registry = RequestRegistry::new();
+        let control = Arc::new(RequestControl::new());
+        registry.register(&ME, "1", Arc::clone(&control));
+
+        assert_eq!(registry.cancel(&ME, "1"), 1);
+        assert!(control.is_cancelled());
+    }
+
+    #[test]
+    fn test_cancel_unknown_id() {
+        let registry = RequestRegistry::new();
+        assert_eq!(registry.cancel(&ME, "missing"), 0);
+    }
+
+    #[test]
+    fn test_unregister_removes_entry() {
+        let registry = RequestRegistry::new();
+        let control = Arc::new(RequestControl::new());
+        let handle = registry.register(&ME, "1", Arc::clone(&control));
+        assert_eq!(registry.len(), 1);
+
+        registry.unregister(&ME, "1", handle);
+        assert!(registry.is_empty());
+        assert_eq!(registry.cancel(&ME, "1"), 0);
+        assert!(!control.is_cancelled());
+    }
+
+    #[test]
+    fn test_duplicate_ids_all_cancelled() {
+        let registry = RequestRegistry::new();
+        let a = Arc::new(RequestControl::new());
+        let b = Arc::new(RequestControl::new());
+        registry.register(&ME, "1", Arc::clone(&a));
+        registry.register(&ME, "1", Arc::clone(&b));
+
+        assert_eq!(registry.cancel(&ME, "1"), 2);
+        assert!(a.is_cancelled());
+        assert!(b.is_cancelled());
+    }
+
+    #[test]
+    fn test_unregister_only_removes_own_handle() {
+        let registry = RequestRegistry::new();
+        let a = Arc::new(RequestControl::new());
+        let b = Arc::new(RequestControl::new());
+        let handle_a = registry.register(&ME, "1", Arc::clone(&a));
+        registry.register(&ME, "1", Arc::clone(&b));
+
+        registry.unregister(&ME, "1", handle_a);
+        assert_eq!(registry.len(), 1);
+
+        registry.cancel(&ME, "1");
+        assert!(!a.is_cancelled());
+        assert!(b.is_cancelled());
+    }
+
+    #[test]
+    fn test_only_the_sender_can_cancel() {
+        let registry = RequestRegistry::new();
+        let control = Arc::new(RequestControl::new());
+        registry.register(&ME, "1", Arc::clone(&control));
+
+        let remote = Peer::Remote("client-fp".to_string());
+        assert_eq!(registry.cancel(&remote, "1"), 0);
+        assert_eq!(registry.cancel(&Peer::Local(502), "1"), 0);
+        assert!(!control.is_cancelled());
+
+        assert_eq!(registry.cancel(&ME, "1"), 1);
+        assert!(control.is_cancelled());
+    }
+}
@@ -0,0 +1,716 @@
+//! leader-key sequences for hotkeys
+//!
+//! `"keys": "ctrl+alt+w, h"` binds `ctrl+alt+w` followed by `h`. the hotkey
+//! listener feeds every key press to a `SequenceMatcher`, which decides whether
+//! the press completes a binding, starts or continues a sequence, cancels one,
+//! or is not ours at all. the matcher takes the current time as an argument
+
+use anyhow::{anyhow, Result};
+use std::collections::BTreeSet;
+use std::fmt;
+use std::time::{Duration, Instant};
+
+use super::hotkeys::{Hotkey, Modifiers};
+use super::keyboard::{self, Layout};
+
+/// the key that abandons a pending sequence
+const CANCEL_KEY: &str = "escape";
+
+/// a hotkey made of one or more chords pressed one after another
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct KeySequence {
+    pub chords: Vec<Hotkey>,
+}
+
+impl KeySequence {
+    /// Parse a sequence like "ctrl+alt+w, h"; a plain hotkey is a sequence of one chord
+    ///
+    /// a comma directly after `+` (or on its own) is the comma key, so
+    /// "cmd+," and "ctrl+alt+w, ," work as expected
+    pub fn parse(s: &str) -> Result<Self> {
+        Self::parse_in(s, keyboard::active())
+    }
+
+    /// Parse a sequence with its keys named as on `layout`
+    pub fn parse_in(s: &str, layout: &Layout) -> Result<Self> {
+        let mut parts: Vec<String> = vec![String::new()];
+        for c in s.chars() {
+            let current = parts.last_mut().expect("parts is never empty");
+            let trimmed = current.trim_end();
+            if c == ',' && !trimmed.is_empty() && !trimmed.ends_with('+') {
+                parts.push(String::new());
+            } else {
+                current.push(c);
+            }
+        }
+
+        if parts.len() == 1 {
+            return Ok(KeySequence {
+                chords: vec![Hotkey::parse_in(s, layout)?],
+            });
+        }
+
+        let chords = parts
+            .iter()
+            .enumerate()
+            .map(|(i, part)| {
+                if part.trim().is_empty() {
+                    return Err(anyhow!("Empty chord {} in hotkey: '{}'", i + 1, s.trim()));
+                }
+                Hotkey::parse_in(part, layout)
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(KeySequence { chords })
+    }
+
+    /// Parse a single key combination, rejecting sequences
+    pub fn parse_chord(s: &str) -> Result<Hotkey> {
+        Self::parse_chord_in(s, keyboard::active())
+    }
+
+    /// Parse a single key combination with its keys named as on `layout`
+    pub fn parse_chord_in(s: &str, layout: &Layout) -> Result<Hotkey> {
+        let mut sequence = Self::parse_in(s, layout)?;
+        if sequence.chords.len() > 1 {
+            return Err(anyhow!(
+                "'{}' is a sequence, only a single key combination is allowed here",
+                s.trim()
+            ));
+        }
+        Ok(sequence.chords.remove(0))
+    }
+
+    /// whether `self` is a shorter sequence that `other` begins with
+    pub fn is_prefix_of(&self, other: &KeySequence) -> bool {
+        self.chords.len() < other.chords.len()
+            && self
+                .chords
+                .iter()
+                .zip(&other.chords)
+                .all(|(a, b)| same_chord(a, b))
+    }
+
+    fn matches(&self, pressed: &[Hotkey]) -> bool {
+        self.chords.len() == pressed.len()
+            && self
+                .chords
+                .iter()
+                .zip(pressed)
+                .all(|(a, b)| same_chord(a, b))
+    }
+
+    fn continues(&self, pressed: &[Hotkey]) -> bool {
+        self.chords.len() > pressed.len()
+            && self
+                .chords
+                .iter()
+                .zip(pressed)
+                .all(|(a, b)| same_chord(a, b))
+    }
+}
+
+impl fmt::Display for KeySequence {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let chords: Vec<String> = self.chords.iter().map(|c| c.to_string()).collect();
+        write!(f, "{}", chords.join(", "))
+    }
+}
+
+impl From<Hotkey> for KeySequence {
+    fn from(hotkey: Hotkey) -> Self {
+        KeySequence {
+            chords: vec![hotkey],
+        }
+    }
+}
+
+/// same modifiers and the same set of keys, in any order
+pub fn same_chord(a: &Hotkey, b: &Hotkey) -> bool {
+    a.modifiers == b.modifiers
+        && a.keys.iter().collect::<BTreeSet<_>>() == b.keys.iter().collect::<BTreeSet<_>>()
+}
+
+fn is_cancel_key(chord: &Hotkey) -> bool {
+    chord.modifiers == Modifiers::default() && chord.keys.len() == 1 && chord.keys[0] == CANCEL_KEY
+}
+
+/// why a pending sequence was abandoned
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CancelReason {
+    /// escape was pressed
+    Escape,
+    /// the next chord did not come in time
+    Timeout,
+    /// the next chord does not continue any binding
+    Unbound,
+}
+
+impl CancelReason {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            CancelReason::Escape => "escape",
+            CancelReason::Timeout => "timeout",
+            CancelReason::Unbound => "unbound",
+        }
+    }
+}
+
+/// what the listener should do with a key press
+#[derive(Debug, Clone, PartialEq)]
+pub enum Step<T> {
+    /// not part of any binding; let the key through
+    Pass,
+    /// a binding is complete; run it
+    Matched { value: T, sequence: KeySequence },
+    /// the chords so far begin at least one binding; wait for the next one
+    Pending {
+        pressed: KeySequence,
+        /// chords that continue a binding, in binding order without duplicates
+        next: Vec<Hotkey>,
+    },
+    /// a pending sequence was abandoned
+    Cancelled {
+        pressed: KeySequence,
+        reason: CancelReason,
+    },
+}
+
+/// state machine matching key presses against sequence bindings
+///
+/// the first binding that matches the chords pressed so far wins, so a
+/// binding for `ctrl+alt+w` means `ctrl+alt+w, h` never triggers
+pub struct SequenceMatcher<T> {
+    bindings: Vec<(KeySequence, T)>,
+    timeout: Duration,
+    pending: Vec<Hotkey>,
+    deadline: Option<Instant>,
+}
+
+impl<T: Clone> SequenceMatcher<T> {
+    /// `timeout` is how long to wait for each chord after the first
+    pub fn new(bindings: Vec<(KeySequence, T)>, timeout: Duration) -> Self {
+        Self {
+            bindings,
+            timeout,
+            pending: Vec::new(),
+            deadline: None,
+        }
+    }
+
+    /// whether a sequence has been started and not finished or cancelled
+    pub fn is_pending(&self) -> bool {
+        !self.pending.is_empty()
+    }
+
+    /// when the pending sequence times out
+    pub fn deadline(&self) -> Option<Instant> {
+        self.deadline
+    }
+
+    /// whether some binding starts with `chord`
+    pub fn is_leader(&self, chord: &Hotkey) -> bool {
+        self.bindings
+            .iter()
+            .any(|(sequence, _)| same_chord(&sequence.chords[0], chord))
+    }
+
+    /// handle a key press: `chord` is the modifiers held and the keys down.
+    /// bindings for which `active` returns false are skipped; it is only
+    /// called for bindings the pressed chords match or continue
+    ///
+    /// a pending sequence that timed out without `expire` being called is
+    /// dropped silently and `chord` is matched as a fresh press
+    pub fn press(
+        &mut self,
+        chord: &Hotkey,
+        now: Instant,
+        mut active: impl FnMut(&T) -> bool,
+    ) -> Step<T> {
+        if self.deadline.is_some_and(|deadline| now >= deadline) {
+            self.reset();
+        }
+
+        if self.is_pending() && is_cancel_key(chord) {
+            return self.cancel(CancelReason::Escape);
+        }
+
+        let mut pressed = std::mem::take(&mut self.pending);
+        pressed.push(chord.clone());
+
+        if let Some((sequence, value)) = self
+            .bindings
+            .iter()
+            .find(|(s, value)| s.matches(&pressed) && active(value))
+        {
+            let step = Step::Matched {
+                value: value.clone(),
+                sequence: sequence.clone(),
+            };
+            self.reset();
+            return step;
+        }
+
+        let mut next: Vec<Hotkey> = Vec::new();
+        for (sequence, value) in &self.bindings {
+            if sequence.continues(&pressed) && active(value) {
+                let chord = &sequence.chords[pressed.len()];
+                if !next.iter().any(|n| same_chord(n, chord)) {
+                    next.push(chord.clone());
+                }
+            }
+        }
+
+        if !next.is_empty() {
+            self.pending = pressed.clone();
+            self.deadline = Some(now + self.timeout);
+            return Step::Pending {
+                pressed: KeySequence { chords: pressed },
+                next,
+            };
+        }
+
+        if pressed.len() > 1 {
+            // the key was meant for the sequence, so it is swallowed too
+            self.reset();
+            return Step::Cancelled {
+                pressed: KeySequence { chords: pressed },
+                reason: CancelReason::Unbound,
+            };
+        }
+
+        self.reset();
+        Step::Pass
+    }
+
+    /// cancel the pending sequence if its deadline has passed
+    pub fn expire(&mut self, now: Instant) -> Option<Step<T>> {
+        match self.deadline {
+            Some(deadline) if now >= deadline => Some(self.cancel(CancelReason::Timeout)),
+            _ => None,
+        }
+    }
+
+    fn cancel(&mut self, reason: CancelReason) -> Step<T> {
+        let pressed = KeySequence {
+            chords: std::mem::take(&mut self.pending),
+        };
+        self.reset();
+        Step::Cancelled { pressed, reason }
+    }
+
+    fn reset(&mut self) {
+        self.pending.clear();
+        self.deadline = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TIMEOUT: Duration = Duration::from_millis(1000);
+
+    fn chord(s: &str) -> Hotkey {
+        Hotkey::parse(s).unwrap()
+    }
+
+    fn seq(s: &str) -> KeySequence {
+        KeySequence::parse(s).unwrap()
+    }
+
+    fn matcher(bindings: &[(&str, &'static str)]) -> SequenceMatcher<&'static str> {
+        SequenceMatcher::new(
+            bindings
+                .iter()
+                .map(|(keys, action)| (seq(keys), *action))
+                .collect(),
+            TIMEOUT,
+        )
+    }
+
+    fn matched(action: &'static str, keys: &str) -> Step<&'static str> {
+        Step::Matched {
+            value: action,
+            sequence: seq(keys),
+        }
+    }
+
+    fn pending(keys: &str, next: &[&str]) -> Step<&'static str> {
+        Step::Pending {
+            pressed: seq(keys),
+            next: next.iter().map(|n| chord(n)).collect(),
+        }
+    }
+
+    fn cancelled(keys: &str, reason: CancelReason) -> Step<&'static str> {
+        Step::Cancelled {
+            pressed: seq(keys),
+            reason,
+        }
+    }
+
+    // ========================================================================
+    // parsing
+    // ========================================================================
+
+    #[test]
+    fn test_parse_single_chord() {
+        let sequence = seq("ctrl+alt+s");
+        assert_eq!(sequence.chords, vec![chord("ctrl+alt+s")]);
+    }
+
+    #[test]
+    fn test_parse_sequence() {
+        let sequence = seq("ctrl+alt+w, h");
+        assert_eq!(sequence.chords, vec![chord("ctrl+alt+w"), chord("h")]);
+
+        assert_eq!(seq("ctrl+alt+w,h"), sequence);
+        assert_eq!(seq(" ctrl+alt+w ,  h "), sequence);
+        assert_eq!(seq("ctrl+a, g, shift+g").chords.len(), 3);
+    }
+
+    #[test]
+    fn test_parse_comma_key() {
+        assert_eq!(seq("cmd+,").chords, vec![chord("cmd+,")]);
+        assert_eq!(seq(",").chords, vec![chord(",")]);
+        assert_eq!(
+            seq("ctrl+alt+w, ,").chords,
+            vec![chord("ctrl+alt+w"), chord(",")]
+        );
+        assert_eq!(seq("ctrl+,, h").chords, vec![chord("ctrl+,"), chord("h")]);
+    }
+
+    #[test]
+    fn test_parse_invalid_sequence() {
+        assert!(KeySequence::parse("").is_err());
+        assert!(KeySequence::parse("ctrl+alt+w,").is_err());
+        assert!(KeySequence::parse("ctrl+alt+w, ctrl").is_err());
+        assert!(KeySequence::parse("ctrl+alt+w, h,").is_err());
+    }
+
+    #[test]
+    fn test_parse_chord() {
+        assert_eq!(
+            KeySequence::parse_chord("shift+h").unwrap(),
+            chord("shift+h")
+        );
+        assert_eq!(KeySequence::parse_chord("cmd+,").unwrap(), chord("cmd+,"));
+        assert!(KeySequence::parse_chord("ctrl+alt+w, h").is_err());
+        assert!(KeySequence::parse_chord("").is_err());
+    }
+
+    #[test]
+    fn test_sequence_display() {
+        assert_eq!(seq("ctrl+alt+s").to_string(), "ctrl+alt+s");
+        assert_eq!(seq("alt+ctrl+w,H").to_string(), "ctrl+alt+w, h");
+        assert_eq!(seq("cmd+,, ,").to_string(), "cmd+,, ,");
+        assert_eq!(seq(&seq("cmd+,, ,").to_string()), seq("cmd+,, ,"));
+    }
+
+    #[test]
+    fn test_is_prefix_of() {
+        assert!(seq("ctrl+alt+w").is_prefix_of(&seq("ctrl+alt+w, h")));
+        assert!(seq("alt+ctrl+w").is_prefix_of(&seq("ctrl+alt+w, h, j")));
+        assert!(seq("ctrl+alt+w, h").is_prefix_of(&seq("ctrl+alt+w, h, j")));
+        assert!(!seq("ctrl+alt+w, h").is_prefix_of(&seq("ctrl+alt+w, h")));
+        assert!(!seq("ctrl+alt+w, j").is_prefix_of(&seq("ctrl+alt+w, h, j")));
+        assert!(!seq("ctrl+alt+w, h").is_prefix_of(&seq("ctrl+alt+w")));
+    }
+
+    // ========================================================================
+    // matching
+    // ========================================================================
+
+    #[test]
+    fn test_single_chord_matches_immediately() {
+        let mut m = matcher(&[("ctrl+alt+m", "maximize")]);
+        let now = Instant::now();
+
+        assert_eq!(
+            m.press(&chord("ctrl+alt+m"), now, |_| true),
+            matched("maximize", "ctrl+alt+m")
+        );
+        assert!(!m.is_pending());
+    }
+
+    #[test]
+    fn test_chord_matches_regardless_of_key_order() {
+        let mut m = matcher(&[("ctrl+s+f", "focus:Safari")]);
+        assert_eq!(
+            m.press(&chord("ctrl+f+s"), Instant::now(), |_| true),
+            matched("focus:Safari", "ctrl+s+f")
+        );
+    }
+
+    #[test]
+    fn test_unbound_key_passes() {
+        let mut m = matcher(&[("ctrl+alt+m", "maximize"), ("ctrl+alt+w, h", "move:left")]);
+        let now = Instant::now();
+
+        assert_eq!(m.press(&chord("h"), now, |_| true), Step::Pass);
+        assert_eq!(m.press(&chord("ctrl+m"), now, |_| true), Step::Pass);
+        assert_eq!(
+            m.press(&chord("ctrl+alt+shift+m"), now, |_| true),
+            Step::Pass
+        );
+        assert!(!m.is_pending());
+    }
+
+    #[test]
+    fn test_sequence_matches() {
+        let mut m = matcher(&[
+            ("ctrl+alt+w, h", "move:left"),
+            ("ctrl+alt+w, l", "move:right"),
+        ]);
+        let now = Instant::now();
+
+        assert_eq!(
+            m.press(&chord("ctrl+alt+w"), now, |_| true),
+            pending("ctrl+alt+w", &["h", "l"])
+        );
+        assert!(m.is_pending());
+        assert_eq!(m.deadline(), Some(now + TIMEOUT));
+
+        assert_eq!(
+            m.press(&chord("l"), now + Duration::from_millis(200), |_| true),
+            matched("move:right", "ctrl+alt+w, l")
+        );
+        assert!(!m.is_pending());
+        assert_eq!(m.deadline(), None);
+    }
+
+    #[test]
+    fn test_three_chord_sequence() {
+        let mut m = matcher(&[
+            ("ctrl+a, g, g", "move:top-left"),
+            ("ctrl+a, g, shift+g", "move:bottom-right"),
+        ]);
+        let start = Instant::now();
+
+        assert_eq!(
+            m.press(&chord("ctrl+a"), start, |_| true),
+            pending("ctrl+a", &["g"])
+        );
+        assert_eq!(
+            m.press(&chord("g"), start + Duration::from_millis(500), |_| true),
+            pending("ctrl+a, g", &["g", "shift+g"])
+        );
+        // each chord restarts the timeout
+        assert_eq!(m.deadline(), Some(start + Duration::from_millis(1500)));
+        assert_eq!(
+            m.press(
+                &chord("shift+g"),
+                start + Duration::from_millis(1400),
+                |_| true
+            ),
+            matched("move:bottom-right", "ctrl+a, g, shift+g")
+        );
+    }
+
+    #[test]
+    fn test_next_has_no_duplicates() {
+        let mut m = matcher(&[
+            ("ctrl+a, g, g", "move:top-left"),
+            ("ctrl+a, g, h", "move:left"),
+            ("ctrl+a, h", "maximize"),
+        ]);
+        assert_eq!(
+            m.press(&chord("ctrl+a"), Instant::now(), |_| true),
+            pending("ctrl+a", &["g", "h"])
+        );
+    }
+
+    #[test]
+    fn test_unbound_chord_cancels_and_is_consumed() {
+        let mut m = matcher(&[("ctrl+alt+w, h", "move:left")]);
+        let now = Instant::now();
+
+        m.press(&chord("ctrl+alt+w"), now, |_| true);
+        assert_eq!(
+            m.press(&chord("x"), now, |_| true),
+            cancelled("ctrl+alt+w, x", CancelReason::Unbound)
+        );
+        assert!(!m.is_pending());
+
+        // the next press starts over
+        assert_eq!(m.press(&chord("h"), now, |_| true), Step::Pass);
+    }
+
+    #[test]
+    fn test_second_chord_needs_exact_modifiers() {
+        let mut m = matcher(&[("ctrl+alt+w, h", "move:left")]);
+        let now = Instant::now();
+
+        m.press(&chord("ctrl+alt+w"), now, |_| true);
+        assert_eq!(
+            m.press(&chord("ctrl+alt+h"), now, |_| true),
+            cancelled("ctrl+alt+w, ctrl+alt+h", CancelReason::Unbound)
+        );
+    }
+
+    #[test]
+    fn test_escape_cancels() {
+        let mut m = matcher(&[("ctrl+alt+w, h", "move:left")]);
+        let now = Instant::now();
+
+        m.press(&chord("ctrl+alt+w"), now, |_| true);
+        assert_eq!(
+            m.press(&chord("escape"), now, |_| true),
+            cancelled("ctrl+alt+w", CancelReason::Escape)
+        );
+        assert!(!m.is_pending());
+        assert_eq!(m.deadline(), None);
+    }
+
+    #[test]
+    fn test_escape_passes_when_idle() {
+        let mut m = matcher(&[("ctrl+alt+w, h", "move:left")]);
+        assert_eq!(
+            m.press(&chord("escape"), Instant::now(), |_| true),
+            Step::Pass
+        );
+    }
+
+    #[test]
+    fn test_escape_with_modifiers_is_a_chord() {
+        let mut m = matcher(&[("ctrl+alt+w, shift+escape", "maximize")]);
+        let now = Instant::now();
+
+        m.press(&chord("ctrl+alt+w"), now, |_| true);
+        assert_eq!(
+            m.press(&chord("shift+escape"), now, |_| true),
+            matched("maximize", "ctrl+alt+w, shift+escape")
+        );
+    }
+
+    #[test]
+    fn test_bound_escape_still_matches_when_idle() {
+        let mut m = matcher(&[("escape", "undo"), ("ctrl+alt+w, h", "move:left")]);
+        let now = Instant::now();
+
+        assert_eq!(
+            m.press(&chord("escape"), now, |_| true),
+            matched("undo", "escape")
+        );
+
+        m.press(&chord("ctrl+alt+w"), now, |_| true);
+        assert_eq!(
+            m.press(&chord("escape"), now, |_| true),
+            cancelled("ctrl+alt+w", CancelReason::Escape)
+        );
+    }
+
+    #[test]
+    fn test_expire() {
+        let mut m = matcher(&[("ctrl+alt+w, h", "move:left")]);
+        let now = Instant::now();
+
+        assert_eq!(m.expire(now), None);
+
+        m.press(&chord("ctrl+alt+w"), now, |_| true);
+        assert_eq!(m.expire(now + TIMEOUT - Duration::from_millis(1)), None);
+        assert!(m.is_pending());
+
+        assert_eq!(
+            m.expire(now + TIMEOUT),
+            Some(cancelled("ctrl+alt+w", CancelReason::Timeout))
+        );
+        assert!(!m.is_pending());
+        assert_eq!(m.expire(now + TIMEOUT * 2), None);
+    }
+
+    #[test]
+    fn test_press_after_deadline_starts_over() {
+        let mut m = matcher(&[("ctrl+alt+w, h", "move:left"), ("h", "maximize")]);
+        let now = Instant::now();
+
+        m.press(&chord("ctrl+alt+w"), now, |_| true);
+        assert_eq!(
+            m.press(&chord("h"), now + TIMEOUT, |_| true),
+            matched("maximize", "h")
+        );
+
+        m.press(&chord("ctrl+alt+w"), now, |_| true);
+        assert_eq!(m.press(&chord("x"), now + TIMEOUT, |_| true), Step::Pass);
+    }
+
+    #[test]
+    fn test_leader_repeated_as_sequence() {
+        let mut m = matcher(&[("ctrl+b, ctrl+b", "focus:Terminal")]);
+        let now = Instant::now();
+
+        assert_eq!(
+            m.press(&chord("ctrl+b"), now, |_| true),
+            pending("ctrl+b", &["ctrl+b"])
+        );
+        assert_eq!(
+            m.press(&chord("ctrl+b"), now, |_| true),
+            matched("focus:Terminal", "ctrl+b, ctrl+b")
+        );
+    }
+
+    #[test]
+    fn test_first_binding_wins() {
+        let mut m = matcher(&[
+            ("ctrl+alt+w, h", "move:left"),
+            ("alt+ctrl+w, h", "maximize"),
+        ]);
+        let now = Instant::now();
+
+        m.press(&chord("ctrl+alt+w"), now, |_| true);
+        assert_eq!(
+            m.press(&chord("h"), now, |_| true),
+            matched("move:left", "ctrl+alt+w, h")
+        );
+    }
+
+    #[test]
+    fn test_complete_binding_beats_longer_one() {
+        let mut m = matcher(&[("ctrl+alt+w, h", "move:left"), ("ctrl+alt+w", "maximize")]);
+        let now = Instant::now();
+
+        assert_eq!(
+            m.press(&chord("ctrl+alt+w"), now, |_| true),
+            matched("maximize", "ctrl+alt+w")
+        );
+        assert_eq!(m.press(&chord("h"), now, |_| true), Step::Pass);
+    }
+
+    #[test]
+    fn test_inactive_bindings_are_skipped() {
+        let mut m = matcher(&[
+            ("ctrl+alt+w", "maximize"),
+            ("ctrl+alt+w", "resize:80"),
+            ("ctrl+alt+q, h", "move:left"),
+        ]);
+        let now = Instant::now();
+
+        assert_eq!(
+            m.press(&chord("ctrl+alt+w"), now, |action| *action != "maximize"),
+            matched("resize:80", "ctrl+alt+w")
+        );
+        assert_eq!(m.press(&chord("ctrl+alt+w"), now, |_| false), Step::Pass);
+        assert_eq!(m.press(&chord("ctrl+alt+q"), now, |_| false), Step::Pass);
+        assert!(!m.is_pending());
+    }
+
+    #[test]
+    fn test_is_leader() {
+        let m = matcher(&[("ctrl+alt+w, h", "move:left"), ("ctrl+alt+m", "maximize")]);
+
+        assert!(m.is_leader(&chord("alt+ctrl+w")));
+        assert!(m.is_leader(&chord("ctrl+alt+m")));
+        assert!(!m.is_leader(&chord("h")));
+    }
+
+    #[test]
+    fn test_no_bindings() {
+        let mut m = matcher(&[]);
+        let now = Instant::now();
+
+        assert_eq!(m.press(&chord("ctrl+alt+w"), now, |_| true), Step::Pass);
+        assert_eq!(m.press(&chord("escape"), now, |_| true), Step::Pass);
+        assert_eq!(m.expire(now + TIMEOUT), None);
+    }
+}
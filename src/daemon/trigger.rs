@@ -0,0 +1,765 @@
+//! double-tap, hold and modifier-only shortcut triggers
+//!
+//! a `tap` shortcut runs on key down and is matched by `sequence`. the other
+//! triggers depend on timing and on keys being let go:
+//! - `double_tap` runs when its keys are pressed twice within its window; both
+//!   presses are swallowed
+//! - `hold` runs once its keys have been down for its hold time, and its
+//!   `release_action` runs when they are let go. letting go earlier does nothing
+//!
+//! the keys can also be a lone modifier like `rshift`. modifier changes are
+//! never swallowed, and a modifier only counts when it is pressed and released
+//! without any other key
+//!
+//! the listener passes `Triggers` key presses, releases and modifier changes
+//! along with the current time
+
+use anyhow::Result;
+use std::fmt;
+use std::time::{Duration, Instant};
+
+use super::hotkeys::Hotkey;
+use super::keyboard::{self, Layout};
+use super::keymap::{AppScope, Signal};
+use super::sequence::{same_chord, KeySequence};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ModifierKind {
+    Ctrl,
+    Alt,
+    Cmd,
+    Shift,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Side {
+    Left,
+    Right,
+}
+
+/// a modifier key on its own; without a side it is either of the two keys
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ModifierKey {
+    pub kind: ModifierKind,
+    pub side: Option<Side>,
+}
+
+impl ModifierKey {
+    /// Parse a modifier like "shift", "rshift" or "right_shift"
+    pub fn parse(s: &str) -> Option<Self> {
+        let s = s.trim().to_lowercase();
+        let (side, name) = if let Some(name) = s.strip_prefix("left_") {
+            (Some(Side::Left), name)
+        } else if let Some(name) = s.strip_prefix("right_") {
+            (Some(Side::Right), name)
+        } else if let Some(name) = s.strip_prefix('l').filter(|name| kind(name).is_some()) {
+            (Some(Side::Left), name)
+        } else if let Some(name) = s.strip_prefix('r').filter(|name| kind(name).is_some()) {
+            (Some(Side::Right), name)
+        } else {
+            (None, s.as_str())
+        };
+
+        Some(ModifierKey {
+            kind: kind(name)?,
+            side,
+        })
+    }
+
+    /// whether pressing `pressed` is pressing this key
+    pub fn covers(&self, pressed: &ModifierKey) -> bool {
+        self.kind == pressed.kind && (self.side.is_none() || self.side == pressed.side)
+    }
+}
+
+fn kind(name: &str) -> Option<ModifierKind> {
+    match name {
+        "ctrl" | "control" => Some(ModifierKind::Ctrl),
+        "alt" | "option" | "opt" => Some(ModifierKind::Alt),
+        "cmd" | "command" | "meta" | "super" => Some(ModifierKind::Cmd),
+        "shift" => Some(ModifierKind::Shift),
+        _ => None,
+    }
+}
+
+impl fmt::Display for ModifierKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let side = match self.side {
+            Some(Side::Left) => "l",
+            Some(Side::Right) => "r",
+            None => "",
+        };
+        let kind = match self.kind {
+            ModifierKind::Ctrl => "ctrl",
+            ModifierKind::Alt => "alt",
+            ModifierKind::Cmd => "cmd",
+            ModifierKind::Shift => "shift",
+        };
+        write!(f, "{}{}", side, kind)
+    }
+}
+
+/// the keys of a double-tap or hold shortcut
+#[derive(Debug, Clone, PartialEq)]
+pub enum TriggerKey {
+    Chord(Hotkey),
+    Modifier(ModifierKey),
+}
+
+impl TriggerKey {
+    /// Parse a single key combination or a lone modifier; sequences are not allowed
+    pub fn parse(s: &str) -> Result<Self> {
+        Self::parse_in(s, keyboard::active())
+    }
+
+    /// Parse a trigger key with its keys named as on `layout`
+    pub fn parse_in(s: &str, layout: &Layout) -> Result<Self> {
+        match ModifierKey::parse(s) {
+            Some(modifier) => Ok(TriggerKey::Modifier(modifier)),
+            None => Ok(TriggerKey::Chord(KeySequence::parse_chord_in(s, layout)?)),
+        }
+    }
+
+    fn chord(&self) -> Option<&Hotkey> {
+        match self {
+            TriggerKey::Chord(chord) => Some(chord),
+            TriggerKey::Modifier(_) => None,
+        }
+    }
+
+    fn modifier(&self) -> Option<&ModifierKey> {
+        match self {
+            TriggerKey::Chord(_) => None,
+            TriggerKey::Modifier(modifier) => Some(modifier),
+        }
+    }
+}
+
+impl fmt::Display for TriggerKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TriggerKey::Chord(chord) => write!(f, "{}", chord),
+            TriggerKey::Modifier(modifier) => write!(f, "{}", modifier),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Gesture {
+    /// pressed twice, the second press at most `within` after the first ended
+    DoubleTap { within: Duration },
+    /// held down for `after`
+    Hold { after: Duration },
+}
+
+impl Gesture {
+    fn is_hold(&self) -> bool {
+        matches!(self, Gesture::Hold { .. })
+    }
+}
+
+impl fmt::Display for Gesture {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Gesture::DoubleTap { .. } => write!(f, "double_tap"),
+            Gesture::Hold { .. } => write!(f, "hold"),
+        }
+    }
+}
+
+/// a double-tap or hold shortcut as the listener sees it
+#[derive(Debug, Clone, PartialEq)]
+pub struct TriggerBinding {
+    pub key: TriggerKey,
+    pub gesture: Gesture,
+    pub action: String,
+    /// for a hold, run when the keys are let go after `action` ran
+    pub release_action: Option<String>,
+    pub scope: AppScope,
+}
+
+impl TriggerBinding {
+    fn run(&self) -> Signal {
+        Signal::Run {
+            action: self.action.clone(),
+            keys: format!("{} ({})", self.key, self.gesture),
+            mode: None,
+        }
+    }
+
+    fn release(&self) -> Option<Signal> {
+        Some(Signal::Run {
+            action: self.release_action.clone()?,
+            keys: format!("{} (release)", self.key),
+            mode: None,
+        })
+    }
+}
+
+/// where a key combination binding stands
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum ChordState {
+    Idle,
+    /// a double-tap binding's keys were pressed once
+    Tapped {
+        binding: usize,
+        deadline: Instant,
+    },
+    /// a hold binding's keys are down and have not been held long enough
+    Pressing {
+        binding: usize,
+        deadline: Instant,
+    },
+    /// the hold ran; its release action runs when the keys are let go
+    Held {
+        binding: usize,
+    },
+}
+
+/// a modifier pressed while no other modifier was down
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Lone {
+    key: ModifierKey,
+    /// time since this key was last tapped on its own, if it was
+    since_tap: Option<Duration>,
+    /// the hold binding it may run, and when
+    hold: Option<(usize, Instant)>,
+    /// whether the hold ran
+    held: bool,
+}
+
+/// double-tap and hold bindings, and the keys in progress
+pub struct Triggers {
+    bindings: Vec<TriggerBinding>,
+    chord: ChordState,
+    /// modifiers currently down
+    down: Vec<ModifierKey>,
+    lone: Option<Lone>,
+    /// the last modifier tapped on its own, and when it was let go
+    last_tap: Option<(ModifierKey, Instant)>,
+}
+
+impl Triggers {
+    pub fn new(bindings: Vec<TriggerBinding>) -> Self {
+        Self {
+            bindings,
+            chord: ChordState::Idle,
+            down: Vec::new(),
+            lone: None,
+            last_tap: None,
+        }
+    }
+
+    /// whether `press` could consume `chord`
+    pub fn wants(&self, chord: &Hotkey) -> bool {
+        self.chord != ChordState::Idle
+            || self
+                .bindings
+                .iter()
+                .any(|b| b.key.chord().is_some_and(|keys| same_chord(keys, chord)))
+    }
+
+    /// whether a key repeat of `chord` belongs to a double-tap or hold in progress
+    pub fn swallows_repeat(&self, chord: &Hotkey) -> bool {
+        let binding = match self.chord {
+            ChordState::Idle => return false,
+            ChordState::Tapped { binding, .. }
+            | ChordState::Pressing { binding, .. }
+            | ChordState::Held { binding } => binding,
+        };
+        self.bindings[binding]
+            .key
+            .chord()
+            .is_some_and(|keys| same_chord(keys, chord))
+    }
+
+    /// when `expire` next has something to do
+    pub fn deadline(&self) -> Option<Instant> {
+        let chord = match self.chord {
+            ChordState::Pressing { deadline, .. } => Some(deadline),
+            _ => None,
+        };
+        let modifier = self
+            .lone
+            .filter(|lone| !lone.held)
+            .and_then(|lone| lone.hold)
+            .map(|(_, deadline)| deadline);
+        match (chord, modifier) {
+            (Some(a), Some(b)) => Some(a.min(b)),
+            (a, b) => a.or(b),
+        }
+    }
+
+    /// handle a key press; `Some` when it is consumed, with the signals to report.
+    /// `active` tells whether a binding applies to the frontmost app
+    pub fn press(
+        &mut self,
+        chord: &Hotkey,
+        now: Instant,
+        mut active: impl FnMut(&AppScope) -> bool,
+    ) -> Option<Vec<Signal>> {
+        match self.chord {
+            ChordState::Tapped { binding, deadline }
+                if now <= deadline && self.swallows_repeat(chord) =>
+            {
+                self.chord = ChordState::Idle;
+                return Some(vec![self.bindings[binding].run()]);
+            }
+            // other keys go on as usual while a hold is down
+            ChordState::Held { .. } => return None,
+            // anything else abandons a double-tap or a hold that has not run
+            _ => self.chord = ChordState::Idle,
+        }
+
+        let (i, binding) = self.bindings.iter().enumerate().find(|(_, b)| {
+            b.key.chord().is_some_and(|keys| same_chord(keys, chord)) && active(&b.scope)
+        })?;
+        self.chord = match binding.gesture {
+            Gesture::DoubleTap { within } => ChordState::Tapped {
+                binding: i,
+                deadline: now + within,
+            },
+            Gesture::Hold { after } => ChordState::Pressing {
+                binding: i,
+                deadline: now + after,
+            },
+        };
+        Some(Vec::new())
+    }
+
+    /// handle the key named `key` being let go
+    pub fn release(&mut self, key: &str, now: Instant) -> Vec<Signal> {
+        let binding = match self.chord {
+            ChordState::Pressing { binding, .. } | ChordState::Held { binding } => binding,
+            _ => return Vec::new(),
+        };
+        let releases = self.bindings[binding]
+            .key
+            .chord()
+            .is_some_and(|keys| keys.keys.iter().any(|k| k == key));
+        if !releases {
+            return Vec::new();
+        }
+
+        let mut signals = self.expire(now);
+        if let ChordState::Held { binding } = self.chord {
+            signals.extend(self.bindings[binding].release());
+        }
+        self.chord = ChordState::Idle;
+        signals
+    }
+
+    /// a key other than a modifier was pressed: the modifiers that are down
+    /// are part of a combination, not pressed on their own
+    pub fn interrupt(&mut self) {
+        if self.lone.is_some_and(|lone| !lone.held) {
+            self.lone = None;
+        }
+        self.last_tap = None;
+    }
+
+    /// handle a modifier key going down or up; these are never swallowed.
+    /// `active` is only asked about bindings that may run
+    pub fn modifier(
+        &mut self,
+        key: ModifierKey,
+        down: bool,
+        now: Instant,
+        mut active: impl FnMut(&AppScope) -> bool,
+    ) -> Vec<Signal> {
+        if down {
+            let alone = self.down.iter().all(|k| *k == key);
+            if !self.down.contains(&key) {
+                self.down.push(key);
+            }
+            if !alone {
+                self.interrupt();
+                return Vec::new();
+            }
+
+            let since_tap = self
+                .last_tap
+                .take()
+                .filter(|(tapped, _)| *tapped == key)
+                .map(|(_, at)| now.saturating_duration_since(at));
+            let hold = self.modifier_binding(&key, true, |_| true, &mut active);
+            self.lone = Some(Lone {
+                key,
+                since_tap,
+                hold: hold.and_then(|i| match self.bindings[i].gesture {
+                    Gesture::Hold { after } => Some((i, now + after)),
+                    Gesture::DoubleTap { .. } => None,
+                }),
+                held: false,
+            });
+            return Vec::new();
+        }
+
+        self.down.retain(|k| *k != key);
+        let Some(lone) = self.lone.take().filter(|lone| lone.key == key) else {
+            return Vec::new();
+        };
+
+        if let Some((binding, deadline)) = lone.hold {
+            if lone.held || now >= deadline {
+                let mut signals = Vec::new();
+                if !lone.held {
+                    signals.push(self.bindings[binding].run());
+                }
+                signals.extend(self.bindings[binding].release());
+                return signals;
+            }
+        }
+
+        // let go without another key: a tap
+        let double_tap = lone.since_tap.and_then(|since| {
+            self.modifier_binding(
+                &key,
+                false,
+                |gesture| matches!(gesture, Gesture::DoubleTap { within } if since <= *within),
+                &mut active,
+            )
+        });
+        match double_tap {
+            Some(binding) => vec![self.bindings[binding].run()],
+            None => {
+                self.last_tap = Some((key, now));
+                Vec::new()
+            }
+        }
+    }
+
+    /// the first binding for modifier `key` that is a hold (or not), passes
+    /// `gesture` and applies to the frontmost app
+    fn modifier_binding(
+        &self,
+        key: &ModifierKey,
+        hold: bool,
+        gesture: impl Fn(&Gesture) -> bool,
+        active: &mut impl FnMut(&AppScope) -> bool,
+    ) -> Option<usize> {
+        self.bindings.iter().position(|b| {
+            b.gesture.is_hold() == hold
+                && gesture(&b.gesture)
+                && b.key.modifier().is_some_and(|m| m.covers(key))
+                && active(&b.scope)
+        })
+    }
+
+    /// run holds whose keys have been down long enough
+    pub fn expire(&mut self, now: Instant) -> Vec<Signal> {
+        let mut signals = Vec::new();
+
+        match self.chord {
+            ChordState::Pressing { binding, deadline } if now >= deadline => {
+                signals.push(self.bindings[binding].run());
+                self.chord = ChordState::Held { binding };
+            }
+            ChordState::Tapped { deadline, .. } if now > deadline => {
+                self.chord = ChordState::Idle;
+            }
+            _ => {}
+        }
+
+        if let Some(lone) = self.lone.as_mut() {
+            if let Some((binding, deadline)) = lone.hold {
+                if !lone.held && now >= deadline {
+                    lone.held = true;
+                    signals.push(self.bindings[binding].run());
+                }
+            }
+        }
+
+        signals
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WINDOW: Duration = Duration::from_millis(300);
+    const HOLD: Duration = Duration::from_millis(500);
+
+    fn ms(n: u64) -> Duration {
+        Duration::from_millis(n)
+    }
+
+    fn chord(s: &str) -> Hotkey {
+        Hotkey::parse(s).unwrap()
+    }
+
+    fn modifier(s: &str) -> ModifierKey {
+        ModifierKey::parse(s).unwrap()
+    }
+
+    fn binding(keys: &str, gesture: Gesture, action: &str) -> TriggerBinding {
+        TriggerBinding {
+            key: TriggerKey::parse(keys).unwrap(),
+            gesture,
+            action: action.to_string(),
+            release_action: None,
+            scope: AppScope::default(),
+        }
+    }
+
+    fn double_tap(keys: &str, action: &str) -> TriggerBinding {
+        binding(keys, Gesture::DoubleTap { within: WINDOW }, action)
+    }
+
+    fn hold(keys: &str, action: &str, release: Option<&str>) -> TriggerBinding {
+        TriggerBinding {
+            release_action: release.map(str::to_string),
+            ..binding(keys, Gesture::Hold { after: HOLD }, action)
+        }
+    }
+
+    fn run(action: &str, keys: &str) -> Signal {
+        Signal::Run {
+            action: action.to_string(),
+            keys: keys.to_string(),
+            mode: None,
+        }
+    }
+
+    fn all(_: &AppScope) -> bool {
+        true
+    }
+
+    /// press and release a lone modifier
+    fn tap(t: &mut Triggers, key: &str, at: Instant) -> Vec<Signal> {
+        let key = modifier(key);
+        let mut signals = t.modifier(key, true, at, all);
+        signals.extend(t.modifier(key, false, at + ms(50), all));
+        signals
+    }
+
+    #[test]
+    fn test_parse_modifier_key() {
+        let right = Some(Side::Right);
+        assert_eq!(modifier("shift").side, None);
+        assert_eq!(modifier("rshift").side, right);
+        assert_eq!(modifier("right_shift").side, right);
+        assert_eq!(modifier("LCmd").side, Some(Side::Left));
+        assert_eq!(modifier("roption").kind, ModifierKind::Alt);
+        assert_eq!(modifier("control").kind, ModifierKind::Ctrl);
+        assert_eq!(modifier("right_command").to_string(), "rcmd");
+        assert!(ModifierKey::parse("r").is_none());
+        assert!(ModifierKey::parse("shift+a").is_none());
+
+        assert!(modifier("shift").covers(&modifier("lshift")));
+        assert!(modifier("rshift").covers(&modifier("rshift")));
+        assert!(!modifier("rshift").covers(&modifier("lshift")));
+        assert!(!modifier("shift").covers(&modifier("rctrl")));
+    }
+
+    #[test]
+    fn test_parse_trigger_key() {
+        assert_eq!(
+            TriggerKey::parse("rshift").unwrap(),
+            TriggerKey::Modifier(modifier("rshift"))
+        );
+        assert_eq!(
+            TriggerKey::parse("ctrl+alt+space").unwrap(),
+            TriggerKey::Chord(chord("ctrl+alt+space"))
+        );
+        assert!(TriggerKey::parse("ctrl+alt+w, h").is_err());
+        assert!(TriggerKey::parse("ctrl+shift").is_err());
+    }
+
+    #[test]
+    fn test_chord_double_tap() {
+        let mut t = Triggers::new(vec![double_tap("ctrl+alt+m", "maximize")]);
+        let now = Instant::now();
+
+        assert!(t.wants(&chord("ctrl+alt+m")));
+        assert!(!t.wants(&chord("ctrl+alt+n")));
+        assert_eq!(t.press(&chord("ctrl+alt+m"), now, all), Some(vec![]));
+        assert!(t.swallows_repeat(&chord("ctrl+alt+m")));
+        assert_eq!(
+            t.press(&chord("ctrl+alt+m"), now + ms(200), all),
+            Some(vec![run("maximize", "ctrl+alt+m (double_tap)")])
+        );
+        assert!(!t.swallows_repeat(&chord("ctrl+alt+m")));
+    }
+
+    #[test]
+    fn test_chord_double_tap_too_slow() {
+        let mut t = Triggers::new(vec![double_tap("ctrl+alt+m", "maximize")]);
+        let now = Instant::now();
+
+        t.press(&chord("ctrl+alt+m"), now, all);
+        // too late: this starts over as a first press
+        assert_eq!(
+            t.press(&chord("ctrl+alt+m"), now + ms(400), all),
+            Some(vec![])
+        );
+        assert_eq!(
+            t.press(&chord("ctrl+alt+m"), now + ms(500), all),
+            Some(vec![run("maximize", "ctrl+alt+m (double_tap)")])
+        );
+    }
+
+    #[test]
+    fn test_chord_double_tap_interrupted() {
+        let mut t = Triggers::new(vec![double_tap("ctrl+alt+m", "maximize")]);
+        let now = Instant::now();
+
+        t.press(&chord("ctrl+alt+m"), now, all);
+        assert_eq!(t.press(&chord("a"), now + ms(50), all), None);
+        assert_eq!(
+            t.press(&chord("ctrl+alt+m"), now + ms(100), all),
+            Some(vec![])
+        );
+    }
+
+    #[test]
+    fn test_chord_hold_and_release() {
+        let mut t = Triggers::new(vec![hold(
+            "ctrl+alt+space",
+            "layout:grid",
+            Some("layout:restore"),
+        )]);
+        let now = Instant::now();
+
+        assert_eq!(t.press(&chord("ctrl+alt+space"), now, all), Some(vec![]));
+        assert_eq!(t.deadline(), Some(now + HOLD));
+        assert!(t.swallows_repeat(&chord("ctrl+alt+space")));
+        assert_eq!(t.expire(now + ms(100)), vec![]);
+
+        assert_eq!(
+            t.expire(now + HOLD),
+            vec![run("layout:grid", "ctrl+alt+space (hold)")]
+        );
+        assert_eq!(t.deadline(), None);
+        // other keys go through while held
+        assert_eq!(t.press(&chord("ctrl+alt+left"), now + ms(600), all), None);
+        assert_eq!(t.release("left", now + ms(650)), vec![]);
+
+        assert_eq!(
+            t.release("space", now + ms(900)),
+            vec![run("layout:restore", "ctrl+alt+space (release)")]
+        );
+        assert!(!t.swallows_repeat(&chord("ctrl+alt+space")));
+    }
+
+    #[test]
+    fn test_chord_hold_released_early() {
+        let mut t = Triggers::new(vec![hold("ctrl+alt+space", "layout:grid", None)]);
+        let now = Instant::now();
+
+        t.press(&chord("ctrl+alt+space"), now, all);
+        assert_eq!(t.release("space", now + ms(200)), vec![]);
+        assert_eq!(t.expire(now + HOLD), vec![]);
+
+        // a release that arrives after the hold time but before the timer still runs it
+        t.press(&chord("ctrl+alt+space"), now + ms(1000), all);
+        assert_eq!(
+            t.release("space", now + ms(1600)),
+            vec![run("layout:grid", "ctrl+alt+space (hold)")]
+        );
+    }
+
+    #[test]
+    fn test_inactive_binding_passes() {
+        let mut t = Triggers::new(vec![double_tap("ctrl+alt+m", "maximize")]);
+        assert_eq!(
+            t.press(&chord("ctrl+alt+m"), Instant::now(), |_| false),
+            None
+        );
+    }
+
+    #[test]
+    fn test_modifier_double_tap() {
+        let mut t = Triggers::new(vec![double_tap("rshift", "focus:Terminal")]);
+        let now = Instant::now();
+
+        assert_eq!(tap(&mut t, "rshift", now), vec![]);
+        assert_eq!(
+            tap(&mut t, "rshift", now + ms(200)),
+            vec![run("focus:Terminal", "rshift (double_tap)")]
+        );
+        // a third tap starts over
+        assert_eq!(tap(&mut t, "rshift", now + ms(400)), vec![]);
+    }
+
+    #[test]
+    fn test_modifier_double_tap_needs_same_key() {
+        let mut t = Triggers::new(vec![double_tap("shift", "focus:Terminal")]);
+        let now = Instant::now();
+
+        tap(&mut t, "lshift", now);
+        assert_eq!(tap(&mut t, "rshift", now + ms(200)), vec![]);
+        assert_eq!(tap(&mut t, "lshift", now + ms(600)), vec![]);
+        assert_eq!(
+            tap(&mut t, "lshift", now + ms(900)),
+            vec![run("focus:Terminal", "shift (double_tap)")]
+        );
+    }
+
+    #[test]
+    fn test_modifier_used_in_combination_is_not_a_tap() {
+        let mut t = Triggers::new(vec![double_tap("rshift", "focus:Terminal")]);
+        let now = Instant::now();
+
+        // shift+a, then a tap
+        t.modifier(modifier("rshift"), true, now, all);
+        t.interrupt();
+        t.modifier(modifier("rshift"), false, now + ms(50), all);
+        assert_eq!(tap(&mut t, "rshift", now + ms(100)), vec![]);
+
+        // a tap, then shift held with another modifier
+        let rshift = modifier("rshift");
+        t.modifier(rshift, true, now + ms(200), all);
+        t.modifier(modifier("lcmd"), true, now + ms(220), all);
+        t.modifier(modifier("lcmd"), false, now + ms(240), all);
+        assert_eq!(t.modifier(rshift, false, now + ms(260), all), vec![]);
+    }
+
+    #[test]
+    fn test_modifier_hold() {
+        let mut t = Triggers::new(vec![
+            hold("rcmd", "layout:grid", Some("layout:restore")),
+            double_tap("rcmd", "maximize"),
+        ]);
+        let now = Instant::now();
+        let rcmd = modifier("rcmd");
+
+        assert_eq!(t.modifier(rcmd, true, now, all), vec![]);
+        assert_eq!(t.deadline(), Some(now + HOLD));
+        assert_eq!(
+            t.expire(now + HOLD),
+            vec![run("layout:grid", "rcmd (hold)")]
+        );
+        // keys pressed while held do not cancel the release action
+        t.interrupt();
+        assert_eq!(
+            t.modifier(rcmd, false, now + ms(800), all),
+            vec![run("layout:restore", "rcmd (release)")]
+        );
+
+        // short presses are taps
+        assert_eq!(tap(&mut t, "rcmd", now + ms(1000)), vec![]);
+        assert_eq!(
+            tap(&mut t, "rcmd", now + ms(1200)),
+            vec![run("maximize", "rcmd (double_tap)")]
+        );
+    }
+
+    #[test]
+    fn test_modifier_scope_asked_only_when_bound() {
+        let mut t = Triggers::new(vec![double_tap("rshift", "focus:Terminal")]);
+        let now = Instant::now();
+        let never = |_: &AppScope| -> bool { panic!("asked for an unbound key") };
+
+        t.modifier(modifier("lcmd"), true, now, never);
+        t.modifier(modifier("lcmd"), false, now + ms(50), never);
+        t.modifier(modifier("lcmd"), true, now + ms(100), never);
+        t.modifier(modifier("lcmd"), false, now + ms(150), never);
+
+        tap(&mut t, "rshift", now + ms(200));
+        let rshift = modifier("rshift");
+        t.modifier(rshift, true, now + ms(300), all);
+        assert_eq!(t.modifier(rshift, false, now + ms(350), |_| false), vec![]);
+    }
+}
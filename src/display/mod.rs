@@ -196,6 +196,17 @@ impl DisplayTarget {
     }
 }
 
+impl std::fmt::Display for DisplayTarget {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DisplayTarget::Next => write!(f, "next"),
+            DisplayTarget::Prev => write!(f, "prev"),
+            DisplayTarget::Index(index) => write!(f, "{}", index),
+            DisplayTarget::Alias(alias) => write!(f, "{}", alias),
+        }
+    }
+}
+
 /// Check if a string is a valid alias name (alphanumeric + underscore, starts with letter/underscore)
 pub fn is_valid_alias_name(s: &str) -> bool {
     if s.is_empty() {
@@ -302,7 +313,7 @@ pub fn resolve_alias<'a>(
 }
 
 /// Find a display by unique ID or name
-fn find_display_by_id_or_name<'a>(
+pub fn find_display_by_id_or_name<'a>(
     identifier: &str,
     displays: &'a [DisplayInfo],
 ) -> Option<&'a DisplayInfo> {
@@ -498,6 +509,14 @@ mod tests {
         assert_eq!(display.unique_id(), "display_456");
     }
 
+    #[test]
+    fn test_display_target_display_roundtrip() {
+        for input in ["next", "prev", "2", "office"] {
+            let target = DisplayTarget::parse(input).unwrap();
+            assert_eq!(target.to_string(), input);
+        }
+    }
+
     #[test]
     fn test_display_target_parse_next() {
         let target = DisplayTarget::parse("next").unwrap();
@@ -9,6 +9,7 @@ pub mod daemon;
 pub mod display;
 pub mod history;
 pub mod installer;
+pub mod remote;
 pub mod spotlight;
 pub mod version;
 pub mod window;
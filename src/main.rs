@@ -6,6 +6,7 @@ mod daemon;
 mod display;
 mod history;
 mod installer;
+mod remote;
 mod spotlight;
 mod version;
 mod window;
@@ -101,8 +102,8 @@ fn do_update_check() -> Result<()> {
         }
         eprintln!();
 
-        // update last check time
-        let mut config = config;
+        // update last check time in the main file only, so includes are not flattened into it
+        let mut config = config::load_main_with_override(None)?;
         config.settings.update.last_check = Some(Utc::now());
         config::save(&config)?;
     }
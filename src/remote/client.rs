@@ -0,0 +1,324 @@
+//! client side of remote control: known hosts, the client key and requests
+//!
+//! the client key is a random secret stored in ~/.cwm/remote/client.key. hosts
+//! only store its SHA-256 fingerprint. known hosts (~/.cwm/remote/hosts.json)
+//! map a name to an address and the pinned server certificate fingerprint
+
+use anyhow::{anyhow, Context, Result};
+use chrono::{DateTime, Utc};
+use native_tls::TlsStream;
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fs;
+use std::io::{BufRead, BufReader, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use crate::daemon::auth::constant_time_eq;
+
+use super::tls;
+
+const CLIENT_KEY_FILE: &str = "client.key";
+const HOSTS_FILE: &str = "hosts.json";
+
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// how long to wait for a response when the request has no deadline
+const RESPONSE_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// a paired remote host
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct KnownHost {
+    pub name: String,
+    pub address: String,
+    pub port: u16,
+    /// pinned SHA-256 fingerprint of the host's certificate
+    pub fingerprint: String,
+    pub paired_at: DateTime<Utc>,
+}
+
+/// persisted list of known hosts
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct KnownHosts {
+    #[serde(default)]
+    pub hosts: Vec<KnownHost>,
+}
+
+impl KnownHosts {
+    /// load known hosts, or an empty list if the file doesn't exist
+    pub fn load(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let content = fs::read_to_string(path)?;
+        serde_json::from_str(&content)
+            .map_err(|e| anyhow!("failed to parse {}: {}", path.display(), e))
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let content = serde_json::to_string_pretty(self)?;
+        super::write_private(path, content.as_bytes())
+    }
+
+    pub fn get(&self, name: &str) -> Option<&KnownHost> {
+        self.hosts
+            .iter()
+            .find(|h| h.name.eq_ignore_ascii_case(name))
+    }
+
+    /// add a host, replacing any existing host with the same name
+    pub fn add(&mut self, host: KnownHost) {
+        self.hosts
+            .retain(|h| !h.name.eq_ignore_ascii_case(&host.name));
+        self.hosts.push(host);
+    }
+
+    pub fn remove(&mut self, name: &str) -> Option<KnownHost> {
+        let index = self
+            .hosts
+            .iter()
+            .position(|h| h.name.eq_ignore_ascii_case(name))?;
+        Some(self.hosts.remove(index))
+    }
+
+    pub fn names(&self) -> Vec<String> {
+        self.hosts.iter().map(|h| h.name.clone()).collect()
+    }
+}
+
+/// get path to the known hosts file
+pub fn hosts_path() -> Result<PathBuf> {
+    Ok(super::remote_dir()?.join(HOSTS_FILE))
+}
+
+/// load the client key, creating it on first use
+pub fn load_or_create_client_key() -> Result<String> {
+    let path = super::remote_dir()?.join(CLIENT_KEY_FILE);
+
+    if path.exists() {
+        let key = fs::read_to_string(&path)
+            .with_context(|| format!("failed to read {}", path.display()))?;
+        let key = key.trim();
+        if !key.is_empty() {
+            return Ok(key.to_string());
+        }
+    }
+
+    let mut bytes = [0u8; 32];
+    rand::thread_rng().fill_bytes(&mut bytes);
+    let key = hex::encode(bytes);
+    super::write_private(&path, key.as_bytes())?;
+
+    Ok(key)
+}
+
+/// name this machine reports when pairing
+pub fn local_hostname() -> String {
+    let mut buf = [0u8; 256];
+    let ret = unsafe { libc::gethostname(buf.as_mut_ptr() as *mut libc::c_char, buf.len()) };
+    if ret != 0 {
+        return "unknown".to_string();
+    }
+
+    let len = buf.iter().position(|&b| b == 0).unwrap_or(buf.len());
+    let name = String::from_utf8_lossy(&buf[..len]).to_string();
+    // drop the domain, e.g. "laptop.local" -> "laptop"
+    name.split('.').next().unwrap_or(&name).to_string()
+}
+
+/// open a TLS connection, checking the server certificate against `pinned`
+///
+/// returns the stream and the server's certificate fingerprint
+fn connect(
+    address: &str,
+    port: u16,
+    pinned: Option<&str>,
+) -> Result<(TlsStream<TcpStream>, String)> {
+    let addr = (address, port)
+        .to_socket_addrs()
+        .with_context(|| format!("failed to resolve {}", address))?
+        .next()
+        .ok_or_else(|| anyhow!("failed to resolve {}", address))?;
+
+    let stream = TcpStream::connect_timeout(&addr, CONNECT_TIMEOUT)
+        .with_context(|| format!("failed to connect to {}:{}", address, port))?;
+    stream.set_read_timeout(Some(RESPONSE_TIMEOUT))?;
+    stream.set_write_timeout(Some(CONNECT_TIMEOUT))?;
+
+    let stream = tls::connector()?
+        .connect(address, stream)
+        .map_err(|e| anyhow!("TLS handshake with {}:{} failed: {}", address, port, e))?;
+
+    let fingerprint = tls::peer_fingerprint(&stream)?;
+    if let Some(pinned) = pinned {
+        if !constant_time_eq(pinned.as_bytes(), fingerprint.as_bytes()) {
+            return Err(anyhow!(
+                "certificate fingerprint of {}:{} changed (expected {}, got {}). \
+                 if the host was reinstalled, remove it and pair again",
+                address,
+                port,
+                tls::short_fingerprint(pinned),
+                tls::short_fingerprint(&fingerprint)
+            ));
+        }
+    }
+
+    Ok((stream, fingerprint))
+}
+
+/// send one request line and read the response, skipping notifications
+fn exchange(stream: &mut TlsStream<TcpStream>, request: &Value) -> Result<Value> {
+    let line = serde_json::to_string(request)?;
+    stream.write_all(line.as_bytes())?;
+    stream.write_all(b"\n")?;
+    stream.flush()?;
+
+    let mut reader = BufReader::new(stream);
+    loop {
+        let mut response = String::new();
+        if reader.read_line(&mut response)? == 0 {
+            return Err(anyhow!("connection closed by remote host"));
+        }
+
+        let value: Value = serde_json::from_str(response.trim())
+            .map_err(|e| anyhow!("invalid response from remote host: {}", e))?;
+        if value.get("method").is_none() {
+            return Ok(value);
+        }
+    }
+}
+
+/// redeem a pairing code on a remote host, returning its certificate fingerprint
+///
+/// nothing is sent unless the host's certificate matches `expected_fingerprint`
+/// (in full or its first 16 characters, as printed by `cwm remote pair`), so the
+/// code can't be redeemed by a host impersonating the real one
+pub fn pair(
+    address: &str,
+    port: u16,
+    code: &str,
+    client_name: &str,
+    client_key: &str,
+    expected_fingerprint: &str,
+) -> Result<String> {
+    let expected = expected_fingerprint.trim().replace(':', "").to_lowercase();
+    if expected.len() < 16 {
+        return Err(anyhow!(
+            "fingerprint '{}' is too short, use the one printed by 'cwm remote pair'",
+            expected_fingerprint
+        ));
+    }
+
+    let (mut stream, fingerprint) = connect(address, port, None)?;
+    if !fingerprint.starts_with(&expected) {
+        return Err(anyhow!(
+            "certificate fingerprint of {}:{} is {}, expected {}",
+            address,
+            port,
+            tls::short_fingerprint(&fingerprint),
+            expected
+        ));
+    }
+
+    let response = exchange(
+        &mut stream,
+        &serde_json::json!({
+            "jsonrpc": "2.0",
+            "method": "pair",
+            "params": { "code": code, "name": client_name },
+            "id": "pair",
+            "client_key": client_key,
+        }),
+    )?;
+
+    if let Some(error) = response.get("error") {
+        let message = error
+            .get("message")
+            .and_then(|m| m.as_str())
+            .unwrap_or("pairing failed");
+        return Err(anyhow!("{}", message));
+    }
+
+    Ok(fingerprint)
+}
+
+/// send a JSON-RPC request to a paired host and return the raw response object
+pub fn request(host: &KnownHost, client_key: &str, mut request: Value) -> Result<Value> {
+    let (mut stream, _) = connect(&host.address, host.port, Some(&host.fingerprint))?;
+
+    if let Some(ms) = request.get("deadline_ms").and_then(|v| v.as_u64()) {
+        // leave room for the remote side to report the deadline itself
+        let timeout = Duration::from_millis(ms) + CONNECT_TIMEOUT;
+        stream.get_ref().set_read_timeout(Some(timeout))?;
+    }
+
+    request["client_key"] = Value::String(client_key.to_string());
+    exchange(&mut stream, &request)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn host(name: &str) -> KnownHost {
+        KnownHost {
+            name: name.to_string(),
+            address: "10.0.0.2".to_string(),
+            port: 7878,
+            fingerprint: "ab".repeat(32),
+            paired_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_known_hosts_add_get_remove() {
+        let mut hosts = KnownHosts::default();
+        hosts.add(host("lab-3"));
+        hosts.add(host("studio"));
+
+        assert!(hosts.get("lab-3").is_some());
+        assert!(hosts.get("LAB-3").is_some());
+        assert!(hosts.get("missing").is_none());
+
+        // re-adding replaces the existing entry
+        let mut moved = host("lab-3");
+        moved.address = "10.0.0.9".to_string();
+        hosts.add(moved);
+        assert_eq!(hosts.hosts.len(), 2);
+        assert_eq!(hosts.get("lab-3").unwrap().address, "10.0.0.9");
+
+        assert!(hosts.remove("lab-3").is_some());
+        assert!(hosts.remove("lab-3").is_none());
+        assert_eq!(hosts.names(), vec!["studio"]);
+    }
+
+    #[test]
+    fn test_known_hosts_save_and_load() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join(HOSTS_FILE);
+
+        let mut hosts = KnownHosts::default();
+        hosts.add(host("lab-3"));
+        hosts.save(&path).unwrap();
+
+        let loaded = KnownHosts::load(&path).unwrap();
+        assert_eq!(loaded.hosts, hosts.hosts);
+    }
+
+    #[test]
+    fn test_pair_needs_a_fingerprint() {
+        // rejected before connecting
+        let err = pair("192.0.2.1", 7878, "123456", "laptop", "key", "3f:9a").unwrap_err();
+        assert!(err.to_string().contains("too short"));
+    }
+
+    #[test]
+    fn test_local_hostname_has_no_domain() {
+        let name = local_hostname();
+        assert!(!name.is_empty());
+        assert!(!name.contains('.'));
+    }
+}
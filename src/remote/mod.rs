@@ -0,0 +1,52 @@
+//! remote control over TCP with TLS
+//!
+//! the daemon can optionally listen on a TCP port (`settings.remote`) and accept
+//! the same JSON-RPC protocol as the Unix socket. connections are encrypted with
+//! a self-signed certificate generated on first run, and only paired clients may
+//! send requests:
+//!
+//! 1. on the controlled machine, `cwm remote pair` prints a one-time code
+//! 2. on the controlling machine, `cwm remote add <name> <address> --code <code>`
+//!    pins the server certificate and registers the client's key fingerprint
+//! 3. `cwm --host <name> focus --app Safari` then runs on the remote machine
+
+pub mod client;
+pub mod pairing;
+pub mod server;
+pub mod tls;
+
+use anyhow::Result;
+use std::fs;
+use std::path::PathBuf;
+
+use crate::config;
+
+/// get the remote state directory (~/.cwm/remote), creating it if needed
+pub fn remote_dir() -> Result<PathBuf> {
+    let dir = config::ensure_cwm_dir()?.join("remote");
+    if !dir.exists() {
+        fs::create_dir_all(&dir)?;
+        set_private(&dir, 0o700)?;
+    }
+    Ok(dir)
+}
+
+/// restrict a file or directory to the current user
+pub(crate) fn set_private(path: &std::path::Path, mode: u32) -> Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+    fs::set_permissions(path, fs::Permissions::from_mode(mode))?;
+    Ok(())
+}
+
+/// write a file atomically (write to temp, then rename) with owner-only permissions
+pub(crate) fn write_private(path: &std::path::Path, content: &[u8]) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    let temp_path = path.with_extension("tmp");
+    fs::write(&temp_path, content)?;
+    set_private(&temp_path, 0o600)?;
+    fs::rename(&temp_path, path)?;
+    Ok(())
+}
@@ -0,0 +1,298 @@
+//! pairing codes and the paired client store
+//!
+//! a pairing code is a one-time 6-digit code shown by `cwm remote pair`. a
+//! client redeems it once to register the fingerprint of its key; the code is
+//! discarded after use, expiry or too many wrong guesses
+
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Utc};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use crate::daemon::auth::constant_time_eq;
+
+/// how long a pairing code stays valid
+pub const CODE_TTL: Duration = Duration::from_secs(300);
+
+/// wrong guesses allowed before a code is discarded
+const MAX_ATTEMPTS: u32 = 5;
+
+const CLIENTS_FILE: &str = "clients.json";
+
+/// a one-time pairing code
+#[derive(Debug, Clone)]
+pub struct PairingCode {
+    code: String,
+    expires_at: Instant,
+    attempts: u32,
+}
+
+/// why a pairing attempt was rejected
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PairingError {
+    /// no code is active (never created, already used or discarded)
+    NoActiveCode,
+    Expired,
+    WrongCode,
+}
+
+impl std::fmt::Display for PairingError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::NoActiveCode => write!(f, "no pairing in progress, run 'cwm remote pair'"),
+            Self::Expired => write!(f, "pairing code expired, run 'cwm remote pair' again"),
+            Self::WrongCode => write!(f, "invalid pairing code"),
+        }
+    }
+}
+
+impl PairingCode {
+    /// generate a random 6-digit code valid for `ttl`
+    pub fn generate(ttl: Duration) -> Self {
+        let code = rand::thread_rng().gen_range(0..1_000_000);
+        Self {
+            code: format!("{:06}", code),
+            expires_at: Instant::now() + ttl,
+            attempts: 0,
+        }
+    }
+
+    pub fn code(&self) -> &str {
+        &self.code
+    }
+
+    /// seconds until the code expires
+    pub fn expires_in(&self) -> u64 {
+        self.expires_at
+            .saturating_duration_since(Instant::now())
+            .as_secs()
+    }
+}
+
+/// the pairing code slot held by the server
+#[derive(Debug, Default)]
+pub struct PairingSlot {
+    active: Option<PairingCode>,
+}
+
+impl PairingSlot {
+    /// start pairing, replacing any previous code
+    pub fn start(&mut self, ttl: Duration) -> PairingCode {
+        let code = PairingCode::generate(ttl);
+        self.active = Some(code.clone());
+        code
+    }
+
+    /// redeem a code; succeeds at most once per code
+    pub fn redeem(&mut self, candidate: &str) -> Result<(), PairingError> {
+        let active = self.active.as_mut().ok_or(PairingError::NoActiveCode)?;
+
+        if Instant::now() >= active.expires_at {
+            self.active = None;
+            return Err(PairingError::Expired);
+        }
+
+        if constant_time_eq(active.code.as_bytes(), candidate.trim().as_bytes()) {
+            self.active = None;
+            return Ok(());
+        }
+
+        active.attempts += 1;
+        if active.attempts >= MAX_ATTEMPTS {
+            self.active = None;
+        }
+        Err(PairingError::WrongCode)
+    }
+
+    #[cfg(test)]
+    pub fn is_active(&self) -> bool {
+        self.active.is_some()
+    }
+}
+
+/// fingerprint stored for a client key (the key itself never leaves the client's disk
+/// except over TLS)
+pub fn client_fingerprint(client_key: &str) -> String {
+    hex::encode(Sha256::digest(client_key.as_bytes()))
+}
+
+/// a client allowed to send requests
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PairedClient {
+    pub name: String,
+    pub fingerprint: String,
+    pub paired_at: DateTime<Utc>,
+}
+
+/// persisted list of paired clients (~/.cwm/remote/clients.json)
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PairedClients {
+    #[serde(default)]
+    pub clients: Vec<PairedClient>,
+}
+
+impl PairedClients {
+    /// load paired clients, or an empty list if the file doesn't exist
+    pub fn load(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let content = fs::read_to_string(path)?;
+        serde_json::from_str(&content)
+            .map_err(|e| anyhow!("failed to parse {}: {}", path.display(), e))
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let content = serde_json::to_string_pretty(self)?;
+        super::write_private(path, content.as_bytes())
+    }
+
+    /// add a client, replacing any entry with the same fingerprint
+    pub fn add(&mut self, name: &str, fingerprint: &str) {
+        self.clients.retain(|c| c.fingerprint != fingerprint);
+        self.clients.push(PairedClient {
+            name: name.to_string(),
+            fingerprint: fingerprint.to_string(),
+            paired_at: Utc::now(),
+        });
+    }
+
+    /// remove clients whose name or fingerprint (or fingerprint prefix) matches
+    pub fn revoke(&mut self, name_or_fingerprint: &str) -> Vec<PairedClient> {
+        let (revoked, kept) = self.clients.drain(..).partition(|c| {
+            c.name == name_or_fingerprint
+                || (name_or_fingerprint.len() >= 8
+                    && c.fingerprint.starts_with(name_or_fingerprint))
+        });
+        self.clients = kept;
+        revoked
+    }
+
+    pub fn contains(&self, fingerprint: &str) -> bool {
+        self.clients
+            .iter()
+            .any(|c| constant_time_eq(c.fingerprint.as_bytes(), fingerprint.as_bytes()))
+    }
+}
+
+/// get path to the paired clients file
+pub fn clients_path() -> Result<PathBuf> {
+    Ok(super::remote_dir()?.join(CLIENTS_FILE))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_code_is_six_digits() {
+        let code = PairingCode::generate(CODE_TTL);
+        assert_eq!(code.code().len(), 6);
+        assert!(code.code().chars().all(|c| c.is_ascii_digit()));
+        assert!(code.expires_in() > 290);
+    }
+
+    #[test]
+    fn test_redeem_once() {
+        let mut slot = PairingSlot::default();
+        let code = slot.start(CODE_TTL);
+
+        assert!(slot.redeem(code.code()).is_ok());
+        assert_eq!(slot.redeem(code.code()), Err(PairingError::NoActiveCode));
+    }
+
+    #[test]
+    fn test_redeem_without_pairing() {
+        let mut slot = PairingSlot::default();
+        assert_eq!(slot.redeem("123456"), Err(PairingError::NoActiveCode));
+    }
+
+    #[test]
+    fn test_redeem_expired() {
+        let mut slot = PairingSlot::default();
+        let code = slot.start(Duration::ZERO);
+        assert_eq!(slot.redeem(code.code()), Err(PairingError::Expired));
+        assert!(!slot.is_active());
+    }
+
+    #[test]
+    fn test_wrong_code_discarded_after_max_attempts() {
+        let mut slot = PairingSlot::default();
+        let code = slot.start(CODE_TTL);
+        let wrong = if code.code() == "000000" {
+            "111111"
+        } else {
+            "000000"
+        };
+
+        for _ in 0..MAX_ATTEMPTS {
+            assert_eq!(slot.redeem(wrong), Err(PairingError::WrongCode));
+        }
+        assert_eq!(slot.redeem(code.code()), Err(PairingError::NoActiveCode));
+    }
+
+    #[test]
+    fn test_new_pairing_replaces_old_code() {
+        let mut slot = PairingSlot::default();
+        let first = slot.start(CODE_TTL);
+        let second = slot.start(CODE_TTL);
+
+        if first.code() != second.code() {
+            assert_eq!(slot.redeem(first.code()), Err(PairingError::WrongCode));
+        }
+        assert!(slot.redeem(second.code()).is_ok());
+    }
+
+    #[test]
+    fn test_client_fingerprint() {
+        let fp = client_fingerprint("secret");
+        assert_eq!(fp.len(), 64);
+        assert_eq!(fp, client_fingerprint("secret"));
+        assert_ne!(fp, client_fingerprint("other"));
+    }
+
+    #[test]
+    fn test_paired_clients_add_and_revoke() {
+        let mut clients = PairedClients::default();
+        clients.add("laptop", "aaaaaaaaaaaaaaaa");
+        clients.add("desktop", "bbbbbbbbbbbbbbbb");
+        assert!(clients.contains("aaaaaaaaaaaaaaaa"));
+
+        // re-pairing the same key replaces the entry
+        clients.add("laptop-renamed", "aaaaaaaaaaaaaaaa");
+        assert_eq!(clients.clients.len(), 2);
+
+        let revoked = clients.revoke("laptop-renamed");
+        assert_eq!(revoked.len(), 1);
+        assert!(!clients.contains("aaaaaaaaaaaaaaaa"));
+
+        // fingerprint prefixes need at least 8 characters
+        assert!(clients.revoke("bbbb").is_empty());
+        assert_eq!(clients.revoke("bbbbbbbb").len(), 1);
+        assert!(clients.clients.is_empty());
+    }
+
+    #[test]
+    fn test_paired_clients_save_and_load() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join(CLIENTS_FILE);
+
+        assert!(PairedClients::load(&path).unwrap().clients.is_empty());
+
+        let mut clients = PairedClients::default();
+        clients.add("laptop", "abc123");
+        clients.save(&path).unwrap();
+
+        let loaded = PairedClients::load(&path).unwrap();
+        assert_eq!(loaded.clients, clients.clients);
+
+        use std::os::unix::fs::PermissionsExt;
+        let mode = fs::metadata(&path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+    }
+}
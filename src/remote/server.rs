@@ -0,0 +1,425 @@
+//! TLS listener for remote JSON-RPC requests
+//!
+//! each connection carries one request line, like the Unix socket. the line is
+//! a normal IPC request plus a top-level `client_key`; the key's fingerprint must
+//! belong to a paired client. the `pair` method is the only one accepted from
+//! unpaired clients
+
+use anyhow::Result;
+use native_tls::TlsAcceptor;
+use serde_json::Value;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use crate::cli::exit_codes;
+use crate::cli::output::{JsonRpcError, JsonRpcResponse};
+
+use super::pairing::{client_fingerprint, PairedClients, PairingCode, PairingSlot, CODE_TTL};
+
+/// largest request line accepted from a remote client
+const MAX_REQUEST_BYTES: u64 = 64 * 1024;
+
+/// how long a connection may stay idle before it is dropped
+const IO_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// handles an authenticated request line from the client with the given key
+/// fingerprint, returning the response line (None for notifications)
+pub type RequestHandler = dyn Fn(&str, &str) -> Option<String> + Send + Sync;
+
+/// pairing and client state shared by all connections
+pub struct ServerState {
+    fingerprint: String,
+    clients_path: PathBuf,
+    pairing: Mutex<PairingSlot>,
+}
+
+impl ServerState {
+    pub fn new(fingerprint: String, clients_path: PathBuf) -> Self {
+        Self {
+            fingerprint,
+            clients_path,
+            pairing: Mutex::new(PairingSlot::default()),
+        }
+    }
+
+    /// fingerprint of the server certificate
+    pub fn fingerprint(&self) -> &str {
+        &self.fingerprint
+    }
+
+    /// create a new one-time pairing code, replacing any previous one
+    pub fn start_pairing(&self) -> PairingCode {
+        let mut slot = self.pairing.lock().unwrap_or_else(|e| e.into_inner());
+        slot.start(CODE_TTL)
+    }
+
+    /// redeem a pairing code and register the client's key fingerprint
+    fn pair(&self, code: &str, name: &str, client_key: &str) -> Result<String, String> {
+        {
+            let mut slot = self.pairing.lock().unwrap_or_else(|e| e.into_inner());
+            slot.redeem(code).map_err(|e| e.to_string())?;
+        }
+
+        let fingerprint = client_fingerprint(client_key);
+        let mut clients = PairedClients::load(&self.clients_path).map_err(|e| e.to_string())?;
+        clients.add(name, &fingerprint);
+        clients
+            .save(&self.clients_path)
+            .map_err(|e| format!("failed to save paired clients: {}", e))?;
+
+        Ok(fingerprint)
+    }
+
+    /// check a client key against the paired clients file
+    ///
+    /// the file is re-read on every request so `cwm remote revoke` applies
+    /// without restarting the daemon
+    fn is_paired(&self, client_key: &str) -> bool {
+        PairedClients::load(&self.clients_path)
+            .map(|clients| clients.contains(&client_fingerprint(client_key)))
+            .unwrap_or(false)
+    }
+}
+
+/// accept TLS connections until `should_stop` returns true
+pub fn serve(
+    listener: TcpListener,
+    acceptor: Arc<TlsAcceptor>,
+    state: Arc<ServerState>,
+    handler: Arc<RequestHandler>,
+    should_stop: &dyn Fn() -> bool,
+) -> Result<()> {
+    // non-blocking so the stop flag is checked regularly
+    listener.set_nonblocking(true)?;
+
+    while !should_stop() {
+        match listener.accept() {
+            Ok((stream, _)) => {
+                let acceptor = Arc::clone(&acceptor);
+                let state = Arc::clone(&state);
+                let handler = Arc::clone(&handler);
+                std::thread::spawn(move || {
+                    // failed handshakes and dropped clients only affect that connection
+                    let _ = handle_connection(stream, &acceptor, &state, handler.as_ref());
+                });
+            }
+            Err(ref e) if e.kind() == std::io::ErrorKind::WouldBlock => {
+                std::thread::sleep(Duration::from_millis(100));
+            }
+            Err(e) => return Err(e.into()),
+        }
+    }
+
+    Ok(())
+}
+
+fn handle_connection(
+    stream: TcpStream,
+    acceptor: &TlsAcceptor,
+    state: &ServerState,
+    handler: &RequestHandler,
+) -> Result<()> {
+    stream.set_nonblocking(false)?;
+    stream.set_read_timeout(Some(IO_TIMEOUT))?;
+    stream.set_write_timeout(Some(IO_TIMEOUT))?;
+
+    let mut tls = acceptor.accept(stream)?;
+
+    let mut line = String::new();
+    BufReader::new((&mut tls).take(MAX_REQUEST_BYTES)).read_line(&mut line)?;
+
+    // the request may run past the idle timeout (e.g. kill --wait)
+    tls.get_ref().set_read_timeout(None)?;
+
+    let line = line.trim();
+    if line.is_empty() {
+        return Ok(());
+    }
+
+    if let Some(response) = process_request(state, line, handler) {
+        tls.write_all(response.as_bytes())?;
+        tls.write_all(b"\n")?;
+        tls.flush()?;
+    }
+    let _ = tls.shutdown();
+
+    Ok(())
+}
+
+/// authenticate a request line and dispatch it
+fn process_request(state: &ServerState, line: &str, handler: &RequestHandler) -> Option<String> {
+    let mut request = match serde_json::from_str::<Value>(line) {
+        Ok(Value::Object(map)) => map,
+        _ => {
+            return error_response(
+                None,
+                exit_codes::INVALID_ARGS,
+                "Invalid request: expected a JSON-RPC object",
+            )
+        }
+    };
+
+    let id = request.get("id").filter(|id| !id.is_null()).map(id_string);
+    let client_key = request
+        .remove("client_key")
+        .and_then(|k| k.as_str().map(str::to_string))
+        .filter(|k| !k.is_empty());
+    let method = request
+        .get("method")
+        .and_then(|m| m.as_str())
+        .unwrap_or("")
+        .to_string();
+
+    if method == "pair" {
+        let params = request.get("params").cloned().unwrap_or(Value::Null);
+        let code = params.get("code").and_then(|v| v.as_str()).unwrap_or("");
+        let name = params
+            .get("name")
+            .and_then(|v| v.as_str())
+            .filter(|n| !n.is_empty())
+            .unwrap_or("unnamed");
+
+        let Some(client_key) = client_key else {
+            return error_response(id, exit_codes::INVALID_ARGS, "pair requires a client key");
+        };
+
+        return match state.pair(code, name, &client_key) {
+            Ok(fingerprint) => success_response(
+                id,
+                serde_json::json!({ "paired": true, "name": name, "fingerprint": fingerprint }),
+            ),
+            Err(msg) => error_response(id, exit_codes::ACCESS_DENIED, &msg),
+        };
+    }
+
+    let client = match client_key {
+        Some(key) if state.is_paired(&key) => client_fingerprint(&key),
+        _ => {
+            return error_response(
+                id,
+                exit_codes::ACCESS_DENIED,
+                "access denied: this client is not paired with the host, run 'cwm remote add'",
+            )
+        }
+    };
+
+    handler(&Value::Object(request).to_string(), &client)
+}
+
+fn id_string(id: &Value) -> String {
+    match id {
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+fn success_response(id: Option<String>, result: Value) -> Option<String> {
+    let response = JsonRpcResponse::with_id(result, id?);
+    serde_json::to_string(&response).ok()
+}
+
+/// errors are always sent, even without an id, so the client isn't left waiting
+fn error_response(id: Option<String>, code: i32, message: &str) -> Option<String> {
+    let mut error = JsonRpcError::new(code, message);
+    error.id = id;
+    serde_json::to_string(&error).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::remote::client::{self, KnownHost};
+    use crate::remote::tls;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use tempfile::tempdir;
+
+    fn echo_handler() -> Arc<RequestHandler> {
+        Arc::new(|line: &str, _client: &str| {
+            let request: Value = serde_json::from_str(line).unwrap();
+            let id = request.get("id").map(id_string)?;
+            success_response(
+                Some(id),
+                serde_json::json!({
+                    "method": request["method"],
+                    "has_client_key": request.get("client_key").is_some(),
+                }),
+            )
+        })
+    }
+
+    fn state_in(dir: &std::path::Path) -> ServerState {
+        ServerState::new("server-fp".to_string(), dir.join("clients.json"))
+    }
+
+    #[test]
+    fn test_unpaired_client_denied() {
+        let dir = tempdir().unwrap();
+        let state = state_in(dir.path());
+        let handler = echo_handler();
+
+        let response = process_request(
+            &state,
+            r#"{"method":"ping","id":1,"client_key":"k"}"#,
+            handler.as_ref(),
+        )
+        .unwrap();
+        let value: Value = serde_json::from_str(&response).unwrap();
+        assert!(value["error"]["message"]
+            .as_str()
+            .unwrap()
+            .contains("not paired"));
+    }
+
+    #[test]
+    fn test_pair_then_request() {
+        let dir = tempdir().unwrap();
+        let state = state_in(dir.path());
+        let handler = echo_handler();
+        let code = state.start_pairing();
+
+        let pair = format!(
+            r#"{{"method":"pair","params":{{"code":"{}","name":"laptop"}},"id":1,"client_key":"k"}}"#,
+            code.code()
+        );
+        let response = process_request(&state, &pair, handler.as_ref()).unwrap();
+        let value: Value = serde_json::from_str(&response).unwrap();
+        assert_eq!(value["result"]["paired"], true);
+        assert_eq!(value["result"]["fingerprint"], client_fingerprint("k"));
+
+        // the client key is stripped before the request reaches the handler
+        let response = process_request(
+            &state,
+            r#"{"method":"ping","id":2,"client_key":"k"}"#,
+            handler.as_ref(),
+        )
+        .unwrap();
+        let value: Value = serde_json::from_str(&response).unwrap();
+        assert_eq!(value["result"]["method"], "ping");
+        assert_eq!(value["result"]["has_client_key"], false);
+
+        // other keys are still rejected
+        let response = process_request(
+            &state,
+            r#"{"method":"ping","id":3,"client_key":"other"}"#,
+            handler.as_ref(),
+        )
+        .unwrap();
+        assert!(response.contains("not paired"));
+    }
+
+    #[test]
+    fn test_pair_with_wrong_code() {
+        let dir = tempdir().unwrap();
+        let state = state_in(dir.path());
+        let code = state.start_pairing();
+        let wrong = if code.code() == "000000" {
+            "111111"
+        } else {
+            "000000"
+        };
+
+        let pair = format!(
+            r#"{{"method":"pair","params":{{"code":"{}"}},"id":1,"client_key":"k"}}"#,
+            wrong
+        );
+        let response = process_request(&state, &pair, echo_handler().as_ref()).unwrap();
+        assert!(response.contains("invalid pairing code"));
+        assert!(!state.is_paired("k"));
+    }
+
+    #[test]
+    fn test_invalid_request() {
+        let dir = tempdir().unwrap();
+        let state = state_in(dir.path());
+        let response = process_request(&state, "[1,2]", echo_handler().as_ref()).unwrap();
+        assert!(response.contains("expected a JSON-RPC object"));
+    }
+
+    #[test]
+    fn test_loopback_tls_pairing() {
+        let dir = tempdir().unwrap();
+        let identity = match tls::load_or_create_identity(dir.path()) {
+            Ok(identity) => identity,
+            Err(e) => {
+                eprintln!("skipping loopback test: {}", e);
+                return;
+            }
+        };
+
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let acceptor = Arc::new(tls::acceptor(&identity).unwrap());
+        let state = Arc::new(ServerState::new(
+            identity.fingerprint.clone(),
+            dir.path().join("clients.json"),
+        ));
+        let stop = Arc::new(AtomicBool::new(false));
+
+        let server = {
+            let state = Arc::clone(&state);
+            let stop = Arc::clone(&stop);
+            std::thread::spawn(move || {
+                serve(listener, acceptor, state, echo_handler(), &|| {
+                    stop.load(Ordering::SeqCst)
+                })
+            })
+        };
+
+        let host = KnownHost {
+            name: "loopback".to_string(),
+            address: "127.0.0.1".to_string(),
+            port,
+            fingerprint: identity.fingerprint.clone(),
+            paired_at: chrono::Utc::now(),
+        };
+        let ping = serde_json::json!({"method": "ping", "id": "1"});
+
+        // not paired yet
+        let response = client::request(&host, "client-key", ping.clone()).unwrap();
+        assert!(response["error"]["message"]
+            .as_str()
+            .unwrap()
+            .contains("not paired"));
+
+        // a wrong expected fingerprint stops pairing before the code is sent
+        let code = state.start_pairing();
+        let err = client::pair(
+            "127.0.0.1",
+            port,
+            code.code(),
+            "test-client",
+            "client-key",
+            &"0".repeat(16),
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("fingerprint"));
+
+        // pair with the one-time code, checking the printed fingerprint prefix
+        let paired = client::pair(
+            "127.0.0.1",
+            port,
+            code.code(),
+            "test-client",
+            "client-key",
+            tls::short_fingerprint(&identity.fingerprint),
+        )
+        .unwrap();
+        assert_eq!(paired, identity.fingerprint);
+
+        let response = client::request(&host, "client-key", ping.clone()).unwrap();
+        assert_eq!(response["result"]["method"], "ping");
+        assert_eq!(response["id"], "1");
+
+        // a different pinned fingerprint is refused before anything is sent
+        let mut spoofed = host.clone();
+        spoofed.fingerprint = "0".repeat(64);
+        let err = client::request(&spoofed, "client-key", ping).unwrap_err();
+        assert!(err.to_string().contains("fingerprint"));
+
+        stop.store(true, Ordering::SeqCst);
+        server.join().unwrap().unwrap();
+    }
+}
@@ -0,0 +1,164 @@
+//! TLS setup for the remote listener
+//!
+//! the server certificate is self-signed and generated with the `openssl` CLI on
+//! first run (macOS ships one in /usr/bin). clients don't verify it against a CA;
+//! instead they pin its SHA-256 fingerprint when pairing
+
+use anyhow::{anyhow, Context, Result};
+use native_tls::{Certificate, Identity, TlsAcceptor, TlsConnector, TlsStream};
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::net::TcpStream;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+const CERT_FILE: &str = "cert.pem";
+const KEY_FILE: &str = "key.pem";
+
+/// validity of the generated certificate
+const CERT_DAYS: &str = "3650";
+
+/// the server's certificate and key, plus the fingerprint clients pin
+pub struct ServerIdentity {
+    pub identity: Identity,
+    pub fingerprint: String,
+    pub cert_path: PathBuf,
+}
+
+/// load the server certificate from `dir`, generating it on first run
+pub fn load_or_create_identity(dir: &Path) -> Result<ServerIdentity> {
+    let cert_path = dir.join(CERT_FILE);
+    let key_path = dir.join(KEY_FILE);
+
+    if !cert_path.exists() || !key_path.exists() {
+        generate_certificate(&cert_path, &key_path)?;
+    }
+
+    let cert_pem =
+        fs::read(&cert_path).with_context(|| format!("failed to read {}", cert_path.display()))?;
+    let key_pem =
+        fs::read(&key_path).with_context(|| format!("failed to read {}", key_path.display()))?;
+
+    let identity = Identity::from_pkcs8(&cert_pem, &key_pem)
+        .map_err(|e| anyhow!("invalid TLS certificate or key: {}", e))?;
+    let der = Certificate::from_pem(&cert_pem)
+        .and_then(|c| c.to_der())
+        .map_err(|e| anyhow!("invalid TLS certificate: {}", e))?;
+
+    Ok(ServerIdentity {
+        identity,
+        fingerprint: fingerprint(&der),
+        cert_path,
+    })
+}
+
+/// generate a self-signed RSA certificate and PKCS#8 key
+fn generate_certificate(cert_path: &Path, key_path: &Path) -> Result<()> {
+    run_openssl(&[
+        "genpkey",
+        "-algorithm",
+        "RSA",
+        "-pkeyopt",
+        "rsa_keygen_bits:2048",
+        "-out",
+        &key_path.to_string_lossy(),
+    ])?;
+    super::set_private(key_path, 0o600)?;
+
+    run_openssl(&[
+        "req",
+        "-new",
+        "-x509",
+        "-key",
+        &key_path.to_string_lossy(),
+        "-out",
+        &cert_path.to_string_lossy(),
+        "-days",
+        CERT_DAYS,
+        "-subj",
+        "/CN=cwm",
+    ])?;
+
+    Ok(())
+}
+
+fn run_openssl(args: &[&str]) -> Result<()> {
+    let output = Command::new("openssl").args(args).output().map_err(|e| {
+        anyhow!(
+            "failed to run openssl (needed to create the TLS certificate): {}",
+            e
+        )
+    })?;
+
+    if !output.status.success() {
+        return Err(anyhow!(
+            "openssl {} failed: {}",
+            args[0],
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+
+    Ok(())
+}
+
+/// SHA-256 fingerprint of a DER-encoded certificate, as lowercase hex
+pub fn fingerprint(der: &[u8]) -> String {
+    hex::encode(Sha256::digest(der))
+}
+
+/// shorten a fingerprint for display
+pub fn short_fingerprint(fingerprint: &str) -> &str {
+    &fingerprint[..fingerprint.len().min(16)]
+}
+
+pub fn acceptor(identity: &ServerIdentity) -> Result<TlsAcceptor> {
+    TlsAcceptor::new(identity.identity.clone())
+        .map_err(|e| anyhow!("failed to create TLS acceptor: {}", e))
+}
+
+/// build a connector for a self-signed server
+///
+/// certificate validation is disabled because trust comes from the pinned
+/// fingerprint, which callers must check with `peer_fingerprint`
+pub fn connector() -> Result<TlsConnector> {
+    TlsConnector::builder()
+        .danger_accept_invalid_certs(true)
+        .danger_accept_invalid_hostnames(true)
+        .build()
+        .map_err(|e| anyhow!("failed to create TLS connector: {}", e))
+}
+
+/// fingerprint of the certificate presented by the other end of a connection
+pub fn peer_fingerprint(stream: &TlsStream<TcpStream>) -> Result<String> {
+    let cert = stream
+        .peer_certificate()
+        .map_err(|e| anyhow!("failed to read server certificate: {}", e))?
+        .ok_or_else(|| anyhow!("server did not present a certificate"))?;
+    let der = cert
+        .to_der()
+        .map_err(|e| anyhow!("invalid server certificate: {}", e))?;
+    Ok(fingerprint(&der))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fingerprint_is_sha256_hex() {
+        let fp = fingerprint(b"certificate");
+        assert_eq!(fp.len(), 64);
+        assert!(fp.chars().all(|c| c.is_ascii_hexdigit()));
+        assert_eq!(fp, fingerprint(b"certificate"));
+        assert_ne!(fp, fingerprint(b"other"));
+    }
+
+    #[test]
+    fn test_short_fingerprint() {
+        assert_eq!(
+            short_fingerprint("0123456789abcdef0123"),
+            "0123456789abcdef"
+        );
+        assert_eq!(short_fingerprint("abc"), "abc");
+    }
+}
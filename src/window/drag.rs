@@ -0,0 +1,267 @@
+//! geometry of modifier + drag window moves and resizes
+//!
+//! a drag starts on a window and follows the cursor: a move shifts the whole
+//! window, a resize moves the edges nearest to where the drag started. the
+//! window is split into thirds on each axis, so starting in a corner third
+//! resizes from that corner, starting in an edge third from that edge, and
+//! starting in the middle resizes from the bottom-right corner
+//!
+//! the daemon reads the window's frame when the drag starts and applies each
+//! frame `Drag::frame_at` returns
+
+/// smallest width and height a resize drag shrinks a window to, in points
+pub const MIN_SIZE: f64 = 100.0;
+
+/// a point in global screen coordinates, with the origin at the top left
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Point {
+    pub x: f64,
+    pub y: f64,
+}
+
+impl Point {
+    pub fn new(x: f64, y: f64) -> Self {
+        Self { x, y }
+    }
+}
+
+/// a window's position and size
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Frame {
+    pub x: f64,
+    pub y: f64,
+    pub width: f64,
+    pub height: f64,
+}
+
+impl Frame {
+    pub fn new(x: f64, y: f64, width: f64, height: f64) -> Self {
+        Self {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+
+    /// whether the frame has a different size than `other`, ignoring fractions of a point
+    pub fn resized_from(&self, other: &Frame) -> bool {
+        self.width.round() != other.width.round() || self.height.round() != other.height.round()
+    }
+
+    /// whether the frame differs from `other`, ignoring fractions of a point
+    pub fn differs_from(&self, other: &Frame) -> bool {
+        self.resized_from(other)
+            || self.x.round() != other.x.round()
+            || self.y.round() != other.y.round()
+    }
+}
+
+/// what a drag does to the window
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DragKind {
+    Move,
+    Resize,
+}
+
+impl DragKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            DragKind::Move => "move",
+            DragKind::Resize => "resize",
+        }
+    }
+}
+
+/// one end of an axis, or neither
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Edge {
+    Start,
+    End,
+    None,
+}
+
+impl Edge {
+    /// the edge of `length` starting at `origin` that `at` is nearest to, by thirds
+    fn nearest(origin: f64, length: f64, at: f64) -> Edge {
+        let offset = at - origin;
+        if offset < length / 3.0 {
+            Edge::Start
+        } else if offset > length * 2.0 / 3.0 {
+            Edge::End
+        } else {
+            Edge::None
+        }
+    }
+}
+
+/// a drag in progress: where it started and the window's frame at that time
+#[derive(Debug, Clone, PartialEq)]
+pub struct Drag {
+    kind: DragKind,
+    start: Point,
+    frame: Frame,
+    horizontal: Edge,
+    vertical: Edge,
+}
+
+impl Drag {
+    pub fn new(kind: DragKind, frame: Frame, start: Point) -> Self {
+        let (mut horizontal, mut vertical) = (Edge::None, Edge::None);
+        if kind == DragKind::Resize {
+            horizontal = Edge::nearest(frame.x, frame.width, start.x);
+            vertical = Edge::nearest(frame.y, frame.height, start.y);
+            if horizontal == Edge::None && vertical == Edge::None {
+                horizontal = Edge::End;
+                vertical = Edge::End;
+            }
+        }
+
+        Self {
+            kind,
+            start,
+            frame,
+            horizontal,
+            vertical,
+        }
+    }
+
+    pub fn kind(&self) -> DragKind {
+        self.kind
+    }
+
+    /// the window's frame when the drag started
+    pub fn start_frame(&self) -> Frame {
+        self.frame
+    }
+
+    /// the window's frame with the cursor at `cursor`
+    pub fn frame_at(&self, cursor: Point) -> Frame {
+        let dx = cursor.x - self.start.x;
+        let dy = cursor.y - self.start.y;
+        let frame = self.frame;
+
+        match self.kind {
+            DragKind::Move => Frame::new(frame.x + dx, frame.y + dy, frame.width, frame.height),
+            DragKind::Resize => {
+                let (x, width) = resize_axis(frame.x, frame.width, dx, self.horizontal);
+                let (y, height) = resize_axis(frame.y, frame.height, dy, self.vertical);
+                Frame::new(x, y, width, height)
+            }
+        }
+    }
+}
+
+/// move `edge` of the span `origin..origin + length` by `delta`, keeping the
+/// other edge in place and the span at least `MIN_SIZE` long
+fn resize_axis(origin: f64, length: f64, delta: f64, edge: Edge) -> (f64, f64) {
+    // a window already smaller than the minimum is not grown by starting a drag
+    let min = MIN_SIZE.min(length);
+    match edge {
+        Edge::None => (origin, length),
+        Edge::End => (origin, (length + delta).max(min)),
+        Edge::Start => {
+            let end = origin + length;
+            let length = (length - delta).max(min);
+            (end - length, length)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn window() -> Frame {
+        Frame::new(100.0, 100.0, 600.0, 300.0)
+    }
+
+    #[test]
+    fn test_move() {
+        let drag = Drag::new(DragKind::Move, window(), Point::new(400.0, 110.0));
+        assert_eq!(
+            drag.frame_at(Point::new(450.0, 80.0)),
+            Frame::new(150.0, 70.0, 600.0, 300.0)
+        );
+        assert_eq!(drag.frame_at(Point::new(400.0, 110.0)), window());
+        // the window can be dragged past the screen edge
+        assert_eq!(
+            drag.frame_at(Point::new(-100.0, 110.0)),
+            Frame::new(-400.0, 100.0, 600.0, 300.0)
+        );
+    }
+
+    #[test]
+    fn test_resize_from_corner() {
+        // top-left third of the window
+        let drag = Drag::new(DragKind::Resize, window(), Point::new(120.0, 120.0));
+        assert_eq!(
+            drag.frame_at(Point::new(70.0, 140.0)),
+            Frame::new(50.0, 120.0, 650.0, 280.0)
+        );
+
+        // bottom-right third
+        let drag = Drag::new(DragKind::Resize, window(), Point::new(680.0, 390.0));
+        assert_eq!(
+            drag.frame_at(Point::new(700.0, 400.0)),
+            Frame::new(100.0, 100.0, 620.0, 310.0)
+        );
+    }
+
+    #[test]
+    fn test_resize_from_edge() {
+        // right third, middle row: only the width changes
+        let drag = Drag::new(DragKind::Resize, window(), Point::new(650.0, 250.0));
+        assert_eq!(
+            drag.frame_at(Point::new(600.0, 300.0)),
+            Frame::new(100.0, 100.0, 550.0, 300.0)
+        );
+
+        // top third, middle column: only the top edge moves
+        let drag = Drag::new(DragKind::Resize, window(), Point::new(400.0, 110.0));
+        assert_eq!(
+            drag.frame_at(Point::new(500.0, 60.0)),
+            Frame::new(100.0, 50.0, 600.0, 350.0)
+        );
+    }
+
+    #[test]
+    fn test_resize_from_middle() {
+        let drag = Drag::new(DragKind::Resize, window(), Point::new(400.0, 250.0));
+        assert_eq!(
+            drag.frame_at(Point::new(450.0, 270.0)),
+            Frame::new(100.0, 100.0, 650.0, 320.0)
+        );
+    }
+
+    #[test]
+    fn test_resize_keeps_min_size() {
+        // shrinking from the right stops at the minimum, the left edge stays
+        let drag = Drag::new(DragKind::Resize, window(), Point::new(690.0, 390.0));
+        assert_eq!(
+            drag.frame_at(Point::new(0.0, 0.0)),
+            Frame::new(100.0, 100.0, MIN_SIZE, MIN_SIZE)
+        );
+
+        // shrinking from the top-left keeps the bottom-right corner in place
+        let drag = Drag::new(DragKind::Resize, window(), Point::new(110.0, 110.0));
+        assert_eq!(
+            drag.frame_at(Point::new(1000.0, 1000.0)),
+            Frame::new(600.0, 300.0, MIN_SIZE, MIN_SIZE)
+        );
+
+        // a window smaller than the minimum is not grown
+        let small = Frame::new(0.0, 0.0, 60.0, 40.0);
+        let drag = Drag::new(DragKind::Resize, small, Point::new(55.0, 35.0));
+        assert_eq!(drag.frame_at(Point::new(0.0, 0.0)), small);
+    }
+
+    #[test]
+    fn test_frame_changes() {
+        let frame = window();
+        assert!(!Frame::new(100.2, 99.9, 600.4, 300.0).differs_from(&frame));
+        assert!(Frame::new(101.0, 100.0, 600.0, 300.0).differs_from(&frame));
+        assert!(!Frame::new(101.0, 100.0, 600.0, 300.0).resized_from(&frame));
+        assert!(Frame::new(100.0, 100.0, 600.0, 301.0).resized_from(&frame));
+    }
+}
@@ -17,6 +17,16 @@ pub enum ResizeTarget {
     Pixels { width: u32, height: Option<u32> },
     /// Width in points, height optional (maintains aspect ratio if None)
     Points { width: u32, height: Option<u32> },
+    /// Percentage of screen width (1-100), height unchanged
+    Width(u32),
+    /// Percentage of screen height (1-100), width unchanged
+    Height(u32),
+    /// Percentage of screen width and height, each 1-100
+    Sides { width: u32, height: u32 },
+    /// Grow (or shrink, if negative) the width by a percentage of the screen width
+    GrowWidth(i32),
+    /// Grow (or shrink, if negative) the height by a percentage of the screen height
+    GrowHeight(i32),
 }
 
 impl ResizeTarget {
@@ -31,6 +41,9 @@ impl ResizeTarget {
     /// - `1920x1080px` - exact pixel dimensions
     /// - `800pt` - 800 points wide (height auto)
     /// - `800x600pt` - exact point dimensions
+    /// - `50%w` / `50%h` - 50% of screen width / height, other side unchanged
+    /// - `50x100%` - 50% of screen width, 100% of screen height
+    /// - `+5%w` / `-5%h` - grow width / shrink height by 5% of the screen
     pub fn parse(s: &str) -> Result<Self> {
         let s = s.trim().to_lowercase();
 
@@ -57,6 +70,41 @@ impl ResizeTarget {
             });
         }
 
+        // handle one side: 50%w or 50%h, or growing/shrinking it: +5%w or -5%h
+        if let Some(side) = s.strip_suffix("%w").or_else(|| s.strip_suffix("%h")) {
+            let is_width = s.ends_with('w');
+            if side.starts_with('+') || side.starts_with('-') {
+                let delta: i32 = side
+                    .parse()
+                    .map_err(|_| anyhow!("Invalid size change: '{}'", s))?;
+                if delta == 0 || delta.abs() > 100 {
+                    return Err(anyhow!(
+                        "Size change must be between 1 and 100 percent, got: {}",
+                        delta
+                    ));
+                }
+                return Ok(if is_width {
+                    ResizeTarget::GrowWidth(delta)
+                } else {
+                    ResizeTarget::GrowHeight(delta)
+                });
+            }
+            let percent = Self::parse_side(side)?;
+            return Ok(if is_width {
+                ResizeTarget::Width(percent)
+            } else {
+                ResizeTarget::Height(percent)
+            });
+        }
+
+        // handle both sides: 50x100%
+        if let Some((width, height)) = s.strip_suffix('%').and_then(|dims| dims.split_once('x')) {
+            return Ok(ResizeTarget::Sides {
+                width: Self::parse_side(width)?,
+                height: Self::parse_side(height)?,
+            });
+        }
+
         // handle percentage: 80% or 80
         let percent_str = s.trim_end_matches('%');
 
@@ -92,6 +140,20 @@ impl ResizeTarget {
         Ok(ResizeTarget::Percent(percent))
     }
 
+    /// Parse the percentage of one side, like the "50" of "50%w"
+    fn parse_side(s: &str) -> Result<u32> {
+        let percent: u32 = s
+            .parse()
+            .map_err(|_| anyhow!("Invalid percentage: '{}'", s))?;
+        if percent == 0 || percent > 100 {
+            return Err(anyhow!(
+                "Percentage must be between 1 and 100, got: {}",
+                percent
+            ));
+        }
+        Ok(percent)
+    }
+
     /// Parse dimension string like "1920" or "1920x1080"
     fn parse_dimensions<F>(dims: &str, constructor: F) -> Result<Self>
     where
@@ -142,6 +204,27 @@ impl FromStr for ResizeTarget {
     }
 }
 
+impl std::fmt::Display for ResizeTarget {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ResizeTarget::Percent(p) => write!(f, "{}", p),
+            ResizeTarget::Pixels { width, height } => match height {
+                Some(h) => write!(f, "{}x{}px", width, h),
+                None => write!(f, "{}px", width),
+            },
+            ResizeTarget::Points { width, height } => match height {
+                Some(h) => write!(f, "{}x{}pt", width, h),
+                None => write!(f, "{}pt", width),
+            },
+            ResizeTarget::Width(p) => write!(f, "{}%w", p),
+            ResizeTarget::Height(p) => write!(f, "{}%h", p),
+            ResizeTarget::Sides { width, height } => write!(f, "{}x{}%", width, height),
+            ResizeTarget::GrowWidth(d) => write!(f, "{:+}%w", d),
+            ResizeTarget::GrowHeight(d) => write!(f, "{:+}%h", d),
+        }
+    }
+}
+
 /// Anchor position for predefined window placement
 #[derive(Debug, Clone, PartialEq)]
 pub enum AnchorPosition {
@@ -507,6 +590,16 @@ extern "C" {
         attribute: core_foundation::string::CFStringRef,
         value: core_foundation::base::CFTypeRef,
     ) -> i32;
+    fn AXUIElementCreateSystemWide() -> AXUIElementRef;
+    fn AXUIElementCopyElementAtPosition(
+        application: AXUIElementRef,
+        x: f32,
+        y: f32,
+        element: *mut AXUIElementRef,
+    ) -> i32;
+    fn AXUIElementGetPid(element: AXUIElementRef, pid: *mut i32) -> i32;
+    // private, but the only way from an AXUIElement to its CGWindowID
+    fn _AXUIElementGetWindow(element: AXUIElementRef, window_id: *mut u32) -> i32;
 }
 
 const K_AX_ERROR_SUCCESS: i32 = 0;
@@ -550,10 +643,18 @@ pub fn focus_app(app: &AppInfo, verbose: bool) -> Result<()> {
     Ok(())
 }
 
-/// Get the frontmost window of an application
+/// Get the frontmost window of an application, or the window `with_window` pinned for it
 unsafe fn get_frontmost_window(pid: i32) -> Result<AXUIElementRef> {
     use core_foundation::base::CFTypeRef;
 
+    // a window pinned by `with_window`
+    if let Some((pinned_pid, window)) = PINNED_WINDOW.with(|pinned| pinned.get()) {
+        if pinned_pid == pid {
+            core_foundation::base::CFRetain(window as CFTypeRef);
+            return Ok(window);
+        }
+    }
+
     let app_element = AXUIElementCreateApplication(pid);
     if app_element.is_null() {
         return Err(anyhow!("Failed to create AXUIElement for PID {}", pid));
@@ -650,6 +751,30 @@ unsafe fn get_focused_window() -> Result<(AXUIElementRef, i32)> {
     Ok((window, pid))
 }
 
+/// Get the window an action would act on: the frontmost window of `app`, or
+/// the focused window without one
+///
+/// Returns (pid, window_id); the window id is 0 if the window server has none for it.
+pub fn target_window_id(app: Option<&AppInfo>) -> Result<(i32, u32)> {
+    use core_foundation::base::CFTypeRef;
+
+    let (window, pid) = unsafe {
+        match app {
+            Some(app) => (get_frontmost_window(app.pid)?, app.pid),
+            None => get_focused_window()?,
+        }
+    };
+
+    let mut window_id: u32 = 0;
+    unsafe {
+        if _AXUIElementGetWindow(window, &mut window_id) != K_AX_ERROR_SUCCESS {
+            window_id = 0;
+        }
+        core_foundation::base::CFRelease(window as CFTypeRef);
+    }
+    Ok((pid, window_id))
+}
+
 /// Set window position
 unsafe fn set_window_position(window: AXUIElementRef, x: f64, y: f64) -> Result<()> {
     use core_foundation::base::CFTypeRef;
@@ -907,9 +1032,14 @@ pub fn terminate_app(app: &AppInfo, force: bool, verbose: bool) -> Result<bool>
 
 /// Wait for an application to terminate
 ///
-/// Polls until the app is no longer running or timeout is reached.
-/// Returns true if app terminated, false if timeout.
-pub fn wait_for_termination(pid: i32, timeout_ms: u64, verbose: bool) -> bool {
+/// Polls until the app is no longer running, timeout is reached or `should_abort` returns true.
+/// Returns true if app terminated, false if timeout or aborted.
+pub fn wait_for_termination(
+    pid: i32,
+    timeout_ms: u64,
+    verbose: bool,
+    should_abort: &dyn Fn() -> bool,
+) -> bool {
     use objc2_app_kit::NSRunningApplication;
     use std::time::{Duration, Instant};
 
@@ -917,6 +1047,13 @@ pub fn wait_for_termination(pid: i32, timeout_ms: u64, verbose: bool) -> bool {
     let timeout = Duration::from_millis(timeout_ms);
 
     while start.elapsed() < timeout {
+        if should_abort() {
+            if verbose {
+                println!("Stopped waiting for termination");
+            }
+            return false;
+        }
+
         let running_app = NSRunningApplication::runningApplicationWithProcessIdentifier(pid);
 
         match running_app {
@@ -1428,7 +1565,10 @@ fn calculate_anchor_position(
     }
 }
 
-/// Resize an app's window to a target size (centered)
+/// Resize an app's window to a target size
+///
+/// Percent, pixel and point targets center the window. Per-side targets (`50%w`,
+/// `50x100%`, `+5%w`) keep its position, see `resize_frame`.
 ///
 /// The `overflow` parameter controls whether the window can extend beyond screen bounds.
 /// If false (default), dimensions are clamped to the usable display area.
@@ -1481,57 +1621,10 @@ pub fn resize_app(
         println!("Usable display bounds: {}x{} at ({}, {})", dw, dh, dx, dy);
     }
 
-    // calculate new dimensions based on target
-    let (mut new_w, mut new_h) = match target {
-        ResizeTarget::Percent(percent) => {
-            let scale = *percent as f64 / 100.0;
-            (dw * scale, dh * scale)
-        }
-        ResizeTarget::Pixels { width, height } => {
-            let w = *width as f64;
-            let h = match height {
-                Some(h) => *h as f64,
-                None => {
-                    // maintain display aspect ratio
-                    w * (dh / dw)
-                }
-            };
-            (w, h)
-        }
-        ResizeTarget::Points { width, height } => {
-            // on macOS, points are the same as pixels for our purposes
-            // (the system handles scaling for Retina displays)
-            let w = *width as f64;
-            let h = match height {
-                Some(h) => *h as f64,
-                None => {
-                    // maintain display aspect ratio
-                    w * (dh / dw)
-                }
-            };
-            (w, h)
-        }
-    };
-
-    // clamp to screen bounds unless overflow is enabled
-    if !overflow {
-        if new_w > dw {
-            if verbose {
-                println!("Clamping width from {} to {} (screen limit)", new_w, dw);
-            }
-            new_w = dw;
-        }
-        if new_h > dh {
-            if verbose {
-                println!("Clamping height from {} to {} (screen limit)", new_h, dh);
-            }
-            new_h = dh;
-        }
-    }
-
-    // center the window
-    let new_x = dx + (dw - new_w) / 2.0;
-    let new_y = dy + (dh - new_h) / 2.0;
+    let (wx, wy) = unsafe { get_window_position(window)? };
+    let (ww, wh) = unsafe { get_window_size(window)? };
+    let (new_x, new_y, new_w, new_h) =
+        resize_frame(target, (wx, wy, ww, wh), (dx, dy, dw, dh), overflow);
 
     if verbose {
         println!("New size: {}x{} at ({}, {})", new_w, new_h, new_x, new_y);
@@ -1543,19 +1636,6 @@ pub fn resize_app(
         core_foundation::base::CFRelease(window as CFTypeRef);
     }
 
-    // format output message based on target type
-    let _size_desc = match target {
-        ResizeTarget::Percent(p) => format!("{}%", p),
-        ResizeTarget::Pixels { width, height } => match height {
-            Some(h) => format!("{}x{}px", width, h),
-            None => format!("{}px wide", width),
-        },
-        ResizeTarget::Points { width, height } => match height {
-            Some(h) => format!("{}x{}pt", width, h),
-            None => format!("{}pt wide", width),
-        },
-    };
-
     if verbose {
         println!("Done.");
     }
@@ -1564,6 +1644,92 @@ pub fn resize_app(
     Ok((new_w as u32, new_h as u32))
 }
 
+/// Calculate the frame (x, y, width, height) of a window at `window` resized to
+/// `target` on a display with usable `display_bounds`
+///
+/// Percent, pixel and point targets center the window. Per-side targets keep
+/// the screen edge the window touches on each axis (the far one if it touches
+/// only that, as a window snapped right), and its position otherwise.
+fn resize_frame(
+    target: &ResizeTarget,
+    window: (f64, f64, f64, f64),
+    display_bounds: (f64, f64, f64, f64),
+    overflow: bool,
+) -> (f64, f64, f64, f64) {
+    let (wx, wy, ww, wh) = window;
+    let (dx, dy, dw, dh) = display_bounds;
+    let percent = |p: u32| p as f64 / 100.0;
+    let grow = |d: i32| d as f64 / 100.0;
+
+    let (mut new_w, mut new_h) = match target {
+        ResizeTarget::Percent(p) => (dw * percent(*p), dh * percent(*p)),
+        // on macOS, points are the same as pixels for our purposes
+        // (the system handles scaling for Retina displays)
+        ResizeTarget::Pixels { width, height } | ResizeTarget::Points { width, height } => {
+            let w = *width as f64;
+            // maintain display aspect ratio without a height
+            let h = height.map_or(w * (dh / dw), |h| h as f64);
+            (w, h)
+        }
+        ResizeTarget::Width(p) => (dw * percent(*p), wh),
+        ResizeTarget::Height(p) => (ww, dh * percent(*p)),
+        ResizeTarget::Sides { width, height } => (dw * percent(*width), dh * percent(*height)),
+        ResizeTarget::GrowWidth(d) => ((ww + dw * grow(*d)).max(1.0), wh),
+        ResizeTarget::GrowHeight(d) => (ww, (wh + dh * grow(*d)).max(1.0)),
+    };
+
+    // clamp to screen bounds unless overflow is enabled
+    if !overflow {
+        new_w = new_w.min(dw);
+        new_h = new_h.min(dh);
+    }
+
+    let centered = matches!(
+        target,
+        ResizeTarget::Percent(_) | ResizeTarget::Pixels { .. } | ResizeTarget::Points { .. }
+    );
+    if centered {
+        return (
+            dx + (dw - new_w) / 2.0,
+            dy + (dh - new_h) / 2.0,
+            new_w,
+            new_h,
+        );
+    }
+
+    (
+        anchored_start(wx, ww, new_w, dx, dw, overflow),
+        anchored_start(wy, wh, new_h, dy, dh, overflow),
+        new_w,
+        new_h,
+    )
+}
+
+/// Calculate where a window side starting at `start` begins once it goes from
+/// `len` to `new_len`, on a screen side from `screen_start` that is `screen_len` long
+fn anchored_start(
+    start: f64,
+    len: f64,
+    new_len: f64,
+    screen_start: f64,
+    screen_len: f64,
+    overflow: bool,
+) -> f64 {
+    let screen_end = screen_start + screen_len;
+    let touches = |a: f64, b: f64| (a - b).abs() < 1.0;
+
+    let new_start = if touches(start + len, screen_end) && !touches(start, screen_start) {
+        start + len - new_len
+    } else {
+        start
+    };
+    if overflow {
+        new_start
+    } else {
+        new_start.min(screen_end - new_len).max(screen_start)
+    }
+}
+
 /// Window data for JSON output
 #[derive(serde::Serialize)]
 pub struct WindowData {
@@ -1674,6 +1840,156 @@ fn get_window_display_index(x: f64, y: f64, width: f64, height: f64) -> Result<u
     Ok(find_display_for_point(center_x, center_y, &displays))
 }
 
+/// A window from the accessibility API, released when dropped
+pub struct Window {
+    element: AXUIElementRef,
+    pid: i32,
+}
+
+// an AXUIElement is a reference to the window in its app, usable from any thread
+unsafe impl Send for Window {}
+
+impl Drop for Window {
+    fn drop(&mut self) {
+        unsafe {
+            core_foundation::base::CFRelease(self.element as core_foundation::base::CFTypeRef);
+        }
+    }
+}
+
+impl Window {
+    /// The pid of the window's app
+    pub fn pid(&self) -> i32 {
+        self.pid
+    }
+
+    /// The window server's id for the window, or 0 if it has none
+    pub fn id(&self) -> u32 {
+        let mut window_id: u32 = 0;
+        unsafe {
+            if _AXUIElementGetWindow(self.element, &mut window_id) != K_AX_ERROR_SUCCESS {
+                window_id = 0;
+            }
+        }
+        window_id
+    }
+
+    /// Raise the window and make it its app's main window
+    pub fn raise(&self) {
+        use core_foundation::boolean::CFBoolean;
+
+        let main_attr = CFString::new("AXMain");
+        let raise_action = CFString::new("AXRaise");
+        unsafe {
+            AXUIElementSetAttributeValue(
+                self.element,
+                main_attr.as_concrete_TypeRef(),
+                CFBoolean::true_value().as_CFTypeRef(),
+            );
+            AXUIElementPerformAction(self.element, raise_action.as_concrete_TypeRef());
+        }
+    }
+
+    /// The window's (x, y, width, height)
+    pub fn frame(&self) -> Result<(f64, f64, f64, f64)> {
+        let (x, y) = unsafe { get_window_position(self.element)? };
+        let (width, height) = unsafe { get_window_size(self.element)? };
+        Ok((x, y, width, height))
+    }
+
+    /// Put the window at (x, y), resizing it to (width, height) if `resize`
+    pub fn set_frame(&self, x: f64, y: f64, width: f64, height: f64, resize: bool) -> Result<()> {
+        unsafe {
+            if resize {
+                set_window_size(self.element, width, height)?;
+            }
+            set_window_position(self.element, x, y)?;
+        }
+        Ok(())
+    }
+}
+
+thread_local! {
+    /// the window `with_window` pins for its app on this thread
+    static PINNED_WINDOW: std::cell::Cell<Option<(i32, AXUIElementRef)>> =
+        const { std::cell::Cell::new(None) };
+}
+
+/// Run `f` with `window` standing in for its app's frontmost window, so
+/// actions on the app act on it
+pub fn with_window<R>(window: &Window, f: impl FnOnce() -> R) -> R {
+    let previous = PINNED_WINDOW.with(|pinned| pinned.replace(Some((window.pid, window.element))));
+    // restore the previous pin even if `f` panics
+    struct Restore(Option<(i32, AXUIElementRef)>);
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            PINNED_WINDOW.with(|pinned| pinned.set(self.0));
+        }
+    }
+    let _restore = Restore(previous);
+    f()
+}
+
+/// Get the window at a point in global coordinates, and the application owning it
+pub fn window_at_point(x: f64, y: f64) -> Result<(AppInfo, Window)> {
+    use core_foundation::base::{CFRelease, CFTypeRef};
+
+    if !accessibility::is_trusted() {
+        return Err(anyhow!(
+            "Accessibility permissions required. Run 'cwm check-permissions' for help."
+        ));
+    }
+
+    let window = unsafe {
+        let system = AXUIElementCreateSystemWide();
+        if system.is_null() {
+            return Err(anyhow!("Failed to create system-wide AXUIElement"));
+        }
+
+        let mut element: AXUIElementRef = std::ptr::null_mut();
+        let result = AXUIElementCopyElementAtPosition(system, x as f32, y as f32, &mut element);
+        CFRelease(system as CFTypeRef);
+
+        if result != K_AX_ERROR_SUCCESS || element.is_null() {
+            return Err(anyhow!("No window at ({}, {}) (error: {})", x, y, result));
+        }
+
+        let mut pid: i32 = 0;
+        let result = AXUIElementGetPid(element, &mut pid);
+
+        // the element is usually a control inside the window, or the window itself
+        let window_attr = CFString::new("AXWindow");
+        let mut window: CFTypeRef = std::ptr::null_mut();
+        let has_window =
+            AXUIElementCopyAttributeValue(element, window_attr.as_concrete_TypeRef(), &mut window)
+                == K_AX_ERROR_SUCCESS
+                && !window.is_null();
+        let element = if has_window {
+            CFRelease(element as CFTypeRef);
+            window as AXUIElementRef
+        } else {
+            element
+        };
+        let window = Window { element, pid };
+
+        if result != K_AX_ERROR_SUCCESS || pid <= 0 {
+            return Err(anyhow!(
+                "Failed to get the application at ({}, {}) (error: {})",
+                x,
+                y,
+                result
+            ));
+        }
+        window
+    };
+
+    let app = super::matching::get_running_apps()?
+        .into_iter()
+        .find(|app| app.pid == window.pid)
+        .ok_or_else(|| anyhow!("No application window at ({}, {})", x, y))?;
+    Ok((app, window))
+}
+
 /// Get information about the currently focused window
 pub fn get_focused_window_info() -> Result<(AppInfo, WindowData, DisplayDataInfo)> {
     use objc2_app_kit::NSWorkspace;
@@ -1945,6 +2261,27 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_resize_target_display_roundtrip() {
+        for input in [
+            "80",
+            "100",
+            "1920px",
+            "1920x1080px",
+            "800pt",
+            "800x600pt",
+            "50%w",
+            "33%h",
+            "50x100%",
+            "+5%w",
+            "-5%h",
+        ] {
+            let target = ResizeTarget::parse(input).unwrap();
+            assert_eq!(target.to_string(), input);
+            assert_eq!(ResizeTarget::parse(&target.to_string()).unwrap(), target);
+        }
+    }
+
     #[test]
     fn test_resize_target_parse_full() {
         assert_eq!(
@@ -2389,6 +2726,151 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_resize_target_parse_sides() {
+        assert_eq!(
+            ResizeTarget::parse("50%w").unwrap(),
+            ResizeTarget::Width(50)
+        );
+        assert_eq!(
+            ResizeTarget::parse("33%H").unwrap(),
+            ResizeTarget::Height(33)
+        );
+        assert_eq!(
+            ResizeTarget::parse("50x100%").unwrap(),
+            ResizeTarget::Sides {
+                width: 50,
+                height: 100
+            }
+        );
+
+        assert!(ResizeTarget::parse("0%w").is_err());
+        assert!(ResizeTarget::parse("101%h").is_err());
+        assert!(ResizeTarget::parse("50x0%").is_err());
+        assert!(ResizeTarget::parse("%w").is_err());
+    }
+
+    #[test]
+    fn test_resize_target_parse_grow() {
+        assert_eq!(
+            ResizeTarget::parse("+5%w").unwrap(),
+            ResizeTarget::GrowWidth(5)
+        );
+        assert_eq!(
+            ResizeTarget::parse("-10%h").unwrap(),
+            ResizeTarget::GrowHeight(-10)
+        );
+
+        assert!(ResizeTarget::parse("+0%w").is_err());
+        assert!(ResizeTarget::parse("-101%w").is_err());
+        assert!(ResizeTarget::parse("+%h").is_err());
+    }
+
+    const DISPLAY: (f64, f64, f64, f64) = (0.0, 25.0, 1440.0, 875.0);
+
+    #[test]
+    fn test_resize_frame_centers_percent() {
+        // resize:50 scales both sides and centers, whatever came before
+        assert_eq!(
+            resize_frame(
+                &ResizeTarget::Percent(50),
+                (0.0, 25.0, 1440.0, 875.0),
+                DISPLAY,
+                false
+            ),
+            (360.0, 243.75, 720.0, 437.5)
+        );
+    }
+
+    #[test]
+    fn test_resize_frame_then_anchor_halves() {
+        // a cycle step like "resize:50x100%;move:left" from a maximized window
+        let maximized = (0.0, 25.0, 1440.0, 875.0);
+        let half = ResizeTarget::Sides {
+            width: 50,
+            height: 100,
+        };
+        let (_, _, w, h) = resize_frame(&half, maximized, DISPLAY, false);
+        assert_eq!((w, h), (720.0, 875.0));
+
+        assert_eq!(
+            calculate_anchor_position(&AnchorPosition::Left, DISPLAY, (w, h)),
+            (0.0, 25.0)
+        );
+        assert_eq!(
+            calculate_anchor_position(&AnchorPosition::Right, DISPLAY, (w, h)),
+            (720.0, 25.0)
+        );
+    }
+
+    #[test]
+    fn test_resize_frame_keeps_touched_edge() {
+        let left_half = (0.0, 25.0, 720.0, 875.0);
+        let right_half = (720.0, 25.0, 720.0, 875.0);
+
+        // a left half grows to the right
+        assert_eq!(
+            resize_frame(&ResizeTarget::Width(75), left_half, DISPLAY, false),
+            (0.0, 25.0, 1080.0, 875.0)
+        );
+        // a right half shrinks toward the right edge
+        assert_eq!(
+            resize_frame(&ResizeTarget::Width(25), right_half, DISPLAY, false),
+            (1080.0, 25.0, 360.0, 875.0)
+        );
+    }
+
+    #[test]
+    fn test_resize_frame_grow() {
+        let left_half = (0.0, 25.0, 720.0, 875.0);
+        let right_half = (720.0, 25.0, 720.0, 875.0);
+        let floating = (100.0, 100.0, 400.0, 300.0);
+
+        // a left half grows to the right
+        assert_eq!(
+            resize_frame(&ResizeTarget::GrowWidth(5), left_half, DISPLAY, false),
+            (0.0, 25.0, 792.0, 875.0)
+        );
+        // a right half shrinks toward the right edge
+        assert_eq!(
+            resize_frame(&ResizeTarget::GrowWidth(-5), right_half, DISPLAY, false),
+            (792.0, 25.0, 648.0, 875.0)
+        );
+        assert_eq!(
+            resize_frame(&ResizeTarget::GrowHeight(-10), floating, DISPLAY, false),
+            (100.0, 100.0, 400.0, 212.5)
+        );
+        // a full-height window cannot grow taller
+        assert_eq!(
+            resize_frame(&ResizeTarget::GrowHeight(10), left_half, DISPLAY, false),
+            left_half
+        );
+    }
+
+    #[test]
+    fn test_resize_frame_keeps_position() {
+        let floating = (100.0, 100.0, 400.0, 300.0);
+        assert_eq!(
+            resize_frame(&ResizeTarget::Width(50), floating, DISPLAY, false),
+            (100.0, 100.0, 720.0, 300.0)
+        );
+        assert_eq!(
+            resize_frame(&ResizeTarget::Height(50), floating, DISPLAY, false),
+            (100.0, 100.0, 400.0, 437.5)
+        );
+
+        // kept on screen unless overflow is allowed
+        let near_edge = (1000.0, 100.0, 400.0, 300.0);
+        assert_eq!(
+            resize_frame(&ResizeTarget::Width(50), near_edge, DISPLAY, false),
+            (720.0, 100.0, 720.0, 300.0)
+        );
+        assert_eq!(
+            resize_frame(&ResizeTarget::Width(50), near_edge, DISPLAY, true),
+            (1000.0, 100.0, 720.0, 300.0)
+        );
+    }
+
     // ========================================================================
     // AnchorPosition tests
     // ========================================================================
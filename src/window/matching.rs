@@ -236,6 +236,14 @@ pub fn find_app(query: &str, apps: &[AppInfo], fuzzy_threshold: usize) -> Option
     None
 }
 
+/// Check if any of the queries finds `app`, using the same rules as `find_app`
+pub fn matches_any(queries: &[String], app: &AppInfo, fuzzy_threshold: usize) -> bool {
+    let apps = std::slice::from_ref(app);
+    queries
+        .iter()
+        .any(|query| find_app(query, apps, fuzzy_threshold).is_some())
+}
+
 /// Get window titles for an application using Accessibility API
 fn get_window_titles(pid: i32) -> Vec<String> {
     use core_foundation::base::{CFTypeRef, TCFType};
@@ -386,6 +394,22 @@ pub fn get_running_apps() -> Result<Vec<AppInfo>> {
     Ok(apps)
 }
 
+/// Get the frontmost application, with its window titles
+pub fn get_frontmost_app() -> Option<AppInfo> {
+    use objc2_app_kit::NSWorkspace;
+
+    let app = NSWorkspace::sharedWorkspace().frontmostApplication()?;
+    let name = app.localizedName()?.to_string();
+    let pid = app.processIdentifier();
+
+    Some(AppInfo {
+        name,
+        pid,
+        bundle_id: app.bundleIdentifier().map(|s| s.to_string()),
+        titles: get_window_titles(pid),
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -890,4 +914,20 @@ mod tests {
         assert!(desc.contains("PR #\\d+"));
         assert!(desc.contains("GitHub - PR #123"));
     }
+
+    #[test]
+    fn test_matches_any() {
+        let apps = test_apps();
+        let slack = &apps[0];
+
+        assert!(matches_any(&["slack".to_string()], slack, 2));
+        assert!(matches_any(
+            &["Xcode".to_string(), "/^Sl/".to_string()],
+            slack,
+            2
+        ));
+        assert!(matches_any(&["general".to_string()], slack, 2));
+        assert!(!matches_any(&["Safari".to_string()], slack, 2));
+        assert!(!matches_any(&[], slack, 2));
+    }
 }
@@ -1,4 +1,5 @@
 pub mod accessibility;
+pub mod drag;
 pub mod manager;
 pub mod matching;
 
@@ -0,0 +1,25 @@
+local hyper = {"cmd", "alt", "ctrl", "shift"}
+
+hs.hotkey.bind({"cmd", "alt", "ctrl"}, "F", function()
+  hs.window.focusedWindow():maximize()
+end)
+
+hs.hotkey.bind({"cmd", "alt", "ctrl"}, "C", function()
+  hs.window.focusedWindow():centerOnScreen()
+end)
+
+hs.hotkey.bind({"cmd", "alt", "ctrl"}, "N", function()
+  local win = hs.window.focusedWindow()
+  win:moveToScreen(win:screen():next())
+end)
+
+hs.hotkey.bind({"cmd", "alt", "ctrl"}, "left", function()
+  local win = hs.window.focusedWindow()
+  local f = win:frame()
+  f.x = f.x - 10
+  win:setFrame(f)
+end)
+
+hs.hotkey.bind({"cmd", "alt"}, "S", function() hs.application.launchOrFocus("Safari") end)
+
+hs.hotkey.bind(hyper, "T", function() hs.application.launchOrFocus("Terminal") end)
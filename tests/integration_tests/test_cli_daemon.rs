@@ -41,9 +41,13 @@ fn run_cwm_with_config(args: &[&str], config_path: &std::path::Path) -> std::pro
     let mut cmd_args = vec!["--config", config_path.to_str().unwrap(), "--no-json"];
     cmd_args.extend(args);
 
+    // keep each test's socket, pid file and lock out of the real ~/.cwm
+    let runtime_dir = config_path.parent().unwrap();
+
     Command::new(&binary)
         .args(&cmd_args)
         .env("CWM_GITHUB_API_URL", mock_server_url())
+        .env("CWM_RUNTIME_DIR", runtime_dir)
         .output()
         .expect("Failed to run cwm")
 }
@@ -63,6 +67,35 @@ fn test_daemon_status_when_not_running() {
     cleanup_test_dir(&test_dir);
 }
 
+#[test]
+fn test_daemon_status_uses_runtime_dir() {
+    let test_dir = create_test_dir(&unique_test_name("daemon_runtime_dir"));
+    let config_path = create_test_config(&test_dir);
+
+    let output = Command::new(cwm_binary_path())
+        .args(["--config", config_path.to_str().unwrap(), "--json"])
+        .args(["daemon", "status"])
+        .env("CWM_RUNTIME_DIR", &test_dir)
+        .output()
+        .expect("Failed to run cwm");
+    assert!(output.status.success());
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let json: serde_json::Value = serde_json::from_str(&stdout).expect("Invalid JSON");
+    let result = &json["result"]["result"];
+    assert_eq!(result["running"], false);
+    assert!(result["socket_path"]
+        .as_str()
+        .unwrap()
+        .starts_with(test_dir.to_str().unwrap()));
+    assert!(result["pid_file"]
+        .as_str()
+        .unwrap()
+        .starts_with(test_dir.to_str().unwrap()));
+
+    cleanup_test_dir(&test_dir);
+}
+
 #[test]
 fn test_daemon_stop_when_not_running() {
     let test_dir = create_test_dir(&unique_test_name("daemon_stop"));